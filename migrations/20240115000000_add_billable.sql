@@ -0,0 +1,5 @@
+-- 项目计费标记
+ALTER TABLE projects ADD COLUMN is_billable BOOLEAN NOT NULL DEFAULT 0;
+
+-- 活动计费标记，为空时沿用项目设置
+ALTER TABLE app_usage ADD COLUMN is_billable BOOLEAN;
@@ -0,0 +1,12 @@
+-- 活动时长调整审计表
+CREATE TABLE IF NOT EXISTS activity_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    activity_id INTEGER NOT NULL,
+    original_end_time DATETIME,
+    original_duration INTEGER NOT NULL,
+    new_end_time DATETIME NOT NULL,
+    new_duration INTEGER NOT NULL,
+    adjusted_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_audit_activity_id ON activity_audit(activity_id);
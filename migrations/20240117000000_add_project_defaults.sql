@@ -0,0 +1,3 @@
+-- 项目默认标签（JSON 数组）和默认类别
+ALTER TABLE projects ADD COLUMN default_tags TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE projects ADD COLUMN default_category TEXT;
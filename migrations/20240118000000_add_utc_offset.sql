@@ -0,0 +1,3 @@
+-- 记录时的 UTC 偏移（秒），旧记录为空时按当前时区计算
+ALTER TABLE app_usage ADD COLUMN utc_offset INTEGER;
+ALTER TABLE pomodoro_records ADD COLUMN utc_offset INTEGER;
@@ -0,0 +1,2 @@
+-- 活动标签（JSON 数组）
+ALTER TABLE app_usage ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
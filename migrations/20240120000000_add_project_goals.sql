@@ -0,0 +1,8 @@
+-- 项目目标：每个项目每种周期（week/month）一个目标时长
+CREATE TABLE IF NOT EXISTS project_goals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    period TEXT NOT NULL,
+    target_seconds INTEGER NOT NULL,
+    UNIQUE (project_id, period)
+);
@@ -0,0 +1,42 @@
+-- 原始记录变化的日期，rebuild_daily_summaries 只重算这些日期
+CREATE TABLE IF NOT EXISTS summary_dirty (
+    date DATE PRIMARY KEY
+);
+
+CREATE TRIGGER IF NOT EXISTS app_usage_summary_dirty_insert AFTER INSERT ON app_usage
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN NEW.utc_offset IS NULL THEN date(NEW.start_time, 'localtime') ELSE date(NEW.start_time, printf('%+d seconds', NEW.utc_offset)) END);
+END;
+
+CREATE TRIGGER IF NOT EXISTS app_usage_summary_dirty_update AFTER UPDATE ON app_usage
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN OLD.utc_offset IS NULL THEN date(OLD.start_time, 'localtime') ELSE date(OLD.start_time, printf('%+d seconds', OLD.utc_offset)) END);
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN NEW.utc_offset IS NULL THEN date(NEW.start_time, 'localtime') ELSE date(NEW.start_time, printf('%+d seconds', NEW.utc_offset)) END);
+END;
+
+CREATE TRIGGER IF NOT EXISTS app_usage_summary_dirty_delete AFTER DELETE ON app_usage
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN OLD.utc_offset IS NULL THEN date(OLD.start_time, 'localtime') ELSE date(OLD.start_time, printf('%+d seconds', OLD.utc_offset)) END);
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_summary_dirty_insert AFTER INSERT ON pomodoro_records
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN NEW.utc_offset IS NULL THEN date(NEW.start_time, 'localtime') ELSE date(NEW.start_time, printf('%+d seconds', NEW.utc_offset)) END);
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_summary_dirty_update AFTER UPDATE ON pomodoro_records
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN OLD.utc_offset IS NULL THEN date(OLD.start_time, 'localtime') ELSE date(OLD.start_time, printf('%+d seconds', OLD.utc_offset)) END);
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN NEW.utc_offset IS NULL THEN date(NEW.start_time, 'localtime') ELSE date(NEW.start_time, printf('%+d seconds', NEW.utc_offset)) END);
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_summary_dirty_delete AFTER DELETE ON pomodoro_records
+BEGIN
+    INSERT OR IGNORE INTO summary_dirty (date) VALUES (CASE WHEN OLD.utc_offset IS NULL THEN date(OLD.start_time, 'localtime') ELSE date(OLD.start_time, printf('%+d seconds', OLD.utc_offset)) END);
+END;
+
+-- 已有记录的日期全部标记一次，首次重建覆盖历史数据
+INSERT OR IGNORE INTO summary_dirty (date)
+SELECT CASE WHEN utc_offset IS NULL THEN date(start_time, 'localtime') ELSE date(start_time, printf('%+d seconds', utc_offset)) END FROM app_usage
+UNION
+SELECT CASE WHEN utc_offset IS NULL THEN date(start_time, 'localtime') ELSE date(start_time, printf('%+d seconds', utc_offset)) END FROM pomodoro_records;
@@ -0,0 +1,47 @@
+-- 活动标签关联表，由 app_usage.tags（JSON 数组）通过触发器同步，用于按标签查询
+CREATE TABLE IF NOT EXISTS activity_tags (
+    activity_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (activity_id, tag_id),
+    FOREIGN KEY(activity_id) REFERENCES app_usage(id) ON DELETE CASCADE,
+    FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_tags_tag ON activity_tags(tag_id);
+
+-- 拆分已有记录的标签，标签名去掉开头的 #
+INSERT OR IGNORE INTO tags (name, created_at)
+SELECT DISTINCT ltrim(trim(t.value), '#'), CURRENT_TIMESTAMP
+FROM app_usage u, json_each(CASE WHEN json_valid(u.tags) THEN u.tags ELSE '[]' END) t
+WHERE ltrim(trim(t.value), '#') != '';
+
+INSERT OR IGNORE INTO activity_tags (activity_id, tag_id)
+SELECT u.id, tags.id
+FROM app_usage u, json_each(CASE WHEN json_valid(u.tags) THEN u.tags ELSE '[]' END) t
+JOIN tags ON tags.name = ltrim(trim(t.value), '#');
+
+CREATE TRIGGER IF NOT EXISTS app_usage_tags_insert AFTER INSERT ON app_usage
+BEGIN
+    INSERT OR IGNORE INTO tags (name, created_at)
+    SELECT ltrim(trim(value), '#'), CURRENT_TIMESTAMP
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END)
+    WHERE ltrim(trim(value), '#') != '';
+    INSERT OR IGNORE INTO activity_tags (activity_id, tag_id)
+    SELECT NEW.id, tags.id
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END) t
+    JOIN tags ON tags.name = ltrim(trim(t.value), '#');
+END;
+
+CREATE TRIGGER IF NOT EXISTS app_usage_tags_update AFTER UPDATE OF tags ON app_usage
+BEGIN
+    DELETE FROM activity_tags WHERE activity_id = NEW.id;
+    INSERT OR IGNORE INTO tags (name, created_at)
+    SELECT ltrim(trim(value), '#'), CURRENT_TIMESTAMP
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END)
+    WHERE ltrim(trim(value), '#') != '';
+    INSERT OR IGNORE INTO activity_tags (activity_id, tag_id)
+    SELECT NEW.id, tags.id
+    FROM json_each(CASE WHEN json_valid(NEW.tags) THEN NEW.tags ELSE '[]' END) t
+    JOIN tags ON tags.name = ltrim(trim(t.value), '#');
+END;
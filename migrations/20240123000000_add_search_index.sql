@@ -0,0 +1,51 @@
+-- 窗口标题和番茄钟备注的全文索引。trigram 分词支持中文等不以空格分词的文本，
+-- kind 为 'activity' 或 'pomodoro'，source_id 为对应表的 id
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    text,
+    kind UNINDEXED,
+    source_id UNINDEXED,
+    tokenize = 'trigram'
+);
+
+CREATE TRIGGER IF NOT EXISTS app_usage_search_insert AFTER INSERT ON app_usage
+WHEN NEW.window_title != ''
+BEGIN
+    INSERT INTO search_index (text, kind, source_id) VALUES (NEW.window_title, 'activity', NEW.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS app_usage_search_update AFTER UPDATE OF window_title ON app_usage
+BEGIN
+    DELETE FROM search_index WHERE kind = 'activity' AND source_id = OLD.id;
+    INSERT INTO search_index (text, kind, source_id)
+    SELECT NEW.window_title, 'activity', NEW.id WHERE NEW.window_title != '';
+END;
+
+CREATE TRIGGER IF NOT EXISTS app_usage_search_delete AFTER DELETE ON app_usage
+BEGIN
+    DELETE FROM search_index WHERE kind = 'activity' AND source_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_search_insert AFTER INSERT ON pomodoro_records
+WHEN NEW.notes IS NOT NULL AND NEW.notes != ''
+BEGIN
+    INSERT INTO search_index (text, kind, source_id) VALUES (NEW.notes, 'pomodoro', NEW.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_search_update AFTER UPDATE OF notes ON pomodoro_records
+BEGIN
+    DELETE FROM search_index WHERE kind = 'pomodoro' AND source_id = OLD.id;
+    INSERT INTO search_index (text, kind, source_id)
+    SELECT NEW.notes, 'pomodoro', NEW.id WHERE NEW.notes IS NOT NULL AND NEW.notes != '';
+END;
+
+CREATE TRIGGER IF NOT EXISTS pomodoro_records_search_delete AFTER DELETE ON pomodoro_records
+BEGIN
+    DELETE FROM search_index WHERE kind = 'pomodoro' AND source_id = OLD.id;
+END;
+
+-- 为已有记录建立索引
+INSERT INTO search_index (text, kind, source_id)
+SELECT window_title, 'activity', id FROM app_usage WHERE window_title != '';
+
+INSERT INTO search_index (text, kind, source_id)
+SELECT notes, 'pomodoro', id FROM pomodoro_records WHERE notes IS NOT NULL AND notes != '';
@@ -0,0 +1,2 @@
+-- 手动补录的活动，与自动追踪的记录区分
+ALTER TABLE app_usage ADD COLUMN manual BOOLEAN NOT NULL DEFAULT 0;
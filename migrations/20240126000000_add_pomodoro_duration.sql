@@ -0,0 +1,3 @@
+-- 番茄钟统一保存在 pomodoro_records，补充会话的计划时长（秒）和类别
+ALTER TABLE pomodoro_records ADD COLUMN duration INTEGER;
+ALTER TABLE pomodoro_records ADD COLUMN category TEXT;
@@ -0,0 +1,8 @@
+-- 活动统一保存在 app_usage，补充活动名称、说明、结束时间、项目和是否高效
+ALTER TABLE app_usage ADD COLUMN name TEXT;
+ALTER TABLE app_usage ADD COLUMN description TEXT;
+ALTER TABLE app_usage ADD COLUMN end_time DATETIME;
+ALTER TABLE app_usage ADD COLUMN project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL;
+ALTER TABLE app_usage ADD COLUMN is_productive BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_app_usage_project_id ON app_usage(project_id);
@@ -0,0 +1,3 @@
+-- 整理时间线会调整开始时间，审计记录同时保留调整前后的开始时间
+ALTER TABLE activity_audit ADD COLUMN original_start_time DATETIME;
+ALTER TABLE activity_audit ADD COLUMN new_start_time DATETIME;
@@ -3,8 +3,11 @@ use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 
 use crate::core::AppResult;
+use crate::application::commands::CommandHandler;
+use crate::application::queries::QueryHandler;
 use crate::application::services::ServiceContainer;
 use crate::application::events::{AppEvent, EventBus};
+use crate::domain::pomodoro::PomodoroManager;
 use crate::infrastructure::storage::SqliteStorage;
 use crate::infrastructure::config::FileConfigManager;
 use crate::plugins::registry::PluginRegistry;
@@ -26,11 +29,10 @@ impl App {
         let event_bus = EventBus::new(event_sender.clone());
         let plugin_registry = Arc::new(PluginRegistry::new(event_sender));
 
-        let services = Arc::new(ServiceContainer::new(
-            storage,
-            config,
-            config_manager,
-        ));
+        let pomodoro = Arc::new(PomodoroManager::new(storage.clone()));
+        let services = Arc::new(
+            ServiceContainer::new(storage, config, config_manager).with_pomodoro(pomodoro),
+        );
 
         Ok(Self {
             services,
@@ -44,6 +46,18 @@ impl App {
         self.services.clone()
     }
 
+    pub fn command_handler(&self) -> CommandHandler {
+        CommandHandler::new(
+            self.services.clone(),
+            self.event_bus.clone(),
+            self.plugin_registry.clone(),
+        )
+    }
+
+    pub fn query_handler(&self) -> QueryHandler {
+        QueryHandler::new(self.services.clone(), self.plugin_registry.clone())
+    }
+
     pub fn get_event_bus(&self) -> EventBus {
         self.event_bus.clone()
     }
@@ -1,7 +1,9 @@
 use crate::application::events::{AppEvent, EventBus};
 use crate::application::services::ServiceContainer;
 use crate::core::{AppError, AppResult};
-use crate::core::models::Project;
+use crate::core::models::{DeletionImpact, Project};
+use crate::domain::ActivityManager;
+use crate::domain::pomodoro::{CompletionChoice, PendingDecision, PomodoroManager};
 use crate::infrastructure::config::Config;
 use crate::plugins::PluginRegistry;
 use std::sync::Arc;
@@ -108,14 +110,41 @@ impl CommandHandler {
     }
 
     pub async fn update_project(&self, project: Project) -> AppResult<()> {
-        self.services.storage.update_project(&project).await?;
+        self.services.storage.save_project(&project).await?;
         self.event_bus.publish(AppEvent::ProjectUpdated(project));
         Ok(())
     }
 
-    pub async fn delete_project(&self, project: Project) -> AppResult<()> {
-        self.services.storage.delete_project(project.id.unwrap()).await?;
+    /// 影响的记录数超过确认阈值且未确认时返回 `ConfirmationRequired`，界面据此弹出确认对话框
+    pub async fn delete_project(&self, project: Project, confirmed: bool) -> AppResult<DeletionImpact> {
+        let id = project
+            .id
+            .ok_or_else(|| AppError::InvalidOperation("项目尚未保存".into()))?;
+        let (impact, _) = self.services.storage.delete_project(id, confirmed).await?;
         self.event_bus.publish(AppEvent::ProjectDeleted(project));
+        Ok(impact)
+    }
+
+    pub async fn adjust_activity_duration(&self, id: i64, delta: chrono::Duration) -> AppResult<()> {
+        let manager = ActivityManager::new(self.services.storage.clone());
+        let activity = manager.adjust_duration(id, delta).await?;
+        self.event_bus.publish(AppEvent::ActivityUpdated(Arc::new(activity)));
         Ok(())
     }
-} 
\ No newline at end of file
+
+    fn pomodoro(&self) -> AppResult<&Arc<PomodoroManager>> {
+        self.services
+            .pomodoro
+            .as_ref()
+            .ok_or_else(|| AppError::InvalidOperation("番茄钟不可用".into()))
+    }
+
+    /// 工作番茄结束后等待用户选择的下一步，界面每次计时刷新时读取
+    pub async fn pending_pomodoro_decision(&self) -> AppResult<Option<PendingDecision>> {
+        Ok(self.pomodoro()?.pending_decision().await)
+    }
+
+    pub async fn resolve_pomodoro_decision(&self, choice: CompletionChoice) -> AppResult<()> {
+        self.pomodoro()?.resolve_decision(choice).await
+    }
+}
@@ -2,11 +2,14 @@ use std::sync::Arc;
 use crate::core::{AppResult, error::AppError, traits::Storage};
 use crate::infrastructure::config::Config;
 use crate::domain::config::ConfigManager;
+use crate::domain::pomodoro::PomodoroManager;
 
 pub struct ServiceContainer {
     pub storage: Arc<dyn Storage + Send + Sync>,
     pub config: Config,
     pub config_manager: Arc<dyn ConfigManager + Send + Sync>,
+    /// 界面运行时的番茄钟，未设置时番茄钟命令返回错误
+    pub pomodoro: Option<Arc<PomodoroManager>>,
 }
 
 impl ServiceContainer {
@@ -19,9 +22,15 @@ impl ServiceContainer {
             storage,
             config,
             config_manager,
+            pomodoro: None,
         }
     }
 
+    pub fn with_pomodoro(mut self, pomodoro: Arc<PomodoroManager>) -> Self {
+        self.pomodoro = Some(pomodoro);
+        self
+    }
+
     pub async fn update_config(&mut self, config: Config) -> AppResult<()> {
         self.config = config;
         Ok(())
@@ -0,0 +1,993 @@
+use crate::core::models::{ExportFormat, PomodoroSession, PomodoroStatus, SearchHit, SearchKind};
+use crate::core::traits::{PomodoroTimer, Storage};
+use crate::core::{AppError, AppResult};
+use crate::domain::analysis::{AnalysisManager, GroupBy, Report, UsageGroup};
+use crate::domain::export::ExportManager;
+use crate::domain::pomodoro::{PomodoroManager, PomodoroSnapshot};
+use crate::infrastructure::crash::POMODORO_SNAPSHOT_FILE;
+use crate::infrastructure::ipc::IpcHandler;
+use async_trait::async_trait;
+use crate::domain::goal::local_midnight;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, Weekday};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use comfy_table::{presets::UTF8_FULL, Table};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Parser)]
+#[command(name = "time_tracker", version, about = "时间追踪与番茄钟")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+    /// 由开机自启项启动
+    #[arg(long = AUTOSTART_FLAG, hide = true)]
+    pub autostart: bool,
+}
+
+/// 写入开机自启项的启动参数
+pub const AUTOSTART_FLAG: &str = "autostart";
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// 在终端中输出分析报表
+    Report {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        /// 时长分布的分组方式
+        #[arg(long, value_enum, default_value_t = ReportGroupBy::Category)]
+        by: ReportGroupBy,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+        /// 只统计该项目（按名称匹配，不区分大小写）
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// 开始工作番茄
+    Start {
+        /// 先切换到该番茄钟预设并保存为默认，例如 `deep-work`
+        #[arg(long)]
+        preset: Option<String>,
+    },
+    /// 继续暂停中的番茄钟
+    Resume,
+    /// 在窗口标题和番茄钟备注中搜索，不指定范围时搜索全部历史
+    Search {
+        query: String,
+        #[command(flatten)]
+        range: DateRangeArgs,
+    },
+    /// 把记录导出到标准输出，例如 `export --range this-week > week.ics`
+    Export {
+        #[command(flatten)]
+        range: DateRangeArgs,
+        #[arg(long, value_enum, default_value_t = ExportFileFormat::Ical)]
+        format: ExportFileFormat,
+        /// iCalendar 中同时导出已完成的番茄钟
+        #[arg(long)]
+        pomodoros: bool,
+    },
+    /// 删除项目，关联的活动和番茄钟保留但解除关联，项目目标一并删除
+    DeleteProject {
+        /// 项目名称，不区分大小写
+        name: String,
+        /// 影响的记录数超过确认阈值时仍然删除
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// 命名的时间范围。`week`、`month`、`year` 为截至今天的最近若干天
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum DateRangeSpec {
+    Today,
+    Yesterday,
+    Week,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    #[value(name = "last-30", alias = "month")]
+    Last30,
+    Year,
+}
+
+impl DateRangeSpec {
+    /// 进行中的范围截止到 `now`，已结束的范围截止到下一天零点
+    pub fn resolve(self, now: DateTime<Local>, week_start: Weekday) -> (DateTime<Local>, DateTime<Local>) {
+        let today = now.date_naive();
+        let days_ago = |days: i64| local_midnight(today - ChronoDuration::days(days));
+        let offset = (7 + today.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+        let week = today - ChronoDuration::days(offset as i64);
+        match self {
+            DateRangeSpec::Today => (days_ago(0), now),
+            DateRangeSpec::Yesterday => (days_ago(1), days_ago(0)),
+            DateRangeSpec::Week => (days_ago(6), now),
+            DateRangeSpec::ThisWeek => (local_midnight(week), now),
+            DateRangeSpec::LastWeek => (local_midnight(week - ChronoDuration::days(7)), local_midnight(week)),
+            DateRangeSpec::ThisMonth => (local_midnight(today.with_day(1).unwrap()), now),
+            DateRangeSpec::Last30 => (days_ago(29), now),
+            DateRangeSpec::Year => (days_ago(364), now),
+        }
+    }
+}
+
+/// `--range` 与 `--from/--to` 二选一，都不指定时为今天
+#[derive(Debug, Clone, Default, PartialEq, Args, Serialize, Deserialize)]
+pub struct DateRangeArgs {
+    #[arg(long, value_enum, conflicts_with_all = ["from", "to"])]
+    pub range: Option<DateRangeSpec>,
+    /// 起始日期（含），格式为 YYYY-MM-DD
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// 结束日期（含），默认为今天
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+}
+
+impl From<DateRangeSpec> for DateRangeArgs {
+    fn from(spec: DateRangeSpec) -> Self {
+        Self {
+            range: Some(spec),
+            ..Self::default()
+        }
+    }
+}
+
+impl DateRangeArgs {
+    pub fn resolve(&self, now: DateTime<Local>, week_start: Weekday) -> AppResult<(DateTime<Local>, DateTime<Local>)> {
+        if self.from.is_none() && self.to.is_none() {
+            return Ok(self.range.unwrap_or(DateRangeSpec::Today).resolve(now, week_start));
+        }
+        let today = now.date_naive();
+        let to = self.to.unwrap_or(today);
+        let from = self.from.unwrap_or(to);
+        if from > to {
+            return Err(AppError::InvalidOperation(format!("起始日期 {} 晚于结束日期 {}", from, to)));
+        }
+        let end = if to >= today {
+            now
+        } else {
+            local_midnight(to + ChronoDuration::days(1))
+        };
+        Ok((local_midnight(from), end))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum ReportGroupBy {
+    App,
+    Category,
+}
+
+impl From<ReportGroupBy> for GroupBy {
+    fn from(by: ReportGroupBy) -> Self {
+        match by {
+            ReportGroupBy::App => GroupBy::App,
+            ReportGroupBy::Category => GroupBy::Category,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Markdown,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum ExportFileFormat {
+    Ical,
+    Csv,
+    Json,
+    Markdown,
+    Html,
+    /// 逐条输出 app_usage 记录，分批读取，适合很长的时间范围
+    UsageCsv,
+}
+
+impl From<ExportFileFormat> for ExportFormat {
+    fn from(format: ExportFileFormat) -> Self {
+        match format {
+            ExportFileFormat::Ical => ExportFormat::ICal,
+            ExportFileFormat::Csv => ExportFormat::CSV,
+            ExportFileFormat::Json => ExportFormat::JSON,
+            ExportFileFormat::Markdown => ExportFormat::Markdown,
+            ExportFileFormat::Html => ExportFormat::HTML,
+            ExportFileFormat::UsageCsv => ExportFormat::CSV,
+        }
+    }
+}
+
+/// 转发给运行中界面进程的命令，与 `Commands` 一一对应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IpcCommand {
+    Report {
+        range: DateRangeArgs,
+        by: ReportGroupBy,
+        format: ReportFormat,
+        project: Option<String>,
+    },
+    Start {
+        preset: Option<String>,
+    },
+    Resume,
+    Search {
+        query: String,
+        range: DateRangeArgs,
+    },
+    Export {
+        range: DateRangeArgs,
+        format: ExportFileFormat,
+        pomodoros: bool,
+    },
+    DeleteProject {
+        name: String,
+        yes: bool,
+    },
+}
+
+impl From<Commands> for IpcCommand {
+    fn from(command: Commands) -> Self {
+        match command {
+            Commands::Report { range, by, format, project } => IpcCommand::Report { range, by, format, project },
+            Commands::Start { preset } => IpcCommand::Start { preset },
+            Commands::Resume => IpcCommand::Resume,
+            Commands::Search { query, range } => IpcCommand::Search { query, range },
+            Commands::Export { range, format, pomodoros } => IpcCommand::Export { range, format, pomodoros },
+            Commands::DeleteProject { name, yes } => IpcCommand::DeleteProject { name, yes },
+        }
+    }
+}
+
+/// 没有界面进程运行时在命令行进程中直接执行
+pub async fn run(command: Commands, storage: Arc<dyn Storage + Send + Sync>, data_dir: &Path) -> AppResult<()> {
+    // 逐条导出直接写到标准输出，不在内存中拼接整个结果
+    if let Commands::Export { range, format: ExportFileFormat::UsageCsv, .. } = &command {
+        let week_start = storage.get_config().await?.unwrap_or_default().ui.week_start;
+        let (start, end) = range.resolve(Local::now(), week_start)?;
+        ExportManager::new(storage).write_usage_csv(start, end, std::io::stdout()).await?;
+        return Ok(());
+    }
+    let output = execute(command.into(), storage, None, data_dir).await?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// 执行命令并返回输出。`pomodoro` 为界面进程中的计时器，为 `None` 时通过快照文件操作番茄钟
+pub async fn execute(
+    command: IpcCommand,
+    storage: Arc<dyn Storage + Send + Sync>,
+    pomodoro: Option<&PomodoroManager>,
+    data_dir: &Path,
+) -> AppResult<String> {
+    match command {
+        IpcCommand::Report { range, by, format, project } => {
+            let config = storage.get_config().await?.unwrap_or_default();
+            let (start, end) = range.resolve(Local::now(), config.ui.week_start)?;
+            let project_id = match project {
+                Some(name) => Some(find_project(storage.as_ref(), &name).await?),
+                None => None,
+            };
+            let report = AnalysisManager::new(storage)
+                .with_category_weights(config.categories.weights)
+                .report_for(start, end, by.into(), project_id)
+                .await?;
+            match format {
+                ReportFormat::Text => Ok(render_text(&report)),
+                ReportFormat::Json => render_json(&report),
+                ReportFormat::Markdown => Ok(render_markdown(&report)),
+                ReportFormat::Csv => render_csv(&report),
+            }
+        }
+        IpcCommand::Start { preset } => {
+            let session = match pomodoro {
+                Some(pomodoro) => start_pomodoro(storage.as_ref(), pomodoro, preset.as_deref()).await?,
+                None => {
+                    let settings = storage.get_config().await?.unwrap_or_default().pomodoro;
+                    let manager = PomodoroManager::new(storage.clone())
+                        .with_settings(settings)
+                        .with_snapshot_path(data_dir.join(POMODORO_SNAPSHOT_FILE));
+                    manager.restore_saved().await?;
+                    start_pomodoro(storage.as_ref(), &manager, preset.as_deref()).await?
+                }
+            };
+            Ok(format!("已开始番茄钟，时长 {}", format_duration(session.duration)))
+        }
+        IpcCommand::Resume => {
+            let (session, remaining) = match pomodoro {
+                Some(pomodoro) => resume_paused(pomodoro).await?,
+                None => resume_pomodoro(storage, &data_dir.join(POMODORO_SNAPSHOT_FILE)).await?,
+            };
+            let phase = match session.status {
+                PomodoroStatus::ShortBreak => "短休息",
+                PomodoroStatus::LongBreak => "长休息",
+                _ => "工作",
+            };
+            Ok(format!("已继续番茄钟（{}），剩余 {}", phase, format_duration(remaining)))
+        }
+        IpcCommand::Search { query, range } => {
+            let now = Local::now();
+            let range = if range == DateRangeArgs::default() {
+                (DateTime::<Local>::from(std::time::UNIX_EPOCH), now)
+            } else {
+                let week_start = storage.get_config().await?.unwrap_or_default().ui.week_start;
+                range.resolve(now, week_start)?
+            };
+            Ok(render_search(&storage.search(&query, range).await?))
+        }
+        IpcCommand::Export { range, format, pomodoros } => {
+            let config = storage.get_config().await?.unwrap_or_default();
+            let (start, end) = range.resolve(Local::now(), config.ui.week_start)?;
+            let exporter = ExportManager::new(storage).with_settings(&config.export);
+            let bytes = match format {
+                ExportFileFormat::Ical => exporter.export_ical(start, end, pomodoros).await?,
+                ExportFileFormat::UsageCsv => {
+                    let mut bytes = Vec::new();
+                    exporter.write_usage_csv(start, end, &mut bytes).await?;
+                    bytes
+                }
+                format => exporter.export_report(start, end, format.into()).await?,
+            };
+            String::from_utf8(bytes).map_err(|e| AppError::System(format!("导出内容不是有效的 UTF-8: {}", e)))
+        }
+        IpcCommand::DeleteProject { name, yes } => {
+            let project_id = find_project(storage.as_ref(), &name).await?;
+            let impact = match storage.delete_project(project_id, yes).await {
+                Ok((impact, _)) => impact,
+                Err(AppError::ConfirmationRequired(count)) => {
+                    return Err(AppError::InvalidOperation(format!(
+                        "删除项目「{}」将影响 {} 条记录，确认删除请加上 --yes",
+                        name, count
+                    )))
+                }
+                Err(e) => return Err(e),
+            };
+            Ok(format!(
+                "已删除项目「{}」：{} 条活动和 {} 个番茄钟已解除关联，删除了 {} 个目标",
+                name, impact.app_usage, impact.pomodoros, impact.goals
+            ))
+        }
+    }
+}
+
+pub fn render_search(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "没有找到匹配的记录".into();
+    }
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header(vec!["时间", "来源", "内容"]);
+    for hit in hits {
+        let source = match (hit.kind, &hit.app_name) {
+            (SearchKind::Activity, Some(app)) => app.clone(),
+            (SearchKind::Activity, None) => "活动".into(),
+            (SearchKind::Pomodoro, _) => "番茄钟".into(),
+        };
+        table.add_row(vec![hit.start_time.format("%Y-%m-%d %H:%M").to_string(), source, hit.snippet.clone()]);
+    }
+    table.to_string()
+}
+
+async fn find_project(storage: &(dyn Storage + Send + Sync), name: &str) -> AppResult<i64> {
+    storage
+        .list_projects()
+        .await?
+        .into_iter()
+        .find(|project| project.name.to_lowercase() == name.trim().to_lowercase())
+        .and_then(|project| project.id)
+        .ok_or_else(|| AppError::NotFound(format!("项目「{}」", name)))
+}
+
+/// 界面进程处理命令行转发的请求，使用界面中的计时器
+pub struct GuiCommandHandler {
+    storage: Arc<dyn Storage + Send + Sync>,
+    pomodoro: Arc<PomodoroManager>,
+    data_dir: PathBuf,
+}
+
+impl GuiCommandHandler {
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>, pomodoro: Arc<PomodoroManager>, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            storage,
+            pomodoro,
+            data_dir: data_dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl IpcHandler for GuiCommandHandler {
+    async fn handle(&self, command: IpcCommand) -> AppResult<String> {
+        execute(command, self.storage.clone(), Some(&self.pomodoro), &self.data_dir).await
+    }
+}
+
+/// 从快照文件继续暂停中的番茄钟。界面和命令行是不同的进程，只能通过快照共享状态
+pub async fn resume_pomodoro(
+    storage: Arc<dyn Storage + Send + Sync>,
+    snapshot_path: &Path,
+) -> AppResult<(PomodoroSession, Duration)> {
+    let paused = PomodoroSnapshot::load(snapshot_path)?.is_some_and(|snapshot| snapshot.paused_at.is_some());
+    if !paused {
+        return Err(AppError::InvalidOperation("没有暂停中的番茄钟".into()));
+    }
+
+    let manager = PomodoroManager::new(storage).with_snapshot_path(snapshot_path);
+    manager.restore_saved().await?;
+    resume_paused(&manager).await
+}
+
+/// 开始工作番茄。指定预设时先切换并写入配置，之后的番茄同样使用该预设
+async fn start_pomodoro(
+    storage: &(dyn Storage + Send + Sync),
+    manager: &PomodoroManager,
+    preset: Option<&str>,
+) -> AppResult<PomodoroSession> {
+    if manager.is_active().await? {
+        return Err(AppError::InvalidOperation("已有进行中的番茄钟".into()));
+    }
+    if let Some(name) = preset {
+        manager.apply_preset(name).await?;
+        let mut config = storage.get_config().await?.unwrap_or_default();
+        config.pomodoro.apply_preset(name)?;
+        storage.save_config(&config).await?;
+    }
+    let minutes = manager.settings().work_duration.as_secs() / 60;
+    manager.start_session(minutes as i32).await?;
+    manager
+        .get_current_session()
+        .await?
+        .ok_or_else(|| AppError::InvalidOperation("番茄钟未能开始".into()))
+}
+
+/// 继续暂停中的会话，返回会话和剩余时间
+async fn resume_paused(manager: &PomodoroManager) -> AppResult<(PomodoroSession, Duration)> {
+    let paused = manager
+        .get_current_session()
+        .await?
+        .is_some_and(|session| session.status == PomodoroStatus::Interrupted);
+    if !paused {
+        return Err(AppError::InvalidOperation("没有暂停中的番茄钟".into()));
+    }
+    manager.resume_session().await?;
+    let session = manager
+        .get_current_session()
+        .await?
+        .ok_or_else(|| AppError::InvalidOperation("没有暂停中的番茄钟".into()))?;
+    let remaining = session.duration.saturating_sub(manager.elapsed().await?);
+    Ok((session, remaining))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+fn groups_table(title: &str, groups: &[UsageGroup]) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header(vec![title, "时长", "记录数"]);
+    for group in groups {
+        table.add_row(vec![
+            group.key.clone(),
+            format_duration(group.total_time),
+            group.count.to_string(),
+        ]);
+    }
+    table
+}
+
+pub fn render_text(report: &Report) -> String {
+    let mut output = format!(
+        "{} - {}\n",
+        report.start.format("%Y-%m-%d"),
+        report.end.format("%Y-%m-%d")
+    );
+    if report.is_empty() {
+        output.push_str("该时间段内没有记录\n");
+        return output;
+    }
+
+    let mut metrics = Table::new();
+    metrics.load_preset(UTF8_FULL).set_header(vec!["指标", "数值"]);
+    metrics.add_row(vec!["总时长".to_string(), format_duration(report.total_time)]);
+    metrics.add_row(vec!["生产性时长".to_string(), format_duration(report.productive_time)]);
+    metrics.add_row(vec!["生产率".to_string(), format!("{:.0}%", report.productivity_score)]);
+    metrics.add_row(vec!["专注时长".to_string(), format_duration(report.focus_time)]);
+    metrics.add_row(vec!["完成番茄".to_string(), report.completed_pomodoros.to_string()]);
+    metrics.add_row(vec!["中断番茄".to_string(), report.interrupted_pomodoros.to_string()]);
+    metrics.add_row(vec!["连续天数".to_string(), report.current_streak.to_string()]);
+    output.push_str(&format!("{}\n", metrics));
+
+    for (title, groups) in [
+        ("分布", &report.breakdown),
+        ("常用应用", &report.top_apps),
+        ("主要项目", &report.top_projects),
+    ] {
+        if !groups.is_empty() {
+            output.push_str(&format!("{}\n", groups_table(title, groups)));
+        }
+    }
+    output
+}
+
+fn groups_markdown(title: &str, groups: &[UsageGroup]) -> String {
+    let mut output = format!("| {} | 时长 | 记录数 |\n| --- | --- | --- |\n", title);
+    for group in groups {
+        output.push_str(&format!(
+            "| {} | {} | {} |\n",
+            group.key.replace('|', "\\|"),
+            format_duration(group.total_time),
+            group.count
+        ));
+    }
+    output
+}
+
+/// GitHub 风格的 Markdown 表格，便于粘贴到日报或 wiki
+pub fn render_markdown(report: &Report) -> String {
+    let mut output = format!(
+        "# {} - {}\n\n",
+        report.start.format("%Y-%m-%d"),
+        report.end.format("%Y-%m-%d")
+    );
+    if report.is_empty() {
+        output.push_str("该时间段内没有记录\n");
+        return output;
+    }
+
+    output.push_str("| 指标 | 数值 |\n| --- | --- |\n");
+    output.push_str(&format!("| 总时长 | {} |\n", format_duration(report.total_time)));
+    output.push_str(&format!("| 生产性时长 | {} |\n", format_duration(report.productive_time)));
+    output.push_str(&format!("| 生产率 | {:.0}% |\n", report.productivity_score));
+    output.push_str(&format!("| 专注时长 | {} |\n", format_duration(report.focus_time)));
+    output.push_str(&format!("| 完成番茄 | {} |\n", report.completed_pomodoros));
+    output.push_str(&format!("| 中断番茄 | {} |\n", report.interrupted_pomodoros));
+    output.push_str(&format!("| 连续天数 | {} |\n", report.current_streak));
+
+    for (title, groups) in [
+        ("分布", &report.breakdown),
+        ("常用应用", &report.top_apps),
+        ("主要项目", &report.top_projects),
+    ] {
+        if !groups.is_empty() {
+            output.push_str(&format!("\n{}", groups_markdown(title, groups)));
+        }
+    }
+    output
+}
+
+fn groups_json(groups: &[UsageGroup]) -> Vec<serde_json::Value> {
+    groups
+        .iter()
+        .map(|g| serde_json::json!({ "name": g.key, "seconds": g.total_time.as_secs(), "count": g.count }))
+        .collect()
+}
+
+/// 时长以秒输出，便于脚本处理
+pub fn render_json(report: &Report) -> AppResult<String> {
+    let value = serde_json::json!({
+        "range": { "start": report.start, "end": report.end },
+        "metrics": {
+            "total_seconds": report.total_time.as_secs(),
+            "productive_seconds": report.productive_time.as_secs(),
+            "productivity_score": report.productivity_score,
+            "focus_seconds": report.focus_time.as_secs(),
+            "completed_pomodoros": report.completed_pomodoros,
+            "interrupted_pomodoros": report.interrupted_pomodoros,
+        },
+        "breakdown": groups_json(&report.breakdown),
+        "top_apps": groups_json(&report.top_apps),
+        "top_projects": groups_json(&report.top_projects),
+        "streak": report.current_streak,
+    });
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// 每行为 `section,name,value,count`，时长以秒为单位
+pub fn render_csv(report: &Report) -> AppResult<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["section", "name", "value", "count"])?;
+    for (name, value) in [
+        ("total_seconds", report.total_time.as_secs().to_string()),
+        ("productive_seconds", report.productive_time.as_secs().to_string()),
+        ("productivity_score", format!("{:.1}", report.productivity_score)),
+        ("focus_seconds", report.focus_time.as_secs().to_string()),
+        ("completed_pomodoros", report.completed_pomodoros.to_string()),
+        ("interrupted_pomodoros", report.interrupted_pomodoros.to_string()),
+        ("streak", report.current_streak.to_string()),
+    ] {
+        writer.write_record(["metric", name, value.as_str(), ""])?;
+    }
+    for (section, groups) in [
+        ("breakdown", &report.breakdown),
+        ("top_app", &report.top_apps),
+        ("top_project", &report.top_projects),
+    ] {
+        for group in groups {
+            writer.write_record([
+                section,
+                group.key.as_str(),
+                group.total_time.as_secs().to_string().as_str(),
+                group.count.to_string().as_str(),
+            ])?;
+        }
+    }
+    let data = writer.into_inner().map_err(|e| AppError::System(e.to_string()))?;
+    String::from_utf8(data).map_err(|e| AppError::System(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Activity, DeletionImpact, Project};
+    use crate::core::traits::MockStorage;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_json_report_top_level_keys() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_activities().returning(|_, _| Ok(Vec::new()));
+        mock_storage.expect_get_pomodoro_sessions().returning(|_, _| Ok(Vec::new()));
+        mock_storage.expect_list_projects().returning(|| Ok(Vec::new()));
+
+        let (start, end) = DateRangeSpec::Week.resolve(Local::now(), Weekday::Mon);
+        let report = AnalysisManager::new(Arc::new(mock_storage))
+            .report(start, end, GroupBy::Category)
+            .await?;
+
+        let json: serde_json::Value = serde_json::from_str(&render_json(&report)?)?;
+        let keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        for key in ["range", "metrics", "breakdown", "top_apps", "top_projects", "streak"] {
+            assert!(keys.contains(&key), "missing {}", key);
+        }
+        assert_eq!(json["metrics"]["total_seconds"], 0);
+
+        // 空时间段不输出表格
+        assert!(render_text(&report).contains("没有记录"));
+        assert!(!render_markdown(&report).contains("| 指标 |"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_paused_pomodoro_from_snapshot() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(POMODORO_SNAPSHOT_FILE);
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MockStorage::new());
+        assert!(resume_pomodoro(storage.clone(), &path).await.is_err());
+
+        // 界面进程开始并暂停
+        let gui = PomodoroManager::new(storage.clone()).with_snapshot_path(&path);
+        gui.start_session(25).await?;
+        gui.pause_session().await?;
+
+        let (session, remaining) = resume_pomodoro(storage.clone(), &path).await?;
+        assert_eq!(session.status, PomodoroStatus::Work);
+        assert!(remaining <= Duration::from_secs(25 * 60) && remaining > Duration::from_secs(24 * 60));
+
+        // 已在运行时不能再次继续
+        assert!(resume_pomodoro(storage, &path).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_start_with_preset_saves_config() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_config().returning(|| Ok(None));
+        mock_storage
+            .expect_save_config()
+            .withf(|config| config.pomodoro.active_preset.as_deref() == Some("deep-work"))
+            .times(1)
+            .returning(|_| Ok(()));
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(mock_storage);
+
+        let command = IpcCommand::Start { preset: Some("deep-work".into()) };
+        let output = execute(command, storage.clone(), None, dir.path()).await?;
+        assert!(output.contains("0h 50m"));
+        let snapshot = PomodoroSnapshot::load(&dir.path().join(POMODORO_SNAPSHOT_FILE))?.unwrap();
+        assert_eq!(snapshot.session.duration, Duration::from_secs(50 * 60));
+
+        // 已有进行中的番茄时不重复开始
+        assert!(execute(IpcCommand::Start { preset: None }, storage.clone(), None, dir.path()).await.is_err());
+        let unknown = IpcCommand::Start { preset: Some("sprint".into()) };
+        assert!(execute(unknown, storage, None, tempfile::tempdir()?.path()).await.is_err());
+        Ok(())
+    }
+
+    fn project_storage() -> MockStorage {
+        let now = Local::now();
+        let activity = |app: &str, minutes: u64, is_productive: bool| Activity {
+            id: None,
+            name: app.into(),
+            start_time: now - chrono::Duration::minutes(30),
+            end_time: None,
+            project_id: Some(1),
+            description: None,
+            duration: Duration::from_secs(minutes * 60),
+            category: "development".into(),
+            is_productive,
+            app_name: app.into(),
+            window_title: app.into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        };
+        let activities = vec![activity("code", 45, true), activity("browser, docs", 15, false)];
+        let session = PomodoroSession {
+            id: Some(1),
+            start_time: now - chrono::Duration::minutes(30),
+            end_time: Some(now - chrono::Duration::minutes(5)),
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: Some(1),
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        };
+
+        // 只允许按项目读取，读取全部记录时 mock 会报错
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_config().returning(|| Ok(None));
+        mock_storage.expect_list_projects().returning(|| {
+            Ok(vec![Project {
+                id: Some(1),
+                ..Project::new("Client A".into(), None)
+            }])
+        });
+        mock_storage
+            .expect_get_project_activities()
+            .withf(|id, _, _| *id == 1)
+            .returning(move |_, _, _| Ok(activities.clone()));
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .withf(|id, _, _| *id == 1)
+            .returning(move |_, _, _| Ok(vec![session.clone()]));
+        mock_storage
+    }
+
+    async fn project_report(format: ReportFormat) -> AppResult<String> {
+        let command = IpcCommand::Report {
+            range: DateRangeSpec::Today.into(),
+            by: ReportGroupBy::Category,
+            format,
+            project: Some("client a".into()),
+        };
+        let dir = tempfile::tempdir()?;
+        execute(command, Arc::new(project_storage()), None, dir.path()).await
+    }
+
+    #[tokio::test]
+    async fn test_project_report_formats() -> AppResult<()> {
+        let text = project_report(ReportFormat::Text).await?;
+        for expected in ["1h 00m", "75%", "0h 25m", "code", "browser, docs"] {
+            assert!(text.contains(expected), "text missing {}", expected);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&project_report(ReportFormat::Json).await?)?;
+        assert_eq!(json["metrics"]["total_seconds"], 3600);
+        assert_eq!(json["metrics"]["completed_pomodoros"], 1);
+        assert_eq!(json["top_apps"][0]["name"], "code");
+
+        let csv = project_report(ReportFormat::Csv).await?;
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let rows: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>()?;
+        assert!(rows.iter().any(|r| *r == vec!["metric", "focus_seconds", "1500", ""]));
+        assert!(rows.iter().any(|r| *r == vec!["top_app", "browser, docs", "900", "1"]));
+
+        let markdown = project_report(ReportFormat::Markdown).await?;
+        assert!(markdown.contains("| 完成番茄 | 1 |"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_report_unknown_project() {
+        let command = IpcCommand::Report {
+            range: DateRangeSpec::Today.into(),
+            by: ReportGroupBy::App,
+            format: ReportFormat::Text,
+            project: Some("missing".into()),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let result = execute(command, Arc::new(project_storage()), None, dir.path()).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_defaults_to_all_history() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_search()
+            .withf(|query, range| query == "report" && range.0 == DateTime::<Local>::from(std::time::UNIX_EPOCH))
+            .returning(|_, _| {
+                Ok(vec![SearchHit {
+                    kind: SearchKind::Activity,
+                    id: 1,
+                    start_time: Local.with_ymd_and_hms(2024, 3, 13, 9, 30, 0).unwrap(),
+                    app_name: Some("Preview".into()),
+                    snippet: "Quarterly **Report**.pdf".into(),
+                }])
+            });
+
+        let cli = Cli::parse_from(["time_tracker", "search", "report"]);
+        let dir = tempfile::tempdir()?;
+        let output = execute(cli.command.unwrap().into(), Arc::new(mock_storage), None, dir.path()).await?;
+        assert!(output.contains("2024-03-13 09:30"));
+        assert!(output.contains("Preview"));
+        assert!(output.contains("Quarterly **Report**.pdf"));
+        assert_eq!(render_search(&[]), "没有找到匹配的记录");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_named_ranges() {
+        let at = |y, m, d, h| Local.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+        // 2024-03-13 是周三
+        let now = at(2024, 3, 13, 15);
+        let cases = [
+            (DateRangeSpec::Today, Weekday::Mon, at(2024, 3, 13, 0), now),
+            (DateRangeSpec::Yesterday, Weekday::Mon, at(2024, 3, 12, 0), at(2024, 3, 13, 0)),
+            (DateRangeSpec::ThisWeek, Weekday::Mon, at(2024, 3, 11, 0), now),
+            (DateRangeSpec::ThisWeek, Weekday::Sun, at(2024, 3, 10, 0), now),
+            (DateRangeSpec::LastWeek, Weekday::Mon, at(2024, 3, 4, 0), at(2024, 3, 11, 0)),
+            (DateRangeSpec::LastWeek, Weekday::Thu, at(2024, 2, 29, 0), at(2024, 3, 7, 0)),
+            (DateRangeSpec::ThisMonth, Weekday::Mon, at(2024, 3, 1, 0), now),
+            (DateRangeSpec::Last30, Weekday::Mon, at(2024, 2, 13, 0), now),
+        ];
+        for (spec, week_start, start, end) in cases {
+            assert_eq!(spec.resolve(now, week_start), (start, end), "{:?} {:?}", spec, week_start);
+        }
+
+        // 周起始日当天本周从当天开始
+        let monday = at(2024, 3, 11, 9);
+        assert_eq!(DateRangeSpec::ThisWeek.resolve(monday, Weekday::Mon).0, at(2024, 3, 11, 0));
+    }
+
+    #[test]
+    fn test_resolve_explicit_dates() -> AppResult<()> {
+        let at = |y, m, d, h| Local.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap();
+        let now = at(2024, 3, 13, 15);
+        let date = |d| NaiveDate::from_ymd_opt(2024, 3, d);
+
+        let range = DateRangeArgs { range: None, from: date(1), to: date(3) };
+        assert_eq!(range.resolve(now, Weekday::Mon)?, (at(2024, 3, 1, 0), at(2024, 3, 4, 0)));
+
+        // 只指定起始日期时截止到现在
+        let range = DateRangeArgs { range: None, from: date(10), to: None };
+        assert_eq!(range.resolve(now, Weekday::Mon)?, (at(2024, 3, 10, 0), now));
+
+        let range = DateRangeArgs { range: None, from: date(5), to: date(4) };
+        assert!(matches!(range.resolve(now, Weekday::Mon), Err(AppError::InvalidOperation(_))));
+
+        // 都不指定时为今天
+        assert_eq!(DateRangeArgs::default().resolve(now, Weekday::Mon)?, (at(2024, 3, 13, 0), now));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_date_range() {
+        let cli = Cli::parse_from(["time_tracker", "report", "--range", "last-week"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report { range: DateRangeArgs { range: Some(DateRangeSpec::LastWeek), .. }, .. })
+        ));
+
+        // 旧的 month 取值仍然可用
+        let cli = Cli::parse_from(["time_tracker", "report", "--range", "month"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report { range: DateRangeArgs { range: Some(DateRangeSpec::Last30), .. }, .. })
+        ));
+
+        let cli = Cli::parse_from(["time_tracker", "report", "--from", "2024-03-01", "--to", "2024-03-07"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report { range: DateRangeArgs { range: None, from: Some(_), to: Some(_) }, .. })
+        ));
+
+        assert!(Cli::try_parse_from(["time_tracker", "report", "--range", "today", "--from", "2024-03-01"]).is_err());
+        assert!(Cli::try_parse_from(["time_tracker", "report", "--to", "03/07/2024"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_report_command() {
+        let cli = Cli::parse_from(["time_tracker", "report", "--range", "week", "--by", "app", "--format", "json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report {
+                range: DateRangeArgs {
+                    range: Some(DateRangeSpec::Week),
+                    from: None,
+                    to: None,
+                },
+                by: ReportGroupBy::App,
+                format: ReportFormat::Json,
+                project: None,
+            })
+        ));
+
+        let cli = Cli::parse_from(["time_tracker", "report", "--format", "csv", "--project", "Client A"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report {
+                format: ReportFormat::Csv,
+                project: Some(ref name),
+                ..
+            }) if name == "Client A"
+        ));
+
+        let cli = Cli::parse_from(["time_tracker", "report", "--format", "markdown"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Report {
+                format: ReportFormat::Markdown,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_start_command() {
+        let cli = Cli::parse_from(["time_tracker", "start", "--preset", "deep-work"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Start { preset: Some(ref name) }) if name == "deep-work"
+        ));
+        let cli = Cli::parse_from(["time_tracker", "start"]);
+        assert!(matches!(cli.command, Some(Commands::Start { preset: None })));
+    }
+
+    #[tokio::test]
+    async fn test_delete_project_requires_yes_above_threshold() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_list_projects()
+            .returning(|| Ok(vec![Project { id: Some(3), ..Project::new("Client".into(), None) }]));
+        mock_storage
+            .expect_delete_project()
+            .withf(|id, confirmed| *id == 3 && !*confirmed)
+            .returning(|_, _| Err(AppError::ConfirmationRequired(120)));
+        mock_storage
+            .expect_delete_project()
+            .withf(|id, confirmed| *id == 3 && *confirmed)
+            .returning(|_, _| Ok((DeletionImpact { app_usage: 100, pomodoros: 19, goals: 1, ..DeletionImpact::default() }, None)));
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(mock_storage);
+        let dir = tempfile::tempdir()?;
+
+        let command = IpcCommand::DeleteProject { name: "client".into(), yes: false };
+        match execute(command, storage.clone(), None, dir.path()).await {
+            Err(AppError::InvalidOperation(message)) => assert!(message.contains("120") && message.contains("--yes")),
+            other => panic!("expected confirmation error, got {:?}", other),
+        }
+
+        let command = IpcCommand::DeleteProject { name: "client".into(), yes: true };
+        let output = execute(command, storage, None, dir.path()).await?;
+        assert!(output.contains("100 条活动"));
+
+        let cli = Cli::parse_from(["time_tracker", "delete-project", "Client", "--yes"]);
+        assert!(matches!(cli.command, Some(Commands::DeleteProject { ref name, yes: true }) if name == "Client"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_export_command() {
+        let cli = Cli::parse_from(["time_tracker", "export", "--range", "this-week", "--pomodoros"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export {
+                range: DateRangeArgs { range: Some(DateRangeSpec::ThisWeek), .. },
+                format: ExportFileFormat::Ical,
+                pomodoros: true,
+            })
+        ));
+
+        let cli = Cli::parse_from(["time_tracker", "export", "--format", "csv"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { format: ExportFileFormat::Csv, pomodoros: false, .. })
+        ));
+    }
+}
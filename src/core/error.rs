@@ -33,6 +33,10 @@ pub enum AppError {
 
     #[error("System error: {0}")]
     System(String),
+
+    /// 批量删除超过阈值，需要用户确认后重试
+    #[error("Confirmation required: {0} records will be affected")]
+    ConfirmationRequired(u64),
 }
 
 impl From<String> for AppError {
@@ -1,5 +1,6 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,62 @@ pub struct Activity {
     pub is_productive: bool,
     pub app_name: String,
     pub window_title: String,
+    /// 为空时沿用所属项目的计费设置
+    #[serde(default)]
+    pub is_billable: Option<bool>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 记录时的 UTC 偏移（秒），用于在切换时区后仍按原本地日期统计
+    #[serde(default)]
+    pub utc_offset: Option<i32>,
+    /// 用户手动补录的记录，不是自动追踪得到的
+    #[serde(default)]
+    pub manual: bool,
+}
+
+/// app_usage 表中的一行，追踪器批量写入和按范围流式读取时使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUsageRecord {
+    pub id: i64,
+    pub app_name: String,
+    pub window_title: String,
+    pub start_time: DateTime<Local>,
+    pub duration: i64,
+    pub category: Option<String>,
+    pub project_id: Option<i64>,
+}
+
+impl From<&Activity> for AppUsageRecord {
+    fn from(activity: &Activity) -> Self {
+        Self {
+            id: activity.id.unwrap_or_default(),
+            app_name: activity.app_name.clone(),
+            window_title: activity.window_title.clone(),
+            start_time: activity.start_time,
+            duration: activity.duration.as_secs() as i64,
+            category: Some(activity.category.clone()).filter(|c| !c.is_empty()),
+            project_id: activity.project_id,
+        }
+    }
+}
+
+/// 当前时区的 UTC 偏移（秒）
+pub fn current_utc_offset() -> i32 {
+    Local::now().offset().local_minus_utc()
+}
+
+/// 按记录时的偏移计算本地日期，旧记录没有偏移时退回当前时区
+pub fn recorded_local_date(time: &DateTime<Local>, utc_offset: Option<i32>) -> NaiveDate {
+    utc_offset
+        .and_then(FixedOffset::east_opt)
+        .map(|offset| time.with_timezone(&offset).date_naive())
+        .unwrap_or_else(|| time.date_naive())
+}
+
+impl Activity {
+    pub fn local_date(&self) -> NaiveDate {
+        recorded_local_date(&self.start_time, self.utc_offset)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -22,6 +79,17 @@ pub struct Project {
     pub id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
+    /// 列表和图表中使用的颜色，格式为 `#RRGGBB`
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub is_billable: bool,
+    /// 在该项目下创建的番茄钟未指定标签时使用
+    #[serde(default)]
+    pub default_tags: Vec<String>,
+    /// 在该项目下创建的记录未指定类别时使用
+    #[serde(default)]
+    pub default_category: Option<String>,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
 }
@@ -39,13 +107,33 @@ impl Project {
             id: None,
             name,
             description,
+            color: None,
+            is_billable: false,
+            default_tags: Vec::new(),
+            default_category: None,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// 未显式指定标签时继承项目默认标签
+    pub fn resolve_tags(&self, tags: Vec<String>) -> Vec<String> {
+        if tags.is_empty() {
+            self.default_tags.clone()
+        } else {
+            tags
+        }
+    }
+
+    /// 未显式指定类别时继承项目默认类别
+    pub fn resolve_category(&self, category: Option<String>) -> Option<String> {
+        category
+            .filter(|c| !c.is_empty())
+            .or_else(|| self.default_category.clone())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PomodoroSession {
     pub id: Option<i64>,
     pub start_time: DateTime<Local>,
@@ -54,6 +142,33 @@ pub struct PomodoroSession {
     pub status: PomodoroStatus,
     pub project_id: Option<i64>,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// 记录时的 UTC 偏移（秒）
+    #[serde(default)]
+    pub utc_offset: Option<i32>,
+}
+
+impl PomodoroSession {
+    pub fn local_date(&self) -> NaiveDate {
+        recorded_local_date(&self.start_time, self.utc_offset)
+    }
+}
+
+/// 手动调整活动起止时间的审计记录，保留调整前的值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityAudit {
+    pub id: Option<i64>,
+    pub activity_id: i64,
+    pub original_start_time: DateTime<Local>,
+    pub new_start_time: DateTime<Local>,
+    pub original_end_time: Option<DateTime<Local>>,
+    pub original_duration: Duration,
+    pub new_end_time: DateTime<Local>,
+    pub new_duration: Duration,
+    pub adjusted_at: DateTime<Local>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -65,6 +180,207 @@ pub enum PomodoroStatus {
     Interrupted,
 }
 
+impl PomodoroStatus {
+    /// pomodoro_records.status 中保存的文本，每日汇总按它统计完成和中断次数
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PomodoroStatus::Work => "Work",
+            PomodoroStatus::ShortBreak => "ShortBreak",
+            PomodoroStatus::LongBreak => "LongBreak",
+            PomodoroStatus::Completed => "Completed",
+            PomodoroStatus::Interrupted => "Interrupted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Work" => Some(PomodoroStatus::Work),
+            "ShortBreak" => Some(PomodoroStatus::ShortBreak),
+            "LongBreak" => Some(PomodoroStatus::LongBreak),
+            "Completed" => Some(PomodoroStatus::Completed),
+            "Interrupted" => Some(PomodoroStatus::Interrupted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GoalPeriod {
+    Week,
+    Month,
+}
+
+impl GoalPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalPeriod::Week => "week",
+            GoalPeriod::Month => "month",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(GoalPeriod::Week),
+            "month" => Some(GoalPeriod::Month),
+            _ => None,
+        }
+    }
+}
+
+/// 项目在每个周期内计划投入的时长，例如本月 20 小时
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectGoal {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub period: GoalPeriod,
+    pub target: Duration,
+}
+
+/// 每日目标统计的内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GoalKind {
+    /// 生产性活动时长，目标单位为秒
+    ProductiveTime,
+    /// 完成的番茄钟个数
+    CompletedPomodoros,
+    /// 指定项目的活动时长，目标单位为秒
+    ProjectTime { project_id: i64 },
+}
+
+impl GoalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalKind::ProductiveTime => "productive_time",
+            GoalKind::CompletedPomodoros => "completed_pomodoros",
+            GoalKind::ProjectTime { .. } => "project_time",
+        }
+    }
+
+    pub fn parse(value: &str, project_id: Option<i64>) -> Option<Self> {
+        match (value, project_id) {
+            ("productive_time", _) => Some(GoalKind::ProductiveTime),
+            ("completed_pomodoros", _) => Some(GoalKind::CompletedPomodoros),
+            ("project_time", Some(project_id)) => Some(GoalKind::ProjectTime { project_id }),
+            _ => None,
+        }
+    }
+
+    pub fn project_id(&self) -> Option<i64> {
+        match self {
+            GoalKind::ProjectTime { project_id } => Some(*project_id),
+            _ => None,
+        }
+    }
+
+    /// 目标值以秒计时为 `true`，以个数计时为 `false`
+    pub fn is_duration(&self) -> bool {
+        !matches!(self, GoalKind::CompletedPomodoros)
+    }
+}
+
+/// 目标在哪些日子生效
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalSchedule {
+    Daily,
+    /// 周一到周五
+    Weekdays,
+    Days(Vec<Weekday>),
+}
+
+impl GoalSchedule {
+    pub fn applies_on(&self, date: NaiveDate) -> bool {
+        match self {
+            GoalSchedule::Daily => true,
+            GoalSchedule::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            GoalSchedule::Days(days) => days.contains(&date.weekday()),
+        }
+    }
+}
+
+/// 每日目标，例如工作日每天 4 小时生产性时间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub id: Option<i64>,
+    pub kind: GoalKind,
+    /// 时长类目标为秒数，番茄钟目标为个数
+    pub target: u64,
+    pub schedule: GoalSchedule,
+    pub active: bool,
+}
+
+impl Goal {
+    /// 目标启用且在 `date` 当天生效
+    pub fn is_due_on(&self, date: NaiveDate) -> bool {
+        self.active && self.schedule.applies_on(date)
+    }
+}
+
+/// 手动补录的活动使用的应用名
+pub const MANUAL_APP_NAME: &str = "manual";
+
+/// 手动补录的结果，与已有记录重叠时仍会保存，由调用方提示用户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualEntry {
+    pub id: i64,
+    /// 与补录时间段重叠的已有记录
+    pub overlaps: Vec<Activity>,
+}
+
+/// 合并导入的记录关联的项目，新建的项目在写入前还没有 id，按名称引用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectRef {
+    Existing(i64),
+    New(String),
+}
+
+/// `Storage::save_merge` 在同一个事务中写入的数据，`projects` 为需要新建的项目
+#[derive(Debug, Clone, Default)]
+pub struct MergeBatch {
+    pub projects: Vec<Project>,
+    pub activities: Vec<(Activity, Option<ProjectRef>)>,
+    pub pomodoros: Vec<(PomodoroSession, Option<ProjectRef>)>,
+}
+
+impl MergeBatch {
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.activities.is_empty() && self.pomodoros.is_empty()
+    }
+}
+
+/// 删除项目前保存的数据，用于撤销删除
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSnapshot {
+    pub project: Project,
+    pub goals: Vec<ProjectGoal>,
+    /// 删除后会失去项目关联的番茄钟
+    pub pomodoro_ids: Vec<i64>,
+    /// 删除后会失去项目关联的活动记录
+    #[serde(default)]
+    pub activity_ids: Vec<i64>,
+    /// 随项目一起删除的项目时长目标
+    #[serde(default)]
+    pub daily_goals: Vec<Goal>,
+}
+
+/// 批量删除前统计的受影响记录数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeletionImpact {
+    pub app_usage: u64,
+    pub pomodoros: u64,
+    pub summaries: u64,
+    /// 删除项目时一并删除的项目目标和每日目标
+    pub goals: u64,
+}
+
+impl DeletionImpact {
+    /// 汇总可以从原始记录重建，不计入确认阈值
+    pub fn total(&self) -> u64 {
+        self.app_usage + self.pomodoros + self.goals
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     pub id: Option<i64>,
@@ -80,6 +396,37 @@ pub struct AppState {
     pub last_update: DateTime<Local>,
 }
 
+/// 由存储层按记录时的本地日期聚合的一天
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    pub total_time: Duration,
+    pub productive_time: Duration,
+    /// 已完成番茄钟的总时长
+    pub focus_time: Duration,
+    pub completed_pomodoros: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Activity,
+    Pomodoro,
+}
+
+/// 全文搜索的一条结果。`snippet` 中匹配的文字用 `SEARCH_HIGHLIGHT` 包围
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: SearchKind,
+    pub id: i64,
+    pub start_time: DateTime<Local>,
+    /// 活动为应用名，番茄钟为 `None`
+    pub app_name: Option<String>,
+    pub snippet: String,
+}
+
+pub const SEARCH_HIGHLIGHT: &str = "**";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailySummary {
     pub date: DateTime<Local>,
@@ -122,6 +469,23 @@ pub enum ExportFormat {
     CSV,
     JSON,
     Excel,
+    HTML,
+    Markdown,
+    /// iCalendar（.ics），每条记录导出为一个日历事件
+    ICal,
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::CSV => write!(f, "CSV"),
+            ExportFormat::JSON => write!(f, "JSON"),
+            ExportFormat::Excel => write!(f, "Excel"),
+            ExportFormat::HTML => write!(f, "HTML"),
+            ExportFormat::Markdown => write!(f, "Markdown"),
+            ExportFormat::ICal => write!(f, "iCalendar"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -145,6 +509,29 @@ pub struct PomodoroStats {
     pub total_time: i64,
     pub average_duration: i64,
     pub completion_rate: f64,
+    /// 按项目统计，未关联项目的会话（包括休息）不计入
+    pub per_project: HashMap<i64, ProjectPomodoroStats>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectPomodoroStats {
+    pub completed: u32,
+    pub interrupted: u32,
+    /// 已完成会话的总时长
+    pub focus_time: Duration,
+}
+
+impl ProjectPomodoroStats {
+    fn record(&mut self, session: &PomodoroSession) {
+        match session.status {
+            PomodoroStatus::Completed => {
+                self.completed += 1;
+                self.focus_time += session.duration;
+            }
+            PomodoroStatus::Interrupted => self.interrupted += 1,
+            _ => {}
+        }
+    }
 }
 
 impl ProductivityStats {
@@ -217,12 +604,44 @@ impl PomodoroStats {
             0.0
         };
         
+        let mut per_project: HashMap<i64, ProjectPomodoroStats> = HashMap::new();
+        for session in sessions {
+            if let Some(project_id) = session.project_id {
+                per_project.entry(project_id).or_default().record(session);
+            }
+        }
+
         Self {
             total_sessions,
             completed_sessions,
             total_time,
             average_duration,
             completion_rate,
+            per_project,
+        }
+    }
+
+    /// 只统计指定项目的会话
+    pub fn calculate_project(sessions: &[PomodoroSession], project_id: i64) -> ProjectPomodoroStats {
+        let mut stats = ProjectPomodoroStats::default();
+        for session in sessions.iter().filter(|s| s.project_id == Some(project_id)) {
+            stats.record(session);
+        }
+        stats
+    }
+
+    /// 每天完成的番茄数，指定项目时只统计该项目
+    pub fn daily_completed(sessions: &[PomodoroSession], project_id: Option<i64>) -> BTreeMap<NaiveDate, u32> {
+        let mut daily = BTreeMap::new();
+        for session in sessions {
+            if session.status != PomodoroStatus::Completed {
+                continue;
+            }
+            if project_id.map_or(false, |id| session.project_id != Some(id)) {
+                continue;
+            }
+            *daily.entry(session.start_time.date_naive()).or_insert(0) += 1;
         }
+        daily
     }
 } 
\ No newline at end of file
@@ -4,6 +4,15 @@ use crate::core::error::AppResult;
 use chrono::{DateTime, Local};
 use crate::domain::config::AppConfig;
 
+/// 分批读取的 app_usage 记录，由 `Storage::stream_app_usage` 返回
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait AppUsageBatches: Send {
+    /// 读取下一批记录，全部读完后返回 `None`
+    async fn next_batch(&mut self) -> AppResult<Option<Vec<AppUsageRecord>>>;
+}
+
+#[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait Storage: Send + Sync {
     async fn initialize(&self) -> AppResult<()>;
@@ -11,18 +20,65 @@ pub trait Storage: Send + Sync {
     // 配置相关
     async fn get_config(&self) -> AppResult<Option<AppConfig>>;
     async fn save_config(&self, config: &AppConfig) -> AppResult<()>;
+
+    /// 用备份替换当前数据，替换前为当前数据做安全备份
+    async fn restore_from_backup(&self, path: &std::path::Path) -> AppResult<()>;
+    /// 批量写入前按存储设置创建安全备份，未启用时返回 `None`
+    async fn safety_backup(&self) -> AppResult<Option<std::path::PathBuf>>;
+
+    /// 重新计算范围内原始记录有变化的日期的每日汇总，返回重算的天数
+    async fn rebuild_daily_summaries(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<u64>;
+    /// 在数据库中按天聚合，只返回有记录的日期，按日期升序
+    async fn get_daily_usage(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<DailyUsage>>;
+    /// 在窗口标题和番茄钟备注中搜索，最近的在前
+    async fn search(&self, query: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<SearchHit>>;
     
     // 活动相关
     async fn save_activity(&self, activity: &Activity) -> AppResult<i64>;
+    /// 在同一个事务中保存，任一条失败时全部回滚
+    async fn save_activities(&self, activities: &[Activity]) -> AppResult<Vec<i64>>;
+    /// 在一个事务中新建项目并写入合并的活动和番茄钟，任何一条失败时全部回滚
+    async fn save_merge(&self, batch: &MergeBatch) -> AppResult<()>;
+    /// 在同一个事务中写入应用使用记录，返回新记录的 id
+    async fn add_app_usage_batch(&self, records: &[AppUsageRecord]) -> AppResult<Vec<i64>>;
+    /// 按 start_time 分批读取 `[start, end)` 内的 app_usage，读取方持有自己的连接，
+    /// 导出和统计很长的时间段时不需要一次把所有记录读进内存
+    async fn stream_app_usage(&self, range: (DateTime<Local>, DateTime<Local>), batch_size: u32) -> AppResult<Box<dyn AppUsageBatches>>;
+    /// 保存手动补录的活动，结束时间必须晚于开始时间；与已有记录重叠时照常保存并返回重叠的记录
+    async fn add_manual_activity(&self, activity: Activity) -> AppResult<ManualEntry>;
     async fn get_activity(&self, id: i64) -> AppResult<Activity>;
     async fn list_activities(&self) -> AppResult<Vec<Activity>>;
     async fn get_activities(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>>;
     async fn get_project_activities(&self, project_id: i64, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>>;
+    /// 标签不区分大小写，开头的 # 可省略
+    async fn get_activities_by_tag(&self, tag: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<Activity>>;
+    async fn update_activity(&self, activity: &Activity) -> AppResult<()>;
+    async fn delete_activity(&self, id: i64) -> AppResult<()>;
+    /// 在 `at` 处把一条记录拆成首尾相接的两条，返回 (前半段, 后半段)
+    async fn split_activity(&self, id: i64, at: DateTime<Local>) -> AppResult<(Activity, Activity)>;
+    /// 在同一个事务中更新活动并写入对应的审计记录，任一条失败时全部回滚
+    async fn update_activities_with_audit(&self, changes: &[(Activity, ActivityAudit)]) -> AppResult<()>;
     
     // 项目相关
     async fn save_project(&self, project: &Project) -> AppResult<i64>;
     async fn get_project(&self, id: i64) -> AppResult<Project>;
     async fn list_projects(&self) -> AppResult<Vec<Project>>;
+    async fn save_project_goal(&self, goal: &ProjectGoal) -> AppResult<i64>;
+    async fn get_project_goal(&self, project_id: i64, period: GoalPeriod) -> AppResult<Option<ProjectGoal>>;
+    async fn snapshot_project(&self, project_id: i64) -> AppResult<ProjectSnapshot>;
+    /// 按原 id 重新创建已删除的项目并恢复目标和番茄钟关联
+    async fn restore_project(&self, snapshot: &ProjectSnapshot) -> AppResult<()>;
+    /// 统计删除项目时会被删除或解除关联的记录
+    async fn count_project_data(&self, project_id: i64) -> AppResult<DeletionImpact>;
+    /// 删除项目并解除活动和番茄钟的关联，超过确认阈值时需传入 `confirmed`。
+    /// 返回实际受影响的记录数和删除前的安全备份
+    async fn delete_project(&self, project_id: i64, confirmed: bool) -> AppResult<(DeletionImpact, Option<std::path::PathBuf>)>;
+
+    // 每日目标
+    /// 没有 id 时新建，否则更新，返回目标 id
+    async fn save_goal(&self, goal: &Goal) -> AppResult<i64>;
+    async fn list_goals(&self) -> AppResult<Vec<Goal>>;
+    async fn delete_goal(&self, id: i64) -> AppResult<()>;
     
     // 番茄钟相关
     async fn save_pomodoro(&self, pomodoro: &PomodoroSession) -> AppResult<i64>;
@@ -30,6 +86,10 @@ pub trait Storage: Send + Sync {
     async fn list_pomodoros(&self) -> AppResult<Vec<PomodoroSession>>;
     async fn get_pomodoro_sessions(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<PomodoroSession>>;
     async fn get_project_pomodoro_sessions(&self, project_id: i64, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<PomodoroSession>>;
+    async fn get_pomodoros_by_tag(&self, tag: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<PomodoroSession>>;
+
+    // 标签相关
+    async fn list_tags(&self) -> AppResult<Vec<Tag>>;
 }
 
 #[async_trait]
@@ -46,8 +106,28 @@ pub trait PomodoroTimer {
     async fn pause_session(&self) -> AppResult<()>;
     async fn resume_session(&self) -> AppResult<()>;
     async fn stop_session(&self) -> AppResult<()>;
+    /// 立即结束当前阶段并进入下一阶段，不记为中断
+    async fn skip_phase(&self) -> AppResult<()>;
+    /// 推迟等待开始的休息，到期后再次提醒
+    async fn snooze_break(&self) -> AppResult<()>;
+    /// 延长当前阶段，只影响本阶段
+    async fn extend_phase(&self, extra: std::time::Duration) -> AppResult<()>;
     async fn get_current_session(&self) -> AppResult<Option<PomodoroSession>>;
     async fn is_active(&self) -> AppResult<bool>;
+    /// 当前阶段结束后的下一个阶段（Work / ShortBreak / LongBreak）
+    async fn next_phase(&self) -> AppResult<PomodoroStatus>;
+    /// 当前阶段已进行的时长，不含暂停时间
+    async fn elapsed(&self) -> AppResult<std::time::Duration>;
+    /// 当前阶段的计划时长，没有进行中的阶段时为 None
+    async fn current_phase_duration(&self) -> AppResult<Option<std::time::Duration>>;
+    /// 已完成的工作番茄数
+    async fn completed_count(&self) -> AppResult<u32>;
+    /// 当前阶段的开始时间，暂停不会改变它，不能用来直接推算已进行时长；没有进行中的阶段时为 None
+    async fn phase_started_at(&self) -> AppResult<Option<DateTime<Local>>>;
+    /// 当前阶段的剩余时长，由 `elapsed` 计算，暂停期间保持不变；没有进行中的阶段时为零
+    async fn get_remaining_time(&self) -> AppResult<std::time::Duration>;
+    /// 切换到命名预设，从下一阶段开始生效
+    async fn apply_preset(&self, name: &str) -> AppResult<()>;
 }
 
 #[async_trait]
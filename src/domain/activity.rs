@@ -1,25 +1,500 @@
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use chrono::{DateTime, Local};
-use crate::core::{AppResult, models::*};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use crate::core::{AppError, AppResult, models::*};
 use crate::core::traits::*;
+use crate::domain::config::ConfigManager;
+use crate::domain::rules::{apply_title_rules, get_app_category, CategoryRule, TitleRule};
+use crate::infrastructure::platform::{PlatformOperations, WindowInfo};
+use crate::plugins::traits::{PluginEvent, PluginEventSender};
+
+/// 整理时间线的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineOptions {
+    /// 相邻活动间小于该值的空隙由前一个活动延长填补，为零时不填补
+    pub fill_gaps_under: chrono::Duration,
+    /// 起止时间四舍五入到整分钟
+    pub round_to_minute: bool,
+    /// 只返回调整结果，不写入
+    pub preview: bool,
+}
+
+impl Default for TimelineOptions {
+    fn default() -> Self {
+        Self {
+            fill_gaps_under: chrono::Duration::minutes(2),
+            round_to_minute: true,
+            preview: false,
+        }
+    }
+}
+
+fn round_to_minute(time: DateTime<Local>) -> DateTime<Local> {
+    let truncated = time
+        .with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time);
+    if time.second() >= 30 {
+        truncated + chrono::Duration::minutes(1)
+    } else {
+        truncated
+    }
+}
+
+/// 返回需要调整的活动（已更新起止时间和时长），进行中的活动不参与。
+/// 先取整再填补空隙，对结果再次执行不会产生变化
+pub fn normalize_activities(activities: &[Activity], opts: &TimelineOptions) -> Vec<Activity> {
+    let mut timeline: Vec<(Activity, DateTime<Local>, DateTime<Local>)> = activities
+        .iter()
+        .filter_map(|activity| activity.end_time.map(|end| (activity.clone(), activity.start_time, end)))
+        .collect();
+    timeline.sort_by_key(|(_, start, _)| *start);
+
+    if opts.round_to_minute {
+        for i in 0..timeline.len() {
+            let (start, end) = (timeline[i].1, timeline[i].2);
+            let (rounded_start, rounded_end) = (round_to_minute(start), round_to_minute(end));
+            if rounded_end > rounded_start {
+                timeline[i].1 = rounded_start;
+                timeline[i].2 = rounded_end;
+                continue;
+            }
+            // 不足一分钟的记录保持原样，避免取整后时长为零；
+            // 上一条的结束时间取整后可能越过它的开始，此时从上一条的结束处开始
+            let Some(previous_end) = i.checked_sub(1).map(|p| timeline[p].2) else { continue };
+            if previous_end > start {
+                if previous_end < end {
+                    timeline[i].1 = previous_end;
+                } else {
+                    // 整条都被覆盖时改为截短上一条，保留这条记录
+                    timeline[i - 1].2 = start;
+                }
+            }
+        }
+    }
+
+    if opts.fill_gaps_under > chrono::Duration::zero() {
+        for i in 1..timeline.len() {
+            let next_start = timeline[i].1;
+            let end = &mut timeline[i - 1].2;
+            let gap = next_start - *end;
+            if gap > chrono::Duration::zero() && gap < opts.fill_gaps_under {
+                *end = next_start;
+            }
+        }
+    }
+
+    timeline
+        .into_iter()
+        .filter(|(activity, start, end)| activity.start_time != *start || activity.end_time != Some(*end))
+        .map(|(mut activity, start, end)| {
+            activity.start_time = start;
+            activity.end_time = Some(end);
+            activity.duration = (end - start).to_std().unwrap_or_default();
+            activity
+        })
+        .collect()
+}
 
 pub struct ActivityManager {
     storage: Arc<dyn Storage + Send + Sync>,
     current_activity: Arc<RwLock<Option<Activity>>>,
+    enabled: Arc<RwLock<bool>>,
+    config_manager: Option<Arc<dyn ConfigManager>>,
+    title_rules: Arc<RwLock<Vec<TitleRule>>>,
+    category_rules: Arc<RwLock<Vec<CategoryRule>>>,
+    platform: Option<Arc<dyn PlatformOperations>>,
+    idle_threshold: Arc<RwLock<Duration>>,
+    /// 空闲期间暂停的活动，用户返回后以它为模板开始新的活动
+    idle_paused: Arc<RwLock<Option<Activity>>>,
+    plugin_events: Option<PluginEventSender>,
+    /// 后台记录时结束的活动发送到这里，由接收方批量保存
+    records: Option<mpsc::Sender<Activity>>,
 }
 
+/// 后台记录循环中未保存记录的上限，超过时记录循环等待保存
+const RECORD_CHANNEL_CAPACITY: usize = 256;
+
 impl ActivityManager {
     pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
         Self {
             storage,
             current_activity: Arc::new(RwLock::new(None)),
+            enabled: Arc::new(RwLock::new(true)),
+            config_manager: None,
+            title_rules: Arc::new(RwLock::new(Vec::new())),
+            category_rules: Arc::new(RwLock::new(Vec::new())),
+            platform: None,
+            idle_threshold: Arc::new(RwLock::new(Duration::ZERO)),
+            idle_paused: Arc::new(RwLock::new(None)),
+            plugin_events: None,
+            records: None,
         }
     }
 
-    async fn start_activity(&self, activity: Activity) -> AppResult<()> {
-        let mut current = self.current_activity.write().await;
-        *current = Some(activity);
+    /// 用于读取系统空闲时间
+    pub fn with_platform(mut self, platform: Arc<dyn PlatformOperations>) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// 活动结束保存后向插件广播
+    pub fn with_plugin_events(mut self, sender: PluginEventSender) -> Self {
+        self.plugin_events = Some(sender);
+        self
+    }
+
+    pub fn with_idle_threshold(mut self, threshold: Duration) -> Self {
+        self.idle_threshold = Arc::new(RwLock::new(threshold));
+        self
+    }
+
+    pub fn with_title_rules(mut self, rules: Vec<TitleRule>) -> Self {
+        self.title_rules = Arc::new(RwLock::new(rules));
+        self
+    }
+
+    pub async fn set_title_rules(&self, rules: Vec<TitleRule>) {
+        *self.title_rules.write().await = rules;
+    }
+
+    pub fn with_category_rules(mut self, rules: Vec<CategoryRule>) -> Self {
+        self.category_rules = Arc::new(RwLock::new(rules));
+        self
+    }
+
+    pub async fn set_category_rules(&self, rules: Vec<CategoryRule>) {
+        *self.category_rules.write().await = rules;
+    }
+
+    /// 从配置中读取记录开关，并在切换时写回配置
+    pub async fn with_config_manager(mut self, config_manager: Arc<dyn ConfigManager>) -> AppResult<Self> {
+        let config = config_manager.get_config().await?;
+        *self.enabled.write().await = config.tracking.enabled;
+        *self.idle_threshold.write().await = config.tracking.idle_threshold;
+        // 无效的规则跳过，不影响其余规则
+        let rules = config
+            .tracking
+            .title_rules
+            .iter()
+            .filter_map(|rule| {
+                TitleRule::new(&rule.pattern, rule.template.clone())
+                    .map_err(|e| log::warn!("{}", e))
+                    .ok()
+            })
+            .collect();
+        self.set_title_rules(rules).await;
+        let rules = config
+            .tracking
+            .category_rules
+            .iter()
+            .filter_map(|rule| {
+                CategoryRule::new(&rule.pattern, rule.category.clone())
+                    .map_err(|e| log::warn!("{}", e))
+                    .ok()
+            })
+            .collect();
+        self.set_category_rules(rules).await;
+        self.config_manager = Some(config_manager);
+        Ok(self)
+    }
+
+    /// 不等待锁读取当前活动，供崩溃处理等同步场景使用
+    pub fn try_current_activity(&self) -> Option<Activity> {
+        self.current_activity.try_read().ok()?.clone()
+    }
+
+    pub async fn is_enabled(&self) -> bool {
+        *self.enabled.read().await
+    }
+
+    /// 全局暂停或恢复记录。暂停时结束并保存当前活动，恢复后从新活动开始
+    pub async fn set_enabled(&self, enabled: bool) -> AppResult<()> {
+        {
+            let mut current = self.enabled.write().await;
+            if *current == enabled {
+                return Ok(());
+            }
+            *current = enabled;
+        }
+
+        if !enabled {
+            self.close_current_activity().await?;
+        }
+
+        if let Some(config_manager) = &self.config_manager {
+            let mut config = config_manager.get_config().await?;
+            config.tracking.enabled = enabled;
+            config_manager.update_config(config).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn toggle_enabled(&self) -> AppResult<bool> {
+        let enabled = !self.is_enabled().await;
+        self.set_enabled(enabled).await?;
+        Ok(enabled)
+    }
+
+    /// 在后台按间隔读取前台窗口并记录，结束的活动通过返回的通道发出，不直接写入存储
+    pub fn spawn(mut self, poll_interval: Duration) -> (ActivityTrackerHandle, mpsc::Receiver<Activity>) {
+        let (sender, receiver) = mpsc::channel(RECORD_CHANNEL_CAPACITY);
+        self.records = Some(sender);
+        let manager = Arc::new(self);
+        let paused = Arc::new(AtomicBool::new(false));
+
+        let tracker = Arc::clone(&manager);
+        let tracker_paused = Arc::clone(&paused);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                if tracker_paused.load(Ordering::SeqCst) {
+                    continue;
+                }
+                if let Err(e) = tracker.poll().await {
+                    log::debug!("读取前台窗口失败: {}", e);
+                }
+            }
+        });
+
+        let tracker = ActivityTrackerHandle {
+            manager,
+            paused,
+            handle: Mutex::new(Some(handle)),
+        };
+        (tracker, receiver)
+    }
+
+    /// 检查空闲状态后读取一次前台窗口
+    pub async fn poll(&self) -> AppResult<()> {
+        if self.check_idle().await? {
+            return Ok(());
+        }
+        let Some(platform) = &self.platform else {
+            return Ok(());
+        };
+        let window = platform.get_active_window()?;
+        self.start_activity(activity_from_window(window, Local::now())).await
+    }
+
+    /// 由记录循环定期调用，返回当前是否处于空闲状态。
+    /// 空闲超过阈值时在空闲开始时刻结束当前活动，用户返回后开始新的活动
+    pub async fn check_idle(&self) -> AppResult<bool> {
+        let threshold = *self.idle_threshold.read().await;
+        let Some(platform) = &self.platform else {
+            return Ok(false);
+        };
+        if threshold.is_zero() {
+            return Ok(false);
+        }
+        // 不支持读取空闲时间的平台按始终活跃处理
+        let idle = match platform.get_system_idle_time() {
+            Ok(idle) => idle,
+            Err(_) => return Ok(false),
+        };
+
+        let now = Local::now();
+        if idle >= threshold {
+            if self.idle_paused.read().await.is_none() {
+                let idle_since = now - chrono::Duration::from_std(idle).unwrap_or_else(|_| chrono::Duration::zero());
+                self.handle_idle_period(idle_since).await?;
+            }
+            return Ok(true);
+        }
+
+        let paused = self.idle_paused.write().await.take();
+        if let Some(mut activity) = paused {
+            activity.id = None;
+            activity.start_time = now;
+            activity.end_time = None;
+            activity.duration = Duration::ZERO;
+            self.start_activity(activity).await?;
+        }
+        Ok(false)
+    }
+
+    async fn handle_idle_period(&self, idle_since: DateTime<Local>) -> AppResult<()> {
+        let Some(current) = self.get_current_activity().await else {
+            return Ok(());
+        };
+        *self.idle_paused.write().await = Some(current);
+        self.close_current_activity_at(idle_since).await
+    }
+
+    /// 在空闲开始的时刻结束并保存当前活动，丢弃之后的空闲时间
+    pub async fn finalize_with_idle(&self, idle_since: Instant) -> AppResult<()> {
+        let idle_for = chrono::Duration::from_std(idle_since.elapsed()).unwrap_or_else(|_| chrono::Duration::zero());
+        self.close_current_activity_at(Local::now() - idle_for).await
+    }
+
+    async fn close_current_activity(&self) -> AppResult<()> {
+        self.close_current_activity_at(Local::now()).await
+    }
+
+    /// 在指定时间结束并保存当前活动，例如系统休眠时不把休眠时间计入活动
+    pub async fn close_current_activity_at(&self, end: DateTime<Local>) -> AppResult<()> {
+        let activity = self.current_activity.write().await.take();
+        if let Some(activity) = activity {
+            self.save_finished(activity, end).await?;
+        }
+        Ok(())
+    }
+
+    async fn save_finished(&self, mut activity: Activity, end: DateTime<Local>) -> AppResult<()> {
+        let end = end.max(activity.start_time);
+        activity.end_time = Some(end);
+        activity.duration = elapsed(activity.start_time, end);
+        if let Some(records) = &self.records {
+            return records
+                .send(activity)
+                .await
+                .map_err(|_| AppError::System("活动记录通道已关闭".into()));
+        }
+        activity.id = Some(self.storage.save_activity(&activity).await?);
+        if let Some(sender) = &self.plugin_events {
+            let _ = sender.send(PluginEvent::ActivityRecorded(activity));
+        }
+        Ok(())
+    }
+
+    /// 手动调整已记录活动的结束时间，并写入审计记录
+    pub async fn adjust_duration(&self, id: i64, delta: chrono::Duration) -> AppResult<Activity> {
+        let mut activity = self.storage.get_activity(id).await?;
+        let original_end = activity
+            .end_time
+            .ok_or_else(|| AppError::InvalidOperation("进行中的活动不能调整时长".into()))?;
+
+        let new_end = original_end + delta;
+        if new_end <= activity.start_time {
+            return Err(AppError::InvalidOperation("调整后的时长必须大于0".into()));
+        }
+
+        // 多取一天以覆盖跨越新结束时间的活动
+        let neighbours = self
+            .storage
+            .get_activities(activity.start_time - chrono::Duration::days(1), new_end + chrono::Duration::days(1))
+            .await?;
+        if let Some(other) = find_overlap(&activity, new_end, &neighbours) {
+            return Err(AppError::InvalidOperation(format!(
+                "调整后与活动「{}」时间重叠",
+                other.name
+            )));
+        }
+
+        let audit = ActivityAudit {
+            id: None,
+            activity_id: id,
+            original_start_time: activity.start_time,
+            new_start_time: activity.start_time,
+            original_end_time: activity.end_time,
+            original_duration: activity.duration,
+            new_end_time: new_end,
+            new_duration: (new_end - activity.start_time).to_std().unwrap_or_default(),
+            adjusted_at: Local::now(),
+        };
+
+        activity.end_time = Some(new_end);
+        activity.duration = audit.new_duration;
+        self.storage
+            .update_activities_with_audit(&[(activity.clone(), audit)])
+            .await?;
+
+        Ok(activity)
+    }
+
+    /// 整理某一天的时间线：填补小空隙并将起止时间取整，每条调整都写入审计记录
+    pub async fn normalize_timeline(&self, date: NaiveDate, opts: &TimelineOptions) -> AppResult<Vec<Activity>> {
+        let start = date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap_or_else(Local::now);
+        let activities: Vec<Activity> = self
+            .storage
+            .get_activities(start, start + chrono::Duration::days(1))
+            .await?
+            .into_iter()
+            .filter(|activity| activity.local_date() == date)
+            .collect();
+
+        let changed = normalize_activities(&activities, opts);
+        if opts.preview {
+            return Ok(changed);
+        }
+
+        let adjusted_at = Local::now();
+        let changes: Vec<(Activity, ActivityAudit)> = changed
+            .iter()
+            .filter_map(|activity| {
+                let id = activity.id?;
+                let original = activities.iter().find(|a| a.id == Some(id))?;
+                let audit = ActivityAudit {
+                    id: None,
+                    activity_id: id,
+                    original_start_time: original.start_time,
+                    new_start_time: activity.start_time,
+                    original_end_time: original.end_time,
+                    original_duration: original.duration,
+                    new_end_time: activity.end_time.unwrap_or(activity.start_time),
+                    new_duration: activity.duration,
+                    adjusted_at,
+                };
+                Some((activity.clone(), audit))
+            })
+            .collect();
+        // 整理一天的记录要么全部生效要么全部不变
+        self.storage.update_activities_with_audit(&changes).await?;
+        Ok(changed)
+    }
+
+    /// 手动补录活动，未指定类别和标签时使用所属项目的默认值；返回值中包含与之重叠的已有记录
+    pub async fn add_manual_entry(&self, mut activity: Activity) -> AppResult<ManualEntry> {
+        activity.utc_offset.get_or_insert_with(current_utc_offset);
+        if let Some(project_id) = activity.project_id {
+            let project = self.storage.get_project(project_id).await?;
+            if let Some(category) = project.resolve_category(Some(activity.category.clone())) {
+                activity.category = category;
+            }
+            activity.tags = project.resolve_tags(std::mem::take(&mut activity.tags));
+        }
+        self.storage.add_manual_activity(activity).await
+    }
+
+    async fn start_activity(&self, mut activity: Activity) -> AppResult<()> {
+        // 暂停期间不记录
+        if !self.is_enabled().await {
+            return Ok(());
+        }
+        activity.utc_offset.get_or_insert_with(current_utc_offset);
+        if let Some(title) = apply_title_rules(&self.title_rules.read().await, &activity.window_title) {
+            activity.name = title;
+        }
+        if let Some(category) = get_app_category(&self.category_rules.read().await, &activity.app_name) {
+            activity.category = category.to_string();
+        }
+        let switched_at = activity.start_time;
+        let previous = {
+            let mut current = self.current_activity.write().await;
+            if let Some(existing) = current.as_mut() {
+                // 窗口没有变化时只延长当前活动，不产生新的记录
+                if existing.app_name == activity.app_name && existing.window_title == activity.window_title {
+                    existing.duration = elapsed(existing.start_time, switched_at);
+                    return Ok(());
+                }
+            }
+            current.replace(activity)
+        };
+        // 切换到其他窗口时才保存上一个活动
+        if let Some(previous) = previous {
+            self.save_finished(previous, switched_at).await?;
+        }
         Ok(())
     }
 
@@ -45,6 +520,135 @@ impl ActivityManager {
     }
 }
 
+/// 后台记录循环的句柄，丢弃时停止循环
+pub struct ActivityTrackerHandle {
+    manager: Arc<ActivityManager>,
+    paused: Arc<AtomicBool>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ActivityTrackerHandle {
+    pub fn manager(&self) -> &Arc<ActivityManager> {
+        &self.manager
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// 暂停读取前台窗口，并结束当前活动
+    pub async fn pause(&self) -> AppResult<()> {
+        self.paused.store(true, Ordering::SeqCst);
+        self.manager.close_current_activity().await
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// 停止记录循环并发出当前活动；句柄和管理器都释放后接收方收到通道关闭
+    pub async fn stop(&self) -> AppResult<()> {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+        self.manager.close_current_activity().await
+    }
+}
+
+impl Drop for ActivityTrackerHandle {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+/// 接收后台记录循环发出的活动，累积到 `max_records` 条或每隔 `flush_interval` 批量写入，
+/// 通道关闭时写入剩余记录后返回。写入失败的记录保留到下次重试
+pub async fn persist_records(
+    storage: Arc<dyn Storage + Send + Sync>,
+    mut records: mpsc::Receiver<Activity>,
+    plugin_events: Option<PluginEventSender>,
+    flush_interval: Duration,
+    max_records: usize,
+) {
+    let mut pending = Vec::new();
+    let mut interval = tokio::time::interval(flush_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            received = records.recv() => match received {
+                Some(activity) => {
+                    pending.push(activity);
+                    if pending.len() < max_records.max(1) {
+                        continue;
+                    }
+                }
+                None => break,
+            },
+            _ = interval.tick() => {}
+        }
+        flush_records(&storage, &mut pending, plugin_events.as_ref()).await;
+    }
+    flush_records(&storage, &mut pending, plugin_events.as_ref()).await;
+}
+
+async fn flush_records(
+    storage: &Arc<dyn Storage + Send + Sync>,
+    pending: &mut Vec<Activity>,
+    plugin_events: Option<&PluginEventSender>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    // 走完整的活动写入路径，保留标题规则生成的名称、生产性、标签和计费等字段
+    match storage.save_activities(pending).await {
+        Ok(ids) => {
+            for (mut activity, id) in pending.drain(..).zip(ids) {
+                activity.id = Some(id);
+                if let Some(sender) = plugin_events {
+                    let _ = sender.send(PluginEvent::ActivityRecorded(activity));
+                }
+            }
+        }
+        Err(e) => log::error!("保存 {} 条活动记录失败: {}", pending.len(), e),
+    }
+}
+
+fn activity_from_window(window: WindowInfo, now: DateTime<Local>) -> Activity {
+    Activity {
+        id: None,
+        name: window.window_title.clone(),
+        start_time: now,
+        end_time: None,
+        project_id: None,
+        description: None,
+        duration: Duration::ZERO,
+        category: String::new(),
+        is_productive: false,
+        app_name: window.app_name,
+        window_title: window.window_title,
+        is_billable: None,
+        tags: Vec::new(),
+        utc_offset: None,
+        manual: false,
+    }
+}
+
+fn elapsed(start: DateTime<Local>, end: DateTime<Local>) -> Duration {
+    end.signed_duration_since(start).to_std().unwrap_or_default()
+}
+
+fn find_overlap<'a>(activity: &Activity, new_end: DateTime<Local>, others: &'a [Activity]) -> Option<&'a Activity> {
+    others.iter().find(|other| {
+        if other.id.is_some() && other.id == activity.id {
+            return false;
+        }
+        let other_end = other.end_time.unwrap_or_else(Local::now);
+        other.start_time < new_end && other_end > activity.start_time
+    })
+}
+
 #[async_trait::async_trait]
 impl TimeTracker for ActivityManager {
     async fn start_tracking(&self, activity: Activity) -> AppResult<()> {
@@ -79,8 +683,519 @@ impl ActivityService for ActivityManager {
 mod tests {
     use super::*;
 
+    fn activity(name: &str) -> Activity {
+        Activity {
+            id: None,
+            name: name.into(),
+            start_time: Local::now(),
+            end_time: None,
+            project_id: None,
+            description: None,
+            duration: std::time::Duration::ZERO,
+            category: "work".into(),
+            is_productive: true,
+            app_name: name.into(),
+            window_title: String::new(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
     #[tokio::test]
     async fn test_activity_manager() {
         // TODO: 添加测试用例
     }
-} 
\ No newline at end of file
+
+    fn recorded(id: i64, name: &str, start: DateTime<Local>, minutes: i64) -> Activity {
+        let mut activity = activity(name);
+        activity.id = Some(id);
+        activity.start_time = start;
+        activity.end_time = Some(start + chrono::Duration::minutes(minutes));
+        activity.duration = std::time::Duration::from_secs(minutes as u64 * 60);
+        activity
+    }
+
+    fn adjust_storage(update_times: usize) -> MockStorage {
+        let start = Local::now() - chrono::Duration::hours(3);
+        let target = recorded(1, "editor", start, 60);
+        let next = recorded(2, "browser", start + chrono::Duration::minutes(65), 30);
+
+        let mut mock_storage = MockStorage::new();
+        let returned = target.clone();
+        mock_storage
+            .expect_get_activity()
+            .returning(move |_| Ok(returned.clone()));
+        mock_storage
+            .expect_get_activities()
+            .returning(move |_, _| Ok(vec![target.clone(), next.clone()]));
+        mock_storage
+            .expect_update_activities_with_audit()
+            .withf(|changes| {
+                changes.len() == 1 && changes[0].1.original_duration == std::time::Duration::from_secs(3600)
+            })
+            .times(update_times)
+            .returning(|_| Ok(()));
+        mock_storage
+    }
+
+    #[tokio::test]
+    async fn test_adjust_duration() -> AppResult<()> {
+        let manager = ActivityManager::new(Arc::new(adjust_storage(1)));
+
+        let adjusted = manager.adjust_duration(1, chrono::Duration::minutes(3)).await?;
+        assert_eq!(adjusted.duration, std::time::Duration::from_secs(63 * 60));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_adjust_duration_rejects_overlap() {
+        let manager = ActivityManager::new(Arc::new(adjust_storage(0)));
+
+        assert!(manager.adjust_duration(1, chrono::Duration::minutes(10)).await.is_err());
+        assert!(manager.adjust_duration(1, chrono::Duration::minutes(-90)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_toggle_tracking() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        // 关闭记录时保存当前活动，且只保存一次
+        mock_storage
+            .expect_save_activity()
+            .withf(|a| a.name == "editor" && a.end_time.is_some())
+            .times(1)
+            .returning(|_| Ok(1));
+
+        let manager = ActivityManager::new(Arc::new(mock_storage));
+        manager.start_tracking(activity("editor")).await?;
+        assert!(manager.is_tracking().await?);
+
+        manager.set_enabled(false).await?;
+        assert!(!manager.is_tracking().await?);
+
+        manager.start_tracking(activity("browser")).await?;
+        assert!(!manager.is_tracking().await?);
+
+        manager.set_enabled(true).await?;
+        manager.start_tracking(activity("terminal")).await?;
+        let current = TimeTracker::get_current_activity(&manager).await?;
+        assert_eq!(current.map(|a| a.name), Some("terminal".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tracking_enabled_is_read_and_persisted_through_config() -> AppResult<()> {
+        use crate::domain::config::{AppConfig, ConfigManagerImpl};
+
+        // 上次关闭记录后重启，启动时仍保持关闭
+        let mut config = AppConfig::default();
+        config.tracking.enabled = false;
+        let mut config_storage = MockStorage::new();
+        config_storage
+            .expect_save_config()
+            .withf(|config| config.tracking.enabled)
+            .times(1)
+            .returning(|_| Ok(()));
+        let config_manager = Arc::new(ConfigManagerImpl::with_config(Arc::new(config_storage), config));
+
+        let manager = ActivityManager::new(Arc::new(MockStorage::new()))
+            .with_config_manager(config_manager.clone())
+            .await?;
+        assert!(!manager.is_enabled().await);
+        manager.start_tracking(activity("editor")).await?;
+        assert!(!manager.is_tracking().await?);
+
+        assert!(manager.toggle_enabled().await?);
+        assert!(config_manager.get_config().await?.tracking.enabled);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recorded_activity_sent_to_plugins() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_activity().returning(|_| Ok(7));
+        let (sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let manager = ActivityManager::new(Arc::new(mock_storage)).with_plugin_events(sender);
+
+        manager.start_tracking(activity("editor")).await?;
+        assert!(events.try_recv().is_err());
+
+        manager.close_current_activity_at(Local::now()).await?;
+        assert!(matches!(
+            events.try_recv(),
+            Ok(PluginEvent::ActivityRecorded(activity)) if activity.id == Some(7) && activity.name == "editor"
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_title_rules_applied_before_storing() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_activity()
+            .withf(|a| a.name == "main.rs" && a.window_title == "main.rs - Visual Studio Code")
+            .times(1)
+            .returning(|_| Ok(1));
+
+        let manager = ActivityManager::new(Arc::new(mock_storage))
+            .with_title_rules(vec![TitleRule::new(r"^(.*) - Visual Studio Code$", "$1")?]);
+        let mut editor = activity("code");
+        editor.window_title = "main.rs - Visual Studio Code".into();
+        manager.start_tracking(editor).await?;
+        manager.set_enabled(false).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_same_window_updates_are_merged() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_activity()
+            .withf(|a| a.name == "editor" && a.duration == std::time::Duration::from_secs(10 * 60))
+            .times(1)
+            .returning(|_| Ok(1));
+        let manager = ActivityManager::new(Arc::new(mock_storage));
+
+        let start = Local::now() - chrono::Duration::minutes(20);
+        for minute in 0..10 {
+            let mut editor = activity("editor");
+            editor.start_time = start + chrono::Duration::minutes(minute);
+            manager.start_tracking(editor).await?;
+        }
+        let current = TimeTracker::get_current_activity(&manager).await?.unwrap();
+        assert_eq!(current.start_time, start);
+        assert_eq!(current.duration, std::time::Duration::from_secs(9 * 60));
+
+        // 切换窗口时保存合并后的单条记录
+        let mut browser = activity("browser");
+        browser.start_time = start + chrono::Duration::minutes(10);
+        manager.start_tracking(browser).await?;
+        assert_eq!(
+            TimeTracker::get_current_activity(&manager).await?.map(|a| a.name),
+            Some("browser".to_string())
+        );
+
+        Ok(())
+    }
+
+    struct IdlePlatform(std::sync::Mutex<Duration>);
+
+    impl IdlePlatform {
+        fn set_idle(&self, idle: Duration) {
+            *self.0.lock().unwrap() = idle;
+        }
+    }
+
+    impl PlatformOperations for IdlePlatform {
+        fn get_active_window(&self) -> AppResult<crate::infrastructure::platform::WindowInfo> {
+            Err(AppError::System("not available in tests".into()))
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+
+        fn get_system_idle_time(&self) -> AppResult<Duration> {
+            Ok(*self.0.lock().unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idle_time_is_not_counted() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        // 空闲的 10 分钟不计入，只保存一次
+        mock_storage
+            .expect_save_activity()
+            .withf(|a| {
+                a.name == "editor"
+                    && a.duration >= Duration::from_secs(20 * 60 - 5)
+                    && a.duration <= Duration::from_secs(20 * 60 + 5)
+            })
+            .times(1)
+            .returning(|_| Ok(1));
+
+        let platform = Arc::new(IdlePlatform(std::sync::Mutex::new(Duration::ZERO)));
+        let manager = ActivityManager::new(Arc::new(mock_storage))
+            .with_platform(platform.clone())
+            .with_idle_threshold(Duration::from_secs(5 * 60));
+        let mut editor = activity("editor");
+        editor.start_time = Local::now() - chrono::Duration::minutes(30);
+        manager.start_tracking(editor).await?;
+
+        assert!(!manager.check_idle().await?);
+        assert!(manager.is_tracking().await?);
+
+        platform.set_idle(Duration::from_secs(10 * 60));
+        assert!(manager.check_idle().await?);
+        assert!(!manager.is_tracking().await?);
+        // 持续空闲不会重复保存
+        assert!(manager.check_idle().await?);
+
+        // 用户返回后从新的活动开始
+        platform.set_idle(Duration::from_secs(1));
+        assert!(!manager.check_idle().await?);
+        let current = TimeTracker::get_current_activity(&manager).await?.unwrap();
+        assert_eq!(current.name, "editor");
+        assert!(current.start_time > Local::now() - chrono::Duration::seconds(5));
+        assert_eq!(current.duration, Duration::ZERO);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_finalize_with_idle_discards_idle_tail() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_activity()
+            .withf(|a| {
+                a.name == "editor"
+                    && a.duration >= Duration::from_secs(6 * 60 - 5)
+                    && a.duration <= Duration::from_secs(6 * 60 + 5)
+            })
+            .times(1)
+            .returning(|_| Ok(1));
+        let manager = ActivityManager::new(Arc::new(mock_storage));
+        let mut editor = activity("editor");
+        editor.start_time = Local::now() - chrono::Duration::minutes(10);
+        manager.start_tracking(editor).await?;
+
+        manager.finalize_with_idle(Instant::now() - Duration::from_secs(4 * 60)).await?;
+        assert!(!manager.is_tracking().await?);
+        Ok(())
+    }
+
+    fn timed(id: i64, start: DateTime<Local>, seconds: i64) -> Activity {
+        let mut activity = activity("editor");
+        activity.id = Some(id);
+        activity.start_time = start;
+        activity.end_time = Some(start + chrono::Duration::seconds(seconds));
+        activity.duration = std::time::Duration::from_secs(seconds as u64);
+        activity
+    }
+
+    fn nine() -> DateTime<Local> {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fill_small_gaps() {
+        let opts = TimelineOptions {
+            round_to_minute: false,
+            ..TimelineOptions::default()
+        };
+        let activities = vec![
+            timed(1, nine(), 600),
+            // 9:11 开始，与上一条相隔 1 分钟
+            timed(2, nine() + chrono::Duration::minutes(11), 600),
+            // 相隔 10 分钟，超过阈值
+            timed(3, nine() + chrono::Duration::minutes(31), 600),
+        ];
+
+        let changed = normalize_activities(&activities, &opts);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].id, Some(1));
+        assert_eq!(changed[0].end_time, Some(nine() + chrono::Duration::minutes(11)));
+        assert_eq!(changed[0].duration, std::time::Duration::from_secs(11 * 60));
+    }
+
+    #[test]
+    fn test_round_boundaries_is_idempotent() {
+        let opts = TimelineOptions::default();
+        let activities = vec![
+            // 9:00:40 - 9:10:20 取整为 9:01 - 9:10
+            timed(1, nine() + chrono::Duration::seconds(40), 580),
+            // 9:11:10 开始，取整为 9:11，与上一条的空隙被填补
+            timed(2, nine() + chrono::Duration::seconds(11 * 60 + 10), 600),
+            // 不足一分钟的记录不取整
+            timed(3, nine() + chrono::Duration::seconds(30 * 60 + 10), 15),
+        ];
+
+        let changed = normalize_activities(&activities, &opts);
+        let first = changed.iter().find(|a| a.id == Some(1)).unwrap();
+        assert_eq!(first.start_time, nine() + chrono::Duration::minutes(1));
+        assert_eq!(first.end_time, Some(nine() + chrono::Duration::minutes(11)));
+        let second = changed.iter().find(|a| a.id == Some(2)).unwrap();
+        assert_eq!(second.start_time, nine() + chrono::Duration::minutes(11));
+        assert_eq!(second.end_time, Some(nine() + chrono::Duration::minutes(21)));
+        assert!(changed.iter().all(|a| a.id != Some(3)));
+
+        // 对整理后的结果再次执行不产生变化
+        let mut normalized = activities.clone();
+        for activity in &changed {
+            let index = normalized.iter().position(|a| a.id == activity.id).unwrap();
+            normalized[index] = activity.clone();
+        }
+        assert!(normalize_activities(&normalized, &opts).is_empty());
+    }
+
+    #[test]
+    fn test_short_record_is_clamped_to_previous_end() {
+        let opts = TimelineOptions {
+            fill_gaps_under: chrono::Duration::zero(),
+            ..TimelineOptions::default()
+        };
+        let activities = vec![
+            // 9:00:00 - 9:05:35 取整为 9:00 - 9:06
+            timed(1, nine(), 335),
+            // 9:05:40 - 9:05:55 不足一分钟，不取整
+            timed(2, nine() + chrono::Duration::seconds(340), 15),
+        ];
+
+        let changed = normalize_activities(&activities, &opts);
+        let first = changed.iter().find(|a| a.id == Some(1)).unwrap();
+        assert_eq!(first.end_time, Some(nine() + chrono::Duration::seconds(340)));
+        assert!(changed.iter().all(|a| a.id != Some(2)));
+
+        let activities = vec![
+            timed(1, nine(), 335),
+            // 9:05:37 - 9:06:07，不足一分钟，上一条取整后的结束时间落在其中
+            timed(2, nine() + chrono::Duration::seconds(337), 30),
+        ];
+        let changed = normalize_activities(&activities, &opts);
+        let second = changed.iter().find(|a| a.id == Some(2)).unwrap();
+        assert_eq!(second.start_time, nine() + chrono::Duration::minutes(6));
+        assert_eq!(second.end_time, Some(nine() + chrono::Duration::seconds(367)));
+    }
+
+    #[tokio::test]
+    async fn test_normalize_timeline_preview_and_write() -> AppResult<()> {
+        let activities = vec![timed(1, nine(), 590), timed(2, nine() + chrono::Duration::minutes(11), 600)];
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_activities()
+            .returning(move |_, _| Ok(activities.clone()));
+        mock_storage
+            .expect_update_activities_with_audit()
+            .withf(|changes| {
+                let [(activity, audit)] = changes else { return false };
+                activity.id == Some(1)
+                    && activity.duration == std::time::Duration::from_secs(11 * 60)
+                    && audit.activity_id == 1
+                    && audit.original_duration == std::time::Duration::from_secs(590)
+                    && audit.original_start_time == nine()
+                    && audit.new_start_time == nine()
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+        let manager = ActivityManager::new(Arc::new(mock_storage));
+        let date = nine().date_naive();
+
+        // 预览不写入
+        let preview = manager
+            .normalize_timeline(date, &TimelineOptions { preview: true, ..TimelineOptions::default() })
+            .await?;
+        assert_eq!(preview.len(), 1);
+
+        let written = manager.normalize_timeline(date, &TimelineOptions::default()).await?;
+        assert_eq!(
+            written.iter().map(|a| (a.id, a.end_time)).collect::<Vec<_>>(),
+            preview.iter().map(|a| (a.id, a.end_time)).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    struct WindowPlatform(std::sync::Mutex<String>);
+
+    impl PlatformOperations for WindowPlatform {
+        fn get_active_window(&self) -> AppResult<WindowInfo> {
+            let app_name = self.0.lock().unwrap().clone();
+            Ok(WindowInfo {
+                title: app_name.clone(),
+                process_name: app_name.clone(),
+                process_id: 1,
+                window_title: format!("{} window", app_name),
+                app_name,
+            })
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawned_tracker_emits_finished_records() -> AppResult<()> {
+        // 后台记录时不直接写入存储
+        let platform = Arc::new(WindowPlatform(std::sync::Mutex::new("editor".into())));
+        let (tracker, mut records) = ActivityManager::new(Arc::new(MockStorage::new()))
+            .with_platform(platform.clone())
+            .spawn(Duration::from_millis(10));
+        let timeout = Duration::from_secs(5);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(records.try_recv().is_err());
+        *platform.0.lock().unwrap() = "browser".into();
+        let editor = tokio::time::timeout(timeout, records.recv()).await.unwrap().unwrap();
+        assert_eq!((editor.app_name.as_str(), editor.window_title.as_str()), ("editor", "editor window"));
+        assert!(editor.end_time.is_some() && editor.id.is_none());
+
+        // 暂停时结束当前活动，之后不再记录
+        tracker.pause().await?;
+        let browser = records.recv().await.unwrap();
+        assert_eq!(browser.app_name, "browser");
+        *platform.0.lock().unwrap() = "terminal".into();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!tracker.manager().is_tracking().await?);
+
+        tracker.resume();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tracker.stop().await?;
+        let terminal = records.recv().await.unwrap();
+        assert_eq!(terminal.app_name, "terminal");
+        drop(tracker);
+        assert!(tokio::time::timeout(timeout, records.recv()).await.unwrap().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_persist_records_flushes_in_batches() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        let mut sequence = mockall::Sequence::new();
+        mock_storage
+            .expect_save_activities()
+            .withf(|activities| activities.len() == 2 && activities[0].app_name == "editor")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(vec![1, 2]));
+        // 通道关闭时写入剩余不足一批的记录
+        mock_storage
+            .expect_save_activities()
+            .withf(|activities| activities.len() == 1 && activities[0].category == "work")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(vec![3]));
+        let (sender, records) = mpsc::channel(8);
+        let (events, mut received) = tokio::sync::mpsc::unbounded_channel();
+
+        for name in ["editor", "browser", "terminal"] {
+            sender.send(activity(name)).await.unwrap();
+        }
+        drop(sender);
+        persist_records(Arc::new(mock_storage), records, Some(events), Duration::from_secs(3600), 2).await;
+
+        let mut ids = Vec::new();
+        while let Ok(PluginEvent::ActivityRecorded(activity)) = received.try_recv() {
+            ids.push(activity.id);
+        }
+        assert_eq!(ids, vec![Some(1), Some(2), Some(3)]);
+        Ok(())
+    }
+}
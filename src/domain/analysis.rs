@@ -1,14 +1,922 @@
 use std::sync::Arc;
-use chrono::{DateTime, Local, Datelike};
+use chrono::{DateTime, Local, Datelike, Timelike};
 use crate::core::{AppResult, models::*, traits::*};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 应用使用记录的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GroupBy {
+    #[default]
+    App,
+    Category,
+    /// 不分组，逐条显示
+    Raw,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageGroup {
+    pub key: String,
+    pub total_time: std::time::Duration,
+    pub count: usize,
+}
+
+/// 按指定方式分组，结果按总时长降序排列
+pub fn group_activities(activities: &[Activity], by: GroupBy) -> Vec<UsageGroup> {
+    let mut groups: Vec<UsageGroup> = match by {
+        GroupBy::Raw => activities
+            .iter()
+            .map(|a| UsageGroup {
+                key: format!("{} - {}", a.app_name, a.window_title),
+                total_time: a.duration,
+                count: 1,
+            })
+            .collect(),
+        GroupBy::App | GroupBy::Category => {
+            let mut map: HashMap<&str, UsageGroup> = HashMap::new();
+            for activity in activities {
+                let key = if by == GroupBy::App {
+                    activity.app_name.as_str()
+                } else {
+                    activity.category.as_str()
+                };
+                let group = map.entry(key).or_insert_with(|| UsageGroup {
+                    key: key.to_string(),
+                    total_time: std::time::Duration::ZERO,
+                    count: 0,
+                });
+                group.total_time += activity.duration;
+                group.count += 1;
+            }
+            map.into_values().collect()
+        }
+    };
+
+    groups.sort_by(|a, b| b.total_time.cmp(&a.total_time).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
+
+/// 按类别权重计算的生产率（0-1）。未配置权重的类别按活动是否生产性计为 1 或 0，
+/// 因此 `weights` 为空时与二元统计一致
+pub fn weighted_productivity_score(activities: &[Activity], weights: &BTreeMap<String, f64>) -> f64 {
+    let mut total = 0.0;
+    let mut weighted = 0.0;
+    for activity in activities {
+        let seconds = activity.duration.as_secs_f64();
+        let weight = category_weight(weights, &activity.category)
+            .unwrap_or(if activity.is_productive { 1.0 } else { 0.0 });
+        total += seconds;
+        weighted += seconds * weight;
+    }
+    if total == 0.0 {
+        0.0
+    } else {
+        weighted / total
+    }
+}
+
+/// 类别名不区分大小写，权重限制在 0 到 1 之间
+fn category_weight(weights: &BTreeMap<String, f64>, category: &str) -> Option<f64> {
+    weights
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(category))
+        .map(|(_, weight)| weight.clamp(0.0, 1.0))
+}
+
+/// 概览页所需的全部数据，一次查询返回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub date: chrono::NaiveDate,
+    /// 当天完成的工作番茄总时长
+    pub focus_time: std::time::Duration,
+    pub completed_pomodoros: usize,
+    pub interrupted_pomodoros: usize,
+    pub top_project: Option<(String, std::time::Duration)>,
+    pub top_app: Option<(String, std::time::Duration)>,
+    pub top_apps: Vec<UsageGroup>,
+    /// 当天最近的番茄钟，最新的在前
+    pub recent_pomodoros: Vec<PomodoroSession>,
+    /// 生产性时长占比（0-100），配置了类别权重时按权重计算
+    pub productivity_score: f64,
+    /// 截至当天连续完成番茄钟的天数
+    pub current_streak: u32,
+}
+
+/// 概览页显示的最近番茄钟数量
+const RECENT_POMODOROS: usize = 5;
+
+/// `group_usage` 每批读取的 app_usage 行数
+const USAGE_BATCH_SIZE: u32 = 5000;
+
+/// 计算连续天数时向前查询的最大范围
+const STREAK_LOOKBACK_DAYS: i64 = 365;
+
+/// 时区偏移的最大可能差值，按记录时的偏移分日前需要扩大查询范围
+const MAX_OFFSET_HOURS: i64 = 14;
+
+/// 按记录时的本地日期汇总活动时长，不受之后时区或夏令时变化影响
+pub fn daily_totals(activities: &[Activity]) -> std::collections::BTreeMap<chrono::NaiveDate, std::time::Duration> {
+    let mut totals = std::collections::BTreeMap::new();
+    for activity in activities {
+        *totals.entry(activity.local_date()).or_default() += activity.duration;
+    }
+    totals
+}
+
+fn day_bounds(date: chrono::NaiveDate) -> (DateTime<Local>, DateTime<Local>) {
+    let start = date.and_hms_opt(0, 0, 0).unwrap().and_local_timezone(Local).earliest().unwrap_or_else(Local::now);
+    let end = date.and_hms_opt(23, 59, 59).unwrap().and_local_timezone(Local).latest().unwrap_or_else(Local::now);
+    (start, end)
+}
+
+impl Dashboard {
+    /// `weights` 为类别生产率权重，为空时按活动是否生产性计分
+    pub fn calculate(
+        date: chrono::NaiveDate,
+        activities: &[Activity],
+        pomodoro_history: &[PomodoroSession],
+        projects: &[Project],
+        weights: &BTreeMap<String, f64>,
+    ) -> Self {
+        let today: Vec<_> = pomodoro_history
+            .iter()
+            .filter(|p| p.local_date() == date)
+            .collect();
+
+        let completed: Vec<_> = today
+            .iter()
+            .filter(|p| p.status == PomodoroStatus::Completed)
+            .collect();
+
+        let productivity_score = weighted_productivity_score(activities, weights) * 100.0;
+
+        let mut project_time: HashMap<i64, std::time::Duration> = HashMap::new();
+        for activity in activities {
+            if let Some(project_id) = activity.project_id {
+                *project_time.entry(project_id).or_default() += activity.duration;
+            }
+        }
+        let top_project = project_time
+            .into_iter()
+            .max_by_key(|(_, duration)| *duration)
+            .and_then(|(id, duration)| {
+                projects
+                    .iter()
+                    .find(|p| p.id == Some(id))
+                    .map(|p| (p.name.clone(), duration))
+            });
+
+        let mut top_apps = group_activities(activities, GroupBy::App);
+        top_apps.truncate(REPORT_TOP_N);
+        let top_app = top_apps.first().map(|g| (g.key.clone(), g.total_time));
+
+        let mut recent_pomodoros: Vec<PomodoroSession> = today.iter().copied().cloned().collect();
+        recent_pomodoros.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+        recent_pomodoros.truncate(RECENT_POMODOROS);
+
+        Self {
+            date,
+            focus_time: completed.iter().map(|p| p.duration).sum(),
+            completed_pomodoros: completed.len(),
+            interrupted_pomodoros: today
+                .iter()
+                .filter(|p| p.status == PomodoroStatus::Interrupted)
+                .count(),
+            top_project,
+            top_app,
+            top_apps,
+            recent_pomodoros,
+            productivity_score,
+            current_streak: Self::streak(date, pomodoro_history),
+        }
+    }
+
+    /// 当天尚未完成番茄钟时从前一天开始计算，避免早上打开应用时连续天数归零
+    fn streak(date: chrono::NaiveDate, history: &[PomodoroSession]) -> u32 {
+        let days: std::collections::HashSet<chrono::NaiveDate> = history
+            .iter()
+            .filter(|p| p.status == PomodoroStatus::Completed)
+            .map(|p| p.local_date())
+            .collect();
+
+        let mut day = if days.contains(&date) { date } else { date - chrono::Duration::days(1) };
+        let mut streak = 0;
+        while days.contains(&day) {
+            streak += 1;
+            day -= chrono::Duration::days(1);
+        }
+        streak
+    }
+}
+
+/// 每天至少完成一定数量番茄钟的连续天数，类似习惯打卡
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreakInfo {
+    pub current: u32,
+    pub longest: u32,
+    /// 最近一次中断连续的日期，即未达标的那一天
+    pub last_broken: Option<chrono::NaiveDate>,
+}
+
+impl StreakInfo {
+    /// `days` 为按记录时本地日期聚合的数据，缺少的日期视为中断；
+    /// 当天尚未达标时不算中断，从前一天开始计算
+    pub fn calculate(days: &[DailyUsage], min_pomodoros: u32, today: chrono::NaiveDate) -> Self {
+        let min_pomodoros = min_pomodoros.max(1);
+        let mut qualified: Vec<chrono::NaiveDate> = days
+            .iter()
+            .filter(|day| day.completed_pomodoros >= min_pomodoros && day.date <= today)
+            .map(|day| day.date)
+            .collect();
+        qualified.sort();
+        qualified.dedup();
+
+        // 连续达标的区间 (开始, 结束)
+        let mut runs: Vec<(chrono::NaiveDate, chrono::NaiveDate)> = Vec::new();
+        for date in qualified {
+            match runs.last_mut() {
+                Some((_, end)) if *end + chrono::Duration::days(1) == date => *end = date,
+                _ => runs.push((date, date)),
+            }
+        }
+
+        let length = |(start, end): (chrono::NaiveDate, chrono::NaiveDate)| ((end - start).num_days() + 1) as u32;
+        let yesterday = today - chrono::Duration::days(1);
+        let ongoing = runs.last().copied().filter(|(_, end)| *end >= yesterday);
+        let last_broken = runs
+            .iter()
+            .rev()
+            .find(|run| Some(**run) != ongoing)
+            .map(|(_, end)| *end + chrono::Duration::days(1));
+
+        Self {
+            current: ongoing.map_or(0, length),
+            longest: runs.iter().copied().map(length).max().unwrap_or(0),
+            last_broken,
+        }
+    }
+}
+
+/// 报表中排行榜显示的条目数
+pub const REPORT_TOP_N: usize = 5;
+
+/// 任意时间段的分析报表，供命令行和导出使用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub total_time: std::time::Duration,
+    pub productive_time: std::time::Duration,
+    /// 生产性时长占比（0-100），配置了类别权重时按权重计算
+    pub productivity_score: f64,
+    pub focus_time: std::time::Duration,
+    pub completed_pomodoros: usize,
+    pub interrupted_pomodoros: usize,
+    /// 按 `by` 分组的时长分布
+    pub breakdown: Vec<UsageGroup>,
+    pub top_apps: Vec<UsageGroup>,
+    pub top_projects: Vec<UsageGroup>,
+    /// 截至时间段结束时连续完成番茄钟的天数
+    pub current_streak: u32,
+}
+
+impl Report {
+    /// `pomodoro_history` 需覆盖计算连续天数所需的范围，统计时只取时间段内的记录
+    pub fn calculate(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        by: GroupBy,
+        activities: &[Activity],
+        pomodoro_history: &[PomodoroSession],
+        projects: &[Project],
+        weights: &BTreeMap<String, f64>,
+    ) -> Self {
+        let pomodoros: Vec<_> = pomodoro_history
+            .iter()
+            .filter(|p| p.start_time >= start && p.start_time <= end)
+            .collect();
+        let completed: Vec<_> = pomodoros
+            .iter()
+            .filter(|p| p.status == PomodoroStatus::Completed)
+            .collect();
+
+        let total_time: std::time::Duration = activities.iter().map(|a| a.duration).sum();
+        let productive_time: std::time::Duration = activities
+            .iter()
+            .filter(|a| a.is_productive)
+            .map(|a| a.duration)
+            .sum();
+        let productivity_score = weighted_productivity_score(activities, weights) * 100.0;
+
+        let mut top_apps = group_activities(activities, GroupBy::App);
+        top_apps.truncate(REPORT_TOP_N);
+
+        let mut project_groups: HashMap<i64, UsageGroup> = HashMap::new();
+        for activity in activities {
+            let Some(project) = activity
+                .project_id
+                .and_then(|id| projects.iter().find(|p| p.id == Some(id)))
+            else {
+                continue;
+            };
+            let group = project_groups.entry(project.id.unwrap_or_default()).or_insert_with(|| UsageGroup {
+                key: project.name.clone(),
+                total_time: std::time::Duration::ZERO,
+                count: 0,
+            });
+            group.total_time += activity.duration;
+            group.count += 1;
+        }
+        let mut top_projects: Vec<_> = project_groups.into_values().collect();
+        top_projects.sort_by(|a, b| b.total_time.cmp(&a.total_time).then_with(|| a.key.cmp(&b.key)));
+        top_projects.truncate(REPORT_TOP_N);
+
+        Self {
+            start,
+            end,
+            total_time,
+            productive_time,
+            productivity_score,
+            focus_time: completed.iter().map(|p| p.duration).sum(),
+            completed_pomodoros: completed.len(),
+            interrupted_pomodoros: pomodoros
+                .iter()
+                .filter(|p| p.status == PomodoroStatus::Interrupted)
+                .count(),
+            breakdown: group_activities(activities, by),
+            top_apps,
+            top_projects,
+            current_streak: Dashboard::streak(end.date_naive(), pomodoro_history),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_time.is_zero() && self.completed_pomodoros == 0 && self.interrupted_pomodoros == 0
+    }
+}
+
+/// 汇总的时间粒度，周从周一开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// `date` 所在周期的第一天
+    pub fn period_start(self, date: chrono::NaiveDate) -> chrono::NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64),
+            Granularity::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// 一个周期的汇总，周期被查询范围截断时只统计范围内的日期
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub period_start: chrono::NaiveDate,
+    pub days: u32,
+    pub total_time: std::time::Duration,
+    pub focus_time: std::time::Duration,
+    pub completed_pomodoros: u32,
+    /// 生产性时长占比（0-1），没有记录时为 0
+    pub productivity_ratio: f64,
+    /// 记录时长最多的一天
+    pub busiest_day: Option<(chrono::NaiveDate, std::time::Duration)>,
+    /// 周一到周日的平均记录时长，没有记录的日期按 0 计入
+    pub weekday_average: [std::time::Duration; 7],
+}
+
+/// 把按天聚合的数据汇总到周期，`range` 为包含首尾的日期范围
+pub fn rollup(days: &[DailyUsage], range: (chrono::NaiveDate, chrono::NaiveDate), granularity: Granularity) -> Vec<Rollup> {
+    let by_date: HashMap<chrono::NaiveDate, &DailyUsage> = days.iter().map(|day| (day.date, day)).collect();
+    let mut rollups: Vec<Rollup> = Vec::new();
+    let mut weekday_counts = [0u32; 7];
+    let mut weekday_totals = [std::time::Duration::ZERO; 7];
+    let mut productive = std::time::Duration::ZERO;
+
+    let finish = |rollup: &mut Rollup, counts: &[u32; 7], totals: &[std::time::Duration; 7], productive: std::time::Duration| {
+        for weekday in 0..7 {
+            if counts[weekday] > 0 {
+                rollup.weekday_average[weekday] = totals[weekday] / counts[weekday];
+            }
+        }
+        if !rollup.total_time.is_zero() {
+            rollup.productivity_ratio = productive.as_secs_f64() / rollup.total_time.as_secs_f64();
+        }
+    };
+
+    let mut date = range.0;
+    while date <= range.1 {
+        let period_start = granularity.period_start(date);
+        if rollups.last().map(|r| r.period_start) != Some(period_start) {
+            if let Some(last) = rollups.last_mut() {
+                finish(last, &weekday_counts, &weekday_totals, productive);
+            }
+            weekday_counts = [0; 7];
+            weekday_totals = [std::time::Duration::ZERO; 7];
+            productive = std::time::Duration::ZERO;
+            rollups.push(Rollup {
+                period_start,
+                days: 0,
+                total_time: std::time::Duration::ZERO,
+                focus_time: std::time::Duration::ZERO,
+                completed_pomodoros: 0,
+                productivity_ratio: 0.0,
+                busiest_day: None,
+                weekday_average: [std::time::Duration::ZERO; 7],
+            });
+        }
+
+        let current = rollups.last_mut().unwrap();
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        current.days += 1;
+        weekday_counts[weekday] += 1;
+        if let Some(day) = by_date.get(&date) {
+            current.total_time += day.total_time;
+            current.focus_time += day.focus_time;
+            current.completed_pomodoros += day.completed_pomodoros;
+            productive += day.productive_time;
+            weekday_totals[weekday] += day.total_time;
+            if !day.total_time.is_zero() && current.busiest_day.map_or(true, |(_, busiest)| day.total_time > busiest) {
+                current.busiest_day = Some((date, day.total_time));
+            }
+        }
+        date += chrono::Duration::days(1);
+    }
+    if let Some(last) = rollups.last_mut() {
+        finish(last, &weekday_counts, &weekday_totals, productive);
+    }
+    rollups
+}
+
+/// 专注度时间线的统计粒度
+pub const FOCUS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// 计算单个时间段的专注度（0-1），可替换为自定义实现
+pub trait FocusScorer: Send + Sync {
+    /// `segments` 为时间段内按开始时间排序的活动片段
+    fn score(&self, segments: &[FocusSegment], interval: std::time::Duration) -> f32;
+}
+
+/// 裁剪到某个时间段内的活动片段
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusSegment {
+    pub app_name: String,
+    pub category: String,
+    pub is_productive: bool,
+    pub duration: std::time::Duration,
+}
+
+/// 默认评分：未记录的时间视为空闲，非生产性类别和频繁切换应用都会降低分数
+#[derive(Debug, Clone)]
+pub struct DefaultFocusScorer {
+    /// 每次切换应用的扣分系数
+    pub switch_penalty: f32,
+    /// 非生产性时间的权重
+    pub unproductive_weight: f32,
+    /// 配置了权重的类别按权重计分，优先于 `unproductive_weight`
+    pub category_weights: BTreeMap<String, f64>,
+}
+
+impl Default for DefaultFocusScorer {
+    fn default() -> Self {
+        Self {
+            switch_penalty: 0.15,
+            unproductive_weight: 0.3,
+            category_weights: BTreeMap::new(),
+        }
+    }
+}
+
+impl FocusScorer for DefaultFocusScorer {
+    fn score(&self, segments: &[FocusSegment], interval: std::time::Duration) -> f32 {
+        if interval.is_zero() {
+            return 0.0;
+        }
+
+        let weighted: f32 = segments
+            .iter()
+            .map(|s| {
+                let weight = match category_weight(&self.category_weights, &s.category) {
+                    Some(weight) => weight as f32,
+                    None if s.is_productive => 1.0,
+                    None => self.unproductive_weight,
+                };
+                s.duration.as_secs_f32() * weight
+            })
+            .sum();
+        let activity = (weighted / interval.as_secs_f32()).min(1.0);
+
+        let switches = segments
+            .windows(2)
+            .filter(|pair| pair[0].app_name != pair[1].app_name)
+            .count();
+
+        activity / (1.0 + self.switch_penalty * switches as f32)
+    }
+}
+
+/// 将活动切分到固定时间段并逐段评分，只返回有活动记录的时间段
+pub fn focus_timeline_for(
+    activities: &[Activity],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    interval: std::time::Duration,
+    scorer: &dyn FocusScorer,
+) -> Vec<(DateTime<Local>, f32)> {
+    let Ok(step) = chrono::Duration::from_std(interval) else {
+        return Vec::new();
+    };
+    if step <= chrono::Duration::zero() {
+        return Vec::new();
+    }
+
+    let mut sorted: Vec<&Activity> = activities.iter().collect();
+    sorted.sort_by_key(|a| a.start_time);
+
+    let mut timeline = Vec::new();
+    let mut bucket_start = start;
+    while bucket_start < end {
+        let bucket_end = (bucket_start + step).min(end);
+        let segments: Vec<FocusSegment> = sorted
+            .iter()
+            .filter_map(|a| {
+                let activity_end = a
+                    .end_time
+                    .unwrap_or_else(|| a.start_time + chrono::Duration::from_std(a.duration).unwrap_or_default());
+                let overlap = (activity_end.min(bucket_end) - a.start_time.max(bucket_start)).to_std().ok()?;
+                (!overlap.is_zero()).then(|| FocusSegment {
+                    app_name: a.app_name.clone(),
+                    category: a.category.clone(),
+                    is_productive: a.is_productive,
+                    duration: overlap,
+                })
+            })
+            .collect();
+
+        if !segments.is_empty() {
+            timeline.push((bucket_start, scorer.score(&segments, interval)));
+        }
+        bucket_start = bucket_end;
+    }
+    timeline
+}
+
+/// 多标签的记录计入每个标签；标签表中不存在的名称使用临时标签
+pub fn aggregate_tags(
+    activities: &[Activity],
+    pomodoros: &[PomodoroSession],
+    tags: &[Tag],
+) -> Vec<(Tag, std::time::Duration, u32)> {
+    let records = activities
+        .iter()
+        .map(|a| (&a.tags, a.duration))
+        .chain(pomodoros.iter().map(|p| (&p.tags, p.duration)));
+
+    let mut totals: HashMap<&str, (std::time::Duration, u32)> = HashMap::new();
+    for (record_tags, duration) in records {
+        // 同一记录中重复的标签只计一次
+        let unique: std::collections::HashSet<&str> = record_tags.iter().map(String::as_str).collect();
+        for tag in unique {
+            let entry = totals.entry(tag).or_default();
+            entry.0 += duration;
+            entry.1 += 1;
+        }
+    }
+
+    let mut stats: Vec<_> = totals
+        .into_iter()
+        .map(|(name, (duration, count))| {
+            let tag = tags.iter().find(|t| t.name == name).cloned().unwrap_or_else(|| Tag {
+                id: None,
+                name: name.to_string(),
+                color: String::new(),
+            });
+            (tag, duration, count)
+        })
+        .collect();
+    stats.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+    stats
+}
+
+/// 每日目标在某一天的进度，时长类目标的 `current`/`target` 为秒数，番茄钟目标为个数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyGoalProgress {
+    pub goal: Goal,
+    pub date: chrono::NaiveDate,
+    pub current: u64,
+    pub target: u64,
+    pub met: bool,
+}
+
+impl DailyGoalProgress {
+    pub fn new(goal: Goal, date: chrono::NaiveDate, current: u64) -> Self {
+        Self {
+            target: goal.target,
+            met: current >= goal.target,
+            goal,
+            date,
+            current,
+        }
+    }
+
+    pub fn percent(&self) -> f32 {
+        if self.target == 0 {
+            return 100.0;
+        }
+        (self.current as f32 / self.target as f32 * 100.0).min(100.0)
+    }
+}
+
+/// 按 星期（周一为 0）× 小时 统计的工作分钟数
+pub type Heatmap = Vec<Vec<u32>>;
+
+/// 把活动和番茄钟的时间段合并后按小时拆分到 7×24 的格子中，
+/// 同一时间既有活动又有番茄钟时只计一次，进行中的番茄钟不计入
+pub fn heatmap_for(
+    activities: &[Activity],
+    pomodoros: &[PomodoroSession],
+    range: (DateTime<Local>, DateTime<Local>),
+) -> Heatmap {
+    let mut spans: Vec<(DateTime<Local>, DateTime<Local>)> = activities
+        .iter()
+        .map(|a| {
+            let end = a
+                .end_time
+                .unwrap_or_else(|| a.start_time + chrono::Duration::from_std(a.duration).unwrap_or_default());
+            (a.start_time, end)
+        })
+        .chain(pomodoros.iter().filter_map(|p| p.end_time.map(|end| (p.start_time, end))))
+        .map(|(start, end)| (start.max(range.0), end.min(range.1)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    spans.sort();
+
+    let mut merged: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let mut seconds = vec![vec![0i64; 24]; 7];
+    for (start, end) in merged {
+        let mut cursor = start;
+        while cursor < end {
+            // 跨越整点时拆到相邻的小时
+            let hour_start = cursor
+                .with_minute(0)
+                .and_then(|t| t.with_second(0))
+                .and_then(|t| t.with_nanosecond(0))
+                .unwrap_or(cursor);
+            let next = (hour_start + chrono::Duration::hours(1)).min(end);
+            if next <= cursor {
+                break;
+            }
+            seconds[cursor.weekday().num_days_from_monday() as usize][cursor.hour() as usize] +=
+                (next - cursor).num_seconds();
+            cursor = next;
+        }
+    }
+    seconds
+        .into_iter()
+        .map(|day| day.into_iter().map(|s| (s / 60) as u32).collect())
+        .collect()
+}
 
 pub struct AnalysisManager {
     storage: Arc<dyn Storage + Send + Sync>,
+    focus_scorer: Arc<dyn FocusScorer>,
+    category_weights: BTreeMap<String, f64>,
 }
 
 impl AnalysisManager {
     pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            focus_scorer: Arc::new(DefaultFocusScorer::default()),
+            category_weights: BTreeMap::new(),
+        }
+    }
+
+    /// 概览和报表的生产率按类别权重计算，同时替换默认的专注度评分
+    pub fn with_category_weights(mut self, weights: BTreeMap<String, f64>) -> Self {
+        self.focus_scorer = Arc::new(DefaultFocusScorer {
+            category_weights: weights.clone(),
+            ..DefaultFocusScorer::default()
+        });
+        self.category_weights = weights;
+        self
+    }
+
+    pub fn with_focus_scorer(mut self, scorer: Arc<dyn FocusScorer>) -> Self {
+        self.focus_scorer = scorer;
+        self
+    }
+
+    /// 按应用或类别分组时分批读取记录并累加，只在内存中保留各组的合计
+    pub async fn group_usage(&self, range: (DateTime<Local>, DateTime<Local>), by: GroupBy) -> AppResult<Vec<UsageGroup>> {
+        if by == GroupBy::Raw {
+            let activities = self.storage.get_activities(range.0, range.1).await?;
+            return Ok(group_activities(&activities, by));
+        }
+
+        let mut map: HashMap<String, UsageGroup> = HashMap::new();
+        let mut stream = self.storage.stream_app_usage(range, USAGE_BATCH_SIZE).await?;
+        while let Some(batch) = stream.next_batch().await? {
+            for record in batch {
+                let key = match by {
+                    GroupBy::Category => record.category.unwrap_or_default(),
+                    _ => record.app_name,
+                };
+                let group = map.entry(key).or_insert_with_key(|key| UsageGroup {
+                    key: key.clone(),
+                    total_time: std::time::Duration::ZERO,
+                    count: 0,
+                });
+                group.total_time += std::time::Duration::from_secs(record.duration.max(0) as u64);
+                group.count += 1;
+            }
+        }
+
+        let mut groups: Vec<UsageGroup> = map.into_values().collect();
+        groups.sort_by(|a, b| b.total_time.cmp(&a.total_time).then_with(|| a.key.cmp(&b.key)));
+        Ok(groups)
+    }
+
+    /// 概览数据：活动、番茄钟历史和项目各查询一次
+    pub async fn dashboard(&self, date: chrono::NaiveDate) -> AppResult<Dashboard> {
+        let (start, end) = day_bounds(date);
+        let history_start = day_bounds(date - chrono::Duration::days(STREAK_LOOKBACK_DAYS)).0;
+        // 其它时区记录的数据可能落在当前时区的前一天或后一天
+        let margin = chrono::Duration::hours(MAX_OFFSET_HOURS);
+
+        let activities: Vec<Activity> = self
+            .storage
+            .get_activities(start - margin, end + margin)
+            .await?
+            .into_iter()
+            .filter(|a| a.local_date() == date)
+            .collect();
+        let pomodoro_history = self.storage.get_pomodoro_sessions(history_start - margin, end + margin).await?;
+        let projects = self.storage.list_projects().await?;
+
+        Ok(Dashboard::calculate(date, &activities, &pomodoro_history, &projects, &self.category_weights))
+    }
+
+    /// 时间段内的汇总报表，活动、番茄钟历史和项目各查询一次
+    pub async fn report(&self, start: DateTime<Local>, end: DateTime<Local>, by: GroupBy) -> AppResult<Report> {
+        self.report_for(start, end, by, None).await
+    }
+
+    /// 指定项目时只统计关联该项目的活动和番茄钟
+    pub async fn report_for(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        by: GroupBy,
+        project_id: Option<i64>,
+    ) -> AppResult<Report> {
+        let history_start = start.min(day_bounds(end.date_naive() - chrono::Duration::days(STREAK_LOOKBACK_DAYS)).0);
+
+        let (activities, pomodoro_history) = match project_id {
+            Some(id) => (
+                self.storage.get_project_activities(id, start, end).await?,
+                self.storage.get_project_pomodoro_sessions(id, history_start, end).await?,
+            ),
+            None => (
+                self.storage.get_activities(start, end).await?,
+                self.storage.get_pomodoro_sessions(history_start, end).await?,
+            ),
+        };
+        let projects = self.storage.list_projects().await?;
+
+        Ok(Report::calculate(
+            start,
+            end,
+            by,
+            &activities,
+            &pomodoro_history,
+            &projects,
+            &self.category_weights,
+        ))
+    }
+
+    /// 按粒度汇总，聚合在存储层完成，不读取单条记录
+    pub async fn rollups(&self, range: (DateTime<Local>, DateTime<Local>), granularity: Granularity) -> AppResult<Vec<Rollup>> {
+        let days = self.storage.get_daily_usage(range).await?;
+        Ok(rollup(&days, (range.0.date_naive(), range.1.date_naive()), granularity))
+    }
+
+    pub async fn weekly_summary(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<Rollup>> {
+        self.rollups(range, Granularity::Week).await
+    }
+
+    pub async fn monthly_summary(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<Rollup>> {
+        self.rollups(range, Granularity::Month).await
+    }
+
+    /// 截至今天每天至少完成 `min_pomodoros` 个番茄钟的连续天数
+    pub async fn focus_streak(&self, min_pomodoros: u32) -> AppResult<StreakInfo> {
+        let now = Local::now();
+        let days = self
+            .storage
+            .get_daily_usage((DateTime::<Local>::from(std::time::UNIX_EPOCH), now + chrono::Duration::hours(MAX_OFFSET_HOURS)))
+            .await?;
+        Ok(StreakInfo::calculate(&days, min_pomodoros, now.date_naive()))
+    }
+
+    /// 目标在 `date` 当天的完成情况，不检查目标是否在当天生效
+    pub async fn goal_progress(&self, goal: &Goal, date: chrono::NaiveDate) -> AppResult<DailyGoalProgress> {
+        let (start, end) = day_bounds(date);
+        let margin = chrono::Duration::hours(MAX_OFFSET_HOURS);
+        let current = match goal.kind {
+            GoalKind::ProductiveTime | GoalKind::CompletedPomodoros => {
+                let usage = self
+                    .storage
+                    .get_daily_usage((start - margin, end + margin))
+                    .await?
+                    .into_iter()
+                    .find(|day| day.date == date);
+                match (goal.kind, usage) {
+                    (_, None) => 0,
+                    (GoalKind::CompletedPomodoros, Some(day)) => day.completed_pomodoros as u64,
+                    (_, Some(day)) => day.productive_time.as_secs(),
+                }
+            }
+            GoalKind::ProjectTime { project_id } => self
+                .storage
+                .get_project_activities(project_id, start - margin, end + margin)
+                .await?
+                .iter()
+                .filter(|activity| activity.local_date() == date)
+                .map(|activity| activity.duration.as_secs())
+                .sum(),
+        };
+        Ok(DailyGoalProgress::new(goal.clone(), date, current))
+    }
+
+    /// 在 `date` 当天生效的所有目标的进度
+    pub async fn daily_goals(&self, date: chrono::NaiveDate) -> AppResult<Vec<DailyGoalProgress>> {
+        let mut progress = Vec::new();
+        for goal in self.storage.list_goals().await? {
+            if goal.is_due_on(date) {
+                progress.push(self.goal_progress(&goal, date).await?);
+            }
+        }
+        Ok(progress)
+    }
+
+    /// 当天每 15 分钟的专注度，用于区分深度专注和碎片化的时段
+    pub async fn focus_timeline(&self, date: chrono::NaiveDate) -> AppResult<Vec<(DateTime<Local>, f32)>> {
+        let (start, end) = day_bounds(date);
+        let activities = self.storage.get_activities(start, end).await?;
+        Ok(focus_timeline_for(&activities, start, end, FOCUS_INTERVAL, self.focus_scorer.as_ref()))
+    }
+
+    /// 按标签汇总番茄钟和活动的时长与记录数，可限定到单个项目
+    pub async fn tag_stats(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        project_id: Option<i64>,
+    ) -> AppResult<Vec<(Tag, std::time::Duration, u32)>> {
+        let (activities, pomodoros) = match project_id {
+            Some(id) => (
+                self.storage.get_project_activities(id, start, end).await?,
+                self.storage.get_project_pomodoro_sessions(id, start, end).await?,
+            ),
+            None => (
+                self.storage.get_activities(start, end).await?,
+                self.storage.get_pomodoro_sessions(start, end).await?,
+            ),
+        };
+        let tags = self.storage.list_tags().await?;
+        Ok(aggregate_tags(&activities, &pomodoros, &tags))
+    }
+
+    /// 项目在范围内各星期、各小时的工作分钟数，用于热力图
+    pub async fn project_heatmap(&self, project_id: i64, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Heatmap> {
+        let activities = self.storage.get_project_activities(project_id, range.0, range.1).await?;
+        let pomodoros = self.storage.get_project_pomodoro_sessions(project_id, range.0, range.1).await?;
+        Ok(heatmap_for(&activities, &pomodoros, range))
+    }
+
+    /// 范围内带有 `tag` 的已完成番茄钟总时长
+    pub async fn tag_focus_time(&self, tag: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<std::time::Duration> {
+        Ok(self
+            .storage
+            .get_pomodoros_by_tag(tag, range)
+            .await?
+            .iter()
+            .filter(|session| session.status == PomodoroStatus::Completed)
+            .map(|session| session.duration)
+            .sum())
     }
 
     async fn calculate_project_summaries(&self, activities: &[Activity], pomodoros: &[PomodoroSession]) -> AppResult<Vec<ProjectSummary>> {
@@ -163,8 +1071,511 @@ impl AnalysisService for AnalysisManager {
 mod tests {
     use super::*;
 
+    fn activity(app_name: &str, category: &str, minutes: u64) -> Activity {
+        Activity {
+            id: None,
+            name: app_name.into(),
+            start_time: Local::now(),
+            end_time: None,
+            project_id: None,
+            description: None,
+            duration: std::time::Duration::from_secs(minutes * 60),
+            category: category.into(),
+            is_productive: true,
+            app_name: app_name.into(),
+            window_title: format!("{} window", app_name),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    fn sample() -> Vec<Activity> {
+        vec![
+            activity("code", "development", 30),
+            activity("code", "development", 20),
+            activity("terminal", "development", 15),
+            activity("browser", "research", 40),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_group_usage_streams_batches() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_stream_app_usage().times(2).returning(|_, _| {
+            let records: Vec<AppUsageRecord> = sample().iter().map(AppUsageRecord::from).collect();
+            let mut batches: std::collections::VecDeque<_> = records.chunks(3).map(<[_]>::to_vec).collect();
+            let mut stream = MockAppUsageBatches::new();
+            stream.expect_next_batch().returning(move || Ok(batches.pop_front()));
+            Ok(Box::new(stream))
+        });
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let range = (Local::now() - chrono::Duration::days(365), Local::now());
+
+        for by in [GroupBy::App, GroupBy::Category] {
+            assert_eq!(manager.group_usage(range, by).await?, group_activities(&sample(), by));
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_analysis_manager() {
         // TODO: 添加测试用例
     }
+
+    fn pomodoro(start: DateTime<Local>, status: PomodoroStatus) -> PomodoroSession {
+        PomodoroSession {
+            id: None,
+            start_time: start,
+            end_time: Some(start + chrono::Duration::minutes(25)),
+            duration: std::time::Duration::from_secs(25 * 60),
+            status,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_matches_individual_queries() -> AppResult<()> {
+        let now = Local::now();
+        let today = now.date_naive();
+        let mut activities = sample();
+        activities[0].project_id = Some(1);
+        activities[3].project_id = Some(2);
+        activities[3].is_productive = false;
+        let mut client = Project::new("Client".into(), None);
+        client.id = Some(1);
+        let mut research = Project::new("Research".into(), None);
+        research.id = Some(2);
+        let projects = vec![client, research];
+        let pomodoros = vec![
+            pomodoro(now, PomodoroStatus::Completed),
+            pomodoro(now, PomodoroStatus::Completed),
+            pomodoro(now, PomodoroStatus::Interrupted),
+            pomodoro(now - chrono::Duration::days(1), PomodoroStatus::Completed),
+            pomodoro(now - chrono::Duration::days(3), PomodoroStatus::Completed),
+        ];
+
+        let mut mock_storage = MockStorage::new();
+        let returned = activities.clone();
+        mock_storage.expect_get_activities().times(1).returning(move |_, _| Ok(returned.clone()));
+        let returned = pomodoros.clone();
+        mock_storage.expect_get_pomodoro_sessions().times(1).returning(move |_, _| Ok(returned.clone()));
+        let returned = projects.clone();
+        mock_storage.expect_list_projects().times(1).returning(move || Ok(returned.clone()));
+
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let dashboard = manager.dashboard(today).await?;
+
+        let top_app = group_activities(&activities, GroupBy::App).remove(0);
+        assert_eq!(dashboard.top_app, Some((top_app.key, top_app.total_time)));
+        assert_eq!(dashboard.top_project, Some(("Research".to_string(), std::time::Duration::from_secs(40 * 60))));
+        assert_eq!(dashboard.completed_pomodoros, 2);
+        assert_eq!(dashboard.interrupted_pomodoros, 1);
+        assert_eq!(dashboard.focus_time, std::time::Duration::from_secs(50 * 60));
+        let total: std::time::Duration = activities.iter().map(|a| a.duration).sum();
+        let productive: std::time::Duration = activities.iter().filter(|a| a.is_productive).map(|a| a.duration).sum();
+        assert!((dashboard.productivity_score - productive.as_secs_f64() / total.as_secs_f64() * 100.0).abs() < 1e-9);
+        assert_eq!(dashboard.current_streak, 2);
+
+        Ok(())
+    }
+
+    fn timed(app_name: &str, start: DateTime<Local>, minutes: i64) -> Activity {
+        let mut activity = activity(app_name, "development", minutes as u64);
+        activity.start_time = start;
+        activity.end_time = Some(start + chrono::Duration::minutes(minutes));
+        activity
+    }
+
+    #[test]
+    fn test_rapid_switching_scores_lower_than_steady_work() {
+        let start = Local::now().date_naive().and_hms_opt(9, 0, 0).unwrap().and_local_timezone(Local).unwrap();
+        let end = start + chrono::Duration::minutes(30);
+
+        let steady = vec![timed("code", start, 15)];
+        // 同样 15 分钟，但每分钟都在切换应用
+        let fragmented: Vec<Activity> = (0..15)
+            .map(|i| {
+                let app = if i % 2 == 0 { "code" } else { "chat" };
+                timed(app, start + chrono::Duration::minutes(i), 1)
+            })
+            .collect();
+
+        let scorer = DefaultFocusScorer::default();
+        let steady = focus_timeline_for(&steady, start, end, FOCUS_INTERVAL, &scorer);
+        let fragmented = focus_timeline_for(&fragmented, start, end, FOCUS_INTERVAL, &scorer);
+
+        assert_eq!(steady.len(), 1);
+        assert_eq!(fragmented.len(), 1);
+        assert_eq!(steady[0].0, start);
+        assert!((steady[0].1 - 1.0).abs() < f32::EPSILON);
+        assert!(fragmented[0].1 < steady[0].1 / 2.0);
+    }
+
+    #[test]
+    fn test_idle_and_unproductive_time_lower_focus() {
+        let start = Local::now().date_naive().and_hms_opt(14, 0, 0).unwrap().and_local_timezone(Local).unwrap();
+        let end = start + chrono::Duration::minutes(15);
+        let scorer = DefaultFocusScorer::default();
+
+        let half_idle = focus_timeline_for(&[timed("code", start, 7)], start, end, FOCUS_INTERVAL, &scorer);
+        let mut browsing = timed("video", start, 15);
+        browsing.is_productive = false;
+        let unproductive = focus_timeline_for(&[browsing], start, end, FOCUS_INTERVAL, &scorer);
+
+        assert!(half_idle[0].1 < 0.5);
+        assert!(unproductive[0].1 < 0.5);
+    }
+
+    #[test]
+    fn test_weighted_productivity_score() {
+        let mut chat = activity("slack", "communication", 30);
+        chat.is_productive = false;
+        let activities = vec![activity("code", "development", 60), chat, activity("notes", "research", 30)];
+
+        // 没有权重时与二元统计一致
+        let binary = weighted_productivity_score(&activities, &BTreeMap::new());
+        assert!((binary - 0.75).abs() < 1e-9);
+
+        let weights: BTreeMap<String, f64> =
+            [("Development".to_string(), 1.0), ("communication".to_string(), 0.5)].into_iter().collect();
+        // 60 * 1.0 + 30 * 0.5 + 30 * 1.0（未配置权重，按生产性计）
+        let weighted = weighted_productivity_score(&activities, &weights);
+        assert!((weighted - 105.0 / 120.0).abs() < 1e-9);
+        assert_eq!(weighted_productivity_score(&[], &weights), 0.0);
+
+        let start = Local::now().date_naive().and_hms_opt(10, 0, 0).unwrap().and_local_timezone(Local).unwrap();
+        let mut meeting = timed("zoom", start, 15);
+        meeting.category = "communication".into();
+        let scorer = DefaultFocusScorer {
+            category_weights: weights,
+            ..DefaultFocusScorer::default()
+        };
+        let timeline = focus_timeline_for(&[meeting], start, start + chrono::Duration::minutes(15), FOCUS_INTERVAL, &scorer);
+        assert!((timeline[0].1 - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_records_bucket_by_recorded_offset() {
+        use chrono::{FixedOffset, TimeZone};
+
+        // 东京时间 1 月 2 日 01:00 记录，即 UTC 1 月 1 日 16:00
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let recorded = tokyo.with_ymd_and_hms(2024, 1, 2, 1, 0, 0).unwrap();
+        let mut in_tokyo = activity("code", "development", 60);
+        in_tokyo.start_time = recorded.with_timezone(&Local);
+        in_tokyo.utc_offset = Some(9 * 3600);
+
+        // 之后在纽约时间 1 月 1 日 20:00 记录，同样是 UTC 1 月 2 日 01:00 左右
+        let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+        let mut in_new_york = activity("code", "development", 30);
+        in_new_york.start_time = new_york.with_ymd_and_hms(2024, 1, 1, 20, 0, 0).unwrap().with_timezone(&Local);
+        in_new_york.utc_offset = Some(-5 * 3600);
+
+        let jan1 = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let jan2 = chrono::NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert_eq!(in_tokyo.local_date(), jan2);
+        assert_eq!(in_new_york.local_date(), jan1);
+
+        let totals = daily_totals(&[in_tokyo, in_new_york]);
+        assert_eq!(totals.get(&jan2), Some(&std::time::Duration::from_secs(60 * 60)));
+        assert_eq!(totals.get(&jan1), Some(&std::time::Duration::from_secs(30 * 60)));
+
+        // 旧记录没有偏移时按当前时区计算
+        let mut legacy = activity("code", "development", 10);
+        legacy.start_time = recorded.with_timezone(&Local);
+        assert_eq!(legacy.local_date(), legacy.start_time.date_naive());
+    }
+
+    #[tokio::test]
+    async fn test_tag_stats_counts_multi_tagged_records() -> AppResult<()> {
+        let mut activities = sample();
+        activities[0].tags = vec!["meetings".into(), "client".into()];
+        activities[3].tags = vec!["research".into()];
+        let mut standup = pomodoro(Local::now(), PomodoroStatus::Completed);
+        standup.tags = vec!["meetings".into(), "meetings".into()];
+        let mut review = pomodoro(Local::now(), PomodoroStatus::Completed);
+        review.tags = vec!["client".into(), "meetings".into()];
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_activities().returning(move |_, _| Ok(activities.clone()));
+        mock_storage
+            .expect_get_pomodoro_sessions()
+            .returning(move |_, _| Ok(vec![standup.clone(), review.clone()]));
+        mock_storage.expect_list_tags().returning(|| {
+            Ok(vec![Tag {
+                id: Some(1),
+                name: "meetings".into(),
+                color: "#E15759".into(),
+            }])
+        });
+
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let stats = manager.tag_stats(Local::now() - chrono::Duration::days(1), Local::now(), None).await?;
+        let find = |name: &str| stats.iter().find(|(tag, _, _)| tag.name == name).cloned().unwrap();
+
+        let minutes = |m: u64| std::time::Duration::from_secs(m * 60);
+        let (meetings, meetings_time, meetings_count) = find("meetings");
+        assert_eq!(meetings.id, Some(1));
+        assert_eq!((meetings_time, meetings_count), (minutes(30 + 25 + 25), 3));
+        assert_eq!((find("client").1, find("client").2), (minutes(30 + 25), 2));
+        assert_eq!((find("research").1, find("research").2), (minutes(40), 1));
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].0.name, "meetings");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tag_stats_filters_by_project() -> AppResult<()> {
+        let mut tagged = activity("code", "development", 45);
+        tagged.project_id = Some(7);
+        tagged.tags = vec!["client".into()];
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_project_activities()
+            .withf(|id, _, _| *id == 7)
+            .returning(move |_, _, _| Ok(vec![tagged.clone()]));
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .withf(|id, _, _| *id == 7)
+            .returning(|_, _, _| Ok(Vec::new()));
+        mock_storage.expect_list_tags().returning(|| Ok(Vec::new()));
+
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let stats = manager.tag_stats(Local::now() - chrono::Duration::days(1), Local::now(), Some(7)).await?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].1, std::time::Duration::from_secs(45 * 60));
+        assert_eq!(stats[0].2, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tag_focus_time_counts_completed_sessions() -> AppResult<()> {
+        let now = Local::now();
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_pomodoros_by_tag()
+            .withf(|tag, _| tag == "#deepwork")
+            .returning(move |_, _| {
+                Ok(vec![
+                    pomodoro(now, PomodoroStatus::Completed),
+                    pomodoro(now, PomodoroStatus::Interrupted),
+                    pomodoro(now, PomodoroStatus::Completed),
+                ])
+            });
+
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let focus = manager.tag_focus_time("#deepwork", (now - chrono::Duration::days(30), now)).await?;
+        assert_eq!(focus, std::time::Duration::from_secs(50 * 60));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_daily_goal_progress() -> AppResult<()> {
+        // 2024-04-01 是周一，2024-04-06 是周六
+        let monday = chrono::NaiveDate::from_ymd_opt(2024, 4, 1).unwrap();
+        let saturday = chrono::NaiveDate::from_ymd_opt(2024, 4, 6).unwrap();
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_daily_usage().returning(move |_| {
+            Ok(vec![DailyUsage {
+                date: monday,
+                productive_time: std::time::Duration::from_secs(5 * 3600),
+                completed_pomodoros: 3,
+                ..Default::default()
+            }])
+        });
+        mock_storage.expect_list_goals().returning(|| {
+            let goal = |kind, target| Goal {
+                id: None,
+                kind,
+                target,
+                schedule: GoalSchedule::Weekdays,
+                active: true,
+            };
+            Ok(vec![
+                goal(GoalKind::ProductiveTime, 4 * 3600),
+                goal(GoalKind::CompletedPomodoros, 8),
+                Goal { active: false, ..goal(GoalKind::ProductiveTime, 3600) },
+            ])
+        });
+
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let progress = manager.daily_goals(monday).await?;
+        assert_eq!(progress.len(), 2);
+        assert_eq!((progress[0].current, progress[0].met), (5 * 3600, true));
+        assert_eq!(progress[0].percent(), 100.0);
+        assert_eq!((progress[1].current, progress[1].target, progress[1].met), (3, 8, false));
+        // 工作日目标周末不生效
+        assert!(manager.daily_goals(saturday).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_goal_progress_counts_only_that_day() -> AppResult<()> {
+        let today = Local::now().date_naive();
+        let mut yesterday = activity("code", "development", 90);
+        yesterday.start_time = Local::now() - chrono::Duration::days(1);
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_project_activities()
+            .withf(|id, _, _| *id == 7)
+            .returning(move |_, _, _| Ok(vec![activity("code", "development", 45), yesterday.clone()]));
+
+        let goal = Goal {
+            id: Some(1),
+            kind: GoalKind::ProjectTime { project_id: 7 },
+            target: 3600,
+            schedule: GoalSchedule::Daily,
+            active: true,
+        };
+        let progress = AnalysisManager::new(Arc::new(mock_storage)).goal_progress(&goal, today).await?;
+        assert_eq!(progress.current, 45 * 60);
+        assert!(!progress.met);
+        assert!((progress.percent() - 75.0).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_app() {
+        let groups = group_activities(&sample(), GroupBy::App);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].key, "code");
+        assert_eq!(groups[0].total_time, std::time::Duration::from_secs(50 * 60));
+        assert_eq!(groups[0].count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_weekly_and_monthly_rollups() -> AppResult<()> {
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 4, day).unwrap();
+        let usage = |day, hours: u64, productive_hours: u64, pomodoros: u32| DailyUsage {
+            date: date(day),
+            total_time: std::time::Duration::from_secs(hours * 3600),
+            productive_time: std::time::Duration::from_secs(productive_hours * 3600),
+            focus_time: std::time::Duration::from_secs(pomodoros as u64 * 25 * 60),
+            completed_pomodoros: pomodoros,
+        };
+        // 2024-04-01 是周一
+        let days = vec![usage(1, 4, 3, 4), usage(3, 6, 3, 2), usage(8, 2, 2, 1), usage(15, 8, 4, 6)];
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_daily_usage().returning(move |_| Ok(days.clone()));
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+        let (start, _) = day_bounds(date(1));
+        let (_, end) = day_bounds(date(30));
+
+        let weeks = manager.weekly_summary((start, end)).await?;
+        assert_eq!(weeks.len(), 5);
+        let first = &weeks[0];
+        assert_eq!((first.period_start, first.days), (date(1), 7));
+        assert_eq!(first.total_time, std::time::Duration::from_secs(10 * 3600));
+        assert_eq!(first.completed_pomodoros, 6);
+        assert!((first.productivity_ratio - 0.6).abs() < 1e-9);
+        assert_eq!(first.busiest_day, Some((date(3), std::time::Duration::from_secs(6 * 3600))));
+        // 第一周只有一个周一
+        assert_eq!(first.weekday_average[0], std::time::Duration::from_secs(4 * 3600));
+        assert_eq!(weeks[4].days, 2);
+
+        let months = manager.monthly_summary((start, end)).await?;
+        assert_eq!(months.len(), 1);
+        let april = &months[0];
+        assert_eq!(april.busiest_day.map(|(day, _)| day), Some(date(15)));
+        assert_eq!(april.focus_time, std::time::Duration::from_secs(13 * 25 * 60));
+        // 四月有 5 个周一：1、8、15、22、29 日，共 14 小时
+        assert_eq!(april.weekday_average[0], std::time::Duration::from_secs(14 * 3600 / 5));
+        // 没有记录的周期也会返回，便于按周期对齐显示
+        assert_eq!(weeks[3].total_time, std::time::Duration::ZERO);
+        assert_eq!(weeks[3].busiest_day, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_focus_streak() {
+        let date = |day| chrono::NaiveDate::from_ymd_opt(2024, 4, day).unwrap();
+        let day = |day, completed| DailyUsage {
+            date: date(day),
+            completed_pomodoros: completed,
+            ..Default::default()
+        };
+        // 1-4 日达标，5 日只完成 1 个，6 日没有记录，7-9 日达标
+        let days = vec![
+            day(1, 2),
+            day(2, 3),
+            day(3, 2),
+            day(4, 4),
+            day(5, 1),
+            day(7, 2),
+            day(8, 2),
+            day(9, 5),
+        ];
+
+        let streak = StreakInfo::calculate(&days, 2, date(10));
+        assert_eq!(streak, StreakInfo { current: 3, longest: 4, last_broken: Some(date(5)) });
+        // 今天已达标时计入今天
+        assert_eq!(StreakInfo::calculate(&days, 2, date(9)).current, 3);
+        // 昨天也未达标时连续中断
+        let broken = StreakInfo::calculate(&days, 2, date(11));
+        assert_eq!((broken.current, broken.last_broken), (0, Some(date(10))));
+        // 门槛为 1 时 5 日也达标，6 日没有记录仍算中断
+        let lenient = StreakInfo::calculate(&days, 1, date(10));
+        assert_eq!((lenient.longest, lenient.last_broken), (5, Some(date(6))));
+        assert_eq!(StreakInfo::calculate(&[], 2, date(10)), StreakInfo::default());
+    }
+
+    #[test]
+    fn test_group_by_category() {
+        let groups = group_activities(&sample(), GroupBy::Category);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, "development");
+        assert_eq!(groups[0].total_time, std::time::Duration::from_secs(65 * 60));
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[1].key, "research");
+    }
+
+    #[test]
+    fn test_group_raw() {
+        let groups = group_activities(&sample(), GroupBy::Raw);
+        assert_eq!(groups.len(), 4);
+        assert!(groups.iter().all(|g| g.count == 1));
+        assert_eq!(groups[0].key, "browser - browser window");
+    }
+
+    #[tokio::test]
+    async fn test_project_heatmap_splits_across_hours() -> AppResult<()> {
+        use chrono::TimeZone;
+        // 2024-04-01 是周一
+        let at = |hour, minute| Local.with_ymd_and_hms(2024, 4, 1, hour, minute, 0).unwrap();
+        let mut coding = activity("code", "development", 0);
+        coding.project_id = Some(1);
+        coding.start_time = at(9, 40);
+        coding.end_time = Some(at(11, 10));
+        // 与活动重叠的番茄钟只计一次
+        let mut focus = pomodoro(at(10, 50), PomodoroStatus::Completed);
+        focus.project_id = Some(1);
+        let mut running = pomodoro(at(14, 0), PomodoroStatus::Work);
+        running.end_time = None;
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_project_activities()
+            .withf(|id, _, _| *id == 1)
+            .returning(move |_, _, _| Ok(vec![coding.clone()]));
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .returning(move |_, _, _| Ok(vec![focus.clone(), running.clone()]));
+        let manager = AnalysisManager::new(Arc::new(mock_storage));
+
+        let heatmap = manager.project_heatmap(1, (at(0, 0), at(23, 0))).await?;
+        assert_eq!((heatmap.len(), heatmap[0].len()), (7, 24));
+        assert_eq!(&heatmap[0][9..12], &[20, 60, 15]);
+        assert_eq!(heatmap.iter().flatten().sum::<u32>(), 95);
+        Ok(())
+    }
 } 
\ No newline at end of file
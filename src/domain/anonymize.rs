@@ -0,0 +1,190 @@
+use crate::core::AppResult;
+use crate::domain::export::ExportData;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnonymizeMode {
+    #[default]
+    None,
+    /// 替换应用、窗口、项目和标签名称，清空描述和备注，时长与结构保持不变
+    Full,
+}
+
+/// 原始名称到化名的映射，可保存下来用于还原支持报告中的内容
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PseudonymMap {
+    pub apps: BTreeMap<String, String>,
+    pub windows: BTreeMap<String, String>,
+    pub projects: BTreeMap<String, String>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl PseudonymMap {
+    pub fn save(&self, path: impl AsRef<Path>) -> AppResult<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// 按首次出现的顺序分配化名，同一名称总是得到同一化名
+fn pseudonym(map: &mut BTreeMap<String, String>, original: &str, make: impl Fn(usize) -> String) -> String {
+    if let Some(existing) = map.get(original) {
+        return existing.clone();
+    }
+    let name = make(map.len());
+    map.insert(original.to_string(), name.clone());
+    name
+}
+
+/// 0 -> A, 25 -> Z, 26 -> AA
+fn letters(mut index: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.insert(0, (b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name
+}
+
+pub fn anonymize(data: &ExportData, mode: AnonymizeMode) -> (ExportData, PseudonymMap) {
+    let mut map = PseudonymMap::default();
+    if mode == AnonymizeMode::None {
+        return (data.clone(), map);
+    }
+
+    let mut data = data.clone();
+
+    // 项目列表可能来自 HashMap，先按 id 排序，同一份数据每次导出得到相同的化名
+    data.projects
+        .sort_by(|a, b| a.id.cmp(&b.id).then_with(|| a.name.cmp(&b.name)));
+    for project in &mut data.projects {
+        project.name = pseudonym(&mut map.projects, &project.name, |i| format!("Project {}", letters(i)));
+        project.description = None;
+        project.default_tags = project
+            .default_tags
+            .iter()
+            .map(|tag| pseudonym(&mut map.tags, tag, |i| format!("Tag {}", i + 1)))
+            .collect();
+    }
+
+    for activity in &mut data.activities {
+        activity.app_name = pseudonym(&mut map.apps, &activity.app_name, |i| format!("App {}", i + 1));
+        activity.window_title = pseudonym(&mut map.windows, &activity.window_title, |i| format!("Window {}", i + 1));
+        activity.name = activity.app_name.clone();
+        activity.description = None;
+        activity.tags = activity
+            .tags
+            .iter()
+            .map(|tag| pseudonym(&mut map.tags, tag, |i| format!("Tag {}", i + 1)))
+            .collect();
+    }
+
+    for session in &mut data.pomodoros {
+        session.notes = None;
+        session.tags = session
+            .tags
+            .iter()
+            .map(|tag| pseudonym(&mut map.tags, tag, |i| format!("Tag {}", i + 1)))
+            .collect();
+    }
+
+    (data, map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::*;
+    use crate::domain::export::summarize;
+    use chrono::Local;
+    use std::time::Duration;
+
+    fn activity(app_name: &str, window_title: &str, project_id: i64, minutes: u64) -> Activity {
+        Activity {
+            id: None,
+            name: app_name.into(),
+            start_time: Local::now(),
+            end_time: None,
+            project_id: Some(project_id),
+            description: Some(format!("working on {}", window_title)),
+            duration: Duration::from_secs(minutes * 60),
+            category: "development".into(),
+            is_productive: true,
+            app_name: app_name.into(),
+            window_title: window_title.into(),
+            is_billable: None,
+            tags: vec!["acme-secret".into()],
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    #[test]
+    fn test_full_anonymization_hides_names_and_keeps_totals() {
+        let mut project = Project::new("Acme Merger".into(), Some("confidential".into()));
+        project.id = Some(1);
+        let data = ExportData {
+            activities: vec![
+                activity("Slack", "#acme-deal", 1, 30),
+                activity("Code", "merger.rs", 1, 45),
+                activity("Slack", "#acme-deal", 1, 15),
+            ],
+            pomodoros: Vec::new(),
+            projects: vec![project],
+        };
+
+        let (anonymized, map) = anonymize(&data, AnonymizeMode::Full);
+
+        let json = serde_json::to_string(&anonymized).unwrap();
+        for original in ["Slack", "Code", "#acme-deal", "merger.rs", "Acme Merger", "confidential", "acme-secret"] {
+            assert!(!json.contains(original), "{} leaked", original);
+        }
+        assert_eq!(anonymized.projects[0].name, "Project A");
+        assert_eq!(anonymized.activities[0].app_name, "App 1");
+        assert_eq!(anonymized.activities[2].app_name, "App 1");
+        assert_eq!(anonymized.activities[1].app_name, "App 2");
+        assert_eq!(map.apps.get("Slack").map(String::as_str), Some("App 1"));
+
+        assert_eq!(summarize(&anonymized), summarize(&data));
+        assert_eq!(anonymized.activities.len(), data.activities.len());
+    }
+
+    #[test]
+    fn test_project_pseudonyms_do_not_depend_on_input_order() {
+        let projects: Vec<Project> = ["Zeta", "Alpha", "Mid"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut project = Project::new(name.into(), None);
+                project.id = Some(i as i64 + 1);
+                project
+            })
+            .collect();
+        let data = ExportData {
+            projects: projects.clone(),
+            ..Default::default()
+        };
+        let reversed = ExportData {
+            projects: projects.into_iter().rev().collect(),
+            ..Default::default()
+        };
+
+        let (_, map) = anonymize(&data, AnonymizeMode::Full);
+        let (_, reversed_map) = anonymize(&reversed, AnonymizeMode::Full);
+        assert_eq!(map.projects, reversed_map.projects);
+        assert_eq!(map.projects.get("Zeta").map(String::as_str), Some("Project A"));
+    }
+
+    #[test]
+    fn test_letters() {
+        assert_eq!(letters(0), "A");
+        assert_eq!(letters(25), "Z");
+        assert_eq!(letters(26), "AA");
+        assert_eq!(letters(27), "AB");
+    }
+}
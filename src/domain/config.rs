@@ -1,10 +1,15 @@
-use crate::core::{AppResult, models::*};
+use crate::core::{AppError, AppResult, models::*};
 use crate::core::traits::Storage;
+use crate::domain::analysis::GroupBy;
+use crate::domain::export::DetailLevel;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use async_trait::async_trait;
+use chrono::{NaiveTime, Weekday};
 
 #[async_trait]
 pub trait ConfigManager: Send + Sync {
@@ -14,24 +19,184 @@ pub trait ConfigManager: Send + Sync {
     async fn update_config(&self, config: AppConfig) -> AppResult<()>;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppConfig {
     pub pomodoro: PomodoroSettings,
     pub notification: NotificationSettings,
     pub ui: UISettings,
     pub storage: StorageSettings,
     pub rules: RuleSettings,
+    #[serde(default)]
+    pub tracking: TrackingSettings,
+    #[serde(default)]
+    pub export: ExportSettings,
+    #[serde(default)]
+    pub maintenance: MaintenanceSettings,
+    #[serde(default)]
+    pub power: PowerSettings,
+    #[serde(default)]
+    pub crash_report: CrashReportSettings,
+    #[serde(default)]
+    pub focus: FocusSettings,
+    #[serde(default)]
+    pub categories: CategorySettings,
+    /// 为空时不推送
+    #[serde(default)]
+    pub webhook: Option<WebhookSettings>,
+    /// 为空时不生成每日摘要
+    #[serde(default)]
+    pub digest: Option<DigestSettings>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AppConfig {
+    /// 重命名自定义类别并更新引用它的类别规则，已记录的活动保留原名称
+    pub fn rename_category(&mut self, from: &str, to: &str) -> AppResult<()> {
+        let to = self.categories.rename(from, to)?;
+        for rule in &mut self.tracking.category_rules {
+            if rule.category == from {
+                rule.category = to.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PomodoroSettings {
     pub work_duration: Duration,
     pub short_break_duration: Duration,
     pub long_break_duration: Duration,
     pub long_break_interval: u32,
+    /// 工作番茄结束后自动开始休息，关闭时由用户选择下一步
+    #[serde(default)]
+    pub auto_start_breaks: bool,
+    /// 工作阶段阻止系统休眠
+    #[serde(default)]
+    pub keep_awake_during_work: bool,
+    /// 工作番茄结束后至少休息的时长，期间不能开始新的工作番茄，为 0 时不限制
+    #[serde(default)]
+    pub min_enforced_break: Duration,
+    /// 工作番茄进行中每隔该时间提醒短暂放松一次，不中断计时
+    #[serde(default)]
+    pub micro_break_every: Option<Duration>,
+    #[serde(default)]
+    pub timing_source: TimingSource,
+    /// 工作番茄结束后推迟休息的时长，到期后再次提醒
+    #[serde(default = "default_snooze_duration")]
+    pub snooze_duration: Duration,
+    /// 同一次休息最多连续推迟的次数
+    #[serde(default = "default_max_snoozes")]
+    pub max_snoozes: u32,
+    /// 各阶段结束时播放的提示音文件，未配置时使用内置提示音
+    #[serde(default)]
+    pub sound_profile: HashMap<PomodoroEvent, Option<PathBuf>>,
+    /// 命名的时长组合，切换时覆盖上面的四项时长设置
+    #[serde(default = "default_pomodoro_presets")]
+    pub presets: Vec<PomodoroPreset>,
+    /// 最近一次应用的预设，手动修改时长后不会清除
+    #[serde(default)]
+    pub active_preset: Option<String>,
+}
+
+impl PomodoroSettings {
+    /// 事件对应的自定义提示音，`None` 表示使用内置提示音
+    pub fn sound_for(&self, event: PomodoroEvent) -> Option<&Path> {
+        self.sound_profile.get(&event).and_then(|path| path.as_deref())
+    }
+
+    /// 按名称查找预设，不区分大小写
+    pub fn preset(&self, name: &str) -> Option<&PomodoroPreset> {
+        self.presets.iter().find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// 用预设覆盖各阶段时长和长休息间隔
+    pub fn apply_preset(&mut self, name: &str) -> AppResult<()> {
+        let preset = self
+            .preset(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("番茄钟预设「{}」", name)))?;
+        self.work_duration = preset.config.work_duration;
+        self.short_break_duration = preset.config.short_break_duration;
+        self.long_break_duration = preset.config.long_break_duration;
+        self.long_break_interval = preset.config.long_break_interval;
+        self.active_preset = Some(preset.name);
+        Ok(())
+    }
+
+    /// 检查自定义提示音文件是否存在
+    pub fn validate_sound_profile(&self) -> AppResult<()> {
+        for (event, path) in &self.sound_profile {
+            if let Some(path) = path {
+                if !path.is_file() {
+                    return Err(AppError::Config(format!(
+                        "提示音文件不存在 ({:?}): {}",
+                        event,
+                        path.display()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroPreset {
+    pub name: String,
+    pub config: PomodoroPresetConfig,
+}
+
+/// 预设包含的时长设置，与 `PomodoroSettings` 中的同名字段对应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroPresetConfig {
+    pub work_duration: Duration,
+    pub short_break_duration: Duration,
+    pub long_break_duration: Duration,
+    pub long_break_interval: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl PomodoroPreset {
+    fn minutes(name: &str, work: u64, short_break: u64, long_break: u64, long_break_interval: u32) -> Self {
+        Self {
+            name: name.into(),
+            config: PomodoroPresetConfig {
+                work_duration: Duration::from_secs(work * 60),
+                short_break_duration: Duration::from_secs(short_break * 60),
+                long_break_duration: Duration::from_secs(long_break * 60),
+                long_break_interval,
+            },
+        }
+    }
+}
+
+fn default_pomodoro_presets() -> Vec<PomodoroPreset> {
+    vec![
+        PomodoroPreset::minutes("classic", 25, 5, 15, 4),
+        PomodoroPreset::minutes("deep-work", 50, 10, 30, 2),
+    ]
+}
+
+/// 触发提示音的番茄钟事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroEvent {
+    WorkComplete,
+    BreakComplete,
+    Interrupted,
+}
+
+/// 番茄钟计时使用的时钟
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimingSource {
+    /// 按系统时间计时，休眠期间同样计入
+    #[default]
+    WallClock,
+    /// 按单调时钟计时，不受系统时间调整影响；部分平台休眠期间单调时钟会暂停
+    Monotonic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NotificationSettings {
     pub enable_system_notifications: bool,
     pub enable_sound: bool,
@@ -39,25 +204,194 @@ pub struct NotificationSettings {
     pub notification_retention_days: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UISettings {
     pub theme: String,
     pub language: String,
     pub show_system_tray: bool,
     pub minimize_to_tray: bool,
     pub start_minimized: bool,
+    /// 开机自启时隐藏到托盘，不抢占焦点
+    #[serde(default)]
+    pub start_hidden: bool,
+    /// 应用使用视图上次选择的分组方式
+    #[serde(default)]
+    pub usage_group_by: GroupBy,
+    /// 禁止同时运行多个实例
+    #[serde(default = "default_true")]
+    pub single_instance: bool,
+    #[serde(default)]
+    pub refresh: RefreshSettings,
+    /// 概览页显示的组件及顺序
+    #[serde(default = "default_dashboard_widgets")]
+    pub dashboard_widgets: Vec<DashboardWidget>,
+    /// 每周的第一天，用于命令行的本周、上周等时间范围
+    #[serde(default = "default_week_start")]
+    pub week_start: Weekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetKind {
+    TodayFocus,
+    Streak,
+    TopApps,
+    GoalRing,
+    RecentPomodoros,
+    /// 新版本添加或已移除的组件，保留在配置中但不显示
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardWidget {
+    pub kind: WidgetKind,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_dashboard_widgets() -> Vec<DashboardWidget> {
+    [
+        WidgetKind::TodayFocus,
+        WidgetKind::Streak,
+        WidgetKind::TopApps,
+        WidgetKind::GoalRing,
+        WidgetKind::RecentPomodoros,
+    ]
+    .into_iter()
+    .map(|kind| DashboardWidget { kind, enabled: true })
+    .collect()
+}
+
+/// 各视图数据的缓存时间，期间除非数据变化否则不重新查询
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefreshSettings {
+    pub overview: Duration,
+    pub activities: Duration,
+    pub statistics: Duration,
+    pub pomodoro: Duration,
+    pub projects: Duration,
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        Self {
+            overview: Duration::from_secs(2),
+            activities: Duration::from_secs(2),
+            statistics: Duration::from_secs(5),
+            pomodoro: Duration::from_secs(2),
+            projects: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorageSettings {
     pub database_path: String,
     pub backup_path: String,
     pub auto_backup: bool,
     pub backup_interval_days: u32,
     pub backup_retention_days: u32,
+    /// 数据库被锁定时的最长等待时间
+    #[serde(default = "default_busy_timeout")]
+    pub busy_timeout: Duration,
+    #[serde(default)]
+    pub synchronous: Synchronous,
+    /// 导入、恢复、清理等破坏性操作前自动备份
+    #[serde(default = "default_true")]
+    pub backup_before_destructive: bool,
+    /// 批量删除影响的记录数超过该值时需要确认
+    #[serde(default = "default_bulk_delete_threshold")]
+    pub bulk_delete_threshold: u64,
+}
+
+/// SQLite `PRAGMA synchronous` 级别
+///
+/// - `Off`：不等待写入落盘，最快，但断电或系统崩溃可能损坏数据库
+/// - `Normal`：配合 WAL 时只在检查点同步，断电可能丢失最近的事务但不会损坏数据库，适合大多数用户
+/// - `Full`：每次提交都同步，最安全但写入最慢
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Synchronous {
+    Off,
+    #[default]
+    Normal,
+    Full,
+}
+
+fn default_week_start() -> Weekday {
+    Weekday::Mon
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_true() -> bool {
+    true
+}
+
+fn default_snooze_duration() -> Duration {
+    Duration::from_secs(2 * 60)
+}
+
+fn default_max_snoozes() -> u32 {
+    3
+}
+
+fn default_max_session_duration() -> Duration {
+    Duration::from_secs(4 * 3600)
+}
+
+fn default_idle_threshold() -> Duration {
+    Duration::from_secs(5 * 60)
+}
+
+fn default_tracking_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_flush_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_flush_max_records() -> usize {
+    50
+}
+
+fn default_busy_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_bulk_delete_threshold() -> u64 {
+    500
+}
+
+impl StorageSettings {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.busy_timeout.is_zero() {
+            return Err(AppError::Config("busy_timeout 必须大于0".into()));
+        }
+        // 过长的等待会让界面在锁竞争时看起来卡死
+        if self.busy_timeout > Duration::from_secs(60) {
+            return Err(AppError::Config("busy_timeout 不能超过60秒".into()));
+        }
+        Ok(())
+    }
+
+    /// 自动备份的间隔，关闭自动备份或间隔为 0 时为 `None`
+    pub fn auto_backup_interval(&self) -> Option<Duration> {
+        if !self.auto_backup || self.backup_interval_days == 0 {
+            return None;
+        }
+        Some(Duration::from_secs(self.backup_interval_days as u64 * 24 * 3600))
+    }
+
+    /// 影响记录数超过阈值且未确认时返回 `ConfirmationRequired`
+    pub fn check_bulk_delete(&self, affected: u64, confirmed: bool) -> AppResult<()> {
+        if affected > self.bulk_delete_threshold && !confirmed {
+            return Err(AppError::ConfirmationRequired(affected));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RuleSettings {
     pub auto_categorize: bool,
     pub productivity_threshold: f64,
@@ -65,6 +399,367 @@ pub struct RuleSettings {
     pub suggestion_threshold: u32,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackingSettings {
+    /// 关闭后不再记录任何活动（私人时间）
+    pub enabled: bool,
+    /// 从窗口标题提取活动名称的规则，按顺序匹配
+    #[serde(default)]
+    pub title_rules: Vec<TitleRuleSettings>,
+    /// 系统空闲超过该时间后停止计入当前活动，为 0 时不检测
+    #[serde(default = "default_idle_threshold")]
+    pub idle_threshold: Duration,
+    /// 按应用名称覆盖类别的规则，按顺序匹配，靠前的规则优先
+    #[serde(default, deserialize_with = "deserialize_category_rules")]
+    pub category_rules: Vec<CategoryRuleSettings>,
+    /// 读取前台窗口的间隔
+    #[serde(default = "default_tracking_interval")]
+    pub tracking_interval: Duration,
+    /// 结束的记录先在内存中累积，间隔到达或数量达到上限时一次写入
+    #[serde(default = "default_flush_interval")]
+    pub flush_interval: Duration,
+    #[serde(default = "default_flush_max_records")]
+    pub flush_max_records: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TitleRuleSettings {
+    pub pattern: String,
+    pub template: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRuleSettings {
+    /// 正则表达式，需要整体匹配时自行加 `^`/`$`
+    pub pattern: String,
+    pub category: String,
+}
+
+/// 兼容旧版的 `{ "关键字": "类别" }` 格式：转换为不区分大小写的包含匹配，
+/// 关键字越长越具体，排在前面
+fn deserialize_category_rules<'de, D>(deserializer: D) -> Result<Vec<CategoryRuleSettings>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Rules {
+        Ordered(Vec<CategoryRuleSettings>),
+        Legacy(std::collections::HashMap<String, String>),
+    }
+
+    Ok(match Rules::deserialize(deserializer)? {
+        Rules::Ordered(rules) => rules,
+        Rules::Legacy(map) => {
+            let mut entries: Vec<(String, String)> = map.into_iter().collect();
+            entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+            entries
+                .into_iter()
+                .map(|(keyword, category)| CategoryRuleSettings {
+                    pattern: format!("(?i){}", regex::escape(&keyword)),
+                    category,
+                })
+                .collect()
+        }
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportSettings {
+    /// 自定义 HTML 报告模板（Handlebars），为空时使用内置模板
+    pub html_template: Option<String>,
+    #[serde(default)]
+    pub detail_level: DetailLevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    PomodoroCompleted,
+    BreakStarted,
+    ActivityRecorded,
+}
+
+/// 把插件事件以 JSON 推送到 HTTP 地址
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    pub url: String,
+    /// 设置后以 `Authorization: Bearer` 头发送
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    /// 推送的事件类型
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<WebhookEvent>,
+}
+
+fn default_webhook_events() -> Vec<WebhookEvent> {
+    vec![WebhookEvent::PomodoroCompleted]
+}
+
+impl WebhookSettings {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            bearer_token: None,
+            events: default_webhook_events(),
+        }
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        let url = reqwest::Url::parse(&self.url)
+            .map_err(|e| AppError::Config(format!("webhook 地址 {} 无效: {}", self.url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::Config(format!("webhook 地址只支持 http 和 https: {}", self.url)));
+        }
+        if self.events.is_empty() {
+            return Err(AppError::Config("webhook 至少需要订阅一种事件".into()));
+        }
+        Ok(())
+    }
+
+    pub fn subscribes(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+/// 每天在固定的本地时间汇总当天数据，发送通知并写入摘要日志
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestSettings {
+    #[serde(default = "default_digest_time")]
+    pub time: NaiveTime,
+    /// 为空时写入数据目录下的 `digest.jsonl`
+    #[serde(default)]
+    pub output_path: Option<PathBuf>,
+}
+
+fn default_digest_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(18, 0, 0).unwrap()
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            time: default_digest_time(),
+            output_path: None,
+        }
+    }
+}
+
+/// 崩溃时在数据目录写入报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReportSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 保留的报告数量，超出时删除最旧的
+    pub max_reports: usize,
+    /// 报告中附带的最近事件数量
+    pub recent_events: usize,
+}
+
+impl Default for CrashReportSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_reports: 5,
+            recent_events: 50,
+        }
+    }
+}
+
+/// 专注模式：工作番茄期间切换到分心应用时提醒
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FocusSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 应用名称，不区分大小写
+    #[serde(default)]
+    pub unproductive_apps: Vec<String>,
+}
+
+/// 内置类别，不能重命名
+pub const BUILTIN_CATEGORIES: [&str; 6] = [
+    "development",
+    "communication",
+    "browsing",
+    "entertainment",
+    "productivity",
+    "other",
+];
+
+/// 类别以名称保存，自定义类别与内置类别一样可用于规则和统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategorySettings {
+    #[serde(default)]
+    pub custom: Vec<String>,
+    /// 类别计入生产率的权重（0-1），为空时按活动是否生产性计分
+    #[serde(default)]
+    pub weights: BTreeMap<String, f64>,
+}
+
+impl CategorySettings {
+    pub fn is_builtin(name: &str) -> bool {
+        BUILTIN_CATEGORIES.iter().any(|builtin| builtin.eq_ignore_ascii_case(name))
+    }
+
+    /// 内置类别在前，自定义类别按添加顺序
+    pub fn all(&self) -> impl Iterator<Item = &str> {
+        BUILTIN_CATEGORIES.iter().copied().chain(self.custom.iter().map(String::as_str))
+    }
+
+    /// 返回去掉首尾空白后的名称，名称为空或已存在（不区分大小写）时返回错误
+    fn check_available(&self, name: &str, except: Option<&str>) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidOperation("类别名称不能为空".into()));
+        }
+        let taken = self
+            .all()
+            .filter(|existing| Some(*existing) != except)
+            .any(|existing| existing.eq_ignore_ascii_case(name));
+        if taken {
+            return Err(AppError::InvalidOperation(format!("类别已存在: {}", name)));
+        }
+        Ok(name.to_string())
+    }
+
+    pub fn add(&mut self, name: &str) -> AppResult<String> {
+        let name = self.check_available(name, None)?;
+        self.custom.push(name.clone());
+        Ok(name)
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> AppResult<String> {
+        if Self::is_builtin(from) {
+            return Err(AppError::InvalidOperation(format!("内置类别不能重命名: {}", from)));
+        }
+        let index = self
+            .custom
+            .iter()
+            .position(|name| name == from)
+            .ok_or_else(|| AppError::NotFound(format!("类别 {}", from)))?;
+        let to = self.check_available(to, Some(from))?;
+        self.custom[index] = to.clone();
+        if let Some(weight) = self.weights.remove(from) {
+            self.weights.insert(to.clone(), weight);
+        }
+        Ok(to)
+    }
+
+    /// 设置类别的生产率权重，`None` 时恢复按是否生产性计分
+    pub fn set_weight(&mut self, category: &str, weight: Option<f64>) -> AppResult<()> {
+        let name = self
+            .all()
+            .find(|existing| existing.eq_ignore_ascii_case(category.trim()))
+            .map(str::to_string)
+            .ok_or_else(|| AppError::NotFound(format!("类别 {}", category)))?;
+        match weight {
+            Some(weight) if !(0.0..=1.0).contains(&weight) => {
+                Err(AppError::InvalidOperation(format!("类别权重应在 0 到 1 之间: {}", weight)))
+            }
+            Some(weight) => {
+                self.weights.insert(name, weight);
+                Ok(())
+            }
+            None => {
+                self.weights.remove(&name);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 系统休眠与唤醒时的处理方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PowerSettings {
+    /// 休眠时暂停正在进行的番茄钟，唤醒后继续
+    #[serde(default = "default_true")]
+    pub pause_timer_on_suspend: bool,
+    /// 休眠时结束当前活动，休眠期间不计入记录
+    #[serde(default = "default_true")]
+    pub drop_sleep_from_tracking: bool,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            pause_timer_on_suspend: true,
+            drop_sleep_from_tracking: true,
+        }
+    }
+}
+
+/// 各类数据保留的天数，为 `None` 时永久保留
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub app_usage_days: Option<u32>,
+    pub pomodoro_days: Option<u32>,
+    pub summaries_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    /// 所有数据使用相同的保留天数
+    pub fn uniform(days: u32) -> Self {
+        Self {
+            app_usage_days: Some(days),
+            pomodoro_days: Some(days),
+            summaries_days: Some(days),
+        }
+    }
+
+    /// 所有数据都永久保留，不需要清理
+    pub fn keeps_everything(&self) -> bool {
+        self.app_usage_days.is_none() && self.pomodoro_days.is_none() && self.summaries_days.is_none()
+    }
+}
+
+/// 后台维护任务的执行间隔，为 0 时不执行该任务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceSettings {
+    pub vacuum_interval: Duration,
+    pub checkpoint_interval: Duration,
+    pub summary_interval: Duration,
+    pub retention_interval: Duration,
+    /// 各类记录的保留天数，全部为空时不清理
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// 在间隔基础上随机推迟的最长时间，避免所有任务同时执行
+    pub jitter: Duration,
+    /// 用户空闲超过该时间才执行维护
+    pub min_idle: Duration,
+    /// 单条应用使用记录的最长时长，超出部分在每日维护时截去，为 0 时不检查
+    #[serde(default = "default_max_session_duration")]
+    pub max_session_duration: Duration,
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            vacuum_interval: Duration::from_secs(7 * 24 * 3600),
+            checkpoint_interval: Duration::from_secs(3600),
+            summary_interval: Duration::from_secs(6 * 3600),
+            retention_interval: Duration::from_secs(24 * 3600),
+            retention: RetentionPolicy::default(),
+            jitter: Duration::from_secs(10 * 60),
+            min_idle: Duration::from_secs(2 * 60),
+            max_session_duration: default_max_session_duration(),
+        }
+    }
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            title_rules: Vec::new(),
+            idle_threshold: default_idle_threshold(),
+            category_rules: Vec::new(),
+            tracking_interval: default_tracking_interval(),
+            flush_interval: default_flush_interval(),
+            flush_max_records: default_flush_max_records(),
+        }
+    }
+}
+
 pub struct ConfigManagerImpl {
     storage: Arc<dyn Storage>,
     config: RwLock<AppConfig>,
@@ -72,9 +767,14 @@ pub struct ConfigManagerImpl {
 
 impl ConfigManagerImpl {
     pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self::with_config(storage, AppConfig::default())
+    }
+
+    /// 以启动时已读取的配置初始化，避免再次读取存储
+    pub fn with_config(storage: Arc<dyn Storage>, config: AppConfig) -> Self {
         Self {
             storage,
-            config: RwLock::new(AppConfig::default()),
+            config: RwLock::new(config),
         }
     }
 }
@@ -82,21 +782,24 @@ impl ConfigManagerImpl {
 #[async_trait]
 impl ConfigManager for ConfigManagerImpl {
     async fn save_config(&self, config: &AppConfig) -> AppResult<()> {
+        self.storage.save_config(config).await?;
         *self.config.write().await = config.clone();
         Ok(())
     }
 
     async fn load_config(&self) -> AppResult<AppConfig> {
-        Ok(self.config.read().await.clone())
+        let config = self.storage.get_config().await?.unwrap_or_default();
+        *self.config.write().await = config.clone();
+        Ok(config)
     }
 
     async fn get_config(&self) -> AppResult<AppConfig> {
         Ok(self.config.read().await.clone())
     }
 
+    /// 写入存储后再更新内存中的配置，重启后仍然生效
     async fn update_config(&self, config: AppConfig) -> AppResult<()> {
-        *self.config.write().await = config;
-        Ok(())
+        self.save_config(&config).await
     }
 }
 
@@ -108,6 +811,16 @@ impl Default for AppConfig {
                 short_break_duration: Duration::from_secs(5 * 60),
                 long_break_duration: Duration::from_secs(15 * 60),
                 long_break_interval: 4,
+                auto_start_breaks: false,
+                keep_awake_during_work: false,
+                min_enforced_break: Duration::ZERO,
+                micro_break_every: None,
+                timing_source: TimingSource::default(),
+                snooze_duration: default_snooze_duration(),
+                max_snoozes: default_max_snoozes(),
+                sound_profile: HashMap::new(),
+                presets: default_pomodoro_presets(),
+                active_preset: None,
             },
             notification: NotificationSettings {
                 enable_system_notifications: true,
@@ -121,6 +834,12 @@ impl Default for AppConfig {
                 show_system_tray: true,
                 minimize_to_tray: true,
                 start_minimized: false,
+                start_hidden: false,
+                usage_group_by: GroupBy::default(),
+                single_instance: true,
+                refresh: RefreshSettings::default(),
+                dashboard_widgets: default_dashboard_widgets(),
+                week_start: default_week_start(),
             },
             storage: StorageSettings {
                 database_path: "time_tracker.db".into(),
@@ -128,6 +847,10 @@ impl Default for AppConfig {
                 auto_backup: true,
                 backup_interval_days: 7,
                 backup_retention_days: 30,
+                busy_timeout: default_busy_timeout(),
+                synchronous: Synchronous::default(),
+                backup_before_destructive: true,
+                bulk_delete_threshold: default_bulk_delete_threshold(),
             },
             rules: RuleSettings {
                 auto_categorize: true,
@@ -135,6 +858,15 @@ impl Default for AppConfig {
                 min_activity_duration: Duration::from_secs(60),
                 suggestion_threshold: 10,
             },
+            tracking: TrackingSettings::default(),
+            export: ExportSettings::default(),
+            maintenance: MaintenanceSettings::default(),
+            power: PowerSettings::default(),
+            crash_report: CrashReportSettings::default(),
+            focus: FocusSettings::default(),
+            categories: CategorySettings::default(),
+            webhook: None,
+            digest: None,
         }
     }
 }
@@ -154,6 +886,99 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_categories() -> AppResult<()> {
+        let mut config = AppConfig::default();
+        config.categories.add(" Research ")?;
+        config.categories.add("Gaming")?;
+        config.tracking.category_rules.push(CategoryRuleSettings {
+            pattern: "(?i)steam".into(),
+            category: "Gaming".into(),
+        });
+
+        assert!(config.categories.add("research").is_err());
+        assert!(config.categories.add("Development").is_err());
+        assert!(config.rename_category("development", "Coding").is_err());
+        assert!(config.rename_category("Gaming", "research").is_err());
+
+        config.rename_category("Gaming", "Gaming (work)")?;
+        assert_eq!(config.categories.custom, vec!["Research", "Gaming (work)"]);
+        assert_eq!(config.tracking.category_rules[0].category, "Gaming (work)");
+        // 只改大小写也允许
+        config.rename_category("Research", "research")?;
+
+        let json = serde_json::to_string(&config)?;
+        assert_eq!(serde_json::from_str::<AppConfig>(&json)?, config);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_category_rules_map() -> AppResult<()> {
+        let settings: TrackingSettings = serde_json::from_str(
+            r#"{"enabled": true, "category_rules": {"code": "development", "code review": "communication"}}"#,
+        )?;
+        assert_eq!(
+            settings.category_rules,
+            vec![
+                CategoryRuleSettings {
+                    pattern: "(?i)code review".into(),
+                    category: "communication".into(),
+                },
+                CategoryRuleSettings {
+                    pattern: "(?i)code".into(),
+                    category: "development".into(),
+                },
+            ]
+        );
+
+        // 保存后为有序列表，可以原样读回
+        let json = serde_json::to_string(&settings)?;
+        assert_eq!(serde_json::from_str::<TrackingSettings>(&json)?, settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sound_profile() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let chime = dir.path().join("chime.wav");
+        std::fs::write(&chime, b"")?;
+
+        let mut settings = AppConfig::default().pomodoro;
+        settings.sound_profile.insert(PomodoroEvent::WorkComplete, Some(chime.clone()));
+        settings.sound_profile.insert(PomodoroEvent::BreakComplete, None);
+        assert_eq!(settings.sound_for(PomodoroEvent::WorkComplete), Some(chime.as_path()));
+        assert_eq!(settings.sound_for(PomodoroEvent::BreakComplete), None);
+        assert_eq!(settings.sound_for(PomodoroEvent::Interrupted), None);
+        settings.validate_sound_profile()?;
+
+        let json = serde_json::to_string(&settings)?;
+        assert_eq!(serde_json::from_str::<PomodoroSettings>(&json)?, settings);
+
+        settings
+            .sound_profile
+            .insert(PomodoroEvent::Interrupted, Some(dir.path().join("missing.wav")));
+        assert!(settings.validate_sound_profile().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_webhook_validation() -> AppResult<()> {
+        let settings: WebhookSettings = serde_json::from_str(r#"{"url": "https://example.com/hook"}"#)?;
+        settings.validate()?;
+        assert!(settings.subscribes(WebhookEvent::PomodoroCompleted));
+        assert!(!settings.subscribes(WebhookEvent::ActivityRecorded));
+
+        for url in ["ftp://example.com/hook", "file:///tmp/hook", "example.com/hook"] {
+            assert!(matches!(WebhookSettings::new(url).validate(), Err(AppError::Config(_))), "{}", url);
+        }
+        let no_events = WebhookSettings {
+            events: Vec::new(),
+            ..WebhookSettings::new("http://localhost:8080")
+        };
+        assert!(no_events.validate().is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_config_lifecycle() -> AppResult<()> {
         let mut mock_storage = MockStorage::new();
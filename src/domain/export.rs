@@ -1,18 +1,1082 @@
-use crate::core::{AppResult, models::*};
+use crate::core::{AppError, AppResult, models::*};
 use crate::core::traits::Storage;
-use chrono::{DateTime, Local};
+use crate::domain::anonymize::{anonymize, AnonymizeMode};
+use crate::domain::config::ExportSettings;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use serde_json;
 use csv;
 use std::time::Duration;
+use std::path::{Path, PathBuf};
+use handlebars::{handlebars_helper, Handlebars};
+
+/// 自定义模板文件的大小上限
+const MAX_TEMPLATE_SIZE: u64 = 1024 * 1024;
+
+/// `write_usage_csv` 每批读取的行数
+const USAGE_EXPORT_BATCH_SIZE: u32 = 5000;
+const HTML_TEMPLATE_NAME: &str = "report";
+
+/// 一次导出包含的全部记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportData {
+    pub activities: Vec<Activity>,
+    pub pomodoros: Vec<PomodoroSession>,
+    pub projects: Vec<Project>,
+}
+
+/// 导出的详细程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DetailLevel {
+    /// 只导出每日汇总，不包含单条记录，便于分享
+    Summary,
+    #[default]
+    Detailed,
+}
+
+/// 按记录时的本地日期汇总的一天
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyAggregate {
+    pub date: NaiveDate,
+    pub total_time: Duration,
+    pub productive_time: Duration,
+    pub activity_count: usize,
+    pub completed_pomodoros: usize,
+    pub focus_time: Duration,
+}
+
+impl DailyAggregate {
+    fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            total_time: Duration::ZERO,
+            productive_time: Duration::ZERO,
+            activity_count: 0,
+            completed_pomodoros: 0,
+            focus_time: Duration::ZERO,
+        }
+    }
+}
+
+pub fn daily_aggregates(data: &ExportData) -> Vec<DailyAggregate> {
+    let mut days: BTreeMap<NaiveDate, DailyAggregate> = BTreeMap::new();
+
+    for activity in &data.activities {
+        let date = activity.local_date();
+        let entry = days.entry(date).or_insert_with(|| DailyAggregate::empty(date));
+        entry.total_time += activity.duration;
+        if activity.is_productive {
+            entry.productive_time += activity.duration;
+        }
+        entry.activity_count += 1;
+    }
+    for session in data.pomodoros.iter().filter(|s| s.status == PomodoroStatus::Completed) {
+        let date = session.local_date();
+        let entry = days.entry(date).or_insert_with(|| DailyAggregate::empty(date));
+        entry.completed_pomodoros += 1;
+        entry.focus_time += session.duration;
+    }
+
+    days.into_values().collect()
+}
+
+/// 报表页脚：总时长、生产性占比以及类别图例
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub total_time: Duration,
+    pub productive_time: Duration,
+    pub unproductive_time: Duration,
+    pub activity_count: usize,
+    pub completed_pomodoros: usize,
+    pub categories: Vec<CategoryLegend>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryLegend {
+    pub name: String,
+    pub color: String,
+    pub total_time: Duration,
+}
+
+const LEGEND_COLORS: [&str; 8] = [
+    "#4E79A7", "#F28E2B", "#E15759", "#76B7B2", "#59A14F", "#EDC948", "#B07AA1", "#FF9DA7",
+];
+
+/// 为类别分配稳定的图例颜色，同一类别在不同报表中颜色一致
+pub fn category_color(category: &str) -> &'static str {
+    let hash = category
+        .bytes()
+        .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    LEGEND_COLORS[hash as usize % LEGEND_COLORS.len()]
+}
+
+/// 所有导出格式共用的汇总计算
+pub fn summarize(data: &ExportData) -> ExportSummary {
+    let mut productive_time = Duration::ZERO;
+    let mut unproductive_time = Duration::ZERO;
+    let mut categories: BTreeMap<&str, Duration> = BTreeMap::new();
+
+    for activity in &data.activities {
+        if activity.is_productive {
+            productive_time += activity.duration;
+        } else {
+            unproductive_time += activity.duration;
+        }
+        *categories.entry(activity.category.as_str()).or_default() += activity.duration;
+    }
+
+    ExportSummary {
+        total_time: productive_time + unproductive_time,
+        productive_time,
+        unproductive_time,
+        activity_count: data.activities.len(),
+        completed_pomodoros: data
+            .pomodoros
+            .iter()
+            .filter(|s| s.status == PomodoroStatus::Completed)
+            .count(),
+        categories: categories
+            .into_iter()
+            .map(|(name, total_time)| CategoryLegend {
+                name: name.to_string(),
+                color: category_color(name).to_string(),
+                total_time,
+            })
+            .collect(),
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 应用使用时长图最多显示的应用数
+const APP_CHART_LIMIT: usize = 10;
+const CHART_WIDTH: f64 = 600.0;
+const CHART_LABEL_WIDTH: f64 = 150.0;
+const BAR_HEIGHT: f64 = 18.0;
+const BAR_GAP: f64 = 6.0;
+const LINE_CHART_HEIGHT: f64 = 200.0;
+const LINE_CHART_PADDING: f64 = 30.0;
+
+/// 各应用的使用时长，按时长降序
+fn app_usage(activities: &[Activity]) -> Vec<(&str, Duration)> {
+    let mut apps: HashMap<&str, Duration> = HashMap::new();
+    for activity in activities {
+        *apps.entry(activity.app_name.as_str()).or_default() += activity.duration;
+    }
+    let mut apps: Vec<_> = apps.into_iter().collect();
+    apps.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    apps
+}
+
+/// 横向条形图，没有数据时返回 `None`
+fn bar_chart_svg(items: &[(&str, Duration)]) -> Option<String> {
+    let max = items.iter().map(|(_, d)| d.as_secs_f64()).fold(0.0, f64::max);
+    if max <= 0.0 {
+        return None;
+    }
+
+    let bar_area = CHART_WIDTH - CHART_LABEL_WIDTH - 80.0;
+    let height = items.len() as f64 * (BAR_HEIGHT + BAR_GAP);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-size=\"12\">\n",
+        w = CHART_WIDTH,
+        h = height
+    );
+    for (index, (name, duration)) in items.iter().enumerate() {
+        let y = index as f64 * (BAR_HEIGHT + BAR_GAP);
+        let width = duration.as_secs_f64() / max * bar_area;
+        let text_y = y + BAR_HEIGHT * 0.75;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            CHART_LABEL_WIDTH - 6.0,
+            text_y,
+            html_escape(name)
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"/>\n",
+            CHART_LABEL_WIDTH,
+            y,
+            width,
+            BAR_HEIGHT,
+            category_color(name)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+            CHART_LABEL_WIDTH + width + 6.0,
+            text_y,
+            ExportManager::format_duration(*duration)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+/// 每日完成番茄数的折线图，没有完成的番茄时返回 `None`
+fn line_chart_svg(points: &[(NaiveDate, usize)]) -> Option<String> {
+    let max = points.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    if max == 0 {
+        return None;
+    }
+
+    let plot_width = CHART_WIDTH - 2.0 * LINE_CHART_PADDING;
+    let plot_height = LINE_CHART_HEIGHT - 2.0 * LINE_CHART_PADDING;
+    let step = plot_width / (points.len().max(2) - 1) as f64;
+    let coords: Vec<(f64, f64)> = points
+        .iter()
+        .enumerate()
+        .map(|(index, (_, count))| {
+            let x = LINE_CHART_PADDING + index as f64 * step;
+            let y = LINE_CHART_HEIGHT - LINE_CHART_PADDING - *count as f64 / max as f64 * plot_height;
+            (x, y)
+        })
+        .collect();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-size=\"12\">\n",
+        w = CHART_WIDTH,
+        h = LINE_CHART_HEIGHT
+    );
+    let baseline = LINE_CHART_HEIGHT - LINE_CHART_PADDING;
+    svg.push_str(&format!(
+        "<line x1=\"{p}\" y1=\"{b}\" x2=\"{e}\" y2=\"{b}\" stroke=\"#999\"/>\n",
+        p = LINE_CHART_PADDING,
+        b = baseline,
+        e = CHART_WIDTH - LINE_CHART_PADDING
+    ));
+    let polyline: Vec<String> = coords.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+    svg.push_str(&format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+        polyline.join(" "),
+        LEGEND_COLORS[0]
+    ));
+    for ((x, y), (date, count)) in coords.iter().zip(points) {
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>{} {}</title></circle>\n",
+            x, y, LEGEND_COLORS[0], date, count
+        ));
+    }
+    // 只标注首尾日期，避免日期多时文字重叠
+    if let (Some((first, _)), Some((last, _))) = (points.first(), points.last()) {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>\n",
+            LINE_CHART_PADDING,
+            LINE_CHART_HEIGHT - 8.0,
+            first
+        ));
+        if points.len() > 1 {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\n",
+                CHART_WIDTH - LINE_CHART_PADDING,
+                LINE_CHART_HEIGHT - 8.0,
+                last
+            ));
+        }
+    }
+    svg.push_str(&format!(
+        "<text x=\"4\" y=\"{}\">{}</text>\n",
+        LINE_CHART_PADDING,
+        max
+    ));
+    svg.push_str("</svg>\n");
+    Some(svg)
+}
+
+/// 转义 Markdown 表格单元格中的竖线和换行
+fn markdown_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn markdown_table(markdown: &mut String, header: &[&str], rows: &[Vec<String>]) {
+    markdown.push_str(&format!("| {} |\n", header.join(" | ")));
+    markdown.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| markdown_cell(cell)).collect();
+        markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    markdown.push('\n');
+}
+
+handlebars_helper!(format_duration_helper: |duration: object| {
+    let secs = duration.get("secs").and_then(|v| v.as_u64()).unwrap_or_default();
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+});
+
+/// 用户提供的 HTML 报告模板
+///
+/// 模板只从字符串编译，不注册模板目录或局部模板，也没有可访问文件的 helper，
+/// 渲染时无法读取报告数据以外的任何内容。
+pub struct HtmlTemplate {
+    registry: Handlebars<'static>,
+}
+
+impl std::fmt::Debug for HtmlTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HtmlTemplate").finish_non_exhaustive()
+    }
+}
+
+impl HtmlTemplate {
+    /// 编译模板，语法错误时返回配置错误
+    pub fn compile(source: &str) -> AppResult<Self> {
+        let mut registry = Handlebars::new();
+        registry.set_dev_mode(false);
+        registry.register_escape_fn(handlebars::html_escape);
+        registry.register_helper("duration", Box::new(format_duration_helper));
+        registry
+            .register_template_string(HTML_TEMPLATE_NAME, source)
+            .map_err(|e| AppError::Config(format!("报告模板无效: {}", e)))?;
+        Ok(Self { registry })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> AppResult<Self> {
+        let path = path.as_ref();
+        let metadata = std::fs::metadata(path)?;
+        if !metadata.is_file() {
+            return Err(AppError::Config(format!("报告模板不是文件: {}", path.display())));
+        }
+        if metadata.len() > MAX_TEMPLATE_SIZE {
+            return Err(AppError::Config(format!("报告模板过大: {}", path.display())));
+        }
+        Self::compile(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn render(&self, data: &ExportData, summary: &ExportSummary, daily: &[DailyAggregate]) -> AppResult<String> {
+        let context = serde_json::json!({
+            "data": data,
+            "summary": summary,
+            "daily": daily,
+            "generated_at": Local::now().to_rfc3339(),
+        });
+        self.registry
+            .render(HTML_TEMPLATE_NAME, &context)
+            .map_err(|e| AppError::InvalidOperation(format!("渲染报告模板失败: {}", e)))
+    }
+}
+
+/// 单个项目的计费汇总
+#[derive(Debug, Clone, Serialize)]
+pub struct BillableProjectTotal {
+    pub project_id: i64,
+    pub project_name: String,
+    pub duration: Duration,
+    pub activity_count: usize,
+}
+
+/// 按项目分组的计费汇总，可直接用于开具账单
+#[derive(Debug, Clone, Serialize)]
+pub struct BillableSummary {
+    pub projects: Vec<BillableProjectTotal>,
+    pub total_duration: Duration,
+}
+
+impl BillableSummary {
+    pub fn calculate(activities: &[Activity], projects: &HashMap<i64, Project>) -> Self {
+        let mut totals: HashMap<i64, BillableProjectTotal> = HashMap::new();
+
+        for activity in filter_billable(activities, projects) {
+            let Some(project_id) = activity.project_id else { continue };
+            let entry = totals.entry(project_id).or_insert_with(|| BillableProjectTotal {
+                project_id,
+                project_name: projects.get(&project_id).map(|p| p.name.clone()).unwrap_or_default(),
+                duration: Duration::ZERO,
+                activity_count: 0,
+            });
+            entry.duration += activity.duration;
+            entry.activity_count += 1;
+        }
+
+        let mut projects: Vec<_> = totals.into_values().collect();
+        projects.sort_by(|a, b| a.project_name.cmp(&b.project_name));
+        let total_duration = projects.iter().map(|p| p.duration).sum();
+
+        Self { projects, total_duration }
+    }
+}
+
+/// 活动未单独设置时沿用所属项目的计费设置
+pub fn is_billable(activity: &Activity, projects: &HashMap<i64, Project>) -> bool {
+    activity.is_billable.unwrap_or_else(|| {
+        activity
+            .project_id
+            .and_then(|id| projects.get(&id))
+            .map(|p| p.is_billable)
+            .unwrap_or(false)
+    })
+}
+
+pub fn filter_billable(activities: &[Activity], projects: &HashMap<i64, Project>) -> Vec<Activity> {
+    activities
+        .iter()
+        .filter(|activity| is_billable(activity, projects))
+        .cloned()
+        .collect()
+}
+
+fn project_index(projects: &[Project]) -> HashMap<i64, Project> {
+    projects
+        .iter()
+        .filter_map(|p| p.id.map(|id| (id, p.clone())))
+        .collect()
+}
+
+/// 导出时显示项目名称，项目已删除时退回显示 id
+fn project_label(project_id: Option<i64>, projects: &HashMap<i64, Project>) -> String {
+    match project_id {
+        Some(id) => projects
+            .get(&id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| format!("#{}", id)),
+        None => String::new(),
+    }
+}
+
+/// iCalendar 内容行的最大字节数，超出时折行
+const ICAL_LINE_LIMIT: usize = 75;
+
+fn ical_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 按 RFC 5545 在字符边界处折行，续行以空格开头
+fn push_ical_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > ICAL_LINE_LIMIT {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// 固定偏移的时区标识，例如 `UTC+08:00`
+fn ical_tzid(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("UTC{}{:02}:{:02}", sign, seconds.abs() / 3600, seconds.abs() % 3600 / 60)
+}
+
+/// 日历中的一个时间块。时间按记录时的偏移显示，旧记录没有偏移时使用当时的本地偏移
+struct IcalEvent {
+    uid: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    offset: FixedOffset,
+    summary: String,
+    description: String,
+    category: Option<String>,
+}
+
+impl IcalEvent {
+    fn new(uid: String, start: DateTime<Local>, end: DateTime<Local>, utc_offset: Option<i32>) -> Self {
+        Self {
+            uid,
+            start,
+            end,
+            offset: utc_offset.and_then(FixedOffset::east_opt).unwrap_or(*start.offset()),
+            summary: String::new(),
+            description: String::new(),
+            category: None,
+        }
+    }
+
+    fn from_activity(activity: &Activity, projects: &HashMap<i64, Project>) -> Self {
+        let end = activity
+            .end_time
+            .unwrap_or_else(|| activity.start_time + chrono::Duration::from_std(activity.duration).unwrap_or_default());
+        let uid = match activity.id {
+            Some(id) => format!("activity-{}@time_tracker", id),
+            None => format!("activity-{}@time_tracker", activity.start_time.timestamp()),
+        };
+        let project = project_label(activity.project_id, projects);
+        let mut description = activity.window_title.clone();
+        if let Some(notes) = activity.description.as_deref().filter(|d| !d.is_empty()) {
+            description = format!("{}\n{}", description, notes);
+        }
+        Self {
+            summary: if project.is_empty() { activity.app_name.clone() } else { project },
+            description,
+            category: Some(activity.category.clone()).filter(|c| !c.is_empty()),
+            ..Self::new(uid, activity.start_time, end, activity.utc_offset)
+        }
+    }
+
+    fn from_pomodoro(session: &PomodoroSession, projects: &HashMap<i64, Project>) -> Self {
+        let end = session
+            .end_time
+            .unwrap_or_else(|| session.start_time + chrono::Duration::from_std(session.duration).unwrap_or_default());
+        let uid = match session.id {
+            Some(id) => format!("pomodoro-{}@time_tracker", id),
+            None => format!("pomodoro-{}@time_tracker", session.start_time.timestamp()),
+        };
+        let project = project_label(session.project_id, projects);
+        Self {
+            summary: if project.is_empty() { "番茄钟".into() } else { format!("番茄钟：{}", project) },
+            description: session.notes.clone().unwrap_or_default(),
+            category: session.category.clone(),
+            ..Self::new(uid, session.start_time, end, session.utc_offset)
+        }
+    }
+
+    fn write(&self, ics: &mut String, stamp: &str) {
+        let tzid = ical_tzid(self.offset);
+        let local = |time: &DateTime<Local>| time.with_timezone(&self.offset).format("%Y%m%dT%H%M%S");
+        push_ical_line(ics, "BEGIN:VEVENT");
+        push_ical_line(ics, &format!("UID:{}", self.uid));
+        push_ical_line(ics, &format!("DTSTAMP:{}", stamp));
+        push_ical_line(ics, &format!("DTSTART;TZID={}:{}", tzid, local(&self.start)));
+        push_ical_line(ics, &format!("DTEND;TZID={}:{}", tzid, local(&self.end)));
+        push_ical_line(ics, &format!("SUMMARY:{}", ical_escape(&self.summary)));
+        if !self.description.is_empty() {
+            push_ical_line(ics, &format!("DESCRIPTION:{}", ical_escape(&self.description)));
+        }
+        if let Some(category) = &self.category {
+            push_ical_line(ics, &format!("CATEGORIES:{}", ical_escape(category)));
+        }
+        push_ical_line(ics, "END:VEVENT");
+    }
+}
+
+/// 每条活动和已完成的番茄钟各一个 VEVENT。每个用到的偏移生成一个固定偏移的 VTIMEZONE，
+/// 导入 Outlook 等日历后显示的仍是记录时的本地时间
+fn render_ical(data: &ExportData, now: DateTime<Local>) -> String {
+    let projects = project_index(&data.projects);
+    let events: Vec<IcalEvent> = data
+        .activities
+        .iter()
+        .map(|activity| IcalEvent::from_activity(activity, &projects))
+        .chain(
+            data.pomodoros
+                .iter()
+                .filter(|s| s.status == PomodoroStatus::Completed)
+                .map(|session| IcalEvent::from_pomodoro(session, &projects)),
+        )
+        .collect();
+    let offsets: BTreeMap<i32, FixedOffset> = events
+        .iter()
+        .map(|event| (event.offset.local_minus_utc(), event.offset))
+        .collect();
+    let stamp = now.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut ics = String::new();
+    push_ical_line(&mut ics, "BEGIN:VCALENDAR");
+    push_ical_line(&mut ics, "VERSION:2.0");
+    push_ical_line(&mut ics, "PRODID:-//time_tracker//Time Tracker//ZH");
+    push_ical_line(&mut ics, "CALSCALE:GREGORIAN");
+    for offset in offsets.values() {
+        let tzid = ical_tzid(*offset);
+        let utc_offset = offset.to_string().replace(':', "");
+        push_ical_line(&mut ics, "BEGIN:VTIMEZONE");
+        push_ical_line(&mut ics, &format!("TZID:{}", tzid));
+        push_ical_line(&mut ics, "BEGIN:STANDARD");
+        push_ical_line(&mut ics, "DTSTART:19700101T000000");
+        push_ical_line(&mut ics, &format!("TZOFFSETFROM:{}", utc_offset));
+        push_ical_line(&mut ics, &format!("TZOFFSETTO:{}", utc_offset));
+        push_ical_line(&mut ics, &format!("TZNAME:{}", tzid));
+        push_ical_line(&mut ics, "END:STANDARD");
+        push_ical_line(&mut ics, "END:VTIMEZONE");
+    }
+    for event in &events {
+        event.write(&mut ics, &stamp);
+    }
+    push_ical_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// 按 id 排列项目，导出结果不随 HashMap 的遍历顺序变化
+fn sorted_projects(projects: HashMap<i64, Project>) -> Vec<Project> {
+    let mut projects: Vec<Project> = projects.into_values().collect();
+    projects.sort_by_key(|project| project.id);
+    projects
+}
 
 pub struct ExportManager {
     storage: Arc<dyn Storage + Send + Sync>,
+    billable_only: bool,
+    html_template: Option<HtmlTemplate>,
+    anonymize: AnonymizeMode,
+    mapping_path: Option<PathBuf>,
+    detail_level: DetailLevel,
 }
 
 impl ExportManager {
     pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            billable_only: false,
+            html_template: None,
+            anonymize: AnonymizeMode::None,
+            mapping_path: None,
+            detail_level: DetailLevel::Detailed,
+        }
+    }
+
+    pub fn with_detail_level(mut self, detail_level: DetailLevel) -> Self {
+        self.detail_level = detail_level;
+        self
+    }
+
+    /// 导出前替换可识别的名称，便于分享给他人排查问题
+    pub fn anonymize(mut self, mode: AnonymizeMode) -> Self {
+        self.anonymize = mode;
+        self
+    }
+
+    /// 匿名导出时同时保存化名映射，便于之后对照
+    pub fn with_mapping_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mapping_path = Some(path.into());
+        self
+    }
+
+    fn prepare(&self, data: ExportData) -> AppResult<ExportData> {
+        if self.anonymize == AnonymizeMode::None {
+            return Ok(data);
+        }
+        let (data, map) = anonymize(&data, self.anonymize);
+        if let Some(path) = &self.mapping_path {
+            map.save(path)?;
+        }
+        Ok(data)
+    }
+
+    /// 使用自定义 HTML 模板，模板在此处编译校验，失败时不替换内置模板
+    pub fn with_html_template(mut self, path: impl AsRef<Path>) -> AppResult<Self> {
+        self.html_template = Some(HtmlTemplate::load(path)?);
+        Ok(self)
+    }
+
+    /// 应用配置中的导出设置。自定义模板无法读取或编译时记录警告并继续使用内置模板，
+    /// 不让一份坏模板导致整个导出失败
+    pub fn with_settings(mut self, settings: &ExportSettings) -> Self {
+        self.detail_level = settings.detail_level;
+        if let Some(path) = settings.html_template.as_deref().filter(|path| !path.trim().is_empty()) {
+            match HtmlTemplate::load(path) {
+                Ok(template) => self.html_template = Some(template),
+                Err(e) => log::warn!("无法使用报告模板 {}，改用内置模板: {}", path, e),
+            }
+        }
+        self
+    }
+
+    /// 只导出计费的活动
+    pub fn with_billable_only(mut self, billable_only: bool) -> Self {
+        self.billable_only = billable_only;
+        self
+    }
+
+    async fn project_map(&self) -> AppResult<HashMap<i64, Project>> {
+        Ok(self
+            .storage
+            .list_projects()
+            .await?
+            .into_iter()
+            .filter_map(|p| p.id.map(|id| (id, p)))
+            .collect())
+    }
+
+    pub async fn collect_data(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<ExportData> {
+        let mut activities = self.storage.get_activities(start, end).await?;
+        let projects = self.project_map().await?;
+        if self.billable_only {
+            activities = filter_billable(&activities, &projects);
+        }
+
+        self.prepare(ExportData {
+            activities,
+            pomodoros: self.storage.get_pomodoro_sessions(start, end).await?,
+            projects: sorted_projects(projects),
+        })
+    }
+
+    /// 导出完整报表（活动、番茄钟与汇总）
+    pub async fn export_report(&self, start: DateTime<Local>, end: DateTime<Local>, format: ExportFormat) -> AppResult<Vec<u8>> {
+        let data = self.collect_data(start, end).await?;
+        if self.detail_level == DetailLevel::Summary {
+            return self.export_daily_summary(&data, format);
+        }
+
+        match format {
+            ExportFormat::CSV => self.export_activities_to_csv(&data),
+            ExportFormat::JSON => {
+                let summary = summarize(&data);
+                self.export_to_json(&serde_json::json!({ "data": data, "summary": summary })).await
+            }
+            ExportFormat::HTML => Ok(self.export_html(&data)?.into_bytes()),
+            ExportFormat::Markdown => Ok(Self::render_markdown(&data, &summarize(&data), &daily_aggregates(&data)).into_bytes()),
+            ExportFormat::Excel => Err(crate::core::error::AppError::NotImplemented("Excel export not implemented yet".into())),
+            ExportFormat::ICal => Ok(render_ical(&data, Local::now()).into_bytes()),
+        }
+    }
+
+    /// 把完整报表写入单个 Markdown 文件，便于粘贴到 wiki 或日报中
+    pub async fn export_markdown<P: AsRef<Path>>(&self, start: DateTime<Local>, end: DateTime<Local>, path: P) -> AppResult<()> {
+        let markdown = self.export_report(start, end, ExportFormat::Markdown).await?;
+        tokio::fs::write(path, markdown).await?;
+        Ok(())
+    }
+
+    /// 导出为 iCalendar，`include_pomodoros` 时同时导出已完成的番茄钟
+    pub async fn export_ical(&self, start: DateTime<Local>, end: DateTime<Local>, include_pomodoros: bool) -> AppResult<Vec<u8>> {
+        let mut data = self.collect_data(start, end).await?;
+        if !include_pomodoros {
+            data.pomodoros.clear();
+        }
+        Ok(render_ical(&data, Local::now()).into_bytes())
+    }
+
+    fn write_summary_csv<W: std::io::Write>(wtr: &mut csv::Writer<W>, summary: &ExportSummary) -> AppResult<()> {
+        wtr.write_record(&[""])?;
+        wtr.write_record(&["Summary"])?;
+        wtr.write_record(&["Total Time", &Self::format_duration(summary.total_time)])?;
+        wtr.write_record(&["Productive", &Self::format_duration(summary.productive_time)])?;
+        wtr.write_record(&["Unproductive", &Self::format_duration(summary.unproductive_time)])?;
+        wtr.write_record(&["Activities", &summary.activity_count.to_string()])?;
+        wtr.write_record(&[""])?;
+        wtr.write_record(&["Category", "Color", "Duration"])?;
+        for category in &summary.categories {
+            wtr.write_record(&[
+                category.name.as_str(),
+                category.color.as_str(),
+                &Self::format_duration(category.total_time),
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// 每日汇总导出：只包含按天聚合的数据和整体汇总，不输出单条记录
+    fn export_daily_summary(&self, data: &ExportData, format: ExportFormat) -> AppResult<Vec<u8>> {
+        let summary = summarize(data);
+        let daily = daily_aggregates(data);
+
+        match format {
+            ExportFormat::CSV => {
+                let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
+                wtr.write_record(&[
+                    "Date",
+                    "Total Time",
+                    "Productive",
+                    "Activities",
+                    "Completed Pomodoros",
+                    "Focus Time",
+                ])?;
+                for day in &daily {
+                    wtr.write_record(&[
+                        day.date.to_string(),
+                        Self::format_duration(day.total_time),
+                        Self::format_duration(day.productive_time),
+                        day.activity_count.to_string(),
+                        day.completed_pomodoros.to_string(),
+                        Self::format_duration(day.focus_time),
+                    ])?;
+                }
+                Self::write_summary_csv(&mut wtr, &summary)?;
+                Ok(wtr.into_inner()?)
+            }
+            ExportFormat::JSON => Ok(serde_json::to_vec_pretty(&serde_json::json!({
+                "daily": daily,
+                "summary": summary,
+            }))?),
+            ExportFormat::HTML => {
+                let records = ExportData {
+                    projects: data.projects.clone(),
+                    ..Default::default()
+                };
+                let html = match &self.html_template {
+                    Some(template) => template.render(&records, &summary, &daily)?,
+                    None => Self::render_html(&records, &summary, Some(&daily)),
+                };
+                Ok(html.into_bytes())
+            }
+            ExportFormat::Markdown => {
+                let records = ExportData {
+                    projects: data.projects.clone(),
+                    ..Default::default()
+                };
+                Ok(Self::render_markdown(&records, &summary, &daily).into_bytes())
+            }
+            ExportFormat::Excel => Err(crate::core::error::AppError::NotImplemented("Excel export not implemented yet".into())),
+            ExportFormat::ICal => Err(crate::core::error::AppError::NotImplemented("iCalendar export of daily summaries not implemented yet".into())),
+        }
+    }
+
+    fn export_html(&self, data: &ExportData) -> AppResult<String> {
+        let summary = summarize(data);
+        match &self.html_template {
+            Some(template) => template.render(data, &summary, &daily_aggregates(data)),
+            None => Ok(Self::render_html(data, &summary, None)),
+        }
+    }
+
+    /// `daily` 不为空时显示每日汇总表，否则逐条显示活动
+    fn render_html(data: &ExportData, summary: &ExportSummary, daily: Option<&[DailyAggregate]>) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>时间追踪报告</title>\n</head>\n<body>\n",
+        );
+
+        html.push_str("<h1>时间追踪报告</h1>\n");
+
+        // 图表直接内嵌 SVG，离线也能打开；没有数据时只显示表格
+        let apps = app_usage(&data.activities);
+        let top_apps = &apps[..apps.len().min(APP_CHART_LIMIT)];
+        if let Some(chart) = bar_chart_svg(top_apps) {
+            html.push_str("<h2>应用使用时长</h2>\n");
+            html.push_str(&chart);
+        }
+        let days = match daily {
+            Some(daily) => daily.to_vec(),
+            None => daily_aggregates(data),
+        };
+        let points: Vec<(NaiveDate, usize)> = days.iter().map(|day| (day.date, day.completed_pomodoros)).collect();
+        if let Some(chart) = line_chart_svg(&points) {
+            html.push_str("<h2>每日完成番茄钟</h2>\n");
+            html.push_str(&chart);
+        }
+
+        html.push_str("<table>\n");
+        match daily {
+            Some(daily) => {
+                html.push_str("<tr><th>日期</th><th>总时长</th><th>生产性</th><th>活动数</th><th>完成番茄钟</th></tr>\n");
+                for day in daily {
+                    html.push_str(&format!(
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                        day.date,
+                        Self::format_duration(day.total_time),
+                        Self::format_duration(day.productive_time),
+                        day.activity_count,
+                        day.completed_pomodoros,
+                    ));
+                }
+            }
+            None => {
+                html.push_str("<tr><th>名称</th><th>开始时间</th><th>时长</th><th>类别</th><th>应用</th></tr>\n");
+                for activity in &data.activities {
+                    html.push_str(&format!(
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td style=\"color:{}\">{}</td><td>{}</td></tr>\n",
+                        html_escape(&activity.name),
+                        activity.start_time.format("%Y-%m-%d %H:%M"),
+                        Self::format_duration(activity.duration),
+                        category_color(&activity.category),
+                        html_escape(&activity.category),
+                        html_escape(&activity.app_name),
+                    ));
+                }
+            }
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<footer>\n<h2>汇总</h2>\n<ul>\n");
+        html.push_str(&format!("<li>总时长：{}</li>\n", Self::format_duration(summary.total_time)));
+        html.push_str(&format!("<li>生产性：{}</li>\n", Self::format_duration(summary.productive_time)));
+        html.push_str(&format!("<li>非生产性：{}</li>\n", Self::format_duration(summary.unproductive_time)));
+        html.push_str(&format!("<li>完成番茄钟：{}</li>\n", summary.completed_pomodoros));
+        html.push_str("</ul>\n<h3>类别图例</h3>\n<ul>\n");
+        for category in &summary.categories {
+            html.push_str(&format!(
+                "<li><span style=\"display:inline-block;width:12px;height:12px;background:{}\"></span> {} ({})</li>\n",
+                category.color,
+                html_escape(&category.name),
+                Self::format_duration(category.total_time),
+            ));
+        }
+        html.push_str("</ul>\n</footer>\n</body>\n</html>\n");
+
+        html
+    }
+
+    /// GitHub 风格的 Markdown 表格，没有记录的部分不输出
+    fn render_markdown(data: &ExportData, summary: &ExportSummary, daily: &[DailyAggregate]) -> String {
+        let mut markdown = String::from("# 时间追踪报告\n\n## 汇总\n\n");
+        markdown_table(
+            &mut markdown,
+            &["指标", "数值"],
+            &[
+                vec!["总时长".into(), Self::format_duration(summary.total_time)],
+                vec!["生产性".into(), Self::format_duration(summary.productive_time)],
+                vec!["非生产性".into(), Self::format_duration(summary.unproductive_time)],
+                vec!["完成番茄钟".into(), summary.completed_pomodoros.to_string()],
+            ],
+        );
+
+        let apps = app_usage(&data.activities);
+        if !apps.is_empty() {
+            markdown.push_str("## 应用使用时长\n\n");
+            let rows: Vec<Vec<String>> = apps
+                .iter()
+                .map(|(name, duration)| vec![name.to_string(), Self::format_duration(*duration)])
+                .collect();
+            markdown_table(&mut markdown, &["应用", "时长"], &rows);
+        }
+
+        let project_names: HashMap<i64, &str> = data
+            .projects
+            .iter()
+            .filter_map(|project| project.id.map(|id| (id, project.name.as_str())))
+            .collect();
+        if !data.pomodoros.is_empty() {
+            markdown.push_str("## 番茄钟\n\n");
+            let rows: Vec<Vec<String>> = data
+                .pomodoros
+                .iter()
+                .map(|session| {
+                    vec![
+                        session.start_time.format("%Y-%m-%d %H:%M").to_string(),
+                        Self::format_duration(session.duration),
+                        format!("{:?}", session.status),
+                        session
+                            .project_id
+                            .and_then(|id| project_names.get(&id).copied())
+                            .unwrap_or("-")
+                            .to_string(),
+                    ]
+                })
+                .collect();
+            markdown_table(&mut markdown, &["开始时间", "时长", "状态", "项目"], &rows);
+        }
+
+        if !data.projects.is_empty() {
+            markdown.push_str("## 项目\n\n");
+            let mut projects: Vec<&Project> = data.projects.iter().collect();
+            projects.sort_by(|a, b| a.name.cmp(&b.name));
+            let rows: Vec<Vec<String>> = projects
+                .iter()
+                .map(|project| {
+                    let total: Duration = data
+                        .activities
+                        .iter()
+                        .filter(|activity| activity.project_id.is_some() && activity.project_id == project.id)
+                        .map(|activity| activity.duration)
+                        .sum();
+                    vec![
+                        project.name.clone(),
+                        project.description.clone().unwrap_or_default(),
+                        Self::format_duration(total),
+                    ]
+                })
+                .collect();
+            markdown_table(&mut markdown, &["项目", "描述", "时长"], &rows);
+        }
+
+        if !daily.is_empty() {
+            markdown.push_str("## 每日汇总\n\n");
+            let rows: Vec<Vec<String>> = daily
+                .iter()
+                .map(|day| {
+                    vec![
+                        day.date.to_string(),
+                        Self::format_duration(day.total_time),
+                        Self::format_duration(day.productive_time),
+                        day.activity_count.to_string(),
+                        day.completed_pomodoros.to_string(),
+                        Self::format_duration(day.focus_time),
+                    ]
+                })
+                .collect();
+            markdown_table(
+                &mut markdown,
+                &["日期", "总时长", "生产性", "活动数", "完成番茄钟", "专注时长"],
+                &rows,
+            );
+        }
+
+        markdown
+    }
+
+    pub async fn billable_summary(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<BillableSummary> {
+        let activities = self.storage.get_activities(start, end).await?;
+        let projects = self.project_map().await?;
+        Ok(BillableSummary::calculate(&activities, &projects))
+    }
+
+    pub async fn export_billable_summary(&self, start: DateTime<Local>, end: DateTime<Local>, format: ExportFormat) -> AppResult<Vec<u8>> {
+        let summary = self.billable_summary(start, end).await?;
+
+        match format {
+            ExportFormat::CSV => {
+                let mut wtr = csv::Writer::from_writer(Vec::new());
+                wtr.write_record(&["Project", "Duration", "Hours", "Activities"])?;
+                for project in &summary.projects {
+                    wtr.write_record(&[
+                        project.project_name.clone(),
+                        Self::format_duration(project.duration),
+                        format!("{:.2}", project.duration.as_secs_f64() / 3600.0),
+                        project.activity_count.to_string(),
+                    ])?;
+                }
+                wtr.write_record(&[
+                    "Total".to_string(),
+                    Self::format_duration(summary.total_duration),
+                    format!("{:.2}", summary.total_duration.as_secs_f64() / 3600.0),
+                    summary.projects.iter().map(|p| p.activity_count).sum::<usize>().to_string(),
+                ])?;
+                Ok(wtr.into_inner()?)
+            }
+            ExportFormat::JSON => self.export_to_json(&summary).await,
+            ExportFormat::HTML => Ok(Self::render_billable_html(&summary).into_bytes()),
+            ExportFormat::Markdown => Ok(Self::render_billable_markdown(&summary).into_bytes()),
+            ExportFormat::Excel => Err(crate::core::error::AppError::NotImplemented("Excel export not implemented yet".into())),
+            // 汇总没有起止时间，无法生成日历事件
+            ExportFormat::ICal => Err(crate::core::error::AppError::InvalidOperation("Billable summaries cannot be exported as iCalendar".into())),
+        }
+    }
+
+    fn billable_rows(summary: &BillableSummary) -> Vec<Vec<String>> {
+        let mut rows: Vec<Vec<String>> = summary
+            .projects
+            .iter()
+            .map(|project| {
+                vec![
+                    project.project_name.clone(),
+                    Self::format_duration(project.duration),
+                    format!("{:.2}", project.duration.as_secs_f64() / 3600.0),
+                    project.activity_count.to_string(),
+                ]
+            })
+            .collect();
+        rows.push(vec![
+            "合计".into(),
+            Self::format_duration(summary.total_duration),
+            format!("{:.2}", summary.total_duration.as_secs_f64() / 3600.0),
+            summary.projects.iter().map(|p| p.activity_count).sum::<usize>().to_string(),
+        ]);
+        rows
+    }
+
+    fn render_billable_html(summary: &BillableSummary) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>计费汇总</title>\n</head>\n<body>\n",
+        );
+        html.push_str("<h1>计费汇总</h1>\n<table>\n");
+        html.push_str("<tr><th>项目</th><th>时长</th><th>小时</th><th>活动数</th></tr>\n");
+        for row in Self::billable_rows(summary) {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", html_escape(&cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+
+    fn render_billable_markdown(summary: &BillableSummary) -> String {
+        let mut markdown = String::from("# 计费汇总\n\n");
+        markdown_table(&mut markdown, &["项目", "时长", "小时", "活动数"], &Self::billable_rows(summary));
+        markdown
     }
 
     fn format_duration(duration: std::time::Duration) -> String {
@@ -23,8 +1087,9 @@ impl ExportManager {
         format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
     }
 
-    async fn export_activities_to_csv(&self, activities: &[Activity]) -> AppResult<Vec<u8>> {
-        let mut wtr = csv::Writer::from_writer(Vec::new());
+    fn export_activities_to_csv(&self, data: &ExportData) -> AppResult<Vec<u8>> {
+        // 页脚汇总的列数与明细不同
+        let mut wtr = csv::WriterBuilder::new().flexible(true).from_writer(Vec::new());
         
         wtr.write_record(&[
             "ID",
@@ -38,16 +1103,12 @@ impl ExportManager {
             "App Name",
             "Window Title",
             "Description",
+            "Manual",
         ])?;
 
-        for activity in activities {
-            let project_name = if let Some(project_id) = activity.project_id {
-                self.storage.get_project(project_id).await
-                    .map(|p| p.name)
-                    .unwrap_or_default()
-            } else {
-                String::new()
-            };
+        let projects = project_index(&data.projects);
+        for activity in &data.activities {
+            let project_name = project_label(activity.project_id, &projects);
 
             wtr.write_record(&[
                 activity.id.map(|id| id.to_string()).unwrap_or_default(),
@@ -61,13 +1122,55 @@ impl ExportManager {
                 activity.app_name.clone(),
                 activity.window_title.clone(),
                 activity.description.clone().unwrap_or_default(),
+                if activity.manual { "Yes" } else { "No" }.to_string(),
             ])?;
         }
 
+        let summary = summarize(&ExportData {
+            activities: data.activities.clone(),
+            ..Default::default()
+        });
+        Self::write_summary_csv(&mut wtr, &summary)?;
+
         Ok(wtr.into_inner()?)
     }
 
-    async fn export_pomodoros_to_csv(&self, sessions: &[PomodoroSession]) -> AppResult<Vec<u8>> {
+    /// 分批读取 app_usage 并逐行写入 CSV，内存占用与时间范围无关，适合导出一年以上的记录。
+    /// 不支持匿名化和只导出计费活动，返回写入的记录数
+    pub async fn write_usage_csv<W: std::io::Write>(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        writer: W,
+    ) -> AppResult<u64> {
+        if self.anonymize != AnonymizeMode::None || self.billable_only {
+            return Err(AppError::InvalidOperation("逐条导出不支持匿名化或只导出计费活动".into()));
+        }
+        let projects = self.project_map().await?;
+        let mut wtr = csv::Writer::from_writer(writer);
+        wtr.write_record(["ID", "Start Time", "Duration", "Project", "Category", "App Name", "Window Title"])?;
+
+        let mut rows = 0;
+        let mut stream = self.storage.stream_app_usage((start, end), USAGE_EXPORT_BATCH_SIZE).await?;
+        while let Some(batch) = stream.next_batch().await? {
+            for record in batch {
+                wtr.write_record(&[
+                    record.id.to_string(),
+                    record.start_time.to_rfc3339(),
+                    Self::format_duration(Duration::from_secs(record.duration.max(0) as u64)),
+                    project_label(record.project_id, &projects),
+                    record.category.unwrap_or_default(),
+                    record.app_name,
+                    record.window_title,
+                ])?;
+                rows += 1;
+            }
+            wtr.flush()?;
+        }
+        Ok(rows)
+    }
+
+    fn export_pomodoros_to_csv(&self, data: &ExportData) -> AppResult<Vec<u8>> {
         let mut wtr = csv::Writer::from_writer(Vec::new());
         
         wtr.write_record(&[
@@ -77,17 +1180,13 @@ impl ExportManager {
             "Duration",
             "Status",
             "Project",
+            "Tags",
             "Notes",
         ])?;
 
-        for session in sessions {
-            let project_name = if let Some(project_id) = session.project_id {
-                self.storage.get_project(project_id).await
-                    .map(|p| p.name)
-                    .unwrap_or_default()
-            } else {
-                String::new()
-            };
+        let projects = project_index(&data.projects);
+        for session in &data.pomodoros {
+            let project_name = project_label(session.project_id, &projects);
 
             wtr.write_record(&[
                 session.id.map(|id| id.to_string()).unwrap_or_default(),
@@ -96,6 +1195,7 @@ impl ExportManager {
                 Self::format_duration(session.duration),
                 format!("{:?}", session.status),
                 project_name,
+                session.tags.join(", "),
                 session.notes.clone().unwrap_or_default(),
             ])?;
         }
@@ -111,22 +1211,47 @@ impl ExportManager {
 #[async_trait::async_trait]
 impl ExportService for ExportManager {
     async fn export_activities(&self, start: DateTime<Local>, end: DateTime<Local>, format: ExportFormat) -> AppResult<Vec<u8>> {
-        let activities = self.storage.get_activities(start, end).await?;
-        
+        let mut activities = self.storage.get_activities(start, end).await?;
+        let projects = self.project_map().await?;
+        if self.billable_only {
+            activities = filter_billable(&activities, &projects);
+        }
+        let data = self.prepare(ExportData {
+            activities,
+            pomodoros: Vec::new(),
+            projects: sorted_projects(projects),
+        })?;
+        if self.detail_level == DetailLevel::Summary {
+            return self.export_daily_summary(&data, format);
+        }
+
         match format {
-            ExportFormat::CSV => self.export_activities_to_csv(&activities).await,
-            ExportFormat::JSON => self.export_to_json(&activities).await,
+            ExportFormat::CSV => self.export_activities_to_csv(&data),
+            ExportFormat::JSON => self.export_to_json(&data.activities).await,
+            ExportFormat::HTML => Ok(self.export_html(&data)?.into_bytes()),
+            ExportFormat::Markdown => Ok(Self::render_markdown(&data, &summarize(&data), &daily_aggregates(&data)).into_bytes()),
             ExportFormat::Excel => Err(crate::core::error::AppError::NotImplemented("Excel export not implemented yet".into())),
+            ExportFormat::ICal => Ok(render_ical(&data, Local::now()).into_bytes()),
         }
     }
 
     async fn export_pomodoros(&self, start: DateTime<Local>, end: DateTime<Local>, format: ExportFormat) -> AppResult<Vec<u8>> {
-        let sessions = self.storage.get_pomodoro_sessions(start, end).await?;
-        
+        let data = self.prepare(ExportData {
+            activities: Vec::new(),
+            pomodoros: self.storage.get_pomodoro_sessions(start, end).await?,
+            projects: sorted_projects(self.project_map().await?),
+        })?;
+        if self.detail_level == DetailLevel::Summary {
+            return self.export_daily_summary(&data, format);
+        }
+
         match format {
-            ExportFormat::CSV => self.export_pomodoros_to_csv(&sessions).await,
-            ExportFormat::JSON => self.export_to_json(&sessions).await,
+            ExportFormat::CSV => self.export_pomodoros_to_csv(&data),
+            ExportFormat::JSON => self.export_to_json(&data.pomodoros).await,
+            ExportFormat::HTML => Err(crate::core::error::AppError::NotImplemented("HTML pomodoro export not implemented yet".into())),
+            ExportFormat::Markdown => Ok(Self::render_markdown(&data, &summarize(&data), &daily_aggregates(&data)).into_bytes()),
             ExportFormat::Excel => Err(crate::core::error::AppError::NotImplemented("Excel export not implemented yet".into())),
+            ExportFormat::ICal => Ok(render_ical(&data, Local::now()).into_bytes()),
         }
     }
 }
@@ -135,8 +1260,442 @@ impl ExportService for ExportManager {
 mod tests {
     use super::*;
 
+    fn activity(project_id: i64, duration_secs: u64) -> Activity {
+        Activity {
+            id: None,
+            name: "coding".into(),
+            start_time: Local::now(),
+            end_time: None,
+            project_id: Some(project_id),
+            description: None,
+            duration: Duration::from_secs(duration_secs),
+            category: "work".into(),
+            is_productive: true,
+            app_name: "editor".into(),
+            window_title: "main.rs".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    fn project(id: i64, name: &str, is_billable: bool) -> Project {
+        let mut project = Project::new(name.into(), None);
+        project.id = Some(id);
+        project.is_billable = is_billable;
+        project
+    }
+
+    #[test]
+    fn test_summary_totals_match_records() {
+        let mut idle = activity(1, 900);
+        idle.is_productive = false;
+        idle.category = "entertainment".into();
+        let data = ExportData {
+            activities: vec![activity(1, 3600), activity(2, 1200), idle],
+            ..Default::default()
+        };
+
+        let summary = summarize(&data);
+        let total: Duration = data.activities.iter().map(|a| a.duration).sum();
+        assert_eq!(summary.total_time, total);
+        assert_eq!(summary.productive_time + summary.unproductive_time, total);
+        assert_eq!(summary.unproductive_time, Duration::from_secs(900));
+        assert_eq!(summary.categories.iter().map(|c| c.total_time).sum::<Duration>(), total);
+        assert_eq!(summary.categories.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_summary_detail_level_has_no_record_rows() -> AppResult<()> {
+        use crate::core::traits::MockStorage;
+
+        let mut idle = activity(2, 900);
+        idle.is_productive = false;
+        idle.app_name = "video".into();
+        let activities = vec![activity(1, 3600), activity(1, 1200), idle];
+        let mut mock_storage = MockStorage::new();
+        let records = activities.clone();
+        mock_storage.expect_get_activities().returning(move |_, _| Ok(records.clone()));
+        mock_storage.expect_get_pomodoro_sessions().returning(|_, _| Ok(Vec::new()));
+        mock_storage
+            .expect_list_projects()
+            .returning(|| Ok(vec![project(1, "Client", true), project(2, "Internal", false)]));
+
+        let manager = ExportManager::new(Arc::new(mock_storage)).with_detail_level(DetailLevel::Summary);
+        let (start, end) = (Local::now() - chrono::Duration::days(1), Local::now());
+
+        let csv = String::from_utf8(manager.export_report(start, end, ExportFormat::CSV).await?).unwrap();
+        assert!(!csv.contains("editor") && !csv.contains("video") && !csv.contains("main.rs"));
+        let today = activities[0].local_date().to_string();
+        assert!(csv.contains(&format!("{},01:35:00,01:20:00,3,0,00:00:00", today)));
+        assert!(csv.contains("Total Time,01:35:00"));
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&manager.export_report(start, end, ExportFormat::JSON).await?)?;
+        assert!(json.get("data").is_none());
+        assert_eq!(json["daily"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["summary"]["activity_count"], 3);
+
+        let html = String::from_utf8(manager.export_report(start, end, ExportFormat::HTML).await?).unwrap();
+        assert!(!html.contains("coding"));
+        assert!(html.contains(&today));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_usage_csv_streams_batches() -> AppResult<()> {
+        use crate::core::traits::{MockAppUsageBatches, MockStorage};
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_list_projects()
+            .returning(|| Ok(vec![project(1, "Client", true)]));
+        mock_storage.expect_stream_app_usage().returning(|_, batch_size| {
+            assert_eq!(batch_size, USAGE_EXPORT_BATCH_SIZE);
+            let mut batches: std::collections::VecDeque<Vec<AppUsageRecord>> = [vec![activity(1, 60), activity(1, 120)], vec![activity(2, 30)]]
+                .iter()
+                .map(|batch| batch.iter().map(AppUsageRecord::from).collect())
+                .collect();
+            let mut stream = MockAppUsageBatches::new();
+            stream.expect_next_batch().returning(move || Ok(batches.pop_front()));
+            Ok(Box::new(stream))
+        });
+
+        let manager = ExportManager::new(Arc::new(mock_storage));
+        let (start, end) = (Local::now() - chrono::Duration::days(400), Local::now());
+        let mut out = Vec::new();
+        assert_eq!(manager.write_usage_csv(start, end, &mut out).await?, 3);
+        let csv = String::from_utf8(out).unwrap();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().nth(1).unwrap().contains(",00:01:00,Client,"));
+        assert!(csv.lines().nth(3).unwrap().contains(",#2,"));
+
+        let anonymized = manager.anonymize(AnonymizeMode::Full);
+        assert!(anonymized.write_usage_csv(start, end, Vec::new()).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_markdown_file() -> AppResult<()> {
+        use crate::core::traits::MockStorage;
+
+        let mut piped = activity(1, 600);
+        piped.app_name = "a|b".into();
+        let activities = vec![activity(1, 3600), piped];
+        let session = PomodoroSession {
+            id: Some(1),
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: Some(1),
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        };
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_activities().returning(move |_, _| Ok(activities.clone()));
+        mock_storage
+            .expect_get_pomodoro_sessions()
+            .returning(move |_, _| Ok(vec![session.clone()]));
+        mock_storage.expect_list_projects().returning(|| Ok(vec![project(1, "Client", true)]));
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("report.md");
+        ExportManager::new(Arc::new(mock_storage))
+            .export_markdown(Local::now() - chrono::Duration::days(1), Local::now(), &path)
+            .await?;
+
+        let markdown = String::from_utf8(std::fs::read(&path)?).expect("markdown is not valid UTF-8");
+        for header in [
+            "| 指标 | 数值 |",
+            "| 应用 | 时长 |",
+            "| 开始时间 | 时长 | 状态 | 项目 |",
+            "| 项目 | 描述 | 时长 |",
+            "| 日期 | 总时长 | 生产性 | 活动数 | 完成番茄钟 | 专注时长 |",
+        ] {
+            assert!(markdown.contains(header), "missing {}", header);
+        }
+        assert!(markdown.contains("| editor | 01:00:00 |"));
+        assert!(markdown.contains("| a\\|b | 00:10:00 |"));
+        assert!(markdown.contains("| Completed | Client |"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_category_names_in_html() {
+        let mut research = activity(1, 1800);
+        research.category = "Research & <Games>".into();
+        let data = ExportData {
+            activities: vec![research, activity(1, 600)],
+            ..Default::default()
+        };
+
+        let summary = summarize(&data);
+        assert!(summary.categories.iter().any(|c| c.name == "Research & <Games>"));
+        let html = ExportManager::render_html(&data, &summary, None);
+        assert!(html.contains("Research &amp; &lt;Games&gt;"));
+        assert!(!html.contains("<Games>"));
+    }
+
+    #[test]
+    fn test_html_embeds_svg_charts() {
+        let mut browser = activity(1, 600);
+        browser.app_name = "browser <dev>".into();
+        let session = PomodoroSession {
+            id: None,
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        };
+        let data = ExportData {
+            activities: vec![activity(1, 3600), browser],
+            pomodoros: vec![session],
+            ..Default::default()
+        };
+
+        let html = ExportManager::render_html(&data, &summarize(&data), None);
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert_eq!(html.matches("<rect").count(), 2);
+        assert!(html.contains("<polyline"));
+        assert!(html.contains("browser &lt;dev&gt;"));
+        assert!(!html.contains("<script"));
+
+        // 没有数据时只保留表格
+        let empty = ExportData::default();
+        let html = ExportManager::render_html(&empty, &summarize(&empty), None);
+        assert!(!html.contains("<svg"));
+        assert!(html.contains("<table>"));
+    }
+
     #[tokio::test]
     async fn test_export_manager() {
         // TODO: 添加测试用例
     }
+
+    #[test]
+    fn test_custom_html_template() -> AppResult<()> {
+        use crate::core::traits::MockStorage;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("report.hbs");
+        std::fs::write(
+            &path,
+            "<h1>ACME report</h1><p>{{summary.activity_count}} activities, {{duration summary.total_time}}</p>\
+             {{#each data.activities}}<i>{{name}}</i>{{/each}}",
+        )?;
+
+        let manager = ExportManager::new(Arc::new(MockStorage::new())).with_html_template(&path)?;
+        let mut tagged = activity(1, 1800);
+        tagged.name = "<script>".into();
+        let data = ExportData {
+            activities: vec![activity(1, 3600), tagged],
+            ..Default::default()
+        };
+
+        let html = manager.export_html(&data)?;
+        assert!(html.starts_with("<h1>ACME report</h1>"));
+        assert!(html.contains("2 activities, 01:30:00"));
+        assert!(html.contains("&lt;script&gt;"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_template_falls_back_when_invalid() -> AppResult<()> {
+        use crate::core::traits::MockStorage;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("report.hbs");
+        std::fs::write(&path, "<h1>{{summary.activity_count}} activities</h1>")?;
+        let data = ExportData {
+            activities: vec![activity(1, 3600)],
+            ..Default::default()
+        };
+
+        let settings = ExportSettings {
+            html_template: Some(path.display().to_string()),
+            ..ExportSettings::default()
+        };
+        let html = ExportManager::new(Arc::new(MockStorage::new())).with_settings(&settings).export_html(&data)?;
+        assert_eq!(html, "<h1>1 activities</h1>");
+
+        // 模板无效时仍能用内置模板导出
+        std::fs::write(&path, "{{#each data.activities}}unclosed")?;
+        let html = ExportManager::new(Arc::new(MockStorage::new())).with_settings(&settings).export_html(&data)?;
+        assert!(html.contains("<table>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_html_template_is_rejected() {
+        assert!(HtmlTemplate::compile("{{#each data.activities}}unclosed").is_err());
+        assert!(HtmlTemplate::load("/nonexistent/report.hbs").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_anonymized_csv_export_writes_mapping() -> AppResult<()> {
+        use crate::core::traits::{ExportService, MockStorage};
+
+        let dir = tempfile::tempdir()?;
+        let mapping = dir.path().join("mapping.json");
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_activities()
+            .returning(|_, _| Ok(vec![activity(1, 3600)]));
+        mock_storage
+            .expect_list_projects()
+            .returning(|| Ok(vec![project(1, "Acme Merger", true)]));
+
+        let manager = ExportManager::new(Arc::new(mock_storage))
+            .anonymize(AnonymizeMode::Full)
+            .with_mapping_file(&mapping);
+        let csv = manager
+            .export_activities(Local::now() - chrono::Duration::days(1), Local::now(), ExportFormat::CSV)
+            .await?;
+        let csv = String::from_utf8(csv).unwrap();
+
+        assert!(!csv.contains("Acme Merger") && !csv.contains("main.rs") && !csv.contains("editor"));
+        assert!(csv.contains("Project A"));
+        assert!(csv.contains("01:00:00"));
+        assert!(std::fs::read_to_string(&mapping)?.contains("Acme Merger"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pomodoro_csv_uses_project_names() -> AppResult<()> {
+        use crate::core::traits::{ExportService, MockStorage};
+
+        let session = |id: i64, project_id: i64| PomodoroSession {
+            id: Some(id),
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: Some(project_id),
+            notes: None,
+            tags: vec!["focus".into(), "review".into()],
+            category: None,
+            utc_offset: None,
+        };
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_pomodoro_sessions()
+            .returning(move |_, _| Ok(vec![session(1, 4217), session(2, 9)]));
+        mock_storage
+            .expect_list_projects()
+            .times(1)
+            .returning(|| Ok(vec![project(4217, "Client Work", true)]));
+
+        let manager = ExportManager::new(Arc::new(mock_storage));
+        let csv = manager
+            .export_pomodoros(Local::now() - chrono::Duration::days(1), Local::now(), ExportFormat::CSV)
+            .await?;
+        let csv = String::from_utf8(csv).unwrap();
+
+        assert!(csv.contains("Client Work"));
+        assert!(!csv.contains("4217"));
+        // 已删除的项目退回显示 id
+        assert!(csv.contains("#9"));
+        assert!(csv.contains("\"focus, review\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_billable_only_excludes_non_billable_project() {
+        let projects: HashMap<i64, Project> = [
+            (1, project(1, "Client", true)),
+            (2, project(2, "Internal", false)),
+        ]
+        .into_iter()
+        .collect();
+        let mut overridden = activity(2, 600);
+        overridden.is_billable = Some(true);
+        let activities = vec![activity(1, 3600), activity(2, 1800), overridden];
+
+        let billable = filter_billable(&activities, &projects);
+        assert_eq!(billable.len(), 2);
+        assert!(billable.iter().all(|a| a.project_id == Some(1) || a.is_billable == Some(true)));
+
+        let summary = BillableSummary::calculate(&activities, &projects);
+        assert_eq!(summary.projects.len(), 2);
+        assert_eq!(summary.total_duration, Duration::from_secs(4200));
+        let internal = summary.projects.iter().find(|p| p.project_id == 2).unwrap();
+        assert_eq!(internal.duration, Duration::from_secs(600));
+
+        let markdown = ExportManager::render_billable_markdown(&summary);
+        assert!(markdown.contains("| Client | 01:00:00 | 1.00 | 1 |"));
+        assert!(markdown.contains("| 合计 | 01:10:00 | 1.17 | 2 |"));
+        let html = ExportManager::render_billable_html(&summary);
+        assert!(html.contains("<td>Internal</td><td>00:10:00</td>"));
+    }
+
+    #[tokio::test]
+    async fn test_ical_export_uses_recorded_offset() -> AppResult<()> {
+        use chrono::TimeZone;
+        use crate::core::traits::MockStorage;
+
+        let start = Utc.with_ymd_and_hms(2024, 4, 1, 1, 0, 0).unwrap().with_timezone(&Local);
+        let mut billed = activity(1, 3600);
+        billed.id = Some(7);
+        billed.start_time = start;
+        billed.end_time = Some(start + chrono::Duration::hours(1));
+        billed.utc_offset = Some(8 * 3600);
+        let mut unassigned = activity(1, 1800);
+        unassigned.project_id = None;
+        unassigned.start_time = start + chrono::Duration::hours(2);
+        unassigned.utc_offset = Some(-5 * 3600);
+        unassigned.window_title = format!("review; notes, {}", "很长的窗口标题".repeat(10));
+        let session = |id: i64, status: PomodoroStatus| PomodoroSession {
+            id: Some(id),
+            start_time: start,
+            end_time: Some(start + chrono::Duration::minutes(25)),
+            duration: Duration::from_secs(25 * 60),
+            status,
+            project_id: Some(1),
+            notes: Some("spec".into()),
+            tags: Vec::new(),
+            category: None,
+            utc_offset: Some(8 * 3600),
+        };
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_activities()
+            .returning(move |_, _| Ok(vec![billed.clone(), unassigned.clone()]));
+        mock_storage.expect_get_pomodoro_sessions().returning(move |_, _| {
+            Ok(vec![session(1, PomodoroStatus::Completed), session(2, PomodoroStatus::Interrupted)])
+        });
+        mock_storage
+            .expect_list_projects()
+            .returning(|| Ok(vec![project(1, "Client", true)]));
+
+        let manager = ExportManager::new(Arc::new(mock_storage));
+        let (from, to) = (start, start + chrono::Duration::days(1));
+        let ics = String::from_utf8(manager.export_ical(from, to, true).await?).unwrap();
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains("UID:activity-7@time_tracker"));
+        assert!(ics.contains("DTSTART;TZID=UTC+08:00:20240401T090000\r\n"));
+        assert!(ics.contains("DTEND;TZID=UTC+08:00:20240401T100000\r\n"));
+        assert!(ics.contains("DTSTART;TZID=UTC-05:00:20240331T220000\r\n"));
+        assert!(ics.contains("TZOFFSETTO:-0500"));
+        assert!(ics.contains("SUMMARY:Client\r\n"));
+        assert!(ics.contains("SUMMARY:editor\r\n"));
+        assert!(ics.contains("DESCRIPTION:review\\; notes\\, "));
+        assert!(ics.contains("SUMMARY:番茄钟：Client\r\n"));
+        // 折行后每行不超过 75 字节
+        assert!(ics.lines().all(|line| line.len() <= ICAL_LINE_LIMIT));
+
+        let ics = String::from_utf8(manager.export_ical(from, to, false).await?).unwrap();
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        Ok(())
+    }
 } 
\ No newline at end of file
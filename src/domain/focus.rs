@@ -0,0 +1,162 @@
+use crate::core::models::PomodoroStatus;
+use crate::core::traits::{PomodoroTimer, TimeTracker};
+use crate::core::AppResult;
+use crate::domain::config::FocusSettings;
+use std::sync::{Arc, Mutex};
+
+/// 发现分心应用时调用，参数为应用名称
+pub type DistractionCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+/// 工作番茄进行中检查当前窗口是否属于分心应用，休息阶段不做任何处理
+pub struct FocusGuard {
+    settings: FocusSettings,
+    tracker: Arc<dyn TimeTracker + Send + Sync>,
+    timer: Arc<dyn PomodoroTimer + Send + Sync>,
+    on_distraction: Option<DistractionCallback>,
+    /// 上一次提醒的应用，停留在同一应用时不重复提醒
+    last_reported: Mutex<Option<String>>,
+}
+
+impl FocusGuard {
+    pub fn new(
+        settings: FocusSettings,
+        tracker: Arc<dyn TimeTracker + Send + Sync>,
+        timer: Arc<dyn PomodoroTimer + Send + Sync>,
+    ) -> Self {
+        Self {
+            settings,
+            tracker,
+            timer,
+            on_distraction: None,
+            last_reported: Mutex::new(None),
+        }
+    }
+
+    pub fn with_callback(mut self, callback: DistractionCallback) -> Self {
+        self.on_distraction = Some(callback);
+        self
+    }
+
+    fn is_unproductive(&self, app_name: &str) -> bool {
+        self.settings
+            .unproductive_apps
+            .iter()
+            .any(|app| app.eq_ignore_ascii_case(app_name))
+    }
+
+    /// 返回当前的分心应用名称。刚切换到该应用时触发回调
+    pub async fn check(&self) -> AppResult<Option<String>> {
+        let offending = if self.settings.enabled && self.is_working().await? {
+            self.tracker
+                .get_current_activity()
+                .await?
+                .map(|activity| activity.app_name)
+                .filter(|app| self.is_unproductive(app))
+        } else {
+            None
+        };
+
+        let newly_detected = {
+            let mut last = self.last_reported.lock().unwrap();
+            let changed = *last != offending;
+            *last = offending.clone();
+            changed
+        };
+        if let (true, Some(app), Some(callback)) = (newly_detected, &offending, &self.on_distraction) {
+            callback(app);
+        }
+        Ok(offending)
+    }
+
+    async fn is_working(&self) -> AppResult<bool> {
+        Ok(matches!(
+            self.timer.get_current_session().await?.map(|s| s.status),
+            Some(PomodoroStatus::Work)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Activity;
+    use crate::core::traits::MockStorage;
+    use crate::domain::activity::ActivityManager;
+    use crate::domain::pomodoro::PomodoroManager;
+
+    fn activity(app: &str) -> Activity {
+        Activity {
+            id: None,
+            name: app.into(),
+            start_time: chrono::Local::now(),
+            end_time: None,
+            project_id: None,
+            description: None,
+            duration: std::time::Duration::ZERO,
+            category: "other".into(),
+            is_productive: false,
+            app_name: app.into(),
+            window_title: String::new(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    fn settings(enabled: bool) -> FocusSettings {
+        FocusSettings {
+            enabled,
+            unproductive_apps: vec!["YouTube".into(), "Steam".into()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nags_only_during_work() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_activity().returning(|_| Ok(1));
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let storage = Arc::new(mock_storage);
+        let tracker = Arc::new(ActivityManager::new(storage.clone()));
+        let timer = Arc::new(PomodoroManager::new(storage));
+
+        let nags = Arc::new(Mutex::new(Vec::new()));
+        let recorded = nags.clone();
+        let guard = FocusGuard::new(settings(true), tracker.clone(), timer.clone())
+            .with_callback(Box::new(move |app| recorded.lock().unwrap().push(app.to_string())));
+
+        // 没有进行中的番茄钟
+        tracker.start_tracking(activity("youtube")).await?;
+        assert_eq!(guard.check().await?, None);
+
+        timer.start_session(25).await?;
+        assert_eq!(guard.check().await?, Some("youtube".to_string()));
+        assert_eq!(guard.check().await?, Some("youtube".to_string()));
+        tracker.start_tracking(activity("code")).await?;
+        assert_eq!(guard.check().await?, None);
+        tracker.start_tracking(activity("steam")).await?;
+        assert_eq!(guard.check().await?, Some("steam".to_string()));
+        // 停留在同一应用只提醒一次
+        assert_eq!(*nags.lock().unwrap(), vec!["youtube".to_string(), "steam".to_string()]);
+
+        // 暂停后不再检查
+        timer.pause_session().await?;
+        assert_eq!(guard.check().await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disabled_guard_does_nothing() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let storage = Arc::new(mock_storage);
+        let tracker = Arc::new(ActivityManager::new(storage.clone()));
+        let timer = Arc::new(PomodoroManager::new(storage));
+        let guard = FocusGuard::new(settings(false), tracker.clone(), timer.clone());
+
+        timer.start_session(25).await?;
+        tracker.start_tracking(activity("youtube")).await?;
+        assert_eq!(guard.check().await?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,446 @@
+use crate::core::models::{GoalKind, GoalPeriod, ProjectGoal};
+use crate::core::traits::Storage;
+use crate::core::AppResult;
+use crate::domain::analysis::{AnalysisManager, DailyGoalProgress};
+use crate::plugins::traits::NotificationPlugin;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 检查每日目标是否达成的间隔
+pub const GOAL_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalStatus {
+    Met,
+    /// 按当前进度能在周期结束前完成
+    OnTrack,
+    /// 按当前进度无法在周期结束前完成
+    AtRisk,
+    /// 周期已结束但未完成
+    Overdue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    pub goal: ProjectGoal,
+    pub period_start: DateTime<Local>,
+    pub period_end: DateTime<Local>,
+    pub logged: Duration,
+    /// 按周期已过去的比例折算，此刻应完成的时长
+    pub expected: Duration,
+    /// 按当前进度预计完成的时间，尚无记录时为空
+    pub estimated_completion: Option<DateTime<Local>>,
+    pub status: GoalStatus,
+}
+
+impl GoalProgress {
+    pub fn remaining(&self) -> Duration {
+        self.goal.target.saturating_sub(self.logged)
+    }
+
+    pub fn percent(&self) -> f32 {
+        if self.goal.target.is_zero() {
+            return 100.0;
+        }
+        (self.logged.as_secs_f32() / self.goal.target.as_secs_f32() * 100.0).min(100.0)
+    }
+}
+
+pub(crate) fn local_midnight(date: NaiveDate) -> DateTime<Local> {
+    let naive = date.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+/// `at` 所在周期的起止时间，周从周一开始
+pub fn period_bounds(period: GoalPeriod, at: DateTime<Local>) -> (DateTime<Local>, DateTime<Local>) {
+    let date = at.date_naive();
+    let (start, end) = match period {
+        GoalPeriod::Week => {
+            let start = date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64);
+            (start, start + ChronoDuration::days(7))
+        }
+        GoalPeriod::Month => {
+            let start = date.with_day(1).unwrap();
+            let end = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            }
+            .unwrap();
+            (start, end)
+        }
+    };
+    (local_midnight(start), local_midnight(end))
+}
+
+/// 以周期内已过去时间的平均速度线性推算完成时间，与项目进度预测的方法一致
+pub fn classify(
+    goal: ProjectGoal,
+    logged: Duration,
+    period_start: DateTime<Local>,
+    period_end: DateTime<Local>,
+    now: DateTime<Local>,
+) -> GoalProgress {
+    let total = (period_end - period_start).num_seconds().max(1) as f64;
+    let elapsed = (now.min(period_end) - period_start).num_seconds().max(0) as f64;
+    let expected = Duration::from_secs_f64(goal.target.as_secs_f64() * elapsed / total);
+
+    let remaining = goal.target.saturating_sub(logged);
+    let estimated_completion = if remaining.is_zero() {
+        Some(now)
+    } else if logged.is_zero() || elapsed == 0.0 {
+        None
+    } else {
+        let seconds = remaining.as_secs_f64() * elapsed / logged.as_secs_f64();
+        Some(now + ChronoDuration::seconds(seconds.ceil() as i64))
+    };
+
+    let status = if remaining.is_zero() {
+        GoalStatus::Met
+    } else if now >= period_end {
+        GoalStatus::Overdue
+    } else if estimated_completion.map_or(false, |at| at <= period_end) {
+        GoalStatus::OnTrack
+    } else {
+        GoalStatus::AtRisk
+    };
+
+    GoalProgress {
+        goal,
+        period_start,
+        period_end,
+        logged,
+        expected,
+        estimated_completion,
+        status,
+    }
+}
+
+pub struct GoalManager {
+    storage: Arc<dyn Storage + Send + Sync>,
+}
+
+impl GoalManager {
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
+        Self { storage }
+    }
+
+    pub async fn set_project_goal(&self, project_id: i64, period: GoalPeriod, target: Duration) -> AppResult<ProjectGoal> {
+        let mut goal = ProjectGoal {
+            id: None,
+            project_id,
+            period,
+            target,
+        };
+        goal.id = Some(self.storage.save_project_goal(&goal).await?);
+        Ok(goal)
+    }
+
+    /// 项目在当前周期内的目标进度，未设置目标时返回 `None`
+    pub async fn project_goal_progress(&self, project_id: i64, period: GoalPeriod) -> AppResult<Option<GoalProgress>> {
+        self.project_goal_progress_at(project_id, period, Local::now()).await
+    }
+
+    /// 以 `now` 所在的周期计算，`now` 在周期结束之后时给出该周期的最终结果
+    pub async fn project_goal_progress_at(
+        &self,
+        project_id: i64,
+        period: GoalPeriod,
+        now: DateTime<Local>,
+    ) -> AppResult<Option<GoalProgress>> {
+        let Some(goal) = self.storage.get_project_goal(project_id, period).await? else {
+            return Ok(None);
+        };
+
+        let (start, end) = period_bounds(period, now);
+        let logged = self
+            .storage
+            .get_project_activities(project_id, start, end)
+            .await?
+            .iter()
+            .map(|activity| activity.duration)
+            .sum();
+
+        Ok(Some(classify(goal, logged, start, end, now)))
+    }
+}
+
+/// 目标达成时的通知内容
+fn goal_met_message(progress: &DailyGoalProgress) -> String {
+    let minutes = progress.target / 60;
+    match progress.goal.kind {
+        GoalKind::ProductiveTime => format!("今天的生产性时间已达到 {}h {:02}m", minutes / 60, minutes % 60),
+        GoalKind::CompletedPomodoros => format!("今天已完成 {} 个番茄钟", progress.target),
+        GoalKind::ProjectTime { .. } => format!("今天的项目时间已达到 {}h {:02}m", minutes / 60, minutes % 60),
+    }
+}
+
+/// 定时检查当天生效的每日目标，每个目标每天达成时只通知一次
+pub struct DailyGoalWatcher {
+    analysis: AnalysisManager,
+    notifier: Option<Arc<dyn NotificationPlugin>>,
+    notified: Mutex<HashSet<(i64, NaiveDate)>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl DailyGoalWatcher {
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
+        Self {
+            analysis: AnalysisManager::new(storage),
+            notifier: None,
+            notified: Mutex::new(HashSet::new()),
+            handle: Mutex::new(None),
+        }
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn NotificationPlugin>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// 返回当天所有生效目标的进度，刚达成的目标发送通知
+    pub async fn check(&self, date: NaiveDate) -> AppResult<Vec<DailyGoalProgress>> {
+        let progress = self.analysis.daily_goals(date).await?;
+        for goal in progress.iter().filter(|goal| goal.met) {
+            let Some(id) = goal.goal.id else {
+                continue;
+            };
+            if !self.notified.lock().unwrap().insert((id, date)) {
+                continue;
+            }
+            if let Some(notifier) = &self.notifier {
+                if let Err(e) = notifier.send_notification("目标达成", &goal_met_message(goal)).await {
+                    log::warn!("发送目标达成通知失败: {}", e);
+                }
+            }
+        }
+        // 只保留当天的记录
+        self.notified.lock().unwrap().retain(|(_, day)| *day == date);
+        Ok(progress)
+    }
+
+    pub fn start(self: &Arc<Self>, poll_interval: Duration) {
+        let watcher = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = watcher.check(Local::now().date_naive()).await {
+                    log::error!("检查每日目标失败: {}", e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for DailyGoalWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Activity, DailyUsage, Goal, GoalSchedule};
+    use crate::core::traits::MockStorage;
+    use crate::plugins::traits::Plugin;
+    use async_trait::async_trait;
+
+    fn at(day: u32) -> DateTime<Local> {
+        local_midnight(NaiveDate::from_ymd_opt(2024, 4, day).unwrap())
+    }
+
+    fn activity(hours: u64) -> Activity {
+        Activity {
+            id: None,
+            name: "code".into(),
+            start_time: at(2),
+            end_time: Some(at(2) + ChronoDuration::hours(hours as i64)),
+            project_id: Some(1),
+            description: None,
+            duration: Duration::from_secs(hours * 3600),
+            category: "development".into(),
+            is_productive: true,
+            app_name: "code".into(),
+            window_title: "main.rs".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    /// 本月目标 20 小时，已记录 `hours` 小时
+    fn manager(hours: u64) -> GoalManager {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_project_goal().returning(|project_id, period| {
+            Ok(Some(ProjectGoal {
+                id: Some(1),
+                project_id,
+                period,
+                target: Duration::from_secs(20 * 3600),
+            }))
+        });
+        mock_storage
+            .expect_get_project_activities()
+            .withf(|project_id, start, end| *project_id == 1 && *start == at(1) && end.date_naive().day() == 1)
+            .returning(move |_, _, _| Ok(vec![activity(hours)]));
+        GoalManager::new(Arc::new(mock_storage))
+    }
+
+    #[tokio::test]
+    async fn test_project_goal_on_track() -> AppResult<()> {
+        // 4 月 16 日时刚好过半，已完成 12 小时
+        let progress = manager(12)
+            .project_goal_progress_at(1, GoalPeriod::Month, at(16))
+            .await?
+            .unwrap();
+        assert_eq!(progress.status, GoalStatus::OnTrack);
+        assert_eq!(progress.expected, Duration::from_secs(10 * 3600));
+        assert!(progress.estimated_completion.unwrap() <= progress.period_end);
+        assert_eq!(progress.remaining(), Duration::from_secs(8 * 3600));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_goal_at_risk() -> AppResult<()> {
+        let progress = manager(6)
+            .project_goal_progress_at(1, GoalPeriod::Month, at(16))
+            .await?
+            .unwrap();
+        assert_eq!(progress.status, GoalStatus::AtRisk);
+        assert!(progress.estimated_completion.unwrap() > progress.period_end);
+        assert!((progress.percent() - 30.0).abs() < 0.01);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_goal_met() -> AppResult<()> {
+        let progress = manager(21)
+            .project_goal_progress_at(1, GoalPeriod::Month, at(16))
+            .await?
+            .unwrap();
+        assert_eq!(progress.status, GoalStatus::Met);
+        assert_eq!(progress.remaining(), Duration::ZERO);
+        assert_eq!(progress.percent(), 100.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify_overdue_and_without_data() {
+        let goal = ProjectGoal {
+            id: None,
+            project_id: 1,
+            period: GoalPeriod::Month,
+            target: Duration::from_secs(20 * 3600),
+        };
+        let (start, end) = period_bounds(GoalPeriod::Month, at(16));
+        assert_eq!(end.date_naive(), NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+
+        let ended = classify(goal.clone(), Duration::from_secs(3600), start, end, end);
+        assert_eq!(ended.status, GoalStatus::Overdue);
+
+        // 周期内尚无记录时无法预测完成时间
+        let empty = classify(goal, Duration::ZERO, start, end, at(3));
+        assert_eq!(empty.status, GoalStatus::AtRisk);
+        assert!(empty.estimated_completion.is_none());
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Plugin for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn version(&self) -> &str {
+            "0.0.0"
+        }
+
+        fn description(&self) -> &str {
+            ""
+        }
+
+        async fn initialize(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn start(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn uninstall(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl NotificationPlugin for RecordingNotifier {
+        async fn send_notification(&self, _title: &str, message: &str) -> AppResult<()> {
+            self.0.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daily_goal_notifies_once_per_day() -> AppResult<()> {
+        let day = |day| NaiveDate::from_ymd_opt(2024, 4, day).unwrap();
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_list_goals().returning(|| {
+            Ok(vec![Goal {
+                id: Some(1),
+                kind: GoalKind::CompletedPomodoros,
+                target: 4,
+                schedule: GoalSchedule::Daily,
+                active: true,
+            }])
+        });
+        mock_storage.expect_get_daily_usage().returning(move |(start, _)| {
+            // 查询起点向前多取了时区余量，加一天后落在查询的日期
+            let date = (start + ChronoDuration::days(1)).date_naive();
+            Ok(vec![DailyUsage {
+                date,
+                completed_pomodoros: 4,
+                ..Default::default()
+            }])
+        });
+        let notifier = Arc::new(RecordingNotifier::default());
+        let watcher = DailyGoalWatcher::new(Arc::new(mock_storage)).with_notifier(notifier.clone());
+
+        assert!(watcher.check(day(1)).await?[0].met);
+        watcher.check(day(1)).await?;
+        assert_eq!(*notifier.0.lock().unwrap(), vec!["今天已完成 4 个番茄钟".to_string()]);
+
+        // 第二天达成时再次通知
+        watcher.check(day(2)).await?;
+        assert_eq!(notifier.0.lock().unwrap().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,812 @@
+use crate::core::models::{Activity, MergeBatch, PomodoroSession, PomodoroStatus, Project, ProjectRef};
+use crate::core::traits::Storage;
+use crate::core::{AppError, AppResult};
+use crate::domain::export::ExportData;
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeRecordKind {
+    Project,
+    Activity,
+    Pomodoro,
+}
+
+/// 同一自然键在不同来源中内容不一致，保留先出现的记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    pub kind: MergeRecordKind,
+    pub key: String,
+    pub source: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub projects_created: usize,
+    pub projects_matched: usize,
+    pub activities: usize,
+    pub pomodoros: usize,
+    /// 完全相同而跳过的记录
+    pub duplicates: usize,
+    pub conflicts: Vec<MergeConflict>,
+    /// 合并前创建的安全备份，未启用时为 None
+    pub safety_backup: Option<PathBuf>,
+}
+
+/// CSV 导入结果，`skipped` 为 (行号, 原因)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub skipped: Vec<(usize, String)>,
+    /// 写入前创建的安全备份，未启用或没有可导入的行时为 None
+    pub safety_backup: Option<PathBuf>,
+}
+
+impl std::fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} imported, {} skipped", self.imported, self.skipped.len())?;
+        if let Some((line, reason)) = self.skipped.first() {
+            write!(f, " (line {}: {})", line, reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// 时长列的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// HH:MM:SS，与导出格式一致
+    Clock,
+    Seconds,
+}
+
+/// CSV 列与活动字段的对应关系，值为表头名称，`None` 表示没有该列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvSchema {
+    pub label: String,
+    pub name: String,
+    /// 开始时间；日期和时间分为两列时为日期列
+    pub start: String,
+    pub start_time: Option<String>,
+    pub end: Option<String>,
+    pub end_time: Option<String>,
+    pub duration: Option<String>,
+    pub duration_format: DurationFormat,
+    pub project: Option<String>,
+    pub category: Option<String>,
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub description: Option<String>,
+    /// Yes/No、true/false，或 RescueTime 的 -2 到 2 评分（大于 0 为有效）
+    pub productive: Option<String>,
+    pub billable: Option<String>,
+    pub manual: Option<String>,
+    /// 逗号分隔
+    pub tags: Option<String>,
+    /// 没有应用列时使用的应用名称
+    pub default_app_name: String,
+}
+
+fn column(name: &str) -> Option<String> {
+    Some(name.to_string())
+}
+
+impl CsvSchema {
+    /// 本应用 `ExportManager` 导出的格式
+    pub fn generic() -> Self {
+        Self {
+            label: "generic".into(),
+            name: "Name".into(),
+            start: "Start Time".into(),
+            start_time: None,
+            end: column("End Time"),
+            end_time: None,
+            duration: column("Duration"),
+            duration_format: DurationFormat::Clock,
+            project: column("Project"),
+            category: column("Category"),
+            app_name: column("App Name"),
+            window_title: column("Window Title"),
+            description: column("Description"),
+            productive: column("Is Productive"),
+            billable: None,
+            manual: column("Manual"),
+            tags: None,
+            default_app_name: String::new(),
+        }
+    }
+
+    /// Toggl Track 的详细报表导出，日期和时间分列
+    pub fn toggl() -> Self {
+        Self {
+            label: "toggl".into(),
+            name: "Description".into(),
+            start: "Start date".into(),
+            start_time: column("Start time"),
+            end: column("End date"),
+            end_time: column("End time"),
+            duration: column("Duration"),
+            duration_format: DurationFormat::Clock,
+            project: column("Project"),
+            category: column("Task"),
+            app_name: None,
+            window_title: None,
+            description: None,
+            productive: None,
+            billable: column("Billable"),
+            manual: None,
+            tags: column("Tags"),
+            default_app_name: "Toggl".into(),
+        }
+    }
+
+    /// RescueTime 的按小时活动导出，时长以秒为单位
+    pub fn rescue_time() -> Self {
+        Self {
+            label: "rescuetime".into(),
+            name: "Activity".into(),
+            start: "Date".into(),
+            start_time: None,
+            end: None,
+            end_time: None,
+            duration: column("Time Spent (seconds)"),
+            duration_format: DurationFormat::Seconds,
+            project: None,
+            category: column("Category"),
+            app_name: column("Activity"),
+            window_title: None,
+            description: None,
+            productive: column("Productivity"),
+            billable: None,
+            manual: None,
+            tags: None,
+            default_app_name: String::new(),
+        }
+    }
+
+    pub fn presets() -> Vec<Self> {
+        vec![Self::toggl(), Self::rescue_time(), Self::generic()]
+    }
+
+    /// 按名称查找预设，不区分大小写
+    pub fn preset(label: &str) -> Option<Self> {
+        Self::presets().into_iter().find(|schema| schema.label.eq_ignore_ascii_case(label))
+    }
+
+    /// 按表头识别预设，名称和开始时间列都存在时视为匹配
+    pub fn detect(headers: &csv::StringRecord) -> Option<Self> {
+        let headers: Vec<&str> = headers.iter().map(str::trim).collect();
+        Self::presets()
+            .into_iter()
+            .find(|schema| schema.required().iter().all(|name| headers.contains(name)))
+    }
+
+    fn required(&self) -> Vec<&str> {
+        [Some(self.name.as_str()), Some(self.start.as_str()), self.start_time.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+/// 按表头名称定位 CSV 列
+struct CsvColumns {
+    schema: CsvSchema,
+    indexes: HashMap<String, usize>,
+}
+
+impl CsvColumns {
+    fn new(headers: &csv::StringRecord, schema: CsvSchema) -> AppResult<Self> {
+        let indexes: HashMap<String, usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.trim().to_string(), index))
+            .collect();
+        if let Some(missing) = schema.required().into_iter().find(|name| !indexes.contains_key(*name)) {
+            return Err(AppError::InvalidOperation(format!("CSV 缺少列: {}", missing)));
+        }
+        Ok(Self { schema, indexes })
+    }
+
+    /// 缺少的列和空单元格都返回 `None`
+    fn get<'r>(&self, record: &'r csv::StringRecord, name: Option<&str>) -> Option<&'r str> {
+        self.indexes
+            .get(name?)
+            .and_then(|index| record.get(*index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// 日期和时间分列时拼接后解析
+    fn time(&self, record: &csv::StringRecord, date: Option<&str>, time: Option<&str>) -> Result<Option<DateTime<Local>>, String> {
+        let Some(date) = self.get(record, date) else {
+            return Ok(None);
+        };
+        match time {
+            Some(_) => match self.get(record, time) {
+                Some(time) => parse_time(&format!("{} {}", date, time)).map(Some),
+                None => Ok(None),
+            },
+            None => parse_time(date).map(Some),
+        }
+    }
+}
+
+/// RFC 3339，或没有时区的本地时间
+fn parse_time(value: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Local));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| naive.and_local_timezone(Local).earliest())
+        .ok_or_else(|| format!("invalid date: {}", value))
+}
+
+/// 导出使用的 HH:MM:SS 格式
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let parts: Vec<u64> = value
+        .split(':')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid duration: {}", value))?;
+    match parts.as_slice() {
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            Ok(Duration::from_secs(hours * 3600 + minutes * 60 + seconds))
+        }
+        _ => Err(format!("invalid duration: {}", value)),
+    }
+}
+
+fn parse_flag(value: Option<&str>, default: bool, field: &str) -> Result<bool, String> {
+    match value.map(str::to_ascii_lowercase).as_deref() {
+        None => Ok(default),
+        Some("yes") | Some("true") => Ok(true),
+        Some("no") | Some("false") => Ok(false),
+        Some(other) => other
+            .parse::<f64>()
+            .map(|score| score > 0.0)
+            .map_err(|_| format!("invalid {} flag: {}", field, other)),
+    }
+}
+
+fn parse_activity(record: &csv::StringRecord, columns: &CsvColumns, projects: &HashMap<String, i64>) -> Result<Activity, String> {
+    let schema = &columns.schema;
+    let name = columns.get(record, Some(schema.name.as_str())).ok_or("missing name")?;
+    let start_time = columns
+        .time(record, Some(schema.start.as_str()), schema.start_time.as_deref())?
+        .ok_or("missing start time")?;
+    let end_time = columns.time(record, schema.end.as_deref(), schema.end_time.as_deref())?;
+    if end_time.map_or(false, |end| end < start_time) {
+        return Err("end time before start time".into());
+    }
+    let duration = match (columns.get(record, schema.duration.as_deref()), end_time) {
+        (Some(value), _) => match schema.duration_format {
+            DurationFormat::Clock => parse_duration(value)?,
+            DurationFormat::Seconds => Duration::from_secs_f64(
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| *seconds >= 0.0)
+                    .ok_or_else(|| format!("invalid duration: {}", value))?,
+            ),
+        },
+        (None, Some(end)) => (end - start_time).to_std().unwrap_or_default(),
+        (None, None) => Duration::ZERO,
+    };
+    let project_id = match columns.get(record, schema.project.as_deref()) {
+        Some(project) => Some(*projects.get(project).ok_or_else(|| format!("unknown project: {}", project))?),
+        None => None,
+    };
+    let is_productive = parse_flag(columns.get(record, schema.productive.as_deref()), true, "productive")?;
+    let manual = parse_flag(columns.get(record, schema.manual.as_deref()), false, "manual")?;
+    let is_billable = columns
+        .get(record, schema.billable.as_deref())
+        .map(|value| parse_flag(Some(value), false, "billable"))
+        .transpose()?;
+    let tags = columns
+        .get(record, schema.tags.as_deref())
+        .map(|tags| {
+            tags.split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Activity {
+        id: None,
+        name: name.to_string(),
+        start_time,
+        end_time,
+        project_id,
+        description: columns.get(record, schema.description.as_deref()).map(String::from),
+        duration,
+        category: columns.get(record, schema.category.as_deref()).unwrap_or_default().to_string(),
+        is_productive,
+        app_name: columns
+            .get(record, schema.app_name.as_deref())
+            .unwrap_or(schema.default_app_name.as_str())
+            .to_string(),
+        window_title: columns.get(record, schema.window_title.as_deref()).unwrap_or_default().to_string(),
+        is_billable,
+        tags,
+        utc_offset: Some(start_time.offset().local_minus_utc()),
+        manual,
+    })
+}
+
+/// JSON 导出既可能带 summary 包装，也可能是裸的 ExportData
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExportFile {
+    Wrapped { data: ExportData },
+    Bare(ExportData),
+}
+
+impl ExportFile {
+    fn into_data(self) -> ExportData {
+        match self {
+            ExportFile::Wrapped { data } | ExportFile::Bare(data) => data,
+        }
+    }
+}
+
+type ActivityKey = (String, String, DateTime<Local>);
+
+fn activity_key(activity: &Activity) -> ActivityKey {
+    (activity.app_name.clone(), activity.window_title.clone(), activity.start_time)
+}
+
+pub struct DataImporter {
+    storage: Arc<dyn Storage + Send + Sync>,
+}
+
+impl DataImporter {
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
+        Self { storage }
+    }
+
+    pub fn read_export(path: impl AsRef<Path>) -> AppResult<ExportData> {
+        let file: ExportFile = serde_json::from_slice(&std::fs::read(path)?)?;
+        Ok(file.into_data())
+    }
+
+    /// 按扩展名选择导入方式
+    pub async fn import(&self, path: impl AsRef<Path>) -> AppResult<ImportReport> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => self.import_csv(path).await,
+            _ => Err(AppError::InvalidOperation(format!("不支持的导入格式: {}", path.display()))),
+        }
+    }
+
+    /// 导入活动 CSV，按表头自动识别 `CsvSchema` 预设
+    pub async fn import_csv(&self, path: impl AsRef<Path>) -> AppResult<ImportReport> {
+        self.import_csv_with_schema(path, None).await
+    }
+
+    /// 导入活动 CSV，未指定 `schema` 时按表头识别，无法识别时需由用户指定列映射。
+    /// 格式错误的行被跳过并记录原因，其余行在同一个事务中保存；遇到空行时视为页脚汇总开始
+    pub async fn import_csv_with_schema(&self, path: impl AsRef<Path>, schema: Option<CsvSchema>) -> AppResult<ImportReport> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+        let headers = reader.headers()?.clone();
+        let schema = schema
+            .or_else(|| CsvSchema::detect(&headers))
+            .ok_or_else(|| AppError::InvalidOperation("无法识别 CSV 的列，请指定列映射".into()))?;
+        let columns = CsvColumns::new(&headers, schema)?;
+        let projects: HashMap<String, i64> = self
+            .storage
+            .list_projects()
+            .await?
+            .into_iter()
+            .filter_map(|project| project.id.map(|id| (project.name, id)))
+            .collect();
+
+        let mut report = ImportReport::default();
+        let mut activities = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            // 表头占第 1 行
+            let fallback_line = index + 2;
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    let line = e.position().map_or(fallback_line, |p| p.line() as usize);
+                    report.skipped.push((line, e.to_string()));
+                    continue;
+                }
+            };
+            if record.iter().all(|field| field.trim().is_empty()) {
+                break;
+            }
+            let line = record.position().map_or(fallback_line, |p| p.line() as usize);
+            match parse_activity(&record, &columns, &projects) {
+                Ok(activity) => activities.push(activity),
+                Err(reason) => report.skipped.push((line, reason)),
+            }
+        }
+
+        if !activities.is_empty() {
+            report.safety_backup = self.storage.safety_backup().await?;
+            report.imported = self.storage.save_activities(&activities).await?.len();
+        }
+        Ok(report)
+    }
+
+    /// 合并多个实例的 JSON 导出。项目按名称对应，记录按自然键去重：
+    /// 活动为 (应用, 窗口标题, 开始时间)，番茄钟为开始时间。
+    /// 每个来源的项目按名称排序后处理，结果与导出中的顺序无关。
+    /// 所有新记录在同一个事务中写入，写入前创建安全备份
+    pub async fn merge_instances(&self, paths: &[impl AsRef<Path>]) -> AppResult<MergeReport> {
+        let mut report = MergeReport::default();
+        let mut batch = MergeBatch::default();
+
+        let mut projects: HashMap<String, Project> = self
+            .storage
+            .list_projects()
+            .await?
+            .into_iter()
+            .map(|project| (project.name.clone(), project))
+            .collect();
+        let mut activities: HashMap<ActivityKey, (Duration, Option<ProjectRef>)> = self
+            .storage
+            .list_activities()
+            .await?
+            .into_iter()
+            .map(|activity| (activity_key(&activity), (activity.duration, activity.project_id.map(ProjectRef::Existing))))
+            .collect();
+        let mut pomodoros: HashMap<DateTime<Local>, (Option<DateTime<Local>>, PomodoroStatus)> = self
+            .storage
+            .list_pomodoros()
+            .await?
+            .into_iter()
+            .map(|session| (session.start_time, (session.end_time, session.status)))
+            .collect();
+
+        for path in paths {
+            let path = path.as_ref();
+            let mut data = Self::read_export(path)?;
+            data.projects.sort_by(|a, b| a.name.cmp(&b.name));
+            let conflict = |kind, key: String, reason: &str| MergeConflict {
+                kind,
+                key,
+                source: path.to_path_buf(),
+                reason: reason.into(),
+            };
+
+            // 来源中的项目 id 到当前数据库项目的映射
+            let mut project_refs: HashMap<i64, ProjectRef> = HashMap::new();
+            for project in data.projects {
+                let source_id = project.id;
+                let project_ref = match projects.get(&project.name) {
+                    Some(existing) => {
+                        if existing.description != project.description || existing.is_billable != project.is_billable {
+                            report.conflicts.push(conflict(
+                                MergeRecordKind::Project,
+                                project.name.clone(),
+                                "项目属性不一致，保留已有项目",
+                            ));
+                        }
+                        report.projects_matched += 1;
+                        // 本次合并中新建的项目也会被后续来源匹配到，此时还没有 id
+                        match existing.id {
+                            Some(id) => ProjectRef::Existing(id),
+                            None => ProjectRef::New(existing.name.clone()),
+                        }
+                    }
+                    None => {
+                        let created = Project { id: None, ..project };
+                        batch.projects.push(created.clone());
+                        report.projects_created += 1;
+                        let project_ref = ProjectRef::New(created.name.clone());
+                        projects.insert(created.name.clone(), created);
+                        project_ref
+                    }
+                };
+                if let Some(source_id) = source_id {
+                    project_refs.insert(source_id, project_ref);
+                }
+            }
+            let remap = |project_id: Option<i64>| project_id.and_then(|id| project_refs.get(&id).cloned());
+
+            for mut activity in data.activities {
+                activity.id = None;
+                let project = remap(activity.project_id);
+                activity.project_id = None;
+                let key = activity_key(&activity);
+
+                if let Some((duration, existing_project)) = activities.get(&key) {
+                    if *duration == activity.duration && *existing_project == project {
+                        report.duplicates += 1;
+                    } else {
+                        report.conflicts.push(conflict(
+                            MergeRecordKind::Activity,
+                            format!("{} @ {}", activity.app_name, activity.start_time),
+                            "同一时间的活动时长或项目不同",
+                        ));
+                    }
+                    continue;
+                }
+
+                activities.insert(key, (activity.duration, project.clone()));
+                batch.activities.push((activity, project));
+                report.activities += 1;
+            }
+
+            for mut session in data.pomodoros {
+                session.id = None;
+                let project = remap(session.project_id);
+                session.project_id = None;
+
+                if let Some((end_time, status)) = pomodoros.get(&session.start_time) {
+                    if *end_time == session.end_time && *status == session.status {
+                        report.duplicates += 1;
+                    } else {
+                        report.conflicts.push(conflict(
+                            MergeRecordKind::Pomodoro,
+                            session.start_time.to_string(),
+                            "同一时间的番茄钟结束时间或状态不同",
+                        ));
+                    }
+                    continue;
+                }
+
+                pomodoros.insert(session.start_time, (session.end_time, session.status));
+                batch.pomodoros.push((session, project));
+                report.pomodoros += 1;
+            }
+        }
+
+        if !batch.is_empty() {
+            report.safety_backup = self.storage.safety_backup().await?;
+            self.storage.save_merge(&batch).await?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::MockStorage;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    fn at(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn project(id: i64, name: &str) -> Project {
+        let mut project = Project::new(name.into(), None);
+        project.id = Some(id);
+        project
+    }
+
+    fn activity(app_name: &str, hour: u32, project_id: i64) -> Activity {
+        Activity {
+            id: None,
+            name: app_name.into(),
+            start_time: at(hour),
+            end_time: None,
+            project_id: Some(project_id),
+            description: None,
+            duration: Duration::from_secs(3600),
+            category: "development".into(),
+            is_productive: true,
+            app_name: app_name.into(),
+            window_title: "main.rs".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    fn session(hour: u32, project_id: i64) -> PomodoroSession {
+        PomodoroSession {
+            id: None,
+            start_time: at(hour),
+            end_time: Some(at(hour) + chrono::Duration::minutes(25)),
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: Some(project_id),
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Db {
+        projects: Vec<Project>,
+        activities: Vec<Activity>,
+        pomodoros: Vec<PomodoroSession>,
+    }
+
+    fn storage(db: Arc<Mutex<Db>>) -> MockStorage {
+        let mut storage = MockStorage::new();
+        storage.expect_safety_backup().times(1).returning(|| Ok(Some(PathBuf::from("safety.db"))));
+        let projects = db.clone();
+        storage
+            .expect_list_projects()
+            .returning(move || Ok(projects.lock().unwrap().projects.clone()));
+        let activities = db.clone();
+        storage
+            .expect_list_activities()
+            .returning(move || Ok(activities.lock().unwrap().activities.clone()));
+        let pomodoros = db.clone();
+        storage
+            .expect_list_pomodoros()
+            .returning(move || Ok(pomodoros.lock().unwrap().pomodoros.clone()));
+        storage.expect_save_merge().times(1).returning(move |batch| {
+            let mut db = db.lock().unwrap();
+            let mut created = HashMap::new();
+            for project in &batch.projects {
+                let id = db.projects.len() as i64 + 1;
+                db.projects.push(Project { id: Some(id), ..project.clone() });
+                created.insert(project.name.clone(), id);
+            }
+            let resolve = |project: &Option<ProjectRef>| match project {
+                Some(ProjectRef::Existing(id)) => Some(*id),
+                Some(ProjectRef::New(name)) => created.get(name).copied(),
+                None => None,
+            };
+            for (activity, project) in &batch.activities {
+                db.activities.push(Activity { project_id: resolve(project), ..activity.clone() });
+            }
+            for (session, project) in &batch.pomodoros {
+                db.pomodoros.push(PomodoroSession { project_id: resolve(project), ..session.clone() });
+            }
+            Ok(())
+        });
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_skips_bad_rows() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("activities.csv");
+        let start = at(9).to_rfc3339();
+        let end = at(10).to_rfc3339();
+        std::fs::write(
+            &path,
+            format!(
+                "ID,Name,Start Time,End Time,Duration,Project,Category,Is Productive,App Name,Window Title,Description\n\
+                 1,code,{start},{end},01:00:00,Client,development,Yes,code,main.rs,\n\
+                 2,mail,yesterday,,00:10:00,,communication,No,mail,inbox,\n\
+                 3,browser,{start},{end},1h,,browsing,Yes,browser,docs,\n\
+                 4,chat,{end},{start},,,communication,No,chat,team,\n\
+                 5,terminal,{start},,,Unknown,development,Yes,terminal,zsh,\n\
+                 6,notes,{end},,00:05:00,,writing,No,notes,todo,\n\
+                 \"\"\nSummary\nTotal Time,01:05:00\n"
+            ),
+        )?;
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_list_projects().returning(|| Ok(vec![project(7, "Client")]));
+        mock_storage.expect_safety_backup().times(1).returning(|| Ok(Some(PathBuf::from("safety.db"))));
+        mock_storage
+            .expect_save_activities()
+            .withf(|activities: &[Activity]| {
+                activities.len() == 2
+                    && activities[0].project_id == Some(7)
+                    && activities[0].duration == Duration::from_secs(3600)
+                    && !activities[1].is_productive
+            })
+            .times(1)
+            .returning(|activities| Ok((1..=activities.len() as i64).collect()));
+
+        let report = DataImporter::new(Arc::new(mock_storage)).import(&path).await?;
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.safety_backup, Some(PathBuf::from("safety.db")));
+        let lines: Vec<usize> = report.skipped.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![3, 4, 5, 6]);
+        assert_eq!(report.to_string(), "2 imported, 4 skipped (line 3: invalid date: yesterday)");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_toggl_export() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("toggl.csv");
+        std::fs::write(
+            &path,
+            "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+             Ann,ann@example.com,,Client,Review,Design review,Yes,2024-03-01,09:00:00,2024-03-01,10:30:00,01:30:00,\"design, review\"\n\
+             Ann,ann@example.com,,,,Email,No,2024-03-01,11:00:00,2024-03-01,10:00:00,00:10:00,\n",
+        )?;
+
+        let headers = csv::StringRecord::from(vec!["Date", "Time Spent (seconds)", "Number of People", "Activity", "Category", "Productivity"]);
+        assert_eq!(CsvSchema::detect(&headers).map(|schema| schema.label), Some("rescuetime".into()));
+        assert_eq!(CsvSchema::detect(&csv::StringRecord::from(vec!["When", "What"])), None);
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_list_projects().returning(|| Ok(vec![project(7, "Client")]));
+        mock_storage.expect_safety_backup().times(1).returning(|| Ok(Some(PathBuf::from("safety.db"))));
+        mock_storage
+            .expect_save_activities()
+            .withf(|activities: &[Activity]| {
+                let review = &activities[0];
+                activities.len() == 1
+                    && review.name == "Design review"
+                    && review.app_name == "Toggl"
+                    && review.start_time == at(9)
+                    && review.duration == Duration::from_secs(90 * 60)
+                    && review.project_id == Some(7)
+                    && review.is_billable == Some(true)
+                    && review.tags == vec!["design".to_string(), "review".to_string()]
+            })
+            .times(1)
+            .returning(|activities| Ok((1..=activities.len() as i64).collect()));
+        let importer = DataImporter::new(Arc::new(mock_storage));
+
+        let report = importer.import_csv(&path).await?;
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.skipped, vec![(3, "end time before start time".to_string())]);
+
+        // 无法识别的表头需要指定列映射
+        let custom = dir.path().join("custom.csv");
+        std::fs::write(&custom, "When,What\n2024-03-01 09:00,code\n")?;
+        assert!(matches!(importer.import_csv(&custom).await, Err(AppError::InvalidOperation(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_two_instances_with_shared_project() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+
+        // 笔记本：Client 项目 id 为 1
+        let laptop = ExportData {
+            projects: vec![project(2, "Laptop only"), project(1, "Client")],
+            activities: vec![activity("code", 9, 1), activity("browser", 10, 2)],
+            pomodoros: vec![session(9, 1)],
+        };
+        // 台式机：同名项目 id 为 5，且包含一条与笔记本重复的活动和一条冲突的活动
+        let mut conflicting = activity("browser", 10, 5);
+        conflicting.duration = Duration::from_secs(600);
+        let desktop = ExportData {
+            projects: vec![project(5, "Client")],
+            activities: vec![activity("code", 9, 5), conflicting, activity("terminal", 14, 5)],
+            pomodoros: vec![session(9, 5), session(14, 5)],
+        };
+
+        let laptop_path = dir.path().join("laptop.json");
+        let desktop_path = dir.path().join("desktop.json");
+        std::fs::write(&laptop_path, serde_json::to_vec(&serde_json::json!({ "data": laptop }))?)?;
+        std::fs::write(&desktop_path, serde_json::to_vec(&desktop)?)?;
+
+        let db = Arc::new(Mutex::new(Db::default()));
+        let importer = DataImporter::new(Arc::new(storage(db.clone())));
+        let report = importer.merge_instances(&[&laptop_path, &desktop_path]).await?;
+
+        assert_eq!(report.projects_created, 2);
+        assert_eq!(report.projects_matched, 1);
+        assert_eq!(report.activities, 3);
+        assert_eq!(report.pomodoros, 2);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].kind, MergeRecordKind::Activity);
+        assert_eq!(report.conflicts[0].source, desktop_path);
+        assert_eq!(report.safety_backup, Some(PathBuf::from("safety.db")));
+
+        let db = db.lock().unwrap();
+        // 新项目按名称顺序创建，与导出中的顺序无关
+        let names: Vec<&str> = db.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Client", "Laptop only"]);
+        let client_id = db.projects.iter().find(|p| p.name == "Client").and_then(|p| p.id);
+        // 两台机器上 Client 项目的记录都指向同一个项目
+        let terminal = db.activities.iter().find(|a| a.app_name == "terminal").unwrap();
+        assert_eq!(terminal.project_id, client_id);
+        assert!(db.pomodoros.iter().all(|s| s.project_id == client_id));
+
+        Ok(())
+    }
+}
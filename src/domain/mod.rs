@@ -1,12 +1,18 @@
 pub mod activity;
 pub mod project;
 pub mod pomodoro;
+pub mod power;
 pub mod analysis;
 pub mod export;
+pub mod anonymize;
+pub mod import;
+pub mod goal;
+pub mod focus;
 pub mod notification;
 pub mod plugin;
 pub mod config;
 pub mod rules;
+pub mod undo;
 
 // Re-export managers
 pub use activity::ActivityManager;
@@ -14,4 +20,8 @@ pub use project::ProjectManager;
 pub use pomodoro::PomodoroManager;
 pub use analysis::AnalysisManager;
 pub use export::ExportManager;
-pub use config::{AppConfig, ConfigManager}; 
\ No newline at end of file
+pub use import::{DataImporter, ImportReport};
+pub use goal::GoalManager;
+pub use focus::FocusGuard;
+pub use config::{AppConfig, ConfigManager};
+pub use undo::{UndoManager, UndoableAction}; 
\ No newline at end of file
@@ -1,6 +1,8 @@
 use crate::core::{AppResult, models::*};
 use crate::core::traits::Storage;
+use crate::domain::config::NotificationSettings;
 use chrono::{DateTime, Local};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use serde::{Serialize, Deserialize};
@@ -11,6 +13,7 @@ pub enum NotificationType {
     PomodoroEnd,
     PomodoroBreakStart,
     PomodoroBreakEnd,
+    MicroBreak,
     ActivityChange,
     ProductivityAlert,
     SystemAlert,
@@ -27,15 +30,54 @@ pub struct Notification {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// 提示音播放接口，由通知插件实现
+pub trait SoundPlayer: Send + Sync {
+    /// 播放音频文件，`None` 时播放内置提示音；音量范围 0 到 1
+    fn play(&self, sound: Option<&Path>, volume: f32) -> AppResult<()>;
+}
+
 pub struct NotificationManager {
     storage: Arc<dyn Storage>,
     sender: broadcast::Sender<Notification>,
+    sound_player: Option<Arc<dyn SoundPlayer>>,
+    enable_sound: bool,
+    sound_volume: f32,
 }
 
 impl NotificationManager {
     pub fn new(storage: Arc<dyn Storage>) -> Self {
         let (sender, _) = broadcast::channel(100);
-        Self { storage, sender }
+        Self {
+            storage,
+            sender,
+            sound_player: None,
+            enable_sound: false,
+            sound_volume: 0.0,
+        }
+    }
+
+    pub fn with_sound(mut self, player: Arc<dyn SoundPlayer>, settings: &NotificationSettings) -> Self {
+        self.sound_player = Some(player);
+        self.enable_sound = settings.enable_sound;
+        self.sound_volume = settings.sound_volume;
+        self
+    }
+
+    /// 播放提示音，自定义文件无法播放时退回内置提示音；播放失败不影响计时
+    pub fn play_sound(&self, sound: Option<&Path>) {
+        let Some(player) = self.sound_player.as_ref().filter(|_| self.enable_sound) else {
+            return;
+        };
+        let result = match sound {
+            Some(path) => player.play(Some(path), self.sound_volume).or_else(|e| {
+                log::warn!("无法播放提示音 {}: {}", path.display(), e);
+                player.play(None, self.sound_volume)
+            }),
+            None => player.play(None, self.sound_volume),
+        };
+        if let Err(e) = result {
+            log::warn!("播放提示音失败: {}", e);
+        }
     }
 
     pub fn subscribe(&self) -> broadcast::Receiver<Notification> {
@@ -105,6 +147,22 @@ impl NotificationManager {
         self.send_notification(notification).await
     }
 
+    pub async fn notify_session_abandoned(&self, session: &PomodoroSession) -> AppResult<()> {
+        let notification = Notification {
+            id: None,
+            notification_type: NotificationType::SystemAlert,
+            title: "番茄钟已中断".into(),
+            message: format!(
+                "上次的番茄钟（开始于 {}）在应用退出后未能完成，已记录为中断",
+                session.start_time.format("%Y-%m-%d %H:%M")
+            ),
+            timestamp: Local::now(),
+            is_read: false,
+            metadata: Some(serde_json::to_value(session)?),
+        };
+        self.send_notification(notification).await
+    }
+
     pub async fn notify_break_start(&self, duration_mins: u32) -> AppResult<()> {
         let notification = Notification {
             id: None,
@@ -118,6 +176,34 @@ impl NotificationManager {
         self.send_notification(notification).await
     }
 
+    /// 长休息前的工作番茄结束时使用，与普通短休息提示区分
+    pub async fn notify_long_break_coming(&self, duration_mins: u32) -> AppResult<()> {
+        let notification = Notification {
+            id: None,
+            notification_type: NotificationType::PomodoroBreakStart,
+            title: "长休息即将开始".into(),
+            message: format!("已完成一轮番茄钟，接下来是{}分钟的长休息", duration_mins),
+            timestamp: Local::now(),
+            is_read: false,
+            metadata: None,
+        };
+        self.send_notification(notification).await
+    }
+
+    /// 工作番茄进行中的短暂放松提醒，不影响计时
+    pub async fn notify_micro_break(&self, count: u32) -> AppResult<()> {
+        let notification = Notification {
+            id: None,
+            notification_type: NotificationType::MicroBreak,
+            title: "站起来活动一下".into(),
+            message: "看向远处 20 秒，放松一下眼睛和肩膀".into(),
+            timestamp: Local::now(),
+            is_read: false,
+            metadata: Some(serde_json::json!({ "count": count })),
+        };
+        self.send_notification(notification).await
+    }
+
     pub async fn notify_break_end(&self) -> AppResult<()> {
         let notification = Notification {
             id: None,
@@ -187,7 +273,28 @@ mod tests {
     use super::*;
     use mockall::mock;
     use mockall::predicate::*;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
     use std::time::Duration;
+    use crate::core::AppError;
+
+    /// 记录播放请求，路径不存在时返回错误
+    #[derive(Default)]
+    struct RecordingPlayer {
+        played: Mutex<Vec<(Option<PathBuf>, f32)>>,
+    }
+
+    impl SoundPlayer for RecordingPlayer {
+        fn play(&self, sound: Option<&Path>, volume: f32) -> AppResult<()> {
+            if let Some(path) = sound {
+                if !path.exists() {
+                    return Err(AppError::NotFound(path.display().to_string()));
+                }
+            }
+            self.played.lock().unwrap().push((sound.map(Path::to_path_buf), volume));
+            Ok(())
+        }
+    }
 
     mock! {
         Storage {}
@@ -240,4 +347,38 @@ mod tests {
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_play_sound_falls_back_to_default() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let chime = dir.path().join("chime.wav");
+        std::fs::write(&chime, b"")?;
+
+        let player = Arc::new(RecordingPlayer::default());
+        let settings = NotificationSettings {
+            enable_system_notifications: true,
+            enable_sound: true,
+            sound_volume: 0.4,
+            notification_retention_days: 30,
+        };
+        let manager = NotificationManager::new(Arc::new(MockStorage::new()))
+            .with_sound(player.clone(), &settings);
+
+        manager.play_sound(Some(&chime));
+        manager.play_sound(Some(&dir.path().join("missing.wav")));
+        manager.play_sound(None);
+        assert_eq!(
+            *player.played.lock().unwrap(),
+            vec![(Some(chime), 0.4), (None, 0.4), (None, 0.4)]
+        );
+
+        // 关闭声音后不再播放
+        let muted = NotificationManager::new(Arc::new(MockStorage::new())).with_sound(
+            player.clone(),
+            &NotificationSettings { enable_sound: false, ..settings },
+        );
+        muted.play_sound(None);
+        assert_eq!(player.played.lock().unwrap().len(), 3);
+        Ok(())
+    }
+}
@@ -1,13 +1,261 @@
-use crate::core::{AppResult, models::*};
+use crate::core::{AppError, AppResult, models::*};
 use crate::core::traits::{Storage, PomodoroTimer, PomodoroService};
+use crate::domain::config::{AppConfig, PomodoroEvent, PomodoroSettings, TimingSource};
+use crate::domain::notification::NotificationManager;
+use crate::infrastructure::platform::{NotificationOptions, PlatformOperations};
+use crate::plugins::traits::{PluginEvent, PluginEventSender};
 use chrono::{DateTime, Local};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::{mpsc, watch, Notify, RwLock};
+use tokio::time::MissedTickBehavior;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::Stream;
+use std::time::{Duration, Instant};
+
+/// 快照保存时刻距离计划结束时间在该范围内，视为会话已正常完成
+const COMPLETION_TOLERANCE: Duration = Duration::from_secs(60);
+
+/// 计划结束后在该时间内重新启动，同样视为正常完成
+const STALE_SESSION_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// 会话以该状态结束时对应的提示音事件
+fn ended_event(status: PomodoroStatus) -> PomodoroEvent {
+    match status {
+        PomodoroStatus::Work => PomodoroEvent::WorkComplete,
+        PomodoroStatus::ShortBreak | PomodoroStatus::LongBreak => PomodoroEvent::BreakComplete,
+        _ => PomodoroEvent::Interrupted,
+    }
+}
+
+/// 持久化的番茄钟运行状态，用于崩溃或重启后恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroSnapshot {
+    pub session: PomodoroSession,
+    pub saved_at: DateTime<Local>,
+    /// 已完成的工作番茄数，恢复后保持长休息的节奏
+    #[serde(default)]
+    pub completed_work_sessions: u32,
+    /// 本阶段已结束的暂停总时长，计划结束时间相应顺延
+    #[serde(default)]
+    pub paused_total: Duration,
+    #[serde(default)]
+    pub paused_at: Option<DateTime<Local>>,
+    /// 暂停前的阶段，继续时恢复到该阶段
+    #[serde(default)]
+    pub resume_status: Option<PomodoroStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RestoreOutcome {
+    /// 会话仍在计划时间内，继续运行
+    Resumed(PomodoroSession),
+    /// 会话在计划时间内自然结束
+    Completed(PomodoroSession),
+    /// 应用在会话中途退出且早已超时，按中断处理
+    Abandoned(PomodoroSession),
+}
+
+impl PomodoroSnapshot {
+    pub fn new(session: PomodoroSession, saved_at: DateTime<Local>) -> Self {
+        Self {
+            session,
+            saved_at,
+            completed_work_sessions: 0,
+            paused_total: Duration::ZERO,
+            paused_at: None,
+            resume_status: None,
+        }
+    }
+
+    fn planned_end(&self) -> DateTime<Local> {
+        let to_chrono = |d: Duration| chrono::Duration::from_std(d).unwrap_or_else(|_| chrono::Duration::zero());
+        self.session.start_time + to_chrono(self.session.duration) + to_chrono(self.paused_total)
+    }
+
+    pub fn load(path: &Path) -> AppResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&std::fs::read(path)?)?))
+    }
+
+    /// 先写临时文件再重命名，写入中途退出不会损坏上一次的快照
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// 根据当前时间判断快照中的会话应恢复、完成还是标记为中断
+    pub fn resolve(self, now: DateTime<Local>) -> RestoreOutcome {
+        let mut session = self.session.clone();
+
+        // 已暂停或已结束的会话原样恢复
+        if !matches!(
+            session.status,
+            PomodoroStatus::Work | PomodoroStatus::ShortBreak | PomodoroStatus::LongBreak
+        ) {
+            return RestoreOutcome::Resumed(session);
+        }
+
+        let planned_end = self.planned_end();
+        if now < planned_end {
+            return RestoreOutcome::Resumed(session);
+        }
+
+        let tolerance = chrono::Duration::from_std(COMPLETION_TOLERANCE).unwrap();
+        let grace = chrono::Duration::from_std(STALE_SESSION_GRACE).unwrap();
+        let ran_to_end = self.saved_at + tolerance >= planned_end;
+        let restarted_in_time = now - planned_end <= grace;
+
+        if ran_to_end || restarted_in_time {
+            session.end_time = Some(planned_end);
+            session.status = PomodoroStatus::Completed;
+            RestoreOutcome::Completed(session)
+        } else {
+            session.end_time = Some(self.saved_at.max(session.start_time));
+            session.duration = (self.saved_at - session.start_time)
+                .to_std()
+                .unwrap_or_default();
+            session.status = PomodoroStatus::Interrupted;
+            RestoreOutcome::Abandoned(session)
+        }
+    }
+}
+
+/// 工作番茄结束且未开启自动休息时，等待用户选择的下一步
+#[derive(Debug, Clone)]
+pub struct PendingDecision {
+    pub completed_session: PomodoroSession,
+    pub next_phase: PomodoroStatus,
+    /// 推迟中时为再次提醒的时间
+    pub snoozed_until: Option<DateTime<Local>>,
+    /// 还可以连续推迟的次数
+    pub snoozes_left: u32,
+}
+
+impl PendingDecision {
+    pub fn can_snooze(&self) -> bool {
+        self.snoozed_until.is_none() && self.snoozes_left > 0
+    }
+}
+
+/// 计时订阅者收到的状态，每秒一次，状态变化时立即发送
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimerTick {
+    /// 当前阶段，暂停时为 Interrupted，空闲时为 None
+    pub status: Option<PomodoroStatus>,
+    pub elapsed: Duration,
+    /// 空闲时为下一个工作番茄的时长，切换预设后随之更新
+    pub remaining: Duration,
+    /// 0 到 1
+    pub progress: f32,
+}
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 休息提醒通知的 id，点击操作按钮表示推迟休息
+pub const BREAK_PROMPT_NOTIFICATION: &str = "pomodoro.break_prompt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionChoice {
+    /// 立即开始休息
+    StartBreak,
+    /// 继续工作 5 分钟
+    ExtendWork,
+    /// 推迟 `snooze_duration` 后再次提醒
+    Snooze,
+    /// 不开始新阶段
+    Idle,
+}
+
+const EXTEND_WORK_DURATION: Duration = Duration::from_secs(5 * 60);
+
+/// 根据已完成的工作番茄数计算下一阶段，每 `long_break_interval` 个工作番茄后进入长休息
+pub fn next_phase_after(
+    current: Option<PomodoroStatus>,
+    completed_work_sessions: u32,
+    long_break_interval: u32,
+) -> PomodoroStatus {
+    match current {
+        Some(PomodoroStatus::Work) => {
+            let completed = completed_work_sessions + 1;
+            if long_break_interval > 0 && completed % long_break_interval == 0 {
+                PomodoroStatus::LongBreak
+            } else {
+                PomodoroStatus::ShortBreak
+            }
+        }
+        _ => PomodoroStatus::Work,
+    }
+}
+
+/// 当前阶段的暂停记录，用于计算不含暂停的已进行时长
+#[derive(Debug, Clone, Default)]
+struct PauseState {
+    paused_at: Option<DateTime<Local>>,
+    paused_total: chrono::Duration,
+    resume_to: Option<PomodoroStatus>,
+}
+
+impl PauseState {
+    fn elapsed(&self, started_at: DateTime<Local>, now: DateTime<Local>) -> Duration {
+        let until = self.paused_at.unwrap_or(now);
+        (until - started_at - self.paused_total).to_std().unwrap_or_default()
+    }
+}
+
+/// 计时参考点。单调时钟模式下，当前时间由参考点的系统时间加上单调时钟经过的时间推算，
+/// 之后系统时间的跳变不影响计时
+#[derive(Debug, Clone, Copy)]
+struct ClockAnchor {
+    wall: DateTime<Local>,
+    instant: Instant,
+}
+
+impl ClockAnchor {
+    fn new(wall: DateTime<Local>) -> Self {
+        Self {
+            wall,
+            instant: Instant::now(),
+        }
+    }
+
+    fn now(&self, source: TimingSource, wall_now: DateTime<Local>, since_anchor: Duration) -> DateTime<Local> {
+        match source {
+            TimingSource::WallClock => wall_now,
+            TimingSource::Monotonic => {
+                self.wall + chrono::Duration::from_std(since_anchor).unwrap_or_else(|_| chrono::Duration::zero())
+            }
+        }
+    }
+}
+
+/// 工作中短暂放松提醒的回调，参数为本次会话中的第几次提醒
+pub type MicroBreakCallback = Arc<dyn Fn(u32) + Send + Sync>;
 
 pub struct PomodoroManager {
     storage: Arc<dyn Storage + Send + Sync>,
     current_session: Arc<RwLock<Option<PomodoroSession>>>,
+    notifications: Option<Arc<NotificationManager>>,
+    /// 应用预设时修改，进行中的阶段保持原时长
+    settings: Mutex<PomodoroSettings>,
+    completed_work_sessions: Arc<RwLock<u32>>,
+    pending_decision: Arc<RwLock<Option<PendingDecision>>>,
+    platform: Option<Arc<dyn PlatformOperations>>,
+    keep_awake: Arc<RwLock<bool>>,
+    break_enforced_until: Arc<RwLock<Option<DateTime<Local>>>>,
+    pause: Arc<RwLock<PauseState>>,
+    micro_breaks_fired: Arc<RwLock<u32>>,
+    micro_break_callback: Option<MicroBreakCallback>,
+    plugin_events: Option<PluginEventSender>,
+    snapshot_path: Option<PathBuf>,
+    anchor: Arc<RwLock<ClockAnchor>>,
+    ticker: Mutex<Option<watch::Sender<TimerTick>>>,
+    state_changed: Arc<Notify>,
 }
 
 impl PomodoroManager {
@@ -15,50 +263,622 @@ impl PomodoroManager {
         Self {
             storage,
             current_session: Arc::new(RwLock::new(None)),
+            notifications: None,
+            settings: Mutex::new(AppConfig::default().pomodoro),
+            completed_work_sessions: Arc::new(RwLock::new(0)),
+            pending_decision: Arc::new(RwLock::new(None)),
+            platform: None,
+            keep_awake: Arc::new(RwLock::new(false)),
+            break_enforced_until: Arc::new(RwLock::new(None)),
+            pause: Arc::new(RwLock::new(PauseState::default())),
+            micro_breaks_fired: Arc::new(RwLock::new(0)),
+            micro_break_callback: None,
+            plugin_events: None,
+            snapshot_path: None,
+            anchor: Arc::new(RwLock::new(ClockAnchor::new(Local::now()))),
+            ticker: Mutex::new(None),
+            state_changed: Arc::new(Notify::new()),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl PomodoroTimer for PomodoroManager {
-    async fn start_session(&self, duration: i32) -> AppResult<()> {
+    pub fn with_settings(mut self, settings: PomodoroSettings) -> Self {
+        self.settings = Mutex::new(settings);
+        self
+    }
+
+    /// 当前设置的副本，不在等待期间持有锁
+    pub fn settings(&self) -> PomodoroSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn with_notifications(mut self, notifications: Arc<NotificationManager>) -> Self {
+        self.notifications = Some(notifications);
+        self
+    }
+
+    pub fn with_platform(mut self, platform: Arc<dyn PlatformOperations>) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    pub fn with_micro_break_callback(mut self, callback: MicroBreakCallback) -> Self {
+        self.micro_break_callback = Some(callback);
+        self
+    }
+
+    /// 工作番茄完成和休息开始时向插件广播事件
+    pub fn with_plugin_events(mut self, sender: PluginEventSender) -> Self {
+        self.plugin_events = Some(sender);
+        self
+    }
+
+    fn emit(&self, event: PluginEvent) {
+        if let Some(sender) = &self.plugin_events {
+            // 接收端只在退出时关闭，此时丢弃事件即可
+            let _ = sender.send(event);
+        }
+    }
+
+    /// 每次状态变化后把快照写入该文件，重启后用 `restore_saved` 恢复
+    pub fn with_snapshot_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot_path = Some(path.into());
+        self
+    }
+
+    /// 每次状态变化后调用，立即通知计时订阅者；保存失败只记录日志，不影响计时
+    async fn persist_snapshot(&self) {
+        self.state_changed.notify_one();
+        let Some(path) = &self.snapshot_path else {
+            return;
+        };
+        let result = match self.snapshot().await {
+            Some(snapshot) => snapshot.save(path),
+            None if path.exists() => std::fs::remove_file(path).map_err(Into::into),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("保存番茄钟状态失败: {}", e);
+        }
+    }
+
+    /// 订阅计时状态，所有订阅者都释放后后台任务随之结束。后台任务每次计时时也检查微休息提醒
+    pub async fn subscribe(self: &Arc<Self>) -> impl Stream<Item = TimerTick> + Unpin {
+        let first = self.current_tick().await;
+        let mut ticker = self.ticker.lock().unwrap();
+        let receiver = match ticker.as_ref().filter(|sender| !sender.is_closed()) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = watch::channel(first);
+                tokio::spawn(Self::run_ticker(Arc::downgrade(self), sender.clone()));
+                *ticker = Some(sender);
+                receiver
+            }
+        };
+        WatchStream::new(receiver)
+    }
+
+    async fn run_ticker(manager: Weak<Self>, sender: watch::Sender<TimerTick>) {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            // 只持有 Notify，避免后台任务让管理器无法释放
+            let Some(state_changed) = manager.upgrade().map(|m| m.state_changed.clone()) else {
+                break;
+            };
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = state_changed.notified() => interval.reset(),
+                _ = sender.closed() => break,
+            }
+            let Some(manager) = manager.upgrade() else {
+                break;
+            };
+            if let Err(e) = manager.check_micro_break().await {
+                log::warn!("发送微休息提醒失败: {}", e);
+            }
+            if let Err(e) = manager.check_snooze().await {
+                log::warn!("发送推迟的休息提醒失败: {}", e);
+            }
+            if sender.send(manager.current_tick().await).is_err() {
+                break;
+            }
+        }
+    }
+
+    async fn current_tick(&self) -> TimerTick {
+        let Some((status, duration)) = self.current_session.read().await.as_ref().map(|s| (s.status, s.duration)) else {
+            return TimerTick {
+                status: None,
+                elapsed: Duration::ZERO,
+                remaining: self.settings().work_duration,
+                progress: 0.0,
+            };
+        };
+        let elapsed = self.elapsed().await.unwrap_or_default();
+        let progress = if duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+        };
+        TimerTick {
+            status: Some(status),
+            elapsed,
+            remaining: duration.saturating_sub(elapsed),
+            progress,
+        }
+    }
+
+    /// 项目在时间范围内的番茄统计，中断的会话计入所属项目
+    pub async fn get_project_stats(
+        &self,
+        project_id: i64,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> AppResult<ProjectPomodoroStats> {
+        let sessions = self.storage.get_project_pomodoro_sessions(project_id, start, end).await?;
+        Ok(PomodoroStats::calculate_project(&sessions, project_id))
+    }
+
+    /// 启动时读取保存的快照并恢复，没有快照或快照损坏时返回 `None`
+    pub async fn restore_saved(&self) -> AppResult<Option<RestoreOutcome>> {
+        let Some(path) = &self.snapshot_path else {
+            return Ok(None);
+        };
+        let snapshot = match PomodoroSnapshot::load(path) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                log::warn!("番茄钟快照损坏，已忽略: {}", e);
+                None
+            }
+        };
+        match snapshot {
+            Some(snapshot) => self.restore(snapshot).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 按配置的时钟取得当前时间，用于计算阶段内已进行的时长
+    async fn timer_now(&self) -> DateTime<Local> {
+        let anchor = *self.anchor.read().await;
+        anchor.now(self.settings().timing_source, Local::now(), anchor.instant.elapsed())
+    }
+
+    /// 由计时循环定期调用，工作阶段每经过 `micro_break_every`（不含暂停）提醒一次，返回本次是否提醒
+    pub async fn check_micro_break(&self) -> AppResult<bool> {
+        let now = self.timer_now().await;
+        self.check_micro_break_at(now).await
+    }
+
+    async fn check_micro_break_at(&self, now: DateTime<Local>) -> AppResult<bool> {
+        let Some(every) = self.settings().micro_break_every.filter(|every| !every.is_zero()) else {
+            return Ok(false);
+        };
+        let (started_at, duration) = match self.current_session.read().await.as_ref() {
+            Some(session) if session.status == PomodoroStatus::Work => (session.start_time, session.duration),
+            _ => return Ok(false),
+        };
+
+        let elapsed = self.pause.read().await.elapsed(started_at, now);
+        // 番茄结束时会进入休息，不再单独提醒
+        if elapsed >= duration {
+            return Ok(false);
+        }
+
+        let due = (elapsed.as_secs() / every.as_secs().max(1)) as u32;
+        {
+            let mut fired = self.micro_breaks_fired.write().await;
+            if due <= *fired {
+                return Ok(false);
+            }
+            *fired = due;
+        }
+
+        if let Some(callback) = &self.micro_break_callback {
+            callback(due);
+        }
+        if let Some(notifications) = &self.notifications {
+            notifications.notify_micro_break(due).await?;
+        }
+        Ok(true)
+    }
+
+    /// 仅在工作阶段阻止系统休眠，休息、暂停和空闲时释放
+    async fn sync_keep_awake(&self) -> AppResult<()> {
+        let Some(platform) = &self.platform else {
+            return Ok(());
+        };
+
+        let working = matches!(
+            self.current_session.read().await.as_ref().map(|s| s.status),
+            Some(PomodoroStatus::Work)
+        );
+        let wanted = self.settings().keep_awake_during_work && working;
+
+        let mut keep_awake = self.keep_awake.write().await;
+        if *keep_awake != wanted {
+            platform.prevent_system_sleep(wanted)?;
+            *keep_awake = wanted;
+        }
+        Ok(())
+    }
+
+    /// 退出应用前调用，确保释放休眠阻止
+    pub async fn shutdown(&self) -> AppResult<()> {
+        let mut keep_awake = self.keep_awake.write().await;
+        if *keep_awake {
+            if let Some(platform) = &self.platform {
+                platform.prevent_system_sleep(false)?;
+            }
+            *keep_awake = false;
+        }
+        Ok(())
+    }
+
+    /// 强制休息的剩余时间，用于界面倒计时
+    pub async fn enforced_break_remaining(&self) -> Option<Duration> {
+        let until = (*self.break_enforced_until.read().await)?;
+        (until - Local::now()).to_std().ok().filter(|remaining| !remaining.is_zero())
+    }
+
+    /// 强制休息期间拒绝开始新的工作番茄
+    async fn ensure_break_taken(&self) -> AppResult<()> {
+        match self.enforced_break_remaining().await {
+            Some(remaining) => Err(AppError::InvalidOperation(format!(
+                "强制休息中，还需休息 {}:{:02}",
+                remaining.as_secs() / 60,
+                remaining.as_secs() % 60
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn pending_decision(&self) -> Option<PendingDecision> {
+        self.pending_decision.read().await.clone()
+    }
+
+    /// 处理工作番茄结束后用户的选择
+    pub async fn resolve_decision(&self, choice: CompletionChoice) -> AppResult<()> {
+        let mut pending = self.pending_decision.write().await;
+        let mut decision = pending
+            .take()
+            .ok_or_else(|| AppError::InvalidOperation("没有待处理的选择".into()))?;
+        if choice == CompletionChoice::Snooze {
+            if !decision.can_snooze() {
+                let reason = if decision.snoozed_until.is_some() { "休息已推迟" } else { "已达到推迟次数上限" };
+                *pending = Some(decision);
+                return Err(AppError::InvalidOperation(reason.into()));
+            }
+            let snooze = chrono::Duration::from_std(self.settings().snooze_duration)
+                .unwrap_or_else(|_| chrono::Duration::zero());
+            decision.snoozed_until = Some(Local::now() + snooze);
+            decision.snoozes_left -= 1;
+            *pending = Some(decision);
+            return Ok(());
+        }
+        drop(pending);
+
+        match choice {
+            CompletionChoice::StartBreak => self.start_break(decision.next_phase).await,
+            CompletionChoice::ExtendWork => {
+                let finished = decision.completed_session;
+                self.begin_session(
+                    PomodoroStatus::Work,
+                    EXTEND_WORK_DURATION,
+                    finished.project_id,
+                    finished.tags,
+                    finished.category,
+                )
+                .await?;
+                // 延长的工作不计入新的番茄，回退计数以免提前进入长休息
+                let mut completed = self.completed_work_sessions.write().await;
+                *completed = completed.saturating_sub(1);
+                Ok(())
+            }
+            CompletionChoice::Snooze | CompletionChoice::Idle => Ok(()),
+        }
+    }
+
+    /// 由计时循环定期调用，推迟到期后再次提醒，返回本次是否提醒
+    pub async fn check_snooze(&self) -> AppResult<bool> {
+        self.check_snooze_at(Local::now()).await
+    }
+
+    async fn check_snooze_at(&self, now: DateTime<Local>) -> AppResult<bool> {
+        let decision = {
+            let mut pending = self.pending_decision.write().await;
+            match pending.as_mut() {
+                Some(decision) if decision.snoozed_until.is_some_and(|until| until <= now) => {
+                    decision.snoozed_until = None;
+                    decision.clone()
+                }
+                _ => return Ok(false),
+            }
+        };
+        self.prompt_decision(&decision);
+        Ok(true)
+    }
+
+    /// 通过系统通知提示开始休息，还能推迟时带上推迟按钮
+    fn prompt_decision(&self, decision: &PendingDecision) {
+        let Some(platform) = &self.platform else {
+            return;
+        };
+        let minutes = self.settings().snooze_duration.as_secs() / 60;
+        let options = NotificationOptions {
+            id: BREAK_PROMPT_NOTIFICATION.into(),
+            title: "番茄钟已完成".into(),
+            message: "该休息一下了".into(),
+            sound: false,
+            action_button: decision.can_snooze().then(|| format!("{} 分钟后提醒", minutes.max(1))),
+            cancel_button: None,
+        };
+        if let Err(e) = platform.show_notification(options) {
+            log::debug!("无法显示休息提醒: {}", e);
+        }
+    }
+
+    /// 把通知操作转交给番茄钟，在应用启动时调用一次
+    pub fn spawn_notification_actions(self: Arc<Self>, mut actions: mpsc::UnboundedReceiver<String>) {
+        tokio::spawn(async move {
+            while let Some(id) = actions.recv().await {
+                if id == BREAK_PROMPT_NOTIFICATION {
+                    if let Err(e) = self.snooze_break().await {
+                        log::warn!("推迟休息失败: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    async fn start_break(&self, phase: PomodoroStatus) -> AppResult<()> {
+        let duration = match phase {
+            PomodoroStatus::LongBreak => self.settings().long_break_duration,
+            _ => self.settings().short_break_duration,
+        };
+        self.begin_session(phase, duration, None, Vec::new(), None).await?;
+        if let Some(session) = self.current_session.read().await.clone() {
+            self.emit(PluginEvent::BreakStarted(session));
+        }
+        Ok(())
+    }
+
+    /// 在项目下开始工作番茄，未指定的标签和类别继承项目默认值
+    pub async fn start_project_session(
+        &self,
+        duration: Duration,
+        project: &Project,
+        tags: Vec<String>,
+        category: Option<String>,
+    ) -> AppResult<()> {
+        self.ensure_break_taken().await?;
+        self.pending_decision.write().await.take();
+        self.begin_session(
+            PomodoroStatus::Work,
+            duration,
+            project.id,
+            project.resolve_tags(tags),
+            project.resolve_category(category),
+        )
+        .await
+    }
+
+    async fn begin_session(
+        &self,
+        status: PomodoroStatus,
+        duration: Duration,
+        project_id: Option<i64>,
+        tags: Vec<String>,
+        category: Option<String>,
+    ) -> AppResult<()> {
         let session = PomodoroSession {
             id: None,
             start_time: Local::now(),
             end_time: None,
-            duration: std::time::Duration::from_secs(duration as u64 * 60),
-            status: PomodoroStatus::Work,
-            project_id: None,
+            duration,
+            status,
+            project_id,
             notes: None,
+            tags,
+            category,
+            utc_offset: Some(current_utc_offset()),
         };
-        let mut current = self.current_session.write().await;
-        *current = Some(session);
-        Ok(())
+        *self.anchor.write().await = ClockAnchor::new(session.start_time);
+        *self.current_session.write().await = Some(session);
+        *self.pause.write().await = PauseState::default();
+        *self.micro_breaks_fired.write().await = 0;
+        self.persist_snapshot().await;
+        self.sync_keep_awake().await
+    }
+
+    fn build_snapshot(session: PomodoroSession, pause: &PauseState, completed: u32) -> PomodoroSnapshot {
+        PomodoroSnapshot {
+            completed_work_sessions: completed,
+            paused_total: pause.paused_total.to_std().unwrap_or_default(),
+            paused_at: pause.paused_at,
+            resume_status: pause.resume_to,
+            ..PomodoroSnapshot::new(session, Local::now())
+        }
+    }
+
+    /// 生成当前会话的快照
+    pub async fn snapshot(&self) -> Option<PomodoroSnapshot> {
+        let session = self.current_session.read().await.clone()?;
+        let pause = self.pause.read().await.clone();
+        let completed = *self.completed_work_sessions.read().await;
+        Some(Self::build_snapshot(session, &pause, completed))
+    }
+
+    /// 不等待锁的快照，供崩溃处理等同步场景使用
+    pub fn try_snapshot(&self) -> Option<PomodoroSnapshot> {
+        let session = self.current_session.try_read().ok()?.clone()?;
+        let pause = self.pause.try_read().map(|p| p.clone()).unwrap_or_default();
+        let completed = self.completed_work_sessions.try_read().map(|c| *c).unwrap_or_default();
+        Some(Self::build_snapshot(session, &pause, completed))
+    }
+
+    /// 启动时从快照恢复会话，已失效的会话会被保存并通知用户
+    pub async fn restore(&self, snapshot: PomodoroSnapshot) -> AppResult<RestoreOutcome> {
+        let was_work = snapshot.session.status == PomodoroStatus::Work;
+        let pause = PauseState {
+            paused_at: snapshot.paused_at,
+            paused_total: chrono::Duration::from_std(snapshot.paused_total).unwrap_or_else(|_| chrono::Duration::zero()),
+            resume_to: snapshot.resume_status,
+        };
+        *self.completed_work_sessions.write().await = snapshot.completed_work_sessions;
+        let outcome = snapshot.resolve(Local::now());
+
+        match &outcome {
+            RestoreOutcome::Resumed(session) => {
+                *self.current_session.write().await = Some(session.clone());
+                *self.pause.write().await = pause;
+                *self.anchor.write().await = ClockAnchor::new(Local::now());
+                self.sync_keep_awake().await?;
+            }
+            RestoreOutcome::Completed(session) => {
+                self.storage.save_pomodoro(session).await?;
+                *self.current_session.write().await = None;
+                if was_work {
+                    *self.completed_work_sessions.write().await += 1;
+                }
+            }
+            RestoreOutcome::Abandoned(session) => {
+                self.storage.save_pomodoro(session).await?;
+                *self.current_session.write().await = None;
+                if let Some(notifications) = &self.notifications {
+                    notifications.notify_session_abandoned(session).await?;
+                }
+            }
+        }
+
+        self.persist_snapshot().await;
+        Ok(outcome)
+    }
+}
+
+#[async_trait::async_trait]
+impl PomodoroTimer for PomodoroManager {
+    async fn start_session(&self, duration: i32) -> AppResult<()> {
+        self.ensure_break_taken().await?;
+        self.pending_decision.write().await.take();
+        self.begin_session(PomodoroStatus::Work, Duration::from_secs(duration as u64 * 60), None, Vec::new(), None)
+            .await
     }
 
     async fn pause_session(&self) -> AppResult<()> {
-        let mut current = self.current_session.write().await;
-        if let Some(session) = current.as_mut() {
+        let now = self.timer_now().await;
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            let mut pause = self.pause.write().await;
+            if pause.paused_at.is_none() {
+                pause.paused_at = Some(now);
+                pause.resume_to = Some(session.status);
+            }
             session.status = PomodoroStatus::Interrupted;
         }
-        Ok(())
+        self.persist_snapshot().await;
+        self.sync_keep_awake().await
     }
 
     async fn resume_session(&self) -> AppResult<()> {
-        let mut current = self.current_session.write().await;
-        if let Some(session) = current.as_mut() {
-            session.status = PomodoroStatus::Work;
+        let now = self.timer_now().await;
+        if let Some(session) = self.current_session.write().await.as_mut() {
+            let mut pause = self.pause.write().await;
+            session.status = pause.resume_to.take().unwrap_or(PomodoroStatus::Work);
+            if let Some(paused_at) = pause.paused_at.take() {
+                pause.paused_total = pause.paused_total + (now - paused_at);
+            }
         }
-        Ok(())
+        self.persist_snapshot().await;
+        self.sync_keep_awake().await
     }
 
     async fn stop_session(&self) -> AppResult<()> {
-        let mut current = self.current_session.write().await;
-        if let Some(mut session) = current.take() {
+        let next_phase = self.next_phase().await?;
+        let finished = self.current_session.write().await.take();
+        if let Some(mut session) = finished {
+            let finished_work = session.status == PomodoroStatus::Work;
+            let event = ended_event(session.status);
             session.end_time = Some(Local::now());
             session.status = PomodoroStatus::Completed;
             self.storage.save_pomodoro(&session).await?;
+            if let Some(notifications) = &self.notifications {
+                notifications.play_sound(self.settings().sound_for(event));
+            }
+
+            if finished_work {
+                self.emit(PluginEvent::PomodoroCompleted(session.clone()));
+                *self.completed_work_sessions.write().await += 1;
+                if !self.settings().min_enforced_break.is_zero() {
+                    let min_break = chrono::Duration::from_std(self.settings().min_enforced_break)
+                        .unwrap_or_else(|_| chrono::Duration::zero());
+                    *self.break_enforced_until.write().await = Some(Local::now() + min_break);
+                }
+                if let Some(notifications) = &self.notifications {
+                    match next_phase {
+                        PomodoroStatus::LongBreak => {
+                            let minutes = self.settings().long_break_duration.as_secs() / 60;
+                            notifications.notify_long_break_coming(minutes as u32).await?;
+                        }
+                        _ => {
+                            let minutes = self.settings().short_break_duration.as_secs() / 60;
+                            notifications.notify_break_start(minutes as u32).await?;
+                        }
+                    }
+                }
+
+                if self.settings().auto_start_breaks {
+                    self.start_break(next_phase).await?;
+                } else {
+                    let decision = PendingDecision {
+                        completed_session: session,
+                        next_phase,
+                        snoozed_until: None,
+                        snoozes_left: self.settings().max_snoozes,
+                    };
+                    self.prompt_decision(&decision);
+                    *self.pending_decision.write().await = Some(decision);
+                }
+            }
+        }
+        self.persist_snapshot().await;
+        self.sync_keep_awake().await
+    }
+
+    async fn skip_phase(&self) -> AppResult<()> {
+        let status = self.current_session.read().await.as_ref().map(|s| s.status);
+        match status {
+            Some(PomodoroStatus::Work) => {
+                self.stop_session().await?;
+                // 跳过时不等待用户选择，直接进入休息
+                let decision = self.pending_decision.write().await.take();
+                if let Some(decision) = decision {
+                    self.start_break(decision.next_phase).await?;
+                }
+                Ok(())
+            }
+            Some(PomodoroStatus::ShortBreak | PomodoroStatus::LongBreak) => {
+                self.ensure_break_taken().await?;
+                self.stop_session().await?;
+                self.begin_session(PomodoroStatus::Work, self.settings().work_duration, None, Vec::new(), None)
+                    .await
+            }
+            Some(_) => Err(AppError::InvalidOperation("暂停中的番茄钟不能跳过".into())),
+            None => Err(AppError::InvalidOperation("没有进行中的番茄钟".into())),
+        }
+    }
+
+    async fn snooze_break(&self) -> AppResult<()> {
+        self.resolve_decision(CompletionChoice::Snooze).await
+    }
+
+    async fn extend_phase(&self, extra: Duration) -> AppResult<()> {
+        {
+            let mut current = self.current_session.write().await;
+            let session = current
+                .as_mut()
+                .ok_or_else(|| AppError::InvalidOperation("没有进行中的番茄钟".into()))?;
+            session.duration += extra;
         }
+        self.persist_snapshot().await;
         Ok(())
     }
 
@@ -69,6 +889,46 @@ impl PomodoroTimer for PomodoroManager {
     async fn is_active(&self) -> AppResult<bool> {
         Ok(self.current_session.read().await.is_some())
     }
+
+    async fn next_phase(&self) -> AppResult<PomodoroStatus> {
+        let current = self.current_session.read().await.as_ref().map(|s| s.status);
+        let completed = *self.completed_work_sessions.read().await;
+        Ok(next_phase_after(current, completed, self.settings().long_break_interval))
+    }
+
+    async fn elapsed(&self) -> AppResult<Duration> {
+        let Some(started_at) = self.phase_started_at().await? else {
+            return Ok(Duration::ZERO);
+        };
+        let now = self.timer_now().await;
+        Ok(self.pause.read().await.elapsed(started_at, now))
+    }
+
+    async fn current_phase_duration(&self) -> AppResult<Option<Duration>> {
+        Ok(self.current_session.read().await.as_ref().map(|s| s.duration))
+    }
+
+    async fn completed_count(&self) -> AppResult<u32> {
+        Ok(*self.completed_work_sessions.read().await)
+    }
+
+    async fn phase_started_at(&self) -> AppResult<Option<DateTime<Local>>> {
+        Ok(self.current_session.read().await.as_ref().map(|s| s.start_time))
+    }
+
+    async fn get_remaining_time(&self) -> AppResult<Duration> {
+        let Some(duration) = self.current_phase_duration().await? else {
+            return Ok(Duration::ZERO);
+        };
+        Ok(duration.saturating_sub(self.elapsed().await?))
+    }
+
+    async fn apply_preset(&self, name: &str) -> AppResult<()> {
+        self.settings.lock().unwrap().apply_preset(name)?;
+        // 进行中的阶段保持原时长，空闲时立即刷新显示的剩余时间
+        self.state_changed.notify_one();
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -85,9 +945,671 @@ impl PomodoroService for PomodoroManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::traits::MockStorage;
+
+    fn running_session(start_time: DateTime<Local>) -> PomodoroSession {
+        PomodoroSession {
+            id: None,
+            start_time,
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Work,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }
+    }
 
     #[tokio::test]
     async fn test_pomodoro_manager() {
         // TODO: 添加测试用例
     }
+
+    #[test]
+    fn test_restore_snapshot_that_completed_normally() {
+        let now = Local::now();
+        let start = now - chrono::Duration::hours(3);
+        // 应用一直运行到计划结束前几秒
+        let saved_at = start + chrono::Duration::minutes(25) - chrono::Duration::seconds(10);
+        let snapshot = PomodoroSnapshot::new(running_session(start), saved_at);
+
+        match snapshot.resolve(now) {
+            RestoreOutcome::Completed(session) => {
+                assert_eq!(session.status, PomodoroStatus::Completed);
+                assert_eq!(session.end_time, Some(start + chrono::Duration::minutes(25)));
+            }
+            other => panic!("expected completed session, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_restore_snapshot_abandoned_far_past_window() {
+        let now = Local::now();
+        let start = now - chrono::Duration::hours(3);
+        // 应用在会话开始 5 分钟后崩溃
+        let saved_at = start + chrono::Duration::minutes(5);
+        let snapshot = PomodoroSnapshot::new(running_session(start), saved_at);
+
+        match snapshot.resolve(now) {
+            RestoreOutcome::Abandoned(session) => {
+                assert_eq!(session.status, PomodoroStatus::Interrupted);
+                assert_eq!(session.end_time, Some(saved_at));
+                assert_eq!(session.duration, Duration::from_secs(5 * 60));
+            }
+            other => panic!("expected abandoned session, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_restore_snapshot_accounts_for_pauses() {
+        let now = Local::now();
+        let start = now - chrono::Duration::minutes(30);
+        // 中途暂停了 10 分钟，计划结束时间顺延到 5 分钟后
+        let snapshot = PomodoroSnapshot {
+            paused_total: Duration::from_secs(10 * 60),
+            ..PomodoroSnapshot::new(running_session(start), now - chrono::Duration::minutes(1))
+        };
+        assert!(matches!(snapshot.resolve(now), RestoreOutcome::Resumed(_)));
+    }
+
+    async fn next_tick(ticks: &mut (impl Stream<Item = TimerTick> + Unpin)) -> TimerTick {
+        use tokio_stream::StreamExt;
+        tokio::time::timeout(Duration::from_millis(500), ticks.next())
+            .await
+            .expect("tick not delivered promptly")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_emits_on_state_change() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let manager = Arc::new(PomodoroManager::new(Arc::new(mock_storage)));
+        let mut ticks = manager.subscribe().await;
+        assert_eq!(next_tick(&mut ticks).await.status, None);
+
+        // 状态变化不必等到下一秒
+        manager.start_session(25).await?;
+        let mut tick = next_tick(&mut ticks).await;
+        while tick.status.is_none() {
+            tick = next_tick(&mut ticks).await;
+        }
+        assert_eq!(tick.status, Some(PomodoroStatus::Work));
+        assert!(tick.remaining <= Duration::from_secs(25 * 60));
+
+        manager.pause_session().await?;
+        while tick.status == Some(PomodoroStatus::Work) {
+            tick = next_tick(&mut ticks).await;
+        }
+        assert_eq!(tick.status, Some(PomodoroStatus::Interrupted));
+
+        // 释放订阅后后台任务退出
+        drop(ticks);
+        assert!(manager.ticker.lock().unwrap().as_ref().unwrap().is_closed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ended_event_matches_finished_phase() {
+        assert_eq!(ended_event(PomodoroStatus::Work), PomodoroEvent::WorkComplete);
+        assert_eq!(ended_event(PomodoroStatus::ShortBreak), PomodoroEvent::BreakComplete);
+        assert_eq!(ended_event(PomodoroStatus::LongBreak), PomodoroEvent::BreakComplete);
+        assert_eq!(ended_event(PomodoroStatus::Interrupted), PomodoroEvent::Interrupted);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_survives_restart() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("pomodoro_snapshot.json");
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(mock_storage);
+
+        let before = PomodoroManager::new(storage.clone())
+            .with_settings(PomodoroSettings {
+                auto_start_breaks: true,
+                ..AppConfig::default().pomodoro
+            })
+            .with_snapshot_path(&path);
+        before.start_session(25).await?;
+        before.stop_session().await?;
+        before.stop_session().await?;
+        before.start_session(25).await?;
+        before.pause_session().await?;
+        assert!(path.exists());
+
+        // 重启后恢复暂停中的会话和已完成的番茄数
+        let after = PomodoroManager::new(storage).with_snapshot_path(&path);
+        assert!(matches!(after.restore_saved().await?, Some(RestoreOutcome::Resumed(_))));
+        assert_eq!(*after.completed_work_sessions.read().await, 1);
+        assert!(after.pause.read().await.paused_at.is_some());
+        after.resume_session().await?;
+        assert_eq!(
+            after.get_current_session().await?.map(|s| s.status),
+            Some(PomodoroStatus::Work)
+        );
+
+        // 没有进行中的会话时删除快照
+        after.stop_session().await?;
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_phase_long_break_on_interval() {
+        let interval = 4;
+        for completed in 0..8 {
+            let expected = if (completed + 1) % interval == 0 {
+                PomodoroStatus::LongBreak
+            } else {
+                PomodoroStatus::ShortBreak
+            };
+            assert_eq!(next_phase_after(Some(PomodoroStatus::Work), completed, interval), expected);
+        }
+        assert_eq!(next_phase_after(Some(PomodoroStatus::LongBreak), 4, interval), PomodoroStatus::Work);
+        assert_eq!(next_phase_after(None, 0, interval), PomodoroStatus::Work);
+    }
+
+    #[tokio::test]
+    async fn test_manager_next_phase_after_completions() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.long_break_interval = 2;
+        let manager = PomodoroManager::new(Arc::new(mock_storage)).with_settings(settings);
+
+        manager.start_session(25).await?;
+        assert_eq!(manager.next_phase().await?, PomodoroStatus::ShortBreak);
+        manager.stop_session().await?;
+
+        manager.start_session(25).await?;
+        assert_eq!(manager.next_phase().await?, PomodoroStatus::LongBreak);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_session_inherits_defaults() -> AppResult<()> {
+        let mut project = Project::new("Client".into(), None);
+        project.id = Some(3);
+        project.default_tags = vec!["billing".into(), "deep-work".into()];
+        project.default_category = Some("development".into());
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_pomodoro()
+            .withf(|session| {
+                session.project_id == Some(3)
+                    && session.tags == ["billing", "deep-work"]
+                    && session.category.as_deref() == Some("development")
+            })
+            .times(1)
+            .returning(|_| Ok(1));
+        let manager = PomodoroManager::new(Arc::new(mock_storage));
+
+        manager
+            .start_project_session(Duration::from_secs(25 * 60), &project, Vec::new(), None)
+            .await?;
+        manager.stop_session().await?;
+
+        // 显式指定的值优先于项目默认值
+        manager
+            .start_project_session(Duration::from_secs(25 * 60), &project, vec!["review".into()], Some("meeting".into()))
+            .await?;
+        let session = manager.get_current_session().await?.unwrap();
+        assert_eq!(session.tags, ["review"]);
+        assert_eq!(session.category.as_deref(), Some("meeting"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enforced_break_blocks_new_work() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.min_enforced_break = Duration::from_secs(5 * 60);
+        let manager = PomodoroManager::new(Arc::new(mock_storage)).with_settings(settings);
+
+        manager.start_session(25).await?;
+        manager.stop_session().await?;
+
+        let remaining = manager.enforced_break_remaining().await.unwrap();
+        assert!(remaining <= Duration::from_secs(5 * 60) && remaining > Duration::from_secs(4 * 60));
+        assert!(matches!(manager.start_session(25).await, Err(AppError::InvalidOperation(_))));
+        let project = Project::new("Client".into(), None);
+        assert!(manager
+            .start_project_session(Duration::from_secs(25 * 60), &project, Vec::new(), None)
+            .await
+            .is_err());
+
+        // 休息时间已过
+        *manager.break_enforced_until.write().await = Some(Local::now() - chrono::Duration::seconds(1));
+        assert!(manager.enforced_break_remaining().await.is_none());
+        manager.start_session(25).await?;
+        assert!(manager.is_active().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_excludes_paused_time() -> AppResult<()> {
+        let manager = PomodoroManager::new(Arc::new(MockStorage::new()));
+        manager.start_session(25).await?;
+        assert_eq!(manager.current_phase_duration().await?, Some(Duration::from_secs(25 * 60)));
+
+        // 模拟 10 分钟前开始，4 分钟前暂停
+        let started_at = Local::now() - chrono::Duration::minutes(10);
+        manager.current_session.write().await.as_mut().unwrap().start_time = started_at;
+        manager.pause_session().await?;
+        manager.pause.write().await.paused_at = Some(Local::now() - chrono::Duration::minutes(4));
+        assert_eq!(manager.phase_started_at().await?, Some(started_at));
+
+        // 暂停期间已进行时长不再增加
+        let paused = manager.elapsed().await?;
+        assert!(paused.abs_diff(Duration::from_secs(6 * 60)) < Duration::from_secs(1));
+
+        manager.resume_session().await?;
+        let resumed = manager.elapsed().await?;
+        assert!(resumed.abs_diff(Duration::from_secs(6 * 60)) < Duration::from_secs(1));
+        // 剩余时长同样不计暂停的 4 分钟
+        let remaining = manager.get_remaining_time().await?;
+        assert!(remaining.abs_diff(Duration::from_secs(19 * 60)) < Duration::from_secs(1));
+        assert_eq!(manager.completed_count().await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_micro_breaks_fire_during_long_session() -> AppResult<()> {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let fired = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&fired);
+        let mut settings = AppConfig::default().pomodoro;
+        settings.micro_break_every = Some(Duration::from_secs(20 * 60));
+        let manager = PomodoroManager::new(Arc::new(MockStorage::new()))
+            .with_settings(settings)
+            .with_micro_break_callback(Arc::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+
+        manager.start_session(90).await?;
+        let started_at = manager.phase_started_at().await?.unwrap();
+
+        // 每 30 秒检查一次，直到会话结束
+        for tick in 0..=(90 * 2) {
+            manager.check_micro_break_at(started_at + chrono::Duration::seconds(tick * 30)).await?;
+        }
+
+        // 第 20、40、60、80 分钟各一次，会话计时不受影响
+        assert_eq!(fired.load(Ordering::SeqCst), 4);
+        assert_eq!(manager.current_phase_duration().await?, Some(Duration::from_secs(90 * 60)));
+        assert!(manager.is_active().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_attribute_interruptions() -> AppResult<()> {
+        let start = Local::now() - chrono::Duration::hours(30);
+        let session = |project_id, status, offset_hours| PomodoroSession {
+            project_id: Some(project_id),
+            status,
+            ..running_session(start + chrono::Duration::hours(offset_hours))
+        };
+        let sessions = vec![
+            session(1, PomodoroStatus::Completed, 0),
+            session(1, PomodoroStatus::Interrupted, 1),
+            session(2, PomodoroStatus::Completed, 2),
+            session(1, PomodoroStatus::Completed, 26),
+        ];
+
+        let mut mock_storage = MockStorage::new();
+        let returned = sessions.clone();
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .returning(move |_, _, _| Ok(returned.clone()));
+        let manager = PomodoroManager::new(Arc::new(mock_storage));
+
+        let stats = manager.get_project_stats(1, start, Local::now()).await?;
+        assert_eq!(stats.completed, 2);
+        assert_eq!(stats.interrupted, 1);
+        assert_eq!(stats.focus_time, Duration::from_secs(50 * 60));
+
+        let daily = PomodoroStats::daily_completed(&sessions, Some(1));
+        assert_eq!(daily.values().sum::<u32>(), 2);
+        assert_eq!(PomodoroStats::daily_completed(&sessions, None).values().sum::<u32>(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_skip_and_extend_phase() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_pomodoro()
+            .withf(|session| session.status == PomodoroStatus::Completed)
+            .times(2)
+            .returning(|_| Ok(1));
+        let manager = PomodoroManager::new(Arc::new(mock_storage));
+        assert!(manager.skip_phase().await.is_err());
+
+        manager.start_session(25).await?;
+        manager.extend_phase(Duration::from_secs(5 * 60)).await?;
+        assert_eq!(manager.current_phase_duration().await?, Some(Duration::from_secs(30 * 60)));
+
+        // 跳过工作直接进入休息，计入已完成的番茄
+        manager.skip_phase().await?;
+        let session = manager.get_current_session().await?.unwrap();
+        assert_eq!(session.status, PomodoroStatus::ShortBreak);
+        assert_eq!(session.duration, Duration::from_secs(5 * 60));
+        assert_eq!(manager.completed_count().await?, 1);
+        assert!(manager.pending_decision().await.is_none());
+
+        // 跳过休息开始新的工作番茄，延长不影响下一阶段
+        manager.skip_phase().await?;
+        let session = manager.get_current_session().await?.unwrap();
+        assert_eq!(session.status, PomodoroStatus::Work);
+        assert_eq!(session.duration, Duration::from_secs(25 * 60));
+
+        manager.pause_session().await?;
+        assert!(manager.skip_phase().await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clock_anchor_ignores_wall_clock_jump_when_monotonic() {
+        let start = Local::now();
+        let anchor = ClockAnchor::new(start);
+        // 单调时钟经过 10 分钟，系统时间被向前调整了 1 小时
+        let wall_now = start + chrono::Duration::minutes(70);
+        let since_anchor = Duration::from_secs(10 * 60);
+
+        assert_eq!(anchor.now(TimingSource::WallClock, wall_now, since_anchor), wall_now);
+        assert_eq!(
+            anchor.now(TimingSource::Monotonic, wall_now, since_anchor),
+            start + chrono::Duration::minutes(10)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_elapsed_follows_timing_source() -> AppResult<()> {
+        for (source, expected_minutes) in [(TimingSource::WallClock, 60), (TimingSource::Monotonic, 10)] {
+            let mut settings = AppConfig::default().pomodoro;
+            settings.timing_source = source;
+            let manager = PomodoroManager::new(Arc::new(MockStorage::new())).with_settings(settings);
+            manager.start_session(90).await?;
+
+            // 模拟系统时间跳变：会话在系统时间上已开始 1 小时，单调时钟只经过 10 分钟
+            let started_at = Local::now() - chrono::Duration::minutes(60);
+            manager.current_session.write().await.as_mut().unwrap().start_time = started_at;
+            *manager.anchor.write().await = ClockAnchor::new(started_at + chrono::Duration::minutes(10));
+
+            let elapsed = manager.elapsed().await?.as_secs() / 60;
+            assert_eq!(elapsed, expected_minutes, "{:?}", source);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pause_state_elapsed_with_multiple_pauses() {
+        let start = Local::now();
+        let pause = PauseState {
+            paused_at: None,
+            paused_total: chrono::Duration::minutes(3),
+            resume_to: None,
+        };
+        assert_eq!(pause.elapsed(start, start + chrono::Duration::minutes(10)), Duration::from_secs(7 * 60));
+
+        let paused = PauseState {
+            paused_at: Some(start + chrono::Duration::minutes(8)),
+            ..pause
+        };
+        assert_eq!(paused.elapsed(start, start + chrono::Duration::minutes(30)), Duration::from_secs(5 * 60));
+    }
+
+    #[tokio::test]
+    async fn test_resume_returns_to_paused_phase() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("pomodoro_snapshot.json");
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(mock_storage);
+        let manager = PomodoroManager::new(storage.clone())
+            .with_settings(PomodoroSettings {
+                auto_start_breaks: true,
+                ..AppConfig::default().pomodoro
+            })
+            .with_snapshot_path(&path);
+
+        manager.start_session(25).await?;
+        manager.stop_session().await?;
+        manager.pause_session().await?;
+        // 重复暂停不覆盖暂停前的阶段
+        manager.pause_session().await?;
+
+        // 其他进程从快照继续
+        let other = PomodoroManager::new(storage).with_snapshot_path(&path);
+        other.restore_saved().await?;
+        other.resume_session().await?;
+        assert_eq!(other.get_current_session().await?.unwrap().status, PomodoroStatus::ShortBreak);
+        assert_eq!(PomodoroSnapshot::load(&path)?.unwrap().paused_at, None);
+        Ok(())
+    }
+
+    async fn manager_with_pending_decision() -> AppResult<PomodoroManager> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let manager = PomodoroManager::new(Arc::new(mock_storage));
+
+        manager.start_session(25).await?;
+        manager.stop_session().await?;
+        assert!(manager.pending_decision().await.is_some());
+        assert!(!manager.is_active().await?);
+
+        Ok(manager)
+    }
+
+    #[tokio::test]
+    async fn test_decision_start_break() -> AppResult<()> {
+        let manager = manager_with_pending_decision().await?;
+        manager.resolve_decision(CompletionChoice::StartBreak).await?;
+
+        let session = manager.get_current_session().await?.unwrap();
+        assert_eq!(session.status, PomodoroStatus::ShortBreak);
+        assert_eq!(session.duration, Duration::from_secs(5 * 60));
+        assert!(manager.pending_decision().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_decision_extend_work() -> AppResult<()> {
+        let manager = manager_with_pending_decision().await?;
+        manager.resolve_decision(CompletionChoice::ExtendWork).await?;
+
+        let session = manager.get_current_session().await?.unwrap();
+        assert_eq!(session.status, PomodoroStatus::Work);
+        assert_eq!(session.duration, EXTEND_WORK_DURATION);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_decision_idle() -> AppResult<()> {
+        let manager = manager_with_pending_decision().await?;
+        manager.resolve_decision(CompletionChoice::Idle).await?;
+
+        assert!(!manager.is_active().await?);
+        assert!(manager.pending_decision().await.is_none());
+        assert!(manager.resolve_decision(CompletionChoice::Idle).await.is_err());
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct RecordingPlatform {
+        sleep_calls: std::sync::Mutex<Vec<bool>>,
+        notifications: std::sync::Mutex<Vec<NotificationOptions>>,
+    }
+
+    impl PlatformOperations for RecordingPlatform {
+        fn get_active_window(&self) -> AppResult<crate::infrastructure::platform::WindowInfo> {
+            Err(AppError::System("not available in tests".into()))
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+
+        fn prevent_system_sleep(&self, prevent: bool) -> AppResult<()> {
+            self.sleep_calls.lock().unwrap().push(prevent);
+            Ok(())
+        }
+
+        fn show_notification(&self, options: NotificationOptions) -> AppResult<()> {
+            self.notifications.lock().unwrap().push(options);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snooze_break_reprompts_until_limit() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.max_snoozes = 1;
+        let platform = Arc::new(RecordingPlatform::default());
+        let manager = PomodoroManager::new(Arc::new(mock_storage))
+            .with_settings(settings)
+            .with_platform(platform.clone());
+
+        manager.start_session(25).await?;
+        manager.stop_session().await?;
+        {
+            let prompts = platform.notifications.lock().unwrap();
+            assert_eq!(prompts.len(), 1);
+            assert_eq!(prompts[0].id, BREAK_PROMPT_NOTIFICATION);
+            assert!(prompts[0].action_button.is_some());
+        }
+
+        manager.snooze_break().await?;
+        let until = manager.pending_decision().await.unwrap().snoozed_until.unwrap();
+        // 推迟中不能再次推迟，到期前不提醒
+        assert!(manager.snooze_break().await.is_err());
+        assert!(!manager.check_snooze_at(until - chrono::Duration::seconds(1)).await?);
+
+        // 到期后再次提醒，次数用完后不再提供推迟按钮
+        assert!(manager.check_snooze_at(until).await?);
+        {
+            let prompts = platform.notifications.lock().unwrap();
+            assert_eq!(prompts.len(), 2);
+            assert!(prompts[1].action_button.is_none());
+        }
+        assert!(manager.snooze_break().await.is_err());
+
+        manager.resolve_decision(CompletionChoice::StartBreak).await?;
+        assert_eq!(manager.get_current_session().await?.unwrap().status, PomodoroStatus::ShortBreak);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_plugin_events_on_completion_and_break() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.auto_start_breaks = true;
+        let (sender, mut events) = mpsc::unbounded_channel();
+        let manager = PomodoroManager::new(Arc::new(mock_storage))
+            .with_settings(settings)
+            .with_plugin_events(sender);
+
+        manager.start_session(25).await?;
+        manager.stop_session().await?;
+        // 休息结束不产生事件
+        manager.stop_session().await?;
+
+        assert!(matches!(
+            events.try_recv(),
+            Ok(PluginEvent::PomodoroCompleted(session)) if session.status == PomodoroStatus::Completed
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(PluginEvent::BreakStarted(session)) if session.status == PomodoroStatus::ShortBreak
+        ));
+        assert!(events.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keep_awake_only_during_work() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.keep_awake_during_work = true;
+        settings.auto_start_breaks = true;
+        let platform = Arc::new(RecordingPlatform::default());
+        let manager = PomodoroManager::new(Arc::new(mock_storage))
+            .with_settings(settings)
+            .with_platform(platform.clone());
+
+        // 工作
+        manager.start_session(25).await?;
+        assert!(*manager.keep_awake.read().await);
+
+        // 工作结束自动进入休息
+        manager.stop_session().await?;
+        assert_eq!(manager.get_current_session().await?.unwrap().status, PomodoroStatus::ShortBreak);
+        assert!(!*manager.keep_awake.read().await);
+
+        // 休息结束后开始新的工作
+        manager.stop_session().await?;
+        manager.start_session(25).await?;
+        assert!(*manager.keep_awake.read().await);
+
+        manager.pause_session().await?;
+        assert!(!*manager.keep_awake.read().await);
+        manager.resume_session().await?;
+        manager.shutdown().await?;
+
+        assert_eq!(*platform.sleep_calls.lock().unwrap(), vec![true, false, true, false, true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_snapshot_still_running() {
+        let now = Local::now();
+        let start = now - chrono::Duration::minutes(10);
+        let snapshot = PomodoroSnapshot::new(running_session(start), now - chrono::Duration::minutes(1));
+
+        assert!(matches!(snapshot.resolve(now), RestoreOutcome::Resumed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_preset_applies_from_next_phase() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_save_pomodoro().returning(|_| Ok(1));
+        let mut settings = AppConfig::default().pomodoro;
+        settings.auto_start_breaks = true;
+        let manager = PomodoroManager::new(Arc::new(mock_storage)).with_settings(settings);
+
+        // 空闲时立即更新剩余时间
+        manager.apply_preset("Deep-Work").await?;
+        assert_eq!(manager.current_tick().await.remaining, Duration::from_secs(50 * 60));
+        assert_eq!(manager.settings().active_preset.as_deref(), Some("deep-work"));
+
+        // 进行中的阶段保持原时长，下一阶段使用新预设
+        manager.start_session(50).await?;
+        manager.apply_preset("classic").await?;
+        assert_eq!(manager.current_phase_duration().await?, Some(Duration::from_secs(50 * 60)));
+        manager.stop_session().await?;
+        assert_eq!(manager.current_phase_duration().await?, Some(Duration::from_secs(5 * 60)));
+
+        assert!(matches!(manager.apply_preset("sprint").await, Err(AppError::NotFound(_))));
+        assert_eq!(manager.settings().active_preset.as_deref(), Some("classic"));
+        Ok(())
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,212 @@
+use crate::core::traits::PomodoroTimer;
+use crate::core::{models::PomodoroStatus, AppResult};
+use crate::domain::activity::ActivityManager;
+use crate::domain::config::PowerSettings;
+use crate::domain::pomodoro::PomodoroManager;
+use crate::infrastructure::platform::PowerEvent;
+use chrono::{DateTime, Local};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    /// 在休眠时刻结束当前活动
+    CloseActivity(DateTime<Local>),
+    PauseTimer,
+    ResumeTimer,
+}
+
+/// 根据配置决定休眠和唤醒时要执行的操作，只恢复由休眠暂停的番茄钟
+#[derive(Debug, Clone)]
+pub struct PowerPolicy {
+    settings: PowerSettings,
+    suspended_at: Option<DateTime<Local>>,
+    timer_paused: bool,
+}
+
+impl PowerPolicy {
+    pub fn new(settings: PowerSettings) -> Self {
+        Self {
+            settings,
+            suspended_at: None,
+            timer_paused: false,
+        }
+    }
+
+    pub fn suspended_since(&self) -> Option<DateTime<Local>> {
+        self.suspended_at
+    }
+
+    /// `work_running` 表示收到事件时是否有进行中的工作番茄，休息阶段在休眠期间照常计时
+    pub fn handle(&mut self, event: PowerEvent, work_running: bool) -> Vec<PowerAction> {
+        let mut actions = Vec::new();
+        match event {
+            PowerEvent::Suspend(at) => {
+                // 部分系统会重复发送休眠通知
+                if self.suspended_at.is_some() {
+                    return actions;
+                }
+                self.suspended_at = Some(at);
+                if self.settings.drop_sleep_from_tracking {
+                    actions.push(PowerAction::CloseActivity(at));
+                }
+                if self.settings.pause_timer_on_suspend && work_running {
+                    self.timer_paused = true;
+                    actions.push(PowerAction::PauseTimer);
+                }
+            }
+            PowerEvent::Resume(_) => {
+                if self.suspended_at.take().is_none() {
+                    return actions;
+                }
+                if std::mem::take(&mut self.timer_paused) {
+                    actions.push(PowerAction::ResumeTimer);
+                }
+            }
+        }
+        actions
+    }
+}
+
+pub struct PowerEventHandler {
+    policy: Mutex<PowerPolicy>,
+    pomodoro: Arc<PomodoroManager>,
+    activity: Arc<ActivityManager>,
+}
+
+impl PowerEventHandler {
+    pub fn new(settings: PowerSettings, pomodoro: Arc<PomodoroManager>, activity: Arc<ActivityManager>) -> Self {
+        Self {
+            policy: Mutex::new(PowerPolicy::new(settings)),
+            pomodoro,
+            activity,
+        }
+    }
+
+    pub async fn handle(&self, event: PowerEvent) -> AppResult<Vec<PowerAction>> {
+        let work_running = matches!(
+            self.pomodoro.get_current_session().await?.map(|s| s.status),
+            Some(PomodoroStatus::Work)
+        );
+        let actions = self.policy.lock().await.handle(event, work_running);
+
+        for action in &actions {
+            match action {
+                PowerAction::CloseActivity(at) => self.activity.close_current_activity_at(*at).await?,
+                PowerAction::PauseTimer => self.pomodoro.pause_session().await?,
+                PowerAction::ResumeTimer => self.pomodoro.resume_session().await?,
+            }
+        }
+        Ok(actions)
+    }
+
+    /// 接收平台层通过 `watch_power_events` 发送的事件
+    pub fn spawn(self: Arc<Self>, mut events: mpsc::UnboundedReceiver<PowerEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = self.handle(event).await {
+                    log::error!("处理电源事件失败: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Activity;
+    use crate::core::traits::{MockStorage, TimeTracker};
+    use std::time::Duration;
+
+    #[test]
+    fn test_policy_pauses_and_resumes_timer() {
+        let mut policy = PowerPolicy::new(PowerSettings::default());
+        let asleep = Local::now();
+
+        assert_eq!(
+            policy.handle(PowerEvent::Suspend(asleep), true),
+            vec![PowerAction::CloseActivity(asleep), PowerAction::PauseTimer]
+        );
+        // 重复的休眠通知被忽略
+        assert!(policy.handle(PowerEvent::Suspend(asleep), true).is_empty());
+        assert_eq!(policy.suspended_since(), Some(asleep));
+
+        let awake = asleep + chrono::Duration::hours(1);
+        assert_eq!(policy.handle(PowerEvent::Resume(awake), false), vec![PowerAction::ResumeTimer]);
+        assert!(policy.handle(PowerEvent::Resume(awake), false).is_empty());
+
+        // 休息阶段不暂停，唤醒时也不恢复
+        assert_eq!(
+            policy.handle(PowerEvent::Suspend(awake), false),
+            vec![PowerAction::CloseActivity(awake)]
+        );
+        assert!(policy.handle(PowerEvent::Resume(awake), false).is_empty());
+    }
+
+    #[test]
+    fn test_policy_disabled() {
+        let mut policy = PowerPolicy::new(PowerSettings {
+            pause_timer_on_suspend: false,
+            drop_sleep_from_tracking: false,
+        });
+        assert!(policy.handle(PowerEvent::Suspend(Local::now()), true).is_empty());
+        assert!(policy.handle(PowerEvent::Resume(Local::now()), true).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handler_applies_policy() -> AppResult<()> {
+        let asleep = Local::now();
+        let started = asleep - chrono::Duration::minutes(30);
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_save_activity()
+            .withf(move |activity: &Activity| {
+                activity.end_time == Some(asleep) && activity.duration == Duration::from_secs(30 * 60)
+            })
+            .times(1)
+            .returning(|_| Ok(1));
+        let storage = Arc::new(mock_storage);
+
+        let activity = Arc::new(ActivityManager::new(storage.clone()));
+        let pomodoro = Arc::new(PomodoroManager::new(storage));
+        activity
+            .start_tracking(Activity {
+                id: None,
+                name: "code".into(),
+                start_time: started,
+                end_time: None,
+                project_id: None,
+                description: None,
+                duration: Duration::ZERO,
+                category: "development".into(),
+                is_productive: true,
+                app_name: "code".into(),
+                window_title: "main.rs".into(),
+                is_billable: None,
+                tags: Vec::new(),
+                utc_offset: None,
+                manual: false,
+            })
+            .await?;
+        pomodoro.start_session(25).await?;
+
+        let handler = PowerEventHandler::new(PowerSettings::default(), pomodoro.clone(), activity.clone());
+        handler.handle(PowerEvent::Suspend(asleep)).await?;
+        assert!(!activity.is_tracking().await?);
+        assert_eq!(
+            pomodoro.get_current_session().await?.map(|s| s.status),
+            Some(PomodoroStatus::Interrupted)
+        );
+
+        handler.handle(PowerEvent::Resume(asleep + chrono::Duration::hours(8))).await?;
+        assert_eq!(
+            pomodoro.get_current_session().await?.map(|s| s.status),
+            Some(PomodoroStatus::Work)
+        );
+
+        Ok(())
+    }
+}
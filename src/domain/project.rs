@@ -1,40 +1,213 @@
 use crate::core::{AppResult, models::*, traits::*};
 use crate::core::traits::Storage;
+use crate::infrastructure::maintenance::{Clock, SystemClock};
 use chrono::{DateTime, Local};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// 项目统计的缓存时间，期间新增的番茄钟不会立即反映
+pub const PROJECT_STATS_TTL: Duration = Duration::from_secs(30);
+
+/// 新项目依次使用的颜色（Tableau 10 分类色板）
+pub const PROJECT_PALETTE: [&str; 10] = [
+    "#4E79A7", "#F28E2B", "#E15759", "#76B7B2", "#59A14F", "#EDC948", "#B07AA1", "#FF9DA7", "#9C755F", "#BAB0AC",
+];
+
+pub const LIGHT_BACKGROUND: &str = "#FFFFFF";
+pub const DARK_BACKGROUND: &str = "#121212";
+
+/// 项目颜色与背景的最低对比度，按 WCAG 对图形元素的要求
+const MIN_CONTRAST: f64 = 3.0;
+
+/// 主题对应的背景色，跟随系统时按浅色处理
+pub fn theme_background(theme: &str) -> &'static str {
+    if theme.eq_ignore_ascii_case("dark") {
+        DARK_BACKGROUND
+    } else {
+        LIGHT_BACKGROUND
+    }
+}
+
+/// 解析 `#RRGGBB`，`#` 可省略
+pub fn parse_hex_color(input: &str) -> Option<[u8; 3]> {
+    let hex = input.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn relative_luminance([r, g, b]: [u8; 3]) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+#[derive(Default)]
+struct ProjectCache {
+    /// 每次失效时加一。查询存储期间缓存被清空过时，查询结果可能已过期，不再写入
+    generation: u64,
+    list: Option<Arc<[Project]>>,
+    by_id: HashMap<i64, Project>,
+    /// 按 (项目, 范围起点, 范围终点) 缓存，值中保存计算时间
+    stats: HashMap<(i64, DateTime<Local>, DateTime<Local>), (DateTime<Local>, ProjectPomodoroStats)>,
+}
 
 pub struct ProjectManager {
     storage: Arc<dyn Storage + Send + Sync>,
+    cache: RwLock<ProjectCache>,
+    clock: Arc<dyn Clock>,
 }
 
 impl ProjectManager {
     pub fn new(storage: Arc<dyn Storage + Send + Sync>) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            cache: RwLock::new(ProjectCache::default()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 所有项目，首次调用时读取存储，之后直到项目被修改前都返回缓存
+    pub async fn projects_cached(&self) -> AppResult<Arc<[Project]>> {
+        let generation = {
+            let cache = self.cache.read().await;
+            if let Some(list) = cache.list.clone() {
+                return Ok(list);
+            }
+            cache.generation
+        };
+        let projects: Arc<[Project]> = self.storage.list_projects().await?.into();
+        let mut cache = self.cache.write().await;
+        if cache.generation != generation {
+            return Ok(projects);
+        }
+        cache.by_id = projects
+            .iter()
+            .filter_map(|project| project.id.map(|id| (id, project.clone())))
+            .collect();
+        cache.list = Some(projects.clone());
+        Ok(projects)
+    }
+
+    /// 项目的番茄统计，同一项目和范围在 `PROJECT_STATS_TTL` 内重复调用时返回缓存，不重新查询
+    pub async fn project_stats_cached(
+        &self,
+        project_id: i64,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> AppResult<ProjectPomodoroStats> {
+        let now = self.clock.now();
+        let key = (project_id, start, end);
+        let generation = {
+            let cache = self.cache.read().await;
+            if let Some((computed_at, stats)) = cache.stats.get(&key) {
+                if (now - *computed_at).to_std().map_or(false, |age| age < PROJECT_STATS_TTL) {
+                    return Ok(stats.clone());
+                }
+            }
+            cache.generation
+        };
+        let sessions = self.storage.get_project_pomodoro_sessions(project_id, start, end).await?;
+        let stats = PomodoroStats::calculate_project(&sessions, project_id);
+        let mut cache = self.cache.write().await;
+        if cache.generation == generation {
+            cache.stats.insert(key, (now, stats.clone()));
+        }
+        Ok(stats)
+    }
+
+    /// 为新项目推荐颜色：按色板顺序取第一个未被使用且与背景对比度足够的颜色，
+    /// 都已使用时按项目数量循环
+    pub fn suggest_color(&self, existing: &[Project], background: &str) -> String {
+        let background = parse_hex_color(background).unwrap_or([0xFF; 3]);
+        let readable: Vec<&str> = PROJECT_PALETTE
+            .iter()
+            .copied()
+            .filter(|color| parse_hex_color(color).map_or(false, |rgb| contrast_ratio(rgb, background) >= MIN_CONTRAST))
+            .collect();
+        let used = |color: &str| {
+            existing
+                .iter()
+                .filter_map(|project| project.color.as_deref())
+                .any(|used| used.trim().eq_ignore_ascii_case(color))
+        };
+        readable
+            .iter()
+            .find(|color| !used(color))
+            .or_else(|| readable.get(existing.len() % readable.len().max(1)))
+            .unwrap_or(&PROJECT_PALETTE[0])
+            .to_string()
+    }
+
+    /// 项目修改后在同一调用中清空缓存，之后的读取不会返回旧数据
+    async fn invalidate(&self) {
+        let mut cache = self.cache.write().await;
+        *cache = ProjectCache {
+            generation: cache.generation + 1,
+            ..ProjectCache::default()
+        };
     }
 }
 
 #[async_trait::async_trait]
 impl ProjectService for ProjectManager {
     async fn create_project(&self, project: Project) -> AppResult<i64> {
-        self.storage.save_project(&project).await
+        let id = self.storage.save_project(&project).await;
+        self.invalidate().await;
+        id
     }
 
     async fn update_project(&self, project: Project) -> AppResult<()> {
-        self.storage.save_project(&project).await?;
-        Ok(())
+        let saved = self.storage.save_project(&project).await;
+        self.invalidate().await;
+        saved.map(|_| ())
     }
 
+    /// 影响的记录数超过确认阈值时返回 `ConfirmationRequired`，确认后应直接调用 `Storage::delete_project`
     async fn delete_project(&self, id: i64) -> AppResult<()> {
-        // TODO: 实现删除项目的功能
-        Ok(())
+        let deleted = self.storage.delete_project(id, false).await;
+        self.invalidate().await;
+        deleted.map(|_| ())
     }
 
     async fn get_project(&self, id: i64) -> AppResult<Project> {
-        self.storage.get_project(id).await
+        let generation = {
+            let cache = self.cache.read().await;
+            if let Some(project) = cache.by_id.get(&id) {
+                return Ok(project.clone());
+            }
+            cache.generation
+        };
+        let project = self.storage.get_project(id).await?;
+        let mut cache = self.cache.write().await;
+        if cache.generation == generation {
+            cache.by_id.insert(id, project.clone());
+        }
+        Ok(project)
     }
 
     async fn list_projects(&self) -> AppResult<Vec<Project>> {
-        self.storage.list_projects().await
+        Ok(self.projects_cached().await?.to_vec())
     }
 }
 
@@ -46,4 +219,111 @@ mod tests {
     async fn test_project_manager() {
         // TODO: 添加测试用例
     }
+
+    struct MockClock(std::sync::Mutex<DateTime<Local>>);
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn project(id: i64, name: &str) -> Project {
+        let mut project = Project::new(name.into(), None);
+        project.id = Some(id);
+        project
+    }
+
+    #[tokio::test]
+    async fn test_project_cache_invalidated_on_update() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        let mut sequence = mockall::Sequence::new();
+        mock_storage
+            .expect_list_projects()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| Ok(vec![project(1, "Client")]));
+        mock_storage.expect_save_project().times(1).returning(|_| Ok(1));
+        mock_storage
+            .expect_list_projects()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| Ok(vec![project(1, "Renamed")]));
+        let manager = ProjectManager::new(Arc::new(mock_storage));
+
+        // 重复读取只查询一次存储，按 id 读取也使用列表缓存
+        assert_eq!(manager.projects_cached().await?[0].name, "Client");
+        assert_eq!(manager.projects_cached().await?.len(), 1);
+        assert_eq!(manager.get_project(1).await?.name, "Client");
+
+        manager.update_project(project(1, "Renamed")).await?;
+        assert_eq!(manager.projects_cached().await?[0].name, "Renamed");
+        assert_eq!(manager.get_project(1).await?.name, "Renamed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_color_skips_used_and_low_contrast() {
+        let manager = ProjectManager::new(Arc::new(MockStorage::new()));
+        let mut first = project(1, "Client");
+        first.color = Some("#4e79a7".into());
+
+        // 橙色在白色背景上对比度不足，跳过
+        assert_eq!(manager.suggest_color(&[first.clone()], LIGHT_BACKGROUND), "#E15759");
+        assert_eq!(manager.suggest_color(&[first.clone()], theme_background("dark")), "#F28E2B");
+        assert_eq!(manager.suggest_color(&[], LIGHT_BACKGROUND), "#4E79A7");
+
+        // 色板用完后循环
+        let all: Vec<Project> = PROJECT_PALETTE
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let mut project = project(i as i64, color);
+                project.color = Some(color.to_string());
+                project
+            })
+            .collect();
+        assert!(PROJECT_PALETTE.contains(&manager.suggest_color(&all, DARK_BACKGROUND).as_str()));
+        assert_eq!(parse_hex_color("12ab"), None);
+        assert_eq!(parse_hex_color(" #12AbEf "), Some([0x12, 0xAB, 0xEF]));
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_expire_after_ttl() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .times(2)
+            .returning(|_, _, _| Ok(Vec::new()));
+        let now = Local::now();
+        let clock = Arc::new(MockClock(std::sync::Mutex::new(now)));
+        let manager = ProjectManager::new(Arc::new(mock_storage)).with_clock(clock.clone());
+        let start = now - chrono::Duration::days(7);
+
+        manager.project_stats_cached(1, start, now).await?;
+        *clock.0.lock().unwrap() += chrono::Duration::seconds(10);
+        manager.project_stats_cached(1, start, now).await?;
+        *clock.0.lock().unwrap() += chrono::Duration::from_std(PROJECT_STATS_TTL).unwrap();
+        let stats = manager.project_stats_cached(1, start, now).await?;
+        assert_eq!(stats, ProjectPomodoroStats::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_cached_per_range_end() -> AppResult<()> {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_get_project_pomodoro_sessions()
+            .times(2)
+            .returning(|_, _, _| Ok(Vec::new()));
+        let manager = ProjectManager::new(Arc::new(mock_storage));
+        let now = Local::now();
+        let start = now - chrono::Duration::days(7);
+
+        // 起点相同但终点不同的范围分别查询
+        manager.project_stats_cached(1, start, now).await?;
+        manager.project_stats_cached(1, start, now - chrono::Duration::days(1)).await?;
+        manager.project_stats_cached(1, start, now).await?;
+        Ok(())
+    }
 } 
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::core::{AppResult, models::*};
+use crate::core::{AppError, AppResult, models::*};
 use crate::core::traits::Storage;
 use chrono::{DateTime, Local};
 use std::sync::Arc;
@@ -24,6 +24,63 @@ pub struct RuleMatch {
     pub matched_patterns: Vec<String>,
 }
 
+/// 从窗口标题中提取活动名称，`template` 中可用 `$1`、`${name}` 引用捕获组
+#[derive(Debug, Clone)]
+pub struct TitleRule {
+    pub pattern: Regex,
+    pub template: String,
+}
+
+impl TitleRule {
+    pub fn new(pattern: &str, template: impl Into<String>) -> AppResult<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| AppError::Config(format!("无效的标题规则 {}: {}", pattern, e)))?;
+        Ok(Self {
+            pattern,
+            template: template.into(),
+        })
+    }
+
+    pub fn apply(&self, title: &str) -> Option<String> {
+        let captures = self.pattern.captures(title)?;
+        let mut result = String::new();
+        captures.expand(&self.template, &mut result);
+        let result = result.trim();
+        (!result.is_empty()).then(|| result.to_string())
+    }
+}
+
+/// 按应用名称覆盖活动类别
+#[derive(Debug, Clone)]
+pub struct CategoryRule {
+    pub pattern: Regex,
+    pub category: String,
+}
+
+impl CategoryRule {
+    pub fn new(pattern: &str, category: impl Into<String>) -> AppResult<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| AppError::Config(format!("无效的类别规则 {}: {}", pattern, e)))?;
+        Ok(Self {
+            pattern,
+            category: category.into(),
+        })
+    }
+}
+
+/// 返回第一条匹配规则的类别，更具体的规则应排在前面
+pub fn get_app_category<'a>(rules: &'a [CategoryRule], app_name: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| rule.pattern.is_match(app_name))
+        .map(|rule| rule.category.as_str())
+}
+
+/// 按顺序匹配，使用第一条匹配的规则
+pub fn apply_title_rules(rules: &[TitleRule], title: &str) -> Option<String> {
+    rules.iter().find_map(|rule| rule.apply(title))
+}
+
 pub struct RuleEngine {
     storage: Arc<dyn Storage>,
     rules: RwLock<Vec<Rule>>,
@@ -235,4 +292,59 @@ mod tests {
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_title_rule_extracts_ticket_id() -> AppResult<()> {
+        let rules = vec![
+            TitleRule::new(r"(?P<ticket>[A-Z]+-\d+).* - Google Chrome$", "${ticket}")?,
+            TitleRule::new(r".* - Google Chrome$", "Browsing")?,
+        ];
+
+        assert_eq!(
+            apply_title_rules(&rules, "[PROJ-1234] Fix login redirect - Jira - Google Chrome"),
+            Some("PROJ-1234".to_string())
+        );
+        // 第一条不匹配时使用下一条
+        assert_eq!(
+            apply_title_rules(&rules, "Rust docs - Google Chrome"),
+            Some("Browsing".to_string())
+        );
+        assert_eq!(apply_title_rules(&rules, "Terminal"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_category_rules_first_match_wins() -> AppResult<()> {
+        let rules = vec![
+            CategoryRule::new(r"(?i)^code review bot$", "communication")?,
+            CategoryRule::new(r"(?i)^code\b", "development")?,
+            CategoryRule::new(r"(?i)bot", "automation")?,
+        ];
+
+        assert_eq!(get_app_category(&rules, "Code Review Bot"), Some("communication"));
+        assert_eq!(get_app_category(&rules, "Code"), Some("development"));
+        assert_eq!(get_app_category(&rules, "Code Review Bot Beta"), Some("development"));
+        assert_eq!(get_app_category(&rules, "Slack Bot"), Some("automation"));
+        // 锚定的规则不会误匹配名称中间的片段
+        assert_eq!(get_app_category(&rules, "Xcode"), None);
+
+        // 顺序颠倒后通用规则先匹配
+        let reversed: Vec<CategoryRule> = rules.into_iter().rev().collect();
+        assert_eq!(get_app_category(&reversed, "Code Review Bot"), Some("automation"));
+        assert!(CategoryRule::new("(unclosed", "x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_title_rule_strips_suffix() -> AppResult<()> {
+        let rule = TitleRule::new(r"^(.*?)(?: - [^-]+)? - Visual Studio Code$", "$1")?;
+
+        assert_eq!(
+            rule.apply("main.rs - time_tracker - Visual Studio Code"),
+            Some("main.rs".to_string())
+        );
+        assert_eq!(rule.apply("README.md - Visual Studio Code"), Some("README.md".to_string()));
+        assert!(TitleRule::new("(unclosed", "$1").is_err());
+        Ok(())
+    }
+}
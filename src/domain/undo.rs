@@ -0,0 +1,160 @@
+use crate::core::models::ProjectSnapshot;
+use crate::core::traits::Storage;
+use crate::core::AppResult;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const UNDO_FILE: &str = "undo.json";
+/// 只保留最近的操作
+pub const UNDO_LIMIT: usize = 20;
+
+/// 可撤销的破坏性操作，保存撤销所需的全部数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UndoableAction {
+    DeleteProject { snapshot: ProjectSnapshot },
+}
+
+impl UndoableAction {
+    pub fn description(&self) -> String {
+        match self {
+            UndoableAction::DeleteProject { snapshot } => format!("删除项目“{}”", snapshot.project.name),
+        }
+    }
+
+    async fn revert(&self, storage: &dyn Storage) -> AppResult<()> {
+        match self {
+            UndoableAction::DeleteProject { snapshot } => storage.restore_project(snapshot).await,
+        }
+    }
+}
+
+/// 撤销栈保存在数据目录中，误删后重启程序仍可撤销
+pub struct UndoManager {
+    storage: Arc<dyn Storage + Send + Sync>,
+    path: PathBuf,
+    actions: Mutex<VecDeque<UndoableAction>>,
+}
+
+impl UndoManager {
+    /// 文件损坏时丢弃原有记录，不影响启动
+    pub fn load(storage: Arc<dyn Storage + Send + Sync>, data_dir: &Path) -> Self {
+        let path = data_dir.join(UNDO_FILE);
+        let actions = match std::fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content).unwrap_or_else(|e| {
+                log::warn!("撤销记录损坏 ({})，已忽略", e);
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+        Self {
+            storage,
+            path,
+            actions: Mutex::new(actions),
+        }
+    }
+
+    fn persist(&self, actions: &VecDeque<UndoableAction>) -> AppResult<()> {
+        std::fs::write(&self.path, serde_json::to_vec(actions)?)?;
+        Ok(())
+    }
+
+    /// 在操作成功后调用
+    pub fn record_undo(&self, action: UndoableAction) -> AppResult<()> {
+        let mut actions = self.actions.lock().unwrap();
+        actions.push_back(action);
+        while actions.len() > UNDO_LIMIT {
+            actions.pop_front();
+        }
+        self.persist(&actions)
+    }
+
+    /// 下一次撤销的操作
+    pub fn peek(&self) -> Option<UndoableAction> {
+        self.actions.lock().unwrap().back().cloned()
+    }
+
+    /// 撤销最近一次操作，没有可撤销的操作时返回 `None`；撤销失败时操作保留在栈中
+    pub async fn undo(&self) -> AppResult<Option<UndoableAction>> {
+        let Some(action) = self.actions.lock().unwrap().pop_back() else {
+            return Ok(None);
+        };
+        if let Err(e) = action.revert(self.storage.as_ref()).await {
+            self.actions.lock().unwrap().push_back(action);
+            return Err(e);
+        }
+        self.persist(&self.actions.lock().unwrap())?;
+        Ok(Some(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Project;
+    use crate::core::traits::MockStorage;
+    use crate::core::AppError;
+    use chrono::{Local, TimeZone};
+    use tempfile::tempdir;
+
+    fn delete_project(id: i64) -> UndoableAction {
+        let mut project = Project::new(format!("project {}", id), None);
+        project.id = Some(id);
+        project.created_at = Local.timestamp_opt(0, 0).unwrap();
+        project.updated_at = project.created_at;
+        UndoableAction::DeleteProject {
+            snapshot: ProjectSnapshot {
+                project,
+                goals: Vec::new(),
+                pomodoro_ids: vec![id * 10],
+                activity_ids: Vec::new(),
+                daily_goals: Vec::new(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undo_survives_restart() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_restore_project()
+            .withf(|snapshot| snapshot.project.id == Some(UNDO_LIMIT as i64 + 4))
+            .times(1)
+            .returning(|_| Ok(()));
+        let storage = Arc::new(mock_storage);
+
+        let manager = UndoManager::load(storage.clone(), dir.path());
+        for id in 0..UNDO_LIMIT as i64 + 5 {
+            manager.record_undo(delete_project(id))?;
+        }
+
+        // 重启后从文件恢复，只保留最近的操作
+        let manager = UndoManager::load(storage, dir.path());
+        assert_eq!(manager.actions.lock().unwrap().len(), UNDO_LIMIT);
+        assert_eq!(manager.actions.lock().unwrap().front(), Some(&delete_project(5)));
+
+        let undone = manager.undo().await?;
+        assert_eq!(undone, Some(delete_project(UNDO_LIMIT as i64 + 4)));
+        assert_eq!(manager.peek(), Some(delete_project(UNDO_LIMIT as i64 + 3)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_undo_keeps_action() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_restore_project()
+            .returning(|_| Err(AppError::InvalidOperation("name taken".into())));
+        let manager = UndoManager::load(Arc::new(mock_storage), dir.path());
+
+        assert!(manager.undo().await?.is_none());
+        manager.record_undo(delete_project(1))?;
+        assert!(manager.undo().await.is_err());
+        assert_eq!(manager.peek(), Some(delete_project(1)));
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use async_trait::async_trait;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use crate::core::AppResult;
 use crate::domain::config::{AppConfig, ConfigManager};
 
@@ -13,30 +14,68 @@ impl FileConfigManager {
         let config_dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("./config"))
             .join("time_tracker");
-        
-        fs::create_dir_all(&config_dir).unwrap_or_default();
-        
+
+        std::fs::create_dir_all(&config_dir).unwrap_or_default();
+
+        Self::with_path(config_dir.join("config.json"))
+    }
+
+    pub fn with_path(config_path: impl Into<PathBuf>) -> Self {
         Self {
-            config_path: config_dir.join("config.json"),
+            config_path: config_path.into(),
         }
     }
+
+    fn temp_path(&self) -> PathBuf {
+        self.config_path.with_extension("json.tmp")
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        self.config_path.with_extension("json.bak")
+    }
+
+    async fn read(path: &Path) -> AppResult<AppConfig> {
+        let content = fs::read_to_string(path).await?;
+        Ok(serde_json::from_str(&content)?)
+    }
 }
 
 #[async_trait]
 impl ConfigManager for FileConfigManager {
+    /// 先写入临时文件并同步到磁盘，再重命名覆盖，避免写入中途崩溃损坏配置
     async fn save_config(&self, config: &AppConfig) -> AppResult<()> {
         let json = serde_json::to_string_pretty(config)?;
-        fs::write(&self.config_path, json).await?;
+        let temp_path = self.temp_path();
+
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(json.as_bytes()).await?;
+        file.sync_all().await?;
+        drop(file);
+
+        // 只备份能正常读取的旧配置，不用损坏的文件覆盖上一次的备份
+        if Self::read(&self.config_path).await.is_ok() {
+            fs::copy(&self.config_path, self.backup_path()).await?;
+        }
+
+        fs::rename(&temp_path, &self.config_path).await?;
         Ok(())
     }
 
+    /// 配置文件损坏时回退到备份，两者都不可用时返回错误而不是静默重置
     async fn load_config(&self) -> AppResult<AppConfig> {
-        match fs::read_to_string(&self.config_path).await {
-            Ok(content) => {
-                let config = serde_json::from_str(&content)?;
-                Ok(config)
+        if !self.config_path.exists() {
+            return match Self::read(&self.backup_path()).await {
+                Ok(config) => Ok(config),
+                Err(_) => Ok(AppConfig::default()),
+            };
+        }
+
+        match Self::read(&self.config_path).await {
+            Ok(config) => Ok(config),
+            Err(e) => {
+                log::warn!("配置文件损坏 ({})，尝试从备份恢复", e);
+                Self::read(&self.backup_path()).await.map_err(|_| e)
             }
-            Err(_) => Ok(AppConfig::default())
         }
     }
 
@@ -47,4 +86,40 @@ impl ConfigManager for FileConfigManager {
     async fn update_config(&self, config: AppConfig) -> AppResult<()> {
         self.save_config(&config).await
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_partial_write_keeps_previous_config() -> AppResult<()> {
+        let dir = tempdir()?;
+        let manager = FileConfigManager::with_path(dir.path().join("config.json"));
+
+        let mut first = AppConfig::default();
+        first.ui.theme = "dark".into();
+        manager.save_config(&first).await?;
+        let mut second = first.clone();
+        second.ui.theme = "light".into();
+        manager.save_config(&second).await?;
+        assert_eq!(manager.load_config().await?, second);
+
+        // 写临时文件时崩溃：目标文件不受影响
+        fs::write(manager.temp_path(), "{\"pomodoro\": {\"work_dur").await?;
+        assert_eq!(manager.load_config().await?, second);
+
+        // 目标文件本身被截断：回退到上一次的备份
+        let content = fs::read_to_string(&manager.config_path).await?;
+        fs::write(&manager.config_path, &content.as_bytes()[..content.len() / 2]).await?;
+        assert_eq!(manager.load_config().await?, first);
+
+        // 损坏的文件不会覆盖备份，下一次保存后恢复正常
+        manager.save_config(&second).await?;
+        assert_eq!(manager.load_config().await?, second);
+        assert_eq!(FileConfigManager::read(&manager.backup_path()).await?, first);
+
+        Ok(())
+    }
+}
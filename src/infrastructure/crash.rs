@@ -0,0 +1,287 @@
+use crate::application::events::{AppEvent, EventBus};
+use crate::core::AppResult;
+use crate::domain::activity::ActivityManager;
+use crate::domain::config::CrashReportSettings;
+use crate::domain::pomodoro::PomodoroManager;
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+pub const CRASH_DIR: &str = "crashes";
+/// 崩溃时写入的番茄钟快照，下次启动时用于恢复
+pub const POMODORO_SNAPSHOT_FILE: &str = "pomodoro_snapshot.json";
+/// 崩溃时尚未保存的当前活动
+pub const PENDING_ACTIVITY_FILE: &str = "pending_activity.json";
+
+/// 在 panic 钩子中执行，不能阻塞也不能再次 panic
+pub type FlushHook = Box<dyn Fn() -> AppResult<()> + Send + Sync>;
+
+pub struct CrashReporter {
+    dir: PathBuf,
+    settings: CrashReportSettings,
+    recent_events: Mutex<VecDeque<(DateTime<Local>, String)>>,
+    flush_hooks: Mutex<Vec<(String, FlushHook)>>,
+}
+
+impl CrashReporter {
+    pub fn new(data_dir: &Path, settings: CrashReportSettings) -> Self {
+        Self {
+            dir: data_dir.join(CRASH_DIR),
+            settings,
+            recent_events: Mutex::new(VecDeque::new()),
+            flush_hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record_event(&self, event: &AppEvent) {
+        // 计时事件过于频繁，对排查没有帮助
+        if matches!(event, AppEvent::PomodoroTick { .. }) {
+            return;
+        }
+        let mut events = self.recent_events.lock().unwrap();
+        events.push_back((Local::now(), format!("{:?}", event)));
+        while events.len() > self.settings.recent_events {
+            events.pop_front();
+        }
+    }
+
+    pub fn watch(self: &Arc<Self>, bus: &EventBus) -> JoinHandle<()> {
+        let reporter = self.clone();
+        let mut events = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => reporter.record_event(&event),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    pub fn add_flush_hook(&self, name: impl Into<String>, hook: FlushHook) {
+        self.flush_hooks.lock().unwrap().push((name.into(), hook));
+    }
+
+    /// 正常退出前移除，释放钩子持有的管理器，使记录通道能够关闭
+    pub fn clear_flush_hooks(&self) {
+        self.flush_hooks.lock().unwrap().clear();
+    }
+
+    /// 替换 panic 钩子，写入报告后继续调用原有钩子
+    pub fn install(self: &Arc<Self>) {
+        if !self.settings.enabled {
+            return;
+        }
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "<non-string panic payload>".into());
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}", l.file(), l.line()))
+                .unwrap_or_default();
+            match reporter.write_report(&message, &location) {
+                Ok(path) => eprintln!("崩溃报告已保存到 {}", path.display()),
+                Err(e) => eprintln!("保存崩溃报告失败: {}", e),
+            }
+            previous(info);
+        }));
+    }
+
+    fn write_report(&self, message: &str, location: &str) -> AppResult<PathBuf> {
+        // 先保存运行状态，即使后面写报告失败也不丢数据
+        let flushed = self.run_flush_hooks();
+        let report = self.render_report(message, location, &flushed, &Backtrace::force_capture().to_string());
+
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self
+            .dir
+            .join(format!("crash-{}.log", Local::now().format("%Y%m%d-%H%M%S-%3f")));
+        std::fs::write(&path, report)?;
+        self.rotate()?;
+        Ok(path)
+    }
+
+    fn run_flush_hooks(&self) -> Vec<String> {
+        // panic 可能发生在持有锁期间，拿不到锁时跳过
+        let Ok(hooks) = self.flush_hooks.try_lock() else {
+            return vec!["skipped: hooks locked".into()];
+        };
+        hooks
+            .iter()
+            .map(|(name, hook)| match hook() {
+                Ok(()) => format!("{}: ok", name),
+                Err(e) => format!("{}: {}", name, e),
+            })
+            .collect()
+    }
+
+    fn render_report(&self, message: &str, location: &str, flushed: &[String], backtrace: &str) -> String {
+        let thread = std::thread::current();
+        let mut report = String::new();
+        report.push_str(&format!("version: {}\n", env!("CARGO_PKG_VERSION")));
+        report.push_str(&format!("time: {}\n", Local::now().to_rfc3339()));
+        report.push_str(&format!("os: {} {}\n", std::env::consts::OS, std::env::consts::ARCH));
+        report.push_str(&format!("thread: {}\n", thread.name().unwrap_or("<unnamed>")));
+        report.push_str(&format!("message: {}\n", message));
+        report.push_str(&format!("location: {}\n", location));
+        report.push_str(&format!("flushed: {}\n", flushed.join(", ")));
+
+        report.push_str("\nrecent events:\n");
+        if let Ok(events) = self.recent_events.try_lock() {
+            for (at, event) in events.iter() {
+                report.push_str(&format!("  {} {}\n", at.format("%H:%M:%S"), event));
+            }
+        }
+
+        report.push_str("\nbacktrace:\n");
+        report.push_str(backtrace);
+        report.push('\n');
+        report
+    }
+
+    fn reports(&self, extension: &str) -> AppResult<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut reports: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == extension))
+            .collect();
+        // 文件名包含时间，按名称排序即按时间排序
+        reports.sort();
+        Ok(reports)
+    }
+
+    fn rotate(&self) -> AppResult<()> {
+        let mut reports = self.reports("log")?;
+        reports.extend(self.reports("seen")?);
+        reports.sort_by_key(|path| path.file_stem().map(|s| s.to_os_string()));
+        let excess = reports.len().saturating_sub(self.settings.max_reports);
+        for path in reports.into_iter().take(excess) {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// 尚未查看的崩溃报告，启动时提示用户
+    pub fn pending_reports(&self) -> AppResult<Vec<PathBuf>> {
+        self.reports("log")
+    }
+
+    /// 用户查看或忽略后不再提示，报告仍保留到轮转删除
+    pub fn mark_seen(&self, report: &Path) -> AppResult<()> {
+        std::fs::rename(report, report.with_extension("seen"))?;
+        Ok(())
+    }
+}
+
+/// 用系统默认程序打开报告，用户可以从中复制内容提交问题
+pub fn open_report(report: &Path) -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(report).spawn()?;
+    Ok(())
+}
+
+/// 崩溃时把当前番茄钟写入快照文件
+pub fn pomodoro_flush_hook(pomodoro: Arc<PomodoroManager>, data_dir: &Path) -> FlushHook {
+    let path = data_dir.join(POMODORO_SNAPSHOT_FILE);
+    Box::new(move || {
+        if let Some(snapshot) = pomodoro.try_snapshot() {
+            std::fs::write(&path, serde_json::to_vec(&snapshot)?)?;
+        }
+        Ok(())
+    })
+}
+
+/// 崩溃时把尚未保存的当前活动写入文件，结束时间为崩溃时刻
+pub fn activity_flush_hook(activity: Arc<ActivityManager>, data_dir: &Path) -> FlushHook {
+    let path = data_dir.join(PENDING_ACTIVITY_FILE);
+    Box::new(move || {
+        if let Some(mut current) = activity.try_current_activity() {
+            let end = Local::now().max(current.start_time);
+            current.end_time = Some(end);
+            current.duration = (end - current.start_time).to_std().unwrap_or_default();
+            std::fs::write(&path, serde_json::to_vec(&current)?)?;
+        }
+        Ok(())
+    })
+}
+
+/// 读取并删除崩溃时写入的恢复文件
+pub fn take_recovery_file<T: DeserializeOwned>(path: &Path) -> AppResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let value = serde_json::from_slice(&std::fs::read(path)?);
+    std::fs::remove_file(path)?;
+    Ok(Some(value?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Project;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_panic_writes_report() -> AppResult<()> {
+        let dir = tempdir()?;
+        let reporter = Arc::new(CrashReporter::new(dir.path(), CrashReportSettings::default()));
+        reporter.record_event(&AppEvent::ProjectCreated(Arc::new(Project::new("Client".into(), None))));
+        reporter.record_event(&AppEvent::PomodoroTick {
+            session_id: 1,
+            elapsed: std::time::Duration::from_secs(1),
+        });
+        let marker = dir.path().join("flushed");
+        let flag = marker.clone();
+        reporter.add_flush_hook("marker", Box::new(move || Ok(std::fs::write(&flag, "ok")?)));
+
+        reporter.install();
+        let result = std::panic::catch_unwind(|| panic!("simulated crash"));
+        // 恢复默认钩子，避免影响其他测试
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        let reports = reporter.pending_reports()?;
+        let report = reports
+            .iter()
+            .map(|path| std::fs::read_to_string(path).unwrap())
+            .find(|content| content.contains("simulated crash"))
+            .expect("crash report not written");
+        for field in ["version: ", "message: simulated crash", "location: ", "flushed: marker: ok", "backtrace:"] {
+            assert!(report.contains(field), "missing {}", field);
+        }
+        assert!(report.contains("ProjectCreated"));
+        assert!(!report.contains("PomodoroTick"));
+        assert!(marker.exists());
+
+        // 查看后不再提示
+        for path in &reports {
+            reporter.mark_seen(path)?;
+        }
+        assert!(reporter.pending_reports()?.is_empty());
+        Ok(())
+    }
+}
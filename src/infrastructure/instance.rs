@@ -0,0 +1,170 @@
+use crate::core::{AppError, AppResult};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const LOCK_FILE_NAME: &str = "instance.lock";
+const ACTIVATE_FILE_NAME: &str = "instance.activate";
+
+/// 心跳超过该时间未更新的锁视为上次崩溃遗留
+pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub heartbeat: DateTime<Local>,
+}
+
+impl LockInfo {
+    pub fn is_stale(&self, now: DateTime<Local>, stale_after: Duration) -> bool {
+        let stale_after = chrono::Duration::from_std(stale_after).unwrap_or_else(|_| chrono::Duration::zero());
+        now - self.heartbeat > stale_after
+    }
+}
+
+#[derive(Debug)]
+pub enum AcquireOutcome {
+    Acquired(InstanceLock),
+    /// 已有实例在运行
+    AlreadyRunning(LockInfo),
+}
+
+/// 单实例锁。运行中的实例需定期调用 `heartbeat` 刷新锁文件
+#[derive(Debug)]
+pub struct InstanceLock {
+    dir: PathBuf,
+}
+
+impl InstanceLock {
+    pub fn acquire(dir: impl AsRef<Path>, stale_after: Duration) -> AppResult<AcquireOutcome> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let lock_path = dir.join(LOCK_FILE_NAME);
+
+        if let Some(existing) = Self::read(&lock_path) {
+            if existing.pid != std::process::id() && !existing.is_stale(Local::now(), stale_after) {
+                return Ok(AcquireOutcome::AlreadyRunning(existing));
+            }
+            log::warn!("清理过期的实例锁 (pid {})", existing.pid);
+            fs::remove_file(&lock_path)?;
+        } else if lock_path.exists() {
+            // 内容损坏的锁文件同样视为过期
+            fs::remove_file(&lock_path)?;
+        }
+
+        // create_new 保证并发启动时只有一个实例成功
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return match Self::read(&lock_path) {
+                    Some(existing) => Ok(AcquireOutcome::AlreadyRunning(existing)),
+                    None => Err(AppError::System("无法获取实例锁".into())),
+                };
+            }
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(&serde_json::to_vec(&Self::current_info())?)?;
+
+        Ok(AcquireOutcome::Acquired(Self { dir }))
+    }
+
+    /// 不获取锁，只检查是否有其他实例在运行
+    pub fn running(dir: impl AsRef<Path>, stale_after: Duration) -> Option<LockInfo> {
+        Self::read(&dir.as_ref().join(LOCK_FILE_NAME))
+            .filter(|info| info.pid != std::process::id() && !info.is_stale(Local::now(), stale_after))
+    }
+
+    pub fn heartbeat(&self) -> AppResult<()> {
+        fs::write(self.lock_path(), serde_json::to_vec(&Self::current_info())?)?;
+        Ok(())
+    }
+
+    /// 第二个实例调用，请求已运行的实例将窗口置前
+    pub fn request_activation(dir: impl AsRef<Path>) -> AppResult<()> {
+        fs::write(dir.as_ref().join(ACTIVATE_FILE_NAME), std::process::id().to_string())?;
+        Ok(())
+    }
+
+    /// 运行中的实例在心跳时检查是否有激活请求
+    pub fn take_activation_request(&self) -> bool {
+        fs::remove_file(self.dir.join(ACTIVATE_FILE_NAME)).is_ok()
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.dir.join(LOCK_FILE_NAME)
+    }
+
+    fn read(path: &Path) -> Option<LockInfo> {
+        fs::read(path).ok().and_then(|data| serde_json::from_slice(&data).ok())
+    }
+
+    fn current_info() -> LockInfo {
+        LockInfo {
+            pid: std::process::id(),
+            heartbeat: Local::now(),
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(self.lock_path());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_lock(dir: &Path, pid: u32, heartbeat: DateTime<Local>) {
+        let info = LockInfo { pid, heartbeat };
+        fs::write(dir.join(LOCK_FILE_NAME), serde_json::to_vec(&info).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_second_acquire_reports_running_instance() -> AppResult<()> {
+        let dir = tempdir()?;
+        write_lock(dir.path(), std::process::id() + 1, Local::now());
+
+        match InstanceLock::acquire(dir.path(), DEFAULT_STALE_AFTER)? {
+            AcquireOutcome::AlreadyRunning(info) => assert_eq!(info.pid, std::process::id() + 1),
+            AcquireOutcome::Acquired(_) => panic!("lock held by another instance should not be acquired"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() -> AppResult<()> {
+        let dir = tempdir()?;
+        write_lock(dir.path(), std::process::id() + 1, Local::now() - chrono::Duration::minutes(5));
+
+        let lock = match InstanceLock::acquire(dir.path(), DEFAULT_STALE_AFTER)? {
+            AcquireOutcome::Acquired(lock) => lock,
+            AcquireOutcome::AlreadyRunning(_) => panic!("stale lock should be replaced"),
+        };
+        assert!(dir.path().join(LOCK_FILE_NAME).exists());
+
+        drop(lock);
+        assert!(!dir.path().join(LOCK_FILE_NAME).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_activation_request() -> AppResult<()> {
+        let dir = tempdir()?;
+        let lock = match InstanceLock::acquire(dir.path(), DEFAULT_STALE_AFTER)? {
+            AcquireOutcome::Acquired(lock) => lock,
+            AcquireOutcome::AlreadyRunning(_) => panic!("empty directory should be lockable"),
+        };
+
+        assert!(!lock.take_activation_request());
+        InstanceLock::request_activation(dir.path())?;
+        assert!(lock.take_activation_request());
+        assert!(!lock.take_activation_request());
+        Ok(())
+    }
+}
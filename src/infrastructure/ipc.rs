@@ -0,0 +1,183 @@
+use crate::cli::IpcCommand;
+use crate::core::{AppError, AppResult};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::task::JoinHandle;
+
+#[cfg(unix)]
+const SOCKET_FILE_NAME: &str = "time_tracker.sock";
+
+/// 每个连接只处理一条请求，请求和响应都是一行 JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IpcResponse {
+    Ok(String),
+    Err(String),
+}
+
+/// 界面进程中执行转发来的命令
+#[async_trait]
+pub trait IpcHandler: Send + Sync {
+    async fn handle(&self, command: IpcCommand) -> AppResult<String>;
+}
+
+#[cfg(unix)]
+fn socket_path(data_dir: &Path) -> std::path::PathBuf {
+    data_dir.join(SOCKET_FILE_NAME)
+}
+
+/// 命名管道不在文件系统中，用数据目录区分不同用户和配置
+#[cfg(windows)]
+fn pipe_name(data_dir: &Path) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    data_dir.hash(&mut hasher);
+    format!(r"\\.\pipe\time_tracker-{:016x}", hasher.finish())
+}
+
+/// 在界面进程中监听命令行请求，需在获得实例锁之后调用
+#[cfg(unix)]
+pub fn serve(data_dir: &Path, handler: Arc<dyn IpcHandler>) -> AppResult<JoinHandle<()>> {
+    let path = socket_path(data_dir);
+    // 持有实例锁时残留的套接字文件只可能来自上次异常退出
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, handler.clone()));
+                }
+                Err(e) => log::warn!("接受命令行连接失败: {}", e),
+            }
+        }
+    }))
+}
+
+#[cfg(windows)]
+pub fn serve(data_dir: &Path, handler: Arc<dyn IpcHandler>) -> AppResult<JoinHandle<()>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = pipe_name(data_dir);
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(&name)?;
+    Ok(tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                log::warn!("接受命令行连接失败: {}", e);
+                continue;
+            }
+            // 先创建下一个管道实例再处理当前连接，避免客户端连接时没有可用实例
+            let next = match ServerOptions::new().create(&name) {
+                Ok(next) => next,
+                Err(e) => {
+                    log::error!("创建命名管道失败: {}", e);
+                    return;
+                }
+            };
+            let connected = std::mem::replace(&mut server, next);
+            tokio::spawn(handle_connection(connected, handler.clone()));
+        }
+    }))
+}
+
+async fn handle_connection<S>(stream: S, handler: Arc<dyn IpcHandler>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = String::new();
+    let response = match BufReader::new(reader).read_line(&mut line).await {
+        Ok(_) => match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => match handler.handle(command).await {
+                Ok(output) => IpcResponse::Ok(output),
+                Err(e) => IpcResponse::Err(e.to_string()),
+            },
+            Err(e) => IpcResponse::Err(format!("无法解析请求: {}", e)),
+        },
+        Err(e) => IpcResponse::Err(e.to_string()),
+    };
+
+    let result = async {
+        let mut data = serde_json::to_vec(&response)?;
+        data.push(b'\n');
+        writer.write_all(&data).await?;
+        writer.shutdown().await?;
+        AppResult::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        log::warn!("回复命令行请求失败: {}", e);
+    }
+}
+
+/// 把命令转发给运行中的界面进程并返回其输出
+pub async fn send(data_dir: &Path, command: &IpcCommand) -> AppResult<String> {
+    #[cfg(unix)]
+    let stream = tokio::net::UnixStream::connect(socket_path(data_dir)).await?;
+    #[cfg(windows)]
+    let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(pipe_name(data_dir))?;
+    request(stream, command).await
+}
+
+async fn request<S>(stream: S, command: &IpcCommand) -> AppResult<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut data = serde_json::to_vec(command)?;
+    data.push(b'\n');
+    writer.write_all(&data).await?;
+
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    if line.is_empty() {
+        return Err(AppError::System("界面进程没有响应".into()));
+    }
+    match serde_json::from_str(&line)? {
+        IpcResponse::Ok(output) => Ok(output),
+        IpcResponse::Err(message) => Err(AppError::InvalidOperation(message)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    struct EchoHandler;
+
+    #[async_trait]
+    impl IpcHandler for EchoHandler {
+        async fn handle(&self, command: IpcCommand) -> AppResult<String> {
+            match command {
+                IpcCommand::Resume => Err(AppError::InvalidOperation("没有暂停中的番茄钟".into())),
+                other => Ok(format!("{:?}", other)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_are_forwarded_to_server() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(send(dir.path(), &IpcCommand::Resume).await.is_err());
+
+        let server = serve(dir.path(), Arc::new(EchoHandler))?;
+        let command = IpcCommand::Report {
+            range: crate::cli::DateRangeSpec::Week.into(),
+            by: crate::cli::ReportGroupBy::App,
+            format: crate::cli::ReportFormat::Json,
+            project: None,
+        };
+        assert_eq!(send(dir.path(), &command).await?, format!("{:?}", command));
+
+        // 服务端的错误原样返回给命令行
+        let err = send(dir.path(), &IpcCommand::Resume).await.unwrap_err();
+        assert!(err.to_string().contains("没有暂停中的番茄钟"));
+
+        server.abort();
+        Ok(())
+    }
+}
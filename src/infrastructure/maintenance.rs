@@ -0,0 +1,366 @@
+use crate::core::traits::Storage;
+use crate::core::{AppError, AppResult};
+use crate::domain::config::{MaintenanceSettings, RetentionPolicy};
+use crate::infrastructure::platform::PlatformOperations;
+use crate::infrastructure::storage::SqliteStorage;
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// 每日汇总重建覆盖的天数
+const SUMMARY_REBUILD_DAYS: u32 = 7;
+
+/// 检查是否有到期维护任务的间隔，任务本身的间隔由 `MaintenanceSettings` 决定
+pub const MAINTENANCE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    WalCheckpoint,
+    RebuildSummaries,
+    /// 截断超过 `max_session_duration` 的应用使用记录，与保留清理同一周期
+    CapSessions,
+    RetentionCleanup,
+    Vacuum,
+}
+
+impl MaintenanceTask {
+    /// 同时到期时按此顺序执行，轻量任务优先
+    pub const ALL: [MaintenanceTask; 5] = [
+        MaintenanceTask::WalCheckpoint,
+        MaintenanceTask::RebuildSummaries,
+        MaintenanceTask::CapSessions,
+        MaintenanceTask::RetentionCleanup,
+        MaintenanceTask::Vacuum,
+    ];
+
+    fn interval(self, settings: &MaintenanceSettings) -> Duration {
+        match self {
+            MaintenanceTask::WalCheckpoint => settings.checkpoint_interval,
+            MaintenanceTask::RebuildSummaries => settings.summary_interval,
+            MaintenanceTask::CapSessions if settings.max_session_duration.is_zero() => Duration::ZERO,
+            MaintenanceTask::CapSessions => settings.retention_interval,
+            MaintenanceTask::RetentionCleanup if settings.retention.keeps_everything() => Duration::ZERO,
+            MaintenanceTask::RetentionCleanup => settings.retention_interval,
+            MaintenanceTask::Vacuum => settings.vacuum_interval,
+        }
+    }
+}
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// 执行具体维护任务的对象，生产环境中为 `SqliteStorage`
+#[async_trait]
+pub trait MaintenanceTarget: Send + Sync {
+    async fn run_task(&self, task: MaintenanceTask, settings: &MaintenanceSettings) -> AppResult<()>;
+}
+
+#[async_trait]
+impl MaintenanceTarget for SqliteStorage {
+    async fn run_task(&self, task: MaintenanceTask, settings: &MaintenanceSettings) -> AppResult<()> {
+        match task {
+            MaintenanceTask::WalCheckpoint => self.wal_checkpoint().await,
+            MaintenanceTask::RebuildSummaries => {
+                let now = Local::now();
+                let since = now - chrono::Duration::days(SUMMARY_REBUILD_DAYS as i64);
+                self.rebuild_daily_summaries((since, now)).await.map(|_| ())
+            }
+            MaintenanceTask::CapSessions => {
+                let (count, trimmed) = self.cap_session_durations(settings.max_session_duration).await?;
+                if count > 0 {
+                    log::info!("截断了 {} 条过长的使用记录，共去掉 {} 分钟", count, trimmed.as_secs() / 60);
+                }
+                Ok(())
+            }
+            MaintenanceTask::RetentionCleanup => match self.cleanup_old_data(&settings.retention, false).await {
+                Ok(_) => Ok(()),
+                // 后台清理不替用户确认大批量删除
+                Err(AppError::ConfirmationRequired(count)) => {
+                    log::warn!("自动清理将删除 {} 条记录，已跳过，请手动确认", count);
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
+            MaintenanceTask::Vacuum => self.vacuum().await,
+        }
+    }
+}
+
+/// 在单个后台任务中按各自间隔执行数据库维护，每次最多执行一个任务
+pub struct MaintenanceScheduler {
+    target: Arc<dyn MaintenanceTarget>,
+    settings: MaintenanceSettings,
+    clock: Arc<dyn Clock>,
+    platform: Option<Arc<dyn PlatformOperations>>,
+    next_due: Mutex<HashMap<MaintenanceTask, DateTime<Local>>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(target: Arc<dyn MaintenanceTarget>, settings: MaintenanceSettings) -> Self {
+        Self::with_clock(target, settings, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(target: Arc<dyn MaintenanceTarget>, settings: MaintenanceSettings, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        let next_due = MaintenanceTask::ALL
+            .into_iter()
+            .filter_map(|task| Self::schedule(task, &settings, now).map(|due| (task, due)))
+            .collect();
+
+        Self {
+            target,
+            settings,
+            clock,
+            platform: None,
+            next_due: Mutex::new(next_due),
+            handle: Mutex::new(None),
+        }
+    }
+
+    /// 用于读取系统空闲时间，用户活跃时暂停维护
+    pub fn with_platform(mut self, platform: Arc<dyn PlatformOperations>) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    fn schedule(task: MaintenanceTask, settings: &MaintenanceSettings, from: DateTime<Local>) -> Option<DateTime<Local>> {
+        let interval = task.interval(settings);
+        if interval.is_zero() {
+            return None;
+        }
+        let interval = chrono::Duration::from_std(interval + Self::jitter(task, settings, from)).ok()?;
+        Some(from + interval)
+    }
+
+    /// 没有随机数依赖，用时间的纳秒部分和任务种类生成抖动
+    fn jitter(task: MaintenanceTask, settings: &MaintenanceSettings, from: DateTime<Local>) -> Duration {
+        let jitter = settings.jitter.as_secs();
+        if jitter == 0 {
+            return Duration::ZERO;
+        }
+        let seed = from.timestamp_subsec_nanos() as u64 ^ (task as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        Duration::from_secs(seed % (jitter + 1))
+    }
+
+    fn user_is_active(&self) -> bool {
+        match &self.platform {
+            // 无法获取空闲时间时不阻止维护
+            Some(platform) => platform
+                .get_system_idle_time()
+                .map(|idle| idle < self.settings.min_idle)
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+
+    /// 执行一个已到期的任务，返回执行的任务
+    pub async fn tick(&self) -> AppResult<Option<MaintenanceTask>> {
+        if self.user_is_active() {
+            return Ok(None);
+        }
+
+        let now = self.clock.now();
+        let task = {
+            let next_due = self.next_due.lock().unwrap();
+            MaintenanceTask::ALL
+                .into_iter()
+                .filter_map(|task| next_due.get(&task).map(|due| (task, *due)))
+                .filter(|(_, due)| *due <= now)
+                .min_by_key(|(_, due)| *due)
+        };
+        let Some((task, due)) = task else {
+            return Ok(None);
+        };
+
+        let result = self.target.run_task(task, &self.settings).await;
+
+        // 从计划时间而不是执行时间推算下一次，避免延迟累积；落后太多时从现在重新开始
+        let mut next = Self::schedule(task, &self.settings, due);
+        if next.map_or(false, |next| next <= now) {
+            next = Self::schedule(task, &self.settings, now);
+        }
+        let mut next_due = self.next_due.lock().unwrap();
+        match next {
+            Some(next) => next_due.insert(task, next),
+            None => next_due.remove(&task),
+        };
+        drop(next_due);
+
+        result.map(|_| Some(task))
+    }
+
+    pub fn start(self: &Arc<Self>, poll_interval: Duration) {
+        let scheduler = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = scheduler.tick().await {
+                    log::error!("数据库维护失败: {}", e);
+                }
+            }
+        });
+
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for MaintenanceScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::platform::WindowInfo;
+
+    struct MockClock(Mutex<DateTime<Local>>);
+
+    impl MockClock {
+        fn advance(&self, by: chrono::Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingTarget {
+        runs: Mutex<Vec<MaintenanceTask>>,
+    }
+
+    impl RecordingTarget {
+        fn count(&self, task: MaintenanceTask) -> usize {
+            self.runs.lock().unwrap().iter().filter(|t| **t == task).count()
+        }
+    }
+
+    #[async_trait]
+    impl MaintenanceTarget for RecordingTarget {
+        async fn run_task(&self, task: MaintenanceTask, _settings: &MaintenanceSettings) -> AppResult<()> {
+            self.runs.lock().unwrap().push(task);
+            Ok(())
+        }
+    }
+
+    struct BusyPlatform;
+
+    impl PlatformOperations for BusyPlatform {
+        fn get_active_window(&self) -> AppResult<WindowInfo> {
+            Err(AppError::System("not available in tests".into()))
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+
+        fn get_system_idle_time(&self) -> AppResult<Duration> {
+            Ok(Duration::from_secs(5))
+        }
+    }
+
+    fn settings() -> MaintenanceSettings {
+        MaintenanceSettings {
+            vacuum_interval: Duration::from_secs(24 * 3600),
+            checkpoint_interval: Duration::from_secs(3600),
+            summary_interval: Duration::from_secs(6 * 3600),
+            retention_interval: Duration::from_secs(24 * 3600),
+            retention: RetentionPolicy::uniform(90),
+            jitter: Duration::ZERO,
+            min_idle: Duration::from_secs(60),
+            max_session_duration: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tasks_run_at_their_cadence() -> AppResult<()> {
+        let clock = Arc::new(MockClock(Mutex::new(Local::now())));
+        let target = Arc::new(RecordingTarget::default());
+        let scheduler = MaintenanceScheduler::with_clock(target.clone(), settings(), clock.clone());
+
+        // 48 小时，每 10 分钟检查一次
+        for _ in 0..(48 * 6) {
+            clock.advance(chrono::Duration::minutes(10));
+            scheduler.tick().await?;
+        }
+        // 48 小时整点同时到期的任务在之后几次检查中依次执行
+        for _ in 0..3 {
+            clock.advance(chrono::Duration::minutes(10));
+            scheduler.tick().await?;
+        }
+
+        assert_eq!(target.count(MaintenanceTask::WalCheckpoint), 48);
+        assert_eq!(target.count(MaintenanceTask::RebuildSummaries), 8);
+        assert_eq!(target.count(MaintenanceTask::RetentionCleanup), 2);
+        assert_eq!(target.count(MaintenanceTask::Vacuum), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paused_while_user_active() -> AppResult<()> {
+        let clock = Arc::new(MockClock(Mutex::new(Local::now())));
+        let target = Arc::new(RecordingTarget::default());
+        let scheduler = MaintenanceScheduler::with_clock(target.clone(), settings(), clock.clone())
+            .with_platform(Arc::new(BusyPlatform));
+
+        clock.advance(chrono::Duration::days(2));
+        assert_eq!(scheduler.tick().await?, None);
+        assert!(target.runs.lock().unwrap().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_retention_is_never_scheduled() {
+        let mut settings = settings();
+        settings.retention = RetentionPolicy::default();
+        let scheduler = MaintenanceScheduler::new(Arc::new(RecordingTarget::default()), settings);
+        assert!(!scheduler.next_due.lock().unwrap().contains_key(&MaintenanceTask::RetentionCleanup));
+        assert!(!scheduler.next_due.lock().unwrap().contains_key(&MaintenanceTask::CapSessions));
+    }
+
+    #[test]
+    fn test_session_cap_runs_with_retention() {
+        let mut settings = settings();
+        settings.max_session_duration = Duration::from_secs(4 * 3600);
+        let clock = Arc::new(MockClock(Mutex::new(Local::now())));
+        let scheduler = MaintenanceScheduler::with_clock(Arc::new(RecordingTarget::default()), settings, clock);
+        let next_due = scheduler.next_due.lock().unwrap();
+        assert_eq!(
+            next_due.get(&MaintenanceTask::CapSessions),
+            next_due.get(&MaintenanceTask::RetentionCleanup)
+        );
+    }
+}
@@ -1,3 +1,7 @@
 pub mod config;
+pub mod crash;
+pub mod instance;
+pub mod ipc;
+pub mod maintenance;
 pub mod platform;
 pub mod storage; 
\ No newline at end of file
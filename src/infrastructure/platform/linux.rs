@@ -0,0 +1,269 @@
+use crate::core::{AppError, AppResult};
+use super::{NotificationActionSender, NotificationOptions, PlatformOperations, WindowInfo};
+use notify_rust::Notification;
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::screensaver::ConnectionExt as _;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, Window};
+use x11rb::rust_connection::RustConnection;
+
+const AUTOSTART_FILE: &str = "time_tracker.desktop";
+
+/// 通知操作按钮的 action key
+const NOTIFICATION_ACTION: &str = "action";
+
+static NOTIFICATION_ACTIONS: OnceCell<NotificationActionSender> = OnceCell::new();
+
+fn x11_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Platform(format!("X11 error: {}", e))
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim_end().to_string())
+}
+
+/// sway 树中获得焦点的窗口节点
+fn focused_node(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) && node["pid"].is_u64() {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(focused_node)
+}
+
+struct X11 {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl X11 {
+    fn connect() -> AppResult<Self> {
+        let (conn, screen) = x11rb::connect(None).map_err(x11_error)?;
+        let root = conn.setup().roots[screen].root;
+        Ok(Self { conn, root })
+    }
+
+    fn atom(&self, name: &str) -> AppResult<u32> {
+        Ok(self
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .atom)
+    }
+
+    fn property(&self, window: Window, name: &str) -> AppResult<Vec<u8>> {
+        let atom = self.atom(name)?;
+        Ok(self
+            .conn
+            .get_property(false, window, atom, AtomEnum::ANY, 0, u32::MAX)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .value)
+    }
+
+    fn property32(&self, window: Window, name: &str) -> AppResult<Option<u32>> {
+        let value = self.property(window, name)?;
+        Ok(value
+            .get(..4)
+            .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+    }
+
+    fn active_window(&self) -> AppResult<WindowInfo> {
+        let window = self
+            .property32(self.root, "_NET_ACTIVE_WINDOW")?
+            .filter(|window| *window != 0)
+            .ok_or_else(|| AppError::Platform("窗口管理器不支持 _NET_ACTIVE_WINDOW".into()))?;
+
+        // 旧程序只设置 WM_NAME
+        let mut title = self.property(window, "_NET_WM_NAME")?;
+        if title.is_empty() {
+            title = self.property(window, "WM_NAME")?;
+        }
+        let window_title = String::from_utf8_lossy(&title).into_owned();
+
+        let process_id = self.property32(window, "_NET_WM_PID")?.unwrap_or(0);
+        let process_name = process_name(process_id).unwrap_or_default();
+        // WM_CLASS 为 "实例名\0类名\0"，类名更接近用户看到的应用名
+        let class = self.property(window, "WM_CLASS")?;
+        let app_name = class
+            .split(|b| *b == 0)
+            .filter(|part| !part.is_empty())
+            .last()
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .unwrap_or_else(|| process_name.clone());
+
+        Ok(WindowInfo {
+            title: window_title.clone(),
+            process_name,
+            process_id,
+            app_name,
+            window_title,
+        })
+    }
+
+    fn idle_time(&self) -> AppResult<Duration> {
+        let info = self
+            .conn
+            .screensaver_query_info(self.root)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+        Ok(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+}
+
+pub struct LinuxPlatform {
+    /// 纯 Wayland 会话中没有 X 服务器时为空
+    x11: Option<X11>,
+}
+
+impl LinuxPlatform {
+    pub fn new() -> AppResult<Self> {
+        let x11 = match X11::connect() {
+            Ok(x11) => Some(x11),
+            Err(e) if std::env::var_os("WAYLAND_DISPLAY").is_some() => {
+                log::warn!("无法连接 X 服务器 ({})，仅使用 Wayland 合成器接口", e);
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(Self { x11 })
+    }
+
+    fn is_wayland() -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+
+    /// Wayland 没有通用的活动窗口接口，目前只支持 sway 的 IPC
+    fn wayland_active_window() -> AppResult<WindowInfo> {
+        let output = Command::new("swaymsg")
+            .args(["-t", "get_tree", "-r"])
+            .output()
+            .map_err(|_| AppError::Platform("当前 Wayland 合成器不支持获取活动窗口".into()))?;
+        if !output.status.success() {
+            return Err(AppError::Platform("当前 Wayland 合成器不支持获取活动窗口".into()));
+        }
+
+        let tree: Value = serde_json::from_slice(&output.stdout)?;
+        let node = focused_node(&tree).ok_or_else(|| AppError::NotFound("活动窗口".into()))?;
+        let window_title = node["name"].as_str().unwrap_or_default().to_string();
+        let process_id = node["pid"].as_u64().unwrap_or(0) as u32;
+        let process_name = process_name(process_id).unwrap_or_default();
+        // 原生 Wayland 窗口使用 app_id，XWayland 窗口使用 X11 的类名
+        let app_name = node["app_id"]
+            .as_str()
+            .or_else(|| node["window_properties"]["class"].as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| process_name.clone());
+
+        Ok(WindowInfo {
+            title: window_title.clone(),
+            process_name,
+            process_id,
+            app_name,
+            window_title,
+        })
+    }
+
+    fn autostart_path() -> AppResult<PathBuf> {
+        let dir = dirs::config_dir().ok_or_else(|| AppError::Platform("无法获取配置目录".into()))?;
+        Ok(dir.join("autostart").join(AUTOSTART_FILE))
+    }
+}
+
+impl PlatformOperations for LinuxPlatform {
+    fn get_active_window(&self) -> AppResult<WindowInfo> {
+        // XWayland 只能看到 X11 窗口，Wayland 会话优先询问合成器
+        if Self::is_wayland() {
+            match (Self::wayland_active_window(), &self.x11) {
+                (Ok(info), _) => return Ok(info),
+                (Err(e), None) => return Err(e),
+                (Err(_), Some(_)) => {}
+            }
+        }
+        match &self.x11 {
+            Some(x11) => x11.active_window(),
+            None => Err(AppError::Platform("无法获取活动窗口".into())),
+        }
+    }
+
+    fn set_autostart(&self, enabled: bool) -> AppResult<()> {
+        let path = Self::autostart_path()?;
+        if enabled {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let exe = std::env::current_exe()?;
+            let entry = format!(
+                "[Desktop Entry]\nType=Application\nName=Time Tracker\nExec=\"{}\" --{}\nX-GNOME-Autostart-enabled=true\n",
+                exe.to_string_lossy(),
+                crate::cli::AUTOSTART_FLAG
+            );
+            std::fs::write(&path, entry)?;
+        } else if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+
+    fn is_autostart_enabled(&self) -> AppResult<bool> {
+        Ok(Self::autostart_path()?.exists())
+    }
+
+    fn get_system_idle_time(&self) -> AppResult<Duration> {
+        match &self.x11 {
+            Some(x11) => x11.idle_time(),
+            None => Err(AppError::Platform("Operation not supported on this platform".into())),
+        }
+    }
+
+    fn show_notification(&self, options: NotificationOptions) -> AppResult<()> {
+        let mut notification = Notification::new();
+        notification.summary(&options.title).body(&options.message);
+        if options.sound {
+            notification.sound_name("message-new-instant");
+        }
+        if let Some(label) = &options.action_button {
+            notification.action(NOTIFICATION_ACTION, label);
+        }
+        if let Some(label) = &options.cancel_button {
+            notification.action("cancel", label);
+        }
+        let handle = notification
+            .show()
+            .map_err(|e| AppError::Platform(format!("发送通知失败: {}", e)))?;
+
+        if options.action_button.is_some() {
+            // 等待用户操作会阻塞到通知关闭，放到独立线程
+            let id = options.id;
+            std::thread::spawn(move || {
+                handle.wait_for_action(|action| {
+                    if action == NOTIFICATION_ACTION {
+                        if let Some(sender) = NOTIFICATION_ACTIONS.get() {
+                            let _ = sender.send(id);
+                        }
+                    }
+                });
+            });
+        }
+        Ok(())
+    }
+
+    fn watch_notification_actions(&self, sender: NotificationActionSender) -> AppResult<()> {
+        NOTIFICATION_ACTIONS
+            .set(sender)
+            .map_err(|_| AppError::InvalidOperation("通知操作已在监听".into()))
+    }
+}
@@ -1,13 +1,73 @@
 use crate::core::{AppError, AppResult};
-use super::{PlatformOperations, WindowInfo, DockIconVisibility, NotificationOptions};
+use super::{
+    PlatformOperations, WindowInfo, DockIconVisibility, NotificationOptions, Permission, PermissionStatus, PowerEvent,
+    PowerEventSender,
+};
+use chrono::Local;
+use once_cell::sync::OnceCell;
 use core_foundation::{
-    base::{CFRelease, CFTypeRef},
-    number::{CFNumberGetValue, CFNumberRef},
-    array::CFArrayRef,
-    dictionary::CFDictionaryRef,
+    base::{CFType, TCFType},
+    boolean::CFBoolean,
+    string::CFString,
+    number::CFNumber,
+    array::{CFArray, CFArrayRef},
+    dictionary::{CFDictionary, CFDictionaryRef},
+};
+use objc::{class, declare::ClassDecl, msg_send, runtime::{Object, Sel}, sel, sel_impl};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    path::{Path, PathBuf},
+    ptr,
+    sync::Mutex,
 };
-use objc::{runtime::Object, msg_send};
-use std::{ffi::c_void, ptr, sync::Mutex};
+
+static POWER_EVENTS: OnceCell<PowerEventSender> = OnceCell::new();
+
+const LAUNCH_AGENT_LABEL: &str = "com.time-tracker.app";
+
+const K_CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY: u32 = 1 << 0;
+const K_CG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+const K_CG_NULL_WINDOW_ID: u32 = 0;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> bool;
+}
+
+// 10.15 起可用
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+    fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> CFArrayRef;
+}
+
+extern "C" fn workspace_will_sleep(_this: &Object, _cmd: Sel, _notification: *mut Object) {
+    if let Some(sender) = POWER_EVENTS.get() {
+        let _ = sender.send(PowerEvent::Suspend(Local::now()));
+    }
+}
+
+extern "C" fn workspace_did_wake(_this: &Object, _cmd: Sel, _notification: *mut Object) {
+    if let Some(sender) = POWER_EVENTS.get() {
+        let _ = sender.send(PowerEvent::Resume(Local::now()));
+    }
+}
+
+unsafe fn ns_string(value: &str) -> *mut Object {
+    let value = CString::new(value).unwrap_or_default();
+    msg_send![class!(NSString), stringWithUTF8String: value.as_ptr()]
+}
+
+unsafe fn from_ns_string(value: *mut Object) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    let utf8: *const c_char = msg_send![value, UTF8String];
+    (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
 
 pub struct MacOSPlatform {
     app_switcher: Mutex<Option<*mut objc::runtime::Object>>,
@@ -23,66 +83,206 @@ impl MacOSPlatform {
         })
     }
 
-    unsafe fn get_window_layer(window_dict: CFDictionaryRef) -> Option<i32> {
-        let layer_key = "kCGWindowLayer";
-        let layer_value = CFNumberRef::from(ptr::null());
-        let mut layer: i32 = 0;
-        
-        if CFNumberGetValue(layer_value, 3, &mut layer as *mut i32 as *mut c_void) {
-            Some(layer)
-        } else {
-            None
+    /// 前台应用的 (应用名, 可执行文件名, pid)
+    unsafe fn frontmost_application() -> Option<(String, String, i32)> {
+        let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: *mut Object = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return None;
         }
-    }
-
-    unsafe fn get_window_pid(window_dict: CFDictionaryRef) -> Option<i32> {
-        let pid_key = "kCGWindowOwnerPID";
-        let pid_value = CFNumberRef::from(ptr::null());
-        let mut pid: i32 = 0;
-        
-        if CFNumberGetValue(pid_value, 3, &mut pid as *mut i32 as *mut c_void) {
-            Some(pid)
-        } else {
+        let pid: i32 = msg_send![app, processIdentifier];
+        let app_name = from_ns_string(msg_send![app, localizedName]).unwrap_or_default();
+        let url: *mut Object = msg_send![app, executableURL];
+        let process_name = if url.is_null() {
             None
+        } else {
+            from_ns_string(msg_send![url, lastPathComponent])
         }
+        .unwrap_or_else(|| app_name.clone());
+        Some((app_name, process_name, pid))
     }
 
-    unsafe fn get_window_bounds(window_dict: CFDictionaryRef) -> Option<f64> {
-        let bounds_key = "kCGWindowBounds";
-        let value = CFNumberRef::from(ptr::null());
-        let mut number: f64 = 0.0;
-        
-        if CFNumberGetValue(value, 12, &mut number as *mut f64 as *mut c_void) {
-            Some(number)
-        } else {
-            None
+    /// 按从前到后的顺序查找属于 `pid` 的第一个普通窗口的标题，缺少屏幕录制权限时系统不返回标题
+    unsafe fn window_title(pid: i32) -> Option<String> {
+        let windows = CGWindowListCopyWindowInfo(
+            K_CG_WINDOW_LIST_OPTION_ON_SCREEN_ONLY | K_CG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS,
+            K_CG_NULL_WINDOW_ID,
+        );
+        if windows.is_null() {
+            return None;
         }
+        let windows: CFArray<CFDictionary<CFString, CFType>> = CFArray::wrap_under_create_rule(windows);
+        let number = |window: &CFDictionary<CFString, CFType>, key: &str| {
+            window
+                .find(&CFString::new(key))
+                .and_then(|value| value.downcast::<CFNumber>())
+                .and_then(|value| value.to_i32())
+        };
+
+        windows.iter().find_map(|window| {
+            // 菜单栏、Dock 等系统元素的层级不为 0
+            if number(&window, "kCGWindowOwnerPID") != Some(pid) || number(&window, "kCGWindowLayer") != Some(0) {
+                return None;
+            }
+            window
+                .find(&CFString::new("kCGWindowName"))
+                .and_then(|value| value.downcast::<CFString>())
+                .map(|title| title.to_string())
+        })
     }
 
-    unsafe fn release_cf_type(cf_type: CFTypeRef) {
-        CFRelease(cf_type);
+    fn launch_agent_path() -> AppResult<PathBuf> {
+        let home = dirs::home_dir().ok_or_else(|| AppError::Platform("无法获取用户主目录".into()))?;
+        Ok(home
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", LAUNCH_AGENT_LABEL)))
     }
 }
 
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 登录时启动当前可执行文件的 LaunchAgent 配置
+fn launch_agent_plist(exe: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>--{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT_LABEL,
+        xml_escape(&exe.to_string_lossy()),
+        crate::cli::AUTOSTART_FLAG
+    )
+}
+
 impl PlatformOperations for MacOSPlatform {
     fn get_active_window(&self) -> Result<WindowInfo, AppError> {
-        // 暂时返回一个空的窗口信息
-        Ok(WindowInfo {
-            title: String::new(),
-            process_name: String::new(),
-            process_id: 0,
-            app_name: String::new(),
-            window_title: String::new(),
-        })
+        unsafe {
+            if !AXIsProcessTrusted() {
+                return Err(AppError::Platform(
+                    "缺少辅助功能权限，请在系统设置的隐私与安全性中允许".into(),
+                ));
+            }
+            let (app_name, process_name, pid) = Self::frontmost_application()
+                .ok_or_else(|| AppError::Platform("无法获取前台应用".into()))?;
+            // 没有屏幕录制权限时只记录应用名
+            let window_title = Self::window_title(pid).unwrap_or_default();
+
+            Ok(WindowInfo {
+                title: window_title.clone(),
+                process_name,
+                process_id: pid as u32,
+                app_name,
+                window_title,
+            })
+        }
     }
 
-    fn set_autostart(&self, _enabled: bool) -> Result<(), AppError> {
-        // 暂时返回成功
+    fn set_autostart(&self, enabled: bool) -> Result<(), AppError> {
+        let path = Self::launch_agent_path()?;
+        if enabled {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, launch_agent_plist(&std::env::current_exe()?))?;
+        } else if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
         Ok(())
     }
 
     fn is_autostart_enabled(&self) -> Result<bool, AppError> {
-        // 暂时返回 false
-        Ok(false)
+        Ok(Self::launch_agent_path()?.exists())
     }
-} 
\ No newline at end of file
+
+    fn check_permissions(&self) -> Result<PermissionStatus, AppError> {
+        unsafe {
+            Ok(PermissionStatus {
+                accessibility: AXIsProcessTrusted(),
+                screen_recording: CGPreflightScreenCaptureAccess(),
+            })
+        }
+    }
+
+    fn request_permissions(&self) -> Result<PermissionStatus, AppError> {
+        let status = self.check_permissions()?;
+        unsafe {
+            if !status.accessibility {
+                // 系统只在首次请求时弹出提示，之后需要用户到系统设置中手动开启
+                let options = core_foundation::dictionary::CFDictionary::from_CFType_pairs(&[(
+                    CFString::new("AXTrustedCheckOptionPrompt"),
+                    CFBoolean::true_value(),
+                )]);
+                AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef());
+            }
+            if !status.screen_recording {
+                CGRequestScreenCaptureAccess();
+            }
+        }
+        self.check_permissions()
+    }
+
+    fn open_permission_settings(&self, permission: Permission) -> Result<(), AppError> {
+        let pane = match permission {
+            Permission::Accessibility => "Privacy_Accessibility",
+            Permission::ScreenRecording => "Privacy_ScreenCapture",
+        };
+        std::process::Command::new("open")
+            .arg(format!("x-apple.systempreferences:com.apple.preference.security?{}", pane))
+            .spawn()?;
+        Ok(())
+    }
+
+    fn watch_power_events(&self, sender: PowerEventSender) -> Result<(), AppError> {
+        POWER_EVENTS
+            .set(sender)
+            .map_err(|_| AppError::InvalidOperation("电源事件已在监听".into()))?;
+
+        unsafe {
+            let mut decl = ClassDecl::new("TimeTrackerPowerObserver", class!(NSObject))
+                .ok_or_else(|| AppError::Platform("无法注册电源事件观察者".into()))?;
+            decl.add_method(
+                sel!(workspaceWillSleep:),
+                workspace_will_sleep as extern "C" fn(&Object, Sel, *mut Object),
+            );
+            decl.add_method(
+                sel!(workspaceDidWake:),
+                workspace_did_wake as extern "C" fn(&Object, Sel, *mut Object),
+            );
+            let observer_class = decl.register();
+            // 观察者在进程生命周期内一直存在，不释放
+            let observer: *mut Object = msg_send![observer_class, new];
+
+            // 休眠通知只通过 NSWorkspace 自己的通知中心发送
+            let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: *mut Object = msg_send![workspace, notificationCenter];
+            let _: () = msg_send![center, addObserver: observer
+                selector: sel!(workspaceWillSleep:)
+                name: ns_string("NSWorkspaceWillSleepNotification")
+                object: ptr::null_mut::<Object>()];
+            let _: () = msg_send![center, addObserver: observer
+                selector: sel!(workspaceDidWake:)
+                name: ns_string("NSWorkspaceDidWakeNotification")
+                object: ptr::null_mut::<Object>()];
+        }
+
+        Ok(())
+    }
+}
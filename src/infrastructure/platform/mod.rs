@@ -1,4 +1,6 @@
 use crate::core::{AppError, AppResult};
+use chrono::{DateTime, Local};
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone)]
 pub struct WindowInfo {
@@ -17,6 +19,8 @@ pub enum DockIconVisibility {
 
 #[derive(Debug, Clone)]
 pub struct NotificationOptions {
+    /// 点击操作按钮时通过 `NotificationActionSender` 发送该 id
+    pub id: String,
     pub title: String,
     pub message: String,
     pub sound: bool,
@@ -24,6 +28,60 @@ pub struct NotificationOptions {
     pub cancel_button: Option<String>,
 }
 
+/// 系统休眠与唤醒，时间为收到通知的时刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    Suspend(DateTime<Local>),
+    Resume(DateTime<Local>),
+}
+
+pub type PowerEventSender = mpsc::UnboundedSender<PowerEvent>;
+
+/// 全局快捷键触发时发送注册时使用的 id
+pub type ShortcutSender = mpsc::UnboundedSender<String>;
+
+/// 用户点击通知的操作按钮时发送通知 id
+pub type NotificationActionSender = mpsc::UnboundedSender<String>;
+
+/// 追踪活动窗口所需的系统权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// 读取前台应用和窗口，缺少时无法追踪
+    Accessibility,
+    /// 读取其他应用的窗口标题，缺少时只能记录应用名
+    ScreenRecording,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionStatus {
+    pub accessibility: bool,
+    pub screen_recording: bool,
+}
+
+impl PermissionStatus {
+    pub fn granted() -> Self {
+        Self {
+            accessibility: true,
+            screen_recording: true,
+        }
+    }
+
+    pub fn missing(&self) -> Vec<Permission> {
+        let mut missing = Vec::new();
+        if !self.accessibility {
+            missing.push(Permission::Accessibility);
+        }
+        if !self.screen_recording {
+            missing.push(Permission::ScreenRecording);
+        }
+        missing
+    }
+
+    pub fn is_tracking_blocked(&self) -> bool {
+        !self.accessibility
+    }
+}
+
 pub trait PlatformOperations: Send + Sync {
     // 基本窗口操作
     fn get_active_window(&self) -> AppResult<WindowInfo>;
@@ -61,6 +119,11 @@ pub trait PlatformOperations: Send + Sync {
         Err(AppError::Platform("Operation not supported on this platform".into()))
     }
 
+    // 每个进程只能注册一次
+    fn watch_notification_actions(&self, sender: NotificationActionSender) -> AppResult<()> {
+        Err(AppError::Platform("Operation not supported on this platform".into()))
+    }
+
     // 全局快捷键
     fn register_global_shortcut(&self, shortcut: &str, id: &str) -> AppResult<()> {
         Err(AppError::Platform("Operation not supported on this platform".into()))
@@ -70,6 +133,11 @@ pub trait PlatformOperations: Send + Sync {
         Err(AppError::Platform("Operation not supported on this platform".into()))
     }
 
+    // 窗口未获得焦点时也能收到快捷键，每个进程只能注册一次
+    fn watch_global_shortcuts(&self, sender: ShortcutSender) -> AppResult<()> {
+        Err(AppError::Platform("Operation not supported on this platform".into()))
+    }
+
     // 系统空闲时间
     fn get_system_idle_time(&self) -> AppResult<std::time::Duration> {
         Err(AppError::Platform("Operation not supported on this platform".into()))
@@ -89,6 +157,25 @@ pub trait PlatformOperations: Send + Sync {
     fn prevent_system_sleep(&self, prevent: bool) -> AppResult<()> {
         Err(AppError::Platform("Operation not supported on this platform".into()))
     }
+
+    // 追踪所需权限，不需要授权的平台视为全部已授予
+    fn check_permissions(&self) -> AppResult<PermissionStatus> {
+        Ok(PermissionStatus::granted())
+    }
+
+    /// 对缺少的权限弹出系统授权提示，返回请求后的状态
+    fn request_permissions(&self) -> AppResult<PermissionStatus> {
+        self.check_permissions()
+    }
+
+    fn open_permission_settings(&self, permission: Permission) -> AppResult<()> {
+        Err(AppError::Platform("Operation not supported on this platform".into()))
+    }
+
+    // 系统休眠/唤醒通知，每个进程只能注册一次
+    fn watch_power_events(&self, sender: PowerEventSender) -> AppResult<()> {
+        Err(AppError::Platform("Operation not supported on this platform".into()))
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -101,6 +188,11 @@ mod macos;
 #[cfg(target_os = "macos")]
 pub use self::macos::MacOSPlatform;
 
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use self::linux::LinuxPlatform;
+
 pub fn init() -> AppResult<Box<dyn PlatformOperations + Send + Sync>> {
     #[cfg(target_os = "windows")]
     {
@@ -110,7 +202,11 @@ pub fn init() -> AppResult<Box<dyn PlatformOperations + Send + Sync>> {
     {
         Ok(Box::new(MacOSPlatform::new()?))
     }
-    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(LinuxPlatform::new()?))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
         Err(AppError::Platform("Platform not supported".into()))
     }
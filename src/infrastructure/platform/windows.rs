@@ -1,22 +1,208 @@
-use crate::error::Result;
-use crate::platform::{PlatformOperations, WindowInfo};
-use std::ffi::OsString;
-use std::os::windows::ffi::OsStringExt;
+use crate::error::{AppError, Result};
+use crate::platform::{PlatformOperations, PowerEvent, PowerEventSender, ShortcutSender, WindowInfo};
+use chrono::Local;
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::sync::{mpsc as std_mpsc, Mutex};
+use std::ffi::{OsStr, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
 use std::ptr;
 use winapi::um::winuser;
+use winapi::shared::minwindef::{DWORD, LPARAM, LRESULT, TRUE, UINT, WPARAM};
 use winapi::shared::windef;
+use winapi::um::processthreadsapi::GetCurrentThreadId;
 use winreg::enums::*;
 use winreg::RegKey;
 use std::path::PathBuf;
 use std::env;
 
+static POWER_EVENTS: OnceCell<PowerEventSender> = OnceCell::new();
+static SHORTCUT_EVENTS: OnceCell<ShortcutSender> = OnceCell::new();
+
+/// 通知快捷键线程处理队列中的请求
+const WM_HOTKEY_REQUEST: UINT = winuser::WM_APP + 1;
+
+enum HotkeyRequest {
+    Register {
+        id: String,
+        shortcut: String,
+        modifiers: UINT,
+        key: UINT,
+        reply: std_mpsc::Sender<Result<()>>,
+    },
+    Unregister {
+        id: String,
+        reply: std_mpsc::Sender<Result<()>>,
+    },
+}
+
+/// RegisterHotKey 只把 WM_HOTKEY 投递到注册线程的消息队列，注册和接收都在同一个线程中进行
+struct HotkeyThread {
+    thread_id: DWORD,
+    requests: std_mpsc::Sender<HotkeyRequest>,
+}
+
+impl HotkeyThread {
+    fn spawn() -> Result<Self> {
+        let (requests, receiver) = std_mpsc::channel();
+        let (ready, thread_id) = std_mpsc::channel();
+        std::thread::spawn(move || unsafe {
+            let mut msg: winuser::MSG = std::mem::zeroed();
+            // 确保消息队列在返回线程 id 之前已创建，否则 PostThreadMessage 会失败
+            winuser::PeekMessageW(&mut msg, ptr::null_mut(), winuser::WM_USER, winuser::WM_USER, winuser::PM_NOREMOVE);
+            let _ = ready.send(GetCurrentThreadId());
+            Self::run(receiver);
+        });
+        let thread_id = thread_id
+            .recv()
+            .map_err(|_| AppError::Platform("无法启动快捷键线程".into()))?;
+        Ok(Self { thread_id, requests })
+    }
+
+    unsafe fn run(requests: std_mpsc::Receiver<HotkeyRequest>) {
+        let mut ids: HashMap<String, i32> = HashMap::new();
+        let mut next_id = 1;
+        let mut msg: winuser::MSG = std::mem::zeroed();
+        while winuser::GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+            match msg.message {
+                winuser::WM_HOTKEY => {
+                    let hotkey = msg.wParam as i32;
+                    if let (Some((id, _)), Some(sender)) =
+                        (ids.iter().find(|(_, value)| **value == hotkey), SHORTCUT_EVENTS.get())
+                    {
+                        let _ = sender.send(id.clone());
+                    }
+                }
+                WM_HOTKEY_REQUEST => {
+                    while let Ok(request) = requests.try_recv() {
+                        match request {
+                            HotkeyRequest::Register { id, shortcut, modifiers, key, reply } => {
+                                // 重新注册同一 id 时替换原来的快捷键
+                                if let Some(old) = ids.remove(&id) {
+                                    winuser::UnregisterHotKey(ptr::null_mut(), old);
+                                }
+                                let result = if winuser::RegisterHotKey(ptr::null_mut(), next_id, modifiers, key) == 0 {
+                                    Err(AppError::Platform(format!("快捷键 {} 已被其他程序占用", shortcut)))
+                                } else {
+                                    ids.insert(id, next_id);
+                                    next_id += 1;
+                                    Ok(())
+                                };
+                                let _ = reply.send(result);
+                            }
+                            HotkeyRequest::Unregister { id, reply } => {
+                                if let Some(hotkey) = ids.remove(&id) {
+                                    winuser::UnregisterHotKey(ptr::null_mut(), hotkey);
+                                }
+                                let _ = reply.send(Ok(()));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn send(&self, make: impl FnOnce(std_mpsc::Sender<Result<()>>) -> HotkeyRequest) -> Result<()> {
+        let (reply, result) = std_mpsc::channel();
+        let closed = || AppError::Platform("快捷键线程已退出".into());
+        self.requests.send(make(reply)).map_err(|_| closed())?;
+        if unsafe { winuser::PostThreadMessageW(self.thread_id, WM_HOTKEY_REQUEST, 0, 0) } == 0 {
+            return Err(closed());
+        }
+        result.recv().map_err(|_| closed())?
+    }
+}
+
+fn virtual_key(name: &str) -> Option<UINT> {
+    let key = match name {
+        "space" => winuser::VK_SPACE,
+        "enter" | "return" => winuser::VK_RETURN,
+        "tab" => winuser::VK_TAB,
+        "esc" | "escape" => winuser::VK_ESCAPE,
+        "backspace" => winuser::VK_BACK,
+        "delete" | "del" => winuser::VK_DELETE,
+        "insert" | "ins" => winuser::VK_INSERT,
+        "home" => winuser::VK_HOME,
+        "end" => winuser::VK_END,
+        "pageup" | "pgup" => winuser::VK_PRIOR,
+        "pagedown" | "pgdn" => winuser::VK_NEXT,
+        "up" => winuser::VK_UP,
+        "down" => winuser::VK_DOWN,
+        "left" => winuser::VK_LEFT,
+        "right" => winuser::VK_RIGHT,
+        _ => {
+            let mut chars = name.chars();
+            return match (chars.next(), chars.next()) {
+                // 字母和数字的虚拟键码与大写 ASCII 相同
+                (Some(c), None) if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as UINT),
+                (Some('f'), Some(_)) => name[1..]
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|n| (1..=24).contains(n))
+                    .map(|n| (winuser::VK_F1 + n - 1) as UINT),
+                _ => None,
+            };
+        }
+    };
+    Some(key as UINT)
+}
+
+/// 解析 "Ctrl+Shift+P" 形式的快捷键，返回 (修饰键, 虚拟键码)
+fn parse_shortcut(shortcut: &str) -> Result<(UINT, UINT)> {
+    let invalid = || AppError::InvalidOperation(format!("无法识别的快捷键: {}", shortcut));
+    let mut modifiers = 0;
+    let mut key = None;
+    for part in shortcut.split('+').map(|part| part.trim().to_ascii_lowercase()) {
+        match part.as_str() {
+            // RegisterHotKey 不区分左右修饰键，Command 在 Windows 上视为 Ctrl
+            "ctrl" | "control" | "lctrl" | "rctrl" | "cmd" | "command" => modifiers |= winuser::MOD_CONTROL as UINT,
+            "alt" | "lalt" | "ralt" | "option" => modifiers |= winuser::MOD_ALT as UINT,
+            "shift" | "lshift" | "rshift" => modifiers |= winuser::MOD_SHIFT as UINT,
+            "win" | "lwin" | "rwin" | "super" | "meta" => modifiers |= winuser::MOD_WIN as UINT,
+            name if key.is_none() => key = Some(virtual_key(name).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        }
+    }
+    let key = key.ok_or_else(invalid)?;
+    // 按住不放时不重复触发
+    Ok((modifiers | winuser::MOD_NOREPEAT as UINT, key))
+}
+
+unsafe extern "system" fn power_window_proc(hwnd: windef::HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == winuser::WM_POWERBROADCAST {
+        let event = match wparam {
+            winuser::PBT_APMSUSPEND => Some(PowerEvent::Suspend(Local::now())),
+            winuser::PBT_APMRESUMEAUTOMATIC => Some(PowerEvent::Resume(Local::now())),
+            _ => None,
+        };
+        if let (Some(event), Some(sender)) = (event, POWER_EVENTS.get()) {
+            let _ = sender.send(event);
+        }
+        return TRUE as LRESULT;
+    }
+    winuser::DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
 pub struct WindowsPlatform {
-    // Windows 平台特定字段
+    /// 首次注册快捷键时启动
+    hotkeys: Mutex<Option<HotkeyThread>>,
 }
 
 impl WindowsPlatform {
     pub fn new() -> Result<Self> {
-        Ok(Self {})
+        Ok(Self {
+            hotkeys: Mutex::new(None),
+        })
+    }
+
+    fn hotkey_request(&self, make: impl FnOnce(std_mpsc::Sender<Result<()>>) -> HotkeyRequest) -> Result<()> {
+        let mut hotkeys = self.hotkeys.lock().unwrap();
+        if hotkeys.is_none() {
+            *hotkeys = Some(HotkeyThread::spawn()?);
+        }
+        hotkeys.as_ref().unwrap().send(make)
     }
 
     fn get_window_text(hwnd: windef::HWND) -> Option<String> {
@@ -69,7 +255,12 @@ impl PlatformOperations for WindowsPlatform {
             .unwrap_or("MyApp");
 
         if enabled {
-            key.set_value(app_name, &exe_path.to_str().unwrap_or_default())?;
+            let command = format!(
+                "\"{}\" --{}",
+                exe_path.to_str().unwrap_or_default(),
+                crate::cli::AUTOSTART_FLAG
+            );
+            key.set_value(app_name, &command)?;
         } else {
             key.delete_value(app_name)?;
         }
@@ -93,4 +284,75 @@ impl PlatformOperations for WindowsPlatform {
             Err(_) => Ok(false),
         }
     }
-} 
\ No newline at end of file
+
+    fn register_global_shortcut(&self, shortcut: &str, id: &str) -> Result<()> {
+        let (modifiers, key) = parse_shortcut(shortcut)?;
+        self.hotkey_request(|reply| HotkeyRequest::Register {
+            id: id.to_string(),
+            shortcut: shortcut.to_string(),
+            modifiers,
+            key,
+            reply,
+        })
+    }
+
+    fn unregister_global_shortcut(&self, id: &str) -> Result<()> {
+        self.hotkey_request(|reply| HotkeyRequest::Unregister {
+            id: id.to_string(),
+            reply,
+        })
+    }
+
+    fn watch_global_shortcuts(&self, sender: ShortcutSender) -> Result<()> {
+        SHORTCUT_EVENTS
+            .set(sender)
+            .map_err(|_| AppError::InvalidOperation("全局快捷键已在监听".into()))
+    }
+
+    fn watch_power_events(&self, sender: PowerEventSender) -> Result<()> {
+        POWER_EVENTS
+            .set(sender)
+            .map_err(|_| AppError::InvalidOperation("电源事件已在监听".into()))?;
+
+        std::thread::spawn(|| unsafe {
+            let class_name: Vec<u16> = OsStr::new("TimeTrackerPowerWatcher")
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect();
+            let class = winuser::WNDCLASSW {
+                lpfnWndProc: Some(power_window_proc),
+                lpszClassName: class_name.as_ptr(),
+                ..std::mem::zeroed()
+            };
+            winuser::RegisterClassW(&class);
+
+            // 仅消息窗口收不到 WM_POWERBROADCAST，使用不显示的顶层窗口
+            let hwnd = winuser::CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                ptr::null(),
+                0,
+                0,
+                0,
+                0,
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if hwnd.is_null() {
+                log::error!("无法创建电源事件窗口");
+                return;
+            }
+
+            let mut msg: winuser::MSG = std::mem::zeroed();
+            while winuser::GetMessageW(&mut msg, hwnd, 0, 0) > 0 {
+                winuser::TranslateMessage(&msg);
+                winuser::DispatchMessageW(&msg);
+            }
+        });
+
+        Ok(())
+    }
+}
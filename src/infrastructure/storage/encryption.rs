@@ -0,0 +1,85 @@
+use crate::core::{AppError, AppResult};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+/// 加密备份的文件头，格式或密钥派生参数变化时更换
+const MAGIC: &[u8; 8] = b"TTBKENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+/// 加密备份的扩展名，明文备份为 `.db`
+pub const ENCRYPTED_BACKUP_EXTENSION: &str = "db.enc";
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> AppResult<chacha20poly1305::Key> {
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidOperation("备份密码不能为空".into()));
+    }
+    let mut key = chacha20poly1305::Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::System(format!("派生备份密钥失败: {}", e)))?;
+    Ok(key)
+}
+
+/// 文件头 + XChaCha20-Poly1305 密文。密钥由 Argon2id 从口令和随机盐派生，文件头作为附加数据一并认证
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> AppResult<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(passphrase, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &output })
+        .map_err(|_| AppError::System("加密备份失败".into()))?;
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> AppResult<Vec<u8>> {
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err(AppError::InvalidOperation("不是加密的备份文件".into()));
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let salt = &header[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = XNonce::from_slice(&header[MAGIC.len() + SALT_LEN..]);
+    let key = derive_key(passphrase, salt)?;
+    XChaCha20Poly1305::new(&key)
+        .decrypt(nonce, Payload { msg: ciphertext, aad: header })
+        .map_err(|_| AppError::InvalidOperation("备份密码错误或文件已损坏".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip() -> AppResult<()> {
+        let data = b"SQLite format 3\0 activity history".to_vec();
+        let encrypted = encrypt(&data, "correct horse")?;
+        assert!(encrypted.starts_with(MAGIC));
+        assert!(!encrypted.windows(data.len()).any(|w| w == data.as_slice()));
+        assert_eq!(decrypt(&encrypted, "correct horse")?, data);
+
+        // 同一口令两次加密使用不同的盐和随机数
+        assert_ne!(encrypt(&data, "correct horse")?, encrypted);
+
+        assert!(matches!(decrypt(&encrypted, "wrong"), Err(AppError::InvalidOperation(_))));
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, "correct horse").is_err());
+        let mut header_tampered = encrypted;
+        header_tampered[MAGIC.len()] ^= 1;
+        assert!(decrypt(&header_tampered, "correct horse").is_err());
+
+        assert!(encrypt(&data, "").is_err());
+        assert!(decrypt(b"plain sqlite file", "correct horse").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+use super::SqliteStorage;
+use crate::core::AppResult;
+use chrono::Local;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Row,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 旧版（rusqlite）数据库文件名
+pub const LEGACY_DATABASE_NAME: &str = "time_tracker.db";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyMigrationReport {
+    pub projects: usize,
+    pub app_usage: usize,
+    pub pomodoros: usize,
+    pub backup_path: PathBuf,
+}
+
+async fn open_read_only(path: &Path) -> AppResult<SqlitePool> {
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(path).read_only(true))
+        .await?)
+}
+
+async fn table_exists(pool: &SqlitePool, table: &str) -> AppResult<bool> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// 旧版数据库由 rusqlite 迁移器维护，有 `migrations` 表而没有 `_sqlx_migrations`
+pub async fn is_legacy_database(path: impl AsRef<Path>) -> AppResult<bool> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(false);
+    }
+    let pool = open_read_only(path).await?;
+    let legacy = table_exists(&pool, "migrations").await? && !table_exists(&pool, "_sqlx_migrations").await?;
+    pool.close().await;
+    Ok(legacy)
+}
+
+/// 将旧版数据库中的项目、应用使用记录和番茄钟复制到当前数据库，迁移前先备份旧文件
+pub async fn migrate_legacy_to_current(
+    old_db: impl AsRef<Path>,
+    new_db: impl AsRef<Path>,
+) -> AppResult<LegacyMigrationReport> {
+    let old_db = old_db.as_ref();
+    let backup_path = old_db.with_extension(format!("legacy-{}.bak", Local::now().format("%Y%m%d%H%M%S")));
+    std::fs::copy(old_db, &backup_path)?;
+
+    let legacy = open_read_only(old_db).await?;
+    let storage = SqliteStorage::new(new_db).await?;
+    let mut report = LegacyMigrationReport {
+        backup_path,
+        ..Default::default()
+    };
+
+    let mut tx = storage.pool().begin().await?;
+    let now = Local::now();
+
+    // 项目：按名称合并，记录旧 id 到新 id 的映射
+    let mut project_ids: HashMap<i64, i64> = HashMap::new();
+    if table_exists(&legacy, "projects").await? {
+        let rows = sqlx::query("SELECT id, name, description FROM projects")
+            .fetch_all(&legacy)
+            .await?;
+        for row in rows {
+            let old_id: i64 = row.get("id");
+            let name: String = row.get("name");
+            let description: Option<String> = row.get("description");
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO projects (name, description, created_at, updated_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(&name)
+            .bind(&description)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            let new_id: i64 = sqlx::query_scalar("SELECT id FROM projects WHERE name = ?")
+                .bind(&name)
+                .fetch_one(&mut *tx)
+                .await?;
+            project_ids.insert(old_id, new_id);
+            report.projects += 1;
+        }
+    }
+
+    // 应用使用记录：旧版类别为文本，转换为类别表中的记录
+    let mut category_ids: HashMap<String, i64> = HashMap::new();
+    let rows = sqlx::query(
+        r#"
+        SELECT app_name, COALESCE(window_title, '') AS window_title, start_time, duration, category,
+               COALESCE(productivity_score, CASE WHEN is_productive THEN 1.0 ELSE 0.0 END) AS productivity_score
+        FROM app_usage
+        "#,
+    )
+    .fetch_all(&legacy)
+    .await?;
+    for row in rows {
+        let category: Option<String> = row.get("category");
+        let category_id = match category.filter(|c| !c.is_empty()) {
+            Some(name) => {
+                if let Some(id) = category_ids.get(&name) {
+                    Some(*id)
+                } else {
+                    sqlx::query(
+                        "INSERT OR IGNORE INTO categories (name, created_at, updated_at) VALUES (?, ?, ?)",
+                    )
+                    .bind(&name)
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                    let id: i64 = sqlx::query_scalar("SELECT id FROM categories WHERE name = ?")
+                        .bind(&name)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                    category_ids.insert(name, id);
+                    Some(id)
+                }
+            }
+            None => None,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO app_usage (app_name, window_title, start_time, duration, category_id, productivity_score, is_productive)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(row.get::<String, _>("app_name"))
+        .bind(row.get::<String, _>("window_title"))
+        .bind(row.get::<String, _>("start_time"))
+        .bind(row.get::<i64, _>("duration"))
+        .bind(category_id)
+        .bind(row.get::<f64, _>("productivity_score"))
+        // 汇总按 is_productive 统计生产性时长
+        .bind(row.get::<f64, _>("productivity_score") >= 0.5)
+        .execute(&mut *tx)
+        .await?;
+        report.app_usage += 1;
+    }
+
+    // 番茄钟记录
+    let rows = sqlx::query("SELECT start_time, end_time, status, notes, project_id FROM pomodoro_records")
+        .fetch_all(&legacy)
+        .await?;
+    for row in rows {
+        let project_id = row
+            .get::<Option<i64>, _>("project_id")
+            .and_then(|id| project_ids.get(&id).copied());
+
+        sqlx::query(
+            r#"
+            INSERT INTO pomodoro_records (start_time, end_time, status, notes, project_id)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(row.get::<String, _>("start_time"))
+        .bind(row.get::<String, _>("end_time"))
+        .bind(row.get::<String, _>("status"))
+        .bind(row.get::<Option<String>, _>("notes"))
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await?;
+        report.pomodoros += 1;
+    }
+
+    tx.commit().await?;
+    legacy.close().await;
+    storage
+        .checkpoint_after_bulk_write((report.projects + report.app_usage + report.pomodoros) as u64)
+        .await;
+
+    Ok(report)
+}
+
+/// 首次启动时检测旧版数据库并迁移，迁移后重命名旧文件避免重复执行
+pub async fn migrate_legacy_if_present(
+    data_dir: impl AsRef<Path>,
+    new_db: impl AsRef<Path>,
+) -> AppResult<Option<LegacyMigrationReport>> {
+    let old_db = data_dir.as_ref().join(LEGACY_DATABASE_NAME);
+    let new_db = new_db.as_ref();
+    if old_db == new_db || new_db.exists() || !is_legacy_database(&old_db).await? {
+        return Ok(None);
+    }
+
+    let report = migrate_legacy_to_current(&old_db, new_db).await?;
+    std::fs::rename(&old_db, old_db.with_extension("db.migrated"))?;
+    log::info!(
+        "已从旧版数据库迁移 {} 个项目、{} 条使用记录、{} 个番茄钟",
+        report.projects,
+        report.app_usage,
+        report.pomodoros
+    );
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+    use tempfile::tempdir;
+
+    async fn create_legacy_db(path: &Path) -> AppResult<()> {
+        let pool = SqlitePoolOptions::new()
+            .connect_with(SqliteConnectOptions::new().filename(path).create_if_missing(true))
+            .await?;
+        pool.execute(
+            r#"
+            CREATE TABLE migrations (version INTEGER PRIMARY KEY, description TEXT NOT NULL, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP);
+            CREATE TABLE projects (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT);
+            "#,
+        )
+        .await?;
+        pool.execute(include_str!("../../../migrations/001_initial_schema.sql")).await?;
+        pool.execute(include_str!("../../../migrations/002_add_productivity.sql")).await?;
+        pool.execute(
+            r#"
+            INSERT INTO projects (id, name, description) VALUES (7, 'Client', NULL), (9, 'Internal', 'ops');
+            INSERT INTO app_usage (app_name, window_title, start_time, duration, category, is_productive)
+            VALUES ('code', 'main.rs', '2024-01-01 09:00:00', 3600, 'development', 1),
+                   ('browser', NULL, '2024-01-01 10:00:00', 600, 'research', 0),
+                   ('terminal', 'zsh', '2024-01-01 10:10:00', 300, 'development', 1);
+            INSERT INTO pomodoro_records (start_time, end_time, status, notes, project_id)
+            VALUES ('2024-01-01 09:00:00', '2024-01-01 09:25:00', 'Completed', NULL, 7),
+                   ('2024-01-01 09:30:00', '2024-01-01 09:40:00', 'Interrupted', 'call', 9);
+            "#,
+        )
+        .await?;
+        pool.close().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_legacy_database() -> AppResult<()> {
+        let dir = tempdir()?;
+        let old_db = dir.path().join(LEGACY_DATABASE_NAME);
+        let new_db = dir.path().join("timetracker.db");
+        create_legacy_db(&old_db).await?;
+        assert!(is_legacy_database(&old_db).await?);
+
+        let report = migrate_legacy_if_present(dir.path(), &new_db).await?.unwrap();
+        assert_eq!((report.projects, report.app_usage, report.pomodoros), (2, 3, 2));
+        assert!(report.backup_path.exists());
+        assert!(!old_db.exists());
+
+        let storage = SqliteStorage::new(&new_db).await?;
+        let count = |table: &'static str| {
+            let pool = storage.pool();
+            async move {
+                sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+                    .fetch_one(&pool)
+                    .await
+            }
+        };
+        assert_eq!(count("projects").await?, 2);
+        assert_eq!(count("app_usage").await?, 3);
+        assert_eq!(count("pomodoro_records").await?, 2);
+        assert_eq!(count("categories").await?, 2);
+
+        // 番茄钟的项目 id 已映射到新库
+        let project_name: String = sqlx::query_scalar(
+            "SELECT p.name FROM pomodoro_records r JOIN projects p ON p.id = r.project_id WHERE r.notes = 'call'",
+        )
+        .fetch_one(&storage.pool())
+        .await?;
+        assert_eq!(project_name, "Internal");
+
+        // 第二次启动不会重复迁移
+        assert!(migrate_legacy_if_present(dir.path(), &new_db).await?.is_none());
+
+        Ok(())
+    }
+}
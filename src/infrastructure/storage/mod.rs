@@ -1,71 +1,823 @@
+mod encryption;
+mod legacy;
 mod models;
 mod queries;
+mod retention;
 
+pub use encryption::ENCRYPTED_BACKUP_EXTENSION;
+pub use legacy::{is_legacy_database, migrate_legacy_if_present, migrate_legacy_to_current, LegacyMigrationReport};
 pub use models::*;
 pub use queries::*;
 
 use crate::core::{AppError, AppResult};
-use crate::domain::config::AppConfig;
-use crate::core::models::{Activity, Project, PomodoroSession};
+use crate::domain::config::{AppConfig, RetentionPolicy, StorageSettings, Synchronous};
+use crate::core::models::{
+    current_utc_offset, Activity, ActivityAudit, DailyUsage, DeletionImpact, Goal, GoalKind, GoalPeriod, ManualEntry, MergeBatch, Project, ProjectGoal,
+    ProjectRef, ProjectSnapshot, PomodoroSession, PomodoroStatus, SearchHit, SearchKind, Tag, SEARCH_HIGHLIGHT,
+};
 use sqlx::{
-    sqlite::{SqlitePool, SqlitePoolOptions},
+    sqlite::{SqlitePool, SqlitePoolOptions, SqliteRow, SqliteSynchronous},
     Pool, Sqlite, Row,
 };
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use tokio::sync::OnceCell;
 use async_trait::async_trait;
-use crate::core::traits::Storage;
-use chrono::{DateTime, Local};
+/// 唯一的存储接口，界面、命令行和后台任务都通过它访问 `SqliteStorage`
+pub use crate::core::traits::{AppUsageBatches, Storage};
+use chrono::{DateTime, Local, Utc};
+
+/// 增量备份的目标文件名，位于备份目录下
+pub const INCREMENTAL_BACKUP_FILE: &str = "incremental.db";
+
+/// 每批比较和复制的页数
+const BACKUP_BATCH_PAGES: u64 = 256;
+
+/// 等待 WAL 清空的重试次数，期间有其他写入时需要重新检查点
+const BACKUP_LOCK_RETRIES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackupProgress {
+    pub pages_done: u64,
+    pub pages_total: u64,
+    /// 内容有变化而实际写入的页数
+    pub pages_copied: u64,
+}
+
+/// 上一次增量备份时每批页面的哈希，用于判断哪些页面发生了变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BackupWatermark {
+    schema_version: i64,
+    page_size: u64,
+    batch_hashes: Vec<u64>,
+}
+
+impl BackupWatermark {
+    fn path(backup: &Path) -> PathBuf {
+        backup.with_extension("db.json")
+    }
+
+    fn load(backup: &Path) -> Option<Self> {
+        if !backup.exists() {
+            return None;
+        }
+        let content = std::fs::read(Self::path(backup)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+}
+
+/// FNV-1a，结果需要跨版本保存，不能使用标准库的哈希
+fn page_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// 逐批比较数据库文件与备份文件，只写入哈希变化的批次。没有基准时全部写入
+fn copy_changed_pages(
+    source: &Path,
+    target: &Path,
+    page_size: u64,
+    base: Option<&BackupWatermark>,
+    on_progress: &dyn Fn(BackupProgress),
+) -> AppResult<Vec<u64>> {
+    let mut source = std::fs::File::open(source)?;
+    let total_len = source.metadata()?.len();
+    let mut target = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(base.is_none())
+        .open(target)?;
+
+    let batch_len = page_size * BACKUP_BATCH_PAGES;
+    let mut buffer = vec![0u8; batch_len as usize];
+    let mut hashes = Vec::new();
+    let mut progress = BackupProgress {
+        pages_total: total_len / page_size,
+        ..BackupProgress::default()
+    };
+
+    let mut offset = 0;
+    while offset < total_len {
+        let len = batch_len.min(total_len - offset) as usize;
+        source.read_exact(&mut buffer[..len])?;
+        let hash = page_hash(&buffer[..len]);
+        let index = hashes.len();
+        if base.and_then(|b| b.batch_hashes.get(index)) != Some(&hash) {
+            target.seek(SeekFrom::Start(offset))?;
+            target.write_all(&buffer[..len])?;
+            progress.pages_copied += len as u64 / page_size;
+        }
+        hashes.push(hash);
+
+        offset += len as u64;
+        progress.pages_done = offset / page_size;
+        on_progress(progress);
+    }
+
+    target.set_len(total_len)?;
+    target.sync_all()?;
+    Ok(hashes)
+}
+
+/// 一次搜索返回的最多结果数
+const SEARCH_LIMIT: i64 = 200;
+
+/// 单次写入超过该行数后执行 WAL 检查点
+const BULK_WRITE_CHECKPOINT_ROWS: u64 = 1000;
 
 pub struct SqliteStorage {
-    pool: Pool<Sqlite>,
+    /// 从备份恢复时替换为新打开的连接池
+    pool: std::sync::RwLock<Pool<Sqlite>>,
+    settings: StorageSettings,
+    database_path: PathBuf,
 }
 
 impl SqliteStorage {
     pub async fn new(database_path: impl AsRef<Path>) -> AppResult<Self> {
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(
-                sqlx::sqlite::SqliteConnectOptions::new()
-                    .filename(database_path.as_ref())
-                    .create_if_missing(true)
-                    .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-                    .foreign_keys(true),
-            )
-            .await?;
+        Self::with_settings(database_path, &AppConfig::default().storage).await
+    }
+
+    /// 按存储设置打开数据库，busy_timeout 和 synchronous 会应用到连接池的每个连接
+    pub async fn with_settings(database_path: impl AsRef<Path>, settings: &StorageSettings) -> AppResult<Self> {
+        settings.validate()?;
+        let pool = open_pool(database_path.as_ref(), settings).await?;
+
+        Ok(Self {
+            pool: std::sync::RwLock::new(pool),
+            settings: settings.clone(),
+            database_path: database_path.as_ref().to_path_buf(),
+        })
+    }
 
-        // 运行迁移
-        sqlx::migrate!("./migrations").run(&pool).await?;
+    fn pool(&self) -> Pool<Sqlite> {
+        self.pool.read().unwrap().clone()
+    }
+
+    /// 关闭连接池，等待正在使用的连接归还
+    pub async fn close(&self) {
+        self.pool().close().await;
+    }
 
-        Ok(Self { pool })
+    /// 备份目录，相对路径按数据库所在的数据目录解析，与启动时的备份任务一致
+    pub fn backup_dir(&self) -> PathBuf {
+        self.database_path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(&self.settings.backup_path)
     }
 
+    /// 写入备份后用 `PRAGMA integrity_check` 校验，未通过时删除备份文件并返回错误
     pub async fn backup(&self, backup_path: impl AsRef<Path>) -> AppResult<()> {
-        let backup_path = backup_path.as_ref().to_string_lossy();
-        sqlx::query(&format!("VACUUM INTO '{}'", backup_path))
-            .execute(&self.pool)
+        let path = backup_path.as_ref();
+        sqlx::query(&format!("VACUUM INTO '{}'", path.to_string_lossy()))
+            .execute(&self.pool())
+            .await?;
+        if let Err(e) = Self::verify_backup(path).await {
+            let _ = std::fs::remove_file(path);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// 对备份文件执行完整的完整性检查
+    pub async fn verify_backup(path: &Path) -> AppResult<()> {
+        check_database_file(path, "integrity_check").await
+    }
+
+    /// 数据库状态，并用 `quick_check` 探测最近一次备份是否可用
+    pub async fn check_health(&self) -> AppResult<StorageHealth> {
+        let quick_check: String = sqlx::query_scalar("PRAGMA quick_check").fetch_one(&self.pool()).await?;
+        let app_usage_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_usage").fetch_one(&self.pool()).await?;
+        let pomodoro_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pomodoro_records")
+            .fetch_one(&self.pool())
             .await?;
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(&self.pool()).await?;
+        let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count").fetch_one(&self.pool()).await?;
+
+        let latest = self.latest_backup()?;
+        // 加密备份没有口令无法检查内容，视为未验证
+        let last_backup_verified = match &latest {
+            Some((path, _)) if !path.to_string_lossy().ends_with(ENCRYPTED_BACKUP_EXTENSION) => {
+                check_database_file(path, "quick_check").await.is_ok()
+            }
+            _ => false,
+        };
+
+        let footprint = self.disk_footprint();
+        Ok(StorageHealth {
+            is_healthy: quick_check == "ok",
+            database_size: footprint.database,
+            app_usage_count: app_usage_count as u64,
+            pomodoro_count: pomodoro_count as u64,
+            last_backup: latest.map(|(_, modified)| modified),
+            last_backup_verified,
+            needs_vacuum: page_count > 0 && freelist_count * 5 > page_count,
+            wal_size: footprint.wal,
+            disk_usage: footprint.total(),
+        })
+    }
+
+    /// 备份目录中修改时间最新的备份
+    fn latest_backup(&self) -> AppResult<Option<(PathBuf, DateTime<Local>)>> {
+        Ok(self.list_backups()?.into_iter().next().map(|info| (info.path, info.created_at)))
+    }
+
+    /// 备份目录中的明文和加密备份，最新的在前
+    pub fn list_backups(&self) -> AppResult<Vec<BackupInfo>> {
+        let dir = self.backup_dir();
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !is_backup_file(&path) {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            backups.push(BackupInfo {
+                encrypted: path.to_string_lossy().ends_with(ENCRYPTED_BACKUP_EXTENSION),
+                created_at: DateTime::<Local>::from(metadata.modified()?),
+                size: metadata.len(),
+                path,
+            });
+        }
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(backups)
+    }
+
+    /// 备份到备份目录并用口令加密为 `.db.enc` 文件，返回其路径。明文副本只在加密期间存在
+    pub async fn backup_encrypted(&self, passphrase: &str) -> AppResult<PathBuf> {
+        if passphrase.is_empty() {
+            return Err(AppError::InvalidOperation("备份密码不能为空".into()));
+        }
+        let dir = self.backup_dir();
+        std::fs::create_dir_all(&dir)?;
+        let stem = format!("backup-{}", Local::now().format("%Y%m%d-%H%M%S%.3f"));
+        let plain = dir.join(format!("{}.tmp", stem));
+        let target = dir.join(format!("{}.{}", stem, ENCRYPTED_BACKUP_EXTENSION));
+
+        let result = async {
+            self.backup(&plain).await?;
+            let data = std::fs::read(&plain)?;
+            let passphrase = passphrase.to_string();
+            let encrypted = tokio::task::spawn_blocking(move || encryption::encrypt(&data, &passphrase))
+                .await
+                .map_err(|e| AppError::System(e.to_string()))??;
+            std::fs::write(&target, encrypted)?;
+            AppResult::Ok(())
+        }
+        .await;
+        let _ = std::fs::remove_file(&plain);
+        result.map(|_| target)
+    }
+
+    /// 解密 `backup_encrypted` 生成的备份并写入 `destination`。不会替换正在使用的数据库，需在关闭连接后自行替换
+    pub async fn restore_encrypted(backup: &Path, passphrase: &str, destination: &Path) -> AppResult<()> {
+        let data = std::fs::read(backup)?;
+        let passphrase = passphrase.to_string();
+        let plain = tokio::task::spawn_blocking(move || encryption::decrypt(&data, &passphrase))
+            .await
+            .map_err(|e| AppError::System(e.to_string()))??;
+        if !plain.starts_with(b"SQLite format 3\0") {
+            return Err(AppError::InvalidOperation("备份内容不是 SQLite 数据库".into()));
+        }
+
+        // 先写临时文件再重命名，避免中途失败留下不完整的数据库
+        let temp = destination.with_extension("restore.tmp");
+        std::fs::write(&temp, plain)?;
+        std::fs::rename(&temp, destination)?;
         Ok(())
     }
 
+    /// 删除备份目录中超过 `backup_retention_days` 的明文和加密备份，返回删除的文件。
+    /// 增量备份会被持续更新，不按时间清理；保留天数为 0 时不清理
+    pub fn cleanup_old_backups(&self) -> AppResult<Vec<PathBuf>> {
+        let dir = self.backup_dir();
+        if self.settings.backup_retention_days == 0 || !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let max_age = std::time::Duration::from_secs(self.settings.backup_retention_days as u64 * 24 * 3600);
+        let mut removed = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !is_backup_file(&path) {
+                continue;
+            }
+            let age = entry.metadata()?.modified()?.elapsed().unwrap_or_default();
+            if age > max_age {
+                std::fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        removed.sort();
+        Ok(removed)
+    }
+
+    pub async fn backup_incremental(&self) -> AppResult<PathBuf> {
+        self.backup_incremental_with_progress(|_| {}).await
+    }
+
+    /// 增量备份到备份目录下的 `incremental.db`，只写入上次备份后变化的页面。
+    /// 没有上次备份、迁移版本或页大小变化时执行完整复制
+    pub async fn backup_incremental_with_progress(&self, on_progress: impl Fn(BackupProgress)) -> AppResult<PathBuf> {
+        let dir = self.backup_dir();
+        std::fs::create_dir_all(&dir)?;
+        let target = dir.join(INCREMENTAL_BACKUP_FILE);
+
+        let schema_version: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations")
+            .fetch_one(&self.pool())
+            .await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&self.pool()).await?;
+        let page_size = page_size as u64;
+
+        let base = BackupWatermark::load(&target)
+            .filter(|w| w.schema_version == schema_version && w.page_size == page_size);
+        if base.is_none() {
+            log::info!("没有可用的增量备份基准，执行完整备份");
+        }
+        // 复制中途失败时旧的哈希已不可信，下次将执行完整备份
+        let _ = std::fs::remove_file(BackupWatermark::path(&target));
+
+        // 先检查点把 WAL 写回主文件，再用写事务阻止其他连接写入，保证复制期间主文件不变
+        let wal_path = PathBuf::from(format!("{}-wal", self.database_path.display()));
+        let mut conn = self.pool().acquire().await?;
+        let mut locked = false;
+        for _ in 0..BACKUP_LOCK_RETRIES {
+            sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *conn).await?;
+            sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+            if std::fs::metadata(&wal_path).map(|m| m.len() == 0).unwrap_or(true) {
+                locked = true;
+                break;
+            }
+            sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+        }
+        if !locked {
+            return Err(AppError::InvalidOperation("数据库写入频繁，请稍后重试备份".into()));
+        }
+
+        // 文件复制是同步 IO，放到阻塞线程执行，进度通过通道转回当前任务
+        let (progress_sender, mut progress) = tokio::sync::mpsc::unbounded_channel();
+        let (source, copy_target) = (self.database_path.clone(), target.clone());
+        let copy = tokio::task::spawn_blocking(move || {
+            copy_changed_pages(&source, &copy_target, page_size, base.as_ref(), &|p| {
+                let _ = progress_sender.send(p);
+            })
+        });
+        while let Some(p) = progress.recv().await {
+            on_progress(p);
+        }
+        let copied = copy.await.map_err(|e| AppError::System(e.to_string()));
+        sqlx::query("ROLLBACK").execute(&mut *conn).await?;
+
+        let watermark = BackupWatermark {
+            schema_version,
+            page_size,
+            batch_hashes: copied??,
+        };
+        std::fs::write(BackupWatermark::path(&target), serde_json::to_vec(&watermark)?)?;
+        Ok(target)
+    }
+
+    /// 在破坏性操作前创建安全备份，返回操作结果和备份路径（未启用时为 None），便于界面提供撤销
+    pub async fn with_safety_backup<F, Fut, T>(&self, op: F) -> AppResult<(T, Option<PathBuf>)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        let backup_path = Storage::safety_backup(self).await?;
+        let result = op().await?;
+        Ok((result, backup_path))
+    }
+
+    /// 统计 `cleanup_old_data` 将删除的记录数
+    pub async fn count_old_data(&self, policy: &RetentionPolicy) -> AppResult<DeletionImpact> {
+        let mut conn = self.pool().acquire().await?;
+        retention::count(&mut *conn, policy, Local::now()).await
+    }
+
+    /// 按保留策略删除各类旧记录，超过确认阈值时需传入 `confirmed`
+    pub async fn cleanup_old_data(
+        &self,
+        policy: &RetentionPolicy,
+        confirmed: bool,
+    ) -> AppResult<(DeletionImpact, Option<PathBuf>)> {
+        let now = Local::now();
+        let impact = self.count_old_data(policy).await?;
+        self.settings.check_bulk_delete(impact.total(), confirmed)?;
+
+        let result = self
+            .with_safety_backup(|| async {
+                let mut tx = self.pool().begin().await?;
+                let deleted = retention::delete(&mut *tx, policy, now).await?;
+                tx.commit().await?;
+                Ok(deleted)
+            })
+            .await?;
+        self.checkpoint_after_bulk_write(result.0.total()).await;
+        Ok(result)
+    }
+
+    /// 把超过 `max` 的单条应用使用记录截断到 `max`，返回截断的记录数和总共去掉的时长
+    pub async fn cap_session_durations(&self, max: std::time::Duration) -> AppResult<(u64, std::time::Duration)> {
+        let max = max.as_secs() as i64;
+        let mut tx = self.pool().begin().await?;
+        let (count, trimmed): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COALESCE(SUM(duration - ?), 0) FROM app_usage WHERE duration > ?")
+                .bind(max)
+                .bind(max)
+                .fetch_one(&mut *tx)
+                .await?;
+        sqlx::query("UPDATE app_usage SET duration = ? WHERE duration > ?")
+            .bind(max)
+            .bind(max)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok((count as u64, std::time::Duration::from_secs(trimmed as u64)))
+    }
+
     pub async fn vacuum(&self) -> AppResult<()> {
         sqlx::query("VACUUM")
-            .execute(&self.pool)
+            .execute(&self.pool())
             .await?;
         Ok(())
     }
 
+    /// 将 WAL 内容写回主数据库并截断 WAL 文件
+    pub async fn wal_checkpoint(&self) -> AppResult<()> {
+        self.checkpoint(CheckpointMode::Truncate).await.map(|_| ())
+    }
+
+    pub async fn checkpoint(&self, mode: CheckpointMode) -> AppResult<CheckpointResult> {
+        let row = sqlx::query(&format!("PRAGMA wal_checkpoint({})", mode.as_sql()))
+            .fetch_one(&self.pool())
+            .await?;
+        Ok(CheckpointResult {
+            busy: row.get::<i64, _>(0) != 0,
+            wal_frames: row.get(1),
+            checkpointed_frames: row.get(2),
+        })
+    }
+
+    /// 大批量写入后截断 WAL，避免 WAL 文件一直保持峰值大小。失败只记录日志
+    async fn checkpoint_after_bulk_write(&self, rows: u64) {
+        if rows < BULK_WRITE_CHECKPOINT_ROWS {
+            return;
+        }
+        match self.checkpoint(CheckpointMode::Truncate).await {
+            Ok(result) if result.busy => log::debug!("WAL 检查点被其他连接阻塞，稍后由维护任务重试"),
+            Ok(_) => {}
+            Err(e) => log::warn!("WAL 检查点失败: {}", e),
+        }
+    }
+
+    pub fn disk_footprint(&self) -> DiskFootprint {
+        let size = |suffix: &str| {
+            std::fs::metadata(format!("{}{}", self.database_path.display(), suffix))
+                .map(|m| m.len())
+                .unwrap_or(0)
+        };
+        DiskFootprint {
+            database: size(""),
+            wal: size("-wal"),
+            shm: size("-shm"),
+        }
+    }
+
     pub async fn transaction<F, T>(&self, f: F) -> AppResult<T>
     where
         F: FnOnce(&mut sqlx::Transaction<'_, Sqlite>) -> AppResult<T>,
     {
-        let mut tx = self.pool.begin().await?;
+        let mut tx = self.pool().begin().await?;
         let result = f(&mut tx).await?;
         tx.commit().await?;
         Ok(result)
     }
 }
 
+/// 读取活动时使用的列，类别名称从 categories 关联得到
+/// 生产性时长，按写入时设置的 is_productive 统计
+const PRODUCTIVE_DURATION_SUM: &str = "SUM(CASE WHEN is_productive THEN duration ELSE 0 END)";
+
+const ACTIVITY_SELECT: &str = "SELECT u.*, c.name AS category_name FROM app_usage u LEFT JOIN categories c ON c.id = u.category_id";
+
+/// 按名称查找类别，不存在时创建，空名称表示未分类
+async fn category_id(conn: &mut sqlx::SqliteConnection, name: &str) -> AppResult<Option<i64>> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let now = Local::now();
+    sqlx::query("INSERT OR IGNORE INTO categories (name, created_at, updated_at) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    let id: i64 = sqlx::query_scalar("SELECT id FROM categories WHERE name = ?")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(Some(id))
+}
+
+async fn insert_activity(conn: &mut sqlx::SqliteConnection, activity: &Activity) -> AppResult<i64> {
+    let category_id = category_id(conn, &activity.category).await?;
+    let result = sqlx::query(
+        r#"
+        INSERT INTO app_usage (
+            name, app_name, window_title, description, start_time, end_time, duration, project_id, category_id,
+            is_productive, is_billable, tags, utc_offset, manual
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&activity.name)
+    .bind(&activity.app_name)
+    .bind(&activity.window_title)
+    .bind(&activity.description)
+    .bind(activity.start_time.with_timezone(&Utc))
+    .bind(activity.end_time.map(|t| t.with_timezone(&Utc)))
+    .bind(activity.duration.as_secs() as i64)
+    .bind(activity.project_id)
+    .bind(category_id)
+    .bind(activity.is_productive)
+    .bind(activity.is_billable)
+    .bind(serde_json::to_string(&activity.tags)?)
+    .bind(activity.utc_offset.unwrap_or_else(current_utc_offset))
+    .bind(activity.manual)
+    .execute(&mut *conn)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+async fn insert_activity_audit(conn: &mut sqlx::SqliteConnection, audit: &ActivityAudit) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO activity_audit (
+            activity_id, original_start_time, new_start_time, original_end_time, original_duration,
+            new_end_time, new_duration, adjusted_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(audit.activity_id)
+    .bind(&audit.original_start_time)
+    .bind(&audit.new_start_time)
+    .bind(&audit.original_end_time)
+    .bind(audit.original_duration.as_secs() as i64)
+    .bind(&audit.new_end_time)
+    .bind(audit.new_duration.as_secs() as i64)
+    .bind(&audit.adjusted_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn update_activity_row(conn: &mut sqlx::SqliteConnection, id: i64, activity: &Activity) -> AppResult<()> {
+    let category_id = category_id(conn, &activity.category).await?;
+    let result = sqlx::query(
+        r#"
+        UPDATE app_usage
+        SET name = ?, app_name = ?, window_title = ?, description = ?, start_time = ?, end_time = ?, duration = ?,
+            project_id = ?, category_id = ?, is_productive = ?, is_billable = ?, tags = ?, utc_offset = ?, manual = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&activity.name)
+    .bind(&activity.app_name)
+    .bind(&activity.window_title)
+    .bind(&activity.description)
+    .bind(activity.start_time.with_timezone(&Utc))
+    .bind(activity.end_time.map(|t| t.with_timezone(&Utc)))
+    .bind(activity.duration.as_secs() as i64)
+    .bind(activity.project_id)
+    .bind(category_id)
+    .bind(activity.is_productive)
+    .bind(activity.is_billable)
+    .bind(serde_json::to_string(&activity.tags)?)
+    .bind(activity.utc_offset.unwrap_or_else(current_utc_offset))
+    .bind(activity.manual)
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("活动 {}", id)));
+    }
+    Ok(())
+}
+
+/// 从 `ACTIVITY_SELECT` 的一行构造活动。追踪器批量写入的记录没有名称和结束时间，
+/// 分别用窗口标题和开始时间加时长补上
+fn activity_from_row(row: &SqliteRow) -> AppResult<Activity> {
+    let start_time: DateTime<Local> = row.try_get("start_time")?;
+    let duration = std::time::Duration::from_secs(row.try_get::<i64, _>("duration")?.max(0) as u64);
+    let window_title: String = row.try_get("window_title")?;
+    let tags: String = row.try_get("tags")?;
+    Ok(Activity {
+        id: Some(row.try_get("id")?),
+        name: row.try_get::<Option<String>, _>("name")?.unwrap_or_else(|| window_title.clone()),
+        start_time,
+        end_time: Some(
+            row.try_get::<Option<DateTime<Local>>, _>("end_time")?
+                .unwrap_or_else(|| start_time + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::zero())),
+        ),
+        project_id: row.try_get("project_id")?,
+        description: row.try_get("description")?,
+        duration,
+        category: row.try_get::<Option<String>, _>("category_name")?.unwrap_or_default(),
+        is_productive: row.try_get("is_productive")?,
+        app_name: row.try_get("app_name")?,
+        window_title,
+        is_billable: row.try_get("is_billable")?,
+        // 标签列由触发器同步到关联表，内容无效时按没有标签处理
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        utc_offset: row.try_get("utc_offset")?,
+        manual: row.try_get("manual")?,
+    })
+}
+
+fn activities_from_rows(rows: &[SqliteRow]) -> AppResult<Vec<Activity>> {
+    rows.iter().map(activity_from_row).collect()
+}
+
+/// 默认标签存为 JSON 数组，内容无效时按没有默认标签处理
+fn project_from_row(row: &SqliteRow) -> AppResult<Project> {
+    let default_tags: String = row.try_get("default_tags")?;
+    Ok(Project {
+        id: Some(row.try_get("id")?),
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        color: row.try_get("color")?,
+        is_billable: row.try_get("is_billable")?,
+        default_tags: serde_json::from_str(&default_tags).unwrap_or_default(),
+        default_category: row.try_get("default_category")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+async fn insert_project(conn: &mut sqlx::SqliteConnection, project: &Project) -> AppResult<i64> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO projects (
+            name, description, color, is_billable, default_tags, default_category, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&project.name)
+    .bind(&project.description)
+    .bind(&project.color)
+    .bind(&project.is_billable)
+    .bind(serde_json::to_string(&project.default_tags)?)
+    .bind(&project.default_category)
+    .bind(&project.created_at)
+    .bind(&project.updated_at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// 标记需要重算每日汇总的日期，按记录时的 UTC 偏移计算本地日期，与 app_usage 上的触发器一致
+async fn mark_summary_dirty(conn: &mut sqlx::SqliteConnection, activity: &Activity) -> AppResult<()> {
+    sqlx::query("INSERT OR IGNORE INTO summary_dirty (date) VALUES (?)")
+        .bind(activity.local_date().to_string())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn fetch_activity(conn: &mut sqlx::SqliteConnection, id: i64) -> AppResult<Activity> {
+    let row = sqlx::query(&format!("{} WHERE u.id = ?", ACTIVITY_SELECT))
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("活动 {}", id)))?;
+    activity_from_row(&row)
+}
+
+/// 标签统一去掉首尾空白和开头的 #，与迁移中拆分活动标签的规则一致
+fn normalize_tag(tag: &str) -> &str {
+    tag.trim().trim_start_matches('#')
+}
+
+/// 写入番茄钟及其标签，调用方负责放在事务中
+async fn insert_pomodoro(conn: &mut sqlx::SqliteConnection, pomodoro: &PomodoroSession) -> AppResult<i64> {
+    // 未结束的会话按计划时长推算结束时间，pomodoro_records.end_time 不能为空
+    let end_time = pomodoro.end_time.unwrap_or_else(|| {
+        pomodoro.start_time + chrono::Duration::from_std(pomodoro.duration).unwrap_or_else(|_| chrono::Duration::zero())
+    });
+    let result = sqlx::query(
+        r#"
+        INSERT INTO pomodoro_records (
+            start_time, end_time, duration, status, notes, project_id, category, utc_offset
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(pomodoro.start_time.with_timezone(&Utc))
+    .bind(end_time.with_timezone(&Utc))
+    .bind(pomodoro.duration.as_secs() as i64)
+    .bind(pomodoro.status.as_str())
+    .bind(&pomodoro.notes)
+    .bind(pomodoro.project_id)
+    .bind(&pomodoro.category)
+    .bind(pomodoro.utc_offset.unwrap_or_else(current_utc_offset))
+    .execute(&mut *conn)
+    .await?;
+    let id = result.last_insert_rowid();
+    save_pomodoro_tags(conn, id, &pomodoro.tags).await?;
+    Ok(id)
+}
+
+async fn save_pomodoro_tags(conn: &mut sqlx::SqliteConnection, pomodoro_id: i64, tags: &[String]) -> AppResult<()> {
+    for tag in tags.iter().map(|tag| normalize_tag(tag)).filter(|tag| !tag.is_empty()) {
+        sqlx::query("INSERT OR IGNORE INTO tags (name, created_at) VALUES (?, ?)")
+            .bind(tag)
+            .bind(Local::now())
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("INSERT OR IGNORE INTO pomodoro_tags (pomodoro_id, tag_id) SELECT ?, id FROM tags WHERE name = ?")
+            .bind(pomodoro_id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// 从 pomodoro_records 的一行构造会话，标签另外从关联表读取。状态无法识别时返回 `None`
+fn pomodoro_from_row(row: &SqliteRow) -> Option<PomodoroSession> {
+    let status: String = row.get("status");
+    let status = PomodoroStatus::parse(&status)?;
+    let start_time: DateTime<Local> = row.get("start_time");
+    let end_time: DateTime<Local> = row.get("end_time");
+    // 旧记录没有保存计划时长，按起止时间计算
+    let duration = row
+        .get::<Option<i64>, _>("duration")
+        .map(|secs| std::time::Duration::from_secs(secs.max(0) as u64))
+        .unwrap_or_else(|| (end_time - start_time).to_std().unwrap_or_default());
+    Some(PomodoroSession {
+        id: Some(row.get("id")),
+        start_time,
+        end_time: Some(end_time),
+        duration,
+        status,
+        project_id: row.get("project_id"),
+        notes: row.get("notes"),
+        tags: Vec::new(),
+        category: row.get("category"),
+        utc_offset: row.get("utc_offset"),
+    })
+}
+
+fn pomodoros_from_rows(rows: &[SqliteRow]) -> Vec<PomodoroSession> {
+    rows.iter()
+        .filter_map(|row| {
+            let session = pomodoro_from_row(row);
+            if session.is_none() {
+                log::warn!("跳过状态无法识别的番茄钟记录: {}", row.get::<i64, _>("id"));
+            }
+            session
+        })
+        .collect()
+}
+
+impl SqliteStorage {
+    /// 从关联表读取番茄钟的标签，按名称排序
+    async fn load_pomodoro_tags(&self, sessions: &mut [PomodoroSession]) -> AppResult<()> {
+        let ids: Vec<i64> = sessions.iter().filter_map(|session| session.id).collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT pt.pomodoro_id, t.name
+            FROM pomodoro_tags pt
+            JOIN tags t ON t.id = pt.tag_id
+            WHERE pt.pomodoro_id IN (SELECT value FROM json_each(?))
+            ORDER BY t.name
+            "#,
+        )
+        .bind(serde_json::to_string(&ids)?)
+        .fetch_all(&self.pool())
+        .await?;
+
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.get("pomodoro_id")).or_default().push(row.get("name"));
+        }
+        for session in sessions {
+            session.tags = session.id.and_then(|id| tags.remove(&id)).unwrap_or_default();
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Storage for SqliteStorage {
     async fn initialize(&self) -> AppResult<()> {
@@ -74,7 +826,7 @@ impl Storage for SqliteStorage {
 
     async fn get_config(&self) -> AppResult<Option<AppConfig>> {
         let result = sqlx::query("SELECT * FROM config WHERE id = 1")
-            .fetch_optional(&self.pool)
+            .fetch_optional(&self.pool())
             .await?;
 
         match result {
@@ -96,200 +848,2023 @@ impl Storage for SqliteStorage {
             "#,
         )
         .bind(&data)
-        .execute(&self.pool)
+        .execute(&self.pool())
         .await?;
         Ok(())
     }
 
     async fn save_activity(&self, activity: &Activity) -> AppResult<i64> {
-        let result = sqlx::query(
-            r#"
-            INSERT INTO activities (
-                title, description, start_time, end_time, project_id, category_id
-            ) VALUES (?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&activity.title)
-        .bind(&activity.description)
-        .bind(&activity.start_time)
-        .bind(&activity.end_time)
-        .bind(&activity.project_id)
-        .bind(&activity.category_id)
-        .execute(&self.pool)
-        .await?;
-        Ok(result.last_insert_rowid())
+        let mut conn = self.pool().acquire().await?;
+        insert_activity(&mut conn, activity).await
     }
 
-    async fn get_activity(&self, id: i64) -> AppResult<Activity> {
-        let activity = sqlx::query_as::<_, Activity>(
-            r#"
-            SELECT * FROM activities WHERE id = ?
-            "#,
-        )
-        .bind(id)
-        .fetch_one(&self.pool)
+    async fn add_manual_activity(&self, mut activity: Activity) -> AppResult<ManualEntry> {
+        let end_time = activity
+            .end_time
+            .ok_or_else(|| AppError::InvalidOperation("补录的活动需要结束时间".into()))?;
+        if end_time <= activity.start_time {
+            return Err(AppError::InvalidOperation("结束时间必须晚于开始时间".into()));
+        }
+        activity.manual = true;
+        activity.duration = (end_time - activity.start_time).to_std().unwrap_or_default();
+
+        // 追踪器写入的记录没有结束时间，按开始时间加时长计算
+        let rows = sqlx::query(&format!(
+            "{} WHERE u.start_time < ? AND COALESCE(u.end_time, datetime(u.start_time, printf('+%d seconds', u.duration))) > ? ORDER BY u.start_time",
+            ACTIVITY_SELECT
+        ))
+        .bind(end_time.with_timezone(&Utc))
+        .bind(activity.start_time.with_timezone(&Utc))
+        .fetch_all(&self.pool())
         .await?;
-        Ok(activity)
+        let overlaps = activities_from_rows(&rows)?;
+
+        let mut conn = self.pool().acquire().await?;
+        let id = insert_activity(&mut conn, &activity).await?;
+        Ok(ManualEntry { id, overlaps })
     }
 
-    async fn list_activities(&self) -> AppResult<Vec<Activity>> {
-        let activities = sqlx::query_as::<_, Activity>(
-            r#"
-            SELECT * FROM activities ORDER BY start_time DESC
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(activities)
+    async fn save_activities(&self, activities: &[Activity]) -> AppResult<Vec<i64>> {
+        let mut tx = self.pool().begin().await?;
+        let mut ids = Vec::with_capacity(activities.len());
+        for activity in activities {
+            ids.push(insert_activity(&mut tx, activity).await?);
+        }
+        tx.commit().await?;
+        self.checkpoint_after_bulk_write(ids.len() as u64).await;
+        Ok(ids)
     }
 
-    async fn get_activities(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>> {
-        let activities = sqlx::query_as::<_, Activity>(
-            r#"
-            SELECT * FROM activities 
-            WHERE start_time >= ? AND end_time <= ?
-            ORDER BY start_time DESC
-            "#,
-        )
-        .bind(start)
-        .bind(end)
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(activities)
+    async fn save_merge(&self, batch: &MergeBatch) -> AppResult<()> {
+        let mut tx = self.pool().begin().await?;
+        let mut created: HashMap<&str, i64> = HashMap::new();
+        for project in &batch.projects {
+            created.insert(project.name.as_str(), insert_project(&mut tx, project).await?);
+        }
+        let resolve = |project: &Option<ProjectRef>| -> AppResult<Option<i64>> {
+            match project {
+                None => Ok(None),
+                Some(ProjectRef::Existing(id)) => Ok(Some(*id)),
+                Some(ProjectRef::New(name)) => created
+                    .get(name.as_str())
+                    .copied()
+                    .map(Some)
+                    .ok_or_else(|| AppError::InvalidOperation(format!("合并的记录引用了未新建的项目: {}", name))),
+            }
+        };
+
+        for (activity, project) in &batch.activities {
+            let activity = Activity {
+                project_id: resolve(project)?,
+                ..activity.clone()
+            };
+            insert_activity(&mut tx, &activity).await?;
+        }
+        for (session, project) in &batch.pomodoros {
+            let session = PomodoroSession {
+                project_id: resolve(project)?,
+                ..session.clone()
+            };
+            insert_pomodoro(&mut tx, &session).await?;
+        }
+        tx.commit().await?;
+        self.checkpoint_after_bulk_write((batch.activities.len() + batch.pomodoros.len()) as u64)
+            .await;
+        Ok(())
     }
 
-    async fn get_project_activities(&self, project_id: i64, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>> {
-        let activities = sqlx::query_as::<_, Activity>(
-            r#"
-            SELECT * FROM activities 
-            WHERE project_id = ? AND start_time >= ? AND end_time <= ?
-            ORDER BY start_time DESC
-            "#,
-        )
-        .bind(project_id)
-        .bind(start)
-        .bind(end)
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(activities)
+    async fn add_app_usage_batch(&self, records: &[AppUsageRecord]) -> AppResult<Vec<i64>> {
+        let mut tx = self.pool().begin().await?;
+        let mut category_ids: HashMap<String, Option<i64>> = HashMap::new();
+        let mut ids = Vec::with_capacity(records.len());
+        // 语句文本不变，连接会缓存预编译结果，每条记录只需绑定参数
+        for record in records {
+            let name = record.category.clone().unwrap_or_default();
+            let category = match category_ids.get(&name) {
+                Some(id) => *id,
+                None => {
+                    let id = category_id(&mut tx, &name).await?;
+                    category_ids.insert(name, id);
+                    id
+                }
+            };
+            let start_time = record.start_time.with_timezone(&Utc);
+            let result = sqlx::query(
+                r#"
+                INSERT INTO app_usage (app_name, window_title, start_time, end_time, duration, category_id, project_id, utc_offset)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&record.app_name)
+            .bind(&record.window_title)
+            .bind(start_time)
+            .bind(start_time + chrono::Duration::seconds(record.duration))
+            .bind(record.duration)
+            .bind(category)
+            .bind(record.project_id)
+            .bind(record.start_time.offset().local_minus_utc())
+            .execute(&mut *tx)
+            .await?;
+            ids.push(result.last_insert_rowid());
+        }
+        tx.commit().await?;
+        self.checkpoint_after_bulk_write(ids.len() as u64).await;
+        Ok(ids)
     }
 
-    async fn save_project(&self, project: &Project) -> AppResult<i64> {
-        let result = sqlx::query(
-            r#"
-            INSERT INTO projects (
-                name, description, color, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&project.name)
-        .bind(&project.description)
-        .bind(&project.color)
-        .bind(&project.created_at)
-        .bind(&project.updated_at)
-        .execute(&self.pool)
-        .await?;
-        Ok(result.last_insert_rowid())
+    async fn stream_app_usage(
+        &self,
+        range: (DateTime<Local>, DateTime<Local>),
+        batch_size: u32,
+    ) -> AppResult<Box<dyn AppUsageBatches>> {
+        let conn = self.pool().acquire().await?;
+        Ok(Box::new(AppUsageStream::new(conn, range.0, range.1, batch_size)))
     }
 
-    async fn get_project(&self, id: i64) -> AppResult<Project> {
-        let project = sqlx::query_as::<_, Project>(
-            r#"
-            SELECT * FROM projects WHERE id = ?
-            "#,
-        )
-        .bind(id)
-        .fetch_one(&self.pool)
-        .await?;
-        Ok(project)
+    async fn safety_backup(&self) -> AppResult<Option<PathBuf>> {
+        if !self.settings.backup_before_destructive {
+            return Ok(None);
+        }
+        let dir = self.backup_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("safety-{}.db", Local::now().format("%Y%m%d-%H%M%S%.3f")));
+        self.backup(&path).await?;
+        Ok(Some(path))
     }
 
-    async fn list_projects(&self) -> AppResult<Vec<Project>> {
-        let projects = sqlx::query_as::<_, Project>(
-            r#"
-            SELECT * FROM projects ORDER BY name
-            "#,
-        )
-        .fetch_all(&self.pool)
-        .await?;
-        Ok(projects)
+    // 备份来自旧版本时重新打开连接池会执行迁移。替换期间连接池先换成备份的临时副本，
+    // 关闭旧连接池前新连接池已经就位，其他任务不会拿到已关闭的连接池。
+    // 替换或打开失败时放回安全备份并重新打开原数据库
+    async fn restore_from_backup(&self, backup_path: &Path) -> AppResult<()> {
+        if backup_path.to_string_lossy().ends_with(ENCRYPTED_BACKUP_EXTENSION) {
+            return Err(AppError::InvalidOperation("加密备份需要先用 restore_encrypted 解密".into()));
+        }
+        Self::verify_backup(backup_path).await?;
+
+        let dir = self.backup_dir();
+        std::fs::create_dir_all(&dir)?;
+        let safety = dir.join(format!("safety-{}.db", Local::now().format("%Y%m%d-%H%M%S%.3f")));
+        self.backup(&safety).await?;
+
+        let staging = self.database_path.with_extension("restore.db");
+        std::fs::copy(backup_path, &staging)?;
+        let interim = match open_pool(&staging, &self.settings).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                remove_database_files(&staging);
+                return Err(e);
+            }
+        };
+        let original = std::mem::replace(&mut *self.pool.write().unwrap(), interim.clone());
+        original.close().await;
+
+        let restored = match replace_database_file(backup_path, &self.database_path) {
+            Ok(()) => open_pool(&self.database_path, &self.settings).await,
+            Err(e) => Err(e),
+        };
+        let result = match restored {
+            Ok(pool) => {
+                *self.pool.write().unwrap() = pool;
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("从备份恢复失败，重新打开原数据库: {}", e);
+                let reopened = match replace_database_file(&safety, &self.database_path) {
+                    Ok(()) => open_pool(&self.database_path, &self.settings).await,
+                    Err(copy_error) => Err(copy_error),
+                };
+                match reopened {
+                    Ok(pool) => *self.pool.write().unwrap() = pool,
+                    // 连原数据库也无法打开时保留临时副本上的连接池，数据仍可读取
+                    Err(reopen_error) => {
+                        log::error!("重新打开原数据库失败: {}", reopen_error);
+                        return Err(e);
+                    }
+                }
+                Err(e)
+            }
+        };
+        interim.close().await;
+        remove_database_files(&staging);
+        result
     }
 
-    async fn save_pomodoro(&self, pomodoro: &PomodoroSession) -> AppResult<i64> {
-        let result = sqlx::query(
+    async fn rebuild_daily_summaries(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<u64> {
+        let (start, end) = range;
+        let first_day = start.date_naive().to_string();
+        let last_day = end.date_naive().to_string();
+        // 偏移最多相差一天，原始记录按放宽后的时间范围筛选，以便使用 start_time 索引
+        let since = (start - chrono::Duration::days(1)).with_timezone(&Utc);
+        let until = (end + chrono::Duration::days(1)).with_timezone(&Utc);
+        let mut tx = self.pool().begin().await?;
+
+        // 原始记录已全部删除的日期不再保留汇总
+        sqlx::query(&format!(
             r#"
-            INSERT INTO pomodoro_sessions (
-                start_time, end_time, duration, status, project_id
-            ) VALUES (?, ?, ?, ?, ?)
+            DELETE FROM daily_summaries
+            WHERE date IN (SELECT date FROM summary_dirty WHERE date BETWEEN ? AND ?)
+              AND date NOT IN (
+                  SELECT {day} FROM app_usage WHERE start_time >= ? AND start_time < ?
+                  UNION
+                  SELECT {day} FROM pomodoro_records WHERE start_time >= ? AND start_time < ?
+              )
             "#,
-        )
-        .bind(&pomodoro.start_time)
-        .bind(&pomodoro.end_time)
-        .bind(&pomodoro.duration)
-        .bind(&pomodoro.status)
-        .bind(&pomodoro.project_id)
-        .execute(&self.pool)
+            day = retention::DAY_EXPR
+        ))
+        .bind(&first_day)
+        .bind(&last_day)
+        .bind(since)
+        .bind(until)
+        .bind(since)
+        .bind(until)
+        .execute(&mut *tx)
         .await?;
-        Ok(result.last_insert_rowid())
-    }
 
-    async fn get_pomodoro(&self, id: i64) -> AppResult<PomodoroSession> {
-        let session = sqlx::query_as::<_, PomodoroSession>(
+        let result = sqlx::query(&format!(
             r#"
-            SELECT * FROM pomodoro_sessions WHERE id = ?
+            WITH target AS (SELECT date AS day FROM summary_dirty WHERE date BETWEEN ? AND ?),
+            usage AS (
+                SELECT {day} AS day, app_name, duration, is_productive
+                FROM app_usage WHERE start_time >= ? AND start_time < ?
+            ),
+            usage_days AS (
+                SELECT day,
+                       SUM(duration) AS total,
+                       {productive} AS productive
+                FROM usage WHERE day IN (SELECT day FROM target) GROUP BY day
+            ),
+            app_days AS (
+                SELECT day, app_name, ROW_NUMBER() OVER (PARTITION BY day ORDER BY SUM(duration) DESC) AS rank
+                FROM usage WHERE day IN (SELECT day FROM target) GROUP BY day, app_name
+            ),
+            pomodoro_days AS (
+                SELECT day,
+                       SUM(status = 'Completed') AS completed,
+                       SUM(status = 'Interrupted') AS interrupted
+                FROM (SELECT {day} AS day, status FROM pomodoro_records WHERE start_time >= ? AND start_time < ?)
+                WHERE day IN (SELECT day FROM target) GROUP BY day
+            )
+            INSERT INTO daily_summaries (
+                date, total_work_time, productive_time, completed_pomodoros, interrupted_pomodoros, most_used_app, updated_at
+            )
+            SELECT t.day,
+                   COALESCE(u.total, 0),
+                   COALESCE(u.productive, 0),
+                   COALESCE(p.completed, 0),
+                   COALESCE(p.interrupted, 0),
+                   a.app_name,
+                   CURRENT_TIMESTAMP
+            FROM target t
+            LEFT JOIN usage_days u ON u.day = t.day
+            LEFT JOIN pomodoro_days p ON p.day = t.day
+            LEFT JOIN app_days a ON a.day = t.day AND a.rank = 1
+            WHERE u.day IS NOT NULL OR p.day IS NOT NULL
+            ON CONFLICT(date) DO UPDATE SET
+                total_work_time = excluded.total_work_time,
+                productive_time = excluded.productive_time,
+                completed_pomodoros = excluded.completed_pomodoros,
+                interrupted_pomodoros = excluded.interrupted_pomodoros,
+                most_used_app = excluded.most_used_app,
+                updated_at = excluded.updated_at
             "#,
-        )
-        .bind(id)
-        .fetch_one(&self.pool)
+            day = retention::DAY_EXPR,
+            productive = PRODUCTIVE_DURATION_SUM
+        ))
+        .bind(&first_day)
+        .bind(&last_day)
+        .bind(since)
+        .bind(until)
+        .bind(since)
+        .bind(until)
+        .execute(&mut *tx)
         .await?;
-        Ok(session)
+
+        sqlx::query("DELETE FROM summary_dirty WHERE date BETWEEN ? AND ?")
+            .bind(&first_day)
+            .bind(&last_day)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
     }
 
-    async fn list_pomodoros(&self) -> AppResult<Vec<PomodoroSession>> {
-        let sessions = sqlx::query_as::<_, PomodoroSession>(
+    async fn get_daily_usage(&self, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<DailyUsage>> {
+        let rows = sqlx::query(&format!(
             r#"
-            SELECT * FROM pomodoro_sessions ORDER BY start_time DESC
+            WITH usage AS (
+                SELECT {day} AS day,
+                       SUM(duration) AS total,
+                       {productive} AS productive
+                FROM app_usage WHERE start_time >= ? AND start_time < ?
+                GROUP BY day
+            ),
+            pomodoros AS (
+                SELECT {day} AS day,
+                       SUM(CAST(ROUND((julianday(end_time) - julianday(start_time)) * 86400) AS INTEGER)) AS focus,
+                       COUNT(*) AS completed
+                FROM pomodoro_records WHERE status = 'Completed' AND start_time >= ? AND start_time < ?
+                GROUP BY day
+            ),
+            days AS (SELECT day FROM usage UNION SELECT day FROM pomodoros)
+            SELECT d.day AS day,
+                   COALESCE(u.total, 0) AS total,
+                   COALESCE(u.productive, 0) AS productive,
+                   COALESCE(p.focus, 0) AS focus,
+                   COALESCE(p.completed, 0) AS completed
+            FROM days d
+            LEFT JOIN usage u ON u.day = d.day
+            LEFT JOIN pomodoros p ON p.day = d.day
+            ORDER BY d.day
             "#,
-        )
-        .fetch_all(&self.pool)
+            day = retention::DAY_EXPR,
+            productive = PRODUCTIVE_DURATION_SUM
+        ))
+        .bind(range.0.with_timezone(&Utc))
+        .bind(range.1.with_timezone(&Utc))
+        .bind(range.0.with_timezone(&Utc))
+        .bind(range.1.with_timezone(&Utc))
+        .fetch_all(&self.pool())
         .await?;
+
+        let seconds = |row: &sqlx::sqlite::SqliteRow, column: &str| {
+            std::time::Duration::from_secs(row.get::<i64, _>(column).max(0) as u64)
+        };
+        rows.iter()
+            .map(|row| {
+                let day: String = row.get("day");
+                Ok(DailyUsage {
+                    date: day
+                        .parse()
+                        .map_err(|e| AppError::System(format!("无效的日期 {}: {}", day, e)))?,
+                    total_time: seconds(row, "total"),
+                    productive_time: seconds(row, "productive"),
+                    focus_time: seconds(row, "focus"),
+                    completed_pomodoros: row.get::<i64, _>("completed").max(0) as u32,
+                })
+            })
+            .collect()
+    }
+
+    async fn search(&self, query: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<SearchHit>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AppError::InvalidOperation("搜索内容不能为空".into()));
+        }
+        // trigram 索引只能匹配至少三个字符，更短的查询逐行比较并在这里标出匹配
+        let short = query.chars().count() < 3;
+        let (filter, snippet, pattern) = if short {
+            (
+                r"search_index.text LIKE ? ESCAPE '\'",
+                "search_index.text".to_string(),
+                format!("%{}%", query.replace('\\', r"\\").replace('%', r"\%").replace('_', r"\_")),
+            )
+        } else {
+            (
+                "search_index MATCH ?",
+                format!("snippet(search_index, 0, '{0}', '{0}', '…', 16)", SEARCH_HIGHLIGHT),
+                format!("\"{}\"", query.replace('"', "\"\"")),
+            )
+        };
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT search_index.kind AS kind,
+                   search_index.source_id AS source_id,
+                   {snippet} AS snippet,
+                   COALESCE(u.start_time, p.start_time) AS start_time,
+                   u.app_name AS app_name
+            FROM search_index
+            LEFT JOIN app_usage u ON search_index.kind = 'activity' AND u.id = search_index.source_id
+            LEFT JOIN pomodoro_records p ON search_index.kind = 'pomodoro' AND p.id = search_index.source_id
+            WHERE {filter}
+              AND COALESCE(u.start_time, p.start_time) >= ?
+              AND COALESCE(u.start_time, p.start_time) < ?
+            ORDER BY start_time DESC
+            LIMIT ?
+            "#
+        ))
+        .bind(pattern)
+        .bind(range.0.with_timezone(&Utc))
+        .bind(range.1.with_timezone(&Utc))
+        .bind(SEARCH_LIMIT)
+        .fetch_all(&self.pool())
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let kind = match row.get::<String, _>("kind").as_str() {
+                    "pomodoro" => SearchKind::Pomodoro,
+                    _ => SearchKind::Activity,
+                };
+                let snippet: String = row.get("snippet");
+                Ok(SearchHit {
+                    kind,
+                    id: row.get("source_id"),
+                    start_time: row.try_get("start_time")?,
+                    app_name: row.get("app_name"),
+                    snippet: if short { highlight(&snippet, query) } else { snippet },
+                })
+            })
+            .collect()
+    }
+
+    async fn get_activity(&self, id: i64) -> AppResult<Activity> {
+        let mut conn = self.pool().acquire().await?;
+        fetch_activity(&mut conn, id).await
+    }
+
+    async fn list_activities(&self) -> AppResult<Vec<Activity>> {
+        let rows = sqlx::query(&format!("{} ORDER BY u.start_time DESC", ACTIVITY_SELECT))
+            .fetch_all(&self.pool())
+            .await?;
+        activities_from_rows(&rows)
+    }
+
+    async fn get_activities(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE u.start_time >= ? AND u.start_time < ? ORDER BY u.start_time DESC",
+            ACTIVITY_SELECT
+        ))
+        .bind(start.with_timezone(&Utc))
+        .bind(end.with_timezone(&Utc))
+        .fetch_all(&self.pool())
+        .await?;
+        activities_from_rows(&rows)
+    }
+
+    async fn get_project_activities(&self, project_id: i64, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<Activity>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE u.project_id = ? AND u.start_time >= ? AND u.start_time < ? ORDER BY u.start_time DESC",
+            ACTIVITY_SELECT
+        ))
+        .bind(project_id)
+        .bind(start.with_timezone(&Utc))
+        .bind(end.with_timezone(&Utc))
+        .fetch_all(&self.pool())
+        .await?;
+        activities_from_rows(&rows)
+    }
+
+    async fn get_activities_by_tag(&self, tag: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<Activity>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            {}
+            JOIN activity_tags at ON at.activity_id = u.id
+            JOIN tags t ON t.id = at.tag_id
+            WHERE t.name = ? COLLATE NOCASE AND u.start_time >= ? AND u.start_time < ?
+            ORDER BY u.start_time DESC
+            "#,
+            ACTIVITY_SELECT
+        ))
+        .bind(normalize_tag(tag))
+        .bind(range.0.with_timezone(&Utc))
+        .bind(range.1.with_timezone(&Utc))
+        .fetch_all(&self.pool())
+        .await?;
+        activities_from_rows(&rows)
+    }
+
+    /// 修改前后的日期都标记为需要重算汇总
+    async fn update_activity(&self, activity: &Activity) -> AppResult<()> {
+        let id = activity.id.ok_or_else(|| AppError::InvalidOperation("活动尚未保存".into()))?;
+        let mut tx = self.pool().begin().await?;
+        let original = fetch_activity(&mut tx, id).await?;
+        update_activity_row(&mut tx, id, activity).await?;
+        mark_summary_dirty(&mut tx, &original).await?;
+        mark_summary_dirty(&mut tx, activity).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_activity(&self, id: i64) -> AppResult<()> {
+        let mut tx = self.pool().begin().await?;
+        let original = fetch_activity(&mut tx, id).await?;
+        sqlx::query("DELETE FROM app_usage WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        mark_summary_dirty(&mut tx, &original).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn split_activity(&self, id: i64, at: DateTime<Local>) -> AppResult<(Activity, Activity)> {
+        let mut tx = self.pool().begin().await?;
+        let mut first = fetch_activity(&mut tx, id).await?;
+        let end_time = first
+            .end_time
+            .ok_or_else(|| AppError::InvalidOperation("进行中的活动不能拆分".into()))?;
+        if at <= first.start_time || at >= end_time {
+            return Err(AppError::InvalidOperation("拆分时间必须在活动的开始和结束之间".into()));
+        }
+
+        let mut second = Activity {
+            id: None,
+            start_time: at,
+            end_time: Some(end_time),
+            duration: (end_time - at).to_std().unwrap_or_default(),
+            ..first.clone()
+        };
+        first.end_time = Some(at);
+        first.duration = (at - first.start_time).to_std().unwrap_or_default();
+
+        update_activity_row(&mut tx, id, &first).await?;
+        second.id = Some(insert_activity(&mut tx, &second).await?);
+        // 跨越午夜的活动拆分后两部分可能落在不同日期
+        mark_summary_dirty(&mut tx, &first).await?;
+        mark_summary_dirty(&mut tx, &second).await?;
+        tx.commit().await?;
+        Ok((first, second))
+    }
+
+    async fn update_activities_with_audit(&self, changes: &[(Activity, ActivityAudit)]) -> AppResult<()> {
+        let mut tx = self.pool().begin().await?;
+        for (activity, audit) in changes {
+            let id = activity.id.ok_or_else(|| AppError::InvalidOperation("活动尚未保存".into()))?;
+            let original = fetch_activity(&mut tx, id).await?;
+            update_activity_row(&mut tx, id, activity).await?;
+            insert_activity_audit(&mut tx, audit).await?;
+            mark_summary_dirty(&mut tx, &original).await?;
+            mark_summary_dirty(&mut tx, activity).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn save_project(&self, project: &Project) -> AppResult<i64> {
+        let mut conn = self.pool().acquire().await?;
+        insert_project(&mut conn, project).await
+    }
+
+    async fn get_project(&self, id: i64) -> AppResult<Project> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM projects WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool())
+        .await?;
+        project_from_row(&row)
+    }
+
+    async fn list_projects(&self) -> AppResult<Vec<Project>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM projects ORDER BY name
+            "#,
+        )
+        .fetch_all(&self.pool())
+        .await?;
+        rows.iter().map(project_from_row).collect()
+    }
+
+    /// 每个项目每种周期只保留一个目标，重复设置时覆盖
+    // 更新已有目标时 last_insert_rowid 不会变化，用 RETURNING 取得实际写入的行
+    async fn save_project_goal(&self, goal: &ProjectGoal) -> AppResult<i64> {
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO project_goals (project_id, period, target_seconds)
+            VALUES (?, ?, ?)
+            ON CONFLICT(project_id, period) DO UPDATE SET target_seconds = excluded.target_seconds
+            RETURNING id
+            "#,
+        )
+        .bind(goal.project_id)
+        .bind(goal.period.as_str())
+        .bind(goal.target.as_secs() as i64)
+        .fetch_one(&self.pool())
+        .await?;
+        Ok(id)
+    }
+
+    async fn get_project_goal(&self, project_id: i64, period: GoalPeriod) -> AppResult<Option<ProjectGoal>> {
+        let row = sqlx::query("SELECT id, target_seconds FROM project_goals WHERE project_id = ? AND period = ?")
+            .bind(project_id)
+            .bind(period.as_str())
+            .fetch_optional(&self.pool())
+            .await?;
+        Ok(row.map(|row| ProjectGoal {
+            id: row.get("id"),
+            project_id,
+            period,
+            target: std::time::Duration::from_secs(row.get::<i64, _>("target_seconds").max(0) as u64),
+        }))
+    }
+
+    async fn snapshot_project(&self, project_id: i64) -> AppResult<ProjectSnapshot> {
+        let project = self.get_project(project_id).await?;
+        let mut goals = Vec::new();
+        for period in [GoalPeriod::Week, GoalPeriod::Month] {
+            goals.extend(self.get_project_goal(project_id, period).await?);
+        }
+        let pomodoro_ids = sqlx::query_scalar("SELECT id FROM pomodoro_records WHERE project_id = ? ORDER BY id")
+            .bind(project_id)
+            .fetch_all(&self.pool())
+            .await?;
+        let activity_ids = sqlx::query_scalar("SELECT id FROM app_usage WHERE project_id = ? ORDER BY id")
+            .bind(project_id)
+            .fetch_all(&self.pool())
+            .await?;
+        let daily_goals = self
+            .list_goals()
+            .await?
+            .into_iter()
+            .filter(|goal| goal.kind.project_id() == Some(project_id))
+            .collect();
+        Ok(ProjectSnapshot {
+            project,
+            goals,
+            pomodoro_ids,
+            activity_ids,
+            daily_goals,
+        })
+    }
+
+    async fn restore_project(&self, snapshot: &ProjectSnapshot) -> AppResult<()> {
+        let project = &snapshot.project;
+        let project_id = project
+            .id
+            .ok_or_else(|| AppError::InvalidOperation("项目快照缺少 id".into()))?;
+
+        let mut tx = self.pool().begin().await?;
+        // 删除后又创建了同名项目时插入会因唯一约束失败，整个恢复回滚
+        sqlx::query(
+            r#"
+            INSERT INTO projects (
+                id, name, description, color, is_billable, default_tags, default_category, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(project_id)
+        .bind(&project.name)
+        .bind(&project.description)
+        .bind(&project.color)
+        .bind(&project.is_billable)
+        .bind(serde_json::to_string(&project.default_tags)?)
+        .bind(&project.default_category)
+        .bind(&project.created_at)
+        .bind(&project.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for goal in &snapshot.goals {
+            sqlx::query("INSERT INTO project_goals (project_id, period, target_seconds) VALUES (?, ?, ?)")
+                .bind(project_id)
+                .bind(goal.period.as_str())
+                .bind(goal.target.as_secs() as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for goal in &snapshot.daily_goals {
+            sqlx::query("INSERT INTO goals (id, kind, project_id, target, schedule, active) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(goal.id)
+                .bind(goal.kind.as_str())
+                .bind(project_id)
+                .bind(goal.target as i64)
+                .bind(serde_json::to_string(&goal.schedule)?)
+                .bind(goal.active)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // 只恢复删除后没有被重新关联到其他项目的番茄钟和活动
+        for pomodoro_id in &snapshot.pomodoro_ids {
+            sqlx::query("UPDATE pomodoro_records SET project_id = ? WHERE id = ? AND project_id IS NULL")
+                .bind(project_id)
+                .bind(pomodoro_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for activity_id in &snapshot.activity_ids {
+            sqlx::query("UPDATE app_usage SET project_id = ? WHERE id = ? AND project_id IS NULL")
+                .bind(project_id)
+                .bind(activity_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn count_project_data(&self, project_id: i64) -> AppResult<DeletionImpact> {
+        let (app_usage, pomodoros, goals): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM app_usage WHERE project_id = ?1),
+                (SELECT COUNT(*) FROM pomodoro_records WHERE project_id = ?1),
+                (SELECT COUNT(*) FROM project_goals WHERE project_id = ?1)
+                    + (SELECT COUNT(*) FROM goals WHERE project_id = ?1)
+            "#,
+        )
+        .bind(project_id)
+        .fetch_one(&self.pool())
+        .await?;
+        Ok(DeletionImpact {
+            app_usage: app_usage as u64,
+            pomodoros: pomodoros as u64,
+            goals: goals as u64,
+            ..DeletionImpact::default()
+        })
+    }
+
+    async fn delete_project(&self, project_id: i64, confirmed: bool) -> AppResult<(DeletionImpact, Option<PathBuf>)> {
+        let impact = self.count_project_data(project_id).await?;
+        self.settings.check_bulk_delete(impact.total(), confirmed)?;
+
+        // 不依赖外键动作，逐表解除关联或删除，返回的数量与实际写入一致
+        let result = self
+            .with_safety_backup(|| async {
+                let mut tx = self.pool().begin().await?;
+                let mut impact = DeletionImpact::default();
+                impact.app_usage = sqlx::query("UPDATE app_usage SET project_id = NULL WHERE project_id = ?")
+                    .bind(project_id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                impact.pomodoros = sqlx::query("UPDATE pomodoro_records SET project_id = NULL WHERE project_id = ?")
+                    .bind(project_id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                for table in ["project_goals", "goals"] {
+                    impact.goals += sqlx::query(&format!("DELETE FROM {} WHERE project_id = ?", table))
+                        .bind(project_id)
+                        .execute(&mut *tx)
+                        .await?
+                        .rows_affected();
+                }
+                let deleted = sqlx::query("DELETE FROM projects WHERE id = ?")
+                    .bind(project_id)
+                    .execute(&mut *tx)
+                    .await?;
+                if deleted.rows_affected() == 0 {
+                    return Err(AppError::NotFound(format!("项目 {}", project_id)));
+                }
+                tx.commit().await?;
+                Ok(impact)
+            })
+            .await?;
+        self.checkpoint_after_bulk_write(result.0.total()).await;
+        Ok(result)
+    }
+
+    async fn save_goal(&self, goal: &Goal) -> AppResult<i64> {
+        let schedule = serde_json::to_string(&goal.schedule)?;
+        match goal.id {
+            Some(id) => {
+                let result = sqlx::query(
+                    "UPDATE goals SET kind = ?, project_id = ?, target = ?, schedule = ?, active = ? WHERE id = ?",
+                )
+                .bind(goal.kind.as_str())
+                .bind(goal.kind.project_id())
+                .bind(goal.target as i64)
+                .bind(&schedule)
+                .bind(goal.active)
+                .bind(id)
+                .execute(&self.pool())
+                .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AppError::NotFound(format!("目标 {}", id)));
+                }
+                Ok(id)
+            }
+            None => {
+                let result = sqlx::query(
+                    "INSERT INTO goals (kind, project_id, target, schedule, active) VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind(goal.kind.as_str())
+                .bind(goal.kind.project_id())
+                .bind(goal.target as i64)
+                .bind(&schedule)
+                .bind(goal.active)
+                .execute(&self.pool())
+                .await?;
+                Ok(result.last_insert_rowid())
+            }
+        }
+    }
+
+    async fn list_goals(&self) -> AppResult<Vec<Goal>> {
+        let rows = sqlx::query("SELECT id, kind, project_id, target, schedule, active FROM goals ORDER BY id")
+            .fetch_all(&self.pool())
+            .await?;
+        let mut goals = Vec::with_capacity(rows.len());
+        for row in rows {
+            let kind: String = row.get("kind");
+            // 无法识别的类型可能来自更新的版本，跳过而不是让整个列表失败
+            let Some(kind) = GoalKind::parse(&kind, row.get("project_id")) else {
+                log::warn!("跳过无法识别的目标类型: {}", kind);
+                continue;
+            };
+            goals.push(Goal {
+                id: Some(row.get("id")),
+                kind,
+                target: row.get::<i64, _>("target").max(0) as u64,
+                schedule: serde_json::from_str(&row.get::<String, _>("schedule"))?,
+                active: row.get("active"),
+            });
+        }
+        Ok(goals)
+    }
+
+    async fn delete_goal(&self, id: i64) -> AppResult<()> {
+        sqlx::query("DELETE FROM goals WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool())
+            .await?;
+        Ok(())
+    }
+
+    async fn save_pomodoro(&self, pomodoro: &PomodoroSession) -> AppResult<i64> {
+        let mut tx = self.pool().begin().await?;
+        let id = insert_pomodoro(&mut tx, pomodoro).await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn get_pomodoro(&self, id: i64) -> AppResult<PomodoroSession> {
+        let row = sqlx::query("SELECT * FROM pomodoro_records WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("番茄钟 {}", id)))?;
+        let mut session = pomodoro_from_row(&row)
+            .ok_or_else(|| AppError::InvalidOperation(format!("番茄钟 {} 的状态无法识别", id)))?;
+        self.load_pomodoro_tags(std::slice::from_mut(&mut session)).await?;
+        Ok(session)
+    }
+
+    async fn list_pomodoros(&self) -> AppResult<Vec<PomodoroSession>> {
+        let rows = sqlx::query("SELECT * FROM pomodoro_records ORDER BY start_time DESC")
+            .fetch_all(&self.pool())
+            .await?;
+        let mut sessions = pomodoros_from_rows(&rows);
+        self.load_pomodoro_tags(&mut sessions).await?;
         Ok(sessions)
     }
 
     async fn get_pomodoro_sessions(&self, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<PomodoroSession>> {
-        let sessions = sqlx::query_as::<_, PomodoroSession>(
+        let rows = sqlx::query(
             r#"
-            SELECT * FROM pomodoro_sessions 
+            SELECT * FROM pomodoro_records
             WHERE start_time >= ? AND end_time <= ?
             ORDER BY start_time DESC
             "#,
         )
-        .bind(start)
-        .bind(end)
-        .fetch_all(&self.pool)
+        .bind(start.with_timezone(&Utc))
+        .bind(end.with_timezone(&Utc))
+        .fetch_all(&self.pool())
         .await?;
+        let mut sessions = pomodoros_from_rows(&rows);
+        self.load_pomodoro_tags(&mut sessions).await?;
         Ok(sessions)
     }
 
     async fn get_project_pomodoro_sessions(&self, project_id: i64, start: DateTime<Local>, end: DateTime<Local>) -> AppResult<Vec<PomodoroSession>> {
-        let sessions = sqlx::query_as::<_, PomodoroSession>(
+        let rows = sqlx::query(
             r#"
-            SELECT * FROM pomodoro_sessions 
+            SELECT * FROM pomodoro_records
             WHERE project_id = ? AND start_time >= ? AND end_time <= ?
             ORDER BY start_time DESC
             "#,
         )
         .bind(project_id)
-        .bind(start)
-        .bind(end)
-        .fetch_all(&self.pool)
+        .bind(start.with_timezone(&Utc))
+        .bind(end.with_timezone(&Utc))
+        .fetch_all(&self.pool())
         .await?;
+        let mut sessions = pomodoros_from_rows(&rows);
+        self.load_pomodoro_tags(&mut sessions).await?;
         Ok(sessions)
     }
+
+    async fn get_pomodoros_by_tag(&self, tag: &str, range: (DateTime<Local>, DateTime<Local>)) -> AppResult<Vec<PomodoroSession>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.* FROM pomodoro_records p
+            JOIN pomodoro_tags pt ON pt.pomodoro_id = p.id
+            JOIN tags t ON t.id = pt.tag_id
+            WHERE t.name = ? COLLATE NOCASE AND p.start_time >= ? AND p.start_time < ?
+            ORDER BY p.start_time DESC
+            "#,
+        )
+        .bind(normalize_tag(tag))
+        .bind(range.0.with_timezone(&Utc))
+        .bind(range.1.with_timezone(&Utc))
+        .fetch_all(&self.pool())
+        .await?;
+        let mut sessions = pomodoros_from_rows(&rows);
+        self.load_pomodoro_tags(&mut sessions).await?;
+        Ok(sessions)
+    }
+
+    async fn list_tags(&self) -> AppResult<Vec<Tag>> {
+        let rows = sqlx::query("SELECT id, name, color FROM tags ORDER BY name")
+            .fetch_all(&self.pool())
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| Tag {
+                id: row.get("id"),
+                name: row.get("name"),
+                color: row.get::<Option<String>, _>("color").unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+/// 先复制到临时文件再重命名，并删除旧数据库的 WAL 和共享内存文件，避免它们被应用到新数据库上
+fn replace_database_file(source: &Path, database_path: &Path) -> AppResult<()> {
+    let temp = database_path.with_extension("restore.tmp");
+    std::fs::copy(source, &temp)?;
+    for suffix in ["-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{}", database_path.display(), suffix));
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    std::fs::rename(&temp, database_path)?;
+    Ok(())
+}
+
+/// 删除数据库文件及其 WAL 和共享内存文件，失败只记录日志
+fn remove_database_files(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let file = PathBuf::from(format!("{}{}", path.display(), suffix));
+        if file.exists() {
+            if let Err(e) = std::fs::remove_file(&file) {
+                log::warn!("删除 {} 失败: {}", file.display(), e);
+            }
+        }
+    }
+}
+
+/// 用 `SEARCH_HIGHLIGHT` 包围 `text` 中所有不区分大小写的 `query`
+fn highlight(text: &str, query: &str) -> String {
+    let lower = text.to_lowercase();
+    let needle = query.to_lowercase();
+    // 大小写转换改变了长度时无法对应位置，原样返回
+    if needle.is_empty() || lower.len() != text.len() {
+        return text.to_string();
+    }
+    let mut output = String::with_capacity(text.len() + 8);
+    let mut last = 0;
+    for (start, _) in lower.match_indices(&needle) {
+        let end = start + needle.len();
+        output.push_str(&text[last..start]);
+        output.push_str(SEARCH_HIGHLIGHT);
+        output.push_str(&text[start..end]);
+        output.push_str(SEARCH_HIGHLIGHT);
+        last = end;
+    }
+    output.push_str(&text[last..]);
+    output
+}
+
+async fn open_pool(database_path: &Path, settings: &StorageSettings) -> AppResult<Pool<Sqlite>> {
+    let synchronous = match settings.synchronous {
+        Synchronous::Off => SqliteSynchronous::Off,
+        Synchronous::Normal => SqliteSynchronous::Normal,
+        Synchronous::Full => SqliteSynchronous::Full,
+    };
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(database_path)
+                .create_if_missing(true)
+                .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+                .busy_timeout(settings.busy_timeout)
+                .synchronous(synchronous)
+                .foreign_keys(true),
+        )
+        .await?;
+
+    // 运行迁移
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+/// 以只读方式打开数据库文件并执行 `PRAGMA <pragma>`，结果不是 "ok" 或无法打开时返回错误
+async fn check_database_file(path: &Path, pragma: &str) -> AppResult<()> {
+    use sqlx::{ConnectOptions, Connection};
+
+    let corrupt = |detail: String| AppError::System(format!("备份 {} 校验失败: {}", path.display(), detail));
+    let mut conn = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .map_err(|e| corrupt(e.to_string()))?;
+    let result: Result<Vec<String>, sqlx::Error> = sqlx::query_scalar(&format!("PRAGMA {}", pragma))
+        .fetch_all(&mut conn)
+        .await;
+    let _ = conn.close().await;
+    match result {
+        Ok(rows) if rows == ["ok"] => Ok(()),
+        Ok(rows) => Err(corrupt(rows.join("; "))),
+        Err(e) => Err(corrupt(e.to_string())),
+    }
+}
+
+/// 备份目录中的明文（`.db`）和加密（`.db.enc`）备份，不含增量备份
+fn is_backup_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name == INCREMENTAL_BACKUP_FILE || !path.is_file() {
+        return false;
+    }
+    name.ends_with(".db") || name.ends_with(&format!(".{}", ENCRYPTED_BACKUP_EXTENSION))
 }
 
 #[derive(sqlx::FromRow)]
 struct ConfigRow {
     id: i64,
     data: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{GoalSchedule, PomodoroStatus, MANUAL_APP_NAME};
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_connection_pragmas() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.busy_timeout = Duration::from_millis(2500);
+        settings.synchronous = Synchronous::Full;
+
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(busy_timeout, 2500);
+
+        // FULL = 2
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(synchronous, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_creates_safety_backup() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = dir.path().join("backups").to_string_lossy().into_owned();
+        settings.backup_before_destructive = true;
+
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        let (_, backup_path) = storage.cleanup_old_data(&RetentionPolicy::uniform(30), false).await?;
+
+        let backup_path = backup_path.expect("safety backup should be created");
+        assert!(backup_path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_backup_roundtrip() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = dir.path().join("backups").to_string_lossy().into_owned();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        storage.save_config(&AppConfig::default()).await?;
+
+        let backup = storage.backup_encrypted("hunter2").await?;
+        assert!(backup.to_string_lossy().ends_with(".db.enc"));
+        // 备份目录中不留明文副本
+        let files = std::fs::read_dir(dir.path().join("backups"))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(files, vec![backup.clone()]);
+        assert!(!std::fs::read(&backup)?.starts_with(b"SQLite"));
+        assert!(storage.backup_encrypted("").await.is_err());
+
+        let restored = dir.path().join("restored.db");
+        assert!(SqliteStorage::restore_encrypted(&backup, "wrong", &restored).await.is_err());
+        assert!(!restored.exists());
+        SqliteStorage::restore_encrypted(&backup, "hunter2", &restored).await?;
+        let restored = SqliteStorage::with_settings(&restored, &settings).await?;
+        assert_eq!(restored.get_config().await?, Some(AppConfig::default()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_backup_fails_verification() -> AppResult<()> {
+        let dir = tempdir()?;
+        let backups = dir.path().join("backups");
+        std::fs::create_dir_all(&backups)?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = backups.to_string_lossy().into_owned();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        storage.save_config(&AppConfig::default()).await?;
+
+        let backup = backups.join("backup-20240101-000000.db");
+        storage.backup(&backup).await?;
+        SqliteStorage::verify_backup(&backup).await?;
+        let health = storage.check_health().await?;
+        assert!(health.is_healthy);
+        assert!(health.last_backup.is_some());
+        assert!(health.last_backup_verified);
+
+        // 保留文件头，覆盖之后的页
+        let mut data = std::fs::read(&backup)?;
+        assert!(data.len() > 4096);
+        for byte in &mut data[4096..] {
+            *byte = 0xA5;
+        }
+        std::fs::write(&backup, data)?;
+
+        assert!(matches!(SqliteStorage::verify_backup(&backup).await, Err(AppError::System(_))));
+        assert!(!storage.check_health().await?.last_backup_verified);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_from_backup() -> AppResult<()> {
+        let dir = tempdir()?;
+        let backups = dir.path().join("backups");
+        std::fs::create_dir_all(&backups)?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = backups.to_string_lossy().into_owned();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let mut config = AppConfig::default();
+        config.ui.theme = "dark".into();
+        storage.save_config(&config).await?;
+        let backup = backups.join("backup-20240101-000000.db");
+        storage.backup(&backup).await?;
+
+        config.ui.theme = "light".into();
+        storage.save_config(&config).await?;
+        storage.restore_from_backup(&backup).await?;
+
+        // 恢复后连接池可继续使用，数据为备份时的状态
+        assert_eq!(storage.get_config().await?.unwrap().ui.theme, "dark");
+        let listed = storage.list_backups()?;
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|info| info.size > 0 && !info.encrypted));
+        let safety = listed.iter().find(|info| info.path != backup).unwrap();
+        assert!(safety.path.file_name().unwrap().to_string_lossy().starts_with("safety-"));
+
+        // 损坏的备份不会替换当前数据
+        std::fs::write(&backup, b"not a database")?;
+        assert!(storage.restore_from_backup(&backup).await.is_err());
+        assert_eq!(storage.get_config().await?.unwrap().ui.theme, "dark");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_old_backups_includes_encrypted() -> AppResult<()> {
+        let dir = tempdir()?;
+        let backups = dir.path().join("backups");
+        std::fs::create_dir_all(&backups)?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = backups.to_string_lossy().into_owned();
+        settings.backup_retention_days = 30;
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let old = std::time::SystemTime::now() - Duration::from_secs(40 * 24 * 3600);
+        for name in ["safety-1.db", "backup-1.db.enc", INCREMENTAL_BACKUP_FILE, "notes.txt", "backup-2.db.enc"] {
+            let file = std::fs::File::create(backups.join(name))?;
+            if name != "backup-2.db.enc" {
+                file.set_modified(old)?;
+            }
+        }
+
+        let removed = storage.cleanup_old_backups()?;
+        assert_eq!(removed, vec![backups.join("backup-1.db.enc"), backups.join("safety-1.db")]);
+        for kept in [INCREMENTAL_BACKUP_FILE, "notes.txt", "backup-2.db.enc"] {
+            assert!(backups.join(kept).exists(), "{} should be kept", kept);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_without_safety_backup() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        assert!(storage.cleanup_old_data(&RetentionPolicy::uniform(30), false).await?.1.is_none());
+
+        Ok(())
+    }
+
+    async fn insert_usage(storage: &SqliteStorage, start_time: DateTime<Local>, count: usize) -> AppResult<()> {
+        for _ in 0..count {
+            sqlx::query("INSERT INTO app_usage (app_name, window_title, start_time, duration) VALUES ('code', 'main.rs', ?, 60)")
+                .bind(start_time.with_timezone(&Utc))
+                .execute(&storage.pool())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn insert_pomodoros(storage: &SqliteStorage, start_time: DateTime<Local>, count: usize) -> AppResult<()> {
+        for _ in 0..count {
+            sqlx::query("INSERT INTO pomodoro_records (start_time, end_time, status) VALUES (?, ?, 'Completed')")
+                .bind(start_time.with_timezone(&Utc))
+                .bind((start_time + chrono::Duration::minutes(25)).with_timezone(&Utc))
+                .execute(&storage.pool())
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_fields_round_trip() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let mut project = Project::new("Client".into(), Some("Billing work".into()));
+        project.color = Some("#336699".into());
+        project.is_billable = true;
+        project.default_tags = vec!["invoice".into()];
+        project.default_category = Some("开发".into());
+        let id = storage.save_project(&project).await?;
+        storage.save_project(&Project::new("Archive".into(), None)).await?;
+
+        let stored = storage.get_project(id).await?;
+        assert_eq!(stored.id, Some(id));
+        assert_eq!(stored.description.as_deref(), Some("Billing work"));
+        assert_eq!(stored.color.as_deref(), Some("#336699"));
+        assert!(stored.is_billable);
+        assert_eq!(stored.default_tags, vec!["invoice".to_string()]);
+        assert_eq!(stored.default_category.as_deref(), Some("开发"));
+
+        let names: Vec<String> = storage.list_projects().await?.into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["Archive", "Client"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_deleted_project() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        let project_id = storage.save_project(&Project::new("Client".into(), None)).await?;
+        storage
+            .save_project_goal(&ProjectGoal {
+                id: None,
+                project_id,
+                period: GoalPeriod::Month,
+                target: Duration::from_secs(20 * 3600),
+            })
+            .await?;
+        storage
+            .save_goal(&Goal {
+                id: None,
+                kind: GoalKind::ProjectTime { project_id },
+                target: 3600,
+                schedule: GoalSchedule::Weekdays,
+                active: true,
+            })
+            .await?;
+        insert_pomodoros(&storage, Local::now(), 2).await?;
+        insert_usage(&storage, Local::now(), 3).await?;
+        for table in ["pomodoro_records", "app_usage"] {
+            sqlx::query(&format!("UPDATE {} SET project_id = ?", table))
+                .bind(project_id)
+                .execute(&storage.pool())
+                .await?;
+        }
+
+        let snapshot = storage.snapshot_project(project_id).await?;
+        assert_eq!(snapshot.goals.len(), 1);
+        assert_eq!(snapshot.pomodoro_ids.len(), 2);
+        assert_eq!(snapshot.activity_ids.len(), 3);
+        assert_eq!(snapshot.daily_goals.len(), 1);
+
+        storage.delete_project(project_id, true).await?;
+        assert!(storage.get_project(project_id).await.is_err());
+        assert!(storage.get_project_goal(project_id, GoalPeriod::Month).await?.is_none());
+
+        storage.restore_project(&snapshot).await?;
+        assert_eq!(storage.get_project(project_id).await?.name, "Client");
+        let restored = storage.snapshot_project(project_id).await?;
+        assert_eq!(restored.pomodoro_ids, snapshot.pomodoro_ids);
+        assert_eq!(restored.activity_ids, snapshot.activity_ids);
+        assert_eq!(restored.daily_goals, snapshot.daily_goals);
+        // 目标重新插入后 id 会变化
+        assert_eq!(restored.goals[0].target, snapshot.goals[0].target);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_project_delete_count_matches_deletions() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+        settings.bulk_delete_threshold = 5;
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let project_id = storage.save_project(&Project::new("Client".into(), None)).await?;
+        storage
+            .save_project_goal(&ProjectGoal {
+                id: None,
+                project_id,
+                period: GoalPeriod::Week,
+                target: Duration::from_secs(10 * 3600),
+            })
+            .await?;
+        storage
+            .save_goal(&Goal {
+                id: None,
+                kind: GoalKind::ProjectTime { project_id },
+                target: 3600,
+                schedule: GoalSchedule::Weekdays,
+                active: true,
+            })
+            .await?;
+        insert_pomodoros(&storage, Local::now(), 2).await?;
+        insert_usage(&storage, Local::now(), 4).await?;
+        for table in ["pomodoro_records", "app_usage"] {
+            sqlx::query(&format!("UPDATE {} SET project_id = ?", table))
+                .bind(project_id)
+                .execute(&storage.pool())
+                .await?;
+        }
+        // 没有关联项目的记录不受影响
+        insert_usage(&storage, Local::now(), 1).await?;
+
+        let impact = storage.count_project_data(project_id).await?;
+        assert_eq!((impact.app_usage, impact.pomodoros, impact.goals), (4, 2, 2));
+        match storage.delete_project(project_id, false).await {
+            Err(AppError::ConfirmationRequired(count)) => assert_eq!(count, 8),
+            other => panic!("expected ConfirmationRequired, got {:?}", other.map(|(impact, _)| impact)),
+        }
+        assert!(storage.get_project(project_id).await.is_ok());
+
+        let (deleted, _) = storage.delete_project(project_id, true).await?;
+        assert_eq!(deleted, impact);
+        let linked: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM app_usage WHERE project_id IS NOT NULL) + (SELECT COUNT(*) FROM pomodoro_records WHERE project_id IS NOT NULL)",
+        )
+        .fetch_one(&storage.pool())
+        .await?;
+        assert_eq!(linked, 0);
+        assert!(storage.list_goals().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_goal_crud() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        let project_id = storage.save_project(&Project::new("Client".into(), None)).await?;
+
+        let mut goal = Goal {
+            id: None,
+            kind: GoalKind::ProductiveTime,
+            target: 4 * 3600,
+            schedule: GoalSchedule::Weekdays,
+            active: true,
+        };
+        goal.id = Some(storage.save_goal(&goal).await?);
+        let project_goal = Goal {
+            id: None,
+            kind: GoalKind::ProjectTime { project_id },
+            target: 3600,
+            schedule: GoalSchedule::Days(vec![chrono::Weekday::Sat]),
+            active: true,
+        };
+        storage.save_goal(&project_goal).await?;
+
+        goal.target = 5 * 3600;
+        goal.active = false;
+        assert_eq!(storage.save_goal(&goal).await?, goal.id.unwrap());
+        let goals = storage.list_goals().await?;
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[0], goal);
+        assert_eq!(goals[1].kind, project_goal.kind);
+        assert_eq!(goals[1].schedule, project_goal.schedule);
+
+        // 删除项目时一并删除项目目标
+        storage.delete_project(project_id, true).await?;
+        assert_eq!(storage.list_goals().await?, vec![goal.clone()]);
+
+        storage.delete_goal(goal.id.unwrap()).await?;
+        assert!(storage.list_goals().await?.is_empty());
+        assert!(matches!(storage.save_goal(&goal).await, Err(AppError::NotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_manual_activity_requires_end_after_start() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let start_time = Local::now();
+        let activity = Activity {
+            id: None,
+            name: "Design review".into(),
+            start_time,
+            end_time: Some(start_time),
+            project_id: None,
+            description: None,
+            duration: Duration::ZERO,
+            category: "meeting".into(),
+            is_productive: true,
+            app_name: MANUAL_APP_NAME.into(),
+            window_title: "Design review".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: true,
+        };
+
+        let result = storage.add_manual_activity(activity.clone()).await;
+        assert!(matches!(result, Err(AppError::InvalidOperation(_))));
+        let open_ended = Activity { end_time: None, ..activity };
+        assert!(matches!(storage.add_manual_activity(open_ended).await, Err(AppError::InvalidOperation(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tags_use_junction_tables() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+
+        // 活动标签由触发器从 JSON 列同步
+        sqlx::query("INSERT INTO app_usage (app_name, window_title, start_time, duration, tags) VALUES ('code', 'main.rs', ?, 60, ?)")
+            .bind(Local::now())
+            .bind(r##"["#deepwork", "review"]"##)
+            .execute(&storage.pool())
+            .await?;
+        let tagged: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM activity_tags at JOIN tags t ON t.id = at.tag_id WHERE t.name = 'deepwork'",
+        )
+        .fetch_one(&storage.pool())
+        .await?;
+        assert_eq!(tagged, 1);
+
+        insert_pomodoros(&storage, Local::now(), 1).await?;
+        let mut conn = storage.pool().acquire().await?;
+        save_pomodoro_tags(&mut conn, 1, &["review".into(), " #DeepWork ".into(), String::new()]).await?;
+        drop(conn);
+
+        let mut sessions = [PomodoroSession {
+            id: Some(1),
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status: PomodoroStatus::Completed,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }];
+        storage.load_pomodoro_tags(&mut sessions).await?;
+        // 去掉空白和开头的 #，空标签被忽略，已有的 review 被复用
+        assert_eq!(sessions[0].tags, vec!["DeepWork".to_string(), "review".to_string()]);
+        let tags: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tags").fetch_one(&storage.pool()).await?;
+        assert_eq!(tags, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pomodoro_roundtrip_uses_pomodoro_records() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+
+        let start = Local::now() - chrono::Duration::minutes(30);
+        let id = storage
+            .save_pomodoro(&PomodoroSession {
+                id: None,
+                start_time: start,
+                end_time: Some(start + chrono::Duration::minutes(25)),
+                duration: Duration::from_secs(25 * 60),
+                status: PomodoroStatus::Completed,
+                project_id: None,
+                notes: Some("写文档".into()),
+                tags: vec!["writing".into()],
+                category: Some("工作".into()),
+                utc_offset: None,
+            })
+            .await?;
+
+        // 标签关联表的外键指向 pomodoro_records
+        let tagged: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pomodoro_tags pt JOIN pomodoro_records p ON p.id = pt.pomodoro_id")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(tagged, 1);
+
+        let session = storage.get_pomodoro(id).await?;
+        assert_eq!(session.status, PomodoroStatus::Completed);
+        assert_eq!(session.duration, Duration::from_secs(25 * 60));
+        assert_eq!(session.tags, vec!["writing".to_string()]);
+        assert_eq!(session.category.as_deref(), Some("工作"));
+        assert!(session.utc_offset.is_some());
+
+        let sessions = storage.get_pomodoro_sessions(start - chrono::Duration::hours(1), Local::now()).await?;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(storage.get_pomodoros_by_tag("writing", (start - chrono::Duration::hours(1), Local::now())).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_app_usage_batch_records_utc_offset() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let start = Local::now() - chrono::Duration::minutes(5);
+        storage
+            .add_app_usage_batch(&[AppUsageRecord {
+                id: 0,
+                app_name: "code".into(),
+                window_title: "main.rs".into(),
+                start_time: start,
+                duration: 60,
+                category: None,
+                project_id: None,
+            }])
+            .await?;
+
+        let offset: Option<i32> = sqlx::query_scalar("SELECT utc_offset FROM app_usage").fetch_one(&storage.pool()).await?;
+        assert_eq!(offset, Some(start.offset().local_minus_utc()));
+        // 范围边界换成 UTC 后与保存的文本可以直接比较
+        let activities = storage.get_activities(start - chrono::Duration::seconds(1), start + chrono::Duration::seconds(1)).await?;
+        assert_eq!(activities.len(), 1);
+        let impact = storage
+            .count_old_data(&RetentionPolicy {
+                app_usage_days: Some(0),
+                ..RetentionPolicy::default()
+            })
+            .await?;
+        assert_eq!(impact.app_usage, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_count_matches_deletions() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+        settings.bulk_delete_threshold = 5;
+
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        let old = Local::now() - chrono::Duration::days(90);
+        insert_usage(&storage, old, 4).await?;
+        insert_usage(&storage, Local::now(), 3).await?;
+        insert_pomodoros(&storage, old, 3).await?;
+        insert_pomodoros(&storage, Local::now(), 2).await?;
+
+        let impact = storage.count_old_data(&RetentionPolicy::uniform(30)).await?;
+        assert_eq!(impact, DeletionImpact { app_usage: 4, pomodoros: 3, summaries: 0 });
+
+        // 超过阈值时未确认不会删除任何记录
+        match storage.cleanup_old_data(&RetentionPolicy::uniform(30), false).await {
+            Err(AppError::ConfirmationRequired(count)) => assert_eq!(count, 7),
+            other => panic!("expected confirmation to be required, got {:?}", other.map(|r| r.0)),
+        }
+        assert_eq!(storage.count_old_data(&RetentionPolicy::uniform(30)).await?, impact);
+
+        let (deleted, _) = storage.cleanup_old_data(&RetentionPolicy::uniform(30), true).await?;
+        assert_eq!(deleted, impact);
+        assert_eq!(storage.count_old_data(&RetentionPolicy::uniform(30)).await?.total(), 0);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_usage")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(remaining, 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_daily_summaries() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        insert_usage(&storage, Local::now(), 3).await?;
+        insert_pomodoros(&storage, Local::now(), 2).await?;
+
+        let range = (Local::now() - chrono::Duration::days(7), Local::now());
+        assert_eq!(storage.rebuild_daily_summaries(range).await?, 1);
+        // 原始记录没有变化时不重新计算
+        assert_eq!(storage.rebuild_daily_summaries(range).await?, 0);
+
+        let (total, completed, app): (i64, i64, String) = sqlx::query_as(
+            "SELECT total_work_time, completed_pomodoros, most_used_app FROM daily_summaries",
+        )
+        .fetch_one(&storage.pool())
+        .await?;
+        assert_eq!((total, completed, app.as_str()), (180, 2, "code"));
+        storage.wal_checkpoint().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_daily_summaries_counts_productive_time() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let start_time = Local::now() - chrono::Duration::minutes(30);
+        storage
+            .save_activity(&Activity {
+                id: None,
+                name: "main.rs".into(),
+                start_time,
+                end_time: Some(start_time + chrono::Duration::minutes(10)),
+                project_id: None,
+                description: None,
+                duration: Duration::from_secs(600),
+                category: "development".into(),
+                is_productive: true,
+                app_name: "code".into(),
+                window_title: "main.rs".into(),
+                is_billable: None,
+                tags: Vec::new(),
+                utc_offset: None,
+                manual: false,
+            })
+            .await?;
+
+        storage
+            .rebuild_daily_summaries((start_time - chrono::Duration::days(1), Local::now()))
+            .await?;
+        let (total, productive): (i64, i64) =
+            sqlx::query_as("SELECT total_work_time, productive_time FROM daily_summaries")
+                .fetch_one(&storage.pool())
+                .await?;
+        assert_eq!((total, productive), (600, 600));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_truncates_wal() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        insert_usage(&storage, Local::now(), 50).await?;
+
+        let before = storage.disk_footprint();
+        assert!(before.wal > 0);
+        assert_eq!(before.total(), before.database + before.wal + before.shm);
+
+        let result = storage.checkpoint(CheckpointMode::Truncate).await?;
+        assert!(!result.busy);
+        assert_eq!(storage.disk_footprint().wal, 0);
+
+        let health = storage.check_health().await?;
+        assert_eq!(health.wal_size, 0);
+        assert_eq!(health.disk_usage, storage.disk_footprint().total());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_titles_and_notes() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let now = Local::now();
+        for (title, start) in [
+            ("Quarterly Report.pdf - Preview", now - chrono::Duration::hours(2)),
+            ("main.rs - time_tracker", now - chrono::Duration::hours(1)),
+            ("季度报告.docx", now - chrono::Duration::days(10)),
+        ] {
+            sqlx::query("INSERT INTO app_usage (app_name, window_title, start_time, duration) VALUES ('viewer', ?, ?, 60)")
+                .bind(title)
+                .bind(start)
+                .execute(&storage.pool())
+                .await?;
+        }
+        sqlx::query("INSERT INTO pomodoro_records (start_time, end_time, status, notes) VALUES (?, ?, 'Completed', ?)")
+            .bind(now - chrono::Duration::minutes(30))
+            .bind(now - chrono::Duration::minutes(5))
+            .bind("finish the quarterly report")
+            .execute(&storage.pool())
+            .await?;
+
+        let all_time = (now - chrono::Duration::days(30), now);
+        let hits = storage.search("quarterly", all_time).await?;
+        assert_eq!(hits.len(), 2);
+        // 最近的在前
+        assert_eq!(hits[0].kind, SearchKind::Pomodoro);
+        assert_eq!(hits[0].app_name, None);
+        assert!(hits[0].snippet.contains("**quarterly**"));
+        assert_eq!(hits[1].kind, SearchKind::Activity);
+        assert_eq!(hits[1].app_name.as_deref(), Some("viewer"));
+        assert!(hits[1].snippet.contains("**Quarterly**"));
+
+        // 少于三个字符的查询
+        let hits = storage.search("报告", all_time).await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].snippet, "季度**报告**.docx");
+        assert!(storage.search("报告", (now - chrono::Duration::days(1), now)).await?.is_empty());
+
+        // 修改和删除后索引同步
+        sqlx::query("UPDATE app_usage SET window_title = 'notes.txt' WHERE window_title LIKE 'main.rs%'")
+            .execute(&storage.pool())
+            .await?;
+        assert!(storage.search("main.rs", all_time).await?.is_empty());
+        assert_eq!(storage.search("notes.txt", all_time).await?.len(), 1);
+        sqlx::query("DELETE FROM pomodoro_records").execute(&storage.pool()).await?;
+        assert_eq!(storage.search("quarterly", all_time).await?.len(), 1);
+
+        assert!(storage.search("  ", all_time).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_daily_summaries_only_dirty_days() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let yesterday = Local::now() - chrono::Duration::days(1);
+        insert_usage(&storage, yesterday, 2).await?;
+        insert_usage(&storage, Local::now(), 1).await?;
+        let range = (Local::now() - chrono::Duration::days(7), Local::now());
+        assert_eq!(storage.rebuild_daily_summaries(range).await?, 2);
+
+        // 只有新增记录的当天被重新计算
+        sqlx::query("INSERT INTO app_usage (app_name, window_title, start_time, duration) VALUES ('browser', 'docs', ?, 600)")
+            .bind(Local::now())
+            .execute(&storage.pool())
+            .await?;
+        assert_eq!(storage.rebuild_daily_summaries(range).await?, 1);
+        let (total, app): (i64, String) = sqlx::query_as(
+            "SELECT total_work_time, most_used_app FROM daily_summaries WHERE date = ?",
+        )
+        .bind(Local::now().date_naive().to_string())
+        .fetch_one(&storage.pool())
+        .await?;
+        assert_eq!((total, app.as_str()), (660, "browser"));
+
+        // 当天记录全部删除后汇总也被删除
+        sqlx::query("DELETE FROM app_usage WHERE start_time < ?")
+            .bind(Local::now() - chrono::Duration::hours(12))
+            .execute(&storage.pool())
+            .await?;
+        storage.rebuild_daily_summaries(range).await?;
+        let dates: Vec<String> = sqlx::query_scalar("SELECT date FROM daily_summaries")
+            .fetch_all(&storage.pool())
+            .await?;
+        assert_eq!(dates, vec![Local::now().date_naive().to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cap_session_durations() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        for duration in [600, 5 * 3600, 9 * 3600] {
+            sqlx::query("INSERT INTO app_usage (app_name, window_title, start_time, duration) VALUES ('code', 'main.rs', ?, ?)")
+                .bind(Local::now())
+                .bind(duration)
+                .execute(&storage.pool())
+                .await?;
+        }
+
+        let (count, trimmed) = storage.cap_session_durations(std::time::Duration::from_secs(4 * 3600)).await?;
+        assert_eq!((count, trimmed), (2, std::time::Duration::from_secs(6 * 3600)));
+        let durations: Vec<i64> = sqlx::query_scalar("SELECT duration FROM app_usage ORDER BY id")
+            .fetch_all(&storage.pool())
+            .await?;
+        assert_eq!(durations, vec![600, 4 * 3600, 4 * 3600]);
+
+        // 再次执行没有可截断的记录
+        assert_eq!(storage.cap_session_durations(std::time::Duration::from_secs(4 * 3600)).await?.0, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_daily_usage_aggregates_in_sql() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let yesterday = Local::now() - chrono::Duration::days(1);
+        insert_usage(&storage, yesterday, 2).await?;
+        insert_usage(&storage, Local::now(), 3).await?;
+        insert_pomodoros(&storage, Local::now(), 2).await?;
+        sqlx::query("UPDATE app_usage SET is_productive = 1 WHERE start_time >= ?")
+            .bind((Local::now() - chrono::Duration::hours(1)).with_timezone(&Utc))
+            .execute(&storage.pool())
+            .await?;
+
+        let days = storage
+            .get_daily_usage((Local::now() - chrono::Duration::days(7), Local::now() + chrono::Duration::hours(1)))
+            .await?;
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, yesterday.date_naive());
+        assert_eq!(days[0].total_time, Duration::from_secs(120));
+        assert_eq!(days[1].total_time, Duration::from_secs(180));
+        assert_eq!(days[0].productive_time, Duration::ZERO);
+        assert_eq!(days[1].productive_time, Duration::from_secs(180));
+        assert_eq!(days[1].completed_pomodoros, 2);
+        assert_eq!(days[1].focus_time, Duration::from_secs(2 * 25 * 60));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_busy_timeout_validation() {
+        let mut settings = AppConfig::default().storage;
+        settings.busy_timeout = Duration::ZERO;
+        assert!(settings.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_incremental_backup_copies_only_changed_pages() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = dir.path().join("backups").to_string_lossy().into_owned();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+        insert_usage(&storage, Local::now(), 2000).await?;
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let record = |p: BackupProgress| progress.lock().unwrap().push(p);
+
+        // 第一次没有基准，完整复制
+        let path = storage.backup_incremental_with_progress(&record).await?;
+        let full = *progress.lock().unwrap().last().unwrap();
+        assert_eq!(full.pages_done, full.pages_total);
+        assert_eq!(full.pages_copied, full.pages_total);
+
+        // 没有变化时不写入任何页面
+        progress.lock().unwrap().clear();
+        storage.backup_incremental_with_progress(&record).await?;
+        assert_eq!(progress.lock().unwrap().last().unwrap().pages_copied, 0);
+
+        // 少量写入只复制变化的批次
+        insert_usage(&storage, Local::now(), 1).await?;
+        progress.lock().unwrap().clear();
+        storage.backup_incremental_with_progress(&record).await?;
+        let incremental = *progress.lock().unwrap().last().unwrap();
+        assert!(incremental.pages_copied > 0);
+        assert!(incremental.pages_copied <= incremental.pages_total);
+
+        // 备份文件是可以打开的完整数据库
+        let backup = SqliteStorage::with_settings(&path, &AppConfig::default().storage).await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_usage")
+            .fetch_one(&backup.pool())
+            .await?;
+        assert_eq!(count, 2001);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_relative_backup_path_resolves_under_data_dir() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = "backups".into();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let path = storage.backup_incremental().await?;
+        assert_eq!(path, dir.path().join("backups").join(INCREMENTAL_BACKUP_FILE));
+        storage.backup(storage.backup_dir().join("backup-20240101-000000.db")).await?;
+        assert_eq!(storage.list_backups()?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_backup_falls_back_when_schema_changes() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_path = dir.path().join("backups").to_string_lossy().into_owned();
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let path = storage.backup_incremental().await?;
+        let mut watermark = BackupWatermark::load(&path).unwrap();
+        watermark.schema_version -= 1;
+        std::fs::write(BackupWatermark::path(&path), serde_json::to_vec(&watermark)?)?;
+
+        let progress = std::sync::Mutex::new(BackupProgress::default());
+        storage
+            .backup_incremental_with_progress(|p| *progress.lock().unwrap() = p)
+            .await?;
+        let last = *progress.lock().unwrap();
+        assert_eq!(last.pages_copied, last.pages_total);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_app_usage_pages_with_cursor() -> AppResult<()> {
+        let dir = tempdir()?;
+        let storage = SqliteStorage::new(dir.path().join("test.db")).await?;
+        let now = Local::now();
+        insert_usage(&storage, now - chrono::Duration::days(40), 3).await?;
+        // 相同的开始时间依靠 id 区分，不能重复或遗漏
+        insert_usage(&storage, now - chrono::Duration::hours(2), 25).await?;
+
+        let mut stream = storage
+            .stream_app_usage((now - chrono::Duration::days(1), now), 10)
+            .await?;
+        let mut sizes = Vec::new();
+        let mut ids = Vec::new();
+        while let Some(batch) = stream.next_batch().await? {
+            sizes.push(batch.len());
+            ids.extend(batch.iter().map(|record| record.id));
+        }
+
+        assert_eq!(sizes, vec![10, 10, 5]);
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(ids, sorted);
+        assert_eq!(ids.len(), 25);
+        assert!(stream.next_batch().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_uses_per_type_retention() -> AppResult<()> {
+        let dir = tempdir()?;
+        let mut settings = AppConfig::default().storage;
+        settings.backup_before_destructive = false;
+        let storage = SqliteStorage::with_settings(dir.path().join("test.db"), &settings).await?;
+
+        let old = Local::now() - chrono::Duration::days(60);
+        insert_usage(&storage, old, 4).await?;
+        insert_pomodoros(&storage, old, 2).await?;
+        insert_usage(&storage, Local::now() - chrono::Duration::days(120), 1).await?;
+        storage
+            .rebuild_daily_summaries((Local::now() - chrono::Duration::days(200), Local::now()))
+            .await?;
+
+        // 只清理原始窗口记录，番茄钟永久保留
+        let policy = RetentionPolicy {
+            app_usage_days: Some(7),
+            pomodoro_days: None,
+            summaries_days: Some(30),
+        };
+        let (deleted, _) = storage.cleanup_old_data(&policy, true).await?;
+        // 60 天前的汇总当天仍有番茄钟记录，被保留；120 天前的只有窗口记录，随之删除
+        assert_eq!(deleted, DeletionImpact { app_usage: 5, pomodoros: 0, summaries: 1 });
+
+        let pomodoros: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pomodoro_records")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(pomodoros, 2);
+        let summaries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM daily_summaries")
+            .fetch_one(&storage.pool())
+            .await?;
+        assert_eq!(summaries, 1);
+        assert_eq!(storage.count_old_data(&policy).await?, DeletionImpact::default());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_app_usage_batch_is_faster_than_single_inserts() -> AppResult<()> {
+        let dir = tempdir()?;
+        let start = Local::now() - chrono::Duration::days(1);
+        let records: Vec<AppUsageRecord> = (0..10_000)
+            .map(|i| AppUsageRecord {
+                id: 0,
+                app_name: format!("app{}", i % 7),
+                window_title: format!("window {}", i),
+                start_time: start + chrono::Duration::seconds(i * 30),
+                duration: 30,
+                category: Some(if i % 2 == 0 { "development" } else { "communication" }.into()),
+                project_id: None,
+            })
+            .collect();
+
+        let single = SqliteStorage::new(dir.path().join("single.db")).await?;
+        let started = std::time::Instant::now();
+        for record in &records {
+            single.add_app_usage_batch(std::slice::from_ref(record)).await?;
+        }
+        let single_elapsed = started.elapsed();
+
+        let batched = SqliteStorage::new(dir.path().join("batched.db")).await?;
+        let started = std::time::Instant::now();
+        let ids = batched.add_app_usage_batch(&records).await?;
+        let batch_elapsed = started.elapsed();
+
+        assert_eq!(ids.len(), records.len());
+        let count = |storage: &SqliteStorage| {
+            let pool = storage.pool();
+            async move {
+                let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_usage").fetch_one(&pool).await?;
+                let categories: i64 = sqlx::query_scalar("SELECT COUNT(DISTINCT category_id) FROM app_usage")
+                    .fetch_one(&pool)
+                    .await?;
+                AppResult::Ok((rows, categories))
+            }
+        };
+        assert_eq!(count(&single).await?, (10_000, 2));
+        assert_eq!(count(&batched).await?, count(&single).await?);
+        assert!(
+            batch_elapsed * 5 < single_elapsed,
+            "batch {:?} vs single {:?}",
+            batch_elapsed,
+            single_elapsed
+        );
+        Ok(())
+    }
+}
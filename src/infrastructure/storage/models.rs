@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use sqlx::FromRow;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -38,5 +39,61 @@ pub struct StorageHealth {
     pub app_usage_count: u64,
     pub pomodoro_count: u64,
     pub last_backup: Option<DateTime<Local>>,
+    /// 最近一次备份通过了完整性检查
+    pub last_backup_verified: bool,
     pub needs_vacuum: bool,
-}
\ No newline at end of file
+    pub wal_size: u64,
+    /// 数据库、WAL 和共享内存文件的总大小
+    pub disk_usage: u64,
+}
+/// 备份目录中的一个备份文件，创建时间取文件修改时间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub created_at: DateTime<Local>,
+    pub size: u64,
+    pub encrypted: bool,
+}
+
+/// `PRAGMA wal_checkpoint` 的模式，见 SQLite 文档
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CheckpointMode {
+    Passive,
+    Full,
+    Restart,
+    #[default]
+    Truncate,
+}
+
+impl CheckpointMode {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Full => "FULL",
+            CheckpointMode::Restart => "RESTART",
+            CheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointResult {
+    /// 有读写连接阻止检查点完成
+    pub busy: bool,
+    pub wal_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+/// 数据库在磁盘上占用的空间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskFootprint {
+    pub database: u64,
+    pub wal: u64,
+    pub shm: u64,
+}
+
+impl DiskFootprint {
+    pub fn total(&self) -> u64 {
+        self.database + self.wal + self.shm
+    }
+}
@@ -1,19 +1,93 @@
 // src/storage/queries.rs
 
-use chrono::{DateTime, Local};
+pub use crate::core::models::AppUsageRecord;
+use crate::core::traits::AppUsageBatches;
+use crate::core::AppResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::pool::PoolConnection;
+use sqlx::{FromRow, Row, Sqlite};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppUsageRecord {
-    pub id: i64,
-    pub app_name: String,
-    pub window_title: String,
-    #[serde(with = "chrono::serde::ts_seconds")]
-    pub start_time: DateTime<Local>,
-    pub duration: i64,
-    pub category: Option<String>,
-    pub project_id: Option<i64>,
+/// 按 `start_time` 升序分批读取 app_usage，用 (start_time, id) 作为游标而不是 OFFSET，
+/// 读取位置靠后时也不需要扫描前面的行
+pub struct AppUsageStream {
+    conn: PoolConnection<Sqlite>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    batch_size: u32,
+    /// 上一批最后一行存储的原始 start_time 和 id
+    cursor: Option<(String, i64)>,
+    done: bool,
+}
+
+impl AppUsageStream {
+    pub(crate) fn new(conn: PoolConnection<Sqlite>, start: DateTime<Local>, end: DateTime<Local>, batch_size: u32) -> Self {
+        Self {
+            conn,
+            start,
+            end,
+            batch_size: batch_size.max(1),
+            cursor: None,
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl AppUsageBatches for AppUsageStream {
+    async fn next_batch(&mut self) -> AppResult<Option<Vec<AppUsageRecord>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        // 游标使用数据库中的原始文本比较，避免时间格式化差异导致重复或遗漏
+        let (after_time, after_id) = self.cursor.clone().unwrap_or_default();
+        let rows = sqlx::query(
+            r#"
+            SELECT u.id, u.app_name, u.window_title, u.start_time, u.duration, u.project_id, c.name AS category
+            FROM app_usage u
+            LEFT JOIN categories c ON c.id = u.category_id
+            WHERE u.start_time >= ? AND u.start_time < ?
+            AND (? = 0 OR (u.start_time, u.id) > (?, ?))
+            ORDER BY u.start_time, u.id
+            LIMIT ?
+            "#,
+        )
+        .bind(self.start.with_timezone(&Utc))
+        .bind(self.end.with_timezone(&Utc))
+        .bind(self.cursor.is_some())
+        .bind(&after_time)
+        .bind(after_id)
+        .bind(self.batch_size)
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        if rows.len() < self.batch_size as usize {
+            self.done = true;
+        }
+        if let Some(last) = rows.last() {
+            self.cursor = Some((last.try_get("start_time")?, last.try_get("id")?));
+        } else {
+            return Ok(None);
+        }
+
+        rows.iter()
+            .map(|row| {
+                Ok(AppUsageRecord {
+                    id: row.try_get("id")?,
+                    app_name: row.try_get("app_name")?,
+                    window_title: row.try_get("window_title")?,
+                    start_time: row.try_get("start_time")?,
+                    duration: row.try_get("duration")?,
+                    category: row.try_get("category")?,
+                    project_id: row.try_get("project_id")?,
+                })
+            })
+            .collect::<sqlx::Result<Vec<_>>>()
+            .map(Some)
+            .map_err(Into::into)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,121 @@
+use super::DeletionImpact;
+use crate::core::AppResult;
+use crate::domain::config::RetentionPolicy;
+use chrono::{DateTime, Local, Utc};
+use sqlx::SqliteConnection;
+
+/// 按记录时的 UTC 偏移划分日期，与 summary_dirty 触发器一致
+pub(super) const DAY_EXPR: &str = "CASE WHEN utc_offset IS NULL THEN date(start_time, 'localtime') \
+                        ELSE date(start_time, printf('%+d seconds', utc_offset)) END";
+
+fn cutoff(days: Option<u32>, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    days.map(|days| now - chrono::Duration::days(days as i64))
+}
+
+/// 原始记录的 start_time 以 UTC 保存，按文本比较前需要换成相同的时区
+fn utc_cutoff(days: Option<u32>, now: DateTime<Local>) -> Option<DateTime<Utc>> {
+    cutoff(days, now).map(|at| at.with_timezone(&Utc))
+}
+
+/// 过期且对应日期已没有保留中的原始记录的汇总，两张原始表任意一张仍有当天记录时保留
+fn summaries_where() -> String {
+    format!(
+        "date < ? AND date NOT IN (
+            SELECT {day} FROM app_usage WHERE ? IS NULL OR start_time >= ?
+            UNION
+            SELECT {day} FROM pomodoro_records WHERE ? IS NULL OR start_time >= ?
+        )",
+        day = DAY_EXPR
+    )
+}
+
+struct Cutoffs {
+    app_usage: Option<DateTime<Utc>>,
+    pomodoros: Option<DateTime<Utc>>,
+    summaries: Option<String>,
+}
+
+impl Cutoffs {
+    fn new(policy: &RetentionPolicy, now: DateTime<Local>) -> Self {
+        Self {
+            app_usage: utc_cutoff(policy.app_usage_days, now),
+            pomodoros: utc_cutoff(policy.pomodoro_days, now),
+            summaries: cutoff(policy.summaries_days, now).map(|at| at.format("%Y-%m-%d").to_string()),
+        }
+    }
+}
+
+pub(super) async fn count(conn: &mut SqliteConnection, policy: &RetentionPolicy, now: DateTime<Local>) -> AppResult<DeletionImpact> {
+    let cutoffs = Cutoffs::new(policy, now);
+    let mut impact = DeletionImpact::default();
+
+    if let Some(cutoff) = cutoffs.app_usage {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_usage WHERE start_time < ?")
+            .bind(cutoff)
+            .fetch_one(&mut *conn)
+            .await?;
+        impact.app_usage = count as u64;
+    }
+    if let Some(cutoff) = cutoffs.pomodoros {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pomodoro_records WHERE start_time < ?")
+            .bind(cutoff)
+            .fetch_one(&mut *conn)
+            .await?;
+        impact.pomodoros = count as u64;
+    }
+    if let Some(cutoff) = &cutoffs.summaries {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM daily_summaries WHERE {}", summaries_where()))
+            .bind(cutoff)
+            .bind(cutoffs.app_usage)
+            .bind(cutoffs.app_usage)
+            .bind(cutoffs.pomodoros)
+            .bind(cutoffs.pomodoros)
+            .fetch_one(&mut *conn)
+            .await?;
+        impact.summaries = count as u64;
+    }
+    Ok(impact)
+}
+
+pub(super) async fn delete(conn: &mut SqliteConnection, policy: &RetentionPolicy, now: DateTime<Local>) -> AppResult<DeletionImpact> {
+    let cutoffs = Cutoffs::new(policy, now);
+    let mut impact = DeletionImpact::default();
+
+    // 清理是为了节省空间，已有的汇总应保持原样，不因原始记录被删除而重算
+    sqlx::query("CREATE TEMP TABLE retention_dirty AS SELECT date FROM summary_dirty")
+        .execute(&mut *conn)
+        .await?;
+
+    if let Some(cutoff) = cutoffs.app_usage {
+        impact.app_usage = sqlx::query("DELETE FROM app_usage WHERE start_time < ?")
+            .bind(cutoff)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+    if let Some(cutoff) = cutoffs.pomodoros {
+        impact.pomodoros = sqlx::query("DELETE FROM pomodoro_records WHERE start_time < ?")
+            .bind(cutoff)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+
+    sqlx::query("DELETE FROM summary_dirty WHERE date NOT IN (SELECT date FROM temp.retention_dirty)")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DROP TABLE temp.retention_dirty").execute(&mut *conn).await?;
+
+    if let Some(cutoff) = &cutoffs.summaries {
+        impact.summaries = sqlx::query(&format!("DELETE FROM daily_summaries WHERE {}", summaries_where()))
+            .bind(cutoff)
+            .bind(cutoffs.app_usage)
+            .bind(cutoffs.app_usage)
+            .bind(cutoffs.pomodoros)
+            .bind(cutoffs.pomodoros)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+    Ok(impact)
+}
@@ -4,6 +4,7 @@ pub mod application;
 pub mod infrastructure;
 pub mod presentation;
 pub mod plugins;
+pub mod cli;
 
 pub use core::error::{AppError, AppResult};
 pub use core::models::*;
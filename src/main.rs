@@ -1,10 +1,40 @@
+use clap::Parser;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+use time_tracker::cli::{self, Cli, GuiCommandHandler, IpcCommand};
 use time_tracker::core::AppResult;
-use time_tracker::infrastructure::storage::Storage;
+use time_tracker::domain::activity::{persist_records, ActivityManager};
+use time_tracker::domain::config::{AppConfig, ConfigManager, ConfigManagerImpl};
+use time_tracker::domain::goal::{DailyGoalWatcher, GOAL_CHECK_INTERVAL};
+use time_tracker::domain::focus::FocusGuard;
+use time_tracker::domain::pomodoro::PomodoroManager;
+use time_tracker::domain::power::PowerEventHandler;
+use time_tracker::application::events::EventBus;
+use time_tracker::core::models::Activity;
+use time_tracker::infrastructure::crash::{
+    activity_flush_hook, pomodoro_flush_hook, take_recovery_file, CrashReporter, PENDING_ACTIVITY_FILE,
+    POMODORO_SNAPSHOT_FILE,
+};
+use time_tracker::infrastructure::maintenance::{MaintenanceScheduler, MAINTENANCE_POLL_INTERVAL};
+use time_tracker::infrastructure::instance::{AcquireOutcome, InstanceLock, DEFAULT_STALE_AFTER};
+use time_tracker::infrastructure::{ipc, platform};
+use time_tracker::infrastructure::storage::{migrate_legacy_if_present, SqliteStorage, Storage};
+use time_tracker::plugins::builtin::{
+    BackupPlugin, BackupScheduler, NotificationPlugin, StatsPlugin, WebhookPlugin, BACKUP_POLL_INTERVAL,
+};
+use time_tracker::plugins::loader::DEFAULT_PLUGIN_DIR;
+use time_tracker::plugins::traits::{NotificationPlugin as NotificationSender, Plugin};
+use time_tracker::plugins::PluginRegistry;
+use time_tracker::presentation::window::startup_visibility;
+use time_tracker::presentation::TrayManager;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
     // 初始化日志
     env_logger::init();
+    let args = Cli::parse();
 
     // 获取数据目录
     let data_dir = dirs::data_dir()
@@ -14,11 +44,255 @@ async fn main() -> AppResult<()> {
     // 确保数据目录存在
     std::fs::create_dir_all(&data_dir)?;
 
-    // 初始化存储
     let database_path = data_dir.join("timetracker.db");
-    Storage::initialize(database_path).await?;
 
-    // TODO: 初始化其他组件并启动应用程序
+    if let Some(command) = args.command {
+        // 界面进程在运行时由它执行，避免两个进程各自维护番茄钟状态
+        if let Some(info) = InstanceLock::running(&data_dir, DEFAULT_STALE_AFTER) {
+            log::info!("转发命令到运行中的实例 (pid {})", info.pid);
+            println!("{}", ipc::send(&data_dir, &IpcCommand::from(command)).await?);
+            return Ok(());
+        }
+        // 没有界面进程时直接访问数据库
+        migrate_legacy_if_present(&data_dir, &database_path).await?;
+        let (storage, _) = open_storage(&database_path).await?;
+        return cli::run(command, storage, &data_dir).await;
+    }
+
+    // 首次启动时迁移旧版数据库
+    migrate_legacy_if_present(&data_dir, &database_path).await?;
+
+    // 单实例、托盘和追踪等设置都来自保存的配置，因此先打开数据库
+    let (sqlite, config) = open_storage(&database_path).await?;
+
+    // 崩溃时把报告写入数据目录，下次启动时提示
+    let crash_reporter = Arc::new(CrashReporter::new(&data_dir, config.crash_report.clone()));
+    crash_reporter.install();
+
+    // 单实例检查：已有实例运行时请求其窗口置前后退出
+    let _instance_lock = if config.ui.single_instance {
+        match InstanceLock::acquire(&data_dir, DEFAULT_STALE_AFTER)? {
+            AcquireOutcome::Acquired(lock) => Some(Arc::new(lock)),
+            AcquireOutcome::AlreadyRunning(info) => {
+                log::info!("已有实例在运行 (pid {})，切换到该实例", info.pid);
+                InstanceLock::request_activation(&data_dir)?;
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
+
+    if let Some(lock) = _instance_lock.clone() {
+        let platform = platform::init().ok();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(DEFAULT_STALE_AFTER / 3);
+            loop {
+                interval.tick().await;
+                if let Err(e) = lock.heartbeat() {
+                    log::warn!("刷新实例锁失败: {}", e);
+                }
+                if lock.take_activation_request() {
+                    if let Some(platform) = &platform {
+                        let _ = platform.bring_to_front();
+                    }
+                }
+            }
+        });
+    }
+
+    let storage: Arc<dyn Storage + Send + Sync> = sqlite.clone();
+    storage.initialize().await?;
+
+    // 番茄钟事件经插件注册表分发，由内置通知插件显示系统通知
+    let (registry_events, _) = broadcast::channel(16);
+    let plugins = Arc::new(PluginRegistry::new(registry_events));
+    plugins.register_plugin("notification", Arc::new(NotificationPlugin::new()))?;
+    if let Some(settings) = config.webhook.clone() {
+        let webhook = Arc::new(WebhookPlugin::new(settings)?);
+        webhook.start().await?;
+        plugins.register_plugin("webhook", webhook)?;
+    }
+    if let Some(settings) = config.digest.clone() {
+        let stats = Arc::new(
+            StatsPlugin::new(storage.clone(), settings, &data_dir).with_notifier(Arc::new(NotificationPlugin::new())),
+        );
+        stats.start().await?;
+        plugins.register_plugin("stats", stats)?;
+    }
+    let backup = Arc::new(BackupPlugin::new(sqlite.backup_dir(), sqlite.clone()));
+    plugins.register_plugin("backup", backup.clone())?;
+    let backup_scheduler = config.storage.auto_backup_interval().map(|interval| {
+        let mut scheduler = BackupScheduler::new(backup, interval);
+        if let Ok(platform) = platform::init() {
+            let platform: Arc<dyn platform::PlatformOperations + Send + Sync> = Arc::from(platform);
+            scheduler = scheduler.with_platform(platform);
+        }
+        let scheduler = Arc::new(scheduler);
+        scheduler.start(BACKUP_POLL_INTERVAL);
+        scheduler
+    });
+    // 检查点、汇总重建、保留清理和 VACUUM 在用户空闲时于同一个后台任务中执行
+    let mut maintenance = MaintenanceScheduler::new(sqlite.clone(), config.maintenance.clone());
+    if let Ok(platform) = platform::init() {
+        let platform: Arc<dyn platform::PlatformOperations + Send + Sync> = Arc::from(platform);
+        maintenance = maintenance.with_platform(platform);
+    }
+    let maintenance = Arc::new(maintenance);
+    maintenance.start(MAINTENANCE_POLL_INTERVAL);
+    let goal_watcher = Arc::new(DailyGoalWatcher::new(storage.clone()).with_notifier(Arc::new(NotificationPlugin::new())));
+    goal_watcher.start(GOAL_CHECK_INTERVAL);
+    let user_plugins = plugins.load_dir(&data_dir.join(DEFAULT_PLUGIN_DIR)).await?;
+    if !user_plugins.is_empty() {
+        log::info!("已加载插件: {}", user_plugins.join(", "));
+    }
+    let plugin_events = plugins.clone().spawn_dispatcher();
+
+    // 上次崩溃时由钩子写下、尚未保存的活动
+    match take_recovery_file::<Activity>(&data_dir.join(PENDING_ACTIVITY_FILE)) {
+        Ok(Some(activity)) => {
+            storage.save_activity(&activity).await?;
+            log::info!("已恢复崩溃前未保存的活动: {}", activity.name);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("读取崩溃前未保存的活动失败: {}", e),
+    }
+
+    // 后台读取前台窗口，结束的活动在单独的任务中批量保存，不阻塞界面
+    // 记录开关、标题规则和类别规则都从配置读取
+    let config_manager: Arc<dyn ConfigManager> = Arc::new(ConfigManagerImpl::with_config(storage.clone(), config.clone()));
+    let mut activity_manager = ActivityManager::new(storage.clone())
+        .with_idle_threshold(config.tracking.idle_threshold)
+        .with_config_manager(config_manager)
+        .await?;
+    if let Ok(platform) = platform::init() {
+        let platform: Arc<dyn platform::PlatformOperations + Send + Sync> = Arc::from(platform);
+        activity_manager = activity_manager.with_platform(platform);
+    }
+    let (tracker, records) = activity_manager.spawn(config.tracking.tracking_interval);
+    let recorder = tokio::spawn(persist_records(
+        storage.clone(),
+        records,
+        Some(plugin_events.clone()),
+        config.tracking.flush_interval,
+        config.tracking.flush_max_records,
+    ));
+
+    // 本进程持有权威的番茄钟状态，命令行请求转发到这里执行
+    let pomodoro = Arc::new(
+        PomodoroManager::new(storage.clone())
+            .with_snapshot_path(data_dir.join(POMODORO_SNAPSHOT_FILE))
+            .with_plugin_events(plugin_events),
+    );
+    pomodoro.restore_saved().await?;
+    // 界面接入前保持一个订阅，使计时任务持续运行并按时发出微休息提醒；
+    // 开启专注模式时每次计时检查当前窗口是否属于分心应用
+    let mut pomodoro_ticks = pomodoro.subscribe().await;
+    let focus_guard = config.focus.enabled.then(|| {
+        FocusGuard::new(config.focus.clone(), tracker.manager().clone(), pomodoro.clone()).with_callback(Box::new(
+            |app: &str| {
+                let message = format!("工作番茄进行中，当前正在使用 {}", app);
+                tokio::spawn(async move {
+                    let notifier = NotificationPlugin::new();
+                    if let Err(e) = NotificationSender::send_notification(&notifier, "专注提醒", &message).await {
+                        log::warn!("发送专注提醒失败: {}", e);
+                    }
+                });
+            },
+        ))
+    });
+    let tick_task = tokio::spawn(async move {
+        while pomodoro_ticks.next().await.is_some() {
+            if let Some(guard) = &focus_guard {
+                if let Err(e) = guard.check().await {
+                    log::debug!("专注检查失败: {}", e);
+                }
+            }
+        }
+    });
+
+    // 崩溃时先保存当前活动和番茄钟，报告中附上最近的事件
+    let event_bus = EventBus::default();
+    let crash_events = crash_reporter.watch(&event_bus);
+    crash_reporter.add_flush_hook("activity", activity_flush_hook(tracker.manager().clone(), &data_dir));
+    crash_reporter.add_flush_hook("pomodoro", pomodoro_flush_hook(pomodoro.clone(), &data_dir));
+
+    // 休眠时在休眠时刻结束当前活动并暂停工作番茄，唤醒后恢复
+    let (power_sender, power_events) = tokio::sync::mpsc::unbounded_channel();
+    let power_task = match platform::init().and_then(|platform| platform.watch_power_events(power_sender)) {
+        Ok(()) => {
+            let handler = PowerEventHandler::new(config.power.clone(), pomodoro.clone(), tracker.manager().clone());
+            Some(Arc::new(handler).spawn(power_events))
+        }
+        Err(e) => {
+            log::info!("无法监听系统休眠事件: {}", e);
+            None
+        }
+    };
+    if _instance_lock.is_some() {
+        ipc::serve(&data_dir, Arc::new(GuiCommandHandler::new(storage, pomodoro.clone(), &data_dir)))?;
+    }
+
+    // 托盘需在决定是否隐藏窗口之前创建
+    let tray = if config.ui.show_system_tray {
+        TrayManager::new()
+            .map_err(|e| log::warn!("系统托盘不可用: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    let visibility = startup_visibility(&config.ui, args.autostart, tray.is_some());
+    log::info!("启动窗口状态: {:?}", visibility);
+
+    let crash_reports = crash_reporter.pending_reports()?;
+    if !crash_reports.is_empty() {
+        log::warn!("上次运行异常退出，崩溃报告: {:?}", crash_reports);
+    }
+
+    // TODO: 初始化其他组件并以 `Window::with_visibility(visibility)` 启动应用程序，
+    // 通过 `set_crash_reports(crash_reports)` 提示用户查看崩溃报告
+
+    // 界面事件循环接入前在此等待退出信号，期间追踪、番茄钟和 IPC 服务在后台运行
+    tokio::signal::ctrl_c().await?;
+    log::info!("收到退出信号，正在停止后台任务");
+
+    if let Some(scheduler) = backup_scheduler {
+        scheduler.stop();
+    }
+    goal_watcher.stop();
+    maintenance.stop();
+    // 专注检查持有活动管理器，等计时任务结束后记录通道才能关闭
+    tick_task.abort();
+    let _ = tick_task.await;
+    crash_reporter.clear_flush_hooks();
+    crash_events.abort();
+    // 处理任务持有活动管理器，等它结束后记录通道才能关闭
+    if let Some(task) = power_task {
+        task.abort();
+        let _ = task.await;
+    }
+    tracker.stop().await?;
+    drop(tracker);
+    let _ = recorder.await;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// 读取保存的配置，数据库中还没有配置时使用默认值。保存的存储设置与默认值不同时，
+/// 按保存的设置重新打开数据库，使 busy_timeout、synchronous 和备份目录生效
+async fn open_storage(database_path: &Path) -> AppResult<(Arc<SqliteStorage>, AppConfig)> {
+    let storage = SqliteStorage::new(database_path).await?;
+    let config = match storage.get_config().await? {
+        Some(config) => config,
+        None => {
+            log::info!("没有保存的配置，使用默认配置");
+            AppConfig::default()
+        }
+    };
+    if config.storage == AppConfig::default().storage {
+        return Ok((Arc::new(storage), config));
+    }
+    storage.close().await;
+    let storage = SqliteStorage::with_settings(database_path, &config.storage).await?;
+    Ok((Arc::new(storage), config))
+}
@@ -1,48 +1,128 @@
-use crate::core::AppResult;
+use crate::core::{AppError, AppResult};
+use crate::infrastructure::maintenance::{Clock, SystemClock};
+use crate::infrastructure::platform::PlatformOperations;
+use crate::infrastructure::storage::SqliteStorage;
 use crate::plugins::traits::Plugin;
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use chrono::{DateTime, Local};
+use tokio::task::JoinHandle;
+
+/// 备份进行中时存在的锁文件，多个实例共用同一备份目录时只有一个执行备份
+const LOCK_FILE_NAME: &str = ".backup.lock";
+
+/// 超过该时间的锁文件视为持有者已崩溃
+const STALE_LOCK_AFTER: Duration = Duration::from_secs(30 * 60);
+
+/// 检查是否需要备份的间隔
+pub const BACKUP_POLL_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 执行数据库备份的对象，生产环境中为 `SqliteStorage`
+#[async_trait]
+pub trait BackupTarget: Send + Sync {
+    async fn backup_to(&self, path: &Path) -> AppResult<()>;
+}
+
+#[async_trait]
+impl BackupTarget for SqliteStorage {
+    async fn backup_to(&self, path: &Path) -> AppResult<()> {
+        self.backup(path).await
+    }
+}
+
+/// 持有期间阻止其他实例备份，释放时删除锁文件
+struct BackupLock(PathBuf);
+
+impl Drop for BackupLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
 
 pub struct BackupPlugin {
     backup_dir: PathBuf,
+    target: Arc<dyn BackupTarget>,
 }
 
 impl BackupPlugin {
-    pub fn new(backup_dir: PathBuf) -> Self {
-        Self { backup_dir }
+    pub fn new(backup_dir: PathBuf, target: Arc<dyn BackupTarget>) -> Self {
+        Self { backup_dir, target }
     }
 
-    pub async fn create_backup(&self) -> AppResult<()> {
-        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-        let backup_path = self.backup_dir.join(format!("backup_{}.db", timestamp));
-        
-        // TODO: 实现备份逻辑
-        
-        Ok(())
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
+
+    pub async fn create_backup(&self) -> AppResult<PathBuf> {
+        let Some(_lock) = self.try_lock()? else {
+            return Err(AppError::InvalidOperation("另一个备份正在进行".into()));
+        };
+        self.write_backup(Local::now()).await
+    }
+
+    async fn write_backup(&self, at: DateTime<Local>) -> AppResult<PathBuf> {
+        std::fs::create_dir_all(&self.backup_dir)?;
+        let backup_path = self.backup_dir.join(format!("backup_{}.db", at.format("%Y%m%d_%H%M%S")));
+        self.target.backup_to(&backup_path).await?;
+        Ok(backup_path)
+    }
+
+    fn try_lock(&self) -> AppResult<Option<BackupLock>> {
+        std::fs::create_dir_all(&self.backup_dir)?;
+        let path = self.backup_dir.join(LOCK_FILE_NAME);
+        let stale = std::fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .map(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK_AFTER)
+            .unwrap_or(false);
+        if stale {
+            let _ = std::fs::remove_file(&path);
+        }
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let _ = write!(file, "{}", std::process::id());
+                Ok(Some(BackupLock(path)))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e.into()),
+        }
     }
 
     pub async fn restore_backup(&self, backup_path: PathBuf) -> AppResult<()> {
         // TODO: 实现恢复逻辑
-        
+
         Ok(())
     }
 
     pub async fn list_backups(&self) -> AppResult<Vec<PathBuf>> {
         let mut backups = Vec::new();
-        
+
         if self.backup_dir.exists() {
             for entry in std::fs::read_dir(&self.backup_dir)? {
                 let entry = entry?;
                 let path = entry.path();
-                if path.is_file() && path.extension().map_or(false, |ext| ext == "db") {
+                let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                if path.is_file() && (name.ends_with(".db") || name.ends_with(".db.enc")) {
                     backups.push(path);
                 }
             }
         }
-        
+
         Ok(backups)
     }
+
+    /// 最新备份文件的修改时间，没有备份时为 `None`
+    pub async fn get_last_backup_time(&self) -> AppResult<Option<DateTime<Local>>> {
+        let mut latest = None;
+        for path in self.list_backups().await? {
+            let modified = DateTime::<Local>::from(std::fs::metadata(&path)?.modified()?);
+            latest = latest.max(Some(modified));
+        }
+        Ok(latest)
+    }
 }
 
 #[async_trait]
@@ -81,4 +161,204 @@ impl Plugin for BackupPlugin {
     fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
         None
     }
-} 
\ No newline at end of file
+}
+
+/// 距上次备份超过间隔时自动备份。启动时立即检查一次，之后定时检查；使用电池时跳过
+pub struct BackupScheduler {
+    plugin: Arc<BackupPlugin>,
+    interval: Duration,
+    clock: Arc<dyn Clock>,
+    platform: Option<Arc<dyn PlatformOperations>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BackupScheduler {
+    pub fn new(plugin: Arc<BackupPlugin>, interval: Duration) -> Self {
+        Self {
+            plugin,
+            interval,
+            clock: Arc::new(SystemClock),
+            platform: None,
+            handle: Mutex::new(None),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 用于读取电源状态，使用电池时推迟备份
+    pub fn with_platform(mut self, platform: Arc<dyn PlatformOperations>) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    fn on_battery(&self) -> bool {
+        // 无法获取电源状态时不阻止备份
+        self.platform
+            .as_ref()
+            .map_or(false, |platform| platform.is_on_battery().unwrap_or(false))
+    }
+
+    async fn is_due(&self, now: DateTime<Local>) -> AppResult<bool> {
+        let Some(last) = self.plugin.get_last_backup_time().await? else {
+            return Ok(true);
+        };
+        Ok(chrono::Duration::from_std(self.interval).map_or(false, |interval| now - last >= interval))
+    }
+
+    /// 到期时执行一次备份，返回备份文件路径
+    pub async fn tick(&self) -> AppResult<Option<PathBuf>> {
+        if self.on_battery() {
+            return Ok(None);
+        }
+        let now = self.clock.now();
+        if !self.is_due(now).await? {
+            return Ok(None);
+        }
+        let Some(_lock) = self.plugin.try_lock()? else {
+            return Ok(None);
+        };
+        // 等待锁期间另一个实例可能刚完成备份
+        if !self.is_due(now).await? {
+            return Ok(None);
+        }
+        self.plugin.write_backup(now).await.map(Some)
+    }
+
+    pub fn start(self: &Arc<Self>, poll_interval: Duration) {
+        let scheduler = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                match scheduler.tick().await {
+                    Ok(Some(path)) => log::info!("已自动备份到 {}", path.display()),
+                    Ok(None) => {}
+                    Err(e) => log::error!("自动备份失败: {}", e),
+                }
+            }
+        });
+
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for BackupScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::platform::WindowInfo;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct MockClock(Mutex<DateTime<Local>>);
+
+    impl MockClock {
+        fn advance(&self, by: chrono::Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[derive(Default)]
+    struct FileTarget {
+        count: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl BackupTarget for FileTarget {
+        async fn backup_to(&self, path: &Path) -> AppResult<()> {
+            *self.count.lock().unwrap() += 1;
+            std::fs::write(path, b"backup")?;
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct BatteryPlatform(AtomicBool);
+
+    impl PlatformOperations for BatteryPlatform {
+        fn get_active_window(&self) -> AppResult<WindowInfo> {
+            Err(AppError::System("not available in tests".into()))
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+
+        fn is_on_battery(&self) -> AppResult<bool> {
+            Ok(self.0.load(Ordering::SeqCst))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backs_up_when_interval_elapsed() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let target = Arc::new(FileTarget::default());
+        let plugin = Arc::new(BackupPlugin::new(dir.path().join("backups"), target.clone()));
+        let clock = Arc::new(MockClock(Mutex::new(Local::now())));
+        let platform = Arc::new(BatteryPlatform::default());
+        let scheduler = BackupScheduler::new(plugin.clone(), Duration::from_secs(24 * 3600))
+            .with_clock(clock.clone())
+            .with_platform(platform.clone());
+
+        // 没有备份时立即备份
+        assert!(scheduler.tick().await?.is_some());
+        assert!(plugin.get_last_backup_time().await?.is_some());
+        clock.advance(chrono::Duration::hours(12));
+        assert_eq!(scheduler.tick().await?, None);
+
+        // 使用电池时推迟
+        clock.advance(chrono::Duration::hours(13));
+        platform.0.store(true, Ordering::SeqCst);
+        assert_eq!(scheduler.tick().await?, None);
+        platform.0.store(false, Ordering::SeqCst);
+        assert!(scheduler.tick().await?.is_some());
+
+        assert_eq!(*target.count.lock().unwrap(), 2);
+        assert_eq!(plugin.list_backups().await?.len(), 2);
+        // 备份完成后释放锁
+        assert!(!plugin.backup_dir().join(LOCK_FILE_NAME).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_skips_while_other_instance_backs_up() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let target = Arc::new(FileTarget::default());
+        let plugin = Arc::new(BackupPlugin::new(dir.path().to_path_buf(), target.clone()));
+        let scheduler = BackupScheduler::new(plugin.clone(), Duration::from_secs(3600));
+
+        let lock = plugin.try_lock()?.expect("lock should be free");
+        assert_eq!(scheduler.tick().await?, None);
+        assert!(matches!(plugin.create_backup().await, Err(AppError::InvalidOperation(_))));
+        drop(lock);
+
+        assert!(scheduler.tick().await?.is_some());
+        assert_eq!(*target.count.lock().unwrap(), 1);
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::core::AppResult;
-use crate::plugins::traits::{Plugin, StatisticsPlugin};
+use crate::plugins::traits::{Plugin, PluginCapabilities, StatisticsPlugin};
 use async_trait::async_trait;
 use chrono::{DateTime, Local};
 use csv::Writer;
@@ -27,6 +27,13 @@ impl Plugin for CsvExportPlugin {
         "CSV数据导出插件"
     }
 
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities {
+            statistics: true,
+            ..PluginCapabilities::default()
+        }
+    }
+
     async fn initialize(&self) -> AppResult<()> {
         Ok(())
     }
@@ -1,7 +1,9 @@
 mod backup;
 mod stats;
 mod notification;
+mod webhook;
 
-pub use backup::BackupPlugin;
-pub use stats::StatsPlugin;
-pub use notification::NotificationPlugin; 
\ No newline at end of file
+pub use backup::{BackupPlugin, BackupScheduler, BackupTarget, BACKUP_POLL_INTERVAL};
+pub use stats::{DailyDigest, StatsPlugin};
+pub use notification::NotificationPlugin;
+pub use webhook::{WebhookPayload, WebhookPlugin}; 
\ No newline at end of file
@@ -1,5 +1,6 @@
+use crate::core::models::PomodoroStatus;
 use crate::core::AppResult;
-use crate::plugins::traits::Plugin;
+use crate::plugins::traits::{self, Plugin, PluginCapabilities, PluginEvent};
 use async_trait::async_trait;
 use notify_rust::Notification;
 
@@ -13,6 +14,13 @@ impl NotificationPlugin {
 
 #[async_trait]
 impl Plugin for NotificationPlugin {
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities {
+            notification: true,
+            ..PluginCapabilities::default()
+        }
+    }
+
     fn name(&self) -> &str {
         "通知插件"
     }
@@ -44,14 +52,75 @@ impl Plugin for NotificationPlugin {
     fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
         None
     }
+
+    async fn on_event(&self, event: &PluginEvent) -> AppResult<()> {
+        match notification_for(event) {
+            Some((title, message)) => traits::NotificationPlugin::send_notification(self, &title, &message).await,
+            None => Ok(()),
+        }
+    }
 }
 
-impl NotificationPlugin {
-    pub async fn send_notification(&self, title: &str, message: &str) -> AppResult<()> {
+/// 事件对应的通知标题和内容，活动记录不提示
+fn notification_for(event: &PluginEvent) -> Option<(String, String)> {
+    match event {
+        PluginEvent::PomodoroCompleted(session) => Some((
+            "番茄钟完成".into(),
+            format!("完成了 {} 分钟的专注", session.duration.as_secs() / 60),
+        )),
+        PluginEvent::BreakStarted(session) => {
+            let title = match session.status {
+                PomodoroStatus::LongBreak => "长休息开始",
+                _ => "休息开始",
+            };
+            Some((title.into(), format!("休息 {} 分钟", session.duration.as_secs() / 60)))
+        }
+        PluginEvent::ActivityRecorded(_) => None,
+    }
+}
+
+#[async_trait]
+impl traits::NotificationPlugin for NotificationPlugin {
+    async fn send_notification(&self, title: &str, message: &str) -> AppResult<()> {
         Notification::new()
             .summary(title)
             .body(message)
             .show()?;
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::PomodoroSession;
+    use chrono::Local;
+    use std::time::Duration;
+
+    fn session(status: PomodoroStatus, minutes: u64) -> PomodoroSession {
+        PomodoroSession {
+            id: Some(1),
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(minutes * 60),
+            status,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }
+    }
+
+    #[test]
+    fn test_notification_for_events() {
+        let completed = PluginEvent::PomodoroCompleted(session(PomodoroStatus::Completed, 25));
+        assert_eq!(
+            notification_for(&completed),
+            Some(("番茄钟完成".to_string(), "完成了 25 分钟的专注".to_string()))
+        );
+
+        let long_break = PluginEvent::BreakStarted(session(PomodoroStatus::LongBreak, 15));
+        assert_eq!(notification_for(&long_break).unwrap().0, "长休息开始");
+    }
+}
@@ -1,13 +1,185 @@
+use crate::core::traits::Storage;
 use crate::core::AppResult;
-use crate::plugins::traits::Plugin;
+use crate::domain::analysis::{AnalysisManager, GroupBy, Report};
+use crate::domain::config::DigestSettings;
+use crate::domain::goal::local_midnight;
+use crate::infrastructure::maintenance::{Clock, SystemClock};
+use crate::plugins::traits::{self, Plugin};
 use async_trait::async_trait;
-use serde_json::Value;
+use chrono::{DateTime, Local, LocalResult, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
-pub struct StatsPlugin;
+pub const DIGEST_FILE_NAME: &str = "digest.jsonl";
+
+/// 摘要日志保留的条数，超出时删除最旧的
+const DIGEST_MAX_ENTRIES: usize = 366;
+
+/// 检查是否到达摘要时间的间隔。轮询而不是睡眠到目标时间，系统休眠和时区变化后仍能按本地时间触发
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 写入摘要日志的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyDigest {
+    pub date: NaiveDate,
+    pub total_seconds: u64,
+    pub productive_seconds: u64,
+    pub productivity_score: f64,
+    pub focus_seconds: u64,
+    pub completed_pomodoros: usize,
+    pub top_category: Option<String>,
+}
+
+impl DailyDigest {
+    pub fn from_report(date: NaiveDate, report: &Report) -> Self {
+        Self {
+            date,
+            total_seconds: report.total_time.as_secs(),
+            productive_seconds: report.productive_time.as_secs(),
+            productivity_score: report.productivity_score,
+            focus_seconds: report.focus_time.as_secs(),
+            completed_pomodoros: report.completed_pomodoros,
+            top_category: report.breakdown.first().map(|group| group.key.clone()),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        let minutes = self.total_seconds / 60;
+        let mut message = format!(
+            "记录 {}h {:02}m，生产率 {:.0}%，完成 {} 个番茄",
+            minutes / 60,
+            minutes % 60,
+            self.productivity_score,
+            self.completed_pomodoros
+        );
+        if let Some(category) = &self.top_category {
+            message.push_str(&format!("，主要类别：{}", category));
+        }
+        message
+    }
+}
+
+/// `after` 之后第一个本地时间为 `time` 的时刻。夏令时跳过的时间顺延一小时，重复的时间取第一次
+pub fn next_run(after: DateTime<Local>, time: NaiveTime) -> DateTime<Local> {
+    let mut date = after.date_naive();
+    loop {
+        let naive = date.and_time(time);
+        let at = match Local.from_local_datetime(&naive) {
+            LocalResult::Single(at) => Some(at),
+            LocalResult::Ambiguous(first, _) => Some(first),
+            LocalResult::None => Local.from_local_datetime(&(naive + chrono::Duration::hours(1))).earliest(),
+        };
+        if let Some(at) = at.filter(|at| *at > after) {
+            return at;
+        }
+        date = date.succ_opt().expect("日期超出范围");
+    }
+}
+
+/// 追加一行并只保留最近的 `DIGEST_MAX_ENTRIES` 条
+fn append_digest(path: &Path, digest: &DailyDigest) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(digest)?)?;
+    drop(file);
+
+    let content = std::fs::read_to_string(path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() > DIGEST_MAX_ENTRIES {
+        let kept = lines[lines.len() - DIGEST_MAX_ENTRIES..].join("\n");
+        std::fs::write(path, kept + "\n")?;
+    }
+    Ok(())
+}
+
+/// 后台任务与插件共享的摘要状态
+#[derive(Clone)]
+struct DigestJob {
+    analysis: Arc<AnalysisManager>,
+    time: NaiveTime,
+    output_path: PathBuf,
+    clock: Arc<dyn Clock>,
+    notifier: Option<Arc<dyn traits::NotificationPlugin>>,
+    next_due: Arc<Mutex<DateTime<Local>>>,
+}
+
+impl DigestJob {
+    async fn tick(&self) -> AppResult<Option<DailyDigest>> {
+        let now = self.clock.now();
+        let due = *self.next_due.lock().unwrap();
+        if now < due {
+            return Ok(None);
+        }
+        // 休眠错过多天时只为最后一次计划时间生成摘要
+        *self.next_due.lock().unwrap() = next_run(now, self.time);
+
+        let date = due.date_naive();
+        let report = self.analysis.report(local_midnight(date), due, GroupBy::Category).await?;
+        if report.is_empty() {
+            return Ok(None);
+        }
+
+        let digest = DailyDigest::from_report(date, &report);
+        append_digest(&self.output_path, &digest)?;
+        if let Some(notifier) = &self.notifier {
+            if let Err(e) = notifier.send_notification("今日摘要", &digest.message()).await {
+                log::warn!("发送每日摘要通知失败: {}", e);
+            }
+        }
+        Ok(Some(digest))
+    }
+}
+
+/// 每天在配置的本地时间汇总当天数据，发送通知并追加到摘要日志，没有记录的日子跳过
+pub struct StatsPlugin {
+    job: DigestJob,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
 
 impl StatsPlugin {
-    pub fn new() -> Self {
-        Self
+    pub fn new(storage: Arc<dyn Storage + Send + Sync>, settings: DigestSettings, data_dir: &Path) -> Self {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        Self {
+            job: DigestJob {
+                analysis: Arc::new(AnalysisManager::new(storage)),
+                time: settings.time,
+                output_path: settings.output_path.unwrap_or_else(|| data_dir.join(DIGEST_FILE_NAME)),
+                next_due: Arc::new(Mutex::new(next_run(clock.now(), settings.time))),
+                clock,
+                notifier: None,
+            },
+            handle: Mutex::new(None),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        *self.job.next_due.lock().unwrap() = next_run(clock.now(), self.job.time);
+        self.job.clock = clock;
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn traits::NotificationPlugin>) -> Self {
+        self.job.notifier = Some(notifier);
+        self
+    }
+
+    pub fn output_path(&self) -> &Path {
+        &self.job.output_path
+    }
+
+    pub fn next_due(&self) -> DateTime<Local> {
+        *self.job.next_due.lock().unwrap()
+    }
+
+    /// 到达计划时间时生成摘要，返回写入的摘要
+    pub async fn tick(&self) -> AppResult<Option<DailyDigest>> {
+        self.job.tick().await
     }
 }
 
@@ -22,7 +194,7 @@ impl Plugin for StatsPlugin {
     }
 
     fn description(&self) -> &str {
-        "提供基本的统计功能"
+        "每天定时生成统计摘要"
     }
 
     async fn initialize(&self) -> AppResult<()> {
@@ -30,10 +202,26 @@ impl Plugin for StatsPlugin {
     }
 
     async fn start(&self) -> AppResult<()> {
+        let job = self.job.clone();
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = job.tick().await {
+                    log::error!("生成每日摘要失败: {}", e);
+                }
+            }
+        });
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
         Ok(())
     }
 
     async fn stop(&self) -> AppResult<()> {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
         Ok(())
     }
 
@@ -44,4 +232,120 @@ impl Plugin for StatsPlugin {
     fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
         None
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Activity;
+    use crate::core::traits::MockStorage;
+
+    struct MockClock(Mutex<DateTime<Local>>);
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap()
+    }
+
+    fn activity(start: DateTime<Local>, minutes: u64) -> Activity {
+        Activity {
+            id: None,
+            name: "code".into(),
+            start_time: start,
+            end_time: Some(start + chrono::Duration::minutes(minutes as i64)),
+            project_id: None,
+            description: None,
+            duration: Duration::from_secs(minutes * 60),
+            category: "development".into(),
+            is_productive: true,
+            app_name: "code".into(),
+            window_title: "code".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    #[test]
+    fn test_next_run() {
+        let time = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        assert_eq!(next_run(at(12, 9, 0), time), at(12, 18, 0));
+        // 恰好在计划时间或之后时排到次日
+        assert_eq!(next_run(at(12, 18, 0), time), at(13, 18, 0));
+        assert_eq!(next_run(at(12, 23, 30), time), at(13, 18, 0));
+    }
+
+    #[tokio::test]
+    async fn test_digest_written_at_scheduled_time() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let mut mock_storage = MockStorage::new();
+        // 12 日有记录，13 日没有
+        mock_storage.expect_get_activities().returning(|start, _| {
+            Ok(if start.date_naive() == at(12, 0, 0).date_naive() {
+                vec![activity(at(12, 9, 0), 90)]
+            } else {
+                Vec::new()
+            })
+        });
+        mock_storage.expect_get_pomodoro_sessions().returning(|_, _| Ok(Vec::new()));
+        mock_storage.expect_list_projects().returning(|| Ok(Vec::new()));
+
+        let clock = Arc::new(MockClock(Mutex::new(at(12, 9, 0))));
+        let plugin = StatsPlugin::new(Arc::new(mock_storage), DigestSettings::default(), dir.path())
+            .with_clock(clock.clone());
+        assert_eq!(plugin.next_due(), at(12, 18, 0));
+        assert_eq!(plugin.tick().await?, None);
+
+        *clock.0.lock().unwrap() = at(12, 18, 1);
+        let digest = plugin.tick().await?.expect("应生成摘要");
+        assert_eq!(digest.date, at(12, 0, 0).date_naive());
+        assert_eq!(digest.total_seconds, 90 * 60);
+        assert_eq!(digest.top_category.as_deref(), Some("development"));
+        assert_eq!(plugin.next_due(), at(13, 18, 0));
+        // 同一天不重复生成
+        assert_eq!(plugin.tick().await?, None);
+
+        // 没有记录的日子跳过
+        *clock.0.lock().unwrap() = at(13, 18, 0);
+        assert_eq!(plugin.tick().await?, None);
+
+        let content = std::fs::read_to_string(dir.path().join(DIGEST_FILE_NAME))?;
+        let lines: Vec<DailyDigest> = content
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines, vec![digest]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_digest_log_keeps_recent_entries() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(DIGEST_FILE_NAME);
+        let first = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        for day in 0..DIGEST_MAX_ENTRIES as i64 + 2 {
+            let digest = DailyDigest {
+                date: first + chrono::Duration::days(day),
+                total_seconds: 60,
+                productive_seconds: 60,
+                productivity_score: 100.0,
+                focus_seconds: 0,
+                completed_pomodoros: 0,
+                top_category: None,
+            };
+            append_digest(&path, &digest)?;
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!(content.lines().count(), DIGEST_MAX_ENTRIES);
+        let oldest: DailyDigest = serde_json::from_str(content.lines().next().unwrap())?;
+        assert_eq!(oldest.date, first + chrono::Duration::days(2));
+        Ok(())
+    }
+}
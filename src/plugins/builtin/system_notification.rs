@@ -1,8 +1,15 @@
-use crate::core::AppResult;
-use crate::plugins::traits::{Plugin, NotificationPlugin};
+use crate::core::{AppError, AppResult};
+use crate::domain::notification::SoundPlayer;
+use crate::plugins::traits::{Plugin, PluginCapabilities, NotificationPlugin};
 use async_trait::async_trait;
 use notify_rust::Notification;
+use rodio::source::{SineWave, Source};
+use rodio::{Decoder, OutputStream, Sink};
 use std::any::Any;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
 
 pub struct SystemNotificationPlugin;
 
@@ -26,6 +33,13 @@ impl Plugin for SystemNotificationPlugin {
         "系统通知插件"
     }
 
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities {
+            notification: true,
+            ..PluginCapabilities::default()
+        }
+    }
+
     async fn initialize(&self) -> AppResult<()> {
         Ok(())
     }
@@ -59,4 +73,43 @@ impl NotificationPlugin for SystemNotificationPlugin {
             
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+impl SoundPlayer for SystemNotificationPlugin {
+    fn play(&self, sound: Option<&Path>, volume: f32) -> AppResult<()> {
+        // 先解码，文件损坏时由调用方退回内置提示音
+        let decoded = match sound {
+            Some(path) => Some(
+                Decoder::new(BufReader::new(File::open(path)?))
+                    .map_err(|e| AppError::Plugin(format!("无法解码提示音 {}: {}", path.display(), e)))?,
+            ),
+            None => None,
+        };
+        let volume = volume.clamp(0.0, 1.0);
+
+        // 输出流不能跨线程传递，在独立线程中打开并播放完毕
+        std::thread::spawn(move || {
+            if let Err(e) = play_blocking(decoded, volume) {
+                log::warn!("播放提示音失败: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+fn play_blocking(decoded: Option<Decoder<BufReader<File>>>, volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    let (_stream, handle) = OutputStream::try_default()?;
+    let sink = Sink::try_new(&handle)?;
+    sink.set_volume(volume);
+    match decoded {
+        Some(source) => sink.append(source),
+        None => {
+            // 内置提示音：两声短促的高低音
+            for freq in [880.0, 660.0] {
+                sink.append(SineWave::new(freq).take_duration(Duration::from_millis(180)).amplify(0.5));
+            }
+        }
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
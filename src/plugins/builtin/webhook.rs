@@ -0,0 +1,302 @@
+use crate::core::{AppError, AppResult};
+use crate::domain::config::{WebhookEvent, WebhookSettings};
+use crate::plugins::traits::{Plugin, PluginEvent};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// 待推送事件的上限，接收端过慢时丢弃新事件而不是阻塞计时器
+const QUEUE_CAPACITY: usize = 64;
+
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub timestamp: DateTime<Local>,
+    pub project_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub duration_seconds: u64,
+}
+
+impl WebhookPayload {
+    pub fn from_event(event: &PluginEvent) -> Self {
+        match event {
+            PluginEvent::PomodoroCompleted(session) => Self {
+                event: WebhookEvent::PomodoroCompleted,
+                timestamp: session.end_time.unwrap_or_else(Local::now),
+                project_id: session.project_id,
+                tags: session.tags.clone(),
+                duration_seconds: session.duration.as_secs(),
+            },
+            PluginEvent::BreakStarted(session) => Self {
+                event: WebhookEvent::BreakStarted,
+                timestamp: session.start_time,
+                project_id: session.project_id,
+                tags: session.tags.clone(),
+                duration_seconds: session.duration.as_secs(),
+            },
+            PluginEvent::ActivityRecorded(activity) => Self {
+                event: WebhookEvent::ActivityRecorded,
+                timestamp: activity.end_time.unwrap_or(activity.start_time),
+                project_id: activity.project_id,
+                tags: activity.tags.clone(),
+                duration_seconds: activity.duration.as_secs(),
+            },
+        }
+    }
+}
+
+/// 把订阅的事件推送到 HTTP 地址。事件先进入有界队列，由 `start` 启动的后台任务逐个发送，失败时按指数退避重试
+pub struct WebhookPlugin {
+    settings: WebhookSettings,
+    backoff: Duration,
+    sender: mpsc::Sender<WebhookPayload>,
+    receiver: Mutex<Option<mpsc::Receiver<WebhookPayload>>>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl WebhookPlugin {
+    pub fn new(settings: WebhookSettings) -> AppResult<Self> {
+        settings.validate()?;
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        Ok(Self {
+            settings,
+            backoff: INITIAL_BACKOFF,
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            worker: Mutex::new(None),
+        })
+    }
+
+    /// 第一次重试前的等待时间，之后每次加倍
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+#[async_trait]
+impl Plugin for WebhookPlugin {
+    fn name(&self) -> &str {
+        "Webhook"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "把番茄钟和活动事件推送到 HTTP 地址"
+    }
+
+    async fn initialize(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        let Some(receiver) = self.receiver.lock().unwrap().take() else {
+            return Ok(());
+        };
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| AppError::Plugin(format!("创建 HTTP 客户端失败: {}", e)))?;
+        let worker = tokio::spawn(run_worker(client, self.settings.clone(), self.backoff, receiver));
+        *self.worker.lock().unwrap() = Some(worker);
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        if let Some(worker) = self.worker.lock().unwrap().take() {
+            worker.abort();
+        }
+        Ok(())
+    }
+
+    async fn uninstall(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
+        None
+    }
+
+    async fn on_event(&self, event: &PluginEvent) -> AppResult<()> {
+        let payload = WebhookPayload::from_event(event);
+        if !self.settings.subscribes(payload.event) {
+            return Ok(());
+        }
+        if self.sender.try_send(payload).is_err() {
+            log::warn!("webhook 队列已满，丢弃事件");
+        }
+        Ok(())
+    }
+}
+
+async fn run_worker(
+    client: reqwest::Client,
+    settings: WebhookSettings,
+    backoff: Duration,
+    mut receiver: mpsc::Receiver<WebhookPayload>,
+) {
+    while let Some(payload) = receiver.recv().await {
+        if let Err(e) = deliver(&client, &settings, backoff, &payload).await {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+/// 网络错误、5xx 和 429 会重试，其他状态码直接放弃
+async fn deliver(
+    client: &reqwest::Client,
+    settings: &WebhookSettings,
+    mut backoff: Duration,
+    payload: &WebhookPayload,
+) -> AppResult<()> {
+    let mut attempt = 1;
+    loop {
+        let mut request = client.post(&settings.url).json(payload);
+        if let Some(token) = &settings.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        let (error, retryable) = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                (
+                    format!("HTTP {}", status),
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+                )
+            }
+            Err(e) => (e.to_string(), true),
+        };
+        if !retryable || attempt >= MAX_ATTEMPTS {
+            return Err(AppError::Plugin(format!(
+                "推送到 {} 失败（已尝试 {} 次）: {}",
+                settings.url, attempt, error
+            )));
+        }
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{PomodoroSession, PomodoroStatus};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn session(status: PomodoroStatus) -> PomodoroSession {
+        PomodoroSession {
+            id: Some(1),
+            start_time: Local::now() - chrono::Duration::minutes(25),
+            end_time: Some(Local::now()),
+            duration: Duration::from_secs(25 * 60),
+            status,
+            project_id: Some(3),
+            notes: None,
+            tags: vec!["deep".into()],
+            category: None,
+            utc_offset: None,
+        }
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let line = line.to_lowercase();
+                        line.strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if data.len() >= end + 4 + length {
+                    return text;
+                }
+            }
+            if n == 0 {
+                return text;
+            }
+        }
+    }
+
+    /// 依次以给定状态码响应，把收到的请求原文发送到返回的通道
+    async fn serve_statuses(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                sender.send(read_request(&mut stream).await).unwrap();
+                let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_posts_subscribed_events_with_retry() -> AppResult<()> {
+        let (url, mut requests) = serve_statuses(vec![503, 200]).await;
+        let settings = WebhookSettings {
+            bearer_token: Some("secret".into()),
+            ..WebhookSettings::new(url)
+        };
+        let plugin = WebhookPlugin::new(settings)?.with_backoff(Duration::from_millis(10));
+        plugin.start().await?;
+
+        // 未订阅的事件不推送
+        plugin.on_event(&PluginEvent::BreakStarted(session(PomodoroStatus::ShortBreak))).await?;
+        plugin.on_event(&PluginEvent::PomodoroCompleted(session(PomodoroStatus::Completed))).await?;
+
+        for _ in 0..2 {
+            let request = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await.unwrap().unwrap();
+            assert!(request.starts_with("POST /hook"));
+            assert!(request.to_lowercase().contains("authorization: bearer secret"));
+            let body: serde_json::Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap())?;
+            assert_eq!(body["event"], "pomodoro_completed");
+            assert_eq!(body["project_id"], 3);
+            assert_eq!(body["tags"], serde_json::json!(["deep"]));
+            assert_eq!(body["duration_seconds"], 1500);
+        }
+        plugin.stop().await
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_does_not_block() -> AppResult<()> {
+        // 未启动时没有消费者，队列满后直接丢弃
+        let plugin = WebhookPlugin::new(WebhookSettings::new("https://example.com/hook"))?;
+        let event = PluginEvent::PomodoroCompleted(session(PomodoroStatus::Completed));
+        let sending = async {
+            for _ in 0..QUEUE_CAPACITY * 2 {
+                plugin.on_event(&event).await?;
+            }
+            AppResult::Ok(())
+        };
+        tokio::time::timeout(Duration::from_secs(1), sending).await.unwrap()
+    }
+
+    #[test]
+    fn test_rejects_invalid_url() {
+        assert!(WebhookPlugin::new(WebhookSettings::new("ftp://example.com/hook")).is_err());
+        assert!(WebhookPlugin::new(WebhookSettings::new("not a url")).is_err());
+    }
+}
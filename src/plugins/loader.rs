@@ -1,46 +1,230 @@
 use std::collections::HashMap;
+use std::ffi::c_void;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use libloading::{Library, Symbol};
-use crate::core::AppResult;
-use crate::plugins::traits::Plugin;
+use async_trait::async_trait;
+use libloading::Library;
+use crate::core::{AppError, AppResult};
+use crate::plugins::traits::{Plugin, PluginCapabilities, PluginEvent, PLUGIN_API_VERSION};
+
+/// 插件导出的接口版本函数，在调用入口函数之前检查
+pub const PLUGIN_VERSION_SYMBOL: &[u8] = b"plugin_api_version\0";
+
+/// 插件导出的入口函数，返回 `Box::into_raw(Box::new(Box<dyn Plugin>))`，创建失败时返回空指针
+pub const PLUGIN_ENTRY_SYMBOL: &[u8] = b"register_plugin\0";
+
+pub type PluginVersionFn = unsafe extern "C" fn() -> u32;
+pub type PluginEntryFn = unsafe extern "C" fn() -> *mut c_void;
+
+/// 默认的插件目录
+pub const DEFAULT_PLUGIN_DIR: &str = "plugins";
+
+/// 在插件库中导出入口函数，参数为创建插件的表达式
+///
+/// ```ignore
+/// time_tracker::declare_plugin!(MyPlugin::default());
+/// ```
+#[macro_export]
+macro_rules! declare_plugin {
+    ($constructor:expr) => {
+        #[no_mangle]
+        pub extern "C" fn plugin_api_version() -> u32 {
+            $crate::plugins::traits::PLUGIN_API_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn register_plugin() -> *mut ::std::ffi::c_void {
+            let plugin: ::std::boxed::Box<dyn $crate::plugins::traits::Plugin> = ::std::boxed::Box::new($constructor);
+            ::std::boxed::Box::into_raw(::std::boxed::Box::new(plugin)) as *mut ::std::ffi::c_void
+        }
+    };
+}
+
+/// 动态库中创建的插件。插件的代码和虚表都在库中，持有库的引用直到插件本身被释放，
+/// 注册表卸载插件时仍在使用它的调用方不会访问已卸载的库
+struct LoadedPlugin {
+    // 字段按声明顺序释放，插件必须先于库释放
+    plugin: Box<dyn Plugin>,
+    _library: Arc<Library>,
+}
+
+#[async_trait]
+impl Plugin for LoadedPlugin {
+    fn api_version(&self) -> u32 {
+        self.plugin.api_version()
+    }
+
+    fn capabilities(&self) -> PluginCapabilities {
+        self.plugin.capabilities()
+    }
+
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    fn version(&self) -> &str {
+        self.plugin.version()
+    }
+
+    fn description(&self) -> &str {
+        self.plugin.description()
+    }
+
+    async fn initialize(&self) -> AppResult<()> {
+        self.plugin.initialize().await
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        self.plugin.start().await
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        self.plugin.stop().await
+    }
+
+    async fn uninstall(&self) -> AppResult<()> {
+        self.plugin.uninstall().await
+    }
+
+    fn get_settings_ui(&self) -> Option<Box<dyn std::any::Any>> {
+        self.plugin.get_settings_ui()
+    }
+
+    async fn on_event(&self, event: &PluginEvent) -> AppResult<()> {
+        self.plugin.on_event(event).await
+    }
+}
 
 pub struct PluginLoader {
-    libraries: HashMap<String, Library>,
+    dir: PathBuf,
+    libraries: HashMap<String, Arc<Library>>,
 }
 
 impl PluginLoader {
     pub fn new() -> Self {
         Self {
+            dir: PathBuf::from(DEFAULT_PLUGIN_DIR),
             libraries: HashMap::new(),
         }
     }
 
-    pub fn load_plugin(&mut self, plugin_name: &str) -> AppResult<Arc<dyn Plugin>> {
-        let lib_path = format!("plugins/{}.so", plugin_name);
-        let lib = unsafe { Library::new(&lib_path)? };
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
 
-        let constructor: Symbol<unsafe fn() -> *mut dyn Plugin> = unsafe {
-            lib.get(b"_plugin_create")?
+    /// 按名称加载默认目录中的插件，文件名可以带平台的库前缀（如 `lib`）
+    pub fn load_plugin(&mut self, plugin_name: &str) -> AppResult<Arc<dyn Plugin>> {
+        let prefixed = self.dir.join(library_file_name(&format!("{}{}", std::env::consts::DLL_PREFIX, plugin_name)));
+        let path = if prefixed.exists() {
+            prefixed
+        } else {
+            self.dir.join(library_file_name(plugin_name))
         };
+        self.load_library(plugin_name, &path)
+    }
 
-        let plugin = unsafe {
-            let raw = constructor();
-            Arc::from_raw(raw as *mut dyn Plugin)
+    /// 加载目录中的所有动态库插件，接口版本不一致或加载失败的插件记录日志后跳过。
+    /// 目录不存在时视为没有插件
+    pub fn load_dir(&mut self, dir: &Path) -> AppResult<Vec<(String, Arc<dyn Plugin>)>> {
+        let mut loaded = Vec::new();
+        for (name, path) in scan_dir(dir)? {
+            match self.load_library(&name, &path) {
+                Ok(plugin) => loaded.push((name, plugin)),
+                Err(e) => log::warn!("跳过插件 {}: {}", path.display(), e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn load_library(&mut self, plugin_name: &str, path: &Path) -> AppResult<Arc<dyn Plugin>> {
+        if self.libraries.contains_key(plugin_name) {
+            return Err(AppError::Plugin(format!("插件 {} 已加载", plugin_name)));
+        }
+        let load_error = |e: libloading::Error| AppError::Plugin(format!("加载插件 {} 失败: {}", path.display(), e));
+
+        // SAFETY: 加载动态库会执行其初始化代码，插件目录中的库视为可信
+        let library = Arc::new(unsafe { Library::new(path) }.map_err(load_error)?);
+        let raw = {
+            // SAFETY: 符号类型与 `declare_plugin!` 导出的函数签名一致
+            let version = unsafe { library.get::<PluginVersionFn>(PLUGIN_VERSION_SYMBOL) }.map_err(load_error)?;
+            let version = unsafe { version() };
+            // 版本不一致时插件的 trait 布局可能不同，不能调用入口函数
+            if version != PLUGIN_API_VERSION {
+                return Err(AppError::Plugin(format!(
+                    "插件 {} 使用的接口版本 {} 与当前版本 {} 不兼容",
+                    plugin_name, version, PLUGIN_API_VERSION
+                )));
+            }
+            let entry = unsafe { library.get::<PluginEntryFn>(PLUGIN_ENTRY_SYMBOL) }.map_err(load_error)?;
+            unsafe { entry() }
         };
+        if raw.is_null() {
+            return Err(AppError::Plugin(format!("插件 {} 创建失败", plugin_name)));
+        }
 
-        self.libraries.insert(plugin_name.to_string(), lib);
-        Ok(plugin)
+        // SAFETY: 入口函数按约定返回 `Box<Box<dyn Plugin>>` 的裸指针，所有权转移给宿主
+        let plugin = unsafe { *Box::from_raw(raw as *mut Box<dyn Plugin>) };
+        self.libraries.insert(plugin_name.to_string(), library.clone());
+        Ok(Arc::new(LoadedPlugin {
+            plugin,
+            _library: library,
+        }))
     }
 
+    /// 释放加载器持有的库引用。仍有插件实例存活时库在最后一个实例释放后才卸载
     pub fn unload_plugin(&mut self, plugin_name: &str) -> AppResult<()> {
-        if let Some(lib) = self.libraries.remove(plugin_name) {
-            drop(lib);
-        }
+        self.libraries.remove(plugin_name);
         Ok(())
     }
 
     pub fn list_plugins(&self) -> AppResult<Vec<String>> {
-        // TODO: 实现插件扫描逻辑
-        Ok(Vec::new())
+        Ok(scan_dir(&self.dir)?.into_iter().map(|(name, _)| name).collect())
+    }
+}
+
+fn library_file_name(stem: &str) -> String {
+    format!("{}.{}", stem, std::env::consts::DLL_EXTENSION)
+}
+
+/// 目录中当前平台的动态库，插件名为去掉库前缀和扩展名后的文件名
+fn scan_dir(dir: &Path) -> AppResult<Vec<(String, PathBuf)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
     }
-} 
\ No newline at end of file
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(std::env::consts::DLL_EXTENSION) {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let name = stem.strip_prefix(std::env::consts::DLL_PREFIX).unwrap_or(stem);
+        plugins.push((name.to_string(), path.clone()));
+    }
+    plugins.sort();
+    Ok(plugins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_dir_skips_invalid_libraries() -> AppResult<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join(library_file_name("broken")), b"not a library")?;
+        std::fs::write(dir.path().join("notes.txt"), b"ignored")?;
+
+        let mut loader = PluginLoader::new().with_dir(dir.path());
+        assert_eq!(loader.list_plugins()?, vec!["broken".to_string()]);
+        assert!(loader.load_dir(dir.path())?.is_empty());
+        assert!(matches!(loader.load_plugin("broken"), Err(AppError::Plugin(_))));
+
+        // 目录不存在时没有插件
+        assert!(loader.load_dir(&dir.path().join("missing"))?.is_empty());
+        Ok(())
+    }
+}
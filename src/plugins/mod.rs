@@ -5,5 +5,5 @@ pub mod traits;
 
 pub use builtin::*;
 pub use loader::PluginLoader;
-pub use registry::{PluginEvent, PluginRegistry};
+pub use registry::{PluginRegistry, RegistryEvent};
 pub use traits::*; 
\ No newline at end of file
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, RwLock};
-use tokio::sync::broadcast;
-use crate::core::AppResult;
+use tokio::sync::{broadcast, mpsc};
+use crate::core::{AppError, AppResult};
 use crate::plugins::loader::PluginLoader;
-use crate::plugins::traits::Plugin;
+use crate::plugins::traits::{Plugin, PluginCapabilities, PluginEvent, PluginEventSender, PLUGIN_API_VERSION};
 
-pub enum PluginEvent {
+/// 插件加载、卸载和出错时发给宿主的通知
+#[derive(Debug, Clone)]
+pub enum RegistryEvent {
     Loaded(String),
     Unloaded(String),
     Error(String),
@@ -14,11 +17,11 @@ pub enum PluginEvent {
 pub struct PluginRegistry {
     plugins: RwLock<HashMap<String, Arc<dyn Plugin>>>,
     loader: RwLock<PluginLoader>,
-    event_sender: broadcast::Sender<PluginEvent>,
+    event_sender: broadcast::Sender<RegistryEvent>,
 }
 
 impl PluginRegistry {
-    pub fn new(event_sender: broadcast::Sender<PluginEvent>) -> Self {
+    pub fn new(event_sender: broadcast::Sender<RegistryEvent>) -> Self {
         Self {
             plugins: RwLock::new(HashMap::new()),
             loader: RwLock::new(PluginLoader::new()),
@@ -26,22 +29,83 @@ impl PluginRegistry {
         }
     }
 
+    /// 接口版本不一致或需要宿主不支持的能力时拒绝加载
+    pub fn check_compatibility(plugin: &dyn Plugin) -> AppResult<()> {
+        let version = plugin.api_version();
+        if version != PLUGIN_API_VERSION {
+            return Err(AppError::Plugin(format!(
+                "插件 {} 使用的接口版本 {} 与当前版本 {} 不兼容",
+                plugin.name(),
+                version,
+                PLUGIN_API_VERSION
+            )));
+        }
+
+        let unsupported = plugin.capabilities().unsupported_by(&PluginCapabilities::supported());
+        if !unsupported.is_empty() {
+            return Err(AppError::Plugin(format!(
+                "插件 {} 需要不支持的能力: {}",
+                plugin.name(),
+                unsupported.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
     pub async fn load_plugin(&self, plugin_name: &str) -> AppResult<()> {
         let plugin = {
             let mut loader = self.loader.write().unwrap();
             loader.load_plugin(plugin_name)?
         };
 
+        if let Err(e) = Self::check_compatibility(plugin.as_ref()) {
+            drop(plugin);
+            self.loader.write().unwrap().unload_plugin(plugin_name)?;
+            return Err(e);
+        }
+
+        self.register(plugin_name, plugin);
+        Ok(())
+    }
+
+    /// 加载并注册目录中的动态库插件，返回成功注册的插件名称。
+    /// 不兼容的插件不注册，记录日志并发送错误事件
+    pub async fn load_dir(&self, dir: &Path) -> AppResult<Vec<String>> {
+        let loaded = self.loader.write().unwrap().load_dir(dir)?;
+        let mut registered = Vec::new();
+        for (name, plugin) in loaded {
+            if let Err(e) = Self::check_compatibility(plugin.as_ref()) {
+                log::warn!("拒绝加载插件 {}: {}", name, e);
+                drop(plugin);
+                self.loader.write().unwrap().unload_plugin(&name)?;
+                self.event_sender
+                    .send(RegistryEvent::Error(format!("加载插件 {} 失败: {}", name, e)))
+                    .unwrap_or_default();
+                continue;
+            }
+            self.register(&name, plugin);
+            registered.push(name);
+        }
+        Ok(registered)
+    }
+
+    /// 注册已创建的插件，例如内置插件
+    pub fn register_plugin(&self, plugin_name: &str, plugin: Arc<dyn Plugin>) -> AppResult<()> {
+        Self::check_compatibility(plugin.as_ref())?;
+        self.register(plugin_name, plugin);
+        Ok(())
+    }
+
+    fn register(&self, plugin_name: &str, plugin: Arc<dyn Plugin>) {
         {
             let mut plugins = self.plugins.write().unwrap();
             plugins.insert(plugin_name.to_string(), plugin);
         }
 
         self.event_sender
-            .send(PluginEvent::Loaded(plugin_name.to_string()))
+            .send(RegistryEvent::Loaded(plugin_name.to_string()))
             .unwrap_or_default();
-
-        Ok(())
     }
 
     pub async fn unload_plugin(&self, plugin_name: &str) -> AppResult<()> {
@@ -56,7 +120,7 @@ impl PluginRegistry {
         }
 
         self.event_sender
-            .send(PluginEvent::Unloaded(plugin_name.to_string()))
+            .send(RegistryEvent::Unloaded(plugin_name.to_string()))
             .unwrap_or_default();
 
         Ok(())
@@ -81,6 +145,30 @@ impl PluginRegistry {
             .collect()
     }
 
+    /// 按名称顺序把事件依次交给所有插件，单个插件出错不影响其他插件
+    pub async fn dispatch(&self, event: &PluginEvent) {
+        let mut plugins = self.get_plugins().await;
+        plugins.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, plugin) in plugins {
+            if let Err(e) = plugin.on_event(event).await {
+                self.event_sender
+                    .send(RegistryEvent::Error(format!("插件 {} 处理事件失败: {}", name, e)))
+                    .unwrap_or_default();
+            }
+        }
+    }
+
+    /// 在后台按到达顺序分发事件，所有发送端释放后任务结束
+    pub fn spawn_dispatcher(self: Arc<Self>) -> PluginEventSender {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                self.dispatch(&event).await;
+            }
+        });
+        sender
+    }
+
     pub async fn load_plugins(&self) -> AppResult<()> {
         let plugin_names = {
             let loader = self.loader.read().unwrap();
@@ -90,7 +178,7 @@ impl PluginRegistry {
         for name in plugin_names {
             if let Err(e) = self.load_plugin(&name).await {
                 self.event_sender
-                    .send(PluginEvent::Error(format!("加载插件 {} 失败: {}", name, e)))
+                    .send(RegistryEvent::Error(format!("加载插件 {} 失败: {}", name, e)))
                     .unwrap_or_default();
             }
         }
@@ -107,11 +195,206 @@ impl PluginRegistry {
         for name in plugin_names {
             if let Err(e) = self.unload_plugin(&name).await {
                 self.event_sender
-                    .send(PluginEvent::Error(format!("卸载插件 {} 失败: {}", name, e)))
+                    .send(RegistryEvent::Error(format!("卸载插件 {} 失败: {}", name, e)))
                     .unwrap_or_default();
             }
         }
 
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Activity, PomodoroSession, PomodoroStatus};
+    use async_trait::async_trait;
+    use chrono::Local;
+    use std::any::Any;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    struct TestPlugin {
+        api_version: u32,
+        capabilities: PluginCapabilities,
+        received: Arc<Mutex<Vec<String>>>,
+        fail_events: bool,
+    }
+
+    #[async_trait]
+    impl Plugin for TestPlugin {
+        fn api_version(&self) -> u32 {
+            self.api_version
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            self.capabilities
+        }
+
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> &str {
+            "测试插件"
+        }
+
+        async fn initialize(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn start(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn uninstall(&self) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn get_settings_ui(&self) -> Option<Box<dyn Any>> {
+            None
+        }
+
+        async fn on_event(&self, event: &PluginEvent) -> AppResult<()> {
+            let label = match event {
+                PluginEvent::PomodoroCompleted(session) => format!("completed:{:?}", session.id),
+                PluginEvent::BreakStarted(session) => format!("break:{:?}", session.status),
+                PluginEvent::ActivityRecorded(activity) => format!("activity:{}", activity.name),
+            };
+            self.received.lock().unwrap().push(label);
+            if self.fail_events {
+                return Err(AppError::Plugin("处理失败".into()));
+            }
+            Ok(())
+        }
+    }
+
+    fn test_plugin(api_version: u32, fail_events: bool) -> (Arc<dyn Plugin>, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let plugin = Arc::new(TestPlugin {
+            api_version,
+            capabilities: PluginCapabilities {
+                statistics: true,
+                ..PluginCapabilities::default()
+            },
+            received: received.clone(),
+            fail_events,
+        });
+        (plugin, received)
+    }
+
+    fn plugin(api_version: u32) -> Arc<dyn Plugin> {
+        test_plugin(api_version, false).0
+    }
+
+    fn session(id: i64, status: PomodoroStatus) -> PomodoroSession {
+        PomodoroSession {
+            id: Some(id),
+            start_time: Local::now(),
+            end_time: None,
+            duration: Duration::from_secs(25 * 60),
+            status,
+            project_id: None,
+            notes: None,
+            tags: Vec::new(),
+            category: None,
+            utc_offset: None,
+        }
+    }
+
+    fn activity(name: &str) -> Activity {
+        Activity {
+            id: Some(1),
+            name: name.into(),
+            start_time: Local::now(),
+            end_time: Some(Local::now()),
+            project_id: None,
+            description: None,
+            duration: Duration::from_secs(60),
+            category: "development".into(),
+            is_productive: true,
+            app_name: name.into(),
+            window_title: name.into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_delivered_to_all_plugins_in_order() -> AppResult<()> {
+        let (sender, mut registry_events) = broadcast::channel(8);
+        let registry = Arc::new(PluginRegistry::new(sender));
+        let (failing, failing_received) = test_plugin(PLUGIN_API_VERSION, true);
+        let (recorder, received) = test_plugin(PLUGIN_API_VERSION, false);
+        registry.register_plugin("failing", failing)?;
+        registry.register_plugin("recorder", recorder)?;
+
+        let events = registry.clone().spawn_dispatcher();
+        events.send(PluginEvent::PomodoroCompleted(session(1, PomodoroStatus::Completed))).unwrap();
+        events.send(PluginEvent::BreakStarted(session(2, PomodoroStatus::ShortBreak))).unwrap();
+        events.send(PluginEvent::ActivityRecorded(activity("code"))).unwrap();
+
+        let expected = vec!["completed:Some(1)", "break:ShortBreak", "activity:code"];
+        for _ in 0..100 {
+            if received.lock().unwrap().len() == expected.len() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*received.lock().unwrap(), expected);
+        // 出错的插件同样收到全部事件，错误通过注册表事件报告
+        assert_eq!(*failing_received.lock().unwrap(), expected);
+
+        let errors = std::iter::from_fn(|| registry_events.try_recv().ok())
+            .filter(|event| matches!(event, RegistryEvent::Error(message) if message.contains("failing")))
+            .count();
+        assert_eq!(errors, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_accepts_matching_api_version() -> AppResult<()> {
+        let (sender, mut events) = broadcast::channel(4);
+        let registry = PluginRegistry::new(sender);
+
+        registry.register_plugin("test", plugin(PLUGIN_API_VERSION))?;
+        assert!(registry.get_plugin("test").await.is_some());
+        assert!(matches!(events.try_recv(), Ok(RegistryEvent::Loaded(name)) if name == "test"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_mismatched_api_version() {
+        let (sender, _events) = broadcast::channel(4);
+        let registry = PluginRegistry::new(sender);
+
+        let err = registry.register_plugin("test", plugin(PLUGIN_API_VERSION + 1)).unwrap_err();
+        assert!(matches!(err, AppError::Plugin(message) if message.contains("接口版本")));
+        assert!(registry.get_plugin("test").await.is_none());
+    }
+
+    #[test]
+    fn test_unsupported_capabilities() {
+        let required = PluginCapabilities {
+            backup: true,
+            settings_ui: true,
+            ..PluginCapabilities::default()
+        };
+        let host = PluginCapabilities {
+            settings_ui: false,
+            ..PluginCapabilities::supported()
+        };
+        assert_eq!(required.unsupported_by(&host), vec!["settings_ui"]);
+        assert!(required.unsupported_by(&PluginCapabilities::supported()).is_empty());
+    }
+}
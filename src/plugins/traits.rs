@@ -1,10 +1,80 @@
+use crate::core::models::{Activity, PomodoroSession};
 use crate::core::AppResult;
 use async_trait::async_trait;
 use std::any::Any;
+use tokio::sync::mpsc;
+
+/// 插件接口版本，`Plugin` 及相关 trait 发生不兼容变更时递增
+pub const PLUGIN_API_VERSION: u32 = 2;
+
+/// 宿主广播给所有插件的事件
+#[derive(Debug, Clone)]
+pub enum PluginEvent {
+    /// 工作番茄完成，会话已保存
+    PomodoroCompleted(PomodoroSession),
+    /// 休息开始，会话状态为短休息或长休息
+    BreakStarted(PomodoroSession),
+    /// 活动结束并已保存
+    ActivityRecorded(Activity),
+}
+
+/// 番茄钟和活动追踪通过该通道把事件交给插件注册表，按发送顺序分发
+pub type PluginEventSender = mpsc::UnboundedSender<PluginEvent>;
+
+/// 插件使用的扩展接口，加载时与宿主支持的能力协商
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PluginCapabilities {
+    pub activity: bool,
+    pub pomodoro: bool,
+    pub statistics: bool,
+    pub notification: bool,
+    pub backup: bool,
+    pub settings_ui: bool,
+}
+
+impl PluginCapabilities {
+    /// 当前宿主支持的能力
+    pub fn supported() -> Self {
+        Self {
+            activity: true,
+            pomodoro: true,
+            statistics: true,
+            notification: true,
+            backup: true,
+            settings_ui: true,
+        }
+    }
+
+    /// 宿主不支持的能力名称
+    pub fn unsupported_by(&self, host: &PluginCapabilities) -> Vec<&'static str> {
+        [
+            ("activity", self.activity, host.activity),
+            ("pomodoro", self.pomodoro, host.pomodoro),
+            ("statistics", self.statistics, host.statistics),
+            ("notification", self.notification, host.notification),
+            ("backup", self.backup, host.backup),
+            ("settings_ui", self.settings_ui, host.settings_ui),
+        ]
+        .into_iter()
+        .filter(|(_, required, supported)| *required && !*supported)
+        .map(|(name, _, _)| name)
+        .collect()
+    }
+}
 
 /// 插件接口
 #[async_trait]
 pub trait Plugin: Send + Sync {
+    /// 插件编译时使用的接口版本，默认实现在插件一侧展开，无需手动覆盖
+    fn api_version(&self) -> u32 {
+        PLUGIN_API_VERSION
+    }
+
+    /// 插件实现的扩展接口
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities::default()
+    }
+
     /// 获取插件名称
     fn name(&self) -> &str;
     
@@ -28,6 +98,11 @@ pub trait Plugin: Send + Sync {
     
     /// 获取插件配置界面
     fn get_settings_ui(&self) -> Option<Box<dyn Any>>;
+
+    /// 处理宿主广播的事件，默认忽略
+    async fn on_event(&self, _event: &PluginEvent) -> AppResult<()> {
+        Ok(())
+    }
 }
 
 /// 活动插件接口
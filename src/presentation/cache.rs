@@ -0,0 +1,212 @@
+use crate::application::events::AppEvent;
+use crate::core::AppResult;
+use crate::domain::config::RefreshSettings;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedView {
+    Overview,
+    Activities,
+    Statistics,
+    Pomodoro,
+    Projects,
+}
+
+impl CachedView {
+    pub fn ttl(self, settings: &RefreshSettings) -> Duration {
+        match self {
+            CachedView::Overview => settings.overview,
+            CachedView::Activities => settings.activities,
+            CachedView::Statistics => settings.statistics,
+            CachedView::Pomodoro => settings.pomodoro,
+            CachedView::Projects => settings.projects,
+        }
+    }
+
+    /// 该事件是否改变了视图所显示的数据
+    pub fn invalidated_by(self, event: &AppEvent) -> bool {
+        match event {
+            AppEvent::ActivityStarted(_) | AppEvent::ActivityStopped(_) | AppEvent::ActivityUpdated(_) => matches!(
+                self,
+                CachedView::Overview | CachedView::Activities | CachedView::Statistics
+            ),
+            AppEvent::ProjectCreated(_) | AppEvent::ProjectUpdated(_) | AppEvent::ProjectDeleted(_) => matches!(
+                self,
+                CachedView::Overview | CachedView::Statistics | CachedView::Projects
+            ),
+            AppEvent::PomodoroStarted(_) | AppEvent::PomodoroCompleted(_) | AppEvent::PomodoroInterrupted(_) => {
+                matches!(
+                    self,
+                    CachedView::Overview | CachedView::Statistics | CachedView::Pomodoro
+                )
+            }
+            AppEvent::PomodoroPaused(_) | AppEvent::PomodoroResumed(_) => self == CachedView::Pomodoro,
+            AppEvent::ConfigUpdated => true,
+            _ => false,
+        }
+    }
+}
+
+/// 按 (视图, 时间范围) 缓存查询结果，在 TTL 内重复渲染不会重新查询
+pub struct ViewCache<R, V> {
+    settings: RefreshSettings,
+    entries: Mutex<HashMap<(CachedView, R), (Instant, V)>>,
+}
+
+impl<R, V> ViewCache<R, V>
+where
+    R: Hash + Eq,
+    V: Clone,
+{
+    pub fn new(settings: RefreshSettings) -> Self {
+        Self {
+            settings,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_settings(&mut self, settings: RefreshSettings) {
+        self.settings = settings;
+    }
+
+    pub fn get(&self, view: CachedView, range: &R) -> Option<V>
+    where
+        R: Clone,
+    {
+        let ttl = view.ttl(&self.settings);
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(&(view, range.clone()))
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < ttl)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// 缓存有效时直接返回，否则执行查询并缓存结果，查询失败不缓存
+    pub async fn get_or_load<F, Fut>(&self, view: CachedView, range: R, load: F) -> AppResult<V>
+    where
+        R: Clone,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<V>>,
+    {
+        if let Some(value) = self.get(view, &range) {
+            return Ok(value);
+        }
+
+        let value = load().await?;
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((view, range), (Instant::now(), value.clone()));
+        Ok(value)
+    }
+
+    pub fn invalidate(&self, view: CachedView) {
+        self.entries.lock().unwrap().retain(|(cached, _), _| *cached != view);
+    }
+
+    pub fn handle_event(&self, event: &AppEvent) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(view, _), _| !view.invalidated_by(event));
+    }
+}
+
+impl<R, V> Default for ViewCache<R, V>
+where
+    R: Hash + Eq,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new(RefreshSettings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Project;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_query_runs_once_within_ttl() -> AppResult<()> {
+        let cache: ViewCache<u32, u64> = ViewCache::default();
+        let counter = AtomicUsize::new(0);
+        let calls = &counter;
+        let query = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(42)
+        };
+
+        // 多次渲染只查询一次
+        for _ in 0..5 {
+            assert_eq!(cache.get_or_load(CachedView::Statistics, 7, query).await?, 42);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // 其他时间范围单独缓存
+        cache.get_or_load(CachedView::Statistics, 30, query).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 无关事件不影响缓存
+        cache.handle_event(&AppEvent::WindowShown);
+        cache.get_or_load(CachedView::Statistics, 7, query).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 数据变化后重新查询
+        let project = Arc::new(Project::new("Client".into(), None));
+        cache.handle_event(&AppEvent::ProjectUpdated(project));
+        cache.get_or_load(CachedView::Statistics, 7, query).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_query_is_not_cached() -> AppResult<()> {
+        let cache: ViewCache<u32, u64> = ViewCache::default();
+        let counter = AtomicUsize::new(0);
+        let calls = &counter;
+
+        let failing = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(crate::core::AppError::System("database is locked".into()))
+        };
+        assert!(cache.get_or_load(CachedView::Statistics, 7, failing).await.is_err());
+
+        // 失败后下一次渲染重新查询
+        let query = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(42)
+        };
+        assert_eq!(cache.get_or_load(CachedView::Statistics, 7, query).await?, 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_reloaded() -> AppResult<()> {
+        let cache: ViewCache<u32, u64> = ViewCache::new(RefreshSettings {
+            statistics: Duration::ZERO,
+            ..RefreshSettings::default()
+        });
+        let counter = AtomicUsize::new(0);
+        let calls = &counter;
+        let query = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(1)
+        };
+
+        cache.get_or_load(CachedView::Statistics, 7, query).await?;
+        cache.get_or_load(CachedView::Statistics, 7, query).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+}
@@ -24,6 +24,8 @@ impl EventHandler {
     }
 
     async fn handle_event(&self, event: AppEvent) {
+        self.state.read().await.stats_cache.handle_event(&event);
+
         match event {
             AppEvent::ActivityStarted(activity) => {
                 let mut state = self.state.write().await;
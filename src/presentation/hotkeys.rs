@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use crate::core::{AppError, AppResult};
+use crate::infrastructure::platform::PlatformOperations;
+use crate::presentation::ui::Message;
+
+/// 快捷键可触发的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    ToggleWindow,
+    ToggleTracking,
+    /// 跳过当前番茄钟阶段，通常用于提前结束休息
+    SkipBreak,
+    /// 撤销最近一次删除，只在窗口内生效
+    Undo,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 4] = [
+        HotkeyAction::ToggleWindow,
+        HotkeyAction::ToggleTracking,
+        HotkeyAction::SkipBreak,
+        HotkeyAction::Undo,
+    ];
+
+    /// 向系统注册全局快捷键时使用的 id
+    pub fn id(&self) -> &'static str {
+        match self {
+            HotkeyAction::ToggleWindow => "toggle_window",
+            HotkeyAction::ToggleTracking => "toggle_tracking",
+            HotkeyAction::SkipBreak => "skip_break",
+            HotkeyAction::Undo => "undo",
+        }
+    }
+
+    /// 注册为系统全局快捷键会抢占其他程序的同名快捷键，例如 Ctrl+Z
+    pub fn is_global(&self) -> bool {
+        !matches!(self, HotkeyAction::Undo)
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.id() == id)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            HotkeyAction::ToggleWindow => "显示/隐藏窗口",
+            HotkeyAction::ToggleTracking => "暂停/恢复记录",
+            HotkeyAction::SkipBreak => "跳过当前阶段",
+            HotkeyAction::Undo => "撤销",
+        }
+    }
+
+    pub fn to_message(self) -> Message {
+        match self {
+            HotkeyAction::ToggleWindow => Message::ToggleWindow,
+            HotkeyAction::ToggleTracking => Message::ToggleTracking,
+            HotkeyAction::SkipBreak => Message::SkipPomodoroPhase,
+            HotkeyAction::Undo => Message::Undo,
+        }
+    }
+}
+
+const MODIFIERS: [&str; 4] = ["ctrl", "alt", "shift", "win"];
+
+/// 不区分左右修饰键；`command` 在 macOS 上对应 Command 键，其他平台上视为 Ctrl
+fn canonical_modifier(name: &str) -> Option<&'static str> {
+    let name = name
+        .strip_prefix("left")
+        .or_else(|| name.strip_prefix("right"))
+        .or_else(|| name.strip_prefix('l').filter(|rest| rest.len() > 2))
+        .or_else(|| name.strip_prefix('r').filter(|rest| rest.len() > 2))
+        .unwrap_or(name);
+    match name {
+        "ctrl" | "control" => Some("ctrl"),
+        "alt" | "option" => Some("alt"),
+        "shift" => Some("shift"),
+        "win" | "super" | "meta" => Some("win"),
+        "cmd" | "command" if cfg!(target_os = "macos") => Some("win"),
+        "cmd" | "command" => Some("ctrl"),
+        _ => None,
+    }
+}
+
+fn canonical_key(name: &str) -> &str {
+    match name {
+        "esc" => "escape",
+        "return" => "enter",
+        "del" => "delete",
+        "ins" => "insert",
+        "pgup" => "pageup",
+        "pgdn" => "pagedown",
+        _ => name,
+    }
+}
+
+/// 统一大小写、空格、别名和修饰键顺序，用于比较两个快捷键是否相同，结果再次规范化时保持不变
+pub fn normalize_shortcut(shortcut: &str) -> String {
+    let mut modifiers = Vec::new();
+    let mut keys = Vec::new();
+    for part in shortcut.split('+').map(|part| part.trim().to_ascii_lowercase()) {
+        match canonical_modifier(&part) {
+            Some(modifier) => modifiers.push(MODIFIERS.iter().position(|m| *m == modifier).unwrap()),
+            None if !part.is_empty() => keys.push(canonical_key(&part).to_string()),
+            None => {}
+        }
+    }
+    modifiers.sort_unstable();
+    modifiers.dedup();
+    modifiers
+        .into_iter()
+        .map(|index| MODIFIERS[index].to_string())
+        .chain(keys)
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// 各操作绑定的快捷键，空字符串表示未绑定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyConfig {
+    pub bindings: HashMap<HotkeyAction, String>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (HotkeyAction::ToggleWindow, "Ctrl+Shift+T".to_string()),
+                (HotkeyAction::ToggleTracking, "Ctrl+Shift+P".to_string()),
+                (HotkeyAction::SkipBreak, "Ctrl+Shift+S".to_string()),
+                (HotkeyAction::Undo, "Ctrl+Z".to_string()),
+            ]),
+        }
+    }
+}
+
+impl HotkeyConfig {
+    /// 绑定了相同快捷键的操作对，按 `HotkeyAction::ALL` 的顺序排列
+    pub fn find_conflicts(&self) -> Vec<(HotkeyAction, HotkeyAction)> {
+        let normalized: Vec<(HotkeyAction, String)> = HotkeyAction::ALL
+            .into_iter()
+            .filter_map(|action| {
+                let shortcut = normalize_shortcut(self.bindings.get(&action)?);
+                (!shortcut.is_empty()).then_some((action, shortcut))
+            })
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for (i, (first, shortcut)) in normalized.iter().enumerate() {
+            for (second, other) in &normalized[i + 1..] {
+                if shortcut == other {
+                    conflicts.push((*first, *second));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// 与 `action` 使用相同快捷键的其他操作
+    pub fn conflicts_with(&self, action: HotkeyAction) -> Vec<HotkeyAction> {
+        self.find_conflicts()
+            .into_iter()
+            .filter_map(|(first, second)| {
+                if first == action {
+                    Some(second)
+                } else if second == action {
+                    Some(first)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// 窗口内按键对应的操作
+    pub fn action_for(&self, shortcut: &str) -> Option<HotkeyAction> {
+        let shortcut = normalize_shortcut(shortcut);
+        HotkeyAction::ALL.into_iter().find(|action| {
+            self.bindings
+                .get(action)
+                .map_or(false, |binding| !shortcut.is_empty() && normalize_shortcut(binding) == shortcut)
+        })
+    }
+
+    fn check_conflicts(&self) -> AppResult<()> {
+        match self.find_conflicts().first() {
+            Some((first, second)) => Err(AppError::InvalidOperation(format!(
+                "“{}”与“{}”使用了相同的快捷键",
+                first.description(),
+                second.description()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 把配置的快捷键同步为系统全局快捷键，窗口未获得焦点时也能触发
+pub struct HotkeyManager {
+    config: Mutex<HotkeyConfig>,
+    /// 当前已向系统注册的操作
+    registered: Mutex<Vec<HotkeyAction>>,
+}
+
+impl HotkeyManager {
+    pub fn new(config: HotkeyConfig) -> Self {
+        Self {
+            config: Mutex::new(config),
+            registered: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn config(&self) -> HotkeyConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// 存在冲突时不修改，修改后需调用 `register_global` 才会生效
+    pub fn set_config(&self, config: HotkeyConfig) -> AppResult<()> {
+        config.check_conflicts()?;
+        *self.config.lock().unwrap() = config;
+        Ok(())
+    }
+
+    /// 新快捷键与其他操作重复时返回错误
+    pub fn set_hotkey(&self, action: HotkeyAction, shortcut: &str) -> AppResult<()> {
+        let mut config = self.config();
+        config.bindings.insert(action, shortcut.to_string());
+        self.set_config(config)
+    }
+
+    /// 先注销上一次注册的快捷键再按当前配置重新注册，单个快捷键失败不影响其他快捷键，
+    /// 全部尝试后返回第一个错误
+    pub fn register_global(&self, platform: &dyn PlatformOperations) -> AppResult<()> {
+        let mut registered = self.registered.lock().unwrap();
+        for action in registered.drain(..) {
+            platform.unregister_global_shortcut(action.id())?;
+        }
+
+        let config = self.config();
+        let mut first_error = None;
+        for action in HotkeyAction::ALL.into_iter().filter(HotkeyAction::is_global) {
+            let Some(shortcut) = config.bindings.get(&action).filter(|s| !s.trim().is_empty()) else {
+                continue;
+            };
+            match platform.register_global_shortcut(shortcut, action.id()) {
+                Ok(()) => registered.push(action),
+                Err(e) => {
+                    log::warn!("注册快捷键 {} ({}) 失败: {}", shortcut, action.description(), e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// 把平台层通过 `watch_global_shortcuts` 发送的 id 转换为界面消息
+    pub fn spawn(
+        self: Arc<Self>,
+        mut shortcuts: mpsc::UnboundedReceiver<String>,
+        messages: mpsc::UnboundedSender<Message>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(id) = shortcuts.recv().await {
+                let Some(action) = HotkeyAction::from_id(&id) else {
+                    log::warn!("未知的快捷键 id: {}", id);
+                    continue;
+                };
+                // 只处理仍在注册中的快捷键，注销前已排队的事件被丢弃
+                if !self.registered.lock().unwrap().contains(&action) {
+                    continue;
+                }
+                if messages.send(action.to_message()).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::AppError;
+    use crate::infrastructure::platform::WindowInfo;
+
+    /// 记录注册情况，"Ctrl+Esc" 模拟被其他程序占用的快捷键
+    #[derive(Default)]
+    struct FakePlatform {
+        shortcuts: Mutex<HashMap<String, String>>,
+    }
+
+    impl PlatformOperations for FakePlatform {
+        fn get_active_window(&self) -> AppResult<WindowInfo> {
+            Err(AppError::NotFound("window".into()))
+        }
+
+        fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+            Ok(())
+        }
+
+        fn is_autostart_enabled(&self) -> AppResult<bool> {
+            Ok(false)
+        }
+
+        fn register_global_shortcut(&self, shortcut: &str, id: &str) -> AppResult<()> {
+            if shortcut == "Ctrl+Esc" {
+                return Err(AppError::InvalidOperation(shortcut.into()));
+            }
+            self.shortcuts.lock().unwrap().insert(id.into(), shortcut.into());
+            Ok(())
+        }
+
+        fn unregister_global_shortcut(&self, id: &str) -> AppResult<()> {
+            self.shortcuts.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_global_syncs_bindings() -> AppResult<()> {
+        let platform = FakePlatform::default();
+        let manager = Arc::new(HotkeyManager::new(HotkeyConfig {
+            bindings: HashMap::from([
+                (HotkeyAction::ToggleTracking, "Ctrl+Space".to_string()),
+                (HotkeyAction::SkipBreak, "Ctrl+Esc".to_string()),
+                (HotkeyAction::Undo, "Ctrl+Z".to_string()),
+            ]),
+        }));
+        assert!(manager.register_global(&platform).is_err());
+        assert_eq!(
+            *platform.shortcuts.lock().unwrap(),
+            HashMap::from([("toggle_tracking".to_string(), "Ctrl+Space".to_string())])
+        );
+
+        let (shortcuts, receiver) = mpsc::unbounded_channel();
+        let (messages, mut received) = mpsc::unbounded_channel();
+        let handle = manager.clone().spawn(receiver, messages);
+        shortcuts.send("skip_break".to_string()).unwrap();
+        shortcuts.send("toggle_tracking".to_string()).unwrap();
+        assert!(matches!(received.recv().await, Some(Message::ToggleTracking)));
+
+        // 重新同步时移除不再配置的快捷键
+        manager.set_config(HotkeyConfig {
+            bindings: HashMap::from([(HotkeyAction::ToggleWindow, "Alt+T".to_string())]),
+        })?;
+        manager.register_global(&platform)?;
+        assert_eq!(
+            *platform.shortcuts.lock().unwrap(),
+            HashMap::from([("toggle_window".to_string(), "Alt+T".to_string())])
+        );
+
+        drop(shortcuts);
+        handle.await.unwrap();
+        assert!(received.recv().await.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_conflicts() -> AppResult<()> {
+        let mut config = HotkeyConfig::default();
+        assert!(config.find_conflicts().is_empty());
+
+        // 大小写和修饰键顺序不同也视为同一个快捷键
+        config.bindings.insert(HotkeyAction::SkipBreak, "shift + CTRL + t".into());
+        assert_eq!(
+            config.find_conflicts(),
+            vec![(HotkeyAction::ToggleWindow, HotkeyAction::SkipBreak)]
+        );
+        assert_eq!(config.conflicts_with(HotkeyAction::ToggleWindow), vec![HotkeyAction::SkipBreak]);
+        assert!(config.conflicts_with(HotkeyAction::ToggleTracking).is_empty());
+
+        // 未绑定的操作不会冲突
+        config.bindings.insert(HotkeyAction::SkipBreak, String::new());
+        config.bindings.insert(HotkeyAction::ToggleTracking, " ".into());
+        assert!(config.find_conflicts().is_empty());
+
+        assert_eq!(HotkeyConfig::default().action_for("ctrl+z"), Some(HotkeyAction::Undo));
+        assert_eq!(HotkeyConfig::default().action_for("Ctrl+Y"), None);
+
+        let manager = HotkeyManager::new(HotkeyConfig::default());
+        assert!(manager.set_hotkey(HotkeyAction::SkipBreak, "Ctrl+Shift+P").is_err());
+        assert_eq!(manager.config(), HotkeyConfig::default());
+        manager.set_hotkey(HotkeyAction::SkipBreak, "Ctrl+Alt+S")?;
+        assert_eq!(manager.config().bindings[&HotkeyAction::SkipBreak], "Ctrl+Alt+S");
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_shortcut_aliases() {
+        assert_eq!(normalize_shortcut("LCtrl + RShift + Esc"), "ctrl+shift+escape");
+        assert_eq!(normalize_shortcut("RightAlt+Return"), "alt+enter");
+        let command = if cfg!(target_os = "macos") { "win+space" } else { "ctrl+space" };
+        assert_eq!(normalize_shortcut("Command+Space"), command);
+
+        for shortcut in ["Ctrl+Shift+T", "cmd+del", "Super+F12", "Option+PgDn", "Shift+Ctrl+Alt+Win+K"] {
+            let normalized = normalize_shortcut(shortcut);
+            assert_eq!(normalize_shortcut(&normalized), normalized);
+        }
+    }
+}
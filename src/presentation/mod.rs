@@ -1,9 +1,14 @@
 pub mod ui;
 pub mod tray;
+pub mod tray_icon;
 pub mod window;
 pub mod views;
 pub mod state;
+pub mod cache;
+pub mod settings;
+pub mod hotkeys;
 
 pub use ui::TimeTrackerApp;
 pub use tray::TrayManager;
-pub use window::Window; 
\ No newline at end of file
+pub use window::Window;
+pub use settings::SettingsModel; 
\ No newline at end of file
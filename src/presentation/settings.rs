@@ -0,0 +1,171 @@
+use crate::core::{AppError, AppResult};
+use crate::domain::config::{AppConfig, PomodoroEvent};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 设置界面共用的可编辑字段，保存时统一转换回 `AppConfig`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsModel {
+    pub general: GeneralSettings,
+    pub pomodoro: PomodoroSettings,
+    pub notification: NotificationSettings,
+    // 界面未涉及的配置原样保留
+    base: AppConfig,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneralSettings {
+    pub theme: String,
+    pub language: String,
+    pub show_system_tray: bool,
+    pub minimize_to_tray: bool,
+    pub start_minimized: bool,
+    pub start_hidden: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroSettings {
+    pub work_duration: Duration,
+    pub short_break_duration: Duration,
+    pub long_break_duration: Duration,
+    pub long_break_interval: u32,
+    pub sound_profile: HashMap<PomodoroEvent, Option<PathBuf>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotificationSettings {
+    pub enable_system_notifications: bool,
+    pub enable_sound: bool,
+    pub sound_volume: f32,
+}
+
+impl SettingsModel {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            general: GeneralSettings {
+                theme: config.ui.theme.clone(),
+                language: config.ui.language.clone(),
+                show_system_tray: config.ui.show_system_tray,
+                minimize_to_tray: config.ui.minimize_to_tray,
+                start_minimized: config.ui.start_minimized,
+                start_hidden: config.ui.start_hidden,
+            },
+            pomodoro: PomodoroSettings {
+                work_duration: config.pomodoro.work_duration,
+                short_break_duration: config.pomodoro.short_break_duration,
+                long_break_duration: config.pomodoro.long_break_duration,
+                long_break_interval: config.pomodoro.long_break_interval,
+                sound_profile: config.pomodoro.sound_profile.clone(),
+            },
+            notification: NotificationSettings {
+                enable_system_notifications: config.notification.enable_system_notifications,
+                enable_sound: config.notification.enable_sound,
+                sound_volume: config.notification.sound_volume,
+            },
+            base: config.clone(),
+        }
+    }
+
+    pub fn to_config(&self) -> AppConfig {
+        let mut config = self.base.clone();
+
+        config.ui.theme = self.general.theme.clone();
+        config.ui.language = self.general.language.clone();
+        config.ui.show_system_tray = self.general.show_system_tray;
+        config.ui.minimize_to_tray = self.general.minimize_to_tray;
+        config.ui.start_minimized = self.general.start_minimized;
+        config.ui.start_hidden = self.general.start_hidden;
+
+        config.pomodoro.work_duration = self.pomodoro.work_duration;
+        config.pomodoro.short_break_duration = self.pomodoro.short_break_duration;
+        config.pomodoro.long_break_duration = self.pomodoro.long_break_duration;
+        config.pomodoro.long_break_interval = self.pomodoro.long_break_interval;
+        config.pomodoro.sound_profile = self.pomodoro.sound_profile.clone();
+
+        config.notification.enable_system_notifications = self.notification.enable_system_notifications;
+        config.notification.enable_sound = self.notification.enable_sound;
+        config.notification.sound_volume = self.notification.sound_volume;
+
+        config
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        if self.general.theme.is_empty() {
+            return Err(AppError::Config("主题不能为空".into()));
+        }
+        if self.general.language.is_empty() {
+            return Err(AppError::Config("语言不能为空".into()));
+        }
+        if self.pomodoro.work_duration.is_zero() {
+            return Err(AppError::Config("工作时长必须大于0".into()));
+        }
+        if self.pomodoro.short_break_duration.is_zero() {
+            return Err(AppError::Config("短休息时长必须大于0".into()));
+        }
+        if self.pomodoro.long_break_duration.is_zero() {
+            return Err(AppError::Config("长休息时长必须大于0".into()));
+        }
+        if self.pomodoro.long_break_interval == 0 {
+            return Err(AppError::Config("长休息间隔必须大于0".into()));
+        }
+        if !(0.0..=1.0).contains(&self.notification.sound_volume) {
+            return Err(AppError::Config("音量必须在 0 到 1 之间".into()));
+        }
+        self.to_config().pomodoro.validate_sound_profile()
+    }
+
+    /// 解析以分钟为单位的输入
+    pub fn parse_minutes(input: &str) -> AppResult<Duration> {
+        input
+            .trim()
+            .parse::<u64>()
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .map_err(|_| AppError::Config(format!("无效的分钟数: {}", input)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_default_config() {
+        let config = AppConfig::default();
+        assert_eq!(SettingsModel::from_config(&config).to_config(), config);
+    }
+
+    #[test]
+    fn test_round_trip_preserves_unedited_sections() {
+        let mut config = AppConfig::default();
+        config.ui.theme = "dark".into();
+        config.pomodoro.work_duration = Duration::from_secs(50 * 60 + 30);
+        config.notification.sound_volume = 0.25;
+        config.storage.backup_path = "/tmp/backups".into();
+        config.rules.suggestion_threshold = 3;
+
+        assert_eq!(SettingsModel::from_config(&config).to_config(), config);
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_duration() {
+        let mut model = SettingsModel::from_config(&AppConfig::default());
+        assert!(model.validate().is_ok());
+
+        model.pomodoro.work_duration = Duration::ZERO;
+        assert!(model.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_sound_file() {
+        let mut model = SettingsModel::from_config(&AppConfig::default());
+        model
+            .pomodoro
+            .sound_profile
+            .insert(PomodoroEvent::WorkComplete, Some(PathBuf::from("/nonexistent/chime.wav")));
+        assert!(model.validate().is_err());
+
+        model.pomodoro.sound_profile.insert(PomodoroEvent::WorkComplete, None);
+        assert!(model.validate().is_ok());
+    }
+}
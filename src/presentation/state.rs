@@ -1,5 +1,7 @@
 use crate::core::models::{Activity, PomodoroSession, Project};
 use crate::domain::analysis::{ProductivityStats, CategoryStats, PomodoroStats};
+use crate::presentation::cache::ViewCache;
+use crate::presentation::views::statistics::TimeRange;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -15,8 +17,11 @@ pub struct AppState {
     pub category_stats: Vec<CategoryStats>,
     pub pomodoro_stats: Option<PomodoroStats>,
     pub daily_distribution: Vec<(u32, std::time::Duration)>,
+    pub stats_cache: Arc<ViewCache<TimeRange, StatsSnapshot>>,
 }
 
+pub type StatsSnapshot = (ProductivityStats, Vec<CategoryStats>, PomodoroStats);
+
 pub type SharedState = Arc<RwLock<AppState>>;
 
 impl AppState {
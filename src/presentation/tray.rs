@@ -1,9 +1,12 @@
-use std::sync::Arc;
-use tray_item::TrayItem;
+use std::sync::{Arc, Mutex};
+use tray_item::{IconSource, TrayItem};
 use crate::core::AppResult;
+use super::tray_icon::TrayState;
+#[cfg(all(target_os = "linux", feature = "ksni"))]
+use super::tray_icon::{render_state_icon, TrayIcon};
 
 pub struct TrayManager {
-    tray: Arc<TrayItem>,
+    tray: Arc<Mutex<TrayItem>>,
 }
 
 impl TrayManager {
@@ -11,7 +14,7 @@ impl TrayManager {
         let tray = TrayItem::new("Time Tracker", "time-tracker-tray")?;
         
         Ok(Self {
-            tray: Arc::new(tray),
+            tray: Arc::new(Mutex::new(tray)),
         })
     }
 
@@ -24,12 +27,41 @@ impl TrayManager {
     }
 
     pub fn set_icon(&self, icon_path: &str) -> AppResult<()> {
-        self.tray.set_icon(icon_path)?;
+        self.tray.lock().unwrap().set_icon(icon_path)?;
+        Ok(())
+    }
+
+    /// 内存中的图标只有 ksni 托盘支持
+    #[cfg(all(target_os = "linux", feature = "ksni"))]
+    pub fn set_icon_image(&self, icon: &TrayIcon) -> AppResult<()> {
+        self.tray.lock().unwrap().set_icon(IconSource::Data {
+            width: icon.width as i32,
+            height: icon.height as i32,
+            data: icon.rgba.clone(),
+        })?;
+        Ok(())
+    }
+
+    /// 根据番茄钟状态和进度刷新托盘图标
+    #[cfg(all(target_os = "linux", feature = "ksni"))]
+    pub fn update_state(&self, state: TrayState, progress: f32) -> AppResult<()> {
+        self.set_icon_image(&render_state_icon(state, progress))
+    }
+
+    /// 其他平台使用随程序打包的图标资源，只区分状态不显示进度
+    #[cfg(not(all(target_os = "linux", feature = "ksni")))]
+    pub fn update_state(&self, state: TrayState, _progress: f32) -> AppResult<()> {
+        let resource = match state {
+            TrayState::Idle => "time-tracker-idle",
+            TrayState::Working => "time-tracker-working",
+            TrayState::Break => "time-tracker-break",
+        };
+        self.tray.lock().unwrap().set_icon(IconSource::Resource(resource))?;
         Ok(())
     }
 
     pub fn set_tooltip(&self, tooltip: &str) -> AppResult<()> {
-        self.tray.set_tooltip(tooltip)?;
+        self.tray.lock().unwrap().set_tooltip(tooltip)?;
         Ok(())
     }
 } 
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::f32::consts::PI;
+
+pub const ICON_SIZE: u32 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayState {
+    Idle,
+    Working,
+    Break,
+}
+
+/// 内存中生成的 RGBA 托盘图标
+#[derive(Debug, Clone)]
+pub struct TrayIcon {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl TrayIcon {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            rgba: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    fn put_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let offset = ((y * self.width + x) * 4) as usize;
+        self.rgba[offset..offset + 4].copy_from_slice(&color);
+    }
+}
+
+const WORK_COLOR: [u8; 4] = [0xE5, 0x39, 0x35, 0xFF];
+const BREAK_COLOR: [u8; 4] = [0x43, 0xA0, 0x47, 0xFF];
+const IDLE_COLOR: [u8; 4] = [0x9E, 0x9E, 0x9E, 0xFF];
+const TRACK_COLOR: [u8; 4] = [0x9E, 0x9E, 0x9E, 0x60];
+
+/// 绘制托盘图标：工作/休息时显示进度环，空闲时显示灰色圆点
+pub fn render_state_icon(state: TrayState, progress: f32) -> TrayIcon {
+    let mut icon = TrayIcon::new(ICON_SIZE, ICON_SIZE);
+    let progress = progress.clamp(0.0, 1.0);
+    let center = ICON_SIZE as f32 / 2.0;
+    let outer = center - 1.0;
+    let inner = outer - 5.0;
+
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            let color = match state {
+                TrayState::Idle => (distance <= inner).then_some(IDLE_COLOR),
+                TrayState::Working | TrayState::Break => {
+                    if distance > outer {
+                        None
+                    } else if distance >= inner {
+                        // 从 12 点方向顺时针计算角度
+                        let angle = (dx.atan2(-dy) + 2.0 * PI) % (2.0 * PI);
+                        if angle / (2.0 * PI) <= progress {
+                            Some(state_color(state))
+                        } else {
+                            Some(TRACK_COLOR)
+                        }
+                    } else if distance <= 3.0 {
+                        Some(state_color(state))
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            if let Some(color) = color {
+                icon.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    icon
+}
+
+fn state_color(state: TrayState) -> [u8; 4] {
+    match state {
+        TrayState::Working => WORK_COLOR,
+        TrayState::Break => BREAK_COLOR,
+        TrayState::Idle => IDLE_COLOR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_state_icon_for_each_state() {
+        for state in [TrayState::Idle, TrayState::Working, TrayState::Break] {
+            let icon = render_state_icon(state, 0.5);
+            assert_eq!(icon.rgba.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+            assert!(icon.rgba.chunks(4).any(|pixel| pixel[3] != 0));
+        }
+    }
+
+    #[test]
+    fn test_progress_changes_icon() {
+        let start = render_state_icon(TrayState::Working, 0.0);
+        let done = render_state_icon(TrayState::Working, 1.0);
+        assert_ne!(start.rgba, done.rgba);
+    }
+}
@@ -1,16 +1,240 @@
 use iced::{
-    mouse,
-    widget::canvas::{self, Frame, Geometry, Path, Program, Renderer, Stroke},
-    Color, Element, Length, Point, Rectangle, Size, Theme,
+    alignment, mouse,
+    widget::canvas::{self, Frame, Geometry, Path, Program, Renderer, Stroke, Text},
+    Color, Element, Length, Point, Rectangle, Size, Theme, Vector,
 };
 use crate::presentation::ui::Message;
 
+/// 未指定颜色时按顺序使用
+const SERIES_COLORS: [Color; 6] = [
+    Color { r: 0.2, g: 0.6, b: 0.9, a: 1.0 },
+    Color { r: 0.95, g: 0.55, b: 0.17, a: 1.0 },
+    Color { r: 0.35, g: 0.63, b: 0.31, a: 1.0 },
+    Color { r: 0.88, g: 0.34, b: 0.35, a: 1.0 },
+    Color { r: 0.69, g: 0.48, b: 0.63, a: 1.0 },
+    Color { r: 0.61, g: 0.46, b: 0.37, a: 1.0 },
+];
+
+const AXIS_WIDTH: f32 = 40.0;
+const LABEL_HEIGHT: f32 = 18.0;
+const LEGEND_HEIGHT: f32 = 20.0;
+const TEXT_SIZE: f32 = 12.0;
+
+/// 一组带名称的数值，按 `ChartData` 中的标签顺序排列
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub values: Vec<f32>,
+    pub color: Option<Color>,
+}
+
+impl Series {
+    pub fn new(name: impl Into<String>, values: Vec<f32>) -> Self {
+        Self {
+            name: name.into(),
+            values,
+            color: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartData {
+    Line { labels: Vec<String>, series: Vec<Series> },
+    /// 每个标签下各系列并排显示
+    Bar { labels: Vec<String>, series: Vec<Series> },
+    /// 每个标签下各系列从下往上叠加
+    StackedBar { labels: Vec<String>, series: Vec<Series> },
+    Pie { labels: Vec<String>, values: Vec<f32> },
+}
+
+/// 鼠标悬停处的数值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tooltip {
+    pub label: String,
+    pub series: String,
+    pub value: f32,
+}
+
+impl ChartData {
+    fn labels(&self) -> &[String] {
+        match self {
+            ChartData::Line { labels, .. }
+            | ChartData::Bar { labels, .. }
+            | ChartData::StackedBar { labels, .. }
+            | ChartData::Pie { labels, .. } => labels,
+        }
+    }
+
+    /// 图例项：名称和颜色
+    fn legend(&self) -> Vec<(&str, Color)> {
+        match self {
+            ChartData::Line { series, .. } | ChartData::Bar { series, .. } | ChartData::StackedBar { series, .. } => series
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| !s.name.is_empty())
+                .map(|(i, s)| (s.name.as_str(), series_color(s, i)))
+                .collect(),
+            ChartData::Pie { labels, .. } => labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| (label.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()]))
+                .collect(),
+        }
+    }
+
+    /// 纵轴上限，叠加柱状图取各列之和的最大值
+    fn max_value(&self) -> f32 {
+        let max = match self {
+            ChartData::Line { series, .. } | ChartData::Bar { series, .. } => series
+                .iter()
+                .flat_map(|s| s.values.iter().copied())
+                .fold(0.0, f32::max),
+            ChartData::StackedBar { labels, series } => (0..labels.len())
+                .map(|i| series.iter().filter_map(|s| s.values.get(i)).sum::<f32>())
+                .fold(0.0, f32::max),
+            ChartData::Pie { values, .. } => values.iter().sum(),
+        };
+        if max > 0.0 {
+            max
+        } else {
+            1.0
+        }
+    }
+
+    /// 查找绘图区域 `size` 中 `point` 处的数值
+    pub fn hit_test(&self, size: Size, point: Point) -> Option<Tooltip> {
+        let area = plot_area(self, size);
+        match self {
+            ChartData::Pie { labels, values } => {
+                let (center, radius) = pie_geometry(area);
+                let offset = point - center;
+                if offset.x.hypot(offset.y) > radius {
+                    return None;
+                }
+                let angle = slice_angle(offset);
+                let total: f32 = values.iter().sum();
+                let mut start = 0.0;
+                for (i, value) in values.iter().enumerate() {
+                    let end = start + value / total * std::f32::consts::TAU;
+                    if angle >= start && angle < end {
+                        return Some(Tooltip {
+                            label: labels.get(i).cloned().unwrap_or_default(),
+                            series: String::new(),
+                            value: *value,
+                        });
+                    }
+                    start = end;
+                }
+                None
+            }
+            _ if !area.contains(point) => None,
+            ChartData::Line { labels, series } => {
+                let step = area.width / (labels.len().max(2) - 1) as f32;
+                let index = ((point.x - area.x) / step).round() as usize;
+                let y = |value: f32| area.y + area.height - value / self.max_value() * area.height;
+                series
+                    .iter()
+                    .filter_map(|s| s.values.get(index).map(|value| (s, *value)))
+                    .min_by(|(_, a), (_, b)| (y(*a) - point.y).abs().total_cmp(&(y(*b) - point.y).abs()))
+                    .map(|(s, value)| Tooltip {
+                        label: labels.get(index).cloned().unwrap_or_default(),
+                        series: s.name.clone(),
+                        value,
+                    })
+            }
+            ChartData::Bar { labels, series } => {
+                let (index, within) = column_at(area, labels.len(), point)?;
+                let slot = (within / (1.0 / series.len().max(1) as f32)) as usize;
+                let s = series.get(slot)?;
+                let value = *s.values.get(index)?;
+                let top = area.y + area.height - value / self.max_value() * area.height;
+                (point.y >= top).then(|| Tooltip {
+                    label: labels[index].clone(),
+                    series: s.name.clone(),
+                    value,
+                })
+            }
+            ChartData::StackedBar { labels, series } => {
+                let (index, _) = column_at(area, labels.len(), point)?;
+                // 从底部向上累计，找到包含鼠标位置的一段
+                let target = (area.y + area.height - point.y) / area.height * self.max_value();
+                let mut base = 0.0;
+                for s in series {
+                    let value = s.values.get(index).copied().unwrap_or(0.0);
+                    if target >= base && target < base + value {
+                        return Some(Tooltip {
+                            label: labels[index].clone(),
+                            series: s.name.clone(),
+                            value,
+                        });
+                    }
+                    base += value;
+                }
+                None
+            }
+        }
+    }
+}
+
+fn series_color(series: &Series, index: usize) -> Color {
+    series.color.unwrap_or(SERIES_COLORS[index % SERIES_COLORS.len()])
+}
+
+/// 去掉图例、纵轴和横轴标签后的绘图区域
+fn plot_area(data: &ChartData, size: Size) -> Rectangle {
+    let legend = if data.legend().is_empty() { 0.0 } else { LEGEND_HEIGHT };
+    let axis = if matches!(data, ChartData::Pie { .. }) { 0.0 } else { AXIS_WIDTH };
+    let labels = if matches!(data, ChartData::Pie { .. }) { 0.0 } else { LABEL_HEIGHT };
+    Rectangle {
+        x: axis,
+        y: legend,
+        width: (size.width - axis).max(1.0),
+        height: (size.height - legend - labels).max(1.0),
+    }
+}
+
+fn pie_geometry(area: Rectangle) -> (Point, f32) {
+    (area.center(), area.width.min(area.height) / 2.0)
+}
+
+/// 从正上方顺时针计算的角度，范围 [0, 2π)
+fn slice_angle(offset: Vector) -> f32 {
+    (offset.x.atan2(-offset.y) + std::f32::consts::TAU) % std::f32::consts::TAU
+}
+
+/// 鼠标所在的列及其在列宽内的相对位置（0 到 1）
+fn column_at(area: Rectangle, columns: usize, point: Point) -> Option<(usize, f32)> {
+    if columns == 0 {
+        return None;
+    }
+    let width = area.width / columns as f32;
+    let offset = (point.x - area.x) / width;
+    let index = offset as usize;
+    (index < columns).then(|| (index, offset.fract()))
+}
+
 pub struct Chart {
-    data: Vec<(f32, f32)>,
+    data: ChartData,
 }
 
 impl Chart {
+    /// 单条折线，横坐标作为标签
     pub fn new(data: Vec<(f32, f32)>) -> Self {
+        let labels = data.iter().map(|(x, _)| x.to_string()).collect();
+        let values = data.iter().map(|(_, y)| *y).collect();
+        Self::with_data(ChartData::Line {
+            labels,
+            series: vec![Series::new("", values)],
+        })
+    }
+
+    pub fn with_data(data: ChartData) -> Self {
         Self { data }
     }
 
@@ -25,7 +249,141 @@ impl Chart {
 }
 
 struct ChartRenderer {
-    data: Vec<(f32, f32)>,
+    data: ChartData,
+}
+
+impl ChartRenderer {
+    fn draw_text(frame: &mut Frame, content: String, position: Point, color: Color, align: alignment::Horizontal) {
+        frame.fill_text(Text {
+            content,
+            position,
+            color,
+            size: TEXT_SIZE,
+            horizontal_alignment: align,
+            vertical_alignment: alignment::Vertical::Center,
+            ..Text::default()
+        });
+    }
+
+    fn draw_axes(&self, frame: &mut Frame, area: Rectangle, text_color: Color) {
+        let axis = Path::new(|builder| {
+            builder.move_to(Point::new(area.x, area.y));
+            builder.line_to(Point::new(area.x, area.y + area.height));
+            builder.line_to(Point::new(area.x + area.width, area.y + area.height));
+        });
+        frame.stroke(&axis, Stroke::default().with_color(text_color).with_width(1.0));
+
+        let max = self.data.max_value();
+        for (value, y) in [(max, area.y), (max / 2.0, area.y + area.height / 2.0), (0.0, area.y + area.height)] {
+            Self::draw_text(frame, format_value(value), Point::new(area.x - 4.0, y), text_color, alignment::Horizontal::Right);
+        }
+
+        let labels = self.data.labels();
+        let line = matches!(self.data, ChartData::Line { .. });
+        for (i, label) in labels.iter().enumerate() {
+            let x = if line {
+                area.x + i as f32 * area.width / (labels.len().max(2) - 1) as f32
+            } else {
+                area.x + (i as f32 + 0.5) * area.width / labels.len() as f32
+            };
+            Self::draw_text(
+                frame,
+                label.clone(),
+                Point::new(x, area.y + area.height + LABEL_HEIGHT / 2.0),
+                text_color,
+                alignment::Horizontal::Center,
+            );
+        }
+    }
+
+    fn draw_legend(&self, frame: &mut Frame, text_color: Color) {
+        let mut x = 0.0;
+        for (name, color) in self.data.legend() {
+            frame.fill_rectangle(Point::new(x, LEGEND_HEIGHT / 2.0 - 5.0), Size::new(10.0, 10.0), color);
+            Self::draw_text(frame, name.to_string(), Point::new(x + 14.0, LEGEND_HEIGHT / 2.0), text_color, alignment::Horizontal::Left);
+            x += 24.0 + name.chars().count() as f32 * TEXT_SIZE * 0.6;
+        }
+    }
+
+    fn draw_data(&self, frame: &mut Frame, area: Rectangle) {
+        let max = self.data.max_value();
+        let height = |value: f32| value / max * area.height;
+        let bottom = area.y + area.height;
+        match &self.data {
+            ChartData::Line { labels, series } => {
+                let step = area.width / (labels.len().max(2) - 1) as f32;
+                for (i, s) in series.iter().enumerate() {
+                    if s.values.is_empty() {
+                        continue;
+                    }
+                    let path = Path::new(|builder| {
+                        builder.move_to(Point::new(area.x, bottom - height(s.values[0])));
+                        for (j, value) in s.values.iter().enumerate().skip(1) {
+                            builder.line_to(Point::new(area.x + j as f32 * step, bottom - height(*value)));
+                        }
+                    });
+                    frame.stroke(&path, Stroke::default().with_color(series_color(s, i)).with_width(2.0));
+                }
+            }
+            ChartData::Bar { labels, series } => {
+                let column = area.width / labels.len().max(1) as f32;
+                let bar = column / series.len().max(1) as f32;
+                for (i, s) in series.iter().enumerate() {
+                    for (j, value) in s.values.iter().enumerate().take(labels.len()) {
+                        frame.fill_rectangle(
+                            Point::new(area.x + j as f32 * column + i as f32 * bar + 1.0, bottom - height(*value)),
+                            Size::new((bar - 2.0).max(1.0), height(*value)),
+                            series_color(s, i),
+                        );
+                    }
+                }
+            }
+            ChartData::StackedBar { labels, series } => {
+                let column = area.width / labels.len().max(1) as f32;
+                for j in 0..labels.len() {
+                    let mut top = bottom;
+                    for (i, s) in series.iter().enumerate() {
+                        let value = s.values.get(j).copied().unwrap_or(0.0);
+                        top -= height(value);
+                        frame.fill_rectangle(
+                            Point::new(area.x + j as f32 * column + column * 0.1, top),
+                            Size::new(column * 0.8, height(value)),
+                            series_color(s, i),
+                        );
+                    }
+                }
+            }
+            ChartData::Pie { values, .. } => {
+                let (center, radius) = pie_geometry(area);
+                let total: f32 = values.iter().sum();
+                let mut start = 0.0;
+                for (i, value) in values.iter().enumerate() {
+                    let sweep = value / total.max(f32::EPSILON) * std::f32::consts::TAU;
+                    let slice = Path::new(|builder| {
+                        builder.move_to(center);
+                        builder.arc(canvas::path::Arc {
+                            center,
+                            radius,
+                            // 画布角度从 x 轴正方向开始，减去 π/2 后从正上方开始
+                            start_angle: start - std::f32::consts::FRAC_PI_2,
+                            end_angle: start + sweep - std::f32::consts::FRAC_PI_2,
+                        });
+                        builder.close();
+                    });
+                    frame.fill(&slice, SERIES_COLORS[i % SERIES_COLORS.len()]);
+                    start += sweep;
+                }
+            }
+        }
+    }
+}
+
+fn format_value(value: f32) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.1}", value)
+    }
 }
 
 impl Program<Message> for ChartRenderer {
@@ -37,9 +395,10 @@ impl Program<Message> for ChartRenderer {
         renderer: &Renderer,
         theme: &Theme,
         bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
         let mut frame = Frame::new(renderer, Size::new(bounds.width, bounds.height));
+        let text_color = theme.palette().text;
 
         // Draw chart background
         let background_color = Color::from_rgb(0.95, 0.95, 0.95);
@@ -49,29 +408,23 @@ impl Program<Message> for ChartRenderer {
             background_color,
         );
 
-        // Draw data points
-        if !self.data.is_empty() {
-            let x_scale = bounds.width / (self.data.len() - 1) as f32;
-            let y_scale = bounds.height;
-
-            let mut builder = Path::builder();
-            builder.move_to(Point::new(0.0, bounds.height - self.data[0].1 * y_scale));
+        let area = plot_area(&self.data, bounds.size());
+        self.draw_legend(&mut frame, text_color);
+        if !matches!(self.data, ChartData::Pie { .. }) {
+            self.draw_axes(&mut frame, area, text_color);
+        }
+        self.draw_data(&mut frame, area);
 
-            for (i, (_x, y)) in self.data.iter().enumerate().skip(1) {
-                builder.line_to(Point::new(
-                    i as f32 * x_scale,
-                    bounds.height - y * y_scale,
-                ));
+        // 悬停时在鼠标旁显示数值
+        if let Some(position) = cursor.position_in(bounds) {
+            if let Some(tooltip) = self.data.hit_test(bounds.size(), position) {
+                let content = if tooltip.series.is_empty() {
+                    format!("{}: {}", tooltip.label, format_value(tooltip.value))
+                } else {
+                    format!("{} · {}: {}", tooltip.label, tooltip.series, format_value(tooltip.value))
+                };
+                Self::draw_text(&mut frame, content, position + Vector::new(8.0, -10.0), text_color, alignment::Horizontal::Left);
             }
-
-            let path = builder.build();
-            let line_color = Color::from_rgb(0.2, 0.6, 0.9);
-            frame.stroke(
-                &path,
-                Stroke::default()
-                    .with_color(line_color)
-                    .with_width(2.0),
-            );
         }
 
         vec![frame.into_geometry()]
@@ -80,9 +433,56 @@ impl Program<Message> for ChartRenderer {
     fn mouse_interaction(
         &self,
         _state: &Self::State,
-        _bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
     ) -> mouse::Interaction {
-        mouse::Interaction::default()
+        if cursor.is_over(bounds) {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_stacked_bar_hit_test() {
+        let data = ChartData::StackedBar {
+            labels: labels(&["Mon", "Tue"]),
+            series: vec![Series::new("development", vec![2.0, 1.0]), Series::new("meeting", vec![2.0, 3.0])],
+        };
+        assert_eq!(data.max_value(), 4.0);
+        // 绘图区域为 x 40..240，y 20..120
+        let size = Size::new(240.0, 138.0);
+        let hit = |x, y| data.hit_test(size, Point::new(x, y));
+
+        assert_eq!(
+            hit(90.0, 110.0),
+            Some(Tooltip { label: "Mon".into(), series: "development".into(), value: 2.0 })
+        );
+        assert_eq!(hit(190.0, 40.0).map(|t| (t.label, t.series)), Some(("Tue".into(), "meeting".into())));
+        assert_eq!(hit(10.0, 110.0), None);
+
+        // 旧的构造方式仍为单条无图例折线
+        let line = ChartData::Line { labels: labels(&["0", "1"]), series: vec![Series::new("", vec![0.5, 1.0])] };
+        assert!(line.legend().is_empty());
+        assert_eq!(Chart::new(vec![(0.0, 0.5), (1.0, 1.0)]).data, line);
+    }
+
+    #[test]
+    fn test_pie_hit_test_starts_at_top() {
+        let data = ChartData::Pie { labels: labels(&["code", "docs"]), values: vec![3.0, 1.0] };
+        let size = Size::new(200.0, 220.0);
+        // 饼图中心 (100, 120)，右侧和下方属于第一块，左上属于第二块
+        assert_eq!(data.hit_test(size, Point::new(150.0, 120.0)).map(|t| t.label), Some("code".into()));
+        assert_eq!(data.hit_test(size, Point::new(80.0, 80.0)).map(|t| t.value), Some(1.0));
+        assert_eq!(data.hit_test(size, Point::new(0.0, 0.0)), None);
+    }
+}
@@ -0,0 +1,74 @@
+use iced::{
+    mouse,
+    widget::canvas::{self, Frame, Geometry, Program},
+    Color, Element, Length, Point, Rectangle, Size, Theme,
+};
+use crate::presentation::ui::Message;
+
+/// 星期 × 小时的热力图，颜色深浅按格子中的分钟数相对最大值计算
+pub struct Heatmap {
+    grid: Vec<Vec<u32>>,
+}
+
+impl Heatmap {
+    pub fn new(grid: Vec<Vec<u32>>) -> Self {
+        Self { grid }
+    }
+
+    pub fn view<'a>(&self) -> Element<'a, Message> {
+        canvas::Canvas::new(HeatmapRenderer {
+            grid: self.grid.clone(),
+        })
+        .width(Length::Fill)
+        .height(Length::Fixed(140.0))
+        .into()
+    }
+}
+
+struct HeatmapRenderer {
+    grid: Vec<Vec<u32>>,
+}
+
+impl Program<Message> for HeatmapRenderer {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, Size::new(bounds.width, bounds.height));
+
+        let rows = self.grid.len().max(1);
+        let columns = self.grid.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let max = self.grid.iter().flatten().copied().max().unwrap_or(0).max(1) as f32;
+        let cell = Size::new(bounds.width / columns as f32, bounds.height / rows as f32);
+        let gap = 2.0;
+
+        for (row, hours) in self.grid.iter().enumerate() {
+            for (column, minutes) in hours.iter().enumerate() {
+                // 没有记录的格子保留浅色底，便于看出网格
+                let intensity = 0.08 + 0.92 * (*minutes as f32 / max);
+                frame.fill_rectangle(
+                    Point::new(column as f32 * cell.width, row as f32 * cell.height),
+                    Size::new((cell.width - gap).max(1.0), (cell.height - gap).max(1.0)),
+                    Color::from_rgba(0.2, 0.6, 0.3, intensity),
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        mouse::Interaction::default()
+    }
+}
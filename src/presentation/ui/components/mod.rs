@@ -3,7 +3,9 @@
 mod button;
 mod card;
 mod chart;
+mod heatmap;
 
 pub use button::Button;
 pub use card::Card;
-pub use chart::Chart;
\ No newline at end of file
+pub use chart::{Chart, ChartData, Series};
+pub use heatmap::Heatmap;
\ No newline at end of file
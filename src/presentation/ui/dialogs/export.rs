@@ -29,7 +29,14 @@ impl Dialog for ExportDialog {
                     .push(Text::new("Format"))
                     .push(
                         PickList::new(
-                            &[ExportFormat::CSV, ExportFormat::JSON, ExportFormat::Excel],
+                            &[
+                                ExportFormat::CSV,
+                                ExportFormat::JSON,
+                                ExportFormat::Excel,
+                                ExportFormat::HTML,
+                                ExportFormat::Markdown,
+                                ExportFormat::ICal,
+                            ],
                             Some(self.format),
                             |_| Message::NoOp,
                         )
@@ -0,0 +1,209 @@
+use iced::{
+    widget::{Button, Column, Row, Text, TextInput},
+    Element, Length,
+};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use crate::core::models::{current_utc_offset, Activity, ManualEntry, Project, MANUAL_APP_NAME};
+use crate::core::{AppError, AppResult};
+use crate::presentation::ui::Message;
+use crate::presentation::ui::styles::button::ButtonStyle;
+use super::base::{Dialog, DialogContainer};
+
+/// 输入框中的时间格式
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// 按 `TIME_FORMAT` 解析本地时间，`label` 用于错误提示
+pub fn parse_input_time(label: &str, input: &str) -> AppResult<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(input.trim(), TIME_FORMAT)
+        .map_err(|_| AppError::InvalidOperation(format!("{}格式应为 YYYY-MM-DD HH:MM", label)))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| AppError::InvalidOperation(format!("{}在当前时区不存在", label)))
+}
+
+/// 补录离开电脑时的工作，例如线下会议
+#[derive(Debug, Clone)]
+pub struct ManualEntryDialog {
+    projects: Vec<Project>,
+    title_input: String,
+    project_input: String,
+    start_input: String,
+    end_input: String,
+    category_input: String,
+    /// 保存失败的原因或与已有记录重叠的提示
+    status: Option<String>,
+}
+
+impl ManualEntryDialog {
+    /// 默认填入过去一小时
+    pub fn new(projects: Vec<Project>) -> Self {
+        let now = Local::now();
+        Self {
+            projects,
+            title_input: String::new(),
+            project_input: String::new(),
+            start_input: (now - chrono::Duration::hours(1)).format(TIME_FORMAT).to_string(),
+            end_input: now.format(TIME_FORMAT).to_string(),
+            category_input: String::new(),
+            status: None,
+        }
+    }
+
+    /// 按输入创建活动，项目按名称匹配；类别为空时使用项目的默认类别
+    pub fn to_activity(&self) -> AppResult<Activity> {
+        let title = self.title_input.trim();
+        if title.is_empty() {
+            return Err(AppError::InvalidOperation("请填写标题".into()));
+        }
+        let start_time = parse_input_time("开始时间", &self.start_input)?;
+        let end_time = parse_input_time("结束时间", &self.end_input)?;
+        if end_time <= start_time {
+            return Err(AppError::InvalidOperation("结束时间必须晚于开始时间".into()));
+        }
+
+        let project_name = self.project_input.trim();
+        let project = if project_name.is_empty() {
+            None
+        } else {
+            Some(
+                self.projects
+                    .iter()
+                    .find(|project| project.name.eq_ignore_ascii_case(project_name))
+                    .ok_or_else(|| AppError::NotFound(format!("项目「{}」", project_name)))?,
+            )
+        };
+        let category = Some(self.category_input.trim().to_string());
+        let category = match project {
+            Some(project) => project.resolve_category(category),
+            None => category,
+        };
+
+        Ok(Activity {
+            id: None,
+            name: title.to_string(),
+            start_time,
+            end_time: Some(end_time),
+            project_id: project.and_then(|p| p.id),
+            description: None,
+            duration: (end_time - start_time).to_std().unwrap_or_default(),
+            category: category.unwrap_or_default(),
+            is_productive: true,
+            app_name: MANUAL_APP_NAME.to_string(),
+            window_title: title.to_string(),
+            is_billable: None,
+            tags: project.map(|p| p.resolve_tags(Vec::new())).unwrap_or_default(),
+            utc_offset: Some(current_utc_offset()),
+            manual: true,
+        })
+    }
+
+    /// 记录保存结果，重叠时提示但不撤销保存
+    pub fn set_result(&mut self, result: &AppResult<ManualEntry>) {
+        self.status = match result {
+            Ok(entry) if entry.overlaps.is_empty() => None,
+            Ok(entry) => Some(format!("已保存，与 {} 条已有记录时间重叠", entry.overlaps.len())),
+            Err(e) => Some(e.to_string()),
+        };
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    fn field<'a>(label: &'a str, placeholder: &'a str, value: &'a str, on_input: fn(String) -> Message) -> Column<'a, Message> {
+        Column::new().spacing(10).push(Text::new(label)).push(
+            TextInput::new(placeholder, value)
+                .on_input(on_input)
+                .padding(10)
+                .width(Length::Fill),
+        )
+    }
+}
+
+impl Dialog for ManualEntryDialog {
+    fn title(&self) -> String {
+        "Add time entry".into()
+    }
+
+    fn view(&self) -> Element<Message> {
+        let mut content = Column::new()
+            .spacing(20)
+            .push(Text::new(self.title()).size(24))
+            .push(Self::field("Title", "What did you work on?", &self.title_input, Message::ManualEntryTitleChanged))
+            .push(Self::field("Project", "Project name (optional)", &self.project_input, Message::ManualEntryProjectChanged))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(Self::field("Start", "YYYY-MM-DD HH:MM", &self.start_input, Message::ManualEntryStartChanged))
+                    .push(Self::field("End", "YYYY-MM-DD HH:MM", &self.end_input, Message::ManualEntryEndChanged)),
+            )
+            .push(Self::field("Category", "Category", &self.category_input, Message::ManualEntryCategoryChanged));
+        if let Some(status) = &self.status {
+            content = content.push(Text::new(status.as_str()));
+        }
+        content = content.push(
+            Row::new()
+                .spacing(10)
+                .push(
+                    Button::new(Text::new("Cancel"))
+                        .style(ButtonStyle::Secondary)
+                        .on_press(Message::CloseManualEntry),
+                )
+                .push(
+                    Button::new(Text::new("Save"))
+                        .style(ButtonStyle::Primary)
+                        .on_press(Message::SubmitManualEntry),
+                ),
+        );
+
+        DialogContainer::new()
+            .push(content)
+            .spacing(20.0)
+            .into_element()
+    }
+
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::ManualEntryTitleChanged(input) => self.title_input = input,
+            Message::ManualEntryProjectChanged(input) => self.project_input = input,
+            Message::ManualEntryStartChanged(input) => self.start_input = input,
+            Message::ManualEntryEndChanged(input) => self.end_input = input,
+            Message::ManualEntryCategoryChanged(input) => self.category_input = input,
+            _ => return,
+        }
+        self.status = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_entry_from_inputs() -> AppResult<()> {
+        let mut project = Project::new("Design".into(), None);
+        project.id = Some(3);
+        project.default_category = Some("meeting".into());
+        let mut dialog = ManualEntryDialog::new(vec![project]);
+        dialog.update(Message::ManualEntryTitleChanged("Offline design review".into()));
+        dialog.update(Message::ManualEntryProjectChanged("design".into()));
+        dialog.update(Message::ManualEntryStartChanged("2024-04-01 14:00".into()));
+        dialog.update(Message::ManualEntryEndChanged("2024-04-01 16:00".into()));
+
+        let activity = dialog.to_activity()?;
+        assert!(activity.manual);
+        assert_eq!(activity.project_id, Some(3));
+        assert_eq!(activity.category, "meeting");
+        assert_eq!(activity.duration, std::time::Duration::from_secs(2 * 3600));
+
+        dialog.update(Message::ManualEntryEndChanged("2024-04-01 13:00".into()));
+        assert!(matches!(dialog.to_activity(), Err(AppError::InvalidOperation(_))));
+        dialog.update(Message::ManualEntryEndChanged("16:00".into()));
+        assert!(dialog.to_activity().is_err());
+        dialog.update(Message::ManualEntryEndChanged("2024-04-01 16:00".into()));
+        dialog.update(Message::ManualEntryProjectChanged("Unknown".into()));
+        assert!(matches!(dialog.to_activity(), Err(AppError::NotFound(_))));
+        Ok(())
+    }
+}
@@ -3,9 +3,11 @@ mod project;
 mod export;
 mod settings;
 mod confirmation;
+mod manual_entry;
 
 pub use base::Dialog;
 pub use project::ProjectDialog;
 pub use export::ExportDialog;
 pub use settings::SettingsDialog;
-pub use confirmation::ConfirmationDialog; 
\ No newline at end of file
+pub use confirmation::ConfirmationDialog;
+pub use manual_entry::{parse_input_time, ManualEntryDialog, TIME_FORMAT}; 
\ No newline at end of file
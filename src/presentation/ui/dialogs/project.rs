@@ -1,15 +1,21 @@
 use iced::{
-    widget::{Button, Column, Container, Row, Text, TextInput},
+    widget::{Button, Checkbox, Column, Container, Row, Text, TextInput},
     Element, Length,
 };
 use crate::core::models::Project;
+use crate::domain::project::parse_hex_color;
 use crate::presentation::ui::{Message, styles};
 use super::base::{Dialog, DialogContainer};
 
+#[derive(Debug, Clone)]
 pub struct ProjectDialog {
     project: Project,
     name_input: String,
     description_input: String,
+    color_input: String,
+    billable_input: bool,
+    default_tags_input: String,
+    default_category_input: String,
 }
 
 impl ProjectDialog {
@@ -18,6 +24,10 @@ impl ProjectDialog {
             project: Project::new(String::new(), None),
             name_input: String::new(),
             description_input: String::new(),
+            color_input: String::new(),
+            billable_input: false,
+            default_tags_input: String::new(),
+            default_category_input: String::new(),
         }
     }
 
@@ -25,9 +35,24 @@ impl ProjectDialog {
         Self {
             name_input: project.name.clone(),
             description_input: project.description.clone().unwrap_or_default(),
+            color_input: project.color.clone().unwrap_or_default(),
+            billable_input: project.is_billable,
+            default_tags_input: project.default_tags.join(", "),
+            default_category_input: project.default_category.clone().unwrap_or_default(),
             project,
         }
     }
+
+    /// 预填推荐的颜色，用户仍可修改
+    pub fn with_color(mut self, color: String) -> Self {
+        self.project.color = Some(color.clone());
+        self.color_input = color;
+        self
+    }
+
+    pub fn project(&self) -> &Project {
+        &self.project
+    }
 }
 
 impl Dialog for ProjectDialog {
@@ -55,6 +80,42 @@ impl Dialog for ProjectDialog {
                             .width(Length::Fill),
                     ),
             )
+            .push(
+                Column::new()
+                    .spacing(10)
+                    .push(Text::new("Color"))
+                    .push(
+                        TextInput::new("#RRGGBB", &self.color_input)
+                            .on_input(Message::ProjectColorChanged)
+                            .padding(10)
+                            .width(Length::Fill),
+                    ),
+            )
+            .push(
+                Checkbox::new("Billable", self.billable_input, Message::ProjectBillableToggled),
+            )
+            .push(
+                Column::new()
+                    .spacing(10)
+                    .push(Text::new("Default tags"))
+                    .push(
+                        TextInput::new("Comma separated", &self.default_tags_input)
+                            .on_input(Message::ProjectDefaultTagsChanged)
+                            .padding(10)
+                            .width(Length::Fill),
+                    ),
+            )
+            .push(
+                Column::new()
+                    .spacing(10)
+                    .push(Text::new("Default category"))
+                    .push(
+                        TextInput::new("Category", &self.default_category_input)
+                            .on_input(Message::ProjectDefaultCategoryChanged)
+                            .padding(10)
+                            .width(Length::Fill),
+                    ),
+            )
             .push(
                 Row::new()
                     .spacing(10)
@@ -76,5 +137,34 @@ impl Dialog for ProjectDialog {
 
     fn update(&mut self, message: Message) {
         // TODO: 实现更新逻辑
+        match message {
+            Message::ProjectBillableToggled(is_billable) => {
+                self.billable_input = is_billable;
+                self.project.is_billable = is_billable;
+            }
+            Message::ProjectDefaultTagsChanged(input) => {
+                self.project.default_tags = input
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                self.default_tags_input = input;
+            }
+            // 输入完整的颜色后才替换，清空时不使用颜色
+            Message::ProjectColorChanged(input) => {
+                if input.trim().is_empty() {
+                    self.project.color = None;
+                } else if let Some([r, g, b]) = parse_hex_color(&input) {
+                    self.project.color = Some(format!("#{:02X}{:02X}{:02X}", r, g, b));
+                }
+                self.color_input = input;
+            }
+            Message::ProjectDefaultCategoryChanged(input) => {
+                let category = input.trim();
+                self.project.default_category = (!category.is_empty()).then(|| category.to_string());
+                self.default_category_input = input;
+            }
+            _ => {}
+        }
     }
 } 
\ No newline at end of file
@@ -3,10 +3,11 @@ use crate::presentation::ui::dialogs::base::Dialog;
 use crate::presentation::ui::Message;
 use crate::presentation::ui::styles::button::{ButtonStyle, PrimaryButton};
 use crate::domain::config::AppConfig;
+use crate::presentation::settings::SettingsModel;
 
 pub struct SettingsDialog {
     title: String,
-    config: AppConfig,
+    model: SettingsModel,
     visible: bool,
     on_save: Box<dyn Fn(AppConfig) -> Message>,
     on_cancel: Box<dyn Fn() -> Message>,
@@ -20,12 +21,16 @@ impl SettingsDialog {
     {
         Self {
             title: "设置".to_string(),
-            config,
+            model: SettingsModel::from_config(&config),
             visible: false,
             on_save: Box::new(on_save),
             on_cancel: Box::new(on_cancel),
         }
     }
+
+    pub fn model_mut(&mut self) -> &mut SettingsModel {
+        &mut self.model
+    }
 }
 
 impl Dialog for SettingsDialog {
@@ -46,11 +51,15 @@ impl Dialog for SettingsDialog {
                             .style(ButtonStyle::Secondary)
                             .on_press((self.on_cancel)()),
                     )
-                    .push(
-                        button("保存")
-                            .style(ButtonStyle::Primary)
-                            .on_press((self.on_save)(self.config.clone())),
-                    ),
+                    .push({
+                        let save = button("保存").style(ButtonStyle::Primary);
+                        // 校验不通过时禁用保存
+                        if self.model.validate().is_ok() {
+                            save.on_press((self.on_save)(self.model.to_config()))
+                        } else {
+                            save
+                        }
+                    }),
             );
 
         content.into()
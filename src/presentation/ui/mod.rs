@@ -3,8 +3,9 @@ use iced::{
     widget::{Button, Column, Container, Row, Text},
     Element, Length, Theme,
 };
-use crate::core::{AppResult, traits::Storage};
+use crate::core::{AppError, AppResult, traits::Storage};
 use crate::infrastructure::config::Config;
+use crate::infrastructure::platform::{self, Permission, PermissionStatus};
 
 pub mod components;
 pub mod dialogs;
@@ -21,6 +22,9 @@ pub enum Message {
     NoOp,
     Exit,
     ToggleWindow,
+    ToggleTracking,
+    SkipPomodoroPhase,
+    Undo,
     ShowSettings,
     ShowAbout,
     ShowHelp,
@@ -83,6 +87,45 @@ pub enum Message {
     ShowMetrics2,
     ShowHealth2,
     ShowBackups2,
+    ProjectBillableToggled(bool),
+    ProjectDefaultTagsChanged(String),
+    ProjectDefaultCategoryChanged(String),
+    ProjectColorChanged(String),
+    OpenPermissionSettings(Permission),
+    ViewCrashReport(std::path::PathBuf),
+    DismissCrashReports,
+    ToggleDashboardEditing,
+    EditDashboard(views::overview::WidgetEdit),
+    CategoryInputChanged(String),
+    EditCategories(views::categories::CategoryEdit),
+    HotkeyInputChanged(crate::presentation::hotkeys::HotkeyAction, String),
+    SaveHotkeys,
+    SearchInputChanged(String),
+    SubmitSearch,
+    ShowManualEntry,
+    CloseManualEntry,
+    ManualEntryTitleChanged(String),
+    ManualEntryProjectChanged(String),
+    ManualEntryStartChanged(String),
+    ManualEntryEndChanged(String),
+    ManualEntryCategoryChanged(String),
+    SubmitManualEntry,
+    ShowActivities,
+    SelectActivity(i64),
+    ActivityProjectChanged(String),
+    ActivityCategoryChanged(String),
+    ActivitySplitTimeChanged(String),
+    EditActivity(views::activities::ActivityEdit),
+    CancelActivityEdit,
+    SelectPomodoroPreset(String),
+    OpenProjectDialog,
+    SelectProject(i64),
+    DeleteProject(i64),
+    ConfirmDeleteProject(i64),
+    CancelDeleteProject,
+    ShowAppUsage,
+    SelectUsageRange(views::app_usage::UsageRange),
+    SelectUsageGroupBy(crate::domain::analysis::GroupBy),
 }
 
 pub struct TimeTrackerApp {
@@ -101,12 +144,18 @@ impl TimeTrackerApp {
     }
 
     pub fn view(&self) -> Element<Message> {
-        let content = match self.state.current_view {
-            View::Overview => self.overview_view(),
-            View::Projects => self.projects_view(),
-            View::Pomodoro => self.pomodoro_view(),
-            View::Settings => self.settings_view(),
-            View::Statistics => self.statistics_view(),
+        let content = if let Some(dialog) = &self.state.manual_entry {
+            dialog.view()
+        } else {
+            match self.state.current_view {
+                View::Overview => self.overview_view(),
+                View::Projects => self.projects_view(),
+                View::Pomodoro => self.pomodoro_view(),
+                View::Settings => self.settings_view(),
+                View::Statistics => self.statistics_view(),
+                View::Activities => self.activities_view(),
+                View::AppUsage => self.app_usage_view(),
+            }
         };
 
         Container::new(content)
@@ -115,23 +164,395 @@ impl TimeTrackerApp {
             .into()
     }
 
+    pub fn set_tracking_enabled(&mut self, enabled: bool) {
+        self.state.tracking_enabled = enabled;
+    }
+
+    pub fn set_permission_status(&mut self, status: PermissionStatus) {
+        self.state.permissions = Some(status);
+    }
+
+    pub fn open_permission_settings(&self, permission: Permission) -> AppResult<()> {
+        platform::init()?.open_permission_settings(permission)
+    }
+
+    pub fn set_crash_reports(&mut self, reports: Vec<std::path::PathBuf>) {
+        self.state.crash_reports = reports;
+    }
+
+    /// 关闭崩溃提示，返回需要标记为已查看的报告
+    pub fn dismiss_crash_reports(&mut self) -> Vec<std::path::PathBuf> {
+        std::mem::take(&mut self.state.crash_reports)
+    }
+
+    pub fn set_dashboard_widgets(&mut self, widgets: Vec<crate::domain::config::DashboardWidget>) {
+        self.state.dashboard_widgets = widgets;
+    }
+
+    pub fn set_goals(&mut self, goals: Vec<(String, crate::domain::goal::GoalProgress)>) {
+        self.state.goals = goals;
+    }
+
+    pub fn set_daily_goals(&mut self, goals: Vec<(String, crate::domain::analysis::DailyGoalProgress)>) {
+        self.state.daily_goals = goals;
+    }
+
+    pub fn toggle_dashboard_editing(&mut self) {
+        self.state.editing_dashboard = !self.state.editing_dashboard;
+    }
+
+    /// 返回修改后的组件配置，由调用方写回 `ui.dashboard_widgets`
+    pub fn edit_dashboard(&mut self, edit: views::overview::WidgetEdit) -> &[crate::domain::config::DashboardWidget] {
+        views::overview::apply_edit(&mut self.state.dashboard_widgets, edit);
+        &self.state.dashboard_widgets
+    }
+
+    pub fn set_categories(&mut self, categories: crate::domain::config::CategorySettings) {
+        self.state.categories = categories;
+    }
+
+    pub fn set_category_input(&mut self, input: String) {
+        self.state.category_input = input;
+    }
+
+    /// 修改 `config` 中的类别，成功后由调用方保存配置；失败时保留输入便于修改
+    pub fn edit_categories(
+        &mut self,
+        config: &mut crate::domain::config::AppConfig,
+        edit: &views::categories::CategoryEdit,
+    ) -> AppResult<()> {
+        views::categories::apply_edit(config, edit)?;
+        self.state.categories = config.categories.clone();
+        self.state.category_input.clear();
+        Ok(())
+    }
+
+    pub fn set_hotkeys(&mut self, hotkeys: crate::presentation::hotkeys::HotkeyConfig) {
+        self.state.hotkeys = hotkeys;
+    }
+
+    pub fn set_hotkey_input(&mut self, action: crate::presentation::hotkeys::HotkeyAction, input: String) {
+        self.state.hotkeys.bindings.insert(action, input);
+    }
+
+    /// 存在冲突时拒绝保存，成功后由调用方调用 `register_global` 并保存配置
+    pub fn save_hotkeys(&self, manager: &crate::presentation::hotkeys::HotkeyManager) -> AppResult<()> {
+        manager.set_config(self.state.hotkeys.clone())
+    }
+
+    pub fn set_focus_streak(&mut self, streak: crate::domain::analysis::StreakInfo) {
+        self.state.focus_streak = Some(streak);
+    }
+
+    pub fn set_dashboard(&mut self, dashboard: crate::domain::analysis::Dashboard) {
+        self.state.dashboard = Some(dashboard);
+    }
+
+    pub fn set_search_input(&mut self, input: String) {
+        self.state.search_input = input;
+    }
+
+    /// 在全部历史中搜索当前输入，空输入时清空结果
+    pub async fn search(&mut self) -> AppResult<()> {
+        if self.state.search_input.trim().is_empty() {
+            self.state.search_results.clear();
+            return Ok(());
+        }
+        let range = (chrono::DateTime::<chrono::Local>::from(std::time::UNIX_EPOCH), chrono::Local::now());
+        self.state.search_results = self.storage.search(&self.state.search_input, range).await?;
+        Ok(())
+    }
+
+    /// 打开补录对话框，`projects` 用于按名称选择项目
+    pub fn open_manual_entry(&mut self, projects: Vec<crate::core::models::Project>) {
+        self.state.manual_entry = Some(ManualEntryDialog::new(projects));
+    }
+
+    /// 打开新建项目对话框，颜色预填为未被 `existing` 使用的推荐颜色
+    pub fn open_project_dialog(&mut self, existing: &[crate::core::models::Project], theme: &str) {
+        let color = crate::domain::ProjectManager::new(self.storage.clone())
+            .suggest_color(existing, crate::domain::project::theme_background(theme));
+        self.state.project_dialog = Some(ProjectDialog::new().with_color(color));
+    }
+
+    pub fn set_projects(&mut self, projects: Vec<crate::core::models::Project>) {
+        self.state.projects = projects;
+        self.state.selected_project = None;
+    }
+
+    /// 选中项目并读取本月目标进度，显示在项目详情卡片上
+    pub async fn select_project(&mut self, id: i64) -> AppResult<()> {
+        let Some(project) = self.state.projects.iter().find(|p| p.id == Some(id)).cloned() else {
+            return Ok(());
+        };
+        let goal = crate::domain::GoalManager::new(self.storage.clone())
+            .project_goal_progress(id, crate::core::models::GoalPeriod::Month)
+            .await?;
+        self.state.selected_project = Some(views::projects::ProjectDetails {
+            project,
+            goal,
+            pending_delete: None,
+        });
+        Ok(())
+    }
+
+    /// 删除项目。影响的记录数超过确认阈值时先在详情卡片上显示影响范围，用户确认后以 `confirmed` 重新调用
+    pub async fn delete_project(&mut self, id: i64, confirmed: bool) -> AppResult<()> {
+        match self.storage.delete_project(id, confirmed).await {
+            Ok(_) => {
+                self.state.projects.retain(|project| project.id != Some(id));
+                self.state.selected_project = None;
+                Ok(())
+            }
+            Err(AppError::ConfirmationRequired(_)) => {
+                let impact = self.storage.count_project_data(id).await?;
+                if let Some(details) = self.state.selected_project.as_mut().filter(|d| d.project.id == Some(id)) {
+                    details.pending_delete = Some(impact);
+                }
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn cancel_project_delete(&mut self) {
+        if let Some(details) = self.state.selected_project.as_mut() {
+            details.pending_delete = None;
+        }
+    }
+
+    /// 统计视图中的图表，例如按类别叠加的每日时长
+    pub fn set_statistics_chart(&mut self, chart: Option<components::ChartData>) {
+        self.state.statistics_chart = chart;
+    }
+
+    /// 项目视图中显示的工作时段热力图，由 `AnalysisManager::project_heatmap` 计算
+    pub fn set_project_heatmap(&mut self, heatmap: Option<crate::domain::analysis::Heatmap>) {
+        self.state.project_heatmap = heatmap;
+    }
+
+    pub fn update_project_dialog(&mut self, message: Message) {
+        if let Some(dialog) = self.state.project_dialog.as_mut() {
+            dialog.update(message);
+        }
+    }
+
+    pub fn close_manual_entry(&mut self) {
+        self.state.manual_entry = None;
+    }
+
+    pub fn update_manual_entry(&mut self, message: Message) {
+        if let Some(dialog) = self.state.manual_entry.as_mut() {
+            dialog.update(message);
+        }
+    }
+
+    /// 保存补录的活动。没有重叠时关闭对话框，重叠或失败时保留对话框并显示提示
+    pub async fn submit_manual_entry(&mut self) -> AppResult<Option<crate::core::models::ManualEntry>> {
+        let Some(dialog) = self.state.manual_entry.as_mut() else {
+            return Ok(None);
+        };
+        let result = match dialog.to_activity() {
+            Ok(activity) => self.storage.add_manual_activity(activity).await,
+            Err(e) => Err(e),
+        };
+        dialog.set_result(&result);
+        if dialog.status().is_none() {
+            self.state.manual_entry = None;
+        }
+        result.map(Some)
+    }
+
+    pub fn show_activities(&mut self) {
+        self.state.current_view = View::Activities;
+    }
+
+    /// 活动视图显示的记录，`projects` 用于显示和按名称修改所属项目
+    pub fn set_activities(&mut self, activities: Vec<crate::core::models::Activity>, projects: Vec<crate::core::models::Project>) {
+        self.state.activities = activities;
+        self.state.projects = projects;
+        self.state.activity_editor = None;
+    }
+
+    pub fn select_activity(&mut self, id: i64) {
+        self.state.activity_editor = self
+            .state
+            .activities
+            .iter()
+            .find(|activity| activity.id == Some(id))
+            .map(|activity| views::activities::ActivityEditor::new(activity.clone(), &self.state.projects));
+        self.state.activity_status = None;
+    }
+
+    pub fn cancel_activity_edit(&mut self) {
+        self.state.activity_editor = None;
+    }
+
+    pub fn update_activity_editor(&mut self, message: Message) {
+        let Some(editor) = self.state.activity_editor.as_mut() else {
+            return;
+        };
+        match message {
+            Message::ActivityProjectChanged(input) => editor.project_input = input,
+            Message::ActivityCategoryChanged(input) => editor.category_input = input,
+            Message::ActivitySplitTimeChanged(input) => editor.split_input = input,
+            _ => {}
+        }
+    }
+
+    /// 对选中的记录执行修改并更新列表，失败时保留编辑框并显示原因
+    pub async fn edit_activity(&mut self, edit: views::activities::ActivityEdit) -> AppResult<()> {
+        use views::activities::ActivityEdit;
+
+        let Some(editor) = self.state.activity_editor.clone() else {
+            return Ok(());
+        };
+        let Some(id) = editor.activity.id else {
+            return Ok(());
+        };
+        let result = match edit {
+            ActivityEdit::Save => match editor.edited(&self.state.projects) {
+                Ok(activity) => self.storage.update_activity(&activity).await.map(|_| vec![activity]),
+                Err(e) => Err(e),
+            },
+            ActivityEdit::Split => match editor.split_at() {
+                Ok(at) => self.storage.split_activity(id, at).await.map(|(first, second)| vec![first, second]),
+                Err(e) => Err(e),
+            },
+            ActivityEdit::Delete => self.storage.delete_activity(id).await.map(|_| Vec::new()),
+        };
+        let replacements = match result {
+            Ok(replacements) => replacements,
+            Err(e) => {
+                self.state.activity_status = Some(e.to_string());
+                return Err(e);
+            }
+        };
+
+        if let Some(index) = self.state.activities.iter().position(|activity| activity.id == Some(id)) {
+            self.state.activities.splice(index..=index, replacements);
+        }
+        self.state.activity_editor = None;
+        self.state.activity_status = None;
+        Ok(())
+    }
+
+    pub fn show_app_usage(&mut self) {
+        self.state.current_view = View::AppUsage;
+    }
+
+    /// 应用使用统计的初始分组方式，取自 `ui.usage_group_by`
+    pub fn set_usage_group_by(&mut self, group_by: crate::domain::analysis::GroupBy) {
+        self.state.usage_group_by = group_by;
+    }
+
+    pub async fn reload_usage_groups(&mut self) -> AppResult<()> {
+        let range = self.state.usage_range.bounds(chrono::Local::now());
+        self.state.usage_groups = crate::domain::AnalysisManager::new(self.storage.clone())
+            .group_usage(range, self.state.usage_group_by)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn select_usage_range(&mut self, range: views::app_usage::UsageRange) -> AppResult<()> {
+        self.state.usage_range = range;
+        self.reload_usage_groups().await
+    }
+
+    /// 切换分组方式并修改 `config`，成功后由调用方保存配置
+    pub async fn select_usage_group_by(
+        &mut self,
+        config: &mut crate::domain::config::AppConfig,
+        group_by: crate::domain::analysis::GroupBy,
+    ) -> AppResult<()> {
+        self.state.usage_group_by = group_by;
+        config.ui.usage_group_by = group_by;
+        self.reload_usage_groups().await
+    }
+
+    pub fn set_pomodoro_presets(&mut self, settings: &crate::domain::config::PomodoroSettings) {
+        self.state.pomodoro_presets = settings.presets.clone();
+        self.state.active_preset = settings.active_preset.clone();
+    }
+
+    /// 切换番茄钟预设并修改 `config`，成功后由调用方保存配置
+    pub async fn select_pomodoro_preset(
+        &mut self,
+        pomodoro: &crate::domain::pomodoro::PomodoroManager,
+        config: &mut crate::domain::config::AppConfig,
+        name: &str,
+    ) -> AppResult<()> {
+        use crate::core::traits::PomodoroTimer;
+
+        pomodoro.apply_preset(name).await?;
+        config.pomodoro.apply_preset(name)?;
+        self.state.active_preset = config.pomodoro.active_preset.clone();
+        Ok(())
+    }
+
     fn overview_view(&self) -> Element<Message> {
-        Column::new()
-            .push(Text::new("概览").size(24))
+        let toggle_label = if self.state.tracking_enabled {
+            "暂停记录"
+        } else {
+            "恢复记录"
+        };
+
+        let mut content = Column::new().push(Text::new("概览").size(24));
+        if let Some(banner) = self.state.permissions.as_ref().and_then(views::permissions::banner_for) {
+            content = content.push(views::permissions::view(&banner));
+        }
+        if let Some(report) = self.state.crash_reports.last() {
+            content = content.push(
+                Row::new()
+                    .spacing(10)
+                    .push(Text::new("上次运行时程序异常退出，已保存崩溃报告").width(Length::Fill))
+                    .push(Button::new(Text::new("查看报告")).on_press(Message::ViewCrashReport(report.clone())))
+                    .push(Button::new(Text::new("忽略")).on_press(Message::DismissCrashReports)),
+            );
+        }
+
+        content
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(Button::new(Text::new(toggle_label)).on_press(Message::ToggleTracking))
+                    .push(Button::new(Text::new("补录时间")).on_press(Message::ShowManualEntry))
+                    .push(Button::new(Text::new("活动记录")).on_press(Message::ShowActivities))
+                    .push(Button::new(Text::new("应用使用")).on_press(Message::ShowAppUsage)),
+            )
+            .push(views::search::view(&self.state.search_input, &self.state.search_results))
+            .push(views::overview::view(
+                &self.state.dashboard_widgets,
+                views::overview::WidgetData {
+                    dashboard: self.state.dashboard.as_ref(),
+                    goals: &self.state.goals,
+                    daily_goals: &self.state.daily_goals,
+                    streak: self.state.focus_streak.as_ref(),
+                },
+                self.state.editing_dashboard,
+            ))
             .spacing(20)
             .into()
     }
 
     fn projects_view(&self) -> Element<Message> {
-        Column::new()
+        let mut content = Column::new()
             .push(Text::new("项目").size(24))
-            .spacing(20)
-            .into()
+            .push(views::projects::view(&self.state.projects, self.state.selected_project.as_ref()))
+            .spacing(20);
+        if let Some(heatmap) = &self.state.project_heatmap {
+            content = content.push(components::Heatmap::new(heatmap.clone()).view());
+        }
+        content.into()
     }
 
     fn pomodoro_view(&self) -> Element<Message> {
         Column::new()
             .push(Text::new("番茄钟").size(24))
+            .push(views::pomodoro::preset_picker(
+                &self.state.pomodoro_presets,
+                self.state.active_preset.as_deref(),
+            ))
             .spacing(20)
             .into()
     }
@@ -139,16 +560,39 @@ impl TimeTrackerApp {
     fn settings_view(&self) -> Element<Message> {
         Column::new()
             .push(Text::new("设置").size(24))
+            .push(views::categories::view(&self.state.categories, &self.state.category_input))
+            .push(views::hotkeys::view(&self.state.hotkeys))
             .spacing(20)
             .into()
     }
 
-    fn statistics_view(&self) -> Element<Message> {
+    fn activities_view(&self) -> Element<Message> {
+        views::activities::view(
+            &self.state.activities,
+            self.state.activity_editor.as_ref(),
+            self.state.activity_status.as_deref(),
+        )
+    }
+
+    fn app_usage_view(&self) -> Element<Message> {
         Column::new()
-            .push(Text::new("统计").size(24))
+            .push(Text::new("应用使用统计").size(24))
+            .push(views::app_usage::view(
+                self.state.usage_range,
+                self.state.usage_group_by,
+                &self.state.usage_groups,
+            ))
             .spacing(20)
             .into()
     }
+
+    fn statistics_view(&self) -> Element<Message> {
+        let mut content = Column::new().push(Text::new("统计").size(24)).spacing(20);
+        if let Some(chart) = &self.state.statistics_chart {
+            content = content.push(components::Chart::with_data(chart.clone()).view());
+        }
+        content.into()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -158,17 +602,75 @@ pub enum View {
     Pomodoro,
     Settings,
     Statistics,
+    Activities,
+    AppUsage,
 }
 
 #[derive(Debug, Clone)]
 pub struct State {
     current_view: View,
+    tracking_enabled: bool,
+    dashboard: Option<crate::domain::analysis::Dashboard>,
+    focus_streak: Option<crate::domain::analysis::StreakInfo>,
+    permissions: Option<PermissionStatus>,
+    dashboard_widgets: Vec<crate::domain::config::DashboardWidget>,
+    goals: Vec<(String, crate::domain::goal::GoalProgress)>,
+    daily_goals: Vec<(String, crate::domain::analysis::DailyGoalProgress)>,
+    editing_dashboard: bool,
+    crash_reports: Vec<std::path::PathBuf>,
+    categories: crate::domain::config::CategorySettings,
+    category_input: String,
+    hotkeys: crate::presentation::hotkeys::HotkeyConfig,
+    search_input: String,
+    search_results: Vec<crate::core::models::SearchHit>,
+    manual_entry: Option<ManualEntryDialog>,
+    project_dialog: Option<ProjectDialog>,
+    project_heatmap: Option<crate::domain::analysis::Heatmap>,
+    statistics_chart: Option<components::ChartData>,
+    activities: Vec<crate::core::models::Activity>,
+    projects: Vec<crate::core::models::Project>,
+    selected_project: Option<views::projects::ProjectDetails>,
+    activity_editor: Option<views::activities::ActivityEditor>,
+    activity_status: Option<String>,
+    pomodoro_presets: Vec<crate::domain::config::PomodoroPreset>,
+    active_preset: Option<String>,
+    usage_range: views::app_usage::UsageRange,
+    usage_group_by: crate::domain::analysis::GroupBy,
+    usage_groups: Vec<crate::domain::analysis::UsageGroup>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
             current_view: View::Overview,
+            tracking_enabled: true,
+            dashboard: None,
+            focus_streak: None,
+            permissions: None,
+            dashboard_widgets: crate::domain::config::AppConfig::default().ui.dashboard_widgets,
+            goals: Vec::new(),
+            daily_goals: Vec::new(),
+            editing_dashboard: false,
+            crash_reports: Vec::new(),
+            categories: crate::domain::config::CategorySettings::default(),
+            category_input: String::new(),
+            hotkeys: crate::presentation::hotkeys::HotkeyConfig::default(),
+            search_input: String::new(),
+            search_results: Vec::new(),
+            manual_entry: None,
+            project_dialog: None,
+            project_heatmap: None,
+            statistics_chart: None,
+            activities: Vec::new(),
+            projects: Vec::new(),
+            selected_project: None,
+            activity_editor: None,
+            activity_status: None,
+            pomodoro_presets: crate::domain::config::AppConfig::default().pomodoro.presets,
+            active_preset: None,
+            usage_range: views::app_usage::UsageRange::default(),
+            usage_group_by: crate::domain::analysis::GroupBy::default(),
+            usage_groups: Vec::new(),
         }
     }
 }
\ No newline at end of file
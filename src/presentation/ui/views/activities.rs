@@ -0,0 +1,179 @@
+use iced::{
+    widget::{Button, Column, Row, Text, TextInput},
+    Element, Length,
+};
+use crate::core::models::{Activity, Project};
+use crate::core::{AppError, AppResult};
+use crate::presentation::ui::dialogs::{parse_input_time, TIME_FORMAT};
+use crate::presentation::ui::Message;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityEdit {
+    /// 保存项目和类别的修改
+    Save,
+    /// 在输入的时间处拆分
+    Split,
+    Delete,
+}
+
+/// 选中的记录和编辑框内容
+#[derive(Debug, Clone)]
+pub struct ActivityEditor {
+    pub activity: Activity,
+    pub project_input: String,
+    pub category_input: String,
+    pub split_input: String,
+}
+
+impl ActivityEditor {
+    /// 拆分时间默认为记录的中点
+    pub fn new(activity: Activity, projects: &[Project]) -> Self {
+        let project_input = activity
+            .project_id
+            .and_then(|id| projects.iter().find(|p| p.id == Some(id)))
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+        let end = activity.end_time.unwrap_or(activity.start_time);
+        let middle = activity.start_time + (end - activity.start_time) / 2;
+        Self {
+            project_input,
+            category_input: activity.category.clone(),
+            split_input: middle.format(TIME_FORMAT).to_string(),
+            activity,
+        }
+    }
+
+    /// 按输入修改项目和类别，项目名为空时取消关联
+    pub fn edited(&self, projects: &[Project]) -> AppResult<Activity> {
+        let name = self.project_input.trim();
+        let project_id = if name.is_empty() {
+            None
+        } else {
+            let project = projects
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| AppError::NotFound(format!("项目「{}」", name)))?;
+            project.id
+        };
+        Ok(Activity {
+            project_id,
+            category: self.category_input.trim().to_string(),
+            ..self.activity.clone()
+        })
+    }
+
+    pub fn split_at(&self) -> AppResult<chrono::DateTime<chrono::Local>> {
+        parse_input_time("拆分时间", &self.split_input)
+    }
+}
+
+fn format_span(activity: &Activity) -> String {
+    let end = activity
+        .end_time
+        .map(|end| end.format("%H:%M").to_string())
+        .unwrap_or_else(|| "…".into());
+    format!("{}–{}", activity.start_time.format("%m-%d %H:%M"), end)
+}
+
+fn editor<'a>(editor: &ActivityEditor) -> Column<'a, Message> {
+    Column::new()
+        .spacing(10)
+        .push(
+            Row::new()
+                .spacing(10)
+                .push(
+                    TextInput::new("项目", &editor.project_input)
+                        .on_input(Message::ActivityProjectChanged)
+                        .width(Length::Fill),
+                )
+                .push(
+                    TextInput::new("类别", &editor.category_input)
+                        .on_input(Message::ActivityCategoryChanged)
+                        .width(Length::Fill),
+                )
+                .push(Button::new(Text::new("保存")).on_press(Message::EditActivity(ActivityEdit::Save))),
+        )
+        .push(
+            Row::new()
+                .spacing(10)
+                .push(
+                    TextInput::new(TIME_FORMAT, &editor.split_input)
+                        .on_input(Message::ActivitySplitTimeChanged)
+                        .width(Length::Fill),
+                )
+                .push(Button::new(Text::new("在此拆分")).on_press(Message::EditActivity(ActivityEdit::Split)))
+                .push(Button::new(Text::new("删除")).on_press(Message::EditActivity(ActivityEdit::Delete)))
+                .push(Button::new(Text::new("取消")).on_press(Message::CancelActivityEdit)),
+        )
+}
+
+/// 记录列表，点击一条记录后在其下方显示编辑框
+pub fn view<'a>(activities: &[Activity], selected: Option<&ActivityEditor>, status: Option<&str>) -> Element<'a, Message> {
+    let mut content = Column::new().spacing(10).push(Text::new("活动记录").size(20));
+    if let Some(status) = status {
+        content = content.push(Text::new(status.to_string()));
+    }
+    for activity in activities {
+        let Some(id) = activity.id else { continue };
+        let label = format!(
+            "{}  {}  {}{}",
+            format_span(activity),
+            activity.app_name,
+            activity.name,
+            if activity.manual { "（手动）" } else { "" }
+        );
+        content = content.push(
+            Button::new(Text::new(label))
+                .width(Length::Fill)
+                .on_press(Message::SelectActivity(id)),
+        );
+        if let Some(selected) = selected.filter(|editor| editor.activity.id == Some(id)) {
+            content = content.push(editor(selected));
+        }
+    }
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn test_editor_resolves_project_by_name() -> AppResult<()> {
+        let start = Local.with_ymd_and_hms(2024, 4, 1, 9, 0, 0).unwrap();
+        let activity = Activity {
+            id: Some(5),
+            name: "main.rs".into(),
+            start_time: start,
+            end_time: Some(start + chrono::Duration::hours(2)),
+            project_id: None,
+            description: None,
+            duration: std::time::Duration::from_secs(2 * 3600),
+            category: "development".into(),
+            is_productive: true,
+            app_name: "code".into(),
+            window_title: "main.rs".into(),
+            is_billable: None,
+            tags: Vec::new(),
+            utc_offset: None,
+            manual: false,
+        };
+        let mut project = Project::new("Client".into(), None);
+        project.id = Some(2);
+        let projects = vec![project];
+
+        let mut editor = ActivityEditor::new(activity, &projects);
+        assert_eq!(editor.split_input, "2024-04-01 10:00");
+        editor.project_input = "client".into();
+        editor.category_input = " meeting ".into();
+        let edited = editor.edited(&projects)?;
+        assert_eq!((edited.project_id, edited.category.as_str()), (Some(2), "meeting"));
+
+        editor.project_input = "Other".into();
+        assert!(matches!(editor.edited(&projects), Err(AppError::NotFound(_))));
+        editor.split_input = "10:30".into();
+        assert!(editor.split_at().is_err());
+        Ok(())
+    }
+}
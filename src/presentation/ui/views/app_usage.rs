@@ -1,95 +1,159 @@
-//src/ui/views/app_usage.rs
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use iced::{
+    widget::{Button, Column, ProgressBar, Row, Text},
+    Element, Length,
+};
+use crate::domain::analysis::{GroupBy, UsageGroup};
+use crate::presentation::ui::{Card, Message};
 
-use eframe::egui;
-use crate::ui::{TimeTrackerApp, styles};
-use super::components::{Card, Chart};
-use chrono::{Local, Duration, NaiveDateTime};
+fn format_duration(duration: std::time::Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
 
-pub fn render(app: &mut TimeTrackerApp, ui: &mut egui::Ui) {
-    ui.heading("应用使用统计");
-    ui.separator();
+/// 应用使用统计的时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsageRange {
+    #[default]
+    Today,
+    Yesterday,
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+}
 
-    ui.horizontal(|ui| {
-        // 时间范围选择
-        ui.label("时间范围:");
-        
-        let time_ranges = ["今天", "昨天", "本周", "上周", "本月"];
-        egui::ComboBox::from_label("")
-            .selected_text(time_ranges[app.selected_time_range])
-            .show_ui(ui, |ui| {
-                for (i, range) in time_ranges.iter().enumerate() {
-                    ui.selectable_value(&mut app.selected_time_range, i, *range);
-                }
-            });
-    });
+impl UsageRange {
+    pub const ALL: [UsageRange; 5] = [
+        UsageRange::Today,
+        UsageRange::Yesterday,
+        UsageRange::ThisWeek,
+        UsageRange::LastWeek,
+        UsageRange::ThisMonth,
+    ];
 
-    // 显示总览数据
-    ui.horizontal(|ui| {
-        Card::new()
-            .show(ui, |ui| {
-                ui.label("总使用时长");
-                ui.heading("8小时32分钟");
-            });
+    pub fn label(self) -> &'static str {
+        match self {
+            UsageRange::Today => "今天",
+            UsageRange::Yesterday => "昨天",
+            UsageRange::ThisWeek => "本周",
+            UsageRange::LastWeek => "上周",
+            UsageRange::ThisMonth => "本月",
+        }
+    }
 
-        Card::new()
-            .show(ui, |ui| {
-                ui.label("生产力得分");
-                ui.heading("76%");
-            });
+    /// `now` 所在时间范围的起止时间，周从周一开始
+    pub fn bounds(self, now: DateTime<Local>) -> (DateTime<Local>, DateTime<Local>) {
+        let today = now.date_naive();
+        let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let (start, end) = match self {
+            UsageRange::Today => (today, today + chrono::Duration::days(1)),
+            UsageRange::Yesterday => (today - chrono::Duration::days(1), today),
+            UsageRange::ThisWeek => (week_start, week_start + chrono::Duration::weeks(1)),
+            UsageRange::LastWeek => (week_start - chrono::Duration::weeks(1), week_start),
+            UsageRange::ThisMonth => {
+                let month_start = today.with_day(1).unwrap_or(today);
+                let next_month = if month_start.month() == 12 {
+                    NaiveDate::from_ymd_opt(month_start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(month_start.year(), month_start.month() + 1, 1)
+                };
+                (month_start, next_month.unwrap_or(today))
+            }
+        };
+        (local_midnight(start, now), local_midnight(end, now))
+    }
+}
 
-        Card::new()
-            .show(ui, |ui| {
-                ui.label("最常用应用");
-                ui.heading("Visual Studio Code");
-            });
-    });
+fn local_midnight(date: NaiveDate, fallback: DateTime<Local>) -> DateTime<Local> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .unwrap_or(fallback)
+}
 
-    ui.separator();
+pub fn group_by_label(by: GroupBy) -> &'static str {
+    match by {
+        GroupBy::App => "按应用",
+        GroupBy::Category => "按类别",
+        GroupBy::Raw => "原始记录",
+    }
+}
 
-    // 显示使用时长图表
-    Chart::new(vec![(0.0, 1.0), (1.0, 2.0), (2.0, 1.5)])
-        .with_size(ui.available_width(), 200.0)
-        .show(ui);
+/// 选中的选项不可点击，其余选项点击后发送对应消息
+fn selector<'a, T: Copy + PartialEq>(
+    options: &[T],
+    selected: T,
+    label: fn(T) -> &'static str,
+    on_press: fn(T) -> Message,
+) -> Row<'a, Message> {
+    options.iter().fold(Row::new().spacing(5), |row, &option| {
+        let button = Button::new(Text::new(label(option)));
+        row.push(if option == selected { button } else { button.on_press(on_press(option)) })
+    })
+}
 
-    ui.separator();
+fn summary_card<'a>(groups: &[UsageGroup]) -> Element<'a, Message> {
+    let total: std::time::Duration = groups.iter().map(|group| group.total_time).sum();
+    let top = groups.first().map(|group| group.key.clone()).unwrap_or_else(|| "无记录".into());
+    Card::new()
+        .spacing(5.0)
+        .push(Text::new("总使用时长"))
+        .push(Text::new(format_duration(total)).size(20))
+        .push(Text::new(format!("最常用：{}", top)))
+        .into_element()
+}
 
-    // 显示应用列表
-    egui::ScrollArea::vertical()
-        .auto_shrink([false; 2])
-        .show(ui, |ui| {
-            for app_data in &app.usage_data {
-                ui.horizontal(|ui| {
-                    ui.label(&app_data.name);
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        ui.label(&format!("{:.1}小时", app_data.duration.num_minutes() as f32 / 60.0));
-                        
-                        // 显示进度条
-                        let progress = app_data.duration.num_minutes() as f32 / (8.0 * 60.0); // 假设以8小时为基准
-                        let rect = ui.available_rect_before_wrap();
-                        let bar_width = 200.0;
-                        let bar_height = 8.0;
-                        let bar_rect = egui::Rect::from_min_size(
-                            egui::pos2(rect.right() - bar_width - 10.0, rect.center().y - bar_height/2.0),
-                            egui::vec2(bar_width, bar_height),
-                        );
-                        
-                        ui.painter().rect_filled(
-                            bar_rect,
-                            2.0,
-                            styles::GRAY_COLOR,
-                        );
-                        
-                        ui.painter().rect_filled(
-                            egui::Rect::from_min_size(
-                                bar_rect.min,
-                                egui::vec2(bar_width * progress.min(1.0), bar_height),
-                            ),
-                            2.0,
-                            styles::PRIMARY_COLOR,
-                        );
-                    });
-                });
-                ui.separator();
-            }
-        });
-}
\ No newline at end of file
+/// 应用使用统计，分组方式的选择由调用方写回 `ui.usage_group_by`
+pub fn view<'a>(range: UsageRange, group_by: GroupBy, groups: &[UsageGroup]) -> Element<'a, Message> {
+    let mut content = Column::new()
+        .spacing(10)
+        .push(
+            Row::new()
+                .spacing(20)
+                .push(Text::new("时间范围"))
+                .push(selector(&UsageRange::ALL, range, UsageRange::label, Message::SelectUsageRange))
+                .push(Text::new("分组"))
+                .push(selector(
+                    &[GroupBy::App, GroupBy::Category, GroupBy::Raw],
+                    group_by,
+                    group_by_label,
+                    Message::SelectUsageGroupBy,
+                )),
+        )
+        .push(summary_card(groups));
+
+    // groups 已按时长降序排列，进度条以最长的一组为满
+    let longest = groups.first().map(|group| group.total_time.as_secs_f32()).unwrap_or(0.0).max(1.0);
+    for group in groups {
+        content = content.push(
+            Row::new()
+                .spacing(10)
+                .push(Text::new(group.key.clone()).width(Length::FillPortion(3)))
+                .push(
+                    ProgressBar::new(0.0..=longest, group.total_time.as_secs_f32())
+                        .width(Length::FillPortion(2)),
+                )
+                .push(Text::new(format_duration(group.total_time)))
+                .push(Text::new(format!("{} 次", group.count))),
+        );
+    }
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_usage_range_bounds() {
+        // 2024-04-17 是周三
+        let now = Local.with_ymd_and_hms(2024, 4, 17, 15, 30, 0).unwrap();
+        let at = |m, d| Local.with_ymd_and_hms(2024, m, d, 0, 0, 0).unwrap();
+
+        assert_eq!(UsageRange::Today.bounds(now), (at(4, 17), at(4, 18)));
+        assert_eq!(UsageRange::Yesterday.bounds(now), (at(4, 16), at(4, 17)));
+        assert_eq!(UsageRange::ThisWeek.bounds(now), (at(4, 15), at(4, 22)));
+        assert_eq!(UsageRange::LastWeek.bounds(now), (at(4, 8), at(4, 15)));
+        assert_eq!(UsageRange::ThisMonth.bounds(now), (at(4, 1), at(5, 1)));
+    }
+}
@@ -0,0 +1,56 @@
+use iced::{
+    widget::{Button, Column, Row, Text, TextInput},
+    Element, Length,
+};
+use crate::core::AppResult;
+use crate::domain::config::{AppConfig, CategorySettings};
+use crate::presentation::ui::Message;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CategoryEdit {
+    Add(String),
+    Rename { from: String, to: String },
+}
+
+/// 修改配置中的自定义类别，由调用方保存配置
+pub fn apply_edit(config: &mut AppConfig, edit: &CategoryEdit) -> AppResult<()> {
+    match edit {
+        CategoryEdit::Add(name) => config.categories.add(name).map(|_| ()),
+        CategoryEdit::Rename { from, to } => config.rename_category(from, to),
+    }
+}
+
+/// `input` 为输入框内容，用作新增或重命名后的名称
+pub fn view<'a>(categories: &CategorySettings, input: &str) -> Element<'a, Message> {
+    let has_input = !input.trim().is_empty();
+    let mut add = Button::new(Text::new("添加"));
+    if has_input {
+        add = add.on_press(Message::EditCategories(CategoryEdit::Add(input.to_string())));
+    }
+
+    let mut content = Column::new()
+        .spacing(10)
+        .push(Text::new("类别").size(20))
+        .push(
+            Row::new()
+                .spacing(10)
+                .push(TextInput::new("类别名称", input).on_input(Message::CategoryInputChanged).width(Length::Fill))
+                .push(add),
+        );
+
+    for name in categories.all() {
+        let mut row = Row::new().spacing(10).push(Text::new(name.to_string()).width(Length::Fill));
+        if !CategorySettings::is_builtin(name) {
+            let mut rename = Button::new(Text::new("重命名"));
+            if has_input {
+                rename = rename.on_press(Message::EditCategories(CategoryEdit::Rename {
+                    from: name.to_string(),
+                    to: input.to_string(),
+                }));
+            }
+            row = row.push(rename);
+        }
+        content = content.push(row);
+    }
+    content.into()
+}
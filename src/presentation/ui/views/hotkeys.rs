@@ -0,0 +1,39 @@
+use iced::{
+    widget::{Button, Column, Row, Text, TextInput},
+    Color, Element, Length,
+};
+use crate::presentation::hotkeys::{HotkeyAction, HotkeyConfig};
+use crate::presentation::ui::Message;
+
+const WARNING: Color = Color::from_rgb(0.8, 0.1, 0.1);
+
+/// 冲突的行旁显示警告，存在冲突时保存按钮不可用
+pub fn view<'a>(config: &HotkeyConfig) -> Element<'a, Message> {
+    let mut content = Column::new().spacing(10).push(Text::new("快捷键").size(20));
+
+    for action in HotkeyAction::ALL {
+        let shortcut = config.bindings.get(&action).map(String::as_str).unwrap_or_default();
+        let mut row = Row::new()
+            .spacing(10)
+            .push(Text::new(action.description()).width(Length::FillPortion(1)))
+            .push(
+                TextInput::new("未设置", shortcut)
+                    .on_input(move |input| Message::HotkeyInputChanged(action, input))
+                    .width(Length::FillPortion(1)),
+            );
+        let conflicts = config.conflicts_with(action);
+        if !conflicts.is_empty() {
+            let names: Vec<_> = conflicts.iter().map(|other| other.description()).collect();
+            row = row.push(Text::new(format!("与“{}”冲突", names.join("、"))).style(WARNING));
+        }
+        content = content.push(row);
+    }
+
+    let mut save = Button::new(Text::new("保存"));
+    if config.find_conflicts().is_empty() {
+        save = save.on_press(Message::SaveHotkeys);
+    } else {
+        content = content.push(Text::new("请先解决快捷键冲突").style(WARNING));
+    }
+    content.push(save).into()
+}
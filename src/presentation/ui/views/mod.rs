@@ -1,10 +1,16 @@
 use crate::presentation::ui::components;
 
+pub mod activities;
+pub mod app_usage;
+pub mod categories;
+pub mod hotkeys;
 pub mod overview;
 pub mod projects;
 pub mod pomodoro;
+pub mod search;
 pub mod statistics;
 pub mod settings;
+pub mod permissions;
 
 pub use overview::*;
 pub use projects::*;
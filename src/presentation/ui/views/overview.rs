@@ -1,42 +1,272 @@
 use iced::{
-    widget::{Column, Container, Row, Text},
+    widget::{Button, Column, Container, ProgressBar, Row, Text},
     Element, Length,
 };
+use crate::domain::analysis::{DailyGoalProgress, Dashboard, StreakInfo};
+use crate::domain::config::{DashboardWidget, WidgetKind};
+use crate::domain::goal::GoalProgress;
 use crate::presentation::ui::Message;
 
-pub fn view<'a>() -> Element<'a, Message> {
-    let content = Column::new()
+fn format_duration(duration: std::time::Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// 各组件渲染所需的数据
+#[derive(Clone, Copy)]
+pub struct WidgetData<'a> {
+    pub dashboard: Option<&'a Dashboard>,
+    /// (项目名称, 目标进度)
+    pub goals: &'a [(String, GoalProgress)],
+    /// (目标名称, 当天进度)，只包含当天生效的每日目标
+    pub daily_goals: &'a [(String, DailyGoalProgress)],
+    pub streak: Option<&'a StreakInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetEdit {
+    Toggle(usize),
+    MoveUp(usize),
+    MoveDown(usize),
+}
+
+/// 按配置顺序返回要显示的组件，未知组件和重复项被跳过
+pub fn visible_widgets(widgets: &[DashboardWidget]) -> Vec<WidgetKind> {
+    let mut kinds = Vec::new();
+    for widget in widgets {
+        if widget.enabled && widget.kind != WidgetKind::Unknown && !kinds.contains(&widget.kind) {
+            kinds.push(widget.kind);
+        }
+    }
+    kinds
+}
+
+pub fn apply_edit(widgets: &mut [DashboardWidget], edit: WidgetEdit) {
+    match edit {
+        WidgetEdit::Toggle(index) => {
+            if let Some(widget) = widgets.get_mut(index) {
+                widget.enabled = !widget.enabled;
+            }
+        }
+        WidgetEdit::MoveUp(index) => {
+            if index > 0 && index < widgets.len() {
+                widgets.swap(index - 1, index);
+            }
+        }
+        WidgetEdit::MoveDown(index) => {
+            if index + 1 < widgets.len() {
+                widgets.swap(index, index + 1);
+            }
+        }
+    }
+}
+
+fn widget_title(kind: WidgetKind) -> &'static str {
+    match kind {
+        WidgetKind::TodayFocus => "Today's Focus",
+        WidgetKind::Streak => "Streak",
+        WidgetKind::TopApps => "Top Apps",
+        WidgetKind::GoalRing => "Goals",
+        WidgetKind::RecentPomodoros => "Recent Pomodoros",
+        WidgetKind::Unknown => "Unavailable widget",
+    }
+}
+
+fn today_focus<'a>(dashboard: &Dashboard) -> Column<'a, Message> {
+    Column::new()
+        .spacing(10)
+        .push(Text::new(format!("Focus: {}", format_duration(dashboard.focus_time))))
+        .push(Text::new(format!(
+            "Pomodoros: {} completed / {} interrupted",
+            dashboard.completed_pomodoros, dashboard.interrupted_pomodoros
+        )))
+        .push(Text::new(format!(
+            "Top project: {}",
+            dashboard.top_project.as_ref().map(|(name, _)| name.as_str()).unwrap_or("-")
+        )))
+        .push(Text::new(format!("Productivity: {:.0}%", dashboard.productivity_score)))
+}
+
+/// 连续天数徽章，没有进行中的连续时返回 `None`
+pub fn streak_badge(streak: &StreakInfo) -> Option<String> {
+    (streak.current > 0).then(|| format!("🔥 {} day streak", streak.current))
+}
+
+fn streak<'a>(streak: &StreakInfo) -> Column<'a, Message> {
+    Column::new()
+        .spacing(5)
+        .push(Text::new(streak_badge(streak).unwrap_or_else(|| "No streak yet".into())))
+        .push(Text::new(format!("Longest: {} days", streak.longest)))
+}
+
+fn top_apps<'a>(dashboard: &Dashboard) -> Column<'a, Message> {
+    dashboard.top_apps.iter().fold(Column::new().spacing(5), |column, group| {
+        column.push(Text::new(format!("{}: {}", group.key, format_duration(group.total_time))))
+    })
+}
+
+fn daily_goal_value(progress: &DailyGoalProgress, value: u64) -> String {
+    if progress.goal.kind.is_duration() {
+        format_duration(std::time::Duration::from_secs(value))
+    } else {
+        value.to_string()
+    }
+}
+
+fn goal_ring<'a>(daily_goals: &[(String, DailyGoalProgress)], goals: &[(String, GoalProgress)]) -> Column<'a, Message> {
+    if daily_goals.is_empty() && goals.is_empty() {
+        return Column::new().push(Text::new("No goals"));
+    }
+    let column = daily_goals.iter().fold(Column::new().spacing(5), |column, (name, progress)| {
+        column
+            .push(Text::new(format!(
+                "{}{}: {} / {}",
+                if progress.met { "✓ " } else { "" },
+                name,
+                daily_goal_value(progress, progress.current),
+                daily_goal_value(progress, progress.target)
+            )))
+            .push(ProgressBar::new(0.0..=100.0, progress.percent()))
+    });
+    goals.iter().fold(column, |column, (name, progress)| {
+        column
+            .push(Text::new(format!(
+                "{}: {} / {}",
+                name,
+                format_duration(progress.logged),
+                format_duration(progress.goal.target)
+            )))
+            .push(ProgressBar::new(0.0..=100.0, progress.percent()))
+    })
+}
+
+fn recent_pomodoros<'a>(dashboard: &Dashboard) -> Column<'a, Message> {
+    if dashboard.recent_pomodoros.is_empty() {
+        return Column::new().push(Text::new("No pomodoros today"));
+    }
+    dashboard.recent_pomodoros.iter().fold(Column::new().spacing(5), |column, session| {
+        column.push(Text::new(format!(
+            "{} {:?} ({})",
+            session.start_time.format("%H:%M"),
+            session.status,
+            format_duration(session.duration)
+        )))
+    })
+}
+
+/// 未知组件返回 `None`，由调用方跳过
+pub fn render_widget<'a>(kind: WidgetKind, data: WidgetData<'_>) -> Option<Element<'a, Message>> {
+    let body = match (kind, data.dashboard, data.streak) {
+        (WidgetKind::Unknown, _, _) => return None,
+        (WidgetKind::GoalRing, _, _) => goal_ring(data.daily_goals, data.goals),
+        (WidgetKind::Streak, _, Some(info)) => streak(info),
+        (_, None, _) => Column::new().push(Text::new("No data")),
+        (WidgetKind::TodayFocus, Some(dashboard), _) => today_focus(dashboard),
+        (WidgetKind::Streak, Some(dashboard), None) => {
+            Column::new().push(Text::new(format!("{} days", dashboard.current_streak)))
+        }
+        (WidgetKind::TopApps, Some(dashboard), _) => top_apps(dashboard),
+        (WidgetKind::RecentPomodoros, Some(dashboard), _) => recent_pomodoros(dashboard),
+    };
+
+    Some(
+        Container::new(Column::new().spacing(10).push(Text::new(widget_title(kind))).push(body))
+            .width(Length::Fill)
+            .into(),
+    )
+}
+
+fn edit_row<'a>(index: usize, widget: &DashboardWidget) -> Element<'a, Message> {
+    let toggle_label = if widget.enabled { "Hide" } else { "Show" };
+    Row::new()
+        .spacing(10)
+        .push(Text::new(widget_title(widget.kind)).width(Length::Fill))
+        .push(Button::new(Text::new("↑")).on_press(Message::EditDashboard(WidgetEdit::MoveUp(index))))
+        .push(Button::new(Text::new("↓")).on_press(Message::EditDashboard(WidgetEdit::MoveDown(index))))
+        .push(Button::new(Text::new(toggle_label)).on_press(Message::EditDashboard(WidgetEdit::Toggle(index))))
+        .into()
+}
+
+pub fn view<'a>(widgets: &[DashboardWidget], data: WidgetData<'_>, editing: bool) -> Element<'a, Message> {
+    let edit_label = if editing { "Done" } else { "Customize" };
+    let mut content = Column::new()
         .spacing(20)
-        .push(
-            Text::new("Overview")
-                .size(24)
-        )
         .push(
             Row::new()
-                .spacing(20)
-                .push(
-                    Container::new(
-                        Column::new()
-                            .spacing(10)
-                            .push(Text::new("Current Activity"))
-                            .push(Text::new("No activity"))
-                    )
-                    .width(Length::Fill)
-                )
-                .push(
-                    Container::new(
-                        Column::new()
-                            .spacing(10)
-                            .push(Text::new("Today's Stats"))
-                            .push(Text::new("No data"))
-                    )
-                    .width(Length::Fill)
-                )
+                .push(Text::new("Overview").size(24).width(Length::Fill))
+                .push(Button::new(Text::new(edit_label)).on_press(Message::ToggleDashboardEditing)),
         );
 
+    if editing {
+        // 编辑模式列出全部配置项，包括已隐藏和不可用的组件
+        for (index, widget) in widgets.iter().enumerate() {
+            content = content.push(edit_row(index, widget));
+        }
+    } else {
+        for kind in visible_widgets(widgets) {
+            if let Some(widget) = render_widget(kind, data) {
+                content = content.push(widget);
+            }
+        }
+    }
+
     Container::new(content)
         .width(Length::Fill)
         .height(Length::Fill)
         .padding(20)
         .into()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget(kind: WidgetKind, enabled: bool) -> DashboardWidget {
+        DashboardWidget { kind, enabled }
+    }
+
+    #[test]
+    fn test_configured_order_determines_render_order() {
+        let mut widgets = vec![
+            widget(WidgetKind::TopApps, true),
+            widget(WidgetKind::Unknown, true),
+            widget(WidgetKind::Streak, true),
+            widget(WidgetKind::GoalRing, false),
+            widget(WidgetKind::TodayFocus, true),
+        ];
+        assert_eq!(
+            visible_widgets(&widgets),
+            vec![WidgetKind::TopApps, WidgetKind::Streak, WidgetKind::TodayFocus]
+        );
+
+        apply_edit(&mut widgets, WidgetEdit::MoveUp(2));
+        apply_edit(&mut widgets, WidgetEdit::MoveUp(1));
+        apply_edit(&mut widgets, WidgetEdit::Toggle(3));
+        assert_eq!(
+            visible_widgets(&widgets),
+            vec![WidgetKind::Streak, WidgetKind::TopApps, WidgetKind::GoalRing, WidgetKind::TodayFocus]
+        );
+    }
+
+    #[test]
+    fn test_unknown_widget_kind_is_kept_but_not_rendered() -> crate::core::AppResult<()> {
+        let widgets: Vec<DashboardWidget> = serde_json::from_str(
+            r#"[{"kind": "weather"}, {"kind": "streak", "enabled": true}]"#,
+        )?;
+        assert_eq!(widgets[0].kind, WidgetKind::Unknown);
+        assert_eq!(visible_widgets(&widgets), vec![WidgetKind::Streak]);
+
+        let data = WidgetData { dashboard: None, goals: &[], daily_goals: &[], streak: None };
+        assert!(render_widget(WidgetKind::Unknown, data).is_none());
+        assert!(render_widget(WidgetKind::Streak, data).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_streak_badge() {
+        let mut info = StreakInfo { current: 5, longest: 8, last_broken: None };
+        assert_eq!(streak_badge(&info).as_deref(), Some("🔥 5 day streak"));
+        info.current = 0;
+        assert!(streak_badge(&info).is_none());
+    }
+}
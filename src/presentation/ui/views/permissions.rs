@@ -0,0 +1,75 @@
+use iced::{
+    widget::{Button, Row, Text},
+    Color, Element, Length,
+};
+use crate::infrastructure::platform::{Permission, PermissionStatus};
+use crate::presentation::ui::{styles, Message};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionBanner {
+    pub message: &'static str,
+    /// 点击按钮后打开的系统设置页面
+    pub permission: Permission,
+    /// 是否完全无法追踪，否则只是记录不完整
+    pub blocking: bool,
+}
+
+/// 辅助功能缺失时优先提示，因为此时完全无法追踪
+pub fn banner_for(status: &PermissionStatus) -> Option<PermissionBanner> {
+    if status.is_tracking_blocked() {
+        return Some(PermissionBanner {
+            message: "缺少辅助功能权限，无法追踪活动窗口",
+            permission: Permission::Accessibility,
+            blocking: true,
+        });
+    }
+    if !status.screen_recording {
+        return Some(PermissionBanner {
+            message: "缺少屏幕录制权限，只能记录应用名称，无法记录窗口标题",
+            permission: Permission::ScreenRecording,
+            blocking: false,
+        });
+    }
+    None
+}
+
+pub fn view<'a>(banner: &PermissionBanner) -> Element<'a, Message> {
+    let appearance = if banner.blocking {
+        styles::text::warning()
+    } else {
+        styles::text::info()
+    };
+
+    Row::new()
+        .spacing(10)
+        .width(Length::Fill)
+        .push(Text::new(banner.message).style(appearance.color.unwrap_or(Color::BLACK)).width(Length::Fill))
+        .push(Button::new(Text::new("打开系统设置")).on_press(Message::OpenPermissionSettings(banner.permission)))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner_for_permission_status() {
+        assert_eq!(banner_for(&PermissionStatus::granted()), None);
+
+        let blocked = banner_for(&PermissionStatus {
+            accessibility: false,
+            screen_recording: false,
+        })
+        .unwrap();
+        assert_eq!(blocked.permission, Permission::Accessibility);
+        assert!(blocked.blocking);
+
+        let degraded = banner_for(&PermissionStatus {
+            accessibility: true,
+            screen_recording: false,
+        })
+        .unwrap();
+        assert_eq!(degraded.permission, Permission::ScreenRecording);
+        assert!(!degraded.blocking);
+    }
+}
@@ -1,11 +1,26 @@
 //src/ui/views/pomodoro.rs
 
 use iced::{
-    widget::{Button, Column, Container, ProgressBar, Row, Text},
+    widget::{Button, Column, Container, PickList, ProgressBar, Row, Text},
     Element, Length,
 };
 use crate::presentation::ui::{Message, TimeTrackerApp, styles, Card};
 use crate::core::models::{PomodoroSession, PomodoroStatus};
+use crate::domain::config::PomodoroPreset;
+
+/// 切换番茄钟预设的下拉框，进行中的阶段不受影响
+pub fn preset_picker<'a>(presets: &[PomodoroPreset], active: Option<&str>) -> Element<'a, Message> {
+    let names: Vec<String> = presets.iter().map(|preset| preset.name.clone()).collect();
+    Row::new()
+        .spacing(10)
+        .push(Text::new("预设"))
+        .push(
+            PickList::new(names, active.map(str::to_string), Message::SelectPomodoroPreset)
+                .placeholder("自定义")
+                .width(Length::Fill),
+        )
+        .into()
+}
 
 pub fn view(app: &TimeTrackerApp) -> Element<Message> {
     let mut content = Column::new().spacing(20).padding(20);
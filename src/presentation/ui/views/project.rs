@@ -3,6 +3,8 @@
 use crate::error::Result;
 use crate::ui::{styles, components::*};
 use crate::ui::TimeTrackerApp;
+use crate::core::models::GoalPeriod;
+use crate::domain::goal::GoalStatus;
 use eframe::egui;
 use chrono::Local;
 
@@ -95,11 +97,19 @@ fn render_project_details(app: &mut TimeTrackerApp, ui: &mut egui::Ui, project:
                 .show(ui)
                 .clicked()
             {
+                let affected = app
+                    .storage
+                    .count_project_data(project.id)
+                    .map(|impact| impact.total())
+                    .unwrap_or_default();
                 app.show_confirmation(
                     "删除项目".to_string(),
-                    format!("确定要删除项目"{}"吗？此操作不可恢复。", project.name),
+                    format!(
+                        "确定要删除项目"{}"吗？{} 条记录将失去项目关联，此操作不可恢复。",
+                        project.name, affected
+                    ),
                     Box::new(move |app| {
-                        app.storage.delete_project(project.id)
+                        app.storage.delete_project(project.id, true)
                     }),
                 );
             }
@@ -275,6 +285,43 @@ fn render_project_details(app: &mut TimeTrackerApp, ui: &mut egui::Ui, project:
                 });
             });
     }
+
+    // 项目目标
+    if let Ok(Some(progress)) = app.storage.get_project_goal_progress(project.id, GoalPeriod::Month) {
+        ui.add_space(styles::SPACING_MEDIUM);
+        ui.heading("本月目标");
+        Card::new()
+            .show(ui, |ui| {
+                ui.label(format!(
+                    "{} / {}",
+                    format_duration(progress.logged),
+                    format_duration(progress.goal.target)
+                ));
+                ui.add(egui::ProgressBar::new(progress.percent() / 100.0));
+                match progress.status {
+                    GoalStatus::Met => {
+                        ui.colored_label(styles::COLOR_SUCCESS, "已达成本月目标");
+                    }
+                    GoalStatus::OnTrack => {
+                        if let Some(estimated) = progress.estimated_completion {
+                            ui.colored_label(
+                                styles::COLOR_SUCCESS,
+                                format!("按当前进度，预计将于 {} 达成", estimated.format("%m-%d")),
+                            );
+                        }
+                    }
+                    GoalStatus::AtRisk => {
+                        ui.colored_label(
+                            styles::COLOR_WARNING,
+                            format!("进度落后，还需 {}", format_duration(progress.remaining())),
+                        );
+                    }
+                    GoalStatus::Overdue => {
+                        ui.colored_label(styles::COLOR_WARNING, "本月目标未达成");
+                    }
+                }
+            });
+    }
 }
 
 fn render_empty_state(ui: &mut egui::Ui) {
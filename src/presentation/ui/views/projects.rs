@@ -1,33 +1,152 @@
 use iced::{
-    widget::{Button, Column, Container},
+    widget::{Button, Column, ProgressBar, Row, Text},
     Element, Length,
 };
-use crate::presentation::ui::{Message, TimeTrackerApp, styles, Card};
-use crate::core::models::Project;
-
-pub fn view(app: &TimeTrackerApp) -> Element<Message> {
-    let mut content = Column::new().spacing(20).padding(20);
-    
-    // 添加项目按钮
-    let add_button = Button::new("添加项目")
-        .style(styles::button::primary())
-        .on_press(Message::OpenProjectDialog);
-    content = content.push(add_button);
-    
-    // 项目列表
-    for project in &app.projects {
-        let project_card = Card::new()
-            .title(&project.name)
-            .subtitle(&format!("创建于: {}", project.created_at.format("%Y-%m-%d")))
-            .content(project.description.as_deref().unwrap_or("无描述"))
-            .on_press(Message::SelectProject(project.id));
-        content = content.push(project_card);
+use crate::core::models::{DeletionImpact, Project};
+use crate::domain::goal::{GoalProgress, GoalStatus};
+use crate::presentation::ui::{Card, Message};
+
+fn format_duration(duration: std::time::Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// 项目视图中选中的项目
+#[derive(Debug, Clone)]
+pub struct ProjectDetails {
+    pub project: Project,
+    /// 本月目标进度，由 `GoalManager::project_goal_progress` 计算，没有设置目标时为空
+    pub goal: Option<GoalProgress>,
+    /// 删除超过确认阈值时等待用户确认的影响范围
+    pub pending_delete: Option<DeletionImpact>,
+}
+
+/// 删除确认中说明会受影响的记录
+pub fn delete_confirmation_text(project: &Project, impact: &DeletionImpact) -> String {
+    format!(
+        "删除「{}」后，{} 条活动和 {} 个番茄钟将解除项目关联，{} 个目标将被删除（共 {} 条记录）。确认删除？",
+        project.name,
+        impact.app_usage,
+        impact.pomodoros,
+        impact.goals,
+        impact.total()
+    )
+}
+
+/// 目标卡片上显示的进度说明
+pub fn goal_status_text(progress: &GoalProgress) -> String {
+    match progress.status {
+        GoalStatus::Met => "已达成本月目标".into(),
+        GoalStatus::OnTrack => match progress.estimated_completion {
+            Some(estimated) => format!("按当前进度，预计将于 {} 达成", estimated.format("%m-%d")),
+            None => "进度正常".into(),
+        },
+        GoalStatus::AtRisk => format!("进度落后，还需 {}", format_duration(progress.remaining())),
+        GoalStatus::Overdue => "本月目标未达成".into(),
     }
-    
-    Container::new(content)
-        .width(Length::Fill)
-        .height(Length::Fill)
-        .into()
 }
 
-// ... existing code ... 
\ No newline at end of file
+fn goal_card<'a>(progress: &GoalProgress) -> Element<'a, Message> {
+    Card::new()
+        .spacing(5.0)
+        .push(Text::new("本月目标").size(18))
+        .push(Text::new(format!(
+            "{} / {}",
+            format_duration(progress.logged),
+            format_duration(progress.goal.target)
+        )))
+        .push(ProgressBar::new(0.0..=100.0, progress.percent()))
+        .push(Text::new(goal_status_text(progress)))
+        .into_element()
+}
+
+fn details_column<'a>(details: &ProjectDetails) -> Column<'a, Message> {
+    let mut column = Column::new()
+        .spacing(10)
+        .push(Text::new(details.project.name.clone()).size(20))
+        .push(Text::new(details.project.description.clone().unwrap_or_else(|| "无描述".into())));
+    if let Some(progress) = &details.goal {
+        column = column.push(goal_card(progress));
+    }
+    let Some(id) = details.project.id else { return column };
+    match &details.pending_delete {
+        Some(impact) => column
+            .push(Text::new(delete_confirmation_text(&details.project, impact)))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(Button::new(Text::new("取消")).on_press(Message::CancelDeleteProject))
+                    .push(Button::new(Text::new("确认删除")).on_press(Message::ConfirmDeleteProject(id))),
+            ),
+        None => column.push(Button::new(Text::new("删除项目")).on_press(Message::DeleteProject(id))),
+    }
+}
+
+/// 项目列表，点击一个项目后在其下方显示详情卡片
+pub fn view<'a>(projects: &[Project], selected: Option<&ProjectDetails>) -> Element<'a, Message> {
+    let mut content = Column::new()
+        .spacing(10)
+        .push(Button::new(Text::new("添加项目")).on_press(Message::OpenProjectDialog));
+    for project in projects {
+        let Some(id) = project.id else { continue };
+        content = content.push(
+            Button::new(Text::new(format!("{}  创建于 {}", project.name, project.created_at.format("%Y-%m-%d"))))
+                .width(Length::Fill)
+                .on_press(Message::SelectProject(id)),
+        );
+        if let Some(selected) = selected.filter(|details| details.project.id == Some(id)) {
+            content = content.push(details_column(selected));
+        }
+    }
+    content.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{GoalPeriod, ProjectGoal};
+    use chrono::{Local, TimeZone};
+    use std::time::Duration;
+
+    fn progress(status: GoalStatus, logged_hours: u64) -> GoalProgress {
+        let start = Local.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        GoalProgress {
+            goal: ProjectGoal {
+                id: Some(1),
+                project_id: 1,
+                period: GoalPeriod::Month,
+                target: Duration::from_secs(20 * 3600),
+            },
+            period_start: start,
+            period_end: Local.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+            logged: Duration::from_secs(logged_hours * 3600),
+            expected: Duration::from_secs(10 * 3600),
+            estimated_completion: Some(Local.with_ymd_and_hms(2024, 4, 25, 0, 0, 0).unwrap()),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_delete_confirmation_text() {
+        let project = Project::new("Client".into(), None);
+        let impact = DeletionImpact {
+            app_usage: 120,
+            pomodoros: 8,
+            goals: 2,
+            ..DeletionImpact::default()
+        };
+        let text = delete_confirmation_text(&project, &impact);
+        assert!(text.contains("120 条活动"));
+        assert!(text.contains("8 个番茄钟"));
+        assert!(text.contains("2 个目标"));
+        assert!(text.contains("共 130 条记录"));
+    }
+
+    #[test]
+    fn test_goal_status_text() {
+        assert_eq!(goal_status_text(&progress(GoalStatus::Met, 20)), "已达成本月目标");
+        assert_eq!(goal_status_text(&progress(GoalStatus::OnTrack, 12)), "按当前进度，预计将于 04-25 达成");
+        assert_eq!(goal_status_text(&progress(GoalStatus::AtRisk, 5)), "进度落后，还需 15h 00m");
+        assert_eq!(goal_status_text(&progress(GoalStatus::Overdue, 5)), "本月目标未达成");
+    }
+}
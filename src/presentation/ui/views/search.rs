@@ -0,0 +1,38 @@
+use iced::{
+    widget::{Button, Column, Row, Text, TextInput},
+    Element, Length,
+};
+use crate::core::models::{SearchHit, SearchKind, SEARCH_HIGHLIGHT};
+use crate::presentation::ui::Message;
+
+/// 搜索框和结果列表，回车或点击按钮时搜索全部历史
+pub fn view<'a>(input: &str, results: &[SearchHit]) -> Element<'a, Message> {
+    let mut content = Column::new().spacing(10).push(
+        Row::new()
+            .spacing(10)
+            .push(
+                TextInput::new("搜索窗口标题和番茄钟备注", input)
+                    .on_input(Message::SearchInputChanged)
+                    .on_submit(Message::SubmitSearch)
+                    .width(Length::Fill),
+            )
+            .push(Button::new(Text::new("搜索")).on_press(Message::SubmitSearch)),
+    );
+
+    for hit in results {
+        let source = match (hit.kind, &hit.app_name) {
+            (SearchKind::Activity, Some(app)) => app.clone(),
+            (SearchKind::Activity, None) => "活动".into(),
+            (SearchKind::Pomodoro, _) => "番茄钟".into(),
+        };
+        content = content.push(
+            Row::new()
+                .spacing(10)
+                .push(Text::new(hit.start_time.format("%Y-%m-%d %H:%M").to_string()))
+                .push(Text::new(source).width(Length::FillPortion(1)))
+                // 文本控件不支持部分加粗，去掉高亮标记
+                .push(Text::new(hit.snippet.replace(SEARCH_HIGHLIGHT, "")).width(Length::FillPortion(3))),
+        );
+    }
+    content.into()
+}
@@ -16,6 +16,10 @@ pub enum Message {
     StopActivity,
     MarkAsProductive,
     MarkAsUnproductive,
+    SelectActivity(i64),
+    AdjustMinutesChanged(String),
+    AdjustDuration,
+    ActivitiesLoaded(Vec<Activity>),
     Tick,
 }
 
@@ -24,6 +28,9 @@ pub struct ActivityView {
     state: SharedState,
     name_input: String,
     category_input: String,
+    selected_activity: Option<i64>,
+    adjust_minutes_input: String,
+    activities: Vec<Activity>,
 }
 
 impl ActivityView {
@@ -33,9 +40,22 @@ impl ActivityView {
             state,
             name_input: String::new(),
             category_input: String::new(),
+            selected_activity: None,
+            adjust_minutes_input: String::new(),
+            activities: Vec::new(),
         }
     }
 
+    /// 读取今天的记录，供选择要调整时长的活动
+    pub fn load_activities(&self) -> Command<Message> {
+        let app = self.app.clone();
+
+        Command::perform(
+            async move { app.query_handler().get_daily_activities().await.unwrap_or_default() },
+            Message::ActivitiesLoaded,
+        )
+    }
+
     pub fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::NameChanged(name) => {
@@ -94,6 +114,46 @@ impl ActivityView {
                     |_| Message::Tick,
                 )
             }
+            Message::SelectActivity(id) => {
+                self.selected_activity = Some(id);
+                Command::none()
+            }
+            Message::AdjustMinutesChanged(input) => {
+                self.adjust_minutes_input = input;
+                Command::none()
+            }
+            Message::AdjustDuration => {
+                let (Some(id), Ok(minutes)) = (
+                    self.selected_activity,
+                    self.adjust_minutes_input.trim().parse::<i64>(),
+                ) else {
+                    return Command::none();
+                };
+                let app = self.app.clone();
+                self.adjust_minutes_input.clear();
+
+                Command::perform(
+                    async move {
+                        if let Err(e) = app
+                            .command_handler()
+                            .adjust_activity_duration(id, chrono::Duration::minutes(minutes))
+                            .await
+                        {
+                            log::warn!("调整活动时长失败: {}", e);
+                        }
+                        app.query_handler().get_daily_activities().await.unwrap_or_default()
+                    },
+                    Message::ActivitiesLoaded,
+                )
+            }
+            Message::ActivitiesLoaded(activities) => {
+                // 选中的记录不在新列表中时取消选择，避免调整到看不见的活动
+                if !activities.iter().any(|a| a.id.is_some() && a.id == self.selected_activity) {
+                    self.selected_activity = None;
+                }
+                self.activities = activities;
+                Command::none()
+            }
             Message::Tick => Command::none(),
         }
     }
@@ -138,10 +198,47 @@ impl ActivityView {
             )
             .spacing(10);
 
+        // 以分钟为单位微调选中活动的时长，可为负数
+        let adjust_row = Row::new()
+            .push(
+                TextInput::new("调整分钟数（如 10 或 -5）", &self.adjust_minutes_input)
+                    .on_input(Message::AdjustMinutesChanged)
+                    .padding(10),
+            )
+            .push({
+                let button = Button::new(Text::new("调整时长")).padding(10);
+                if self.selected_activity.is_some() {
+                    button.on_press(Message::AdjustDuration)
+                } else {
+                    button
+                }
+            })
+            .spacing(10);
+
+        // 今天的记录，点击选中后可用上面的输入框调整时长
+        let mut activity_list = Column::new().spacing(5);
+        for activity in &self.activities {
+            let Some(id) = activity.id else { continue };
+            let marker = if self.selected_activity == Some(id) { "▶ " } else { "" };
+            activity_list = activity_list.push(
+                Button::new(Text::new(format!(
+                    "{}{}  {}  {} 分钟",
+                    marker,
+                    activity.start_time.format("%H:%M"),
+                    activity.name,
+                    activity.duration.as_secs() / 60
+                )))
+                .width(Length::Fill)
+                .on_press(Message::SelectActivity(id)),
+            );
+        }
+
         let content = Column::new()
             .push(input_row)
             .push(control_row)
             .push(productivity_row)
+            .push(adjust_row)
+            .push(activity_list)
             .spacing(20)
             .padding(20);
 
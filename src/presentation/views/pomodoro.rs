@@ -1,5 +1,6 @@
 use crate::application::App;
-use crate::core::models::{PomodoroSession, Project};
+use crate::core::models::{PomodoroSession, PomodoroStatus, Project};
+use crate::domain::pomodoro::{CompletionChoice, PendingDecision};
 use crate::presentation::state::SharedState;
 use iced::{
     widget::{Button, Column, Container, Row, Text, TextInput, PickList, Space},
@@ -19,6 +20,9 @@ pub enum Message {
     StopPomodoro,
     Tick,
     ProjectsLoaded(Vec<Project>),
+    DecisionPending(Option<PendingDecision>),
+    EnforcedBreak(Option<Duration>),
+    ResolveDecision(CompletionChoice),
 }
 
 pub struct PomodoroView {
@@ -28,6 +32,8 @@ pub struct PomodoroView {
     tags_input: String,
     selected_project: Option<Project>,
     available_projects: Vec<Project>,
+    pending_decision: Option<PendingDecision>,
+    enforced_break_until: Option<std::time::Instant>,
 }
 
 impl PomodoroView {
@@ -39,6 +45,8 @@ impl PomodoroView {
             tags_input: String::new(),
             selected_project: None,
             available_projects: Vec::new(),
+            pending_decision: None,
+            enforced_break_until: None,
         }
     }
 
@@ -118,11 +126,43 @@ impl PomodoroView {
                     |_| Message::Tick,
                 )
             }
-            Message::Tick => Command::none(),
+            Message::Tick => {
+                // 工作番茄结束时由这里取到待选择的下一步
+                let app = self.app.clone();
+
+                Command::perform(
+                    async move {
+                        app.command_handler().pending_pomodoro_decision().await.ok().flatten()
+                    },
+                    Message::DecisionPending,
+                )
+            }
             Message::ProjectsLoaded(projects) => {
                 self.available_projects = projects;
                 Command::none()
             }
+            Message::DecisionPending(decision) => {
+                self.pending_decision = decision;
+                Command::none()
+            }
+            Message::EnforcedBreak(remaining) => {
+                self.enforced_break_until = remaining.map(|r| std::time::Instant::now() + r);
+                Command::none()
+            }
+            Message::ResolveDecision(choice) => {
+                // 推迟后仍在等待选择，由下一次 DecisionPending 更新
+                if choice != CompletionChoice::Snooze {
+                    self.pending_decision = None;
+                }
+                let app = self.app.clone();
+
+                Command::perform(
+                    async move {
+                        app.command_handler().resolve_pomodoro_decision(choice).await.ok();
+                    },
+                    |_| Message::Tick,
+                )
+            }
         }
     }
 
@@ -148,12 +188,56 @@ impl PomodoroView {
             .push(project_picker)
             .push(Space::with_height(Length::Fixed(20)));
 
+        if let Some(decision) = &self.pending_decision {
+            column = column.push(self.decision_buttons(decision));
+        }
+
+        // 强制休息倒计时，每次 Tick 重新计算
+        let remaining = self
+            .enforced_break_until
+            .map(|until| until.saturating_duration_since(std::time::Instant::now()))
+            .filter(|remaining| !remaining.is_zero());
+        if let Some(remaining) = remaining {
+            column = column.push(Text::new(format!(
+                "强制休息中，{:02}:{:02} 后可开始新的番茄钟",
+                remaining.as_secs() / 60,
+                remaining.as_secs() % 60
+            )));
+        }
+
         Container::new(column)
             .width(Length::Fill)
             .height(Length::Fill)
             .into()
     }
 
+    fn decision_buttons(&self, decision: &PendingDecision) -> Element<Message> {
+        let break_label = if decision.next_phase == PomodoroStatus::LongBreak {
+            "开始长休息"
+        } else {
+            "开始休息"
+        };
+
+        let title = match decision.snoozed_until {
+            Some(until) => format!("休息已推迟到 {}", until.format("%H:%M")),
+            None => "番茄钟已完成，接下来？".to_string(),
+        };
+        let mut buttons = Row::new()
+            .spacing(10)
+            .push(Button::new(Text::new(break_label)).on_press(Message::ResolveDecision(CompletionChoice::StartBreak)))
+            .push(Button::new(Text::new("再工作5分钟")).on_press(Message::ResolveDecision(CompletionChoice::ExtendWork)));
+        if decision.can_snooze() {
+            buttons = buttons.push(Button::new(Text::new("推迟休息")).on_press(Message::ResolveDecision(CompletionChoice::Snooze)));
+        }
+        buttons = buttons.push(Button::new(Text::new("稍后再说")).on_press(Message::ResolveDecision(CompletionChoice::Idle)));
+
+        Column::new()
+            .spacing(10)
+            .push(Text::new(title))
+            .push(buttons)
+            .into()
+    }
+
     fn timer_display(&self) -> Element<Message> {
         // TODO: 显示当前番茄钟状态和剩余时间
         Text::new("25:00").size(40).into()
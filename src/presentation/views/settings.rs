@@ -1,15 +1,19 @@
-use crate::infrastructure::config::Config;
+use crate::domain::config::AppConfig;
+use crate::infrastructure::storage::BackupInfo;
+use crate::presentation::settings::SettingsModel;
 use iced::{
     widget::{Button, Checkbox, Column, Container, Row, Text, TextInput, PickList, Slider},
     Element, Length, Theme,
 };
 use crate::presentation::ui::styles;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ThemeChanged(Theme),
     LanguageChanged(String),
-    AutoStartChanged(bool),
+    StartMinimizedChanged(bool),
+    StartHiddenChanged(bool),
     MinimizeToTrayChanged(bool),
     WorkDurationChanged(String),
     ShortBreakDurationChanged(String),
@@ -18,15 +22,98 @@ pub enum Message {
     SoundEnabledChanged(bool),
     SoundVolumeChanged(i32),
     SaveSettings,
+    BackupsLoaded(Vec<BackupInfo>),
+    /// 数据库、WAL 和共享内存文件的总字节数
+    DiskUsageLoaded(u64),
+    /// 选择要恢复的备份，确认后才执行
+    RestoreRequested(PathBuf),
+    RestoreCancelled,
+    /// 由应用对 `pending_restore` 执行 `Storage::restore_from_backup`
+    RestoreConfirmed,
+    RestoreFinished(Result<(), String>),
 }
 
 pub struct SettingsView {
-    config: Config,
+    model: SettingsModel,
+    backups: Vec<BackupInfo>,
+    disk_usage: Option<u64>,
+    pending_restore: Option<PathBuf>,
+    restore_status: Option<String>,
 }
 
 impl SettingsView {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            model: SettingsModel::from_config(config),
+            backups: Vec::new(),
+            disk_usage: None,
+            pending_restore: None,
+            restore_status: None,
+        }
+    }
+
+    /// 等待恢复的备份
+    pub fn pending_restore(&self) -> Option<&Path> {
+        self.pending_restore.as_deref()
+    }
+
+    pub fn update(&mut self, message: Message) {
+        let model = &mut self.model;
+        match message {
+            Message::ThemeChanged(theme) => {
+                model.general.theme = if theme == Theme::Dark { "dark" } else { "light" }.into();
+            }
+            Message::LanguageChanged(language) => model.general.language = language,
+            Message::StartMinimizedChanged(value) => model.general.start_minimized = value,
+            Message::StartHiddenChanged(value) => model.general.start_hidden = value,
+            Message::MinimizeToTrayChanged(value) => model.general.minimize_to_tray = value,
+            Message::WorkDurationChanged(input) => {
+                if let Ok(duration) = SettingsModel::parse_minutes(&input) {
+                    model.pomodoro.work_duration = duration;
+                }
+            }
+            Message::ShortBreakDurationChanged(input) => {
+                if let Ok(duration) = SettingsModel::parse_minutes(&input) {
+                    model.pomodoro.short_break_duration = duration;
+                }
+            }
+            Message::LongBreakDurationChanged(input) => {
+                if let Ok(duration) = SettingsModel::parse_minutes(&input) {
+                    model.pomodoro.long_break_duration = duration;
+                }
+            }
+            Message::LongBreakIntervalChanged(input) => {
+                if let Ok(interval) = input.trim().parse() {
+                    model.pomodoro.long_break_interval = interval;
+                }
+            }
+            Message::SoundEnabledChanged(value) => model.notification.enable_sound = value,
+            Message::SoundVolumeChanged(volume) => {
+                model.notification.sound_volume = volume as f32 / 100.0;
+            }
+            Message::SaveSettings => {}
+            Message::BackupsLoaded(backups) => self.backups = backups,
+            Message::DiskUsageLoaded(bytes) => self.disk_usage = Some(bytes),
+            Message::RestoreRequested(path) => {
+                self.pending_restore = Some(path);
+                self.restore_status = None;
+            }
+            Message::RestoreCancelled => self.pending_restore = None,
+            Message::RestoreConfirmed => {}
+            Message::RestoreFinished(result) => {
+                self.pending_restore = None;
+                self.restore_status = Some(match result {
+                    Ok(()) => "恢复完成，恢复前的数据已另存为安全备份".into(),
+                    Err(e) => format!("恢复失败: {}", e),
+                });
+            }
+        }
+    }
+
+    /// 校验通过后返回待保存的配置
+    pub fn to_config(&self) -> crate::core::AppResult<AppConfig> {
+        self.model.validate()?;
+        Ok(self.model.to_config())
     }
 
     pub fn view(&self) -> Element<Message> {
@@ -36,6 +123,7 @@ impl SettingsView {
             .push(self.general_section())
             .push(self.pomodoro_section())
             .push(self.sound_section())
+            .push(self.backup_section())
             .push(
                 Button::new(Text::new("保存设置"))
                     .on_press(Message::SaveSettings)
@@ -54,15 +142,15 @@ impl SettingsView {
         let theme_picker = PickList::new(
             "主题",
             themes.as_slice(),
-            Some(Theme::Light),
+            Some(if self.model.general.theme == "dark" { Theme::Dark } else { Theme::Light }),
             Message::ThemeChanged,
         );
 
-        let languages = vec!["zh-CN", "en-US"];
+        let languages = vec!["zh-CN".to_string(), "en-US".to_string()];
         let language_picker = PickList::new(
             "语言",
-            languages.as_slice(),
-            Some("zh-CN"),
+            languages,
+            Some(self.model.general.language.clone()),
             Message::LanguageChanged,
         );
 
@@ -79,11 +167,15 @@ impl SettingsView {
             .spacing(10)
             .push(Text::new("常规设置").size(24))
             .push(
-                Checkbox::new("开机自启动", true)
-                    .on_toggle(Message::AutoStartChanged),
+                Checkbox::new("启动时最小化", self.model.general.start_minimized)
+                    .on_toggle(Message::StartMinimizedChanged),
+            )
+            .push(
+                Checkbox::new("开机自启时隐藏到托盘", self.model.general.start_hidden)
+                    .on_toggle(Message::StartHiddenChanged),
             )
             .push(
-                Checkbox::new("最小化到托盘", true)
+                Checkbox::new("最小化到托盘", self.model.general.minimize_to_tray)
                     .on_toggle(Message::MinimizeToTrayChanged),
             )
             .into()
@@ -96,28 +188,28 @@ impl SettingsView {
             .push(
                 TextInput::new(
                     "工作时长（分钟）",
-                    "25",
+                    &(self.model.pomodoro.work_duration.as_secs() / 60).to_string(),
                     Message::WorkDurationChanged,
                 ),
             )
             .push(
                 TextInput::new(
                     "短休息时长（分钟）",
-                    "5",
+                    &(self.model.pomodoro.short_break_duration.as_secs() / 60).to_string(),
                     Message::ShortBreakDurationChanged,
                 ),
             )
             .push(
                 TextInput::new(
                     "长休息时长（分钟）",
-                    "15",
+                    &(self.model.pomodoro.long_break_duration.as_secs() / 60).to_string(),
                     Message::LongBreakDurationChanged,
                 ),
             )
             .push(
                 TextInput::new(
                     "长休息间隔",
-                    "4",
+                    &self.model.pomodoro.long_break_interval.to_string(),
                     Message::LongBreakIntervalChanged,
                 ),
             )
@@ -129,13 +221,82 @@ impl SettingsView {
             .spacing(10)
             .push(Text::new("声音设置").size(24))
             .push(
-                Checkbox::new("启用声音", true)
+                Checkbox::new("启用声音", self.model.notification.enable_sound)
                     .on_toggle(Message::SoundEnabledChanged),
             )
             .push(
-                Slider::new(0..=100, 70, Message::SoundVolumeChanged)
-                    .step(1),
+                Slider::new(
+                    0..=100,
+                    (self.model.notification.sound_volume * 100.0).round() as i32,
+                    Message::SoundVolumeChanged,
+                )
+                .step(1),
             )
             .into()
     }
-} 
\ No newline at end of file
+
+    fn backup_section(&self) -> Element<Message> {
+        let mut column = Column::new().spacing(10).push(Text::new("备份与恢复").size(24));
+        if let Some(bytes) = self.disk_usage {
+            column = column.push(Text::new(format!("数据库占用 {}", format_size(bytes))));
+        }
+
+        if let Some(path) = &self.pending_restore {
+            let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            column = column
+                .push(Text::new(format!(
+                    "将用备份 {} 替换当前所有数据，恢复前会自动备份当前数据。确定恢复吗？",
+                    name
+                )))
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .push(Button::new(Text::new("取消")).on_press(Message::RestoreCancelled))
+                        .push(
+                            Button::new(Text::new("确认恢复"))
+                                .on_press(Message::RestoreConfirmed)
+                                .style(styles::button::primary()),
+                        ),
+                );
+        } else if self.backups.is_empty() {
+            column = column.push(Text::new("暂无备份"));
+        }
+
+        for backup in &self.backups {
+            let name = backup.path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            let mut restore = Button::new(Text::new("恢复"));
+            // 加密备份需要口令，不能在这里直接恢复
+            if self.pending_restore.is_none() && !backup.encrypted {
+                restore = restore.on_press(Message::RestoreRequested(backup.path.clone()));
+            }
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(Text::new(name).width(Length::Fill))
+                    .push(Text::new(backup.created_at.format("%Y-%m-%d %H:%M").to_string()))
+                    .push(Text::new(format_size(backup.size)))
+                    .push(restore),
+            );
+        }
+
+        if let Some(status) = &self.restore_status {
+            column = column.push(Text::new(status.clone()));
+        }
+        column.into()
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
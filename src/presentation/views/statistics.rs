@@ -1,6 +1,7 @@
 use crate::application::App;
 use crate::core::models::Project;
 use crate::domain::analysis::{ProductivityStats, CategoryStats, PomodoroStats};
+use crate::presentation::cache::CachedView;
 use crate::presentation::state::SharedState;
 use iced::{
     widget::{Button, Column, Container, Row, Text, PickList, Space},
@@ -18,10 +19,11 @@ pub enum Message {
         categories: Vec<CategoryStats>,
         pomodoro: PomodoroStats,
     },
+    StatsFailed(String),
     ExportData(ExportFormat),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TimeRange {
     Today,
     Week,
@@ -63,6 +65,7 @@ pub struct StatisticsView {
     selected_project: Option<Project>,
     selected_range: TimeRange,
     available_projects: Vec<Project>,
+    load_error: Option<String>,
 }
 
 impl StatisticsView {
@@ -73,6 +76,7 @@ impl StatisticsView {
             selected_project: None,
             selected_range: TimeRange::Today,
             available_projects: Vec::new(),
+            load_error: None,
         }
     }
 
@@ -86,7 +90,15 @@ impl StatisticsView {
                 self.selected_range = range;
                 self.load_stats()
             }
-            Message::StatsLoaded { .. } => Command::none(),
+            Message::StatsLoaded { .. } => {
+                self.load_error = None;
+                Command::none()
+            }
+            Message::StatsFailed(error) => {
+                // 保留上次的统计数据，只提示失败原因
+                self.load_error = Some(error);
+                Command::none()
+            }
             Message::ExportData(format) => {
                 let (start, end) = self.get_time_range();
                 match format {
@@ -104,27 +116,30 @@ impl StatisticsView {
 
     fn load_stats(&self) -> Command<Message> {
         let app = Arc::clone(&self.app);
+        let state = Arc::clone(&self.state);
+        let range = self.selected_range;
         let (start, end) = self.get_time_range();
         Command::perform(async move {
-            let productivity = app.query_handler()
-                .get_productivity_stats(start, end)
-                .await
-                .unwrap_or_default();
-
-            let categories = app.query_handler()
-                .get_category_stats(start, end)
-                .await
-                .unwrap_or_default();
-
-            let pomodoro = app.query_handler()
-                .get_pomodoro_stats(start, end)
-                .await
-                .unwrap_or_default();
-
-            Message::StatsLoaded {
-                productivity,
-                categories,
-                pomodoro,
+            let cache = Arc::clone(&state.read().await.stats_cache);
+            // 任一查询失败时整体失败，不把空结果当作有效数据缓存
+            let loaded = cache
+                .get_or_load(CachedView::Statistics, range, || async {
+                    let query = app.query_handler();
+                    Ok((
+                        query.get_productivity_stats(start, end).await?,
+                        query.get_category_stats(start, end).await?,
+                        query.get_pomodoro_stats(start, end).await?,
+                    ))
+                })
+                .await;
+
+            match loaded {
+                Ok((productivity, categories, pomodoro)) => Message::StatsLoaded {
+                    productivity,
+                    categories,
+                    pomodoro,
+                },
+                Err(e) => Message::StatsFailed(e.to_string()),
             }
         }, |msg| msg)
     }
@@ -185,9 +200,12 @@ impl StatisticsView {
     }
 
     fn stats_display(&self) -> Element<Message> {
-        Column::new()
+        let mut column = Column::new()
             .push(Text::new("统计数据").size(20))
-            .push(Space::with_height(Length::Fixed(10.0)))
-            .into()
+            .push(Space::with_height(Length::Fixed(10.0)));
+        if let Some(error) = &self.load_error {
+            column = column.push(Text::new(format!("统计数据加载失败：{}", error)));
+        }
+        column.into()
     }
 } 
\ No newline at end of file
@@ -3,15 +3,56 @@ use iced::{
     Application, Settings,
 };
 use crate::core::AppResult;
+use crate::domain::config::UISettings;
 use crate::presentation::ui::TimeTrackerApp;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupVisibility {
+    Shown,
+    Minimized,
+    /// 只显示托盘图标，不创建可见窗口
+    HiddenToTray,
+}
+
+/// 决定启动时窗口是否可见。隐藏到托盘前必须确认托盘可用，否则窗口将无法找回
+pub fn startup_visibility(settings: &UISettings, launched_at_login: bool, tray_available: bool) -> StartupVisibility {
+    let tray_usable = settings.show_system_tray && settings.minimize_to_tray && tray_available;
+
+    if launched_at_login && settings.start_hidden {
+        return if tray_usable {
+            StartupVisibility::HiddenToTray
+        } else {
+            StartupVisibility::Shown
+        };
+    }
+
+    if settings.start_minimized {
+        if tray_usable {
+            StartupVisibility::HiddenToTray
+        } else {
+            StartupVisibility::Minimized
+        }
+    } else {
+        StartupVisibility::Shown
+    }
+}
+
 pub struct Window {
     app: TimeTrackerApp,
+    visibility: StartupVisibility,
 }
 
 impl Window {
     pub fn new(app: TimeTrackerApp) -> Self {
-        Self { app }
+        Self {
+            app,
+            visibility: StartupVisibility::Shown,
+        }
+    }
+
+    pub fn with_visibility(mut self, visibility: StartupVisibility) -> Self {
+        self.visibility = visibility;
+        self
     }
 
     pub fn run(self) -> AppResult<()> {
@@ -20,6 +61,7 @@ impl Window {
                 size: (800, 600),
                 position: Position::Centered,
                 min_size: Some((400, 300)),
+                visible: self.visibility != StartupVisibility::HiddenToTray,
                 ..Default::default()
             },
             ..Default::default()
@@ -35,4 +77,40 @@ impl Window {
     pub fn hide(&self) -> AppResult<()> {
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::config::AppConfig;
+
+    fn settings(start_hidden: bool, start_minimized: bool) -> UISettings {
+        UISettings {
+            start_hidden,
+            start_minimized,
+            ..AppConfig::default().ui
+        }
+    }
+
+    #[test]
+    fn test_startup_visibility() {
+        use StartupVisibility::*;
+
+        // 开机自启且托盘可用时隐藏
+        assert_eq!(startup_visibility(&settings(true, false), true, true), HiddenToTray);
+        // 托盘不可用时仍显示窗口
+        assert_eq!(startup_visibility(&settings(true, false), true, false), Shown);
+        // 手动启动不受 start_hidden 影响
+        assert_eq!(startup_visibility(&settings(true, false), false, true), Shown);
+        assert_eq!(startup_visibility(&settings(false, false), true, true), Shown);
+
+        // 未开启最小化到托盘时不隐藏
+        let mut no_tray = settings(true, false);
+        no_tray.minimize_to_tray = false;
+        assert_eq!(startup_visibility(&no_tray, true, true), Shown);
+
+        // 原有的启动时最小化
+        assert_eq!(startup_visibility(&settings(false, true), false, true), HiddenToTray);
+        assert_eq!(startup_visibility(&settings(false, true), false, false), Minimized);
+    }
+}
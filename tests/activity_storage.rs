@@ -0,0 +1,61 @@
+//! 追踪器写入的活动记录能通过同一个 `SqliteStorage` 读回
+
+use chrono::{Duration as ChronoDuration, Local};
+use std::sync::Arc;
+use std::time::Duration;
+use time_tracker::core::models::Activity;
+use time_tracker::core::traits::Storage;
+use time_tracker::core::AppResult;
+use time_tracker::domain::activity::persist_records;
+use time_tracker::infrastructure::storage::SqliteStorage;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn tracked_record_is_readable_through_get_activities() -> AppResult<()> {
+    let dir = tempfile::tempdir()?;
+    let storage = Arc::new(SqliteStorage::new(dir.path().join("test.db")).await?);
+
+    let start = Local::now() - ChronoDuration::minutes(10);
+    let (sender, records) = mpsc::channel(1);
+    sender
+        .send(Activity {
+            id: None,
+            name: "Editing main.rs".into(),
+            start_time: start,
+            end_time: Some(start + ChronoDuration::minutes(5)),
+            project_id: None,
+            description: None,
+            duration: Duration::from_secs(5 * 60),
+            category: "开发".into(),
+            is_productive: true,
+            app_name: "code".into(),
+            window_title: "main.rs".into(),
+            is_billable: Some(true),
+            tags: vec!["deepwork".into()],
+            utc_offset: None,
+            manual: false,
+        })
+        .await
+        .unwrap();
+    drop(sender);
+    persist_records(storage.clone(), records, None, Duration::from_secs(3600), 50).await;
+
+    let activities = storage.get_activities(start - ChronoDuration::hours(1), Local::now()).await?;
+    assert_eq!(activities.len(), 1);
+    let activity = &activities[0];
+    assert_eq!(activity.app_name, "code");
+    assert_eq!(activity.window_title, "main.rs");
+    assert_eq!(activity.category, "开发");
+    assert_eq!(activity.duration, Duration::from_secs(5 * 60));
+    assert_eq!(activity.start_time.timestamp(), start.timestamp());
+    // 规则和项目默认值写入的字段随记录一起保存
+    assert_eq!(activity.name, "Editing main.rs");
+    assert!(activity.is_productive);
+    assert_eq!(activity.is_billable, Some(true));
+    assert_eq!(activity.tags, vec!["deepwork".to_string()]);
+
+    // 同一条记录也能按 id 读取
+    let by_id = storage.get_activity(activity.id.unwrap()).await?;
+    assert_eq!(by_id.app_name, "code");
+    Ok(())
+}
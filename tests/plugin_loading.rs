@@ -0,0 +1,59 @@
+//! 编译 `tests/plugins/example_plugin` 并通过插件目录加载。
+//! 需要完整编译一次示例插件，默认跳过，使用 `cargo test -- --ignored` 运行
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use time_tracker::plugins::traits::{Plugin, PLUGIN_API_VERSION};
+use time_tracker::plugins::{PluginLoader, PluginRegistry};
+use tokio::sync::broadcast;
+
+fn build_example_plugin(plugin_dir: &Path) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = root.join("target").join("example_plugin");
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(root.join("tests/plugins/example_plugin/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("无法运行 cargo");
+    assert!(status.success(), "编译示例插件失败");
+
+    let file_name = format!(
+        "{}example_plugin.{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_EXTENSION
+    );
+    std::fs::create_dir_all(plugin_dir).unwrap();
+    std::fs::copy(target_dir.join("debug").join(&file_name), plugin_dir.join(&file_name)).unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_load_example_plugin_from_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let plugin_dir = dir.path().join("plugins");
+    build_example_plugin(&plugin_dir);
+
+    let mut loader = PluginLoader::new();
+    let loaded = loader.load_dir(&plugin_dir).unwrap();
+    assert_eq!(loaded.len(), 1);
+    let (name, plugin) = &loaded[0];
+    assert_eq!(name, "example_plugin");
+    assert_eq!(plugin.name(), "example");
+    assert_eq!(plugin.api_version(), PLUGIN_API_VERSION);
+    drop(loaded);
+
+    let (sender, _events) = broadcast::channel(4);
+    let registry = Arc::new(PluginRegistry::new(sender));
+    assert_eq!(registry.load_dir(&plugin_dir).await.unwrap(), vec!["example_plugin".to_string()]);
+
+    // 卸载后仍持有的实例可以继续使用，库在实例释放后才卸载
+    let plugin = registry.get_plugin("example_plugin").await.unwrap();
+    registry.unload_plugins().await.unwrap();
+    assert!(registry.get_plugin("example_plugin").await.is_none());
+    assert_eq!(plugin.description(), "动态加载示例插件");
+    plugin.start().await.unwrap();
+}
@@ -0,0 +1,46 @@
+//! 用于测试动态加载的最小插件
+
+use async_trait::async_trait;
+use std::any::Any;
+use time_tracker::core::AppResult;
+use time_tracker::plugins::traits::Plugin;
+
+#[derive(Default)]
+pub struct ExamplePlugin;
+
+#[async_trait]
+impl Plugin for ExamplePlugin {
+    fn name(&self) -> &str {
+        "example"
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn description(&self) -> &str {
+        "动态加载示例插件"
+    }
+
+    async fn initialize(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn start(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn stop(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    async fn uninstall(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn get_settings_ui(&self) -> Option<Box<dyn Any>> {
+        None
+    }
+}
+
+time_tracker::declare_plugin!(ExamplePlugin);
@@ -0,0 +1,65 @@
+//! 启动时从配置读取的类别规则会作用到追踪器记录的窗口上
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time_tracker::core::AppResult;
+use time_tracker::domain::activity::ActivityManager;
+use time_tracker::domain::config::{AppConfig, CategoryRuleSettings, ConfigManager, ConfigManagerImpl};
+use time_tracker::infrastructure::platform::{PlatformOperations, WindowInfo};
+use time_tracker::infrastructure::storage::SqliteStorage;
+
+struct WindowPlatform(Mutex<String>);
+
+impl PlatformOperations for WindowPlatform {
+    fn get_active_window(&self) -> AppResult<WindowInfo> {
+        let app_name = self.0.lock().unwrap().clone();
+        Ok(WindowInfo {
+            title: app_name.clone(),
+            process_name: app_name.clone(),
+            process_id: 1,
+            window_title: format!("{} window", app_name),
+            app_name,
+        })
+    }
+
+    fn set_autostart(&self, _enabled: bool) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn is_autostart_enabled(&self) -> AppResult<bool> {
+        Ok(false)
+    }
+}
+
+#[tokio::test]
+async fn configured_category_rule_applies_to_tracked_window() -> AppResult<()> {
+    let dir = tempfile::tempdir()?;
+    let storage = Arc::new(SqliteStorage::new(dir.path().join("test.db")).await?);
+
+    let mut config = AppConfig::default();
+    config.tracking.category_rules = vec![CategoryRuleSettings {
+        pattern: "(?i)editor".into(),
+        category: "开发".into(),
+    }];
+    let config_manager: Arc<dyn ConfigManager> = Arc::new(ConfigManagerImpl::with_config(storage.clone(), config));
+
+    let platform = Arc::new(WindowPlatform(Mutex::new("Editor".into())));
+    let (tracker, mut records) = ActivityManager::new(storage)
+        .with_config_manager(config_manager)
+        .await?
+        .with_platform(platform.clone())
+        .spawn(Duration::from_millis(10));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    *platform.0.lock().unwrap() = "browser".into();
+    let timeout = Duration::from_secs(5);
+    let editor = tokio::time::timeout(timeout, records.recv()).await.unwrap().unwrap();
+    assert_eq!(editor.app_name, "Editor");
+    assert_eq!(editor.category, "开发");
+
+    tracker.stop().await?;
+    let browser = tokio::time::timeout(timeout, records.recv()).await.unwrap().unwrap();
+    assert_eq!(browser.app_name, "browser");
+    assert_ne!(browser.category, "开发");
+    Ok(())
+}